@@ -4,8 +4,11 @@ use cargo_metadata::{
 };
 
 use crate::{
+    changelog,
     changelog::{section::segment, Section},
-    commit, ChangeLog,
+    commit,
+    utils::time_to_offset_date_time,
+    ChangeLog,
 };
 
 #[derive(Clone, Copy)]
@@ -94,6 +97,44 @@ impl ChangeLog {
         Ok((out, package))
     }
 
+    /// Create a changelog with a single release named `version` from all commits in `range`, with generated segments as
+    /// chosen by `selection`.
+    ///
+    /// `range` is a revision range like `v1.0.0..HEAD`, `v1.0.0..` which implies `HEAD`, or just `HEAD` to use the entire history.
+    /// Unlike [`for_package_with_write_lock()`][ChangeLog::for_package_with_write_lock()], commits aren't filtered by the crate they change.
+    pub fn from_commit_range(
+        repo: &gix::Repository,
+        range: &str,
+        version: changelog::Version,
+        selection: segment::Selection,
+    ) -> anyhow::Result<Self> {
+        let (items, to, from) = crate::git::history::collect_range(repo, range)?;
+        let commit_time = |id: gix::ObjectId| -> anyhow::Result<_> {
+            Ok(time_to_offset_date_time(
+                repo.find_object(id)?.try_into_commit()?.committer()?.time,
+            ))
+        };
+        let date_time = commit_time(to)?;
+        let prev_date_time = from.map(commit_time).transpose()?;
+        let history: Vec<_> = items.iter().collect();
+
+        Ok(ChangeLog {
+            sections: vec![Section::Release {
+                date: match version {
+                    changelog::Version::Unreleased => None,
+                    changelog::Version::Semantic(_) => Some(date_time),
+                },
+                name: version,
+                heading_level: changelog::DEFAULT_HEADING_LEVEL,
+                version_prefix: Section::DEFAULT_PREFIX.to_owned(),
+                version_label: None,
+                segments: Section::segments_from_history(&history, selection, date_time, prev_date_time),
+                removed_messages: Default::default(),
+                unknown: Default::default(),
+            }],
+        })
+    }
+
     pub fn from_history_segments(
         package: &Package,
         segments: &[commit::history::Segment<'_>],
@@ -101,24 +142,24 @@ impl ChangeLog {
         selection: segment::Selection,
     ) -> Self {
         ChangeLog {
-            sections: {
-                let mut s = segments.windows(2).fold(Vec::new(), |mut acc, segments| {
-                    acc.push(Section::from_history_segment(
-                        package,
-                        &segments[0],
-                        repo,
-                        selection,
-                        (&segments[1]).into(),
-                    ));
-                    acc
-                });
-                if let Some(segment) = segments.last() {
-                    s.push(Section::from_history_segment(package, segment, repo, selection, None))
-                }
-                s
-            },
+            sections: Self::sections_from_history_segments(package, segments, repo, selection).collect(),
         }
     }
+
+    /// Like [`from_history_segments()`][ChangeLog::from_history_segments()], but produces one section at a time as they are
+    /// needed to avoid holding all of them in memory at once.
+    ///
+    /// Use it with [`write::sections()`][changelog::write::sections()] to render each section as soon as it was generated.
+    pub fn sections_from_history_segments<'a>(
+        package: &'a Package,
+        segments: &'a [commit::history::Segment<'a>],
+        repo: &'a gix::Repository,
+        selection: segment::Selection,
+    ) -> impl Iterator<Item = Section> + 'a {
+        segments.iter().enumerate().map(move |(idx, segment)| {
+            Section::from_history_segment(package, segment, repo, selection, segments.get(idx + 1))
+        })
+    }
 }
 
 fn path_from_manifest(path: &Utf8Path) -> Utf8PathBuf {
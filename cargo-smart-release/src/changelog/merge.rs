@@ -77,12 +77,14 @@ impl Section {
             (
                 Section::Release {
                     date: dest_date,
+                    version_label: dest_version_label,
                     segments: dest_segments,
                     removed_messages,
                     ..
                 },
                 Section::Release {
                     date: src_date,
+                    version_label: src_version_label,
                     segments: src_segments,
                     unknown: src_unknown,
                     ..
@@ -102,8 +104,11 @@ impl Section {
                         }
                         Segment::Details(section::Data::Parsed)
                         | Segment::Statistics(section::Data::Parsed)
-                        | Segment::Clippy(section::Data::Parsed) => {
-                            unreachable!("BUG: Clippy, statistics, and details are set if generated, or not present")
+                        | Segment::Clippy(section::Data::Parsed)
+                        | Segment::MigrationGuide(section::Data::Parsed) => {
+                            unreachable!(
+                                "BUG: Clippy, statistics, details and migration guides are set if generated, or not present"
+                            )
                         }
                         Segment::Conventional(conventional) => {
                             merge_conventional(removed_messages, dest_segments, conventional)
@@ -117,9 +122,18 @@ impl Section {
                         details @ Segment::Details(_) => {
                             merge_read_only_segment(dest_segments, |s| matches!(s, Segment::Details(_)), details, mode)
                         }
+                        guide @ Segment::MigrationGuide(_) => merge_read_only_segment(
+                            dest_segments,
+                            |s| matches!(s, Segment::MigrationGuide(_)),
+                            guide,
+                            mode,
+                        ),
                     }
                 }
                 *dest_date = src_date;
+                if src_version_label.is_some() {
+                    *dest_version_label = src_version_label;
+                }
             }
         }
     }
@@ -31,6 +31,10 @@ pub enum Section {
         heading_level: usize,
         /// What came right before the version
         version_prefix: String,
+        /// The text to show instead of the prefixed version of a semantic release, like a product version.
+        ///
+        /// The actual version is retained in a machine tag to be able to read it back.
+        version_label: Option<String>,
         /// text of events of everything we couldn't parse
         unknown: String,
         /// Removed git conventional messages parsed back from html tags. These may live without a headline, to delete the headline.
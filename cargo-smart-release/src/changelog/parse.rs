@@ -8,7 +8,7 @@ use std::{
 use gix::bstr::ByteSlice;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, tag_no_case, take_till, take_while, take_while_m_n},
+    bytes::complete::{tag, tag_no_case, take_till, take_until, take_while, take_while_m_n},
     combinator::{all_consuming, map, map_res, opt},
     error::{FromExternalError, ParseError},
     sequence::{delimited, preceded, separated_pair, terminated, tuple},
@@ -110,6 +110,7 @@ impl Section {
         Headline {
             level,
             version_prefix,
+            version_label,
             version,
             date,
         }: Headline,
@@ -164,6 +165,12 @@ impl Section {
                             segments.push(Segment::Details(section::Data::Parsed));
                             State::SkipGenerated
                         }
+                        Some((Event::Text(title), _range))
+                            if title.starts_with(section::segment::MigrationGuide::TITLE) =>
+                        {
+                            segments.push(Segment::MigrationGuide(section::Data::Parsed));
+                            State::SkipGenerated
+                        }
                         Some((Event::Text(title), _range))
                             if title.starts_with(as_headline("feat").expect("valid"))
                                 || title.starts_with(as_headline("add").expect("valid"))
@@ -224,6 +231,7 @@ impl Section {
                 None => changelog::Version::Unreleased,
             },
             version_prefix,
+            version_label,
             date,
             removed_messages,
             heading_level: level,
@@ -460,6 +468,7 @@ fn skip_to_next_section_title(events: &mut Peekable<OffsetIter<'_, '_>>, level:
 struct Headline {
     level: usize,
     version_prefix: String,
+    version_label: Option<String>,
     version: Option<semver::Version>,
     date: Option<time::OffsetDateTime>,
 }
@@ -487,13 +496,32 @@ fn headline<'a, E: ParseError<&'a str> + FromExternalError<&'a str, ()>>(i: &'a
                     hashes,
                     greedy_whitespace,
                     alt((
-                        tuple((
-                            opt(tag("v")),
-                            map_res(take_till(|c: char| c.is_whitespace()), |v| {
-                                semver::Version::parse(v).map_err(|_| ()).map(Some)
-                            }),
-                        )),
-                        map(tag_no_case("unreleased"), |_| (None, None)),
+                        map(
+                            tuple((
+                                take_until(Section::VERSION_TAG_START),
+                                tag(Section::VERSION_TAG_START),
+                                opt(tag("v")),
+                                map_res(take_until(Section::VERSION_TAG_END), |v| {
+                                    semver::Version::parse(v).map_err(|_| ())
+                                }),
+                                tag(Section::VERSION_TAG_END),
+                            )),
+                            |(label, _, prefix, version, _)| (Some(label.trim_end()), prefix, Some(version)),
+                        ),
+                        map(
+                            tuple((
+                                opt(tag("v")),
+                                map_res(take_till(|c: char| c.is_whitespace()), |v| {
+                                    semver::Version::parse(v).map_err(|_| ()).map(Some)
+                                }),
+                            )),
+                            |(prefix, version)| (None, prefix, version),
+                        ),
+                        map(
+                            tuple((take_until(Section::UNRELEASED_TAG), tag(Section::UNRELEASED_TAG))),
+                            |_| (None, None, None),
+                        ),
+                        map(tag_no_case("unreleased"), |_| (None, None, None)),
                     )),
                 ),
                 opt(preceded(
@@ -501,12 +529,35 @@ fn headline<'a, E: ParseError<&'a str> + FromExternalError<&'a str, ()>>(i: &'a
                     delimited(
                         tag("("),
                         map_res(
-                            tuple((take_n_digits(4), tag("-"), take_n_digits(2), tag("-"), take_n_digits(2))),
-                            |(year, _, month, _, day)| {
+                            tuple((
+                                take_n_digits(4),
+                                tag("-"),
+                                take_n_digits(2),
+                                tag("-"),
+                                take_n_digits(2),
+                                opt(preceded(
+                                    tag(" "),
+                                    tuple((
+                                        take_n_digits(2),
+                                        tag(":"),
+                                        take_n_digits(2),
+                                        tag(":"),
+                                        take_n_digits(2),
+                                        tag("Z"),
+                                    )),
+                                )),
+                            )),
+                            |(year, _, month, _, day, hms)| {
                                 time::Month::try_from(month as u8).map_err(|_| ()).and_then(|month| {
-                                    time::Date::from_calendar_date(year as i32, month, day as u8)
-                                        .map_err(|_| ())
-                                        .map(|d| d.midnight().assume_utc())
+                                    let date = time::Date::from_calendar_date(year as i32, month, day as u8)
+                                        .map_err(|_| ())?;
+                                    match hms {
+                                        Some((hour, _, minute, _, second, _)) => date
+                                            .with_hms(hour as u8, minute as u8, second as u8)
+                                            .map_err(|_| ())
+                                            .map(|dt| dt.assume_utc()),
+                                        None => Ok(date.midnight().assume_utc()),
+                                    }
                                 })
                             },
                         ),
@@ -516,9 +567,10 @@ fn headline<'a, E: ParseError<&'a str> + FromExternalError<&'a str, ()>>(i: &'a
             )),
             greedy_whitespace,
         ),
-        |((hashes, (prefix, version)), date)| Headline {
+        |((hashes, (label, prefix, version)), date)| Headline {
             level: hashes.len(),
             version_prefix: prefix.map(ToOwned::to_owned).unwrap_or_else(String::new),
+            version_label: label.map(ToOwned::to_owned),
             version,
             date,
         },
@@ -27,9 +27,43 @@ impl Section {
     ) -> Self {
         let date_time = segment_head_time(segment, repo);
         let prev_date_time = prev_segment.map(|segment| segment_head_time(segment, repo));
+        let segments = Self::segments_from_history(&segment.history, selection, date_time, prev_date_time);
 
+        let version = crate::git::try_strip_tag_path(segment.head.name.as_ref())
+            .map(|tag_name| {
+                let package_name =
+                    (!is_top_level_package(&package.manifest_path, repo)).then_some(package.name.as_str());
+                changelog::Version::Semantic(
+                    utils::parse_possibly_prefixed_tag_version(package_name, tag_name)
+                        .expect("here we always have a valid version as it passed a filter when creating it"),
+                )
+            })
+            .unwrap_or_else(|| changelog::Version::Unreleased);
+        let date = match version {
+            changelog::Version::Unreleased => None,
+            changelog::Version::Semantic(_) => Some(date_time),
+        };
+
+        Section::Release {
+            name: version,
+            date,
+            heading_level: changelog::DEFAULT_HEADING_LEVEL,
+            version_prefix: Self::DEFAULT_PREFIX.to_owned(),
+            version_label: None,
+            segments,
+            removed_messages: Default::default(),
+            unknown: Default::default(),
+        }
+    }
+    /// Generate all segments selected by `selection` from the commits in `history`, ordered from newest to oldest, with
+    /// `date_time` being the time of the release and `prev_date_time` being the time of the release before it, if any.
+    pub(crate) fn segments_from_history(
+        history: &[&commit::history::Item],
+        selection: section::segment::Selection,
+        date_time: OffsetDateTime,
+        prev_date_time: Option<OffsetDateTime>,
+    ) -> Vec<Segment> {
         let mut segments = Vec::new();
-        let history = &segment.history;
         if !history.is_empty() {
             if selection.contains(Selection::GIT_CONVENTIONAL) {
                 let mut mapping = BTreeMap::default();
@@ -59,6 +93,25 @@ impl Section {
                     })
                 }));
             }
+            if selection.contains(Selection::MIGRATION_GUIDE) {
+                let entries: Vec<_> = history
+                    .iter()
+                    .filter_map(|item| {
+                        item.message.breaking_description.as_ref().map(|description| {
+                            section::segment::migration_guide::Entry {
+                                title: item.message.title.clone(),
+                                id: item.id,
+                                description: description.clone(),
+                            }
+                        })
+                    })
+                    .collect();
+                if !entries.is_empty() {
+                    segments.push(Segment::MigrationGuide(section::Data::Generated(
+                        section::segment::MigrationGuide { entries },
+                    )));
+                }
+            }
             let message_by_category = selection
                 .intersects(Selection::COMMIT_STATISTICS | Selection::COMMIT_DETAILS)
                 .then(|| {
@@ -129,31 +182,7 @@ impl Section {
                 })));
             }
         }
-
-        let version = crate::git::try_strip_tag_path(segment.head.name.as_ref())
-            .map(|tag_name| {
-                let package_name =
-                    (!is_top_level_package(&package.manifest_path, repo)).then_some(package.name.as_str());
-                changelog::Version::Semantic(
-                    utils::parse_possibly_prefixed_tag_version(package_name, tag_name)
-                        .expect("here we always have a valid version as it passed a filter when creating it"),
-                )
-            })
-            .unwrap_or_else(|| changelog::Version::Unreleased);
-        let date = match version {
-            changelog::Version::Unreleased => None,
-            changelog::Version::Semantic(_) => Some(date_time),
-        };
-
-        Section::Release {
-            name: version,
-            date,
-            heading_level: changelog::DEFAULT_HEADING_LEVEL,
-            version_prefix: Self::DEFAULT_PREFIX.to_owned(),
-            segments,
-            removed_messages: Default::default(),
-            unknown: Default::default(),
-        }
+        segments
     }
 }
 
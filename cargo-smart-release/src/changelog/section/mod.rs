@@ -11,6 +11,7 @@ pub enum Segment {
     Details(Data<segment::Details>),
     Statistics(Data<segment::CommitStatistics>),
     Clippy(Data<segment::ThanksClippy>),
+    MigrationGuide(Data<segment::MigrationGuide>),
 }
 
 #[derive(Eq, Debug, Clone)]
@@ -32,7 +33,7 @@ impl Segment {
     pub fn is_read_only(&self) -> bool {
         match self {
             Segment::User { .. } | Segment::Conventional { .. } => false,
-            Segment::Clippy(_) | Segment::Statistics(_) | Segment::Details(_) => true,
+            Segment::Clippy(_) | Segment::Statistics(_) | Segment::Details(_) | Segment::MigrationGuide(_) => true,
         }
     }
 }
@@ -82,6 +82,8 @@ pub mod details {
     pub struct Message {
         pub title: String,
         pub id: gix::ObjectId,
+        /// The author of the commit, or `None` if it isn't known.
+        pub author: Option<Author>,
     }
 
     impl From<&crate::commit::history::Item> for Message {
@@ -89,9 +91,32 @@ pub mod details {
             Message {
                 title: v.message.title.to_owned(),
                 id: v.id,
+                author: Some(Author {
+                    name: v.author.name.to_string(),
+                    email: v.author.email.to_string(),
+                }),
             }
         }
     }
+
+    /// The identity of a commit author.
+    #[derive(PartialEq, Eq, Debug, Clone)]
+    pub struct Author {
+        /// The name to display, like `Alice Doe`.
+        pub name: String,
+        /// The email address, like `alice@example.com`.
+        pub email: String,
+    }
+
+    impl Author {
+        /// The username of the author on GitHub if it can be derived from their `noreply` email address,
+        /// like `alice` for `12345+alice@users.noreply.github.com`.
+        pub fn github_username(&self) -> Option<&str> {
+            let local = self.email.strip_suffix("@users.noreply.github.com")?;
+            let username = local.split_once('+').map_or(local, |(_id, username)| username);
+            (!username.is_empty()).then_some(username)
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -123,6 +148,28 @@ impl CommitStatistics {
     pub const TITLE: &'static str = "Commit Statistics";
 }
 
+pub mod migration_guide {
+    /// The description of a breaking change along with the commit it originated from.
+    #[derive(PartialEq, Eq, Debug, Clone)]
+    pub struct Entry {
+        /// The title of the commit with the breaking change.
+        pub title: String,
+        pub id: gix::ObjectId,
+        /// The body of the `BREAKING CHANGE` footer, possibly spanning multiple lines.
+        pub description: String,
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MigrationGuide {
+    /// All breaking changes that come with a description, in commit order.
+    pub entries: Vec<migration_guide::Entry>,
+}
+
+impl MigrationGuide {
+    pub const TITLE: &'static str = "Migration Guide";
+}
+
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct ThanksClippy {
     pub count: usize,
@@ -138,5 +185,6 @@ bitflags! {
         const COMMIT_DETAILS = 1<<1;
         const COMMIT_STATISTICS = 1<<2;
         const GIT_CONVENTIONAL = 1<<3;
+        const MIGRATION_GUIDE = 1<<4;
     }
 }
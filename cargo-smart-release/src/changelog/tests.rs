@@ -1,6 +1,6 @@
 mod repository_url {
 
-    use crate::changelog::write::RepositoryUrl;
+    use crate::changelog::write::{Forge, RepositoryUrl};
 
     #[test]
     fn github_https_url() {
@@ -19,4 +19,73 @@ mod repository_url {
             )
         }
     }
+
+    #[test]
+    fn web_url_and_links_per_forge() {
+        let id = gix::ObjectId::from_hex(b"e69de29bb2d1d6434b8b29ae775ad8c2e48c5391").unwrap();
+        for (inputs, web_url, issue_url, commit_url) in [
+            (
+                [
+                    "https://github.com/byron/gitoxide.git",
+                    "git@github.com:byron/gitoxide.git",
+                ],
+                "https://github.com/byron/gitoxide",
+                "https://github.com/byron/gitoxide/issues/42",
+                "https://github.com/byron/gitoxide/commit/e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            ),
+            (
+                [
+                    "https://gitlab.com/byron/gitoxide.git",
+                    "git@gitlab.com:byron/gitoxide.git",
+                ],
+                "https://gitlab.com/byron/gitoxide",
+                "https://gitlab.com/byron/gitoxide/-/issues/42",
+                "https://gitlab.com/byron/gitoxide/-/commit/e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            ),
+            (
+                [
+                    "https://bitbucket.org/byron/gitoxide.git",
+                    "git@bitbucket.org:byron/gitoxide.git",
+                ],
+                "https://bitbucket.org/byron/gitoxide",
+                "https://bitbucket.org/byron/gitoxide/issues/42",
+                "https://bitbucket.org/byron/gitoxide/commits/e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            ),
+        ] {
+            for input in inputs {
+                let url = RepositoryUrl::from(gix::url::parse(input.into()).unwrap());
+                assert_eq!(url.web_url().as_deref(), Some(web_url), "{input}");
+                assert_eq!(url.issue_url("42").as_deref(), Some(issue_url), "{input}");
+                assert_eq!(url.commit_url(&id).as_deref(), Some(commit_url), "{input}");
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_hosts_are_linked_only_if_a_forge_is_assumed() {
+        for input in [
+            "https://git.example.com/byron/gitoxide.git",
+            "git@git.example.com:byron/gitoxide.git",
+        ] {
+            let mut url = RepositoryUrl::from(gix::url::parse(input.into()).unwrap());
+            assert_eq!(url.forge(), None);
+            assert_eq!(url.web_url(), None, "{input}: unknown hosts aren't linked by default");
+            assert_eq!(url.issue_url("42"), None);
+
+            url.unknown_host_forge = Some(Forge::GitLab);
+            assert_eq!(url.forge(), Some(Forge::GitLab));
+            assert_eq!(
+                url.issue_url("42").as_deref(),
+                Some("https://git.example.com/byron/gitoxide/-/issues/42"),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn ssh_urls_need_the_git_user_to_be_linked() {
+        let url = RepositoryUrl::from(gix::url::parse("ssh://byron@gitlab.com/byron/gitoxide.git".into()).unwrap());
+        assert_eq!(url.forge(), Some(Forge::GitLab));
+        assert_eq!(url.web_url(), None);
+    }
 }
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeMap, rc::Rc};
 
 use gix::{bstr::ByteSlice, url::Scheme, Url};
 
@@ -14,14 +14,17 @@ use crate::{
 
 struct PrefixedVersion<'a> {
     version_prefix: &'a str,
+    version_label: Option<&'a str>,
     name: &'a changelog::Version,
+    unreleased_label: &'a str,
 }
 
 impl<'a> std::fmt::Display for PrefixedVersion<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.name {
-            changelog::Version::Unreleased => f.write_str("Unreleased"),
-            changelog::Version::Semantic(v) => write!(f, "{}{}", self.version_prefix, v),
+        match (self.name, self.version_label) {
+            (changelog::Version::Unreleased, _) => f.write_str(self.unreleased_label),
+            (changelog::Version::Semantic(_), Some(label)) => f.write_str(label),
+            (changelog::Version::Semantic(v), None) => write!(f, "{}{}", self.version_prefix, v),
         }
     }
 }
@@ -34,18 +37,78 @@ pub enum Linkables {
         /// The location of the repository to link to
         repository_url: RepositoryUrl,
     },
+    /// Use markdown links relative to a base path, like `../commit/<sha>`, to link to linkable items in a site
+    /// that mirrors the repository, for example its documentation.
+    Relative {
+        /// The path all links are relative to, like `..`, or `None` to leave linkable items in their textual representation.
+        base: Option<String>,
+    },
     /// Leave them in a textual representation for the hosting platform to auto-link them
     AsText,
+    /// Use markdown links to the urls produced by the given functions, which allows to link to any kind of forge.
+    ///
+    /// Linkable items are left in their textual representation if the respective function returns `None`.
+    Custom {
+        /// Produce the url to the commit with the given id.
+        commit_url: Rc<dyn Fn(&gix::oid) -> Option<String>>,
+        /// Produce the url to the issue with the given id, like `42`.
+        issue_url: Rc<dyn Fn(&str) -> Option<String>>,
+    },
+}
+
+/// A platform hosting repositories, which determines how issues and commits are linked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forge {
+    /// `github.com`
+    GitHub,
+    /// `gitlab.com` or a self-hosted instance.
+    GitLab,
+    /// `bitbucket.org`
+    Bitbucket,
+}
+
+impl Forge {
+    /// Return the forge hosted at `host`, if it is one of the known ones.
+    pub fn from_host(host: &str) -> Option<Self> {
+        Some(match host {
+            "github.com" => Forge::GitHub,
+            "gitlab.com" => Forge::GitLab,
+            "bitbucket.org" => Forge::Bitbucket,
+            _ => return None,
+        })
+    }
+
+    fn issue_path(&self, id: &str) -> String {
+        match self {
+            Forge::GitHub | Forge::Bitbucket => format!("/issues/{}", id),
+            Forge::GitLab => format!("/-/issues/{}", id),
+        }
+    }
+
+    fn commit_path(&self, id: &gix::oid) -> String {
+        match self {
+            Forge::GitHub => format!("/commit/{}", id),
+            Forge::GitLab => format!("/-/commit/{}", id),
+            Forge::Bitbucket => format!("/commits/{}", id),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct RepositoryUrl {
     pub inner: gix::Url,
+    /// The forge to assume if the host isn't a known one, like [`Forge::GitLab`] for a self-hosted instance.
+    ///
+    /// If `None`, no links are produced for unknown hosts.
+    pub unknown_host_forge: Option<Forge>,
 }
 
 impl From<gix::Url> for RepositoryUrl {
     fn from(v: Url) -> Self {
-        RepositoryUrl { inner: v }
+        RepositoryUrl {
+            inner: v,
+            unknown_host_forge: None,
+        }
     }
 }
 
@@ -54,6 +117,34 @@ impl RepositoryUrl {
         self.inner.host().map(|h| h == "github.com").unwrap_or(false)
     }
 
+    /// The forge hosting this repository, which is [`unknown_host_forge`][Self::unknown_host_forge] if the host isn't known.
+    pub fn forge(&self) -> Option<Forge> {
+        let host = self.inner.host()?;
+        Forge::from_host(host).or(self.unknown_host_forge)
+    }
+
+    /// The url to the web page of this repository, like `https://gitlab.com/user/repo`, if its forge is known
+    /// and it can be reached through the web.
+    pub fn web_url(&self) -> Option<String> {
+        let host = self.inner.host().filter(|_| self.forge().is_some())?;
+        let accessible = match self.inner.scheme {
+            Scheme::Http | Scheme::Https | Scheme::Git => true,
+            Scheme::Ssh => self.inner.user() == Some("git"),
+            _ => false,
+        };
+        accessible.then(|| format!("https://{}{}", host, self.cleaned_path()))
+    }
+
+    /// The url to the issue with `id`, like `42`, if the forge of this repository is known.
+    pub fn issue_url(&self, id: &str) -> Option<String> {
+        Some(format!("{}{}", self.web_url()?, self.forge()?.issue_path(id)))
+    }
+
+    /// The url to the commit with `id`, if the forge of this repository is known.
+    pub fn commit_url(&self, id: &gix::oid) -> Option<String> {
+        Some(format!("{}{}", self.web_url()?, self.forge()?.commit_path(id)))
+    }
+
     fn cleaned_path(&self) -> String {
         let path = self.inner.path.to_str_lossy().into_owned();
         let path = path.strip_suffix(".git").map(ToOwned::to_owned).unwrap_or(path);
@@ -80,6 +171,60 @@ impl RepositoryUrl {
             None | Some(_) => None,
         }
     }
+
+    /// The url to the profile of the GitHub user with `username`, if this repository is hosted on GitHub.
+    pub fn github_profile_https(&self, username: &str) -> Option<String> {
+        self.is_github().then(|| format!("https://github.com/{}", username))
+    }
+}
+
+/// Define how the date of a release should be written
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DateMode {
+    /// Write the calendar day in the timezone the release was made in, like `2022-01-31`.
+    #[default]
+    Date,
+    /// Convert the date to UTC before writing its calendar day, like `2022-01-31`.
+    DateInUtc,
+    /// Convert the date to UTC and write it along with the time of day, like `2022-01-31 23:10:05Z`.
+    DateTimeInUtc,
+}
+
+/// The state of an issue as known by the forge hosting it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueState {
+    /// The issue is still open.
+    Open,
+    /// The issue was closed.
+    Closed,
+}
+
+impl IssueState {
+    /// The marker to write after an issue link to indicate its state.
+    fn marker(&self) -> &'static str {
+        match self {
+            IssueState::Open => "○",
+            IssueState::Closed => "✓",
+        }
+    }
+}
+
+/// Options to further configure how sections are written
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// How to write the date of a release.
+    pub date: DateMode,
+    /// The label to use in the heading of the unreleased section instead of `Unreleased`, like `Next Release`.
+    ///
+    /// If set and html tags are written, the heading is marked to be recognized as unreleased section when parsed again.
+    pub unreleased_label: Option<String>,
+    /// The state of issues by their id, like `"42"`, to annotate issues written as link with a marker for their state.
+    ///
+    /// Issues without a known state are written without marker.
+    pub issue_states: BTreeMap<String, IssueState>,
+    /// If `true`, consecutive commits with the exact same title in the details segment are written as single entry
+    /// that lists all of their ids, like `Fix bug (abc123, def456)`, which happens with cherry-picks, for example.
+    pub collapse_identical_titles: bool,
 }
 
 bitflags::bitflags! {
@@ -87,12 +232,22 @@ bitflags::bitflags! {
         const SECTION_TITLE = 1<<0;
         const HTML_TAGS = 1<<1;
         const DETAIL_TAGS = 1<<2;
+        /// Write the descriptions of breaking changes into their own section, instead of only marking them as breaking.
+        const MIGRATION_GUIDE = 1<<3;
+        /// Append the authors of each entry in the details segment, like `Fix bug (abc123) by @alice`.
+        ///
+        /// Authors are written as GitHub username if it is known from their email address, or by name otherwise.
+        const DETAIL_AUTHORS = 1<<4;
     }
 }
 
 impl Section {
+    pub const UNRELEASED_LABEL: &'static str = "Unreleased";
     pub const UNKNOWN_TAG_START: &'static str = "<csr-unknown>";
     pub const UNKNOWN_TAG_END: &'static str = "<csr-unknown/>";
+    pub const UNRELEASED_TAG: &'static str = "<csr-unreleased/>";
+    pub const VERSION_TAG_START: &'static str = "<csr-version name=\"";
+    pub const VERSION_TAG_END: &'static str = "\"/>";
     pub const READONLY_TAG: &'static str = "<csr-read-only-do-not-edit/>\n"; // needs a newline to not interfere with formatting
     #[cfg(windows)]
     pub const NL: &'static str = "\r\n";
@@ -106,6 +261,7 @@ impl Section {
         mut out: impl std::fmt::Write,
         link_mode: &Linkables,
         components: Components,
+        options: &Options,
     ) -> std::fmt::Result {
         match self {
             Section::Verbatim { text, .. } => {
@@ -117,26 +273,45 @@ impl Section {
                 date,
                 heading_level,
                 version_prefix,
+                version_label,
                 segments,
                 removed_messages,
                 unknown,
             } => {
                 if components.contains(Components::SECTION_TITLE) {
+                    let unreleased_label = options.unreleased_label.as_deref();
                     write!(
                         out,
                         "{} {}",
                         heading(*heading_level),
-                        PrefixedVersion { version_prefix, name }
+                        PrefixedVersion {
+                            version_prefix,
+                            version_label: version_label.as_deref(),
+                            name,
+                            unreleased_label: unreleased_label.unwrap_or(Section::UNRELEASED_LABEL),
+                        }
                     )?;
+                    if matches!(name, changelog::Version::Unreleased)
+                        && unreleased_label.map_or(false, |label| label != Section::UNRELEASED_LABEL)
+                        && components.contains(Components::HTML_TAGS)
+                    {
+                        write!(out, " {}", Section::UNRELEASED_TAG)?;
+                    }
+                    if let (changelog::Version::Semantic(version), Some(_)) = (name, version_label) {
+                        if components.contains(Components::HTML_TAGS) {
+                            write!(
+                                out,
+                                " {}{}{}{}",
+                                Section::VERSION_TAG_START,
+                                version_prefix,
+                                version,
+                                Section::VERSION_TAG_END
+                            )?;
+                        }
+                    }
                     match date {
                         None => out.write_str("\n\n"),
-                        Some(date) => writeln!(
-                            out,
-                            " ({:04}-{:02}-{:02})\n",
-                            date.year(),
-                            date.month() as u32,
-                            date.day()
-                        ),
+                        Some(date) => writeln!(out, " ({})\n", format_date(*date, options.date)),
                     }?;
                 }
                 if !removed_messages.is_empty() && components.contains(Components::HTML_TAGS) {
@@ -148,7 +323,7 @@ impl Section {
 
                 let section_level = *heading_level + 1;
                 for segment in segments {
-                    segment.write_to(section_level, link_mode, components, &mut out)?;
+                    segment.write_to(section_level, link_mode, components, options, &mut out)?;
                 }
                 if !unknown.is_empty() && components.contains(Components::HTML_TAGS) {
                     writeln!(out, "{}", Section::UNKNOWN_TAG_START)?;
@@ -161,6 +336,36 @@ impl Section {
     }
 }
 
+/// Render each of `sections` into its own string as [`Section::write_to()`] would, one at a time and only when the returned
+/// iterator is advanced.
+///
+/// This allows to write a changelog with many sections to a file, or any other destination, without having all sections in memory,
+/// particularly when `sections` are generated on the fly with [`ChangeLog::sections_from_history_segments()`].
+pub fn sections<'a>(
+    sections: impl IntoIterator<Item = Section> + 'a,
+    link_mode: &'a Linkables,
+    components: Components,
+    options: &'a Options,
+) -> impl Iterator<Item = Result<String, std::fmt::Error>> + 'a {
+    sections.into_iter().map(move |section| {
+        let mut out = String::new();
+        section.write_to(&mut out, link_mode, components, options)?;
+        Ok(out)
+    })
+}
+
+fn format_date(date: time::OffsetDateTime, mode: DateMode) -> String {
+    let date = match mode {
+        DateMode::Date => date,
+        DateMode::DateInUtc | DateMode::DateTimeInUtc => date.to_offset(time::UtcOffset::UTC),
+    };
+    let day = format!("{:04}-{:02}-{:02}", date.year(), date.month() as u32, date.day());
+    match mode {
+        DateMode::Date | DateMode::DateInUtc => day,
+        DateMode::DateTimeInUtc => format!("{} {:02}:{:02}:{:02}Z", day, date.hour(), date.minute(), date.second()),
+    }
+}
+
 fn assure_ends_with_empty_line(out: &mut impl std::fmt::Write, text: &str) -> std::fmt::Result {
     if !(text.ends_with("\n\n") || text.ends_with("\r\n\r\n")) {
         out.write_str(Section::NL)?;
@@ -174,15 +379,95 @@ fn heading(level: usize) -> String {
     "#".repeat(level)
 }
 
+/// An item that could be linked, but would be written as text with a particular [`Linkables`] mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnlinkableWarning {
+    /// The issue with `id` mentioned in the release `version` can't be linked.
+    Issue { version: changelog::Version, id: String },
+    /// The commit with `id` mentioned in the release `version` can't be linked.
+    Commit {
+        version: changelog::Version,
+        id: gix::ObjectId,
+    },
+}
+
 impl ChangeLog {
+    /// Return all issues and commits in generated segments that [`write_to()`][ChangeLog::write_to()] would write as text
+    /// instead of as link when using `link_mode`, for instance because the repository url isn't on a supported forge.
+    ///
+    /// Each issue is reported only once per release.
+    pub fn check_linkable(&self, link_mode: &Linkables) -> Vec<UnlinkableWarning> {
+        let mut out = Vec::new();
+        for section in &self.sections {
+            let (version, segments) = match section {
+                Section::Release { name, segments, .. } => (name, segments),
+                Section::Verbatim { .. } => continue,
+            };
+            let push_issue = |id: &String, out: &mut Vec<UnlinkableWarning>| {
+                if issue_url(id, link_mode).is_none() {
+                    let warning = UnlinkableWarning::Issue {
+                        version: version.clone(),
+                        id: id.clone(),
+                    };
+                    if !out.contains(&warning) {
+                        out.push(warning);
+                    }
+                }
+            };
+            for segment in segments {
+                match segment {
+                    Segment::Details(section::Data::Generated(segment::Details { commits_by_category })) => {
+                        for (category, messages) in commits_by_category {
+                            if let Category::Issue(id) = category {
+                                push_issue(id, &mut out);
+                            }
+                            out.extend(
+                                messages
+                                    .iter()
+                                    .filter(|message| commit_url(&message.id, link_mode).is_none())
+                                    .map(|message| UnlinkableWarning::Commit {
+                                        version: version.clone(),
+                                        id: message.id,
+                                    }),
+                            );
+                        }
+                    }
+                    Segment::Statistics(section::Data::Generated(segment::CommitStatistics {
+                        unique_issues, ..
+                    })) => {
+                        for category in unique_issues {
+                            if let Category::Issue(id) = category {
+                                push_issue(id, &mut out);
+                            }
+                        }
+                    }
+                    Segment::MigrationGuide(section::Data::Generated(segment::MigrationGuide { entries })) => {
+                        out.extend(
+                            entries
+                                .iter()
+                                .filter(|entry| commit_url(&entry.id, link_mode).is_none())
+                                .map(|entry| UnlinkableWarning::Commit {
+                                    version: version.clone(),
+                                    id: entry.id,
+                                }),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+        }
+        out
+    }
+
     pub fn write_to(
         &self,
         mut out: impl std::fmt::Write,
         link_mode: &Linkables,
         components: Components,
+        options: &Options,
     ) -> std::fmt::Result {
         for section in &self.sections {
-            section.write_to(&mut out, link_mode, components)?;
+            section.write_to(&mut out, link_mode, components, options)?;
         }
         Ok(())
     }
@@ -194,6 +479,7 @@ impl section::Segment {
         section_level: usize,
         link_mode: &Linkables,
         components: Components,
+        options: &Options,
         mut out: impl std::fmt::Write,
     ) -> std::fmt::Result {
         let write_html = components.contains(Components::HTML_TAGS);
@@ -278,14 +564,39 @@ impl section::Segment {
                     writeln!(out, "{}\n", segment::Details::HTML_PREFIX)?;
                 }
                 for (category, messages) in commits_by_category.iter() {
-                    writeln!(out, " * **{}**", format_category(category, link_mode))?;
-                    for message in messages {
-                        writeln!(
+                    writeln!(out, " * **{}**", format_category(category, link_mode, options))?;
+                    let mut messages = messages.as_slice();
+                    while let Some(message) = messages.first() {
+                        let num_identical = if options.collapse_identical_titles {
+                            messages.iter().take_while(|m| m.title == message.title).count()
+                        } else {
+                            1
+                        };
+                        let (identical, rest) = messages.split_at(num_identical);
+                        write!(
                             out,
                             "    - {} ({})",
                             capitalize_message_title(&message.title),
-                            format_oid(&message.id, link_mode)
+                            identical
+                                .iter()
+                                .map(|m| format_oid(&m.id, link_mode))
+                                .collect::<Vec<_>>()
+                                .join(", ")
                         )?;
+                        if components.contains(Components::DETAIL_AUTHORS) {
+                            let mut authors = Vec::new();
+                            for author in identical.iter().filter_map(|m| m.author.as_ref()) {
+                                let author = format_author(author, link_mode);
+                                if !authors.contains(&author) {
+                                    authors.push(author);
+                                }
+                            }
+                            if !authors.is_empty() {
+                                write!(out, " by {}", authors.join(", "))?;
+                            }
+                        }
+                        writeln!(out)?;
+                        messages = rest;
                     }
                 }
                 if write_details_tags {
@@ -348,7 +659,7 @@ impl section::Segment {
                         if unique_issues.len() == 1 { "was" } else { "were" },
                         unique_issues
                             .iter()
-                            .map(|c| format_category(c, link_mode))
+                            .map(|c| format_category(c, link_mode, options))
                             .collect::<Vec<_>>()
                             .join(", ")
                     )?;
@@ -367,6 +678,31 @@ impl section::Segment {
                     if *count > 1 { "times" } else { "time" }
                 )?;
             }
+            Segment::MigrationGuide(section::Data::Generated(segment::MigrationGuide { entries }))
+                if !entries.is_empty() && components.contains(Components::MIGRATION_GUIDE) =>
+            {
+                writeln!(out, "{} {}\n", heading(section_level), segment::MigrationGuide::TITLE)?;
+                if write_html {
+                    writeln!(out, "{}", Section::READONLY_TAG)?;
+                }
+                for entry in entries {
+                    writeln!(
+                        out,
+                        " - **{}** ({})",
+                        capitalize_message_title(&entry.title),
+                        format_oid(&entry.id, link_mode)
+                    )?;
+                    for line in entry.description.lines() {
+                        if line.trim().is_empty() {
+                            writeln!(out)?;
+                        } else {
+                            writeln!(out, "   {}", line)?;
+                        }
+                    }
+                }
+                writeln!(out)?;
+            }
+            Segment::MigrationGuide(_) => {}
             Segment::Clippy(_) => {}
             Segment::Statistics(_) => {}
             Segment::Details(_) => {}
@@ -375,27 +711,58 @@ impl section::Segment {
     }
 }
 
-fn format_category(cat: &Category, link_mode: &Linkables) -> String {
-    match (cat, link_mode) {
-        (Category::Issue(id), Linkables::AsLinks { repository_url }) => match repository_url.github_https() {
-            Some(base_url) => {
-                format!("[#{}]({}/issues/{})", id, base_url, id)
-            }
-            None => format_category(cat, &Linkables::AsText),
+fn relative_base(base: &Option<String>) -> Option<&str> {
+    base.as_deref().map(|base| base.trim_end_matches('/'))
+}
+
+fn issue_url(id: &str, link_mode: &Linkables) -> Option<String> {
+    match link_mode {
+        Linkables::AsLinks { repository_url } => repository_url.issue_url(id),
+        Linkables::Relative { base } => relative_base(base).map(|base| format!("{}/issues/{}", base, id)),
+        Linkables::Custom { issue_url, .. } => issue_url(id),
+        Linkables::AsText => None,
+    }
+}
+
+fn commit_url(id: &gix::oid, link_mode: &Linkables) -> Option<String> {
+    match link_mode {
+        Linkables::AsLinks { repository_url } => repository_url.commit_url(id),
+        Linkables::Relative { base } => relative_base(base).map(|base| format!("{}/commit/{}", base, id)),
+        Linkables::Custom { commit_url, .. } => commit_url(id),
+        Linkables::AsText => None,
+    }
+}
+
+fn format_category(cat: &Category, link_mode: &Linkables, options: &Options) -> String {
+    match cat {
+        Category::Issue(id) => match issue_url(id, link_mode) {
+            Some(url) => match options.issue_states.get(id) {
+                Some(state) => format!("[#{}]({}) {}", id, url, state.marker()),
+                None => format!("[#{}]({})", id, url),
+            },
+            None => cat.to_string(),
         },
-        (_, _) => cat.to_string(),
+        Category::Uncategorized => cat.to_string(),
     }
 }
 
 fn format_oid(id: &gix::oid, link_mode: &Linkables) -> String {
-    match link_mode {
-        Linkables::AsText => id.to_hex_with_len(7).to_string(),
-        Linkables::AsLinks { repository_url } => match repository_url.github_https() {
-            Some(base_url) => {
-                format!("[`{}`]({}/commit/{})", id.to_hex_with_len(7), base_url, id)
-            }
-            None => format_oid(id, &Linkables::AsText),
+    match commit_url(id, link_mode) {
+        Some(url) => format!("[`{}`]({})", id.to_hex_with_len(7), url),
+        None => id.to_hex_with_len(7).to_string(),
+    }
+}
+
+fn format_author(author: &segment::details::Author, link_mode: &Linkables) -> String {
+    match author.github_username() {
+        Some(username) => match link_mode {
+            Linkables::AsLinks { repository_url } => match repository_url.github_profile_https(username) {
+                Some(url) => format!("[@{}]({})", username, url),
+                None => format!("@{}", username),
+            },
+            _ => format!("@{}", username),
         },
+        None => author.name.clone(),
     }
 }
 
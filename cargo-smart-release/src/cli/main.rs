@@ -20,6 +20,7 @@ fn main() -> anyhow::Result<()> {
             no_preview,
             no_links,
             without,
+            migration_guide,
             allow_dirty,
         } => {
             init_logging(false);
@@ -30,7 +31,7 @@ fn main() -> anyhow::Result<()> {
                     no_links,
                     preview: !no_preview,
                     dependencies: !no_dependencies,
-                    generator_segments: names_to_segment_selection(&without)?,
+                    generator_segments: names_to_segment_selection(&without, migration_guide)?,
                 },
                 crates,
             )?
@@ -47,6 +48,7 @@ fn main() -> anyhow::Result<()> {
             no_tag,
             no_push,
             changelog_without,
+            changelog_migration_guide,
             dangerously_pass_no_verify,
             no_auto_publish_of_stable_crates,
             no_conservative_pre_release_version_handling,
@@ -82,7 +84,7 @@ fn main() -> anyhow::Result<()> {
                     allow_auto_publish_of_stable_crates: !no_auto_publish_of_stable_crates,
                     update_crates_index,
                     preview: !no_changelog_preview,
-                    generator_segments: names_to_segment_selection(&changelog_without)?,
+                    generator_segments: names_to_segment_selection(&changelog_without, changelog_migration_guide)?,
                     allow_fully_generated_changelogs,
                     changelog_links: !no_changelog_links,
                     allow_changelog_github_release: !no_changelog_github_release,
@@ -113,23 +115,23 @@ fn to_bump_spec(spec: &str) -> anyhow::Result<cargo_smart_release::version::Bump
 
 fn names_to_segment_selection(
     names: &[String],
+    migration_guide: bool,
 ) -> anyhow::Result<cargo_smart_release::changelog::section::segment::Selection> {
     use cargo_smart_release::changelog::section::segment::Selection;
-    Ok(if names.is_empty() {
-        Selection::all()
-    } else {
-        let mut deselected = Selection::empty();
-        for name in names {
-            deselected |= match name.as_str() {
-                "clippy" => Selection::CLIPPY,
-                "commit-details" => Selection::COMMIT_DETAILS,
-                "commit-statistics" => Selection::COMMIT_STATISTICS,
-                "git-conventional" => Selection::GIT_CONVENTIONAL,
-                other => anyhow::bail!("Invalid changelog segment selector: {:?}", other),
-            };
-        }
-        Selection::all().difference(deselected)
-    })
+    let mut selection = Selection::all().difference(Selection::MIGRATION_GUIDE);
+    if migration_guide {
+        selection |= Selection::MIGRATION_GUIDE;
+    }
+    for name in names {
+        selection.remove(match name.as_str() {
+            "clippy" => Selection::CLIPPY,
+            "commit-details" => Selection::COMMIT_DETAILS,
+            "commit-statistics" => Selection::COMMIT_STATISTICS,
+            "git-conventional" => Selection::GIT_CONVENTIONAL,
+            other => anyhow::bail!("Invalid changelog segment selector: {:?}", other),
+        });
+    }
+    Ok(selection)
 }
 
 fn init_logging(verbose: bool) {
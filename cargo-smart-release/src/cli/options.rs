@@ -99,6 +99,10 @@ pub enum SubCommands {
         #[clap(long, help_heading = Some("CHANGELOG"))]
         changelog_without: Vec<String>,
 
+        /// Also generate a migration guide from the 'BREAKING CHANGE' footers of commit messages.
+        #[clap(long, help_heading = Some("CHANGELOG"))]
+        changelog_migration_guide: bool,
+
         /// If unset, about-to-be changed changelogs will be previewed using 'bat', if available, and when executing.
         ///
         /// If set, no preview will ever be displayed, but note that empty changelogs will always stop the release process.
@@ -175,6 +179,10 @@ pub enum SubCommands {
         #[clap(long, help_heading = Some("CUSTOMIZATION"))]
         without: Vec<String>,
 
+        /// Also generate a migration guide from the 'BREAKING CHANGE' footers of commit messages.
+        #[clap(long, help_heading = Some("CUSTOMIZATION"))]
+        migration_guide: bool,
+
         /// Take into consideration any dependencies of the crates to generate the changelog for.
         ///
         /// This flag is useful if you plan to review and finalize changelogs before a smart-release, where dependencies
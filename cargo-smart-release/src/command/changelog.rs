@@ -95,8 +95,9 @@ pub fn changelog(opts: Options, crates: Vec<String>) -> anyhow::Result<()> {
                 if dry_run {
                     Components::SECTION_TITLE
                 } else {
-                    Components::all()
+                    Components::all() - Components::DETAIL_AUTHORS
                 },
+                &Default::default(),
             )
             .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
             file.write_all(buf.as_bytes())
@@ -482,8 +482,9 @@ fn gather_changelog_data<'a, 'meta>(
                 if dry_run {
                     changelog::write::Components::SECTION_TITLE
                 } else {
-                    changelog::write::Components::all()
+                    changelog::write::Components::all() - changelog::write::Components::DETAIL_AUTHORS
                 },
+                &Default::default(),
             )?;
             lock.with_mut(|file| file.write_all(write_buf.as_bytes()))?;
             *made_change |= previous_content.map(|previous| write_buf != previous).unwrap_or(true);
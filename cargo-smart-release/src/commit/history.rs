@@ -11,6 +11,7 @@ pub struct Item {
     pub id: gix::ObjectId,
     pub message: Message,
     pub commit_time: gix::actor::Time,
+    pub author: gix::actor::Signature,
     pub tree_id: gix::ObjectId,
     pub parent_tree_id: Option<gix::ObjectId>,
 }
@@ -23,7 +24,7 @@ mod tests {
     fn size_of_item() {
         assert_eq!(
             std::mem::size_of::<Item>(),
-            200,
+            264,
             "there are plenty of these loaded at a time and we should not let it grow unnoticed."
         )
     }
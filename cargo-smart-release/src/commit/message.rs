@@ -91,8 +91,10 @@ impl From<&'_ str> for Message {
                     Some(c.type_()),
                     c.body().map(Into::into),
                     c.breaking(),
-                    c.breaking_description()
-                        .and_then(|d| if d == c.description() { None } else { Some(d) }),
+                    c.footers()
+                        .iter()
+                        .find(|footer| footer.breaking())
+                        .map(|footer| footer.value()),
                 )
             })
             .unwrap_or_else(|_| {
@@ -197,4 +199,19 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn conventional_breaking_without_footer_has_no_breaking_description() {
+        assert_eq!(
+            Message::from("feat!: hi\n\nthe body"),
+            Message {
+                title: "hi".into(),
+                body: Some("the body".into()),
+                kind: Some("feat"),
+                breaking: true,
+                breaking_description: None,
+                additions: vec![]
+            }
+        )
+    }
 }
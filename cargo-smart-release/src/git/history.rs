@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     iter::FromIterator,
     path::PathBuf,
 };
@@ -45,43 +45,15 @@ pub fn collect(repo: &gix::Repository) -> anyhow::Result<Option<commit::History>
         .sorting(gix::traverse::commit::Sorting::ByCommitTimeNewestFirst)
         .all()?
     {
-        let commit_id = commit_id?;
-        let (message, tree_id, parent_tree_id, commit_time) = {
-            let (message, tree_id, commit_time, parent_commit_id) = {
-                let object = commit_id.object()?;
-                let commit = object.to_commit_ref();
-                let parent = commit.parents().next();
-                (commit.message.to_vec(), commit.tree(), commit.committer.time, parent)
-            };
-            (
-                message,
-                tree_id,
-                parent_commit_id.map(|id| id.attach(&handle).object().expect("present").to_commit_ref().tree()),
-                commit_time,
-            )
+        let item = match to_item(commit_id?, &handle)? {
+            Some(item) => item,
+            None => continue,
         };
-
-        let message = match message.to_str() {
-            Err(_) => {
-                log::warn!(
-                    "Commit message of {} could not be decoded to UTF-8 - ignored",
-                    commit_id.as_ref()
-                );
-                continue;
-            }
-            Ok(m) => m,
-        };
-        data_by_tree_id.insert(tree_id, handle.find_object(tree_id)?.data.to_owned());
-        if let Some(tree_id) = parent_tree_id {
+        data_by_tree_id.insert(item.tree_id, handle.find_object(item.tree_id)?.data.to_owned());
+        if let Some(tree_id) = item.parent_tree_id {
             data_by_tree_id.insert(tree_id, handle.find_object(tree_id)?.data.to_owned());
         }
-        items.push(commit::history::Item {
-            id: commit_id.detach(),
-            commit_time,
-            message: commit::Message::from(message),
-            tree_id,
-            parent_tree_id,
-        });
+        items.push(item);
     }
 
     Ok(Some(commit::History {
@@ -91,6 +63,83 @@ pub fn collect(repo: &gix::Repository) -> anyhow::Result<Option<commit::History>
     }))
 }
 
+/// Return all commits reachable from the end of `range` but not from its start, newest first, along with the ids of the commits
+/// at the end and the start of the range.
+///
+/// `range` is a revision range like `from..to`, `from..` to imply `HEAD` as end, or just `to` to use its entire history.
+pub fn collect_range(
+    repo: &gix::Repository,
+    range: &str,
+) -> anyhow::Result<(Vec<Item>, gix::ObjectId, Option<gix::ObjectId>)> {
+    let (from, to) = match range.split_once("..") {
+        Some((from, to)) => (Some(from), if to.is_empty() { "HEAD" } else { to }),
+        None => (None, range),
+    };
+    let peel_to_commit = |spec: &str| -> anyhow::Result<gix::ObjectId> {
+        Ok(repo
+            .rev_parse_single(spec)?
+            .object()?
+            .peel_to_kind(gix::object::Kind::Commit)?
+            .id)
+    };
+    let to = peel_to_commit(to)?;
+    let from = from.map(peel_to_commit).transpose()?;
+
+    let hidden = match from {
+        Some(from) => from
+            .attach(repo)
+            .ancestors()
+            .all()?
+            .map(|id| id.map(|id| id.detach()))
+            .collect::<Result<HashSet<_>, _>>()?,
+        None => HashSet::default(),
+    };
+    let mut items = Vec::new();
+    for commit_id in to
+        .attach(repo)
+        .ancestors()
+        .sorting(gix::traverse::commit::Sorting::ByCommitTimeNewestFirst)
+        .all()?
+    {
+        let commit_id = commit_id?;
+        if hidden.contains(&commit_id.detach()) {
+            continue;
+        }
+        items.extend(to_item(commit_id, repo)?);
+    }
+    Ok((items, to, from))
+}
+
+/// Turn the commit with `commit_id` into a history item, or return `None` if its message isn't valid UTF-8.
+fn to_item(commit_id: gix::Id<'_>, repo: &gix::Repository) -> anyhow::Result<Option<Item>> {
+    let object = commit_id.object()?;
+    let commit = object.to_commit_ref();
+    let parent_tree_id = commit
+        .parents()
+        .next()
+        .map(|id| -> anyhow::Result<_> { Ok(id.attach(repo).object()?.to_commit_ref().tree()) })
+        .transpose()?;
+
+    let message = match commit.message.to_str() {
+        Err(_) => {
+            log::warn!(
+                "Commit message of {} could not be decoded to UTF-8 - ignored",
+                commit_id.as_ref()
+            );
+            return Ok(None);
+        }
+        Ok(m) => m,
+    };
+    Ok(Some(Item {
+        id: commit_id.detach(),
+        commit_time: commit.committer.time,
+        author: commit.author.to_owned(),
+        message: commit::Message::from(message),
+        tree_id: commit.tree(),
+        parent_tree_id,
+    }))
+}
+
 /// Return the head reference followed by all tags affecting `crate_name` as per our tag name rules, ordered by ancestry.
 pub fn crate_ref_segments<'h>(
     package: &Package,
@@ -0,0 +1,76 @@
+use cargo_smart_release::{
+    changelog,
+    changelog::{section, section::segment::Selection, Section},
+    ChangeLog,
+};
+
+use crate::Result;
+
+#[test]
+fn commits_after_the_start_of_the_range_make_up_a_single_release() -> Result {
+    let repo = gix::open(gix_testtools::scripted_fixture_read_only("make_commit_range.sh")?)?;
+    let version = changelog::Version::Semantic("1.1.0".parse()?);
+    let log = ChangeLog::from_commit_range(&repo, "v1.0.0..", version.clone(), Selection::all())?;
+    assert_eq!(log.sections.len(), 1);
+    let segments = match &log.sections[0] {
+        Section::Release {
+            name, date, segments, ..
+        } => {
+            assert_eq!(*name, version);
+            assert!(date.is_some(), "releases are dated with the time of the last commit");
+            segments
+        }
+        Section::Verbatim { .. } => unreachable!("only releases are generated"),
+    };
+
+    let statistics = segments
+        .iter()
+        .find_map(|s| match s {
+            section::Segment::Statistics(section::Data::Generated(stats)) => Some(stats),
+            _ => None,
+        })
+        .expect("statistics are selected");
+    assert_eq!(statistics.count, 3, "the tagged commit isn't part of the range");
+    assert_eq!(statistics.conventional_count, 2);
+    assert_eq!(
+        statistics.unique_issues,
+        vec![section::segment::details::Category::Issue("1".into())]
+    );
+
+    let guide = segments
+        .iter()
+        .find_map(|s| match s {
+            section::Segment::MigrationGuide(section::Data::Generated(guide)) => Some(guide),
+            _ => None,
+        })
+        .expect("there is a breaking change with description");
+    assert_eq!(guide.entries.len(), 1);
+    assert_eq!(guide.entries[0].description, "use `baz()` instead of `bar()`");
+
+    assert!(segments
+        .iter()
+        .any(|s| matches!(s, section::Segment::Clippy(section::Data::Generated(c)) if c.count == 1)));
+    assert_eq!(
+        segments
+            .iter()
+            .filter(|s| matches!(s, section::Segment::Conventional(_)))
+            .count(),
+        2,
+        "one for features, one for breaking fixes"
+    );
+
+    let log = ChangeLog::from_commit_range(
+        &repo,
+        "HEAD",
+        changelog::Version::Unreleased,
+        Selection::COMMIT_STATISTICS,
+    )?;
+    match &log.sections[0] {
+        Section::Release { date, segments, .. } => {
+            assert_eq!(*date, None, "unreleased sections have no date");
+            assert_eq!(segments.len(), 1, "only the selected segments are generated");
+        }
+        Section::Verbatim { .. } => unreachable!("only releases are generated"),
+    }
+    Ok(())
+}
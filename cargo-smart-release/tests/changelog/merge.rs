@@ -18,6 +18,7 @@ fn sections() {
             Section::Release {
                 heading_level: 3,
                 version_prefix: "".into(),
+                version_label: None,
                 removed_messages: vec![],
                 date: Some(
                     time::Date::from_calendar_date(2021, time::Month::September, 14)
@@ -36,6 +37,7 @@ fn sections() {
             Section::Release {
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 removed_messages: vec![],
                 date: None,
                 name: changelog::Version::Semantic("0.9.0".parse().unwrap()),
@@ -55,6 +57,7 @@ fn sections() {
                 removed_messages: vec![],
                 name: changelog::Version::Unreleased,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 heading_level: 2,
                 segments: vec![section::Segment::Clippy(section::Data::Generated(
                     section::segment::ThanksClippy { count: 4 },
@@ -67,6 +70,7 @@ fn sections() {
                 removed_messages: vec![],
                 heading_level: 2,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 segments: vec![section::Segment::Clippy(section::Data::Generated(
                     section::segment::ThanksClippy { count: 3 },
                 ))],
@@ -79,6 +83,7 @@ fn sections() {
                 removed_messages: vec![],
                 heading_level: 2,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 segments: vec![section::Segment::Clippy(section::Data::Generated(
                     section::segment::ThanksClippy { count: 2 },
                 ))],
@@ -90,6 +95,7 @@ fn sections() {
                 removed_messages: vec![],
                 heading_level: 2,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 segments: Vec::new(),
             },
         ],
@@ -109,6 +115,7 @@ fn sections() {
                     name: changelog::Version::Unreleased,
                     heading_level: 3,
                     version_prefix: "".into(),
+                    version_label: None,
                     removed_messages: vec![],
                     segments: vec![section::Segment::Clippy(section::Data::Generated(
                         section::segment::ThanksClippy { count: 4 }
@@ -119,6 +126,7 @@ fn sections() {
                     heading_level: 3,
                     removed_messages: vec![],
                     version_prefix: "".into(),
+                    version_label: None,
                     date: Some(
                         time::Date::from_calendar_date(2021, time::Month::September, 15)
                             .unwrap()
@@ -141,6 +149,7 @@ fn sections() {
                     unknown: String::new(),
                     heading_level: 3,
                     version_prefix: Section::DEFAULT_PREFIX.into(),
+                    version_label: None,
                     removed_messages: vec![],
                     segments: vec![section::Segment::Clippy(section::Data::Generated(
                         section::segment::ThanksClippy { count: 2 }
@@ -152,6 +161,7 @@ fn sections() {
                     unknown: "undocumented".into(),
                     heading_level: 3,
                     version_prefix: "".into(),
+                    version_label: None,
                     removed_messages: vec![],
                     segments: Vec::new(),
                 },
@@ -177,6 +187,7 @@ fn segments() {
                 unknown: "".into(),
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 removed_messages: vec![],
                 segments: vec![
                     section::Segment::Conventional(section::segment::Conventional {
@@ -202,6 +213,7 @@ fn segments() {
                 name: changelog::Version::Unreleased,
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 removed_messages: vec![],
                 segments: vec![section::Segment::Clippy(section::Data::Parsed)], // only clippy still available
                 unknown: Default::default(),
@@ -209,6 +221,7 @@ fn segments() {
             Section::Release {
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 removed_messages: vec![],
                 date: Some(
                     time::Date::from_calendar_date(2021, time::Month::September, 15)
@@ -228,6 +241,7 @@ fn segments() {
                 unknown: "".into(),
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 removed_messages: vec![],
                 segments: vec![
                     section::Segment::Details(section::Data::Parsed),
@@ -282,6 +296,7 @@ fn segments() {
                 name: changelog::Version::Unreleased,
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 removed_messages: vec![],
                 segments: segments.clone(),
                 unknown: Default::default(),
@@ -289,6 +304,7 @@ fn segments() {
             Section::Release {
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 removed_messages: vec![],
                 date: Some(
                     time::Date::from_calendar_date(2021, time::Month::September, 15)
@@ -306,6 +322,7 @@ fn segments() {
                 unknown: "".into(),
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 removed_messages: vec![],
                 segments: segments.clone(),
             },
@@ -315,6 +332,7 @@ fn segments() {
                 unknown: "".into(),
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 removed_messages: vec![],
                 segments: {
                     let mut v = segments.clone();
@@ -339,6 +357,7 @@ fn segments() {
                     unknown: "".into(),
                     heading_level: 3,
                     version_prefix: Section::DEFAULT_PREFIX.into(),
+                    version_label: None,
                     removed_messages: vec![],
                     segments: vec![
                         section::Segment::Conventional(section::segment::Conventional {
@@ -369,6 +388,7 @@ fn segments() {
                     name: changelog::Version::Unreleased,
                     heading_level: 3,
                     version_prefix: Section::DEFAULT_PREFIX.into(),
+                    version_label: None,
                     removed_messages: vec![],
                     segments: vec![clippy],
                     unknown: Default::default(),
@@ -376,6 +396,7 @@ fn segments() {
                 Section::Release {
                     heading_level: 3,
                     version_prefix: Section::DEFAULT_PREFIX.into(),
+                    version_label: None,
                     removed_messages: vec![],
                     date: Some(
                         time::Date::from_calendar_date(2021, time::Month::September, 15)
@@ -402,6 +423,7 @@ fn segments() {
                     unknown: "".into(),
                     heading_level: 3,
                     version_prefix: Section::DEFAULT_PREFIX.into(),
+                    version_label: None,
                     removed_messages: vec![],
                     segments: vec![details, statistics],
                 },
@@ -1,5 +1,7 @@
 use gix::ObjectId;
 
+mod from_commit_range;
+
 mod parse;
 
 mod write_and_parse;
@@ -37,6 +37,7 @@ fn all_unknown_in_section() {
                 date: None,
                 heading_level: 3,
                 version_prefix: "".into(),
+                version_label: None,
                 segments: vec![Segment::User {
                     markdown: "- hello ~~this is not understood~~\n* this isn't either\n\n".into()
                 }],
@@ -48,6 +49,7 @@ fn all_unknown_in_section() {
                 date: None,
                 heading_level: 4,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 segments: vec![Segment::User {
                     markdown: "Some free text in a paragraph\nthat won't parse.\n".into()
                 }],
@@ -69,6 +71,7 @@ fn unknown_link_and_headline() {
             date: None,
             heading_level: 4,
             version_prefix: "".into(),
+            version_label: None,
             segments: vec![Segment::User {
                 markdown: "##### Special\n\nHello [there][194] period.\n".into()
             }],
@@ -94,6 +97,7 @@ fn known_and_unknown_sections_are_sorted() {
                 date: None,
                 heading_level: 3,
                 version_prefix: "".into(),
+                version_label: None,
                 unknown: "".into(),
                 segments: vec![Segment::User {
                     markdown: "TBD\n".into()
@@ -105,6 +109,7 @@ fn known_and_unknown_sections_are_sorted() {
                 date: None,
                 heading_level: 3,
                 version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
                 unknown: "".into(),
                 segments: vec![
                     Segment::User {
@@ -18,6 +18,7 @@ fn conventional_write_empty_messages() -> Result {
         sections: vec![Section::Release {
             heading_level: 4,
             version_prefix: Section::DEFAULT_PREFIX.into(),
+            version_label: None,
             date: Some(time::OffsetDateTime::from_unix_timestamp(0)?),
             name: changelog::Version::Semantic("1.0.2-beta.2".parse()?),
             removed_messages: vec![second_message],
@@ -59,7 +60,12 @@ fn conventional_write_empty_messages() -> Result {
         let log = log.clone();
         for _round in 1..=2 {
             let mut md = String::new();
-            log.write_to(&mut md, link_mode, changelog::write::Components::all())?;
+            log.write_to(
+                &mut md,
+                link_mode,
+                changelog::write::Components::all(),
+                &Default::default(),
+            )?;
             insta::assert_snapshot!(md);
 
             let parsed_log = ChangeLog::from_markdown(&md);
@@ -72,7 +78,12 @@ fn conventional_write_empty_messages() -> Result {
     ] {
         for section in &log.sections {
             let mut buf = String::new();
-            section.write_to(&mut buf, &changelog::write::Linkables::AsText, *components)?;
+            section.write_to(
+                &mut buf,
+                &changelog::write::Linkables::AsText,
+                *components,
+                &Default::default(),
+            )?;
             insta::assert_snapshot!(buf);
         }
     }
@@ -93,12 +104,14 @@ fn all_section_types_round_trips_lossy() -> Result {
                 date: None,
                 name: changelog::Version::Unreleased,
                 version_prefix: "".into(),
+                version_label: None,
                 segments: Vec::new(),
                 unknown: "hello\nworld\n".into(),
             },
             Section::Release {
                 heading_level: 4,
                 version_prefix: "".into(),
+                version_label: None,
                 removed_messages: vec![],
                 date: Some(time::OffsetDateTime::from_unix_timestamp(0)?),
                 name: changelog::Version::Semantic("1.0.2-beta.2".parse()?),
@@ -127,10 +140,12 @@ fn all_section_types_round_trips_lossy() -> Result {
                                     section::segment::details::Message {
                                         title: "Just the title".into(),
                                         id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                                        author: None,
                                     },
                                     section::segment::details::Message {
                                         title: "Title and body".into(),
                                         id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5392"),
+                                        author: None,
                                     },
                                 ],
                             );
@@ -140,10 +155,12 @@ fn all_section_types_round_trips_lossy() -> Result {
                                     section::segment::details::Message {
                                         title: "Just the title".into(),
                                         id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5392"),
+                                        author: None,
                                     },
                                     section::segment::details::Message {
                                         title: "Another title".into(),
                                         id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                                        author: None,
                                     },
                                 ],
                             );
@@ -164,7 +181,12 @@ fn all_section_types_round_trips_lossy() -> Result {
     ] {
         // NOTE: we can't run this a second time as the statistical information will be gone (it was never parsed back)
         let mut md = String::new();
-        log.write_to(&mut md, link_mode, changelog::write::Components::all())?;
+        log.write_to(
+            &mut md,
+            link_mode,
+            changelog::write::Components::all(),
+            &Default::default(),
+        )?;
         insta::assert_snapshot!(md);
 
         let parsed_log = ChangeLog::from_markdown(&md);
@@ -178,9 +200,722 @@ fn all_section_types_round_trips_lossy() -> Result {
     ] {
         for section in &log.sections {
             let mut buf = String::new();
-            section.write_to(&mut buf, &changelog::write::Linkables::AsText, *components)?;
+            section.write_to(
+                &mut buf,
+                &changelog::write::Linkables::AsText,
+                *components,
+                &Default::default(),
+            )?;
             insta::assert_snapshot!(buf);
         }
     }
     Ok(())
 }
+
+#[test]
+fn release_dates_can_be_written_in_utc_and_with_time() -> Result {
+    use changelog::write::{Components, DateMode, Linkables, Options};
+    let late_at_night_west_of_utc = time::OffsetDateTime::from_unix_timestamp(1_643_676_600)?
+        .to_offset(time::UtcOffset::from_hms(-5, 0, 0).expect("valid offset"));
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: None,
+        removed_messages: vec![],
+        date: Some(late_at_night_west_of_utc),
+        name: changelog::Version::Semantic("1.0.0".parse()?),
+        segments: Vec::new(),
+        unknown: String::new(),
+    };
+
+    for (mode, expected_headline, expected_date) in [
+        (
+            DateMode::Date,
+            "## v1.0.0 (2022-01-31)",
+            utc(time::Month::January, 31, 0, 0),
+        ),
+        (
+            DateMode::DateInUtc,
+            "## v1.0.0 (2022-02-01)",
+            utc(time::Month::February, 1, 0, 0),
+        ),
+        (
+            DateMode::DateTimeInUtc,
+            "## v1.0.0 (2022-02-01 00:50:00Z)",
+            utc(time::Month::February, 1, 0, 50),
+        ),
+    ] {
+        let mut md = String::new();
+        section.write_to(
+            &mut md,
+            &Linkables::AsText,
+            Components::SECTION_TITLE,
+            &Options {
+                date: mode,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(md.lines().next(), Some(expected_headline));
+
+        let parsed_log = ChangeLog::from_markdown(&md);
+        match &parsed_log.sections[0] {
+            Section::Release { date, .. } => assert_eq!(*date, Some(expected_date), "{:?}", mode),
+            Section::Verbatim { .. } => unreachable!("the written headline is parsed as release"),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn unreleased_sections_can_have_a_custom_label() -> Result {
+    use changelog::write::{Components, Linkables, Options};
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: None,
+        removed_messages: vec![],
+        date: None,
+        name: changelog::Version::Unreleased,
+        segments: Vec::new(),
+        unknown: String::new(),
+    };
+
+    for (label, components, expected_headline) in [
+        (None, Components::all(), "## Unreleased"),
+        (Some("Unreleased"), Components::all(), "## Unreleased"),
+        (
+            Some("Next Release"),
+            Components::all(),
+            "## Next Release <csr-unreleased/>",
+        ),
+        (
+            Some("main (unreleased)"),
+            Components::all(),
+            "## main (unreleased) <csr-unreleased/>",
+        ),
+        (Some("Next Release"), Components::SECTION_TITLE, "## Next Release"),
+    ] {
+        let mut md = String::new();
+        section.write_to(
+            &mut md,
+            &Linkables::AsText,
+            components,
+            &Options {
+                unreleased_label: label.map(ToOwned::to_owned),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(md.lines().next(), Some(expected_headline));
+
+        if components.contains(Components::HTML_TAGS) {
+            let parsed_log = ChangeLog::from_markdown(&md);
+            assert_eq!(parsed_log.sections.len(), 1);
+            match &parsed_log.sections[0] {
+                Section::Release { name, .. } => assert_eq!(*name, changelog::Version::Unreleased),
+                Section::Verbatim { .. } => unreachable!("{:?} is parsed as unreleased section", expected_headline),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn released_sections_can_have_a_custom_label() -> Result {
+    use changelog::write::{Components, Linkables};
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: Some("2023.1 LTS".into()),
+        removed_messages: vec![],
+        date: Some(utc(time::Month::January, 2, 0, 0)),
+        name: changelog::Version::Semantic("1.2.3".parse()?),
+        segments: Vec::new(),
+        unknown: String::new(),
+    };
+
+    let mut md = String::new();
+    section.write_to(
+        &mut md,
+        &Linkables::AsText,
+        Components::SECTION_TITLE,
+        &Default::default(),
+    )?;
+    assert_eq!(md.lines().next(), Some("## 2023.1 LTS (2022-01-02)"));
+
+    let mut md = String::new();
+    section.write_to(&mut md, &Linkables::AsText, Components::all(), &Default::default())?;
+    assert_eq!(
+        md.lines().next(),
+        Some("## 2023.1 LTS <csr-version name=\"v1.2.3\"/> (2022-01-02)")
+    );
+
+    let parsed_log = ChangeLog::from_markdown(&md);
+    assert_eq!(
+        parsed_log.sections,
+        vec![section.clone()],
+        "the label and the version round-trip"
+    );
+
+    let mut generated = section;
+    if let Section::Release { version_label, .. } = &mut generated {
+        *version_label = None;
+    }
+    let merged = parsed_log.merge_generated(ChangeLog {
+        sections: vec![generated],
+    });
+    let mut regenerated = String::new();
+    merged.write_to(
+        &mut regenerated,
+        &Linkables::AsText,
+        Components::all(),
+        &Default::default(),
+    )?;
+    assert_eq!(regenerated, md, "the label is kept when regenerating without override");
+    Ok(())
+}
+
+fn utc(month: time::Month, day: u8, hour: u8, minute: u8) -> time::OffsetDateTime {
+    time::Date::from_calendar_date(2022, month, day)
+        .and_then(|date| date.with_hms(hour, minute, 0))
+        .expect("valid date")
+        .assume_utc()
+}
+
+#[test]
+fn relative_links_point_to_commits_and_issues_below_base() -> Result {
+    use changelog::write::{Components, Linkables};
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: None,
+        removed_messages: vec![],
+        date: None,
+        name: changelog::Version::Semantic("1.0.0".parse()?),
+        segments: vec![section::Segment::Details(section::Data::Generated(
+            section::segment::Details {
+                commits_by_category: {
+                    let mut h = BTreeMap::default();
+                    h.insert(
+                        section::segment::details::Category::Issue("42".into()),
+                        vec![section::segment::details::Message {
+                            title: "Just the title".into(),
+                            id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                            author: None,
+                        }],
+                    );
+                    h
+                },
+            },
+        ))],
+        unknown: String::new(),
+    };
+
+    for (base, expected) in [
+        (
+            Some("../"),
+            " * **[#42](../issues/42)**\n    - Just the title ([`e69de29`](../commit/e69de29bb2d1d6434b8b29ae775ad8c2e48c5391))\n",
+        ),
+        (
+            None,
+            " * **#42**\n    - Just the title (e69de29)\n",
+        ),
+    ] {
+        let mut md = String::new();
+        section.write_to(
+            &mut md,
+            &Linkables::Relative {
+                base: base.map(ToOwned::to_owned),
+            },
+            Components::empty(),
+            &Default::default(),
+        )?;
+        assert!(md.contains(expected), "{md:?} should contain {expected:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn custom_links_are_produced_by_user_provided_functions() -> Result {
+    use std::rc::Rc;
+
+    use changelog::write::{Components, Linkables};
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: None,
+        removed_messages: vec![],
+        date: None,
+        name: changelog::Version::Semantic("1.0.0".parse()?),
+        segments: vec![section::Segment::Details(section::Data::Generated(
+            section::segment::Details {
+                commits_by_category: {
+                    let mut h = BTreeMap::default();
+                    for issue in ["42", "43"] {
+                        h.insert(
+                            section::segment::details::Category::Issue(issue.into()),
+                            vec![section::segment::details::Message {
+                                title: "Just the title".into(),
+                                id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                                author: None,
+                            }],
+                        );
+                    }
+                    h
+                },
+            },
+        ))],
+        unknown: String::new(),
+    };
+
+    let mut md = String::new();
+    section.write_to(
+        &mut md,
+        &Linkables::Custom {
+            commit_url: Rc::new(|id: &gix::oid| Some(format!("https://example.com/c/{}", id.to_hex_with_len(8)))),
+            issue_url: Rc::new(|id: &str| (id == "42").then(|| format!("https://example.com/i/{id}"))),
+        },
+        Components::empty(),
+        &Default::default(),
+    )?;
+    for expected in [
+        " * **[#42](https://example.com/i/42)**\n",
+        " * **#43**\n",
+        "    - Just the title ([`e69de29`](https://example.com/c/e69de29b))\n",
+    ] {
+        assert!(md.contains(expected), "{md:?} should contain {expected:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn consecutive_identical_titles_in_details_can_be_collapsed() -> Result {
+    use changelog::write::{Components, Linkables, Options};
+    let message = |title: &str, id: &str| section::segment::details::Message {
+        title: title.into(),
+        id: hex_to_id(id),
+        author: None,
+    };
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: None,
+        removed_messages: vec![],
+        date: None,
+        name: changelog::Version::Semantic("1.0.0".parse()?),
+        segments: vec![section::Segment::Details(section::Data::Generated(
+            section::segment::Details {
+                commits_by_category: {
+                    let mut h = BTreeMap::default();
+                    h.insert(
+                        section::segment::details::Category::Uncategorized,
+                        vec![
+                            message("fix: bug", "0000000000000000000000000000000000000001"),
+                            message("fix: bug", "0000000000000000000000000000000000000002"),
+                            message("fix: other bug", "0000000000000000000000000000000000000003"),
+                            message("fix: bug", "0000000000000000000000000000000000000004"),
+                        ],
+                    );
+                    h
+                },
+            },
+        ))],
+        unknown: String::new(),
+    };
+
+    for (collapse_identical_titles, expected) in [
+        (
+            false,
+            "    - Fix: bug (0000000)\n    - Fix: bug (0000000)\n    - Fix: other bug (0000000)\n    - Fix: bug (0000000)\n",
+        ),
+        (
+            true,
+            "    - Fix: bug (0000000, 0000000)\n    - Fix: other bug (0000000)\n    - Fix: bug (0000000)\n",
+        ),
+    ] {
+        let mut md = String::new();
+        section.write_to(
+            &mut md,
+            &Linkables::AsText,
+            Components::empty(),
+            &Options {
+                collapse_identical_titles,
+                ..Default::default()
+            },
+        )?;
+        assert!(md.contains(expected), "{md:?} should contain {expected:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn detail_authors_are_written_on_demand() -> Result {
+    use changelog::write::{Components, Linkables, Options};
+    let message = |id: &str, name: &str, email: &str| section::segment::details::Message {
+        title: "fix: bug".into(),
+        id: hex_to_id(id),
+        author: Some(section::segment::details::Author {
+            name: name.into(),
+            email: email.into(),
+        }),
+    };
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: None,
+        removed_messages: vec![],
+        date: None,
+        name: changelog::Version::Semantic("1.0.0".parse()?),
+        segments: vec![section::Segment::Details(section::Data::Generated(
+            section::segment::Details {
+                commits_by_category: {
+                    let mut h = BTreeMap::default();
+                    h.insert(
+                        section::segment::details::Category::Uncategorized,
+                        vec![
+                            message(
+                                "0000000000000000000000000000000000000001",
+                                "Alice",
+                                "42+alice@users.noreply.github.com",
+                            ),
+                            message(
+                                "0000000000000000000000000000000000000002",
+                                "Alice",
+                                "alice@users.noreply.github.com",
+                            ),
+                            message("0000000000000000000000000000000000000003", "Bob", "bob@example.com"),
+                        ],
+                    );
+                    h
+                },
+            },
+        ))],
+        unknown: String::new(),
+    };
+    let options = Options {
+        collapse_identical_titles: true,
+        ..Default::default()
+    };
+
+    let mut md = String::new();
+    section.write_to(&mut md, &Linkables::AsText, Components::empty(), &options)?;
+    assert!(
+        md.contains("    - Fix: bug (0000000, 0000000, 0000000)\n"),
+        "authors are not written by default"
+    );
+
+    for (link_mode, expected) in [
+        (
+            Linkables::AsText,
+            "    - Fix: bug (0000000, 0000000, 0000000) by @alice, Bob\n",
+        ),
+        (
+            Linkables::AsLinks {
+                repository_url: gix::Url::try_from(b"https://github.com/user/repo.git".as_bstr())?.into(),
+            },
+            "by [@alice](https://github.com/alice), Bob\n",
+        ),
+    ] {
+        let mut md = String::new();
+        section.write_to(&mut md, &link_mode, Components::DETAIL_AUTHORS, &options)?;
+        assert!(md.contains(expected), "{md:?} should contain {expected:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn sections_can_be_rendered_one_at_a_time() -> Result {
+    use changelog::write::{Components, Linkables};
+    let log = ChangeLog {
+        sections: vec![
+            Section::Verbatim {
+                text: "# Changelog\n".into(),
+                generated: true,
+            },
+            Section::Release {
+                heading_level: 2,
+                version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
+                removed_messages: vec![],
+                date: None,
+                name: changelog::Version::Unreleased,
+                segments: Vec::new(),
+                unknown: String::new(),
+            },
+            Section::Release {
+                heading_level: 2,
+                version_prefix: Section::DEFAULT_PREFIX.into(),
+                version_label: None,
+                removed_messages: vec![],
+                date: Some(utc(time::Month::January, 2, 0, 0)),
+                name: changelog::Version::Semantic("1.0.0".parse()?),
+                segments: Vec::new(),
+                unknown: String::new(),
+            },
+        ],
+    };
+
+    let mut expected = String::new();
+    log.write_to(
+        &mut expected,
+        &Linkables::AsText,
+        Components::all(),
+        &Default::default(),
+    )?;
+
+    let options = Default::default();
+    let rendered = changelog::write::sections(log.sections.clone(), &Linkables::AsText, Components::all(), &options)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    assert_eq!(rendered.len(), 3, "one string per section");
+    assert_eq!(
+        rendered.concat(),
+        expected,
+        "the result is the same as writing everything at once"
+    );
+    Ok(())
+}
+
+#[test]
+fn check_linkable_reports_items_that_would_be_written_as_text() -> Result {
+    use changelog::write::{Linkables, UnlinkableWarning};
+    let version = changelog::Version::Semantic("1.0.0".parse()?);
+    let commit = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    let log = ChangeLog {
+        sections: vec![Section::Release {
+            heading_level: 2,
+            version_prefix: Section::DEFAULT_PREFIX.into(),
+            version_label: None,
+            removed_messages: vec![],
+            date: None,
+            name: version.clone(),
+            segments: vec![
+                section::Segment::Details(section::Data::Generated(section::segment::Details {
+                    commits_by_category: {
+                        let mut h = BTreeMap::default();
+                        h.insert(
+                            section::segment::details::Category::Issue("42".into()),
+                            vec![section::segment::details::Message {
+                                title: "Just the title".into(),
+                                id: commit,
+                                author: None,
+                            }],
+                        );
+                        h
+                    },
+                })),
+                section::Segment::Statistics(section::Data::Generated(section::segment::CommitStatistics {
+                    count: 1,
+                    duration: None,
+                    time_passed_since_last_release: None,
+                    conventional_count: 0,
+                    unique_issues: vec![section::segment::details::Category::Issue("42".into())],
+                })),
+            ],
+            unknown: String::new(),
+        }],
+    };
+
+    let github = Linkables::AsLinks {
+        repository_url: gix::url::parse("https://github.com/byron/gitoxide".into())?.into(),
+    };
+    assert!(log.check_linkable(&github).is_empty(), "everything can be linked");
+    assert!(log
+        .check_linkable(&Linkables::Relative {
+            base: Some("..".into())
+        })
+        .is_empty());
+
+    let elsewhere = Linkables::AsLinks {
+        repository_url: gix::url::parse("https://example.com/byron/gitoxide".into())?.into(),
+    };
+    assert_eq!(
+        log.check_linkable(&elsewhere),
+        vec![
+            UnlinkableWarning::Issue {
+                version: version.clone(),
+                id: "42".into()
+            },
+            UnlinkableWarning::Commit { version, id: commit }
+        ],
+        "each issue is reported only once"
+    );
+    Ok(())
+}
+
+#[test]
+fn links_follow_the_conventions_of_the_forge() -> Result {
+    use changelog::write::{Components, Linkables};
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: None,
+        removed_messages: vec![],
+        date: None,
+        name: changelog::Version::Semantic("1.0.0".parse()?),
+        segments: vec![section::Segment::Details(section::Data::Generated(
+            section::segment::Details {
+                commits_by_category: {
+                    let mut h = BTreeMap::default();
+                    h.insert(
+                        section::segment::details::Category::Issue("42".into()),
+                        vec![section::segment::details::Message {
+                            title: "Just the title".into(),
+                            id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                            author: None,
+                        }],
+                    );
+                    h
+                },
+            },
+        ))],
+        unknown: String::new(),
+    };
+
+    for (url, expected) in [
+        (
+            "git@gitlab.com:user/repo.git",
+            " * **[#42](https://gitlab.com/user/repo/-/issues/42)**\n    - Just the title ([`e69de29`](https://gitlab.com/user/repo/-/commit/e69de29bb2d1d6434b8b29ae775ad8c2e48c5391))\n",
+        ),
+        (
+            "https://bitbucket.org/user/repo.git",
+            " * **[#42](https://bitbucket.org/user/repo/issues/42)**\n    - Just the title ([`e69de29`](https://bitbucket.org/user/repo/commits/e69de29bb2d1d6434b8b29ae775ad8c2e48c5391))\n",
+        ),
+        (
+            "https://git.example.com/user/repo.git",
+            " * **#42**\n    - Just the title (e69de29)\n",
+        ),
+    ] {
+        let mut md = String::new();
+        section.write_to(
+            &mut md,
+            &Linkables::AsLinks {
+                repository_url: gix::url::parse(url.into())?.into(),
+            },
+            Components::empty(),
+            &Default::default(),
+        )?;
+        assert!(md.contains(expected), "{md:?} should contain {expected:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn issue_links_can_be_annotated_with_their_state() -> Result {
+    use changelog::write::{Components, IssueState, Linkables, Options};
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: None,
+        removed_messages: vec![],
+        date: None,
+        name: changelog::Version::Semantic("1.0.0".parse()?),
+        segments: vec![
+            section::Segment::Details(section::Data::Generated(section::segment::Details {
+                commits_by_category: {
+                    let mut h = BTreeMap::default();
+                    for id in ["1", "2", "3"] {
+                        h.insert(
+                            section::segment::details::Category::Issue(id.into()),
+                            vec![section::segment::details::Message {
+                                title: "Just the title".into(),
+                                id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                                author: None,
+                            }],
+                        );
+                    }
+                    h
+                },
+            })),
+            section::Segment::Statistics(section::Data::Generated(section::segment::CommitStatistics {
+                count: 1,
+                duration: None,
+                time_passed_since_last_release: None,
+                conventional_count: 0,
+                unique_issues: vec![section::segment::details::Category::Issue("1".into())],
+            })),
+        ],
+        unknown: String::new(),
+    };
+    let options = Options {
+        issue_states: [("1".to_string(), IssueState::Closed), ("2".into(), IssueState::Open)]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut md = String::new();
+    section.write_to(
+        &mut md,
+        &Linkables::AsLinks {
+            repository_url: gix::url::parse("https://github.com/byron/gitoxide".into())?.into(),
+        },
+        Components::empty(),
+        &options,
+    )?;
+    for expected in [
+        " * **[#1](https://github.com/byron/gitoxide/issues/1) ✓**\n",
+        " * **[#2](https://github.com/byron/gitoxide/issues/2) ○**\n",
+        " * **[#3](https://github.com/byron/gitoxide/issues/3)**\n",
+        "worked on: [#1](https://github.com/byron/gitoxide/issues/1) ✓\n",
+    ] {
+        assert!(md.contains(expected), "{md:?} should contain {expected:?}");
+    }
+
+    let mut md = String::new();
+    section.write_to(&mut md, &Linkables::AsText, Components::empty(), &options)?;
+    assert!(
+        md.contains(" * **#1**\n"),
+        "issues written as text don't get a marker as the forge will show their state"
+    );
+    Ok(())
+}
+
+#[test]
+fn migration_guide_is_written_if_enabled() -> Result {
+    use changelog::write::{Components, Linkables};
+    let section = Section::Release {
+        heading_level: 2,
+        version_prefix: Section::DEFAULT_PREFIX.into(),
+        version_label: None,
+        removed_messages: vec![],
+        date: None,
+        name: changelog::Version::Semantic("2.0.0".parse()?),
+        segments: vec![section::Segment::MigrationGuide(section::Data::Generated(
+            section::segment::MigrationGuide {
+                entries: vec![section::segment::migration_guide::Entry {
+                    title: "rename `foo()` to `bar()`".into(),
+                    id: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                    description: "Replace all calls to `foo()`\nwith `bar()`.\n\nThere is no other change.".into(),
+                }],
+            },
+        ))],
+        unknown: String::new(),
+    };
+
+    let mut md = String::new();
+    section.write_to(&mut md, &Linkables::AsText, Components::all(), &Default::default())?;
+    assert!(
+        md.contains(
+            "### Migration Guide\n\n<csr-read-only-do-not-edit/>\n\n - **Rename `foo()` to `bar()`** (e69de29)\n   Replace all calls to `foo()`\n   with `bar()`.\n\n   There is no other change.\n"
+        ),
+        "{md:?}"
+    );
+    let parsed_log = ChangeLog::from_markdown(&md);
+    match &parsed_log.sections[0] {
+        Section::Release { segments, .. } => assert_eq!(
+            segments,
+            &[section::Segment::MigrationGuide(section::Data::Parsed)],
+            "the migration guide is read-only and will be regenerated"
+        ),
+        Section::Verbatim { .. } => unreachable!("the written headline is parsed as release"),
+    }
+
+    let mut md = String::new();
+    section.write_to(
+        &mut md,
+        &Linkables::AsText,
+        Components::all().difference(Components::MIGRATION_GUIDE),
+        &Default::default(),
+    )?;
+    assert!(!md.contains("Migration Guide"), "it's possible to turn it off");
+    Ok(())
+}
@@ -0,0 +1,14 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -b main
+
+git commit -q --allow-empty -m "initial"
+git tag v1.0.0
+
+git commit -q --allow-empty -m "feat: add foo (#1)"
+git commit -q --allow-empty -m "fix!: rename bar
+
+BREAKING CHANGE: use \`baz()\` instead of \`bar()\`"
+git commit -q --allow-empty -m "thanks clippy"
@@ -1,6 +1,8 @@
 pub mod explode;
 pub mod index;
 pub mod multi_index;
+pub mod repack;
+pub use repack::repack;
 pub mod verify;
 
 #[cfg(any(feature = "async-client", feature = "blocking-client"))]
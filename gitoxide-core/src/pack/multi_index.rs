@@ -1,4 +1,9 @@
-use std::{io::BufWriter, path::PathBuf, sync::atomic::AtomicBool};
+use std::{
+    collections::HashMap,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use anyhow::bail;
 use gix::Progress;
@@ -7,34 +12,847 @@ use crate::OutputFormat;
 
 pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
 
+/// Verify the multi-index at `multi_index_path` without rewriting it, similar to `git multi-pack-index verify`.
+///
+/// This validates its checksum, fan-out table and object order, and checks that each entry refers to a known pack
+/// and matches the offset recorded in that pack's index. The first inconsistency is returned as error.
 pub fn verify(multi_index_path: PathBuf, progress: impl Progress, should_interrupt: &AtomicBool) -> anyhow::Result<()> {
     gix::odb::pack::multi_index::File::at(multi_index_path)?.verify_integrity_fast(progress, should_interrupt)?;
     Ok(())
 }
 
+/// Verify only the object with `id` in the multi-index at `multi_index_path` by decoding it from its pack and assuring
+/// its hash matches, and print its kind, size and location to `out`.
+pub fn verify_object(
+    multi_index_path: PathBuf,
+    id: gix::ObjectId,
+    mut out: impl std::io::Write,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    use gix::odb::pack;
+    let file = pack::multi_index::File::at(multi_index_path)?;
+    let outcome = file.verify_object(
+        id,
+        &mut pack::multi_index::verify::object::Bundles::default(),
+        &mut pack::cache::Never,
+        should_interrupt,
+    )?;
+    let (pack_id, pack_offset) =
+        file.pack_id_and_pack_offset_at_index(file.lookup(id).expect("verified objects are present"));
+    writeln!(out, "{id} {} {} {pack_id} {pack_offset}", outcome.kind, outcome.size)?;
+    Ok(())
+}
+
+/// Options for use in [`create()`].
+#[derive(Default)]
+pub struct CreateOptions {
+    pub object_hash: gix::hash::Kind,
+    /// Also write a reverse index next to the multi-index.
+    pub reverse_index: bool,
+    /// Record progress in a checkpoint file next to the multi-index so that a subsequent invocation for the same indices
+    /// can continue where an interrupted one left off.
+    pub resumable: bool,
+    /// Fail if there are more index files than this to signal that a repack is due.
+    pub max_packs: Option<usize>,
+    /// Fail if ref-deltas in the packs refer to bases that aren't contained in any of them.
+    pub check_delta_bases: bool,
+    /// Write a new layer of an incremental multi-index into the `multi-pack-index.d` directory next to the multi-index,
+    /// with only the packs that aren't contained in its existing layers yet, instead of writing the multi-index itself.
+    pub incremental: bool,
+    /// Also write a reachability bitmap for all objects reachable from the references of the repository owning the packs,
+    /// which implies writing a reverse index.
+    pub bitmap: bool,
+    /// The file name of the index of the pack whose objects should be preferred over duplicates in other packs.
+    pub preferred_pack: Option<PathBuf>,
+}
+
+/// Write a multi-index for all `index_paths` to `output_path`, along with a reverse index and a bitmap next to it as
+/// configured by `options`, similar to `git multi-pack-index write`.
+///
+/// Reverse indices and bitmaps which belonged to a previous multi-index at `output_path` are removed afterwards.
+/// If the multi-index is written incrementally, `output_path` is only used to determine the directory the packs are in.
 pub fn create(
     index_paths: Vec<PathBuf>,
     output_path: PathBuf,
     progress: impl Progress,
     should_interrupt: &AtomicBool,
-    object_hash: gix::hash::Kind,
+    CreateOptions {
+        object_hash,
+        reverse_index,
+        resumable,
+        max_packs,
+        check_delta_bases,
+        incremental,
+        bitmap,
+        preferred_pack,
+    }: CreateOptions,
 ) -> anyhow::Result<()> {
+    use gix::odb::pack::multi_index::write;
+
+    let pack_dir = pack_dir_of(&output_path)?;
+    let layers_dir = pack_dir.join("multi-pack-index.d");
+    let chain_path = layers_dir.join("multi-pack-index-chain");
+    let mut chain = Vec::new();
+    if incremental {
+        match std::fs::read_to_string(&chain_path) {
+            Ok(content) => {
+                for line in content.lines() {
+                    chain.push(gix::hash::ObjectId::from_hex(line.as_bytes())?);
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    let bitmap = if bitmap {
+        let repo = gix::discover(&pack_dir)?;
+        let mut tips: Vec<_> = repo
+            .references()?
+            .all()?
+            .filter_map(Result::ok)
+            .filter_map(|reference| reference.target().try_id().map(ToOwned::to_owned))
+            .collect();
+        tips.extend(repo.head_id().ok().map(gix::Id::detach));
+        Some(write::Bitmap {
+            tips,
+            options: gix::odb::pack::bitmap::write::Options { hash_cache: true },
+        })
+    } else {
+        None
+    };
+    let options = write::Options {
+        object_hash,
+        reverse_index: reverse_index || bitmap.is_some(),
+        checkpoint: resumable.then(|| write::Checkpoint {
+            path: output_path.with_extension("checkpoint"),
+            interval: CHECKPOINT_INTERVAL,
+        }),
+        deterministic: false,
+        max_packs,
+        check_delta_bases,
+        base_layers: chain
+            .iter()
+            .map(|checksum| layers_dir.join(write::layer_file_name(checksum)))
+            .collect(),
+        preferred_pack,
+        bitmap,
+    };
+
+    if incremental {
+        let mut out = Vec::new();
+        let outcome = gix::odb::pack::multi_index::File::write_from_index_paths(
+            index_paths,
+            &mut out,
+            progress,
+            should_interrupt,
+            options,
+        )?;
+        std::fs::create_dir_all(&layers_dir)?;
+        write_file(
+            &layers_dir.join(write::layer_file_name(&outcome.multi_index_checksum)),
+            &out,
+        )?;
+        chain.push(outcome.multi_index_checksum);
+        let chain: String = chain.iter().map(|checksum| format!("{checksum}\n")).collect();
+        write_file(&chain_path, chain.as_bytes())?;
+        return Ok(());
+    }
+
     let mut out = BufWriter::new(gix::lock::File::acquire_to_update_resource(
-        output_path,
+        &output_path,
         gix::lock::acquire::Fail::Immediately,
         None,
     )?);
-    gix::odb::pack::multi_index::File::write_from_index_paths(
+    let outcome = gix::odb::pack::multi_index::File::write_from_index_paths(
         index_paths,
         &mut out,
         progress,
         should_interrupt,
-        gix::odb::pack::multi_index::write::Options { object_hash },
+        options,
     )?;
+    let checksum = outcome.multi_index_checksum;
+    if let Some(data) = outcome.reverse_index {
+        write_file(&pack_dir.join(write::reverse_index_file_name(&checksum)), &data)?;
+    }
+    if let Some(data) = outcome.bitmap {
+        write_file(&pack_dir.join(write::bitmap_file_name(&checksum)), &data)?;
+    }
     out.into_inner()?.commit()?;
+
+    for entry in std::fs::read_dir(&pack_dir)? {
+        let path = entry?.path();
+        let is_stale = path.file_name().and_then(|name| name.to_str()).map_or(false, |name| {
+            name.starts_with("multi-pack-index-")
+                && (name.ends_with(".rev") || name.ends_with(".bitmap"))
+                && !name.contains(&checksum.to_string())
+        });
+        if is_stale {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove all packs referenced by the multi-index at `multi_index_path` which don't contain any of its objects anymore,
+/// and rewrite the multi-index without them, similar to `git multi-pack-index expire`.
+///
+/// Packs with a `.keep` file are retained, and the multi-index is rewritten first so it never refers to removed packs.
+pub fn expire(multi_index_path: PathBuf, progress: impl Progress, should_interrupt: &AtomicBool) -> anyhow::Result<()> {
+    use gix::odb::pack;
+
+    let multi_index = pack::multi_index::File::at(&multi_index_path)?;
+    let pack_dir = pack_dir_of(&multi_index_path)?;
+    let mut num_objects_by_pack = vec![0_usize; multi_index.num_indices() as usize];
+    for entry in multi_index.iter() {
+        num_objects_by_pack[entry.pack_index as usize] += 1;
+    }
+    let (expired, retained): (Vec<_>, Vec<_>) = multi_index
+        .index_names()
+        .iter()
+        .map(|name| pack_dir.join(name))
+        .zip(num_objects_by_pack)
+        .partition(|(index_path, num_objects)| *num_objects == 0 && !index_path.with_extension("keep").is_file());
+    if expired.is_empty() {
+        return Ok(());
+    }
+    let options = options_of(&multi_index, &pack_dir);
+    drop(multi_index);
+
+    create(
+        retained.into_iter().map(|(index_path, _)| index_path).collect(),
+        multi_index_path,
+        progress,
+        should_interrupt,
+        options,
+    )?;
+    for (index_path, _) in expired {
+        remove_pack_files(&index_path)?;
+    }
+    Ok(())
+}
+
+/// Write the objects of a batch of packs referenced by the multi-index at `multi_index_path` into a new pack and add it to the
+/// multi-index, similar to `git multi-pack-index repack`. The packs of the batch are left in place to be removed by [`expire()`].
+///
+/// Packs are selected from the least recently modified one until the estimated size of their objects that the multi-index refers
+/// to reaches `batch_size` bytes, skipping packs with a `.keep` file and packs which reach it on their own.
+/// Nothing is done if that size isn't reached or if there is only one pack to repack.
+/// If `batch_size` is 0, all packs without a `.keep` file are repacked.
+pub fn repack(
+    multi_index_path: PathBuf,
+    batch_size: u64,
+    mut progress: impl Progress,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    use gix::odb::pack;
+
+    let multi_index = pack::multi_index::File::at(&multi_index_path)?;
+    let object_hash = multi_index.object_hash();
+    let pack_dir = pack_dir_of(&multi_index_path)?;
+    let bundles = multi_index
+        .index_names()
+        .iter()
+        .map(|name| pack::Bundle::at(pack_dir.join(name), object_hash))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut num_objects_by_pack = vec![0_u64; bundles.len()];
+    for entry in multi_index.iter() {
+        num_objects_by_pack[entry.pack_index as usize] += 1;
+    }
+
+    let mut candidates: Vec<_> = bundles
+        .iter()
+        .enumerate()
+        .filter(|(_, bundle)| !bundle.index.path().with_extension("keep").is_file())
+        .map(|(pack_index, bundle)| {
+            let mtime = bundle
+                .pack
+                .path()
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+            (pack_index, mtime)
+        })
+        .collect();
+    candidates.sort_by_key(|(_, mtime)| *mtime);
+    let mut selected = vec![false; bundles.len()];
+    let mut num_selected = 0;
+    let mut total_size = 0;
+    for (pack_index, _) in candidates {
+        if batch_size != 0 {
+            let bundle = &bundles[pack_index];
+            let num_objects = u64::from(bundle.index.num_objects());
+            if num_objects == 0 {
+                continue;
+            }
+            let expected_size = bundle.pack.data_len() as u64 * num_objects_by_pack[pack_index] / num_objects;
+            if expected_size >= batch_size {
+                continue;
+            }
+            total_size += expected_size;
+        }
+        selected[pack_index] = true;
+        num_selected += 1;
+        if batch_size != 0 && total_size >= batch_size {
+            break;
+        }
+    }
+    if total_size < batch_size || num_selected <= 1 {
+        return Ok(());
+    }
+
+    let mut entries: Vec<_> = multi_index
+        .iter()
+        .filter(|entry| selected[entry.pack_index as usize])
+        .collect();
+    entries.sort_by_key(|entry| (entry.pack_index, entry.pack_offset));
+    // The counts of each pack are ordered by offset and start at the position stored alongside, so that ofs-deltas can be
+    // copied if their base is among the preceding counts of the same pack.
+    let mut counts = Vec::with_capacity(entries.len());
+    let mut count_bundles = Vec::with_capacity(entries.len());
+    {
+        let mut offsets = Vec::new();
+        let mut pack_start = 0;
+        for (position, entry) in entries.iter().enumerate() {
+            let bundle = &bundles[entry.pack_index as usize];
+            if position == 0 || entries[position - 1].pack_index != entry.pack_index {
+                offsets = bundle.index.iter().map(|e| e.pack_offset).collect();
+                offsets.sort_unstable();
+                pack_start = position;
+            }
+            let end = match offsets.get(offsets.partition_point(|pack_offset| *pack_offset <= entry.pack_offset)) {
+                Some(next_pack_offset) => *next_pack_offset,
+                None => (bundle.pack.data_len() - object_hash.len_in_bytes()) as u64,
+            };
+            counts.push(pack::data::output::Count::from_data(
+                entry.oid,
+                Some(pack::data::entry::Location {
+                    pack_id: bundle.pack.id,
+                    entry_size: (end - entry.pack_offset) as usize,
+                    pack_offset: entry.pack_offset,
+                }),
+            ));
+            count_bundles.push((bundle, pack_start));
+        }
+    }
+    let outcome = {
+        let mut progress = progress.add_child("write objects");
+        progress.init(Some(counts.len()), gix::progress::count("objects"));
+        let mut buf = Vec::new();
+        let entries = (0..counts.len()).step_by(ENTRIES_PER_CHUNK).map(|chunk_start| {
+            (chunk_start..counts.len().min(chunk_start + ENTRIES_PER_CHUNK))
+                .map(|position| {
+                    if should_interrupt.load(Ordering::Relaxed) {
+                        return Err(Error::Interrupted);
+                    }
+                    progress.inc();
+                    let (bundle, pack_start) = count_bundles[position];
+                    copy_entry(
+                        bundle,
+                        &counts[position],
+                        &counts[pack_start..position],
+                        pack_start,
+                        &mut buf,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
+        });
+        write_pack(entries, counts.len() as u32, &pack_dir, object_hash, should_interrupt)?
+    };
+
+    let mut index_paths: Vec<_> = bundles.iter().map(|bundle| bundle.index.path().to_owned()).collect();
+    index_paths.extend(outcome.index_path);
+    let options = options_of(&multi_index, &pack_dir);
+    drop(bundles);
+    drop(multi_index);
+    create(index_paths, multi_index_path, progress, should_interrupt, options)?;
+    if let Some(keep_path) = outcome.keep_path {
+        std::fs::remove_file(keep_path)?;
+    }
+    Ok(())
+}
+
+/// Split all packs referenced by the multi-index at `multi_index_path` into packs of at most `max_pack_size` bytes each,
+/// and rewrite the multi-index to refer to these instead. The previous packs are removed along with their sidecar files,
+/// like `.rev` and `.keep`, once the new multi-index is in place.
+///
+/// Objects are grouped by kind and delta chain, with bases preceding their deltas, so that existing deltas can be copied as is.
+/// Ref-deltas and deltas whose base ends up in another pack are written undeltified instead.
+/// New packs are written into a temporary directory and are only moved into place once all of them are complete,
+/// so nothing is left behind if the operation is interrupted before.
+pub fn split(
+    multi_index_path: PathBuf,
+    max_pack_size: u64,
+    mut progress: impl Progress,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    use gix::odb::pack;
+
+    let multi_index = pack::multi_index::File::at(&multi_index_path)?;
+    let object_hash = multi_index.object_hash();
+    let pack_dir = pack_dir_of(&multi_index_path)?;
+    let bundles = multi_index
+        .index_names()
+        .iter()
+        .map(|name| pack::Bundle::at(pack_dir.join(name), object_hash))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let nodes = {
+        let mut progress = progress.add_child("classify objects");
+        progress.init(
+            Some(multi_index.num_objects() as usize),
+            gix::progress::count("objects"),
+        );
+        // All objects of each pack by their offset, to find the base of ofs-deltas and where each entry ends.
+        let objects_by_offset: Vec<Vec<_>> = bundles
+            .iter()
+            .map(|bundle| {
+                let mut objects: Vec<_> = bundle.index.iter().map(|e| (e.pack_offset, e.oid)).collect();
+                objects.sort_by_key(|(pack_offset, _)| *pack_offset);
+                objects
+            })
+            .collect();
+        let mut nodes = Vec::with_capacity(multi_index.num_objects() as usize);
+        let mut base_ids = Vec::with_capacity(multi_index.num_objects() as usize);
+        let mut node_by_id = HashMap::with_capacity(multi_index.num_objects() as usize);
+        for entry in multi_index.iter() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                bail!("Interrupted");
+            }
+            let bundle = &bundles[entry.pack_index as usize];
+            let objects = &objects_by_offset[entry.pack_index as usize];
+            let position_of = |pack_offset: u64| {
+                objects
+                    .binary_search_by_key(&pack_offset, |(pack_offset, _)| *pack_offset)
+                    .map_err(|_| anyhow::anyhow!("No object starts at offset {} in its pack", pack_offset))
+            };
+            let pack_entry = bundle.pack.entry(entry.pack_offset);
+            let end = match objects.get(position_of(entry.pack_offset)? + 1) {
+                Some((next_pack_offset, _)) => *next_pack_offset,
+                None => (bundle.pack.data_len() - object_hash.len_in_bytes()) as u64,
+            };
+            base_ids.push(match pack_entry.header {
+                pack::data::entry::Header::OfsDelta { base_distance } => {
+                    Some(objects[position_of(pack_entry.base_pack_offset(base_distance))?].1)
+                }
+                _ => None,
+            });
+            node_by_id.insert(entry.oid, nodes.len());
+            nodes.push(Node {
+                id: entry.oid,
+                pack_index: entry.pack_index,
+                entry: pack_entry,
+                data_end: end,
+                base: None,
+            });
+            progress.inc();
+        }
+        // Ofs-deltas can only be copied if the multi-index chose their base from the same pack.
+        for (node_index, base_id) in base_ids.into_iter().enumerate() {
+            nodes[node_index].base = base_id
+                .and_then(|id| node_by_id.get(&id).copied())
+                .filter(|base| nodes[*base].pack_index == nodes[node_index].pack_index);
+        }
+        nodes
+    };
+
+    let mut order: Vec<_> = {
+        let mut chain_by_node = vec![None::<(usize, u32)>; nodes.len()];
+        let mut chain = Vec::new();
+        for node_index in 0..nodes.len() {
+            let mut cursor = node_index;
+            while chain_by_node[cursor].is_none() {
+                match nodes[cursor].base {
+                    Some(base) => {
+                        chain.push(cursor);
+                        if chain.len() > nodes.len() {
+                            bail!("Object {} is part of a delta cycle", nodes[node_index].id);
+                        }
+                        cursor = base;
+                    }
+                    None => chain_by_node[cursor] = Some((cursor, 0)),
+                }
+            }
+            let (root, mut depth) = chain_by_node[cursor].expect("set above");
+            while let Some(node) = chain.pop() {
+                depth += 1;
+                chain_by_node[node] = Some((root, depth));
+            }
+        }
+        let mut kind_by_root = HashMap::new();
+        let mut order = Vec::with_capacity(nodes.len());
+        for (node_index, chain) in chain_by_node.into_iter().enumerate() {
+            let (root, depth) = chain.expect("all nodes are assigned to a chain");
+            let kind = match kind_by_root.get(&root) {
+                Some(kind) => *kind,
+                None => {
+                    let kind = nodes[root].header(&bundles)?.kind;
+                    kind_by_root.insert(root, kind);
+                    kind
+                }
+            };
+            let (root, node) = (&nodes[root], &nodes[node_index]);
+            order.push((
+                (
+                    kind,
+                    root.pack_index,
+                    root.entry.pack_offset(),
+                    depth,
+                    node.pack_index,
+                    node.entry.pack_offset(),
+                ),
+                node_index,
+            ));
+        }
+        order
+    };
+    order.sort_by_key(|(key, _)| *key);
+
+    let pack_overhead = 12 /* header */ + object_hash.len_in_bytes() as u64;
+    let mut packs = Vec::new();
+    {
+        let mut steps = Vec::new();
+        let mut position_in_pack = HashMap::new();
+        let mut pack_size = pack_overhead;
+        for (_, node_index) in order {
+            let step_and_size = |position_in_pack: &HashMap<usize, usize>| -> anyhow::Result<(Step, u64)> {
+                let node = &nodes[node_index];
+                Ok(match node.base {
+                    None if !node.entry.header.is_delta() => (
+                        Step::Copy {
+                            node: node_index,
+                            base: None,
+                        },
+                        node.copied_size(),
+                    ),
+                    Some(base) if position_in_pack.contains_key(&base) => (
+                        Step::Copy {
+                            node: node_index,
+                            base: position_in_pack.get(&base).copied(),
+                        },
+                        node.copied_size(),
+                    ),
+                    _ => {
+                        // The worst case of zlib, which resorts to storing data that doesn't compress.
+                        let size = node.header(&bundles)?.object_size;
+                        (
+                            Step::Undeltify { node: node_index },
+                            size + 5 * (size / 16383 + 1) + 6 + MAX_ENTRY_HEADER_SIZE,
+                        )
+                    }
+                })
+            };
+            let (mut step, mut size) = step_and_size(&position_in_pack)?;
+            if pack_size + size > max_pack_size && !steps.is_empty() {
+                packs.push(std::mem::take(&mut steps));
+                position_in_pack.clear();
+                pack_size = pack_overhead;
+                (step, size) = step_and_size(&position_in_pack)?;
+            }
+            pack_size += size;
+            position_in_pack.insert(node_index, steps.len());
+            steps.push(step);
+        }
+        if !steps.is_empty() {
+            packs.push(steps);
+        }
+    }
+
+    let staging_dir = tempfile::tempdir_in(&pack_dir)?;
+    let mut staged = Vec::with_capacity(packs.len());
+    {
+        let mut progress = progress.add_child("write packs");
+        progress.init(Some(nodes.len()), gix::progress::count("objects"));
+        let mut buf = Vec::new();
+        for steps in packs {
+            let entries = steps.chunks(ENTRIES_PER_CHUNK).map(|steps| {
+                steps
+                    .iter()
+                    .map(|step| {
+                        if should_interrupt.load(Ordering::Relaxed) {
+                            return Err(Error::Interrupted);
+                        }
+                        progress.inc();
+                        step.to_entry(&nodes, &bundles, &mut buf)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            });
+            let outcome = write_pack(
+                entries,
+                steps.len() as u32,
+                staging_dir.path(),
+                object_hash,
+                should_interrupt,
+            )?;
+            staged.extend(outcome.data_path.zip(outcome.index_path));
+        }
+    }
+
+    let mut index_paths = Vec::with_capacity(staged.len());
+    let mut new_paths = Vec::with_capacity(staged.len() * 2);
+    for (data_path, index_path) in staged {
+        for path in [data_path, index_path] {
+            new_paths.push(pack_dir.join(path.file_name().expect("pack files have names")));
+        }
+        index_paths.push(new_paths[new_paths.len() - 1].clone());
+    }
+    let previous_index_paths: Vec<_> = bundles
+        .iter()
+        .map(|bundle| bundle.index.path().to_owned())
+        .filter(|path| !new_paths.contains(path))
+        .collect();
+    let options = options_of(&multi_index, &pack_dir);
+    drop(bundles);
+    drop(multi_index);
+
+    for path in &new_paths {
+        std::fs::rename(
+            staging_dir
+                .path()
+                .join(path.file_name().expect("pack files have names")),
+            path,
+        )?;
+    }
+    create(index_paths, multi_index_path, progress, should_interrupt, options)?;
+    for index_path in previous_index_paths {
+        remove_pack_files(&index_path)?;
+    }
+    Ok(())
+}
+
+/// The largest size of the header of an entry in a pack, with the size of the object and the distance to its base.
+const MAX_ENTRY_HEADER_SIZE: u64 = 20;
+/// The amount of entries to produce at once when writing a pack.
+const ENTRIES_PER_CHUNK: usize = 1000;
+/// The amount of entries to collect from indices before storing them in a checkpoint when writing a multi-index.
+const CHECKPOINT_INTERVAL: usize = 1_000_000;
+
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error(transparent)]
+    Decode(#[from] gix::odb::pack::data::decode::Error),
+    #[error(transparent)]
+    Entry(#[from] gix::odb::pack::data::output::entry::Error),
+    #[error("Object {id} is not present in its pack")]
+    NotFound { id: gix::ObjectId },
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// An object referenced by the multi-index, along with the entry in its pack.
+struct Node {
+    id: gix::ObjectId,
+    pack_index: u32,
+    entry: gix::odb::pack::data::Entry,
+    /// The offset at which the data of `entry` ends.
+    data_end: u64,
+    /// The node of the base object if `entry` is a delta against it and the object is taken from the same pack.
+    base: Option<usize>,
+}
+
+impl Node {
+    /// Return the count to copy the entry of this node with.
+    fn count(&self, bundles: &[gix::odb::pack::Bundle]) -> gix::odb::pack::data::output::Count {
+        let pack_offset = self.entry.pack_offset();
+        gix::odb::pack::data::output::Count::from_data(
+            self.id,
+            Some(gix::odb::pack::data::entry::Location {
+                pack_id: bundles[self.pack_index as usize].pack.id,
+                entry_size: (self.data_end - pack_offset) as usize,
+                pack_offset,
+            }),
+        )
+    }
+
+    fn copied_size(&self) -> u64 {
+        self.data_end - self.entry.data_offset + MAX_ENTRY_HEADER_SIZE
+    }
+
+    fn header(
+        &self,
+        bundles: &[gix::odb::pack::Bundle],
+    ) -> Result<gix::odb::pack::data::decode::header::Outcome, Error> {
+        use gix::odb::pack::data::decode::header::ResolvedBase;
+        let bundle = &bundles[self.pack_index as usize];
+        Ok(bundle.pack.decode_header(self.entry.clone(), |id| {
+            bundle
+                .index
+                .lookup(id)
+                .map(|index| ResolvedBase::InPack(bundle.pack.entry(bundle.index.pack_offset_at_index(index))))
+        })?)
+    }
+}
+
+/// The way an object is written into a new pack.
+enum Step {
+    /// Copy the entry of `node` as is, and if it's a delta, refer to its `base` by its position in the new pack.
+    Copy { node: usize, base: Option<usize> },
+    /// Decode the object of `node` and write it as base object.
+    Undeltify { node: usize },
+}
+
+impl Step {
+    fn to_entry(
+        &self,
+        nodes: &[Node],
+        bundles: &[gix::odb::pack::Bundle],
+        buf: &mut Vec<u8>,
+    ) -> Result<gix::odb::pack::data::output::Entry, Error> {
+        Ok(match *self {
+            Step::Copy { node, base } => {
+                let node = &nodes[node];
+                let potential_bases: Vec<_> = node
+                    .base
+                    .filter(|_| base.is_some())
+                    .map(|base| nodes[base].count(bundles))
+                    .into_iter()
+                    .collect();
+                copy_entry(
+                    &bundles[node.pack_index as usize],
+                    &node.count(bundles),
+                    &potential_bases,
+                    base.unwrap_or_default(),
+                    buf,
+                )?
+            }
+            Step::Undeltify { node } => undeltify(
+                &bundles[nodes[node].pack_index as usize],
+                &nodes[node].count(bundles),
+                buf,
+            )?,
+        })
+    }
+}
+
+/// Copy the entry of the object of `count` from the pack of `bundle` for writing it into a new pack, referring to the base
+/// of an ofs-delta by its position among `potential_bases` plus `bases_index_offset`.
+/// The object is decoded and written as base object if its entry can't be copied, like if it's a delta whose base isn't
+/// among `potential_bases`.
+fn copy_entry(
+    bundle: &gix::odb::pack::Bundle,
+    count: &gix::odb::pack::data::output::Count,
+    potential_bases: &[gix::odb::pack::data::output::Count],
+    bases_index_offset: usize,
+    buf: &mut Vec<u8>,
+) -> Result<gix::odb::pack::data::output::Entry, Error> {
+    use gix::odb::pack;
+    let location = count
+        .entry_pack_location
+        .as_ref()
+        .expect("copied objects are located in a pack");
+    let entry = pack::find::Entry {
+        data: bundle
+            .pack
+            .entry_slice(location.entry_range(location.pack_offset))
+            .expect("entries are within their pack")
+            .to_vec(),
+        version: bundle.pack.version(),
+    };
+    match pack::data::output::Entry::from_pack_entry(
+        entry,
+        count,
+        potential_bases,
+        bases_index_offset,
+        None::<fn(u32, u64) -> Option<gix::ObjectId>>,
+        pack::data::Version::V2,
+    ) {
+        Some(entry) => Ok(entry?),
+        None => undeltify(bundle, count, buf),
+    }
+}
+
+/// Decode the object of `count` from the pack of `bundle` for writing it as base object into a new pack.
+fn undeltify(
+    bundle: &gix::odb::pack::Bundle,
+    count: &gix::odb::pack::data::output::Count,
+    buf: &mut Vec<u8>,
+) -> Result<gix::odb::pack::data::output::Entry, Error> {
+    use gix::odb::pack;
+    let (data, _location) = bundle
+        .find(count.id, buf, &mut pack::cache::Never)?
+        .ok_or(Error::NotFound { id: count.id })?;
+    Ok(pack::data::output::Entry::from_data(count, &data)?)
+}
+
+/// Return the directory containing the multi-index at `multi_index_path` and the packs it refers to.
+fn pack_dir_of(multi_index_path: &Path) -> anyhow::Result<PathBuf> {
+    let dir = multi_index_path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Multi-index at '{}' must be in a directory", multi_index_path.display()))?;
+    Ok(if dir.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        dir.to_owned()
+    })
+}
+
+/// Return options to rewrite `multi_index` with, which retain the reverse index and bitmap if it has them.
+fn options_of(multi_index: &gix::odb::pack::multi_index::File, pack_dir: &Path) -> CreateOptions {
+    use gix::odb::pack::multi_index::write;
+    let checksum = multi_index.checksum();
+    CreateOptions {
+        object_hash: multi_index.object_hash(),
+        reverse_index: pack_dir.join(write::reverse_index_file_name(&checksum)).is_file(),
+        bitmap: pack_dir.join(write::bitmap_file_name(&checksum)).is_file(),
+        ..Default::default()
+    }
+}
+
+/// Remove the pack of `index_path` along with its index and all of its sidecar files, ignoring those that don't exist.
+fn remove_pack_files(index_path: &Path) -> anyhow::Result<()> {
+    for extension in ["pack", "idx", "rev", "bitmap", "keep", "promisor", "mtimes"] {
+        match std::fs::remove_file(index_path.with_extension(extension)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+    }
     Ok(())
 }
 
+/// Write `data` to `path` atomically.
+fn write_file(path: &Path, data: &[u8]) -> anyhow::Result<()> {
+    use std::io::Write;
+    let mut file = gix::lock::File::acquire_to_update_resource(path, gix::lock::acquire::Fail::Immediately, None)?;
+    file.write_all(data)?;
+    file.commit()?;
+    Ok(())
+}
+
+/// Write the `num_entries` entries produced in chunks by `entries` into a new pack along with its index in `pack_dir`.
+/// The pack is streamed into a temporary file in `pack_dir` first, which is removed once the pack is in place.
+fn write_pack(
+    entries: impl Iterator<Item = Result<Vec<gix::odb::pack::data::output::Entry>, Error>>,
+    num_entries: u32,
+    pack_dir: &Path,
+    object_hash: gix::hash::Kind,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<gix::odb::pack::bundle::write::Outcome> {
+    use gix::odb::pack;
+    let mut pack_file = tempfile::NamedTempFile::new_in(pack_dir)?;
+    let mut pack_writer = pack::data::output::bytes::FromEntriesIter::new(
+        entries,
+        BufWriter::new(pack_file.as_file_mut()),
+        num_entries,
+        pack::data::Version::V2,
+        object_hash,
+    );
+    for res in pack_writer.by_ref() {
+        res?;
+    }
+    pack_writer.into_write().flush()?;
+
+    Ok(pack::Bundle::write_to_directory(
+        std::io::BufReader::new(pack_file.reopen()?),
+        Some(pack_dir),
+        gix::progress::Discard,
+        should_interrupt,
+        None,
+        pack::bundle::write::Options {
+            object_hash,
+            ..Default::default()
+        },
+    )?)
+}
+
 #[cfg(feature = "serde1")]
 mod info {
     use std::path::PathBuf;
@@ -0,0 +1,94 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use anyhow::bail;
+use gix::Progress;
+
+use crate::OutputFormat;
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
+
+pub struct Options {
+    pub format: OutputFormat,
+    /// Put the objects of all packs that aren't kept into the new pack, instead of only loose objects.
+    pub all_into_one: bool,
+    /// The file names of packs to keep, in addition to those with a `.keep` file.
+    pub keep_packs: Vec<PathBuf>,
+    /// Delete packs and loose objects which are redundant after writing the new pack.
+    pub delete_redundant: bool,
+    /// Write a reachability bitmap for the new pack, if all objects reachable from references end up in it.
+    pub write_bitmap_index: bool,
+    /// Write a commit-graph with all commits reachable from references after packing.
+    pub commit_graph: Option<gix::maintenance::commit_graph::Options>,
+}
+
+/// Consolidate the loose objects and possibly all packs of `repo` into a new pack, similar to `git repack`, and print
+/// what was written and deleted to `out`.
+pub fn repack<P>(
+    repo: gix::Repository,
+    progress: P,
+    should_interrupt: &AtomicBool,
+    mut out: impl std::io::Write,
+    Options {
+        format,
+        all_into_one,
+        keep_packs,
+        delete_redundant,
+        write_bitmap_index,
+        commit_graph,
+    }: Options,
+) -> anyhow::Result<()>
+where
+    P: Progress,
+    P::SubProgress: 'static,
+{
+    if format != OutputFormat::Human {
+        bail!("Only human format is supported right now");
+    }
+    let outcome = repo.maintenance(
+        gix::maintenance::Options {
+            all_into_one,
+            keep_packs,
+            delete_redundant,
+            write_bitmap_index,
+            commit_graph,
+        },
+        progress,
+        should_interrupt,
+    )?;
+    match outcome.pack {
+        Some(pack) => writeln!(
+            out,
+            "Wrote {} objects to {}",
+            pack.index.num_objects,
+            pack.data_path
+                .as_ref()
+                .expect("always written to a directory")
+                .display()
+        )?,
+        None => writeln!(out, "Nothing to pack")?,
+    }
+    if let Some(bitmap) = outcome.bitmap {
+        writeln!(out, "Wrote bitmap for {} commits", bitmap.commits.len())?;
+    } else if write_bitmap_index {
+        writeln!(
+            out,
+            "Skipped writing bitmap as not all objects reachable from references are in the new pack"
+        )?;
+    }
+    if let Some(commit_graph) = outcome.commit_graph {
+        writeln!(
+            out,
+            "Wrote commit-graph with {} commits to {}, which now consists of {} file(s)",
+            commit_graph.num_commits,
+            commit_graph.path.display(),
+            commit_graph.num_files
+        )?;
+    }
+    for path in outcome.deleted_packs {
+        writeln!(out, "Deleted {}", path.display())?;
+    }
+    if outcome.deleted_loose_objects > 0 {
+        writeln!(out, "Deleted {} loose objects", outcome.deleted_loose_objects)?;
+    }
+    Ok(())
+}
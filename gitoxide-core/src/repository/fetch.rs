@@ -10,6 +10,8 @@ pub struct Options {
     pub ref_specs: Vec<BString>,
     pub shallow: gix::remote::fetch::Shallow,
     pub handshake_info: bool,
+    /// If `true`, apply the bundles advertised by the remote before fetching.
+    pub bundle_uri: bool,
 }
 
 pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
@@ -32,6 +34,7 @@ pub(crate) mod function {
             remote,
             handshake_info,
             shallow,
+            bundle_uri,
             ref_specs,
         }: Options,
     ) -> anyhow::Result<()>
@@ -53,6 +56,7 @@ pub(crate) mod function {
             .prepare_fetch(Default::default())?
             .with_dry_run(dry_run)
             .with_shallow(shallow)
+            .with_bundle_uri(bundle_uri)
             .receive(&gix::interrupt::IS_INTERRUPTED)?;
 
         if handshake_info {
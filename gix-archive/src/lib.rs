@@ -1,2 +1,95 @@
-#![deny(rust_2018_idioms)]
+//! Write the content of a tree into an archive, like `git archive` does, honoring the `export-ignore` and `export-subst`
+//! attributes of its entries.
+//!
+//! Archives are reproducible as all entries use the same, configurable modification time, and compression is pluggable as
+//! [`Format::Tar`] can be written into any compressing writer, like one for `zstd` or `xz`.
+//! ## Feature Flags
+#![cfg_attr(
+    feature = "document-features",
+    cfg_attr(doc, doc = ::document_features::document_features!())
+)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+#![deny(missing_docs, rust_2018_idioms)]
 #![forbid(unsafe_code)]
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+/// The format of the archive to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// An uncompressed `tar` archive, which requires the `tar` feature.
+    ///
+    /// Pass a compressing writer as output to use compression algorithms that aren't built-in.
+    Tar,
+    /// A `tar` archive compressed with `gzip`, which requires the `tar_gz` feature.
+    TarGz {
+        /// The compression level from 0 (none) to 9 (best), or `None` for the default level.
+        compression_level: Option<u8>,
+    },
+    /// A `zip` archive whose files are compressed with `deflate`, which requires the `zip` feature.
+    ///
+    /// Note that it can only be written into outputs that can seek.
+    Zip {
+        /// The compression level from 0 (none) to 9 (best), or `None` for the default level.
+        compression_level: Option<i32>,
+    },
+}
+
+/// Options for use in [`write_to()`] and [`write_to_seek()`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// The format of the archive.
+    pub format: Format,
+    /// A path to prepend to the path of all entries, like `project-1.0/` to put all of them into a directory.
+    ///
+    /// Note that it must end with a slash to be a directory, which is then written as first entry.
+    pub tree_prefix: Option<BString>,
+    /// The modification time of all entries in seconds since epoch, typically the time of the commit the tree belongs to.
+    ///
+    /// Using the same time for all entries makes archives of the same tree identical.
+    pub modification_time: u32,
+    /// The commit the tree belongs to, which is used to expand `$Format:…$` placeholders in files with the `export-subst`
+    /// attribute and is stored as comment in `zip` archives.
+    ///
+    /// If `None`, placeholders aren't expanded.
+    pub commit_id: Option<ObjectId>,
+    /// Attribute patterns that apply to all entries, typically from `info/attributes` and `core.attributesFile`,
+    /// with lower precedence than the patterns of `.gitattributes` files in the tree.
+    pub attribute_globals: gix_attributes::MatchGroup,
+    /// Whether or not to ignore the case when matching attribute patterns.
+    pub case: gix_glob::pattern::Case,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            format: Format::Tar,
+            tree_prefix: None,
+            modification_time: 0,
+            commit_id: None,
+            attribute_globals: Default::default(),
+            case: Default::default(),
+        }
+    }
+}
+
+/// The outcome of [`write_to()`] and [`write_to_seek()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of entries written into the archive, including directories.
+    pub num_entries: usize,
+    /// The amount of entries that were skipped due to the `export-ignore` attribute.
+    ///
+    /// Children of skipped directories aren't counted.
+    pub num_ignored: usize,
+    /// The amount of files whose content was altered due to the `export-subst` attribute.
+    pub num_substituted: usize,
+}
+
+///
+pub mod write;
+pub use write::function::{write_to, write_to_seek};
+
+#[cfg(any(feature = "tar", feature = "zip"))]
+mod subst;
@@ -0,0 +1,100 @@
+use bstr::{BString, ByteSlice, ByteVec};
+use gix_hash::oid;
+
+const START: &[u8] = b"$Format:";
+
+/// Expand all `$Format:<format>$` placeholders in `input` with information about the commit with `id`, or return `None`
+/// if there is none.
+///
+/// The `<format>` supports a subset of the placeholders of `git log --format`, and unknown placeholders are kept verbatim.
+pub(crate) fn expand(input: &[u8], id: &oid, commit: &gix_object::Commit) -> Option<BString> {
+    let mut out = BString::default();
+    let mut rest = input;
+    let mut expanded = false;
+    while let Some(start) = rest.find(START) {
+        let format_start = start + START.len();
+        let end = match rest[format_start..].find_byte(b'$') {
+            Some(end) => format_start + end,
+            None => break,
+        };
+        out.extend_from_slice(&rest[..start]);
+        format(&rest[format_start..end], id, commit, &mut out);
+        rest = &rest[end + 1..];
+        expanded = true;
+    }
+    expanded.then(|| {
+        out.extend_from_slice(rest);
+        out
+    })
+}
+
+fn format(mut format: &[u8], id: &oid, commit: &gix_object::Commit, out: &mut BString) {
+    while let Some(pos) = format.find_byte(b'%') {
+        out.extend_from_slice(&format[..pos]);
+        format = &format[pos + 1..];
+        let consumed = placeholder(format, id, commit, out);
+        if consumed == 0 {
+            out.push_byte(b'%');
+        }
+        format = &format[consumed..];
+    }
+    out.extend_from_slice(format);
+}
+
+/// Write the expansion of the placeholder at the beginning of `input` to `out` and return the amount of bytes it
+/// consists of, or 0 if it's unknown.
+fn placeholder(input: &[u8], id: &oid, commit: &gix_object::Commit, out: &mut BString) -> usize {
+    const ABBREV: usize = 7;
+    let hex = |id: &oid, out: &mut BString, len: Option<usize>| match len {
+        Some(len) => out.push_str(id.to_hex_with_len(len).to_string()),
+        None => out.push_str(id.to_hex().to_string()),
+    };
+    match input {
+        [b'%', ..] => out.push_byte(b'%'),
+        [b'n', ..] => out.push_byte(b'\n'),
+        [b'H', ..] => hex(id, out, None),
+        [b'h', ..] => hex(id, out, Some(ABBREV)),
+        [b'T', ..] => hex(&commit.tree, out, None),
+        [b't', ..] => hex(&commit.tree, out, Some(ABBREV)),
+        [kind @ (b'P' | b'p'), ..] => {
+            for (idx, parent) in commit.parents.iter().enumerate() {
+                if idx != 0 {
+                    out.push_byte(b' ');
+                }
+                hex(parent, out, (*kind == b'p').then_some(ABBREV));
+            }
+        }
+        [b's', ..] => out.push_str(
+            gix_object::commit::MessageRef::from_bytes(&commit.message)
+                .summary()
+                .as_bytes(),
+        ),
+        [b'b', ..] => {
+            if let Some(body) = gix_object::commit::MessageRef::from_bytes(&commit.message).body {
+                out.push_str(body);
+            }
+        }
+        [b'B', ..] => out.push_str(&commit.message),
+        [who @ (b'a' | b'c'), field, ..] => {
+            let signature = if *who == b'a' {
+                &commit.author
+            } else {
+                &commit.committer
+            };
+            let time = &signature.time;
+            match field {
+                b'n' => out.push_str(&signature.name),
+                b'e' => out.push_str(&signature.email),
+                b'd' => out.push_str(time.format(gix_date::time::format::DEFAULT)),
+                b'D' => out.push_str(time.format(gix_date::time::format::RFC2822)),
+                b'i' => out.push_str(time.format(gix_date::time::format::ISO8601)),
+                b'I' => out.push_str(time.format(gix_date::time::format::ISO8601_STRICT)),
+                b't' => out.push_str(time.format(gix_date::time::format::UNIX)),
+                _ => return 0,
+            }
+            return 2;
+        }
+        _ => return 0,
+    }
+    1
+}
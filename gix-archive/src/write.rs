@@ -0,0 +1,429 @@
+#[cfg(any(feature = "tar", feature = "zip"))]
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+#[cfg(any(feature = "tar", feature = "zip"))]
+use gix_hash::oid;
+use gix_hash::ObjectId;
+#[cfg(any(feature = "tar", feature = "zip"))]
+use gix_object::tree::EntryMode;
+
+use crate::Format;
+#[cfg(any(feature = "tar", feature = "zip"))]
+use crate::{Options, Outcome};
+
+/// The error returned by [`write_to()`][crate::write_to()] and [`write_to_seek()`][crate::write_to_seek()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not find object {id}")]
+    FindObject {
+        id: ObjectId,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+    #[error("Object {id} was expected to be a {expected}, but was a {actual}")]
+    ObjectKind {
+        id: ObjectId,
+        expected: gix_object::Kind,
+        actual: gix_object::Kind,
+    },
+    #[error(transparent)]
+    Decode(#[from] gix_object::decode::Error),
+    #[error("Could not write the archive")]
+    Io(#[from] std::io::Error),
+    #[error("The {format:?} format can't be written as the feature to support it isn't enabled")]
+    FormatUnavailable { format: Format },
+    #[error("Zip archives can only be written into outputs that can seek")]
+    ZipWithoutSeek,
+    #[cfg(feature = "zip")]
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+}
+
+pub(crate) mod function {
+    use std::io::{Seek, Write};
+
+    use gix_hash::oid;
+
+    #[cfg(any(feature = "tar", feature = "zip"))]
+    use super::traverse;
+    use super::Error;
+    use crate::{Format, Options, Outcome};
+
+    /// Write all entries of the tree with id `tree` into `out` in the format configured in `options`, using `find` to
+    /// look up objects.
+    ///
+    /// Entries with the `export-ignore` attribute are skipped, along with all their children if they are directories, while
+    /// `$Format:…$` placeholders in files with the `export-subst` attribute are expanded if [`Options::commit_id`] is set.
+    /// Submodules are written as empty directories.
+    ///
+    /// As `zip` archives can't be streamed, use [`write_to_seek()`] to write them.
+    #[cfg_attr(not(feature = "tar"), allow(unused_variables))]
+    pub fn write_to<Find, E>(tree: &oid, find: Find, out: &mut dyn Write, options: Options) -> Result<Outcome, Error>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::Data<'a>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        match options.format {
+            #[cfg(feature = "tar")]
+            Format::Tar => {
+                let mut ar = tar::Builder::new(out);
+                let outcome = traverse(tree, find, &options, &mut |path, mode, data| {
+                    super::tar_format::append(&mut ar, path, mode, data, options.modification_time)
+                })?;
+                ar.finish()?;
+                Ok(outcome)
+            }
+            #[cfg(feature = "tar_gz")]
+            Format::TarGz { compression_level } => {
+                let compression = compression_level.map_or_else(flate2::Compression::default, |level| {
+                    flate2::Compression::new(level.into())
+                });
+                let mut ar = tar::Builder::new(flate2::write::GzEncoder::new(out, compression));
+                let outcome = traverse(tree, find, &options, &mut |path, mode, data| {
+                    super::tar_format::append(&mut ar, path, mode, data, options.modification_time)
+                })?;
+                ar.into_inner()?.finish()?;
+                Ok(outcome)
+            }
+            Format::Zip { .. } => Err(Error::ZipWithoutSeek),
+            #[allow(unreachable_patterns)]
+            format => Err(Error::FormatUnavailable { format }),
+        }
+    }
+
+    /// Like [`write_to()`], but supports all formats as `out` can seek.
+    pub fn write_to_seek<Find, E>(
+        tree: &oid,
+        find: Find,
+        mut out: impl Write + Seek,
+        options: Options,
+    ) -> Result<Outcome, Error>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::Data<'a>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        match options.format {
+            #[cfg(feature = "zip")]
+            Format::Zip { compression_level } => {
+                let mut ar = zip::ZipWriter::new(out);
+                let file_options = zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .compression_level(compression_level)
+                    .last_modified_time(super::zip_format::date_time(options.modification_time));
+                let outcome = traverse(tree, find, &options, &mut |path, mode, data| {
+                    super::zip_format::append(&mut ar, path, mode, data, file_options)
+                })?;
+                if let Some(id) = options.commit_id {
+                    ar.set_comment(id.to_string());
+                }
+                ar.finish()?;
+                Ok(outcome)
+            }
+            #[cfg(not(feature = "zip"))]
+            Format::Zip { .. } => Err(Error::FormatUnavailable { format: options.format }),
+            _ => write_to(tree, find, &mut out, options),
+        }
+    }
+}
+
+#[cfg(any(feature = "tar", feature = "zip"))]
+/// Called with the path of each entry in the archive, including the tree prefix, along with its mode and data.
+/// Directories, including submodules, have no data.
+type Append<'a> = dyn FnMut(&BStr, EntryMode, &[u8]) -> Result<(), Error> + 'a;
+
+#[cfg(any(feature = "tar", feature = "zip"))]
+fn traverse<Find, E>(tree: &oid, find: Find, options: &Options, append: &mut Append<'_>) -> Result<Outcome, Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::Data<'a>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut state = State {
+        find,
+        buf: Vec::new(),
+        stack: Default::default(),
+        options,
+        commit: None,
+        path: BString::default(),
+        outcome: Outcome {
+            num_entries: 0,
+            num_ignored: 0,
+            num_substituted: 0,
+        },
+    };
+    if let Some(id) = options.commit_id {
+        let data = find_kind(&mut state.find, &mut state.buf, &id, gix_object::Kind::Commit)?;
+        let commit = gix_object::CommitRef::from_bytes(data)?.into();
+        state.commit = Some((id, commit));
+    }
+    if let Some(prefix) = options
+        .tree_prefix
+        .as_ref()
+        .and_then(|prefix| prefix.strip_suffix(b"/"))
+    {
+        append(prefix.as_bstr(), EntryMode::Tree, &[])?;
+        state.outcome.num_entries += 1;
+    }
+    let tree = state.find_tree(tree)?;
+    state.push_attributes(&tree)?;
+    state.tree(&tree, append)?;
+    Ok(state.outcome)
+}
+
+#[cfg(any(feature = "tar", feature = "zip"))]
+struct State<'a, Find> {
+    find: Find,
+    buf: Vec<u8>,
+    /// The attribute patterns of all `.gitattributes` files of the directories leading to `path`.
+    stack: gix_attributes::MatchGroup,
+    options: &'a Options,
+    commit: Option<(ObjectId, gix_object::Commit)>,
+    /// The path of the current entry, relative to the root of the tree.
+    path: BString,
+    outcome: Outcome,
+}
+
+#[cfg(any(feature = "tar", feature = "zip"))]
+impl<'a, Find, E> State<'a, Find>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<gix_object::Data<'b>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    fn tree(&mut self, tree: &gix_object::Tree, append: &mut Append<'_>) -> Result<(), Error> {
+        for entry in &tree.entries {
+            let previous_len = self.path.len();
+            if previous_len != 0 {
+                self.path.push_byte(b'/');
+            }
+            self.path.extend_from_slice(&entry.filename);
+            self.entry(entry, append)?;
+            self.path.truncate(previous_len);
+        }
+        Ok(())
+    }
+
+    fn entry(&mut self, entry: &gix_object::tree::Entry, append: &mut Append<'_>) -> Result<(), Error> {
+        let (export_ignore, export_subst) = self.attributes(entry.mode.is_tree());
+        if export_ignore {
+            self.outcome.num_ignored += 1;
+            return Ok(());
+        }
+        let path = self.archive_path();
+        match entry.mode {
+            EntryMode::Tree => {
+                append(path.as_ref(), entry.mode, &[])?;
+                let tree = self.find_tree(&entry.oid)?;
+                self.push_attributes(&tree)?;
+                let res = self.tree(&tree, append);
+                self.stack.patterns.pop();
+                res?;
+            }
+            EntryMode::Commit => append(path.as_ref(), entry.mode, &[])?,
+            EntryMode::Blob | EntryMode::BlobExecutable | EntryMode::Link => {
+                let data = find_kind(&mut self.find, &mut self.buf, &entry.oid, gix_object::Kind::Blob)?;
+                let expanded = match &self.commit {
+                    Some((id, commit)) if export_subst && entry.mode != EntryMode::Link => {
+                        crate::subst::expand(data, id, commit)
+                    }
+                    _ => None,
+                };
+                match expanded {
+                    Some(data) => {
+                        self.outcome.num_substituted += 1;
+                        append(path.as_ref(), entry.mode, &data)?
+                    }
+                    None => append(path.as_ref(), entry.mode, data)?,
+                }
+            }
+        }
+        self.outcome.num_entries += 1;
+        Ok(())
+    }
+
+    /// Return the path of the current entry within the archive.
+    fn archive_path(&self) -> BString {
+        let mut path = self.options.tree_prefix.clone().unwrap_or_default();
+        path.extend_from_slice(&self.path);
+        path
+    }
+
+    /// Return whether the `export-ignore` and `export-subst` attributes are set for the current entry.
+    fn attributes(&self, is_dir: bool) -> (bool, bool) {
+        let mut matches = Vec::new();
+        for group in [&self.stack, &self.options.attribute_globals] {
+            group.pattern_matching_relative_path_all(
+                self.path.as_bstr(),
+                Some(is_dir),
+                self.options.case,
+                &mut matches,
+            );
+        }
+        let (mut export_ignore, mut export_subst) = (None, None);
+        let assignments = matches
+            .iter()
+            .filter_map(|m| match m.value {
+                gix_attributes::Value::Assignments(assignments) => Some(assignments),
+                gix_attributes::Value::MacroAttributes(_) => None,
+            })
+            .flat_map(|assignments| assignments.iter().rev());
+        for assignment in assignments {
+            let state = match assignment.name.as_str() {
+                "export-ignore" => &mut export_ignore,
+                "export-subst" => &mut export_subst,
+                _ => continue,
+            };
+            if state.is_none() {
+                *state = Some(assignment.state == gix_attributes::State::Set);
+            }
+        }
+        (export_ignore.unwrap_or(false), export_subst.unwrap_or(false))
+    }
+
+    /// Push the patterns of the `.gitattributes` file in `tree` onto the attribute stack, or an empty list if there is none,
+    /// so there is one list per directory.
+    fn push_attributes(&mut self, tree: &gix_object::Tree) -> Result<(), Error> {
+        let attributes = tree
+            .entries
+            .iter()
+            .find(|e| e.filename == ".gitattributes" && matches!(e.mode, EntryMode::Blob | EntryMode::BlobExecutable));
+        match attributes {
+            Some(entry) => {
+                let mut source = self.path.clone();
+                if !source.is_empty() {
+                    source.push_byte(b'/');
+                }
+                source.extend_from_slice(&entry.filename);
+                let source = gix_path::from_bstring(source);
+                let data = find_kind(&mut self.find, &mut self.buf, &entry.oid, gix_object::Kind::Blob)?;
+                let list = gix_attributes::PatternList::from_bytes(data, source, Some(std::path::Path::new("")));
+                self.stack.patterns.push(list);
+            }
+            None => self.stack.patterns.push(gix_attributes::PatternList {
+                patterns: Vec::new(),
+                source: None,
+                base: None,
+            }),
+        }
+        Ok(())
+    }
+
+    fn find_tree(&mut self, id: &oid) -> Result<gix_object::Tree, Error> {
+        let data = find_kind(&mut self.find, &mut self.buf, id, gix_object::Kind::Tree)?;
+        Ok(gix_object::TreeRef::from_bytes(data)?.into())
+    }
+}
+
+#[cfg(any(feature = "tar", feature = "zip"))]
+fn find_kind<'a, Find, E>(
+    find: &mut Find,
+    buf: &'a mut Vec<u8>,
+    id: &oid,
+    expected: gix_object::Kind,
+) -> Result<&'a [u8], Error>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<gix_object::Data<'b>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let obj = find(id, buf).map_err(|err| Error::FindObject {
+        id: id.to_owned(),
+        source: err.into(),
+    })?;
+    if obj.kind != expected {
+        return Err(Error::ObjectKind {
+            id: id.to_owned(),
+            expected,
+            actual: obj.kind,
+        });
+    }
+    Ok(obj.data)
+}
+
+#[cfg(feature = "tar")]
+mod tar_format {
+    use std::io::Write;
+
+    use bstr::{BStr, ByteSlice};
+    use gix_object::tree::EntryMode;
+
+    /// Append an entry to `ar` with the permissions `git` would use with the default `tar.umask` of `0002`.
+    pub fn append<W: Write>(
+        ar: &mut tar::Builder<W>,
+        path: &BStr,
+        mode: EntryMode,
+        data: &[u8],
+        modification_time: u32,
+    ) -> Result<(), super::Error> {
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(modification_time.into());
+        let path = gix_path::from_bstr(path);
+        match mode {
+            EntryMode::Tree | EntryMode::Commit => {
+                header.set_entry_type(tar::EntryType::Directory);
+                header.set_mode(0o775);
+                header.set_size(0);
+                ar.append_data(&mut header, path, std::io::empty())?;
+            }
+            EntryMode::Link => {
+                header.set_entry_type(tar::EntryType::Symlink);
+                header.set_mode(0o777);
+                header.set_size(0);
+                ar.append_link(&mut header, path, gix_path::from_bstr(data.as_bstr()))?;
+            }
+            EntryMode::Blob | EntryMode::BlobExecutable => {
+                header.set_entry_type(tar::EntryType::Regular);
+                header.set_mode(if mode == EntryMode::Blob { 0o664 } else { 0o775 });
+                header.set_size(data.len() as u64);
+                ar.append_data(&mut header, path, data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "zip")]
+mod zip_format {
+    use std::io::{Seek, Write};
+
+    use bstr::{BStr, ByteSlice};
+    use gix_object::tree::EntryMode;
+
+    /// Append an entry to `ar` with the permissions `git` would use.
+    pub fn append<W: Write + Seek>(
+        ar: &mut zip::ZipWriter<W>,
+        path: &BStr,
+        mode: EntryMode,
+        data: &[u8],
+        options: zip::write::FileOptions,
+    ) -> Result<(), super::Error> {
+        let path = path.to_str_lossy().into_owned();
+        match mode {
+            EntryMode::Tree | EntryMode::Commit => ar.add_directory(path, options.unix_permissions(0o775))?,
+            EntryMode::Link => ar.add_symlink(path, data.to_str_lossy(), options.unix_permissions(0o777))?,
+            EntryMode::Blob | EntryMode::BlobExecutable => {
+                ar.start_file(
+                    path,
+                    options.unix_permissions(if mode == EntryMode::Blob { 0o664 } else { 0o775 }),
+                )?;
+                ar.write_all(data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert `seconds` since epoch into the time format of `zip` archives, which can only represent
+    /// times between 1980 and 2107 and falls back to the earliest possible time.
+    pub fn date_time(seconds: u32) -> zip::DateTime {
+        time::OffsetDateTime::from_unix_timestamp(seconds.into())
+            .ok()
+            .and_then(|t| {
+                zip::DateTime::from_date_and_time(
+                    t.year().try_into().ok()?,
+                    t.month().into(),
+                    t.day(),
+                    t.hour(),
+                    t.minute(),
+                    t.second(),
+                )
+                .ok()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,251 @@
+#![cfg(any(feature = "tar", feature = "zip"))]
+
+use gix_hash::ObjectId;
+use gix_odb::FindExt;
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const COMMIT_TIME: u32 = 946771200;
+
+struct Fixture {
+    odb: gix_odb::Handle,
+    commit: ObjectId,
+    tree: ObjectId,
+}
+
+fn fixture() -> Result<Fixture> {
+    let dir = gix_testtools::scripted_fixture_read_only("make_archive_repo.sh")?;
+    let odb = gix_odb::at(dir.join(".git").join("objects"))?;
+    let commit = ObjectId::from_hex(std::fs::read_to_string(dir.join("head.id"))?.trim().as_bytes())?;
+    let tree = odb.find_commit(commit, &mut Vec::new())?.tree();
+    Ok(Fixture { odb, commit, tree })
+}
+
+fn options(fixture: &Fixture, format: gix_archive::Format) -> gix_archive::Options {
+    gix_archive::Options {
+        format,
+        tree_prefix: Some("prefix/".into()),
+        modification_time: COMMIT_TIME,
+        commit_id: Some(fixture.commit),
+        ..Default::default()
+    }
+}
+
+fn expected_subst(fixture: &Fixture) -> String {
+    format!("commit {} by author <author@example.com>\n", fixture.commit)
+}
+
+const EXPECTED_OUTCOME: gix_archive::Outcome = gix_archive::Outcome {
+    num_entries: 11,
+    num_ignored: 2,
+    num_substituted: 1,
+};
+
+#[cfg(feature = "tar")]
+mod tar {
+    use std::io::Read;
+
+    use gix_odb::FindExt;
+
+    use crate::{expected_subst, fixture, options, Fixture, COMMIT_TIME, EXPECTED_OUTCOME};
+
+    fn write(fixture: &Fixture, format: gix_archive::Format) -> crate::Result<(gix_archive::Outcome, Vec<u8>)> {
+        let mut buf = Vec::new();
+        let outcome = gix_archive::write_to(
+            &fixture.tree,
+            |id, buf| fixture.odb.find(id, buf),
+            &mut buf,
+            options(fixture, format),
+        )?;
+        Ok((outcome, buf))
+    }
+
+    #[test]
+    fn entries_honor_export_attributes() -> crate::Result {
+        let fixture = fixture()?;
+        let (outcome, buf) = write(&fixture, gix_archive::Format::Tar)?;
+        assert_eq!(outcome, EXPECTED_OUTCOME);
+
+        let mut archive = ::tar::Archive::new(buf.as_slice());
+        let mut entries = Vec::new();
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let header = entry.header();
+            assert_eq!(header.mtime()?, COMMIT_TIME as u64, "all entries have the same time");
+            let (kind, mode) = (header.entry_type(), header.mode()?);
+            let path = entry.path()?.to_string_lossy().into_owned();
+            let content = match kind {
+                ::tar::EntryType::Symlink => entry.link_name()?.expect("present").to_string_lossy().into_owned(),
+                _ => {
+                    let mut content = String::new();
+                    entry.read_to_string(&mut content)?;
+                    content
+                }
+            };
+            entries.push((path, kind, mode, content));
+        }
+
+        let dir = |path: &str| (path.to_owned(), ::tar::EntryType::Directory, 0o775, String::new());
+        let file = |path: &str, mode: u32, content: &str| {
+            (path.to_owned(), ::tar::EntryType::Regular, mode, content.to_owned())
+        };
+        assert_eq!(
+            entries,
+            vec![
+                dir("prefix"),
+                file(
+                    "prefix/.gitattributes",
+                    0o664,
+                    "/ignored-dir export-ignore\nsubst export-subst\n"
+                ),
+                file("prefix/a", 0o664, "a\n"),
+                dir("prefix/dir"),
+                file("prefix/dir/.gitattributes", 0o664, "secret export-ignore\n"),
+                file("prefix/dir/b", 0o664, "b\n"),
+                dir("prefix/dir/sub"),
+                file("prefix/dir/sub/c", 0o664, "c\n"),
+                file("prefix/exe", 0o775, "#!/bin/sh\n"),
+                ("prefix/link".into(), ::tar::EntryType::Symlink, 0o777, "a".into()),
+                file("prefix/subst", 0o664, &expected_subst(&fixture)),
+            ],
+            "ignored entries are skipped, and placeholders are expanded"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn placeholders_are_kept_without_commit() -> crate::Result {
+        let fixture = fixture()?;
+        let mut buf = Vec::new();
+        let outcome = gix_archive::write_to(
+            &fixture.tree,
+            |id, buf| fixture.odb.find(id, buf),
+            &mut buf,
+            gix_archive::Options {
+                commit_id: None,
+                ..options(&fixture, gix_archive::Format::Tar)
+            },
+        )?;
+        assert_eq!(outcome.num_substituted, 0);
+
+        let mut archive = ::tar::Archive::new(buf.as_slice());
+        let mut subst = archive
+            .entries()?
+            .filter_map(Result::ok)
+            .find(|e| e.path().map_or(false, |p| p.ends_with("subst")))
+            .expect("present");
+        let mut content = String::new();
+        subst.read_to_string(&mut content)?;
+        assert_eq!(content, "commit $Format:%H$ by $Format:%an <%ae>$\n");
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "tar_gz")]
+    fn tar_gz_is_compressed_tar_and_reproducible() -> crate::Result {
+        let fixture = fixture()?;
+        let (_, tar) = write(&fixture, gix_archive::Format::Tar)?;
+        let (outcome, tar_gz) = write(
+            &fixture,
+            gix_archive::Format::TarGz {
+                compression_level: Some(9),
+            },
+        )?;
+        assert_eq!(outcome, EXPECTED_OUTCOME);
+        assert_eq!(
+            write(
+                &fixture,
+                gix_archive::Format::TarGz {
+                    compression_level: Some(9)
+                }
+            )?
+            .1,
+            tar_gz,
+            "archives of the same tree are identical"
+        );
+
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(tar_gz.as_slice()).read_to_end(&mut decompressed)?;
+        assert_eq!(decompressed, tar);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "zip")]
+    fn zip_needs_seek() -> crate::Result {
+        let fixture = fixture()?;
+        let err = write(
+            &fixture,
+            gix_archive::Format::Zip {
+                compression_level: None,
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<gix_archive::write::Error>(),
+            Some(gix_archive::write::Error::ZipWithoutSeek)
+        ));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "zip")]
+mod zip {
+    use std::io::{Cursor, Read};
+
+    use gix_odb::FindExt;
+
+    use crate::{expected_subst, fixture, options, EXPECTED_OUTCOME};
+
+    #[test]
+    fn entries_honor_export_attributes() -> crate::Result {
+        let fixture = fixture()?;
+        let mut buf = Cursor::new(Vec::new());
+        let outcome = gix_archive::write_to_seek(
+            &fixture.tree,
+            |id, buf| fixture.odb.find(id, buf),
+            &mut buf,
+            options(
+                &fixture,
+                gix_archive::Format::Zip {
+                    compression_level: None,
+                },
+            ),
+        )?;
+        assert_eq!(outcome, EXPECTED_OUTCOME);
+
+        let mut archive = ::zip::ZipArchive::new(buf)?;
+        assert_eq!(
+            archive.comment(),
+            fixture.commit.to_string().as_bytes(),
+            "the commit is stored as comment"
+        );
+        let mut names: Vec<_> = archive.file_names().map(ToOwned::to_owned).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "prefix/",
+                "prefix/.gitattributes",
+                "prefix/a",
+                "prefix/dir/",
+                "prefix/dir/.gitattributes",
+                "prefix/dir/b",
+                "prefix/dir/sub/",
+                "prefix/dir/sub/c",
+                "prefix/exe",
+                "prefix/link",
+                "prefix/subst",
+            ]
+        );
+
+        let mut content = String::new();
+        archive.by_name("prefix/subst")?.read_to_string(&mut content)?;
+        assert_eq!(content, expected_subst(&fixture));
+
+        let exe = archive.by_name("prefix/exe")?;
+        assert_eq!(exe.unix_mode().map(|mode| mode & 0o777), Some(0o775));
+        assert_eq!(exe.last_modified().year(), 2000);
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+mkdir -p dir/sub ignored-dir
+echo a > a
+printf '#!/bin/sh\n' > exe && chmod +x exe
+ln -s a link
+echo b > dir/b
+echo c > dir/sub/c
+echo secret > dir/secret
+echo i > ignored-dir/file
+echo 'commit $Format:%H$ by $Format:%an <%ae>$' > subst
+
+cat <<EOF >.gitattributes
+/ignored-dir export-ignore
+subst export-subst
+EOF
+echo "secret export-ignore" > dir/.gitattributes
+
+git add -A
+git commit -q -m "init"
+git rev-parse @ > head.id
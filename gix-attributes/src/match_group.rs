@@ -50,10 +50,12 @@ impl Pattern for Ignore {
     }
 }
 
-/// A value of an attribute pattern, which is either a macro definition or
+/// A value of an attribute pattern, which is either a macro definition or a set of assignments.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 pub enum Value {
+    /// The assignments a macro expands to.
     MacroAttributes(Vec<Assignment>),
+    /// The assignments associated with a pattern.
     Assignments(Vec<Assignment>),
 }
 
@@ -129,6 +131,145 @@ where
             .rev()
             .find_map(|pl| pl.pattern_matching_relative_path(relative_path, basename_pos, is_dir, case))
     }
+
+    /// Add the given file at `source` if it exists, otherwise do nothing. If a `root` is provided, it's not considered a global file anymore.
+    /// Returns true if the file was added, or false if it didn't exist.
+    pub fn add_patterns_file(
+        &mut self,
+        source: impl Into<PathBuf>,
+        follow_symlinks: bool,
+        root: Option<&Path>,
+        buf: &mut Vec<u8>,
+    ) -> std::io::Result<bool> {
+        let previous_len = self.patterns.len();
+        self.patterns
+            .extend(PatternList::<T>::from_file(source.into(), root, follow_symlinks, buf)?);
+        Ok(self.patterns.len() != previous_len)
+    }
+
+    /// Add patterns as parsed from `bytes`, providing their `source` path and possibly their `root` path, the path they
+    /// are relative to. This also means that `source` is contained within `root` if `root` is provided.
+    pub fn add_patterns_buffer(&mut self, bytes: &[u8], source: impl Into<PathBuf>, root: Option<&Path>) {
+        self.patterns
+            .push(PatternList::<T>::from_bytes(bytes, source.into(), root));
+    }
+}
+
+impl MatchGroup<Attributes> {
+    /// Given `git_dir`, a `.git` repository, load attribute patterns from `attributes_file` if it is provided, typically
+    /// the value of `core.attributesFile`, and from the file named `info_attributes_file_name` within `info/` if provided.
+    ///
+    /// Use `Some(Path::new("attributes"))` to read `info/attributes` just like `git` does, or `None` to ignore the `info` directory.
+    /// Note that it's not considered an error if any of the provided files do not exist.
+    pub fn attributes_from_git_dir(
+        git_dir: impl AsRef<Path>,
+        attributes_file: Option<PathBuf>,
+        info_attributes_file_name: Option<&Path>,
+        buf: &mut Vec<u8>,
+    ) -> std::io::Result<Self> {
+        let mut group = Self::default();
+
+        let follow_symlinks = true;
+        // order matters! Less important ones first.
+        group.patterns.extend(
+            attributes_file
+                .map(|file| PatternList::<Attributes>::from_file(file, None, follow_symlinks, buf))
+                .transpose()?
+                .flatten(),
+        );
+        group.patterns.extend(
+            info_attributes_file_name
+                .map(|name| {
+                    PatternList::<Attributes>::from_file(
+                        git_dir.as_ref().join("info").join(name),
+                        None,
+                        follow_symlinks,
+                        buf,
+                    )
+                })
+                .transpose()?
+                .flatten(),
+        );
+        Ok(group)
+    }
+
+    /// Match `relative_path`, a path relative to the repository containing all patterns, and append all matches to `out`,
+    /// from the highest priority to the lowest, to allow assignments to be filled in the order they should be applied.
+    /// `case` specifies whether cases should be folded during matching or not, and is typically derived from `core.ignoreCase`.
+    ///
+    /// Returns true if at least one pattern matched.
+    pub fn pattern_matching_relative_path_all<'a, 'b>(
+        &'b self,
+        relative_path: impl Into<&'a BStr>,
+        is_dir: Option<bool>,
+        case: gix_glob::pattern::Case,
+        out: &mut Vec<Match<'b, Value>>,
+    ) -> bool {
+        let relative_path = relative_path.into();
+        let basename_pos = relative_path.rfind(b"/").map(|p| p + 1);
+        let previous_len = out.len();
+        for pl in self.patterns.iter().rev() {
+            pl.patterns_matching_relative_path(relative_path, basename_pos, is_dir, case, out);
+        }
+        out.len() != previous_len
+    }
+
+    /// Return the attribute assignments that the macro named `macro_name` expands to, with all macros it sets being
+    /// expanded recursively, or `None` if no such macro is defined.
+    ///
+    /// Like `git`, the last definition of a macro wins, and the built-in `binary` macro is used if `binary` isn't
+    /// defined otherwise. Within a macro, later assignments take precedence over earlier ones and the assignments of
+    /// nested macros. Each attribute is only assigned once, which also protects against macros that expand to themselves.
+    /// Macros which are set are only represented by the attributes they expand to, and aren't part of the output themselves.
+    pub fn expand_macro(&self, macro_name: &str) -> Option<Vec<Assignment>> {
+        let assignments = self.macro_assignments(macro_name)?;
+        let mut seen = vec![macro_name.to_owned()];
+        let mut out = Vec::new();
+        self.expand_macro_inner(assignments.as_ref(), &mut seen, &mut out);
+        Some(out)
+    }
+
+    fn macro_assignments(&self, macro_name: &str) -> Option<std::borrow::Cow<'_, [Assignment]>> {
+        self.patterns
+            .iter()
+            .rev()
+            .flat_map(|pl| pl.patterns.iter().rev())
+            .find_map(|pm| match &pm.value {
+                Value::MacroAttributes(assignments) if pm.pattern.text == macro_name => {
+                    Some(std::borrow::Cow::Borrowed(assignments.as_slice()))
+                }
+                _ => None,
+            })
+            .or_else(|| {
+                (macro_name == "binary").then(|| {
+                    ["diff", "merge", "text"]
+                        .iter()
+                        .map(|name| Assignment {
+                            name: crate::Name((*name).into()),
+                            state: crate::State::Unset,
+                        })
+                        .collect::<Vec<_>>()
+                        .into()
+                })
+            })
+    }
+
+    fn expand_macro_inner(&self, assignments: &[Assignment], seen: &mut Vec<String>, out: &mut Vec<Assignment>) {
+        for assignment in assignments.iter().rev() {
+            let name = assignment.name.as_str();
+            if seen.iter().any(|seen| seen == name) {
+                continue;
+            }
+            seen.push(name.to_owned());
+            match self
+                .macro_assignments(name)
+                .filter(|_| assignment.state == crate::State::Set)
+            {
+                Some(nested) => self.expand_macro_inner(nested.as_ref(), seen, out),
+                None => out.push(assignment.clone()),
+            }
+        }
+    }
 }
 
 impl MatchGroup<Ignore> {
@@ -165,32 +306,6 @@ impl MatchGroup<Ignore> {
             patterns: vec![PatternList::<Ignore>::from_overrides(patterns)],
         }
     }
-
-    /// Add the given file at `source` if it exists, otherwise do nothing. If a `root` is provided, it's not considered a global file anymore.
-    /// Returns true if the file was added, or false if it didn't exist.
-    pub fn add_patterns_file(
-        &mut self,
-        source: impl Into<PathBuf>,
-        follow_symlinks: bool,
-        root: Option<&Path>,
-        buf: &mut Vec<u8>,
-    ) -> std::io::Result<bool> {
-        let previous_len = self.patterns.len();
-        self.patterns.extend(PatternList::<Ignore>::from_file(
-            source.into(),
-            root,
-            follow_symlinks,
-            buf,
-        )?);
-        Ok(self.patterns.len() != previous_len)
-    }
-
-    /// Add patterns as parsed from `bytes`, providing their `source` path and possibly their `root` path, the path they
-    /// are relative to. This also means that `source` is contained within `root` if `root` is provided.
-    pub fn add_patterns_buffer(&mut self, bytes: &[u8], source: impl Into<PathBuf>, root: Option<&Path>) {
-        self.patterns
-            .push(PatternList::<Ignore>::from_bytes(bytes, source.into(), root));
-    }
 }
 
 fn read_in_full_ignore_missing(path: &Path, follow_symlinks: bool, buf: &mut Vec<u8>) -> std::io::Result<bool> {
@@ -205,7 +320,9 @@ fn read_in_full_ignore_missing(path: &Path, follow_symlinks: bool, buf: &mut Vec
             file.read_to_end(buf)?;
             true
         }
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => false,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound ||
+            // TODO: use the enum variant NotADirectory for this once stabilized
+            err.raw_os_error() == Some(20) /* Not a directory */ => false,
         Err(err) => return Err(err),
     })
 }
@@ -330,6 +447,49 @@ where
     }
 }
 
+impl PatternList<Attributes> {
+    /// Append all matches of patterns matching `relative_path` to `out`, from the last pattern to the first, providing a
+    /// pre-computed `basename_pos` which is the starting position of the basename of `relative_path`.
+    /// `is_dir` is true if `relative_path` is a directory.
+    /// `case` specifies whether cases should be folded during matching or not.
+    pub fn patterns_matching_relative_path<'a>(
+        &'a self,
+        relative_path: &BStr,
+        basename_pos: Option<usize>,
+        is_dir: Option<bool>,
+        case: gix_glob::pattern::Case,
+        out: &mut Vec<Match<'a, Value>>,
+    ) {
+        let (relative_path, basename_start_pos) =
+            match self.strip_base_handle_recompute_basename_pos(relative_path, basename_pos) {
+                Some(t) => t,
+                None => return,
+            };
+        out.extend(
+            self.patterns
+                .iter()
+                .rev()
+                .filter(|pm| Attributes::may_use_glob_pattern(&pm.pattern))
+                .filter_map(
+                    |PatternMapping {
+                         pattern,
+                         value,
+                         sequence_number,
+                     }| {
+                        pattern
+                            .matches_repo_relative_path(relative_path, basename_start_pos, is_dir, case)
+                            .then_some(Match {
+                                pattern,
+                                value,
+                                source: self.source.as_deref(),
+                                sequence_number: *sequence_number,
+                            })
+                    },
+                ),
+        );
+    }
+}
+
 impl PatternList<Ignore> {
     /// Parse a list of patterns, using slashes as path separators
     pub fn from_overrides(patterns: impl IntoIterator<Item = impl Into<OsString>>) -> Self {
@@ -116,3 +116,140 @@ mod ignore {
         }
     }
 }
+
+mod attributes {
+    use gix_attributes::{Attributes, MatchGroup, PatternList};
+    use gix_glob::pattern::Case;
+    use gix_testtools::{fixture_bytes, fixture_path};
+
+    fn group() -> MatchGroup<Attributes> {
+        MatchGroup {
+            patterns: vec![PatternList::<Attributes>::from_bytes(
+                &fixture_bytes("attributes/mixed-case.txt"),
+                "mixed-case.txt",
+                None,
+            )],
+        }
+    }
+
+    fn matching_lines(group: &MatchGroup<Attributes>, path: &str, case: Case) -> Vec<usize> {
+        let mut out = Vec::new();
+        let has_match = group.pattern_matching_relative_path_all(path, None, case, &mut out);
+        assert_eq!(has_match, !out.is_empty());
+        out.into_iter().map(|m| m.sequence_number).collect()
+    }
+
+    #[test]
+    fn case_sensitive_matching_requires_exact_case() {
+        let group = group();
+        assert_eq!(matching_lines(&group, "a.txt", Case::Sensitive), [4]);
+        assert_eq!(matching_lines(&group, "a.TXT", Case::Sensitive), [1]);
+        assert_eq!(
+            matching_lines(&group, "dir/file.md", Case::Sensitive),
+            Vec::<usize>::new()
+        );
+        assert_eq!(
+            matching_lines(&group, "sub/readme", Case::Sensitive),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn case_folding_matches_mixed_case_paths() {
+        let group = group();
+        assert_eq!(
+            matching_lines(&group, "a.txt", Case::Fold),
+            [4, 1],
+            "all matches are returned, the last pattern first"
+        );
+        assert_eq!(matching_lines(&group, "sub/A.Txt", Case::Fold), [4, 1]);
+        assert_eq!(matching_lines(&group, "dir/file.MD", Case::Fold), [2]);
+        assert_eq!(matching_lines(&group, "sub/readme", Case::Fold), [3]);
+    }
+
+    #[test]
+    fn case_folding_does_not_alter_the_match_itself() {
+        let group = group();
+        let (mut sensitive, mut folded) = (Vec::new(), Vec::new());
+        group.pattern_matching_relative_path_all("Dir/file.md", None, Case::Sensitive, &mut sensitive);
+        group.pattern_matching_relative_path_all("dir/FILE.md", None, Case::Fold, &mut folded);
+        assert_eq!(sensitive.len(), 1);
+        assert_eq!(sensitive, folded, "pattern, value, source and line are the same");
+    }
+
+    #[test]
+    fn from_git_dir_with_custom_info_file_name() -> crate::Result {
+        let git_dir = fixture_path("attributes/git-dir");
+        let mut buf = Vec::new();
+
+        let group = MatchGroup::attributes_from_git_dir(&git_dir, None, Some("myattributes".as_ref()), &mut buf)?;
+        assert_eq!(group.patterns.len(), 1);
+        let mut out = Vec::new();
+        assert!(group.pattern_matching_relative_path_all("src/lib.rs", None, Case::Sensitive, &mut out));
+        assert_eq!(out.len(), 1);
+        assert_eq!(
+            out[0].source,
+            Some(git_dir.join("info").join("myattributes").as_path()),
+            "the source reflects the custom file name"
+        );
+
+        let group = MatchGroup::attributes_from_git_dir(&git_dir, None, Some("attributes".as_ref()), &mut buf)?;
+        assert_eq!(group.patterns.len(), 1, "the standard name works just the same");
+
+        let group = MatchGroup::attributes_from_git_dir(
+            &git_dir,
+            Some(fixture_path("attributes/mixed-case.txt")),
+            None,
+            &mut buf,
+        )?;
+        assert_eq!(group.patterns.len(), 1, "the info directory can be ignored entirely");
+
+        let group = MatchGroup::attributes_from_git_dir(&git_dir, None, Some("missing".as_ref()), &mut buf)?;
+        assert!(group.patterns.is_empty(), "missing files are not an error");
+        Ok(())
+    }
+
+    #[test]
+    fn macros_expand_to_their_transitive_assignments() {
+        use gix_attributes::State;
+        let group = MatchGroup {
+            patterns: vec![PatternList::<Attributes>::from_bytes(
+                b"[attr]a x -y\n[attr]b a z=1\n[attr]y yes\n[attr]cycle cycle other\n",
+                "macros.txt",
+                None,
+            )],
+        };
+        let expand = |name: &str| {
+            group.expand_macro(name).map(|assignments| {
+                assignments
+                    .into_iter()
+                    .map(|a| (a.name.as_str().to_owned(), a.state))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            expand("b"),
+            Some(vec![
+                ("z".into(), State::Value("1".into())),
+                ("y".into(), State::Unset),
+                ("x".into(), State::Set),
+            ]),
+            "nested macros are expanded if set, later assignments win"
+        );
+        assert_eq!(
+            expand("cycle"),
+            Some(vec![("other".into(), State::Set)]),
+            "self-references are ignored"
+        );
+        assert_eq!(
+            expand("binary"),
+            Some(vec![
+                ("text".into(), State::Unset),
+                ("merge".into(), State::Unset),
+                ("diff".into(), State::Unset),
+            ]),
+            "the built-in macro is known"
+        );
+        assert_eq!(expand("x"), None, "attributes aren't macros");
+    }
+}
@@ -112,10 +112,83 @@ mod access {
         w & 1 == 1
     }
 
-    const RLW_RUNNING_BITS: u64 = 4 * 8;
-    const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+    use super::{RLW_LARGEST_RUNNING_COUNT, RLW_RUNNING_BITS};
 }
 
+mod write {
+    use std::convert::TryInto;
+
+    use super::{Vec, RLW_LARGEST_LITERAL_COUNT, RLW_LARGEST_RUNNING_COUNT, RLW_RUNNING_BITS};
+
+    impl Vec {
+        /// Create a bitmap in which only the bits at the given indices are set, which must be in ascending order.
+        ///
+        /// Like in `git`, the amount of bits is determined by the highest index.
+        pub fn from_set_bits(set_bits: impl IntoIterator<Item = usize>) -> Self {
+            let mut words = std::vec::Vec::<u64>::new();
+            let mut num_bits = 0;
+            for index in set_bits {
+                let word = index / 64;
+                if words.len() <= word {
+                    words.resize(word + 1, 0);
+                }
+                words[word] |= 1 << (index % 64);
+                num_bits = index + 1;
+            }
+
+            let mut bits = std::vec::Vec::with_capacity(words.len() + 1);
+            let mut rlw;
+            let mut words = words.as_slice();
+            loop {
+                let running_len = words
+                    .iter()
+                    .take(RLW_LARGEST_RUNNING_COUNT as usize)
+                    .take_while(|word| **word == 0)
+                    .count();
+                words = &words[running_len..];
+                let literal_len = words
+                    .iter()
+                    .take(RLW_LARGEST_LITERAL_COUNT as usize)
+                    .take_while(|word| **word != 0)
+                    .count();
+                rlw = bits.len();
+                bits.push(((running_len as u64) << 1) | ((literal_len as u64) << (1 + RLW_RUNNING_BITS)));
+                bits.extend_from_slice(&words[..literal_len]);
+                words = &words[literal_len..];
+                if words.is_empty() {
+                    break;
+                }
+            }
+
+            Vec {
+                num_bits: num_bits.try_into().expect("not more than 4 billion bits"),
+                bits,
+                rlw: rlw as u64,
+            }
+        }
+
+        /// Serialize this bitmap to `out` in the format understood by [`decode()`][super::decode()].
+        pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+            let len: u32 = self.bits.len().try_into().expect("not more than 4 billion words");
+            out.write_all(&self.num_bits.to_be_bytes())?;
+            out.write_all(&len.to_be_bytes())?;
+            for word in &self.bits {
+                out.write_all(&word.to_be_bytes())?;
+            }
+            let rlw: u32 = self
+                .rlw
+                .try_into()
+                .expect("the last run length word is within the bits");
+            out.write_all(&rlw.to_be_bytes())
+        }
+    }
+}
+
+const RLW_RUNNING_BITS: u64 = 4 * 8;
+const RLW_LITERAL_BITS: u64 = 64 - 1 - RLW_RUNNING_BITS;
+const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+const RLW_LARGEST_LITERAL_COUNT: u64 = (1 << RLW_LITERAL_BITS) - 1;
+
 /// A growable collection of u64 that are seen as stream of individual bits.
 #[allow(dead_code)]
 #[derive(Clone)]
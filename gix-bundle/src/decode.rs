@@ -0,0 +1,96 @@
+use std::io::BufRead;
+
+use bstr::{BString, ByteSlice};
+use gix_hash::ObjectId;
+
+use crate::{Header, Prerequisite, Ref, Version};
+
+/// The error returned by [`Header::from_read()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read the bundle header")]
+    Io(#[from] std::io::Error),
+    #[error("The signature {signature:?} doesn't belong to a v2 or v3 bundle")]
+    UnsupportedSignature { signature: BString },
+    #[error("The bundle ended before its header was complete")]
+    UnexpectedEof,
+    #[error("The object format {format:?} is not supported")]
+    UnsupportedObjectFormat { format: BString },
+    #[error("The bundle capability {capability:?} is not supported")]
+    UnsupportedCapability { capability: BString },
+    #[error("The header line {line:?} is malformed")]
+    MalformedLine { line: BString },
+    #[error(transparent)]
+    Id(#[from] gix_hash::decode::Error),
+}
+
+impl Header {
+    /// Read a header from `read`, leaving it positioned at the first byte of the pack that follows it.
+    pub fn from_read(read: &mut dyn BufRead) -> Result<Self, Error> {
+        let mut buf = Vec::new();
+        read.read_until(b'\n', &mut buf)?;
+        let signature = buf.strip_suffix(b"\n").ok_or(Error::UnexpectedEof)?;
+        let version = if signature == Version::V2.signature().as_bytes() {
+            Version::V2
+        } else if signature == Version::V3.signature().as_bytes() {
+            Version::V3
+        } else {
+            return Err(Error::UnsupportedSignature {
+                signature: signature.into(),
+            });
+        };
+        let mut header = Header {
+            version,
+            object_hash: gix_hash::Kind::Sha1,
+            filter: None,
+            prerequisites: Vec::new(),
+            refs: Vec::new(),
+        };
+        loop {
+            buf.clear();
+            read.read_until(b'\n', &mut buf)?;
+            let line = buf.strip_suffix(b"\n").ok_or(Error::UnexpectedEof)?;
+            if line.is_empty() {
+                break;
+            }
+            let malformed = || Error::MalformedLine { line: line.into() };
+            if let Some(capability) = line.strip_prefix(b"@").filter(|_| version == Version::V3) {
+                let (name, value) = capability.split_once_str(b"=").unwrap_or((capability, &[][..]));
+                match name {
+                    b"object-format" => {
+                        header.object_hash = match value {
+                            b"sha1" => gix_hash::Kind::Sha1,
+                            _ => return Err(Error::UnsupportedObjectFormat { format: value.into() }),
+                        }
+                    }
+                    b"filter" => header.filter = Some(value.into()),
+                    _ => {
+                        return Err(Error::UnsupportedCapability {
+                            capability: capability.into(),
+                        })
+                    }
+                }
+            } else if let Some(prerequisite) = line.strip_prefix(b"-") {
+                let (hex, comment) = match prerequisite.split_once_str(b" ") {
+                    Some((hex, comment)) => (hex, Some(comment.into())),
+                    None => (prerequisite, None),
+                };
+                header.prerequisites.push(Prerequisite {
+                    id: ObjectId::from_hex(hex)?,
+                    comment,
+                });
+            } else {
+                let (hex, name) = line.split_once_str(b" ").ok_or_else(malformed)?;
+                if name.is_empty() {
+                    return Err(malformed());
+                }
+                header.refs.push(Ref {
+                    id: ObjectId::from_hex(hex)?,
+                    name: name.into(),
+                });
+            }
+        }
+        Ok(header)
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::Write;
+
+use crate::{Header, Version};
+
+impl Header {
+    /// Write this header to `out`, which is expected to be followed by the pack of the bundle.
+    ///
+    /// Capabilities are only written for [`Version::V3`] bundles.
+    pub fn write_to(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        out.write_all(self.version.signature().as_bytes())?;
+        out.write_all(b"\n")?;
+        if self.version == Version::V3 {
+            out.write_all(b"@object-format=")?;
+            out.write_all(self.object_hash.to_string().to_ascii_lowercase().as_bytes())?;
+            out.write_all(b"\n")?;
+            if let Some(filter) = &self.filter {
+                out.write_all(b"@filter=")?;
+                out.write_all(filter)?;
+                out.write_all(b"\n")?;
+            }
+        }
+        for prerequisite in &self.prerequisites {
+            out.write_all(b"-")?;
+            prerequisite.id.write_hex_to(&mut *out)?;
+            if let Some(comment) = &prerequisite.comment {
+                out.write_all(b" ")?;
+                out.write_all(comment)?;
+            }
+            out.write_all(b"\n")?;
+        }
+        for r in &self.refs {
+            r.id.write_hex_to(&mut *out)?;
+            out.write_all(b" ")?;
+            out.write_all(&r.name)?;
+            out.write_all(b"\n")?;
+        }
+        out.write_all(b"\n")
+    }
+}
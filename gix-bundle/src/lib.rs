@@ -0,0 +1,73 @@
+//! Read and write the header of [git bundle](https://git-scm.com/docs/gitformat-bundle) files, which is followed by a pack
+//! containing all objects reachable from its references but not from its prerequisites.
+//! ## Feature Flags
+#![cfg_attr(
+    feature = "document-features",
+    cfg_attr(doc, doc = ::document_features::document_features!())
+)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+#![deny(missing_docs, rust_2018_idioms)]
+#![forbid(unsafe_code)]
+
+use bstr::BString;
+use gix_hash::ObjectId;
+
+/// The version of the bundle format.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Version {
+    /// The original format, which only supports `SHA1` objects.
+    V2,
+    /// The format which adds capabilities to the header, which can describe the object format and a filter.
+    V3,
+}
+
+impl Version {
+    /// Return the signature line of the version, without trailing newline.
+    pub fn signature(&self) -> &'static str {
+        match self {
+            Version::V2 => "# v2 git bundle",
+            Version::V3 => "# v3 git bundle",
+        }
+    }
+}
+
+/// An object which must be present in the repository to unbundle a bundle, along with its history.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Prerequisite {
+    /// The id of the object.
+    pub id: ObjectId,
+    /// A description of the object, typically the subject of a commit, which is informational only.
+    pub comment: Option<BString>,
+}
+
+/// A reference contained in a bundle.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ref {
+    /// The object the reference points to.
+    pub id: ObjectId,
+    /// The name of the reference, typically a full reference name like `refs/heads/main` or `HEAD`.
+    pub name: BString,
+}
+
+/// The header of a bundle file, which precedes the pack it contains.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    /// The version of the bundle.
+    pub version: Version,
+    /// The kind of hash used for all objects in the bundle.
+    pub object_hash: gix_hash::Kind,
+    /// The object filter the pack was created with, turning the bundle into a partial one, as is only possible in `V3` bundles.
+    pub filter: Option<BString>,
+    /// Objects that must be present for the bundle to be applicable.
+    pub prerequisites: Vec<Prerequisite>,
+    /// The references in the bundle.
+    pub refs: Vec<Ref>,
+}
+
+///
+pub mod decode;
+mod encode;
@@ -0,0 +1,92 @@
+use gix_bundle::{Header, Prerequisite, Ref, Version};
+use gix_hash::ObjectId;
+
+fn id(hex: &str) -> ObjectId {
+    ObjectId::from_hex(hex.as_bytes()).expect("valid hex")
+}
+
+mod header {
+    use super::*;
+
+    const V2: &[u8] = b"# v2 git bundle\n\
+-1111111111111111111111111111111111111111 the subject\n\
+2222222222222222222222222222222222222222 refs/heads/main\n\
+3333333333333333333333333333333333333333 refs/tags/v1.0\n\
+\n\
+PACK";
+
+    #[test]
+    fn read_v2_leaves_the_reader_at_the_pack() -> Result<(), Box<dyn std::error::Error>> {
+        let mut read = V2;
+        let header = Header::from_read(&mut read)?;
+        assert_eq!(
+            header,
+            Header {
+                version: Version::V2,
+                object_hash: gix_hash::Kind::Sha1,
+                filter: None,
+                prerequisites: vec![Prerequisite {
+                    id: id("1111111111111111111111111111111111111111"),
+                    comment: Some("the subject".into()),
+                }],
+                refs: vec![
+                    Ref {
+                        id: id("2222222222222222222222222222222222222222"),
+                        name: "refs/heads/main".into(),
+                    },
+                    Ref {
+                        id: id("3333333333333333333333333333333333333333"),
+                        name: "refs/tags/v1.0".into(),
+                    }
+                ],
+            }
+        );
+        assert_eq!(read, b"PACK", "the pack follows right after the header");
+        Ok(())
+    }
+
+    #[test]
+    fn write_and_read_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        for version in [Version::V2, Version::V3] {
+            let mut read = V2;
+            let mut header = Header::from_read(&mut read)?;
+            header.version = version;
+            header.prerequisites.push(Prerequisite {
+                id: id("4444444444444444444444444444444444444444"),
+                comment: None,
+            });
+            if version == Version::V3 {
+                header.filter = Some("blob:none".into());
+            }
+            let mut buf = Vec::new();
+            header.write_to(&mut buf)?;
+            assert_eq!(Header::from_read(&mut buf.as_slice())?, header);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn v3_capabilities() -> Result<(), Box<dyn std::error::Error>> {
+        let mut read: &[u8] = b"# v3 git bundle\n@object-format=sha1\n@filter=blob:none\n\n";
+        let header = Header::from_read(&mut read)?;
+        assert_eq!(header.version, Version::V3);
+        assert_eq!(header.filter, Some("blob:none".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() {
+        for input in [
+            &b""[..],
+            b"# v4 git bundle\n\n",
+            b"# v2 git bundle\n@object-format=sha1\n\n",
+            b"# v3 git bundle\n@object-format=sha256\n\n",
+            b"# v3 git bundle\n@unknown\n\n",
+            b"# v2 git bundle\n2222222222222222222222222222222222222222\n\n",
+            b"# v2 git bundle\n2222222222222222222222222222222222222222 refs/heads/main\n",
+        ] {
+            let mut read = input;
+            assert!(Header::from_read(&mut read).is_err(), "{:?}", bstr::BStr::new(input));
+        }
+    }
+}
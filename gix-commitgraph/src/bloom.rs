@@ -0,0 +1,172 @@
+//! Bloom filters of the paths changed by each commit, which allow to quickly skip commits that didn't change a path of interest.
+//!
+//! They are stored in the `BIDX` and `BDAT` chunks of commit-graph files and are compatible with the ones written by
+//! `git commit-graph write --changed-paths`.
+use std::collections::HashSet;
+
+use bstr::ByteSlice;
+
+/// The amount of changed files after which a commit's filter is stored as containing all paths, just like `git` does.
+pub const MAX_CHANGED_PATHS: usize = 512;
+
+/// The parameters used to create all filters of a commit-graph file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Settings {
+    /// The version of the hash function used to hash paths, which is always 1.
+    pub hash_version: u32,
+    /// The amount of hashes, and thus bits, to set for each path.
+    pub num_hashes: u32,
+    /// The amount of bits to reserve in a filter for each path it contains.
+    pub bits_per_entry: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            hash_version: 1,
+            num_hashes: 7,
+            bits_per_entry: 10,
+        }
+    }
+}
+
+/// The size of the settings stored at the beginning of the `BDAT` chunk.
+pub(crate) const SETTINGS_LEN: usize = 3 * 4;
+
+impl Settings {
+    pub(crate) fn from_bytes(data: &[u8]) -> Self {
+        let read_u32 = |ofs: usize| u32::from_be_bytes([data[ofs], data[ofs + 1], data[ofs + 2], data[ofs + 3]]);
+        Settings {
+            hash_version: read_u32(0),
+            num_hashes: read_u32(4),
+            bits_per_entry: read_u32(8),
+        }
+    }
+
+    pub(crate) fn to_bytes(self) -> [u8; SETTINGS_LEN] {
+        let mut out = [0; SETTINGS_LEN];
+        out[..4].copy_from_slice(&self.hash_version.to_be_bytes());
+        out[4..8].copy_from_slice(&self.num_hashes.to_be_bytes());
+        out[8..].copy_from_slice(&self.bits_per_entry.to_be_bytes());
+        out
+    }
+}
+
+/// The hashes of a path, which are computed once to [query][Filter::contains()] any amount of filters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    hashes: Vec<u32>,
+}
+
+impl Key {
+    /// Compute the key of `path`, which is relative to the root of the repository and uses slashes as separators, for use with
+    /// filters created with `settings`.
+    pub fn new(path: &[u8], settings: &Settings) -> Self {
+        let hash0 = murmur3_seeded(0x293a_e76f, path);
+        let hash1 = murmur3_seeded(0x7e64_6e2c, path);
+        Key {
+            hashes: (0..settings.num_hashes)
+                .map(|i| hash0.wrapping_add(i.wrapping_mul(hash1)))
+                .collect(),
+        }
+    }
+
+    fn bit_positions(&self, filter_len: usize) -> impl Iterator<Item = (usize, u8)> + '_ {
+        let num_bits = filter_len as u64 * 8;
+        self.hashes.iter().map(move |hash| {
+            let bit = u64::from(*hash) % num_bits;
+            ((bit / 8) as usize, 1 << (bit % 8))
+        })
+    }
+}
+
+/// The bloom filter of a single commit, as stored in a commit-graph file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Filter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Filter<'a> {
+    /// Create a new instance from `data`, whose keys must have been created with the same settings as the ones used to create it.
+    pub fn from_bytes(data: &'a [u8]) -> Self {
+        Filter { data }
+    }
+
+    /// The raw data of the filter.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Return `false` if the path of `key` was definitely not changed by the commit, or `true` if it might have been.
+    ///
+    /// Note that filters without data are written for commits whose changes weren't computed, and they contain all paths.
+    pub fn contains(&self, key: &Key) -> bool {
+        if self.data.is_empty() {
+            return true;
+        }
+        key.bit_positions(self.data.len())
+            .all(|(byte, mask)| self.data[byte] & mask != 0)
+    }
+}
+
+/// Return the data of a filter for a commit that changed the files at `paths` compared to its first parent, or compared to an
+/// empty tree if it has no parent, for use with filters created with `settings`.
+///
+/// The leading directories of each path are added as well. If there are more than [`MAX_CHANGED_PATHS`] `paths`, the
+/// filter contains all paths, so callers may stop collecting changes at that point.
+pub fn filter_data<'a>(paths: impl IntoIterator<Item = &'a [u8]>, settings: &Settings) -> Vec<u8> {
+    let mut unique = HashSet::new();
+    for (num_paths, mut path) in paths.into_iter().enumerate() {
+        if num_paths == MAX_CHANGED_PATHS {
+            return vec![0xff];
+        }
+        while unique.insert(path) {
+            match path.rfind_byte(b'/') {
+                Some(pos) => path = &path[..pos],
+                None => break,
+            }
+        }
+    }
+
+    let len = (unique.len() * settings.bits_per_entry as usize + 7) / 8;
+    let mut data = vec![0; len.max(1)];
+    for path in unique {
+        let len = data.len();
+        for (byte, mask) in Key::new(path, settings).bit_positions(len) {
+            data[byte] |= mask;
+        }
+    }
+    data
+}
+
+/// The 32 bit variant of the murmur3 hash, with bytes of `data` being sign-extended just like `git` does it for version 1 of the
+/// hash used by changed-path filters.
+fn murmur3_seeded(mut seed: u32, data: &[u8]) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let byte = |byte: u8| byte as i8 as u32;
+
+    let mut chunks = data.chunks_exact(4);
+    for chunk in &mut chunks {
+        let k = byte(chunk[0]) | byte(chunk[1]) << 8 | byte(chunk[2]) << 16 | byte(chunk[3]) << 24;
+        seed ^= k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        seed = seed.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let k = tail
+            .iter()
+            .enumerate()
+            .fold(0, |k, (index, tail_byte)| k ^ byte(*tail_byte) << (8 * index));
+        seed ^= k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+    }
+
+    seed ^= data.len() as u32;
+    seed ^= seed >> 16;
+    seed = seed.wrapping_mul(0x85eb_ca6b);
+    seed ^= seed >> 13;
+    seed = seed.wrapping_mul(0xc2b2_ae35);
+    seed ^= seed >> 16;
+    seed
+}
@@ -4,7 +4,10 @@ use std::{
     path::Path,
 };
 
-use crate::file::{self, commit::Commit, File, COMMIT_DATA_ENTRY_SIZE_SANS_HASH};
+use crate::{
+    bloom,
+    file::{self, commit::Commit, File, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, GENERATION_DATA_OVERFLOW_MASK},
+};
 
 /// Access
 impl File {
@@ -13,6 +16,39 @@ impl File {
         self.base_graph_count
     }
 
+    /// Returns the bloom filter of the paths changed by the commit at the given lexigraphical position, or `None` if this file
+    /// doesn't contain bloom filters.
+    ///
+    /// Keys to query the filter must be created with [`bloom_filter_settings()`][File::bloom_filter_settings()].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is out of bounds.
+    pub fn bloom_filter_at(&self, pos: file::Position) -> Option<bloom::Filter<'_>> {
+        assert!(
+            pos.0 < self.num_commits(),
+            "expected lexigraphical position less than {}, got {}",
+            self.num_commits(),
+            pos.0
+        );
+        let index = &self.data[self.bloom_filter_index_offset?..];
+        let read_end = |pos: usize| u32::from_be_bytes(index[pos * 4..][..4].try_into().unwrap()) as usize;
+        let pos = pos.0 as usize;
+        let start = if pos == 0 { 0 } else { read_end(pos - 1) };
+        let filters = &self.data[self.bloom_filter_data_range.clone()?];
+        filters.get(start..read_end(pos)).map(bloom::Filter::from_bytes)
+    }
+
+    /// The settings used to create all bloom filters in this file, or `None` if it doesn't contain bloom filters.
+    pub fn bloom_filter_settings(&self) -> Option<&bloom::Settings> {
+        self.bloom_filter_settings.as_ref()
+    }
+
+    /// Returns `true` if this file stores corrected commit dates, also known as generation numbers v2.
+    pub fn has_corrected_commit_dates(&self) -> bool {
+        self.generation_data_offset.is_some()
+    }
+
     /// Returns the commit data for the commit located at the given lexigraphical position.
     ///
     /// `pos` must range from 0 to self.num_commits().
@@ -122,6 +158,22 @@ impl File {
         &self.data[start..][..entry_size]
     }
 
+    /// Returns the amount of seconds to add to the committer timestamp of the commit at `pos` to obtain its corrected commit date,
+    /// or `None` if this file doesn't contain generation data or if it is corrupt.
+    pub(crate) fn corrected_commit_date_offset(&self, pos: file::Position) -> Option<u64> {
+        let start = self.generation_data_offset? + pos.0 as usize * 4;
+        let offset = u32::from_be_bytes(self.data[start..][..4].try_into().unwrap());
+        if offset & GENERATION_DATA_OVERFLOW_MASK == 0 {
+            return Some(u64::from(offset));
+        }
+        let overflow_pos = (offset & !GENERATION_DATA_OVERFLOW_MASK) as usize;
+        let overflow = &self.data[self.generation_data_overflow_range.clone()?];
+        overflow
+            .get(overflow_pos * 8..)
+            .and_then(|overflow| overflow.get(..8))
+            .map(|offset| u64::from_be_bytes(offset.try_into().unwrap()))
+    }
+
     /// Returns the byte slice for this file's entire Extra Edge List (EDGE) chunk.
     pub(crate) fn extra_edges_data(&self) -> Option<&[u8]> {
         Some(&self.data[self.extra_edges_list_range.clone()?])
@@ -6,6 +6,7 @@ use std::{
 };
 
 use crate::{
+    bloom,
     file::{self, File, EXTENDED_EDGES_MASK, LAST_EXTENDED_EDGE_MASK, NO_PARENT},
     graph,
 };
@@ -63,6 +64,23 @@ impl<'a> Commit<'a> {
         self.commit_timestamp
     }
 
+    /// Returns the bloom filter of the paths this commit changed compared to its first parent, along with the settings that
+    /// [keys][bloom::Key::new()] have to be created with to query it, or `None` if the file it is stored in doesn't contain filters.
+    pub fn bloom_filter(&self) -> Option<(bloom::Filter<'a>, &'a bloom::Settings)> {
+        Some((self.file.bloom_filter_at(self.pos)?, self.file.bloom_filter_settings()?))
+    }
+
+    /// Returns the corrected commit date of this commit, also known as generation number v2, or `None` if the file it is stored
+    /// in doesn't contain it.
+    ///
+    /// The value is the committer timestamp, increased if necessary to be larger than the corrected commit dates of all parents.
+    /// Note that these values can only be compared if all files of a graph store them.
+    pub fn corrected_commit_date(&self) -> Option<u64> {
+        self.file
+            .corrected_commit_date_offset(self.pos)
+            .map(|offset| self.commit_timestamp + offset)
+    }
+
     /// Returns the generation number of this commit.
     ///
     /// Commits without parents have generation number 1. Commits with parents have a generation
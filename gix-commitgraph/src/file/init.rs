@@ -6,9 +6,14 @@ use std::{
 use bstr::ByteSlice;
 use memmap2::Mmap;
 
-use crate::file::{
-    ChunkId, File, BASE_GRAPHS_LIST_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
-    EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+use crate::{
+    bloom,
+    file::{
+        ChunkId, File, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_INDEX_CHUNK_ID,
+        COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN,
+        GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID,
+        SIGNATURE,
+    },
 };
 
 /// The error used in [`File::at()`].
@@ -196,6 +201,61 @@ impl TryFrom<&Path> for File {
 
         let extra_edges_list_range = chunks.usize_offset_by_id(EXTENDED_EDGES_LIST_CHUNK_ID).ok();
 
+        let expect_u32_per_commit = |id: ChunkId| {
+            move |chunk_range: std::ops::Range<usize>| {
+                let expected_size = oid_lookup_count as usize * 4;
+                if chunk_range.len() != expected_size {
+                    return Err(Error::InvalidChunkSize {
+                        id,
+                        msg: format!("expected chunk length {expected_size}, got {}", chunk_range.len()),
+                    });
+                }
+                Ok(chunk_range.start)
+            }
+        };
+        let generation_data_offset = chunks
+            .validated_usize_offset_by_id(
+                GENERATION_DATA_CHUNK_ID,
+                expect_u32_per_commit(GENERATION_DATA_CHUNK_ID),
+            )
+            .ok()
+            .transpose()?;
+        let generation_data_overflow_range = chunks.usize_offset_by_id(GENERATION_DATA_OVERFLOW_CHUNK_ID).ok();
+
+        let bloom_filter_index_offset = chunks
+            .validated_usize_offset_by_id(
+                BLOOM_FILTER_INDEX_CHUNK_ID,
+                expect_u32_per_commit(BLOOM_FILTER_INDEX_CHUNK_ID),
+            )
+            .ok()
+            .transpose()?;
+        let bloom_filter_data_range = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_DATA_CHUNK_ID, |chunk_range| {
+                if chunk_range.len() < bloom::SETTINGS_LEN {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_DATA_CHUNK_ID,
+                        msg: format!(
+                            "expected chunk length of at least {}, got {}",
+                            bloom::SETTINGS_LEN,
+                            chunk_range.len()
+                        ),
+                    });
+                }
+                Ok(chunk_range)
+            })
+            .ok()
+            .transpose()?;
+        // Like `git`, ignore bloom filters unless both chunks are present.
+        let (bloom_filter_index_offset, bloom_filter_data_range, bloom_filter_settings) =
+            match (bloom_filter_index_offset, bloom_filter_data_range) {
+                (Some(index_offset), Some(data_range)) => (
+                    Some(index_offset),
+                    Some(data_range.start + bloom::SETTINGS_LEN..data_range.end),
+                    Some(bloom::Settings::from_bytes(&data[data_range])),
+                ),
+                _ => (None, None, None),
+            };
+
         let trailer = &data[chunks.highest_offset() as usize..];
         if trailer.len() != object_hash.len_in_bytes() {
             return Err(Error::Trailer(format!(
@@ -232,10 +292,15 @@ impl TryFrom<&Path> for File {
         Ok(File {
             base_graph_count,
             base_graphs_list_offset,
+            bloom_filter_data_range,
+            bloom_filter_index_offset,
+            bloom_filter_settings,
             commit_data_offset,
             data,
             extra_edges_list_range,
             fan,
+            generation_data_offset,
+            generation_data_overflow_range,
             oid_lookup_offset,
             path: path.to_owned(),
             hash_len: object_hash.len_in_bytes(),
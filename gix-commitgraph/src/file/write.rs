@@ -0,0 +1,391 @@
+//! Write commit-graph files, either as single file or as layer on top of a chain of existing files.
+use std::{
+    convert::TryInto,
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bloom,
+    file::{
+        File, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_INDEX_CHUNK_ID, COMMIT_DATA_CHUNK_ID,
+        COMMIT_DATA_ENTRY_SIZE_SANS_HASH, EXTENDED_EDGES_LIST_CHUNK_ID, EXTENDED_EDGES_MASK, FAN_LEN,
+        GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, GENERATION_DATA_OVERFLOW_MASK, HEADER_LEN,
+        LAST_EXTENDED_EDGE_MASK, NO_PARENT, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+    },
+    Graph, GENERATION_NUMBER_MAX, MAX_COMMITS,
+};
+
+mod error {
+    /// The error returned by [`File::write_to()`][crate::file::File::write_to()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+        #[error("The parent {parent} of commit {id} is neither part of the commits to write nor of the base graph")]
+        MissingParent {
+            id: gix_hash::ObjectId,
+            parent: gix_hash::ObjectId,
+        },
+        #[error(
+            "The commit-graph would contain {0} commits, but only {} commits are allowed",
+            crate::MAX_COMMITS
+        )]
+        TooManyCommits(u64),
+        #[error("The commit-graph would be based on {0} files, but only 255 are allowed")]
+        TooManyBaseGraphs(usize),
+    }
+}
+pub use error::Error;
+
+/// A commit to write into a commit-graph file, along with all information about it that is stored there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// The id of the tree the commit points to.
+    pub tree: ObjectId,
+    /// The ids of all parents of the commit, which must be part of the commits to write or of the base graph.
+    pub parents: Vec<ObjectId>,
+    /// The time at which the commit was committed, in seconds since 1970-01-01 00:00:00 UTC.
+    pub committer_timestamp: u64,
+    /// The data of the bloom filter of paths changed by this commit as created by [`bloom::filter_data()`] with default
+    /// [settings][bloom::Settings], or `None` if it isn't known.
+    ///
+    /// It's only written if [`Options::changed_paths`] is set, and commits without data will match all paths.
+    pub bloom_filter: Option<Vec<u8>>,
+}
+
+/// Options for use in [`File::write_to()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The kind of hash used for all object ids.
+    pub object_hash: gix_hash::Kind,
+    /// If `true`, store corrected commit dates, also known as generation numbers v2, along with the topological levels of
+    /// each commit.
+    ///
+    /// They are not written if a file of the base graph doesn't contain them, as they would be ignored by readers.
+    pub corrected_commit_dates: bool,
+    /// If `true`, store the [bloom filters][Commit::bloom_filter] of all commits to speed up finding commits that change
+    /// a path, like `git commit-graph write --changed-paths`.
+    pub changed_paths: bool,
+}
+
+/// The outcome of [`File::write_to()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The checksum of the written file, which is also part of its name if it's part of a chain of files.
+    pub checksum: ObjectId,
+    /// The amount of commits written into the file.
+    pub num_commits: u32,
+    /// If `true`, corrected commit dates were written.
+    pub corrected_commit_dates: bool,
+}
+
+/// The position of a commit in the graph consisting of the base graph and the file being written.
+type GraphPosition = u32;
+
+impl File {
+    /// Write a commit-graph file containing `commits` to `out`, building on top of all files of the `base` graph if `Some`,
+    /// and using `should_interrupt` to abort early.
+    ///
+    /// Commits that are already contained in `base` are skipped, but the parents of all other commits must be contained
+    /// in either `commits` or `base`.
+    /// If `base` is set, the file is meant to be added to the end of its chain and should be named after its checksum.
+    pub fn write_to(
+        mut commits: Vec<Commit>,
+        base: Option<&Graph>,
+        out: impl std::io::Write,
+        should_interrupt: &AtomicBool,
+        Options {
+            object_hash,
+            corrected_commit_dates,
+            changed_paths,
+        }: Options,
+    ) -> Result<Outcome, Error> {
+        commits.sort_by_key(|commit| commit.id);
+        commits.dedup_by(|lhs, rhs| lhs.id == rhs.id);
+        if let Some(base) = base {
+            commits.retain(|commit| base.lookup(commit.id).is_none());
+        }
+
+        let base_files = base.map_or(&[][..], |base| base.files());
+        let num_base_commits = base.map_or(0, Graph::num_commits);
+        let num_commits = u64::from(num_base_commits) + commits.len() as u64;
+        if num_commits > u64::from(MAX_COMMITS) {
+            return Err(Error::TooManyCommits(num_commits));
+        }
+        let num_base_graphs: u8 = base_files
+            .len()
+            .try_into()
+            .map_err(|_| Error::TooManyBaseGraphs(base_files.len()))?;
+
+        let parents = commits
+            .iter()
+            .map(|commit| {
+                commit
+                    .parents
+                    .iter()
+                    .map(|parent| {
+                        commits
+                            .binary_search_by(|other| other.id.cmp(parent))
+                            .ok()
+                            .map(|pos| num_base_commits + pos as u32)
+                            .or_else(|| base.and_then(|base| base.lookup(parent)).map(|pos| pos.0))
+                            .ok_or(Error::MissingParent {
+                                id: commit.id,
+                                parent: *parent,
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+
+        let corrected_commit_dates = corrected_commit_dates && base_files.iter().all(File::has_corrected_commit_dates);
+        let generations = compute_generations(&commits, &parents, base, num_base_commits, should_interrupt)?;
+
+        let num_extra_edges: usize = parents
+            .iter()
+            .filter(|parents| parents.len() > 2)
+            .map(|p| p.len() - 1)
+            .sum();
+        let overflowing_offsets: Vec<u64> = if corrected_commit_dates {
+            commits
+                .iter()
+                .zip(&generations)
+                .map(|(commit, generation)| generation.corrected_commit_date - commit.committer_timestamp)
+                .filter(|offset| *offset > u64::from(!GENERATION_DATA_OVERFLOW_MASK))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let bloom_filter_data_len: usize = commits
+            .iter()
+            .map(|commit| commit.bloom_filter.as_ref().map_or(0, Vec::len))
+            .sum();
+
+        let num_commits = commits.len();
+        let hash_len = object_hash.len_in_bytes();
+        let mut cf = gix_chunk::file::Index::for_writing();
+        cf.plan_chunk(OID_FAN_CHUNK_ID, (FAN_LEN * 4) as u64);
+        cf.plan_chunk(OID_LOOKUP_CHUNK_ID, (num_commits * hash_len) as u64);
+        cf.plan_chunk(
+            COMMIT_DATA_CHUNK_ID,
+            (num_commits * (hash_len + COMMIT_DATA_ENTRY_SIZE_SANS_HASH)) as u64,
+        );
+        if corrected_commit_dates {
+            cf.plan_chunk(GENERATION_DATA_CHUNK_ID, (num_commits * 4) as u64);
+            if !overflowing_offsets.is_empty() {
+                cf.plan_chunk(
+                    GENERATION_DATA_OVERFLOW_CHUNK_ID,
+                    (overflowing_offsets.len() * 8) as u64,
+                );
+            }
+        }
+        if num_extra_edges > 0 {
+            cf.plan_chunk(EXTENDED_EDGES_LIST_CHUNK_ID, (num_extra_edges * 4) as u64);
+        }
+        if changed_paths {
+            cf.plan_chunk(BLOOM_FILTER_INDEX_CHUNK_ID, (num_commits * 4) as u64);
+            cf.plan_chunk(
+                BLOOM_FILTER_DATA_CHUNK_ID,
+                (bloom::SETTINGS_LEN + bloom_filter_data_len) as u64,
+            );
+        }
+        if num_base_graphs > 0 {
+            cf.plan_chunk(BASE_GRAPHS_LIST_CHUNK_ID, (base_files.len() * hash_len) as u64);
+        }
+
+        let mut out = gix_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&[1 /* version */, object_hash as u8])?;
+        out.write_all(&[
+            cf.num_chunks().try_into().expect("BUG: wrote more than 256 chunks"),
+            num_base_graphs,
+        ])?;
+
+        let mut chunk_write = cf.into_write(&mut out, HEADER_LEN)?;
+        while let Some(chunk_to_write) = chunk_write.next_chunk() {
+            match chunk_to_write {
+                OID_FAN_CHUNK_ID => {
+                    let mut fan = [0u32; FAN_LEN];
+                    for commit in &commits {
+                        fan[usize::from(commit.id.first_byte())] += 1;
+                    }
+                    let mut num_commits = 0;
+                    for count in fan {
+                        num_commits += count;
+                        chunk_write.write_all(&num_commits.to_be_bytes())?;
+                    }
+                }
+                OID_LOOKUP_CHUNK_ID => {
+                    for commit in &commits {
+                        chunk_write.write_all(commit.id.as_slice())?;
+                    }
+                }
+                COMMIT_DATA_CHUNK_ID => {
+                    let mut extra_edges = 0;
+                    for ((commit, parents), generation) in commits.iter().zip(&parents).zip(&generations) {
+                        chunk_write.write_all(commit.tree.as_slice())?;
+                        let parent2 = match parents.len() {
+                            0 | 1 => NO_PARENT,
+                            2 => parents[1],
+                            num_parents => {
+                                let edge = EXTENDED_EDGES_MASK | extra_edges;
+                                extra_edges += num_parents as u32 - 1;
+                                edge
+                            }
+                        };
+                        chunk_write.write_all(&parents.first().copied().unwrap_or(NO_PARENT).to_be_bytes())?;
+                        chunk_write.write_all(&parent2.to_be_bytes())?;
+                        let timestamp_high_bits = (commit.committer_timestamp >> 32) as u32 & 0x3;
+                        chunk_write.write_all(&(generation.level << 2 | timestamp_high_bits).to_be_bytes())?;
+                        chunk_write.write_all(&(commit.committer_timestamp as u32).to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_CHUNK_ID => {
+                    let mut num_overflows = 0;
+                    for (commit, generation) in commits.iter().zip(&generations) {
+                        let offset = generation.corrected_commit_date - commit.committer_timestamp;
+                        let offset = if offset > u64::from(!GENERATION_DATA_OVERFLOW_MASK) {
+                            num_overflows += 1;
+                            GENERATION_DATA_OVERFLOW_MASK | (num_overflows - 1)
+                        } else {
+                            offset as u32
+                        };
+                        chunk_write.write_all(&offset.to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_OVERFLOW_CHUNK_ID => {
+                    for offset in &overflowing_offsets {
+                        chunk_write.write_all(&offset.to_be_bytes())?;
+                    }
+                }
+                EXTENDED_EDGES_LIST_CHUNK_ID => {
+                    for parents in parents.iter().filter(|parents| parents.len() > 2) {
+                        let (last, others) = parents[1..].split_last().expect("more than two parents");
+                        for parent in others {
+                            chunk_write.write_all(&parent.to_be_bytes())?;
+                        }
+                        chunk_write.write_all(&(LAST_EXTENDED_EDGE_MASK | last).to_be_bytes())?;
+                    }
+                }
+                BLOOM_FILTER_INDEX_CHUNK_ID => {
+                    let mut end = 0u32;
+                    for commit in &commits {
+                        end += commit.bloom_filter.as_ref().map_or(0, Vec::len) as u32;
+                        chunk_write.write_all(&end.to_be_bytes())?;
+                    }
+                }
+                BLOOM_FILTER_DATA_CHUNK_ID => {
+                    chunk_write.write_all(&bloom::Settings::default().to_bytes())?;
+                    for filter in commits.iter().filter_map(|commit| commit.bloom_filter.as_ref()) {
+                        chunk_write.write_all(filter)?;
+                    }
+                }
+                BASE_GRAPHS_LIST_CHUNK_ID => {
+                    for file in base_files {
+                        chunk_write.write_all(file.checksum().as_bytes())?;
+                    }
+                }
+                unknown => unreachable!("BUG: forgot to implement chunk {:?}", std::str::from_utf8(&unknown)),
+            }
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+        }
+
+        let checksum: ObjectId = out.hash.digest().into();
+        out.inner.write_all(checksum.as_slice())?;
+        Ok(Outcome {
+            checksum,
+            num_commits: num_commits as u32,
+            corrected_commit_dates,
+        })
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+struct Generation {
+    /// The topological level, i.e. generation number v1, which is 0 until it was computed.
+    level: u32,
+    corrected_commit_date: u64,
+}
+
+/// Compute the generation of all `commits`, whose parents are at the given graph positions, in an order that assures that
+/// the generations of parents are known before the ones of their children.
+fn compute_generations(
+    commits: &[Commit],
+    parents: &[Vec<GraphPosition>],
+    base: Option<&Graph>,
+    num_base_commits: u32,
+    should_interrupt: &AtomicBool,
+) -> Result<Vec<Generation>, Error> {
+    let mut generations = vec![Generation::default(); commits.len()];
+    let generation_of = |generations: &[Generation], pos: GraphPosition| -> Generation {
+        match pos.checked_sub(num_base_commits) {
+            Some(pos) => generations[pos as usize],
+            None => {
+                let commit = base
+                    .expect("positions below the amount of base commits are in the base graph")
+                    .commit_at(crate::graph::Position(pos));
+                Generation {
+                    level: commit.generation(),
+                    corrected_commit_date: commit
+                        .corrected_commit_date()
+                        .unwrap_or_else(|| commit.committer_timestamp()),
+                }
+            }
+        }
+    };
+
+    let mut stack = Vec::new();
+    for start in 0..commits.len() {
+        if generations[start].level != 0 {
+            continue;
+        }
+        stack.push(start);
+        while let Some(&pos) = stack.last() {
+            if generations[pos].level != 0 {
+                stack.pop();
+                continue;
+            }
+            let num_stacked = stack.len();
+            stack.extend(
+                parents[pos]
+                    .iter()
+                    .filter_map(|parent| parent.checked_sub(num_base_commits))
+                    .map(|parent| parent as usize)
+                    .filter(|parent| generations[*parent].level == 0),
+            );
+            if stack.len() != num_stacked {
+                continue;
+            }
+
+            let mut generation = Generation {
+                level: 1,
+                corrected_commit_date: commits[pos].committer_timestamp,
+            };
+            for parent in parents[pos].iter().map(|parent| generation_of(&generations, *parent)) {
+                generation.level = generation.level.max(parent.level.saturating_add(1));
+                generation.corrected_commit_date =
+                    generation.corrected_commit_date.max(parent.corrected_commit_date + 1);
+            }
+            generation.level = generation.level.min(GENERATION_NUMBER_MAX);
+            generations[pos] = generation;
+            stack.pop();
+        }
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+    }
+    Ok(generations)
+}
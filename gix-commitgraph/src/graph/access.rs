@@ -52,6 +52,11 @@ impl Graph {
 
 /// Access fundamentals
 impl Graph {
+    /// The files making up this graph, with the base file first.
+    pub(crate) fn files(&self) -> &[File] {
+        &self.files
+    }
+
     fn lookup_by_id(&self, id: &gix_hash::oid) -> Option<LookupByIdResult<'_>> {
         let mut current_file_start = 0;
         for file in &self.files {
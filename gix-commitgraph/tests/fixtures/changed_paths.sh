@@ -0,0 +1,30 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b root
+
+echo a > a
+mkdir -p dir/subdir
+echo b > dir/b
+git add . && git commit -q -m root
+
+git checkout -q -b nested
+echo c > dir/subdir/c
+echo "non-ascii" > "dir/ä"
+git add . && git commit -q -m nested
+
+git checkout -q -b side root
+echo changed > a
+git commit -q -am side
+
+git checkout -q -b many root
+mkdir many
+for n in $(seq 600); do
+  echo $n > many/$n
+done
+git add . && GIT_COMMITTER_DATE="1990-01-01 00:00:00 +0000" git commit -q -m "many, committed before its parent"
+
+git checkout -q -b merges nested
+git merge -q -m octopus --no-ff side many >/dev/null
+git rm -q dir/b && git commit -q -m "delete"
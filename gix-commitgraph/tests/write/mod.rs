@@ -0,0 +1,237 @@
+use std::{path::Path, process::Command, sync::atomic::AtomicBool};
+
+use gix_commitgraph::{
+    bloom,
+    file::{self, write},
+    Graph,
+};
+
+fn git(repo_dir: &Path, args: &[&str]) -> Vec<u8> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_dir)
+        .env_remove("GIT_DIR")
+        .output()
+        .expect("git can be executed");
+    assert!(
+        output.status.success(),
+        "{args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+fn id(hex: &str) -> gix_hash::ObjectId {
+    gix_hash::ObjectId::from_hex(hex.as_bytes()).expect("40 bytes hex")
+}
+
+/// Obtain all commits of the repository at `repo_dir` along with their bloom filters, computed from the changes `git` sees.
+fn all_commits(repo_dir: &Path) -> Vec<write::Commit> {
+    let log = git(repo_dir, &["log", "--all", "--format=%H %T %ct %P"]);
+    String::from_utf8(log)
+        .expect("ascii")
+        .lines()
+        .map(|line| {
+            let mut tokens = line.split(' ');
+            let commit = tokens.next().expect("commit");
+            let tree = tokens.next().expect("tree");
+            let committer_timestamp = tokens.next().expect("time").parse().expect("number");
+            let parents: Vec<_> = tokens.filter(|token| !token.is_empty()).map(id).collect();
+
+            let mut diff_args = vec!["diff-tree", "-r", "--no-renames", "--no-commit-id", "--name-only", "-z"];
+            let first_parent = parents.first().map(ToString::to_string);
+            match first_parent.as_deref() {
+                Some(parent) => diff_args.push(parent),
+                None => diff_args.push("--root"),
+            }
+            diff_args.push(commit);
+            let changes = git(repo_dir, &diff_args);
+            let bloom_filter = bloom::filter_data(
+                changes.split(|b| *b == 0).filter(|path| !path.is_empty()),
+                &bloom::Settings::default(),
+            );
+
+            write::Commit {
+                id: id(commit),
+                tree: id(tree),
+                parents,
+                committer_timestamp,
+                bloom_filter: Some(bloom_filter),
+            }
+        })
+        .collect()
+}
+
+fn options(changed_paths: bool) -> write::Options {
+    write::Options {
+        object_hash: gix_hash::Kind::Sha1,
+        corrected_commit_dates: true,
+        changed_paths,
+    }
+}
+
+#[test]
+fn single_file_is_identical_to_the_one_written_by_git() -> crate::Result {
+    for changed_paths in [false, true] {
+        let dir = gix_testtools::scripted_fixture_writable("changed_paths.sh")?;
+        let mut args = vec![
+            "-c",
+            "commitGraph.generationVersion=2",
+            "commit-graph",
+            "write",
+            "--reachable",
+        ];
+        if changed_paths {
+            args.push("--changed-paths");
+        }
+        git(dir.path(), &args);
+        let graph_path = dir.path().join(".git/objects/info/commit-graph");
+        let expected = std::fs::read(&graph_path)?;
+
+        let commits = all_commits(dir.path());
+        let num_commits = commits.len();
+        let mut actual = Vec::new();
+        let outcome = file::File::write_to(
+            commits,
+            None,
+            &mut actual,
+            &AtomicBool::default(),
+            options(changed_paths),
+        )?;
+        assert_eq!(outcome.num_commits as usize, num_commits);
+        assert!(outcome.corrected_commit_dates);
+        assert!(
+            actual == expected,
+            "the written file is byte-for-byte the same as the one written by git, with changed_paths = {changed_paths}"
+        );
+        assert_eq!(&actual[actual.len() - 20..], outcome.checksum.as_slice());
+
+        let file = file::File::at(&graph_path)?;
+        assert_eq!(file.checksum(), outcome.checksum);
+        assert!(file.has_corrected_commit_dates());
+        let many = file
+            .lookup(id(std::str::from_utf8(&git(dir.path(), &["rev-parse", "many"]))?.trim()))
+            .expect("present");
+        let many = file.commit_at(many);
+        assert!(
+            many.corrected_commit_date().expect("present") > many.committer_timestamp(),
+            "the commit is dated before its parent"
+        );
+        if !changed_paths {
+            assert!(file.bloom_filter_settings().is_none());
+            assert!(many.bloom_filter().is_none());
+            continue;
+        }
+
+        let settings = file.bloom_filter_settings().expect("present");
+        assert_eq!(*settings, bloom::Settings::default());
+        let nested = id(std::str::from_utf8(&git(dir.path(), &["rev-parse", "nested"]))?.trim());
+        let filter = file
+            .bloom_filter_at(file.lookup(nested).expect("present"))
+            .expect("present");
+        for path in ["dir", "dir/subdir", "dir/subdir/c", "dir/ä"] {
+            assert!(filter.contains(&bloom::Key::new(path.as_bytes(), settings)), "{path}");
+        }
+        assert!(!filter.contains(&bloom::Key::new(b"a", settings)), "a wasn't changed");
+        let (filter, filter_settings) = many.bloom_filter().expect("present");
+        assert_eq!(filter_settings, settings);
+        assert_eq!(
+            filter.as_bytes(),
+            &[0xff],
+            "too many changes make the filter match everything"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn split_chain_is_identical_to_the_one_written_by_git() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_writable("changed_paths.sh")?;
+    let nested = git(dir.path(), &["rev-parse", "nested"]);
+    let write_layer = |stdin: Option<&[u8]>| -> crate::Result {
+        let mut args = vec![
+            "-c",
+            "commitGraph.generationVersion=2",
+            "commit-graph",
+            "write",
+            "--no-progress",
+            "--split=no-merge",
+            "--changed-paths",
+        ];
+        args.push(if stdin.is_some() {
+            "--stdin-commits"
+        } else {
+            "--reachable"
+        });
+        let mut child = Command::new("git")
+            .args(args)
+            .current_dir(dir.path())
+            .stdin(std::process::Stdio::piped())
+            .spawn()?;
+        std::io::Write::write_all(&mut child.stdin.take().expect("piped"), stdin.unwrap_or_default())?;
+        assert!(child.wait()?.success());
+        Ok(())
+    };
+    write_layer(Some(&nested))?;
+    write_layer(None)?;
+
+    let graphs_dir = dir.path().join(".git/objects/info/commit-graphs");
+    let chain = std::fs::read_to_string(graphs_dir.join("commit-graph-chain"))?;
+    let layers: Vec<_> = chain
+        .lines()
+        .map(|hash| graphs_dir.join(format!("graph-{hash}.graph")))
+        .collect();
+    assert_eq!(layers.len(), 2);
+
+    let commits = all_commits(dir.path());
+    let nested_commits: Vec<_> = commits
+        .iter()
+        .filter(|commit| {
+            commit.parents.is_empty() || commit.id == id(std::str::from_utf8(&nested).expect("ascii").trim())
+        })
+        .cloned()
+        .collect();
+    let mut base = Vec::new();
+    let outcome = file::File::write_to(nested_commits, None, &mut base, &AtomicBool::default(), options(true))?;
+    assert_eq!(outcome.num_commits, 2);
+    assert!(base == std::fs::read(&layers[0])?, "the base layer is the same");
+
+    let base = Graph::new(vec![file::File::at(&layers[0])?])?;
+    let mut top = Vec::new();
+    let outcome = file::File::write_to(
+        commits.clone(),
+        Some(&base),
+        &mut top,
+        &AtomicBool::default(),
+        options(true),
+    )?;
+    assert_eq!(
+        outcome.num_commits as usize,
+        commits.len() - 2,
+        "commits in the base are skipped"
+    );
+    assert!(
+        top == std::fs::read(&layers[1])?,
+        "the layer on top refers to the base and is the same as well"
+    );
+    assert_eq!(
+        format!("graph-{}.graph", outcome.checksum),
+        layers[1].file_name().expect("present").to_str().expect("valid")
+    );
+    Ok(())
+}
+
+#[test]
+fn parents_must_be_known() {
+    let commit = write::Commit {
+        id: gix_hash::ObjectId::null(gix_hash::Kind::Sha1),
+        tree: gix_hash::ObjectId::empty_tree(gix_hash::Kind::Sha1),
+        parents: vec![gix_hash::ObjectId::empty_blob(gix_hash::Kind::Sha1)],
+        committer_timestamp: 0,
+        bloom_filter: None,
+    };
+    assert!(matches!(
+        file::File::write_to(vec![commit], None, Vec::new(), &AtomicBool::default(), options(false)),
+        Err(write::Error::MissingParent { .. })
+    ));
+}
@@ -1,6 +1,6 @@
 use std::{borrow::Cow, convert::TryFrom};
 
-use bstr::BStr;
+use bstr::{BStr, ByteSlice};
 
 use crate::{file::MetadataFilter, value, File};
 
@@ -222,6 +222,78 @@ impl<'event> File<'event> {
         self.strings_filter(key.section_name, key.subsection_name, key.value_name, filter)
     }
 
+    /// Like [`string()`][File::string()], but splits the value at each `delimiter` into components with surrounding
+    /// whitespace removed, like `a, b,c` into `a`, `b` and `c`.
+    ///
+    /// Empty components, like the ones produced by trailing or consecutive delimiters as in `a,,b,`, are skipped unless
+    /// `keep_empty` is `true`, in which case the example yields `a`, ``, `b` and ``.
+    pub fn string_list(
+        &self,
+        section_name: impl AsRef<str>,
+        subsection_name: Option<&BStr>,
+        key: impl AsRef<str>,
+        delimiter: u8,
+        keep_empty: bool,
+    ) -> Option<Vec<Cow<'_, BStr>>> {
+        let value = self.string(section_name, subsection_name, key)?;
+        let mut out = Vec::new();
+        split_list(value, delimiter, keep_empty, &mut out);
+        Some(out)
+    }
+
+    /// Like [`string_list()`][File::string_list()], but suitable for statically known `key`s like `remote.origin.url`.
+    pub fn string_list_by_key<'a>(
+        &self,
+        key: impl Into<&'a BStr>,
+        delimiter: u8,
+        keep_empty: bool,
+    ) -> Option<Vec<Cow<'_, BStr>>> {
+        let key = crate::parse::key(key)?;
+        self.string_list(
+            key.section_name,
+            key.subsection_name,
+            key.value_name,
+            delimiter,
+            keep_empty,
+        )
+    }
+
+    /// Like [`string_list()`][File::string_list()], but considers all values of a multi-var, each split at `delimiter`,
+    /// and returns all of their components in order.
+    pub fn string_list_multi_value(
+        &self,
+        section_name: impl AsRef<str>,
+        subsection_name: Option<&BStr>,
+        key: impl AsRef<str>,
+        delimiter: u8,
+        keep_empty: bool,
+    ) -> Option<Vec<Cow<'_, BStr>>> {
+        let values = self.strings(section_name, subsection_name, key)?;
+        let mut out = Vec::new();
+        for value in values {
+            split_list(value, delimiter, keep_empty, &mut out);
+        }
+        Some(out)
+    }
+
+    /// Like [`string_list_multi_value()`][File::string_list_multi_value()], but suitable for statically known `key`s
+    /// like `remote.origin.url`.
+    pub fn string_list_multi_value_by_key<'a>(
+        &self,
+        key: impl Into<&'a BStr>,
+        delimiter: u8,
+        keep_empty: bool,
+    ) -> Option<Vec<Cow<'_, BStr>>> {
+        let key = crate::parse::key(key)?;
+        self.string_list_multi_value(
+            key.section_name,
+            key.subsection_name,
+            key.value_name,
+            delimiter,
+            keep_empty,
+        )
+    }
+
     /// Similar to [`values(…)`][File::values()] but returning integers if at least one of them was found
     /// and if none of them overflows.
     pub fn integers(
@@ -272,3 +344,23 @@ impl<'event> File<'event> {
         self.integers_filter(key.section_name, key.subsection_name, key.value_name, filter)
     }
 }
+
+fn split_list<'a>(value: Cow<'a, BStr>, delimiter: u8, keep_empty: bool, out: &mut Vec<Cow<'a, BStr>>) {
+    let keep = |component: &&[u8]| keep_empty || !component.is_empty();
+    match value {
+        Cow::Borrowed(value) => out.extend(
+            value
+                .split_str(&[delimiter])
+                .map(ByteSlice::trim)
+                .filter(keep)
+                .map(|component| Cow::Borrowed(component.as_bstr())),
+        ),
+        Cow::Owned(value) => out.extend(
+            value
+                .split_str(&[delimiter])
+                .map(ByteSlice::trim)
+                .filter(keep)
+                .map(|component| Cow::Owned(component.into())),
+        ),
+    }
+}
@@ -0,0 +1,70 @@
+use std::convert::TryFrom;
+
+use bstr::ByteSlice;
+
+use crate::{
+    file::{credential, MetadataFilter},
+    File,
+};
+
+/// Credential configuration
+impl<'event> File<'event> {
+    /// Return the `credential.helper`, `credential.username` and `credential.useHttpPath` configuration from
+    /// `credential` sections as well as from `credential.<url>` sections whose url matches `url`, all in order of
+    /// precedence, as git would do.
+    ///
+    /// See [`sections_by_name_and_url_filter()`][File::sections_by_name_and_url_filter()] for how `credential.<url>` sections are matched,
+    /// with the difference that paths of patterns must match exactly, and that if `url` has no user, a `username` picked up from
+    /// one section is used as user when matching all sections after it.
+    pub fn credential_config(&self, url: &gix_url::Url) -> Result<credential::CredentialConfig, credential::Error> {
+        self.credential_config_filter(url, &mut |_| true)
+    }
+
+    /// Like [`credential_config()`][File::credential_config()], but only consider sections for which `filter` returns `true`.
+    pub fn credential_config_filter(
+        &self,
+        url: &gix_url::Url,
+        filter: &mut MetadataFilter,
+    ) -> Result<credential::CredentialConfig, credential::Error> {
+        let mut out = credential::CredentialConfig::default();
+        let mut url = url.clone();
+        let url_had_user_initially = url.user().is_some();
+        super::url::normalize(&mut url);
+
+        let sections = match self.sections_by_name_and_filter("credential", filter) {
+            Some(sections) => sections,
+            None => return Ok(out),
+        };
+        for section in sections {
+            if let Some(pattern) = section.header().subsection_name() {
+                if !super::url::url_matches(pattern, &url).map_or(false, |m| m.path_is_exact_or_unspecified(&url)) {
+                    continue;
+                }
+            }
+            for value in section.values("helper") {
+                if value.trim().is_empty() {
+                    out.helpers.clear();
+                } else {
+                    out.helpers.push(value.into_owned());
+                }
+            }
+            if !url_had_user_initially {
+                if let Some(user) = section.value("username").filter(|n| !n.trim().is_empty()) {
+                    if let Ok(user) = user.to_str() {
+                        url.set_user(Some(user.to_owned()));
+                    }
+                    out.username = Some(user.into_owned());
+                }
+            }
+            if let Some(value) = section.value("useHttpPath") {
+                out.use_http_path = crate::Boolean::try_from(value)
+                    .map_err(|err| credential::Error::InvalidUseHttpPath {
+                        source: err,
+                        section: section.header().to_bstring(),
+                    })?
+                    .0;
+            }
+        }
+        Ok(out)
+    }
+}
@@ -1,4 +1,6 @@
 mod comfort;
+mod credential;
 mod mutate;
 mod raw;
 mod read_only;
+mod url;
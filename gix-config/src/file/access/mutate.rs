@@ -4,7 +4,7 @@ use bstr::BStr;
 use gix_features::threading::OwnShared;
 
 use crate::{
-    file::{self, rename_section, write::ends_with_newline, MetadataFilter, SectionBodyIdsLut, SectionId, SectionMut},
+    file::{self, rename_section, write::ends_with_newline, MetadataFilter, SectionId, SectionMut},
     lookup,
     parse::{section, Event, FrontMatterEvents},
     File,
@@ -224,29 +224,7 @@ impl<'event> File<'event> {
         self.section_order
             .remove(self.section_order.iter().position(|v| *v == id)?);
         let section = self.sections.remove(&id)?;
-        let lut = self
-            .section_lookup_tree
-            .get_mut(&section.header.name)
-            .expect("lookup cache still has name to be deleted");
-        // NOTE: this leaves empty lists in the data structure which our code now has to deal with.
-        for entry in lut {
-            match section.header.subsection_name.as_deref() {
-                Some(subsection_name) => {
-                    if let SectionBodyIdsLut::NonTerminal(map) = entry {
-                        if let Some(ids) = map.get_mut(subsection_name) {
-                            ids.remove(ids.iter().position(|v| *v == id).expect("present"));
-                            break;
-                        }
-                    }
-                }
-                None => {
-                    if let SectionBodyIdsLut::Terminal(ids) = entry {
-                        ids.remove(ids.iter().position(|v| *v == id).expect("present"));
-                        break;
-                    }
-                }
-            }
-        }
+        self.remove_section_id_from_lookup(id, &section.header);
         Some(section)
     }
 
@@ -301,8 +279,7 @@ impl<'event> File<'event> {
             .rev()
             .next()
             .expect("list of sections were empty, which violates invariant");
-        let section = self.sections.get_mut(&id).expect("known section-id");
-        section.header = section::Header::new(new_name, new_subsection_name)?;
+        self.set_section_header(id, section::Header::new(new_name, new_subsection_name)?);
         Ok(())
     }
 
@@ -324,11 +301,72 @@ impl<'event> File<'event> {
             .rev()
             .find(|id| filter(self.sections.get(id).expect("each id has a section").meta()))
             .ok_or(rename_section::Error::Lookup(lookup::existing::Error::KeyMissing))?;
-        let section = self.sections.get_mut(&id).expect("known section-id");
-        section.header = section::Header::new(new_name, new_subsection_name)?;
+        self.set_section_header(id, section::Header::new(new_name, new_subsection_name)?);
         Ok(())
     }
 
+    /// Merge all sections with `name` and `subsection_name` into the first one of them, and rename it to use `new_name`
+    /// and `new_subsection_name`, returning the id of the merged section.
+    ///
+    /// The bodies of all sections are concatenated in order of appearance, which retains all of their keys, values and comments,
+    /// and thus all values of multi-vars. The now empty duplicates are removed, and events between them and their successor,
+    /// like comments and whitespace, are retained.
+    /// The merged section keeps its [metadata][file::Metadata], but its trust is lowered to the lowest trust of all merged sections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::{borrow::Cow, convert::TryFrom};
+    /// let mut config = gix_config::File::try_from(
+    /// r#"[remote "origin"]
+    ///     url = https://example.com/repo
+    /// [core]
+    ///     bare = false
+    /// [remote "origin"]
+    ///     fetch = +refs/heads/*:refs/remotes/origin/*
+    /// "#)?;
+    ///
+    /// config.rename_and_merge_sections("remote", Some("origin".into()), "remote", Some(Cow::Borrowed("origin".into())))?;
+    /// assert_eq!(config.to_string(), "[remote \"origin\"]\n    url = https://example.com/repo\n    fetch = +refs/heads/*:refs/remotes/origin/*\n[core]\n    bare = false\n");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn rename_and_merge_sections<'a>(
+        &mut self,
+        name: impl AsRef<str>,
+        subsection_name: impl Into<Option<&'a BStr>>,
+        new_name: impl Into<Cow<'event, str>>,
+        new_subsection_name: impl Into<Option<Cow<'event, BStr>>>,
+    ) -> Result<SectionId, rename_section::Error> {
+        let header = section::Header::new(new_name, new_subsection_name)?;
+        let mut ids = self
+            .section_ids_by_name_and_subname(name.as_ref(), subsection_name.into())?
+            .collect::<Vec<_>>();
+        if ids.is_empty() {
+            return Err(lookup::existing::Error::SectionMissing.into());
+        }
+        let merged_id = ids.remove(0);
+        for id in ids {
+            self.move_frontmatter_post_section_to_predecessor(id);
+            let section = self.remove_section_by_id(id).expect("each id has a section");
+            let merged = self.sections.get_mut(&merged_id).expect("each id has a section");
+            if section.meta.trust < merged.meta.trust {
+                merged.meta = OwnShared::new(file::Metadata {
+                    trust: section.meta.trust,
+                    ..(*merged.meta).clone()
+                });
+            }
+            let mut events = section.body.0.into_iter().peekable();
+            if matches!(merged.body.0.last(), Some(Event::Newline(_)))
+                && matches!(events.peek(), Some(Event::Newline(_)))
+            {
+                events.next();
+            }
+            merged.body.0.extend(events);
+        }
+        self.set_section_header(merged_id, header);
+        Ok(merged_id)
+    }
+
     /// Append another File to the end of ourselves, without losing any information.
     pub fn append(&mut self, other: Self) -> &mut Self {
         self.append_or_insert(other, None)
@@ -0,0 +1,158 @@
+use std::borrow::Cow;
+
+use bstr::BStr;
+
+use crate::{
+    file::{self, MetadataFilter},
+    File,
+};
+
+/// Url matching
+impl<'event> File<'event> {
+    /// Return all sections named `name` without subsection, along with all `name.<url>` sections whose subsection is a url pattern
+    /// matching `url`, in the order they appear in, as long as `filter` returns `true` for their metadata.
+    ///
+    /// A url pattern matches if scheme, host and port are the same as in `url`, with the
+    /// host possibly containing glob patterns per dot-separated component, like `*.example.com`. If the pattern
+    /// has a user, it must match as well. If it has a path, it must be a prefix of the path of `url` that ends at a
+    /// path component boundary, so `https://host/org` matches `https://host/org/repo.git` but not `https://host/organization`.
+    /// Trailing slashes of paths are ignored, and patterns without a path match all paths.
+    pub fn sections_by_name_and_url_filter<'a>(
+        &'a self,
+        name: &'a str,
+        url: &gix_url::Url,
+        filter: &'a mut MetadataFilter,
+    ) -> Option<impl Iterator<Item = &'a file::Section<'event>> + 'a> {
+        let mut url = url.clone();
+        normalize(&mut url);
+        self.sections_by_name_and_filter(name, filter).map(move |sections| {
+            sections.filter(move |section| {
+                section
+                    .header()
+                    .subsection_name()
+                    .map_or(true, |pattern| url_matches(pattern, &url).is_some())
+            })
+        })
+    }
+
+    /// Return the value of `key` in the most specific of the sections returned by
+    /// [`sections_by_name_and_url_filter()`][File::sections_by_name_and_url_filter()], similar to how git reads `http.<url>.*`.
+    ///
+    /// A section whose pattern matches a longer host is more specific, then one matching a longer path, then one whose
+    /// pattern has a user. Sections without subsection are the least specific, and among equally specific sections
+    /// the last one wins.
+    pub fn string_by_url_filter(
+        &self,
+        section_name: &str,
+        key: &str,
+        url: &gix_url::Url,
+        filter: &mut MetadataFilter,
+    ) -> Option<Cow<'_, BStr>> {
+        let mut url = url.clone();
+        normalize(&mut url);
+        let mut best: Option<(Match, Cow<'_, BStr>)> = None;
+        for id in self.section_ids_by_name(section_name).ok()? {
+            let section = self
+                .sections
+                .get(&id)
+                .expect("section doesn't have id from from lookup");
+            if !filter(section.meta()) {
+                continue;
+            }
+            let specificity = match section.header().subsection_name() {
+                Some(pattern) => match url_matches(pattern, &url) {
+                    Some(specificity) => specificity,
+                    None => continue,
+                },
+                None => Match::default(),
+            };
+            if best.as_ref().map_or(false, |(best, _)| specificity < *best) {
+                continue;
+            }
+            if let Some(value) = section.value(key) {
+                best = Some((specificity, value));
+            }
+        }
+        best.map(|(_, value)| value)
+    }
+}
+
+/// How specifically a url pattern matched a url, with more specific matches comparing greater.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Match {
+    /// The length of the host in the pattern.
+    host_len: usize,
+    /// The length of the matched path including its implied trailing slash, so a pattern without path has a length of 1.
+    path_len: usize,
+    /// `true` if the pattern has a user.
+    user_matched: bool,
+}
+
+impl Match {
+    /// Return `true` if the pattern had a path that is the same as the one of the url, or no path at all.
+    pub(crate) fn path_is_exact_or_unspecified(&self, url: &gix_url::Url) -> bool {
+        self.path_len <= 1 || self.path_len == url.path.len() + 1
+    }
+}
+
+/// Return how specifically `pattern` matches the normalized `url`, or `None` if it doesn't match.
+pub(crate) fn url_matches(pattern: &bstr::BStr, url: &gix_url::Url) -> Option<Match> {
+    let (mut pattern, has_path) = match gix_url::parse(pattern) {
+        Ok(pattern) => (pattern, true),
+        // Patterns like `ssh://host` are valid even though they aren't valid urls to clone from.
+        Err(gix_url::parse::Error::MissingResourceLocation) => {
+            let mut pattern = pattern.to_owned();
+            pattern.push(b'/');
+            (gix_url::parse(pattern.as_ref()).ok()?, false)
+        }
+        Err(_) => return None,
+    };
+    normalize(&mut pattern);
+    let is_http = matches!(pattern.scheme, gix_url::Scheme::Https | gix_url::Scheme::Http);
+    let ports = if is_http {
+        (pattern.port_or_default(), url.port_or_default())
+    } else {
+        (pattern.port, url.port)
+    };
+    let path_len = if !has_path || pattern.path_is_root() {
+        1
+    } else {
+        path_prefix_len(&pattern.path, &url.path)?
+    };
+    let user_matches = pattern.user().is_none() || pattern.user() == url.user();
+    (user_matches && pattern.scheme == url.scheme && host_matches(pattern.host(), url.host()) && ports.0 == ports.1)
+        .then(|| Match {
+            host_len: pattern.host().map_or(0, str::len),
+            path_len,
+            user_matched: pattern.user().is_some(),
+        })
+}
+
+/// Return the length of `prefix` plus its implied trailing slash if it's a prefix of `path` ending at a path component boundary.
+fn path_prefix_len(prefix: &[u8], path: &[u8]) -> Option<usize> {
+    let rest = path.strip_prefix(prefix)?;
+    (rest.is_empty() || rest.starts_with(b"/")).then(|| prefix.len() + 1)
+}
+
+fn host_matches(pattern: Option<&str>, host: Option<&str>) -> bool {
+    match (pattern, host) {
+        (Some(pattern), Some(host)) => {
+            let lfields = pattern.split('.');
+            let rfields = host.split('.');
+            if lfields.clone().count() != rfields.clone().count() {
+                return false;
+            }
+            lfields
+                .zip(rfields)
+                .all(|(pat, value)| gix_glob::wildmatch(pat.into(), value.into(), gix_glob::wildmatch::Mode::empty()))
+        }
+        (None, None) => true,
+        (Some(_), None) | (None, Some(_)) => false,
+    }
+}
+
+pub(crate) fn normalize(url: &mut gix_url::Url) {
+    if !url.path_is_root() && url.path.ends_with(b"/") {
+        url.path.pop();
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, path::PathBuf};
 
 use crate::{
     file::{init, init::Options, Metadata},
@@ -18,6 +18,28 @@ pub enum Error {
     Init(#[from] init::Error),
 }
 
+/// What happened to a path when loading configuration files with [`File::from_paths_metadata_with_report()`].
+#[derive(Debug)]
+pub enum Status {
+    /// The file was read and its configuration is part of the returned `File`.
+    Loaded,
+    /// The file didn't exist and was skipped.
+    NotFound,
+    /// The same path was seen before and was skipped.
+    Duplicate,
+    /// The file couldn't be read and was skipped as [`Options::ignore_io_errors`] was set.
+    Io(std::io::Error),
+}
+
+/// A record of what happened to a single path when loading configuration files with [`File::from_paths_metadata_with_report()`].
+#[derive(Debug)]
+pub struct Report {
+    /// The path that was passed in, made absolute by prepending the current working directory if it was relative.
+    pub path: PathBuf,
+    /// What happened when trying to load `path`.
+    pub status: Status,
+}
+
 /// Instantiation from one or more paths
 impl File<'static> {
     /// Load the single file at `path` with `source` without following include directives.
@@ -73,6 +95,48 @@ impl File<'static> {
         err_on_non_existing_paths: bool,
         options: Options<'_>,
     ) -> Result<Option<Self>, Error> {
+        Self::from_paths_metadata_buf_inner(path_meta, buf, err_on_non_existing_paths, options, None)
+    }
+
+    /// Like [from_paths_metadata()][Self::from_paths_metadata()], but skips paths that don't exist and returns a [report][Report]
+    /// for each input path along with the `File`, telling whether it was loaded or why it was skipped.
+    ///
+    /// This is useful to understand why a certain configuration file isn't taking effect.
+    /// Note that IO errors other than missing files still abort the operation unless [`Options::ignore_io_errors`] is set.
+    pub fn from_paths_metadata_with_report(
+        path_meta: impl IntoIterator<Item = impl Into<Metadata>>,
+        options: Options<'_>,
+    ) -> Result<(Option<Self>, Vec<Report>), Error> {
+        let mut buf = Vec::with_capacity(512);
+        let mut report = Vec::new();
+        let err_on_nonexisting_paths = false;
+        let config = Self::from_paths_metadata_buf_inner(
+            path_meta,
+            &mut buf,
+            err_on_nonexisting_paths,
+            options,
+            Some(&mut report),
+        )?;
+        Ok((config, report))
+    }
+
+    fn from_paths_metadata_buf_inner(
+        path_meta: impl IntoIterator<Item = impl Into<Metadata>>,
+        buf: &mut Vec<u8>,
+        err_on_non_existing_paths: bool,
+        options: Options<'_>,
+        mut report: Option<&mut Vec<Report>>,
+    ) -> Result<Option<Self>, Error> {
+        let mut record = |path: &std::path::Path, status: Status| {
+            if let Some(report) = report.as_mut() {
+                let path = if path.is_relative() {
+                    std::env::current_dir().map_or_else(|_| path.to_owned(), |cwd| cwd.join(path))
+                } else {
+                    path.to_owned()
+                };
+                report.push(Report { path, status });
+            }
+        };
         let mut target = None;
         let mut seen = BTreeSet::default();
         for (path, mut meta) in path_meta.into_iter().filter_map(|meta| {
@@ -80,18 +144,26 @@ impl File<'static> {
             meta.path.take().map(|p| (p, meta))
         }) {
             if !seen.insert(path.clone()) {
+                record(&path, Status::Duplicate);
                 continue;
             }
 
             buf.clear();
-            match std::io::copy(
+            let status = match std::io::copy(
                 &mut match std::fs::File::open(&path) {
                     Ok(f) => f,
-                    Err(err) if !err_on_non_existing_paths && err.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(err) if !err_on_non_existing_paths && err.kind() == std::io::ErrorKind::NotFound => {
+                        record(&path, Status::NotFound);
+                        continue;
+                    }
                     Err(err) => {
                         let err = Error::Io { source: err, path };
                         if options.ignore_io_errors {
                             log::warn!("ignoring: {err:#?}");
+                            match err {
+                                Error::Io { source, path } => record(&path, Status::Io(source)),
+                                Error::Init(_) => unreachable!("we just created an IO error"),
+                            }
                             continue;
                         } else {
                             return Err(err);
@@ -100,22 +172,25 @@ impl File<'static> {
                 },
                 buf,
             ) {
-                Ok(_) => {}
+                Ok(_) => Status::Loaded,
                 Err(err) => {
                     if options.ignore_io_errors {
-                        log::warn!(
-                            "ignoring: {:#?}",
-                            Error::Io {
-                                source: err,
-                                path: path.clone()
-                            }
-                        );
+                        let err = Error::Io {
+                            source: err,
+                            path: path.clone(),
+                        };
+                        log::warn!("ignoring: {:#?}", err);
                         buf.clear();
+                        match err {
+                            Error::Io { source, .. } => Status::Io(source),
+                            Error::Init(_) => unreachable!("we just created an IO error"),
+                        }
                     } else {
                         return Err(Error::Io { source: err, path });
                     }
                 }
             };
+            record(&path, status);
             meta.path = Some(path);
 
             let config = Self::from_bytes_owned(buf, meta, options)?;
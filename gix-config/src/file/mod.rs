@@ -25,6 +25,35 @@ mod util;
 ///
 pub mod section;
 
+///
+pub mod credential {
+    use bstr::BString;
+
+    /// The error returned by [`File::credential_config(…)`][crate::File::credential_config()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not parse 'useHttpPath' key in section {section}")]
+        InvalidUseHttpPath {
+            section: BString,
+            source: crate::value::Error,
+        },
+    }
+
+    /// The `credential.*` and `credential.<url>.*` configuration applicable to a particular url.
+    #[derive(Debug, Default, Clone, PartialEq, Eq)]
+    pub struct CredentialConfig {
+        /// The values of all `helper` keys in order of precedence, with an empty value clearing all helpers seen before it.
+        ///
+        /// These are the definitions of programs to call, each to be interpreted by the credentials implementation.
+        pub helpers: Vec<BString>,
+        /// The last non-empty `username` value, only set if the url doesn't specify a user itself.
+        pub username: Option<BString>,
+        /// The value of the last `useHttpPath` key, or `false` if it wasn't set.
+        pub use_http_path: bool,
+    }
+}
+
 ///
 pub mod rename_section {
     /// The error returned by [`File::rename_section(…)`][crate::File::rename_section()].
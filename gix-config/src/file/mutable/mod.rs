@@ -8,13 +8,14 @@ pub(crate) mod multi_value;
 pub(crate) mod section;
 pub(crate) mod value;
 
-fn escape_value(value: &BStr) -> BString {
+/// Escape `value` according to git's rules, and quote it if required to preserve it or if `force_quote` is `true`.
+fn escape_value(value: &BStr, force_quote: bool) -> BString {
     let starts_with_whitespace = value.first().map_or(false, |b| b.is_ascii_whitespace());
     let ends_with_whitespace = value
         .get(value.len().saturating_sub(1))
         .map_or(false, |b| b.is_ascii_whitespace());
     let contains_comment_indicators = value.find_byteset(b";#").is_some();
-    let quote = starts_with_whitespace || ends_with_whitespace || contains_comment_indicators;
+    let quote = force_quote || starts_with_whitespace || ends_with_whitespace || contains_comment_indicators;
 
     let mut buf: BString = Vec::with_capacity(value.len()).into();
     if quote {
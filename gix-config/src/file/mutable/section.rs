@@ -22,6 +22,7 @@ use crate::{
 pub struct SectionMut<'a, 'event> {
     section: &'a mut Section<'event>,
     implicit_newline: bool,
+    quote_values: bool,
     whitespace: Whitespace<'event>,
     newline: SmallVec<[u8; 2]>,
 }
@@ -59,7 +60,7 @@ impl<'a, 'event> SectionMut<'a, 'event> {
         match value {
             Some(value) => {
                 body.extend(self.whitespace.key_value_separators());
-                body.push(Event::Value(escape_value(value).into()));
+                body.push(Event::Value(escape_value(value, self.quote_values).into()));
             }
             None => body.push(Event::Value(Cow::Borrowed("".into()))),
         }
@@ -133,10 +134,10 @@ impl<'a, 'event> SectionMut<'a, 'event> {
                 let value_range = value_range.unwrap_or(key_range.end - 1..key_range.end);
                 let range_start = value_range.start;
                 let ret = self.remove_internal(value_range, false);
-                self.section
-                    .body
-                    .0
-                    .insert(range_start, Event::Value(escape_value(value.into()).into()));
+                self.section.body.0.insert(
+                    range_start,
+                    Event::Value(escape_value(value.into(), self.quote_values).into()),
+                );
                 Some(ret)
             }
         }
@@ -171,6 +172,16 @@ impl<'a, 'event> SectionMut<'a, 'event> {
         self
     }
 
+    /// If `on` is `true`, all values written from now on will be enclosed in double-quotes, even if git wouldn't need
+    /// them to preserve the value. This is _disabled by default_, which quotes values only if they start or end with whitespace,
+    /// or contain comment characters.
+    ///
+    /// Note that escaping of special characters like `"` and `\` happens either way.
+    pub fn set_quote_values(&mut self, on: bool) -> &mut Self {
+        self.quote_values = on;
+        self
+    }
+
     /// Sets the exact whitespace to use before each newly created key-value pair,
     /// with only whitespace characters being permissible.
     ///
@@ -217,6 +228,7 @@ impl<'a, 'event> SectionMut<'a, 'event> {
         Self {
             section,
             implicit_newline: true,
+            quote_values: false,
             whitespace,
             newline,
         }
@@ -257,7 +269,7 @@ impl<'a, 'event> SectionMut<'a, 'event> {
         let mut size = 0;
 
         let body = &mut self.section.body.0;
-        body.insert(index.0, Event::Value(escape_value(value).into()));
+        body.insert(index.0, Event::Value(escape_value(value, self.quote_values).into()));
         size += 1;
 
         let sep_events = self.whitespace.key_value_separators();
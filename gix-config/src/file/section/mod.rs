@@ -76,61 +76,7 @@ impl<'a> Section<'a> {
     /// as it was parsed.
     pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
         self.header.write_to(&mut out)?;
-
-        if self.body.0.is_empty() {
-            return Ok(());
-        }
-
-        let nl = self
-            .body
-            .as_ref()
-            .iter()
-            .find_map(extract_newline)
-            .unwrap_or_else(|| platform_newline());
-
-        if !self
-            .body
-            .as_ref()
-            .iter()
-            .take_while(|e| !matches!(e, Event::SectionKey(_)))
-            .any(|e| e.to_bstr_lossy().contains_str(nl))
-        {
-            out.write_all(nl)?;
-        }
-
-        let mut saw_newline_after_value = true;
-        let mut in_key_value_pair = false;
-        for (idx, event) in self.body.as_ref().iter().enumerate() {
-            match event {
-                Event::SectionKey(_) => {
-                    if !saw_newline_after_value {
-                        out.write_all(nl)?;
-                    }
-                    saw_newline_after_value = false;
-                    in_key_value_pair = true;
-                }
-                Event::Newline(_) if !in_key_value_pair => {
-                    saw_newline_after_value = true;
-                }
-                Event::Value(_) | Event::ValueDone(_) => {
-                    in_key_value_pair = false;
-                }
-                _ => {}
-            }
-            event.write_to(&mut out)?;
-            if let Event::ValueNotDone(_) = event {
-                if self
-                    .body
-                    .0
-                    .get(idx + 1)
-                    .filter(|e| matches!(e, Event::Newline(_)))
-                    .is_none()
-                {
-                    out.write_all(nl)?;
-                }
-            }
-        }
-        Ok(())
+        write_body_to(self.body.as_ref(), out)
     }
 
     /// Return additional information about this sections origin.
@@ -143,3 +89,51 @@ impl<'a> Section<'a> {
         SectionMut::new(self, newline)
     }
 }
+
+/// Write the body `events` of a section to `out`, assuring that values are terminated by a newline.
+pub(crate) fn write_body_to(events: &[Event<'_>], mut out: impl std::io::Write) -> std::io::Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let nl = events
+        .iter()
+        .find_map(extract_newline)
+        .unwrap_or_else(|| platform_newline());
+
+    if !events
+        .iter()
+        .take_while(|e| !matches!(e, Event::SectionKey(_)))
+        .any(|e| e.to_bstr_lossy().contains_str(nl))
+    {
+        out.write_all(nl)?;
+    }
+
+    let mut saw_newline_after_value = true;
+    let mut in_key_value_pair = false;
+    for (idx, event) in events.iter().enumerate() {
+        match event {
+            Event::SectionKey(_) => {
+                if !saw_newline_after_value {
+                    out.write_all(nl)?;
+                }
+                saw_newline_after_value = false;
+                in_key_value_pair = true;
+            }
+            Event::Newline(_) if !in_key_value_pair => {
+                saw_newline_after_value = true;
+            }
+            Event::Value(_) | Event::ValueDone(_) => {
+                in_key_value_pair = false;
+            }
+            _ => {}
+        }
+        event.write_to(&mut out)?;
+        if let Event::ValueNotDone(_) = event {
+            if events.get(idx + 1).filter(|e| matches!(e, Event::Newline(_))).is_none() {
+                out.write_all(nl)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -113,6 +113,104 @@ impl<'event> File<'event> {
         new_section_id
     }
 
+    /// Remove `id` from the lookup table entry identified by `header`.
+    pub(crate) fn remove_section_id_from_lookup(&mut self, id: SectionId, header: &section::Header<'event>) {
+        let lut = self
+            .section_lookup_tree
+            .get_mut(&header.name)
+            .expect("lookup cache still has name to be deleted");
+        // NOTE: this leaves empty lists in the data structure which our code now has to deal with.
+        for entry in lut {
+            match header.subsection_name.as_deref() {
+                Some(subsection_name) => {
+                    if let SectionBodyIdsLut::NonTerminal(map) = entry {
+                        if let Some(ids) = map.get_mut(subsection_name) {
+                            ids.remove(ids.iter().position(|v| *v == id).expect("present"));
+                            break;
+                        }
+                    }
+                }
+                None => {
+                    if let SectionBodyIdsLut::Terminal(ids) = entry {
+                        ids.remove(ids.iter().position(|v| *v == id).expect("present"));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Set the `header` of the section with `id`, and update the lookup table to match, keeping the section order intact.
+    pub(crate) fn set_section_header(&mut self, id: SectionId, header: section::Header<'event>) {
+        let section = self.sections.get_mut(&id).expect("known section-id");
+        let previous_header = std::mem::replace(&mut section.header, header);
+        self.remove_section_id_from_lookup(id, &previous_header);
+
+        let section_order = &self.section_order;
+        let lookup_section_order = move |section_id| {
+            section_order
+                .iter()
+                .position(|id| *id == section_id)
+                .expect("section exists")
+        };
+        let order = lookup_section_order(id);
+        let header = &self.sections[&id].header;
+        let lookup = self.section_lookup_tree.entry(header.name.clone()).or_default();
+        let ids = match header.subsection_name.clone() {
+            Some(subsection_name) => {
+                let pos = lookup
+                    .iter()
+                    .position(|node| matches!(node, SectionBodyIdsLut::NonTerminal(_)))
+                    .unwrap_or_else(|| {
+                        lookup.push(SectionBodyIdsLut::NonTerminal(Default::default()));
+                        lookup.len() - 1
+                    });
+                match &mut lookup[pos] {
+                    SectionBodyIdsLut::NonTerminal(subsections) => subsections.entry(subsection_name).or_default(),
+                    SectionBodyIdsLut::Terminal(_) => unreachable!("found non-terminal above"),
+                }
+            }
+            None => {
+                let pos = lookup
+                    .iter()
+                    .position(|node| matches!(node, SectionBodyIdsLut::Terminal(_)))
+                    .unwrap_or_else(|| {
+                        lookup.push(SectionBodyIdsLut::Terminal(Vec::new()));
+                        lookup.len() - 1
+                    });
+                match &mut lookup[pos] {
+                    SectionBodyIdsLut::Terminal(ids) => ids,
+                    SectionBodyIdsLut::NonTerminal(_) => unreachable!("found terminal above"),
+                }
+            }
+        };
+        let insert_pos = find_insert_pos_by_order(ids, order, lookup_section_order);
+        ids.insert(insert_pos, id);
+    }
+
+    /// Move the events following the section with `id` to the end of the events following its preceding section,
+    /// or to the frontmatter if there is no preceding section.
+    pub(crate) fn move_frontmatter_post_section_to_predecessor(&mut self, id: SectionId) {
+        let events = match self.frontmatter_post_section.remove(&id) {
+            Some(events) => events,
+            None => return,
+        };
+        let predecessor = self
+            .section_order
+            .iter()
+            .position(|v| *v == id)
+            .and_then(|pos| pos.checked_sub(1))
+            .map(|pos| self.section_order[pos]);
+        match predecessor {
+            Some(predecessor) => self
+                .frontmatter_post_section
+                .entry(predecessor)
+                .or_default()
+                .extend(events),
+            None => self.frontmatter_events.extend(events),
+        }
+    }
+
     /// Returns the mapping between section and subsection name to section ids.
     pub(crate) fn section_ids_by_name_and_subname<'a>(
         &'a self,
@@ -1,6 +1,10 @@
 use bstr::{BStr, BString, ByteSlice};
 
-use crate::{file::Section, parse::Event, File};
+use crate::{
+    file::{Section, SectionId},
+    parse::Event,
+    File,
+};
 
 impl File<'_> {
     /// Serialize this type into a `BString` for convenience.
@@ -16,9 +20,29 @@ impl File<'_> {
     /// Stream ourselves to the given `out` in order to reproduce this file mostly losslessly
     /// as it was parsed, while writing only sections for which `filter` returns true.
     pub fn write_to_filter(
+        &self,
+        out: impl std::io::Write,
+        filter: impl FnMut(&Section<'_>) -> bool,
+    ) -> std::io::Result<()> {
+        self.write_sections(out, filter, false)
+    }
+
+    /// Stream ourselves to the given `out` with sections and keys sorted into a canonical order, which is useful
+    /// to produce reproducible output that is easy to compare.
+    ///
+    /// Sections are ordered by their case-insensitive name and their subsection name, and keys are ordered
+    /// case-insensitively within their section. The order is stable, so sections of the same name and the values
+    /// of multi-vars keep their relative order. Comments on the lines directly above a key and on its line
+    /// move along with it.
+    pub fn write_to_canonical(&self, out: impl std::io::Write) -> std::io::Result<()> {
+        self.write_sections(out, |_| true, true)
+    }
+
+    fn write_sections(
         &self,
         mut out: impl std::io::Write,
         mut filter: impl FnMut(&Section<'_>) -> bool,
+        canonical: bool,
     ) -> std::io::Result<()> {
         let nl = self.detect_newline_style();
 
@@ -33,8 +57,19 @@ impl File<'_> {
             }
         }
 
+        let mut section_order: Vec<SectionId> = self.section_order.iter().copied().collect();
+        if canonical {
+            section_order.sort_by_cached_key(|id| {
+                let header = &self.sections[id].header;
+                (
+                    header.name().to_ascii_lowercase(),
+                    header.subsection_name().map(ToOwned::to_owned),
+                )
+            });
+        }
+
         let mut prev_section_ended_with_newline = true;
-        for section_id in &self.section_order {
+        for section_id in &section_order {
             if !prev_section_ended_with_newline {
                 out.write_all(nl)?;
             }
@@ -42,9 +77,17 @@ impl File<'_> {
             if !filter(section) {
                 continue;
             }
-            section.write_to(&mut out)?;
+            let sorted_body;
+            let body = if canonical {
+                sorted_body = sort_keys(section.body.as_ref());
+                sorted_body.as_ref()
+            } else {
+                section.body.as_ref()
+            };
+            section.header.write_to(&mut out)?;
+            crate::file::section::write_body_to(body, &mut out)?;
 
-            prev_section_ended_with_newline = ends_with_newline(section.body.0.as_ref(), nl, false);
+            prev_section_ended_with_newline = ends_with_newline(body, nl, false);
             if let Some(post_matter) = self.frontmatter_post_section.get(section_id) {
                 if !prev_section_ended_with_newline {
                     out.write_all(nl)?;
@@ -70,6 +113,59 @@ impl File<'_> {
     }
 }
 
+/// Reorder the key-value pairs in `events` by their key, which compares case-insensitively, keeping the comments and whitespace
+/// preceding each key attached to it. Events on the line of the section header and after the last value stay in place.
+fn sort_keys<'a>(events: &[Event<'a>]) -> crate::parse::section::Events<'a> {
+    let mut head_end = 0;
+    for (idx, event) in events.iter().enumerate() {
+        match event {
+            Event::SectionKey(_) => break,
+            Event::Newline(_) => {
+                head_end = idx + 1;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let mut entries = Vec::new();
+    let mut entry_start = head_end;
+    let mut key = None;
+    let mut in_multi_line_value = false;
+    for (idx, event) in events.iter().enumerate().skip(head_end) {
+        match event {
+            Event::SectionKey(k) => {
+                if let Some(prev_key) = key.take() {
+                    entries.push((prev_key, entry_start..idx));
+                    entry_start = idx;
+                }
+                key = Some(k);
+            }
+            Event::ValueNotDone(_) => in_multi_line_value = true,
+            Event::Value(_) | Event::ValueDone(_) => in_multi_line_value = false,
+            Event::Newline(_) if !in_multi_line_value => {
+                if let Some(key) = key.take() {
+                    entries.push((key, entry_start..idx + 1));
+                    entry_start = idx + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(key) = key {
+        entries.push((key, entry_start..events.len()));
+        entry_start = events.len();
+    }
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut out: crate::parse::section::Events<'a> = events[..head_end].iter().cloned().collect();
+    for (_key, range) in entries {
+        out.extend(events[range].iter().cloned());
+    }
+    out.extend(events[entry_start..].iter().cloned());
+    out
+}
+
 pub(crate) fn ends_with_newline(e: &[crate::parse::Event<'_>], nl: impl AsRef<[u8]>, default: bool) -> bool {
     if e.is_empty() {
         return default;
@@ -67,6 +67,91 @@ mod rename_section {
         ));
     }
 }
+mod rename_and_merge_sections {
+    use std::{borrow::Cow, convert::TryFrom};
+
+    use gix_config::file;
+
+    #[test]
+    fn duplicates_are_merged_into_the_first_section_with_all_multivars() -> crate::Result {
+        let mut file = gix_config::File::try_from(
+            "[remote \"origin\"]\n\turl = https://example.com/repo\n\tfetch = +refs/heads/main:refs/remotes/origin/main\n# between\n[core]\n\tbare = false\n[remote \"origin\"]\n\t# a comment\n\tfetch = +refs/tags/*:refs/tags/*\n",
+        )?;
+        let id = file.rename_and_merge_sections(
+            "remote",
+            Some("origin".into()),
+            "remote",
+            Some(Cow::Borrowed("origin".into())),
+        )?;
+
+        assert_eq!(
+            file.to_string(),
+            "[remote \"origin\"]\n\turl = https://example.com/repo\n\tfetch = +refs/heads/main:refs/remotes/origin/main\n# between\n\t# a comment\n\tfetch = +refs/tags/*:refs/tags/*\n[core]\n\tbare = false\n"
+        );
+        assert_eq!(file.sections().count(), 2);
+        assert_eq!(
+            file.section("remote", Some("origin".into()))?.id(),
+            id,
+            "the merged section is the only one left"
+        );
+        assert_eq!(
+            file.strings("remote", Some("origin".into()), "fetch")
+                .expect("present")
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>(),
+            ["+refs/heads/main:refs/remotes/origin/main", "+refs/tags/*:refs/tags/*"],
+            "all fetch specs are retained in order"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn renaming_is_reflected_in_lookups() -> crate::Result {
+        let mut file = gix_config::File::try_from("[core]\n\ta = 1\n[other]\n[core]\n\ta = 2\n")?;
+        file.rename_and_merge_sections("core", None, "new-core", None)?;
+
+        assert!(file.section("core", None).is_err(), "the previous name is gone");
+        assert_eq!(
+            file.string("new-core", None, "a").expect("present").as_ref(),
+            "2",
+            "the last value still wins"
+        );
+        assert_eq!(file.to_string(), "[new-core]\n\ta = 1\n\ta = 2\n[other]\n");
+        Ok(())
+    }
+
+    #[test]
+    fn merged_section_has_the_lowest_trust() -> crate::Result {
+        let mut file = gix_config::File::try_from("[core]\n\ta = 1\n")?;
+        let reduced = file::Metadata {
+            trust: gix_sec::Trust::Reduced,
+            ..file::Metadata::api()
+        };
+        file.append(gix_config::File::from_bytes_no_includes(
+            b"[core]\n\ta = 2\n",
+            reduced,
+            Default::default(),
+        )?);
+        assert_eq!(file.sections().count(), 2);
+
+        file.rename_and_merge_sections("core", None, "core", None)?;
+        let section = file.section("core", None)?;
+        assert_eq!(section.meta().trust, gix_sec::Trust::Reduced);
+        assert_eq!(
+            section.meta().source,
+            file::Metadata::api().source,
+            "everything else is unchanged"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_sections_are_an_error() {
+        let mut file = gix_config::File::try_from("[core]\n").unwrap();
+        assert!(file.rename_and_merge_sections("remote", None, "remote", None).is_err());
+    }
+}
 mod set_meta {
     use gix_config::file;
 
@@ -237,6 +237,55 @@ fn single_section() {
     );
 }
 
+#[test]
+fn string_list() -> crate::Result {
+    let config = File::try_from(
+        r#"
+    [tool]
+        list = a, b ,,c,
+        quoted = " x ;y"
+        multi = 1,2
+        multi = ,3
+    "#,
+    )?;
+    let list = config.string_list("tool", None, "list", b',', false).expect("present");
+    assert_eq!(
+        list,
+        vec![cow_str("a"), cow_str("b"), cow_str("c")],
+        "empty components are skipped"
+    );
+    let list = config.string_list("tool", None, "list", b',', true).expect("present");
+    assert_eq!(
+        list,
+        vec![cow_str("a"), cow_str("b"), cow_str(""), cow_str("c"), cow_str("")],
+        "empty components are kept if desired, including the trailing one"
+    );
+    assert_eq!(
+        config.string_list_by_key("tool.quoted", b';', false).expect("present"),
+        vec![cow_str("x"), cow_str("y")],
+        "values with escapes or quotes work as well"
+    );
+    assert_eq!(
+        config.string_list("tool", None, "multi", b',', false).expect("present"),
+        vec![cow_str("3")],
+        "only the last value of a multi-var is considered"
+    );
+    assert_eq!(
+        config
+            .string_list_multi_value("tool", None, "multi", b',', false)
+            .expect("present"),
+        vec![cow_str("1"), cow_str("2"), cow_str("3")]
+    );
+    assert_eq!(
+        config
+            .string_list_multi_value_by_key("tool.multi", b',', true)
+            .expect("present"),
+        vec![cow_str("1"), cow_str("2"), cow_str(""), cow_str("3")]
+    );
+    assert!(config.string_list("tool", None, "missing", b',', false).is_none());
+    Ok(())
+}
+
 #[test]
 fn sections_by_name() -> crate::Result {
     let config = r#"
@@ -398,3 +447,142 @@ fn overrides_with_implicit_booleans_work_across_sections() {
         "empty implicit booleans "
     );
 }
+
+#[test]
+fn string_by_url_filter() -> crate::Result {
+    let config = File::try_from(
+        r#"
+    [http "https://user@host.example.com"]
+        value = user
+    [http "https://host.example.com/org/repo"]
+        value = repo
+    [http "https://host.example.com/org"]
+        value = org
+    [http "https://*.example.com/org/repo/sub"]
+        value = glob-deep-path
+    [http]
+        value = generic
+    [http "https://host.example.com/organization"]
+        value = organization
+    "#,
+    )?;
+    let value = |url: &str| -> crate::Result<Option<String>> {
+        Ok(config
+            .string_by_url_filter("http", "value", &gix_url::parse(url.into())?, &mut |_| true)
+            .map(|v| v.to_string()))
+    };
+
+    assert_eq!(
+        value("https://host.example.com/org/repo.git")?.as_deref(),
+        Some("org"),
+        "paths of patterns match as prefix at path component boundaries"
+    );
+    assert_eq!(
+        value("https://host.example.com/org/repo/")?.as_deref(),
+        Some("repo"),
+        "the longest matching path wins even if it comes first"
+    );
+    assert_eq!(
+        value("https://host.example.com/org/repo/sub")?.as_deref(),
+        Some("repo"),
+        "a longer host is more specific than a longer path"
+    );
+    assert_eq!(
+        value("https://other.example.com/org/repo/sub")?.as_deref(),
+        Some("glob-deep-path")
+    );
+    assert_eq!(
+        value("https://user@host.example.com/other")?.as_deref(),
+        Some("user"),
+        "a pattern with user beats one without, and generic values"
+    );
+    assert_eq!(
+        value("https://host.example.com/orga")?.as_deref(),
+        Some("generic"),
+        "prefixes must end at a path component boundary"
+    );
+    assert_eq!(
+        value("https://host.example.com/organization/repo")?.as_deref(),
+        Some("organization")
+    );
+    assert_eq!(value("ssh://host.example.com/org")?.as_deref(), Some("generic"));
+    Ok(())
+}
+
+#[test]
+fn credential_config() -> crate::Result {
+    let config = File::try_from(
+        r#"
+    [credential]
+        helper = global
+        username = global-user
+    [credential "https://*.example.com"]
+        helper = example
+    [credential "https://host.example.com/repo"]
+        helper =
+        helper = repo-only
+        useHttpPath = true
+    [credential "https://other.com"]
+        helper = other
+        username = other-user
+    [credential "ssh://git@host.example.com"]
+        helper = ssh
+    [credential "https://global-user@host.example.com"]
+        helper = global-user-only
+    "#,
+    )?;
+
+    let cfg = config.credential_config(&gix_url::parse("https://host.example.com/".into())?)?;
+    assert_eq!(
+        cfg.helpers,
+        vec!["global", "example", "global-user-only"],
+        "host globs are matched per component, and the configured username is used for matching subsequent sections"
+    );
+    assert_eq!(cfg.username, Some("global-user".into()));
+    assert!(!cfg.use_http_path);
+
+    let cfg = config.credential_config(&gix_url::parse("https://host.example.com/repo/".into())?)?;
+    assert_eq!(
+        cfg.helpers,
+        vec!["repo-only", "global-user-only"],
+        "an empty helper resets the list, and paths match if the pattern has one, ignoring trailing slashes"
+    );
+    assert!(cfg.use_http_path);
+
+    let cfg = config.credential_config(&gix_url::parse("https://sub.host.example.com".into())?)?;
+    assert_eq!(cfg.helpers, vec!["global"], "globs don't span multiple components");
+
+    let cfg = config.credential_config(&gix_url::parse("https://other.com:443/path".into())?)?;
+    assert_eq!(
+        cfg.helpers,
+        vec!["global", "other"],
+        "default ports are the same as no port"
+    );
+    assert_eq!(cfg.username, Some("other-user".into()));
+
+    let cfg = config.credential_config(&gix_url::parse("ssh://host.example.com/repo".into())?)?;
+    assert_eq!(cfg.helpers, vec!["global"], "the user must match if set in the pattern");
+    let cfg = config.credential_config(&gix_url::parse("ssh://git@host.example.com/repo".into())?)?;
+    assert_eq!(
+        cfg.helpers,
+        vec!["global", "ssh"],
+        "patterns without path match all paths"
+    );
+
+    let cfg = config.credential_config(&gix_url::parse("https://user@host.example.com".into())?)?;
+    assert_eq!(
+        cfg.helpers,
+        vec!["global", "example"],
+        "the user of the url takes precedence over the configured one"
+    );
+    assert_eq!(
+        cfg.username, None,
+        "the configured username isn't used if the url has one"
+    );
+
+    let config = File::try_from("[credential]\nuseHttpPath = nonsense")?;
+    assert!(config
+        .credential_config(&gix_url::parse("https://example.com".into())?)
+        .is_err());
+    Ok(())
+}
@@ -213,6 +213,45 @@ fn multiple_paths_multi_value_and_filter() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn report_tells_which_paths_were_loaded() -> crate::Result {
+    let dir = tempdir()?;
+
+    let a_path = dir.path().join("a");
+    fs::write(a_path.as_path(), b"[core]\na = true")?;
+    let missing_path = dir.path().join("missing");
+
+    let paths = vec![a_path.clone(), missing_path.clone(), a_path.clone()];
+    let (config, report) = File::from_paths_metadata_with_report(
+        paths
+            .into_iter()
+            .map(|p| gix_config::file::Metadata::from(Source::Local).at(p)),
+        Default::default(),
+    )?;
+    let config = config.expect("one file was loaded");
+    assert_eq!(config.boolean("core", None, "a"), Some(Ok(true)));
+    assert_eq!(config.sections().count(), 1, "duplicates aren't loaded again");
+
+    assert_eq!(
+        report.iter().map(|r| r.path.clone()).collect::<Vec<_>>(),
+        vec![a_path.clone(), missing_path, a_path],
+        "each input path is reported in order"
+    );
+    assert!(matches!(
+        report[0].status,
+        gix_config::file::init::from_paths::Status::Loaded
+    ));
+    assert!(matches!(
+        report[1].status,
+        gix_config::file::init::from_paths::Status::NotFound
+    ));
+    assert!(matches!(
+        report[2].status,
+        gix_config::file::init::from_paths::Status::Duplicate
+    ));
+    Ok(())
+}
+
 fn into_meta(paths: impl IntoIterator<Item = PathBuf>) -> impl IntoIterator<Item = gix_config::file::Metadata> {
     paths
         .into_iter()
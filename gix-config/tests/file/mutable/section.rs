@@ -93,6 +93,7 @@ mod set {
     use std::convert::TryInto;
 
     use super::multi_value_section;
+    use crate::file::cow_str;
 
     #[test]
     fn various_escapes_onto_various_kinds_of_values() -> crate::Result {
@@ -118,6 +119,21 @@ mod set {
         );
         Ok(())
     }
+
+    #[test]
+    fn values_round_trip_byte_exact() -> crate::Result {
+        for quote in [false, true] {
+            let value = "  ssh -i \"~/.ssh/id\" \\ \t ";
+            let mut config = gix_config::File::default();
+            let mut section = config.new_section("core", None)?;
+            section.set_quote_values(quote);
+            section.set("sshCommand".try_into()?, value);
+
+            let config: gix_config::File = config.to_string().parse()?;
+            assert_eq!(config.string("core", None, "sshCommand"), Some(cow_str(value)));
+        }
+        Ok(())
+    }
 }
 
 mod push {
@@ -200,6 +216,25 @@ mod push {
             assert_eq!(config.to_bstring(), expected);
         }
     }
+
+    #[test]
+    fn values_can_be_quoted_unconditionally() {
+        for (value, expected) in [
+            ("", "$head\tk = \"\"$nl"),
+            ("a b", "$head\tk = \"a b\"$nl"),
+            (" a b", "$head\tk = \" a b\"$nl"),
+            ("a \"b\"\\", "$head\tk = \"a \\\"b\\\"\\\\\"$nl"),
+        ] {
+            let mut config = gix_config::File::default();
+            let mut section = config.new_section("a", None).unwrap();
+            section.set_implicit_newline(false).set_quote_values(true);
+            section.push(Key::try_from("k").unwrap(), Some(value.into()));
+            let expected = expected
+                .replace("$head", &format!("[a]{nl}", nl = section.newline()))
+                .replace("$nl", &section.newline().to_string());
+            assert_eq!(config.to_bstring(), expected);
+        }
+    }
 }
 
 mod push_with_comment {
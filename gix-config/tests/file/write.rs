@@ -1,6 +1,6 @@
 use std::convert::TryFrom;
 
-use bstr::ByteVec;
+use bstr::{ByteSlice, ByteVec};
 use gix_config::file::{init, Metadata};
 
 #[test]
@@ -109,6 +109,23 @@ fn complex_lossless_roundtrip() {
     );
 }
 
+#[test]
+fn canonical_order_sorts_sections_and_keys_while_keeping_comments_and_multivars_in_place() {
+    let input = "[remote \"origin\"]\n\tfetch = one\n\turl = u\n\tfetch = two\n[Core] # side\n\t; the editor\n\teditor = vim\n\tbare = false ; not bare\n\tAbbrev = 7\n[alias]\n\tco = checkout\n";
+    let config = gix_config::File::try_from(input).unwrap();
+
+    let mut buf = Vec::<u8>::new();
+    config.write_to_canonical(&mut buf).unwrap();
+    let expected = "[alias]\n\tco = checkout\n[Core] # side\n\tAbbrev = 7\n\tbare = false ; not bare\n\t; the editor\n\teditor = vim\n[remote \"origin\"]\n\tfetch = one\n\tfetch = two\n\turl = u\n";
+    assert_eq!(buf.to_str_lossy(), expected);
+
+    let config = gix_config::File::try_from(expected).unwrap();
+    assert_eq!(config.to_bstring(), expected, "the source order is retained by default");
+    let mut buf = Vec::<u8>::new();
+    config.write_to_canonical(&mut buf).unwrap();
+    assert_eq!(buf.to_str_lossy(), expected, "canonical output is stable");
+}
+
 mod to_filter {
     use bstr::ByteSlice;
     use gix_config::file::Metadata;
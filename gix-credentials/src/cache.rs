@@ -0,0 +1,267 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use bstr::{BStr, BString};
+
+use crate::{program::main::Action, protocol::Context};
+
+/// Options for [`invoke()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The socket through which to talk to the daemon, or `None` to use the [default socket][default_socket()].
+    pub socket: Option<PathBuf>,
+    /// The duration for which the daemon keeps stored credentials, which is 15 minutes by default like in `git`.
+    pub timeout: Duration,
+    /// The shell command to start the daemon with when credentials are stored but the daemon isn't running, with the path to the
+    /// socket appended as argument. The daemon is expected to print `ok` once it accepts connections.
+    ///
+    /// It's `git credential-cache--daemon` by default, and can be replaced with a program calling [`daemon::serve()`] to avoid the need
+    /// for `git`. If `None`, credentials aren't stored unless the daemon is already running.
+    pub daemon: Option<BString>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            socket: None,
+            timeout: Duration::from_secs(900),
+            daemon: Some("git credential-cache--daemon".into()),
+        }
+    }
+}
+
+/// The error returned by [`invoke()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not communicate with the credential cache daemon at '{}'", socket.display())]
+    Connect { socket: PathBuf, source: std::io::Error },
+    #[error("Could not start the credential cache daemon with {command:?}")]
+    SpawnDaemon { command: BString, source: std::io::Error },
+    #[error("The credential cache daemon started with {command:?} didn't signal that it is ready")]
+    DaemonNotReady { command: BString },
+    #[error(transparent)]
+    Context(#[from] crate::protocol::context::decode::Error),
+    #[error(transparent)]
+    Url(#[from] crate::protocol::Error),
+}
+
+/// Return the socket `git credential-cache` uses by default, which is `~/.git-credential-cache/socket` if that directory exists,
+/// or `$XDG_CACHE_HOME/git/credential/socket`, with `XDG_CACHE_HOME` defaulting to `~/.cache`.
+pub fn default_socket() -> Option<PathBuf> {
+    let home = gix_path::home_dir();
+    if let Some(dir) = home
+        .as_ref()
+        .map(|home| home.join(".git-credential-cache"))
+        .filter(|dir| dir.is_dir())
+    {
+        return Some(dir.join("socket"));
+    }
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.map(|home| home.join(".cache")))
+        .map(|dir| dir.join("git").join("credential").join("socket"))
+}
+
+/// Perform `action` for the credentials described by `ctx` by talking to the credential cache daemon configured in `options`,
+/// the same way `git credential-cache` does, which also makes it compatible with the daemon of `git`.
+///
+/// Credentials are only returned for [`Action::Get`], and nothing is returned if the daemon isn't running.
+/// Use this function with [`program::main()`][crate::program::main()] to implement a credential helper program.
+#[allow(clippy::result_large_err)]
+pub fn invoke(action: Action, mut ctx: Context, options: &Options) -> Result<Option<Context>, Error> {
+    let socket = match options.socket.clone().or_else(default_socket) {
+        Some(socket) => socket,
+        None => return Ok(None),
+    };
+    ctx.destructure_url_if_needed()?;
+    let mut request = format!("action={}\ntimeout={}\n", action.as_str(), options.timeout.as_secs()).into_bytes();
+    ctx.write_to(&mut request).map_err(|source| Error::Connect {
+        socket: socket.clone(),
+        source,
+    })?;
+
+    let response = match send(&socket, &request) {
+        Ok(response) => response,
+        Err(err)
+            if matches!(
+                err.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            match (action, options.daemon.as_ref()) {
+                (Action::Store, Some(command)) => {
+                    spawn_daemon(command.as_ref(), &socket)?;
+                    send(&socket, &request).map_err(|source| Error::Connect {
+                        socket: socket.clone(),
+                        source,
+                    })?
+                }
+                _ => return Ok(None),
+            }
+        }
+        Err(source) => return Err(Error::Connect { socket, source }),
+    };
+    Ok(match action {
+        Action::Get => {
+            Some(Context::from_bytes(&response)?).filter(|ctx| ctx.username.is_some() || ctx.password.is_some())
+        }
+        Action::Store | Action::Erase => None,
+    })
+}
+
+fn send(socket: &Path, request: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.write_all(request)?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    Ok(response)
+}
+
+/// Start the daemon with `command` and wait until it signals that it accepts connections on `socket`.
+/// The daemon keeps running in the background.
+#[allow(clippy::result_large_err)]
+fn spawn_daemon(command: &BStr, socket: &Path) -> Result<(), Error> {
+    let spawn_err = |source| Error::SpawnDaemon {
+        command: command.to_owned(),
+        source,
+    };
+    let mut child = gix_command::prepare(gix_path::from_bstr(command).into_owned())
+        .with_shell()
+        .arg(socket)
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(spawn_err)?;
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().expect("configured"))
+        .read_line(&mut line)
+        .map_err(spawn_err)?;
+    if line.trim_end() != "ok" {
+        return Err(Error::DaemonNotReady {
+            command: command.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+///
+pub mod daemon {
+    use std::{
+        io::{Read, Write},
+        os::unix::net::{UnixListener, UnixStream},
+        path::Path,
+        sync::atomic::{AtomicBool, Ordering},
+        time::{Duration, Instant},
+    };
+
+    use bstr::ByteSlice;
+
+    use crate::protocol::Context;
+
+    /// The duration the daemon waits for credentials to be stored if it has none.
+    const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Accept connections on `socket` and serve the requests of [`invoke()`][super::invoke()] or `git credential-cache`,
+    /// keeping stored credentials in memory until they expire.
+    ///
+    /// `ready` is called once connections are accepted, which is where a daemon program would print `ok` to signal readiness.
+    /// The function returns once no credentials were stored for a while, an `exit` action is received, or `should_interrupt` is set.
+    pub fn serve(socket: &Path, should_interrupt: &AtomicBool, ready: impl FnOnce()) -> std::io::Result<()> {
+        if let Some(dir) = socket.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+        match std::fs::remove_file(socket) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        let listener = UnixListener::bind(socket)?;
+        listener.set_nonblocking(true)?;
+        ready();
+
+        let mut entries = Vec::<(Context, Instant)>::new();
+        let mut empty_since = Instant::now();
+        let res = loop {
+            let now = Instant::now();
+            entries.retain(|(_, expires_at)| *expires_at > now);
+            if !entries.is_empty() {
+                empty_since = now;
+            }
+            if should_interrupt.load(Ordering::Relaxed) || now.duration_since(empty_since) > IDLE_TIMEOUT {
+                break Ok(());
+            }
+            match listener.accept() {
+                Ok((stream, _addr)) => {
+                    if handle(stream, &mut entries) == Flow::Exit {
+                        break Ok(());
+                    }
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(50))
+                }
+                Err(err) => break Err(err),
+            }
+        };
+        std::fs::remove_file(socket).ok();
+        res
+    }
+
+    #[derive(PartialEq, Eq)]
+    enum Flow {
+        Continue,
+        Exit,
+    }
+
+    fn handle(mut stream: UnixStream, entries: &mut Vec<(Context, Instant)>) -> Flow {
+        let mut request = Vec::new();
+        if stream.set_nonblocking(false).is_err() || stream.read_to_end(&mut request).is_err() {
+            return Flow::Continue;
+        }
+        let (mut action, mut timeout) = (None, Duration::default());
+        for line in request.lines().take(2) {
+            if let Some(value) = line.strip_prefix(b"action=") {
+                action = Some(value);
+            } else if let Some(value) = line.strip_prefix(b"timeout=") {
+                timeout = value
+                    .to_str()
+                    .ok()
+                    .and_then(|secs| secs.parse().ok())
+                    .map_or(timeout, Duration::from_secs);
+            }
+        }
+        let query = match Context::from_bytes(&request) {
+            Ok(ctx) => ctx,
+            Err(_) => return Flow::Continue,
+        };
+        match action {
+            Some(b"get") => {
+                if let Some((entry, _)) = entries.iter().find(|(entry, _)| query.matches(entry)) {
+                    Context {
+                        username: entry.username.clone(),
+                        password: entry.password.clone(),
+                        ..Default::default()
+                    }
+                    .write_to(&mut stream)
+                    .ok();
+                }
+            }
+            Some(b"store") => {
+                entries.retain(|(entry, _)| !query.matches(entry));
+                entries.push((query, Instant::now() + timeout));
+            }
+            Some(b"erase") => entries.retain(|(entry, _)| !query.matches(entry)),
+            Some(b"exit") => return Flow::Exit,
+            _ => {}
+        }
+        stream.flush().ok();
+        Flow::Continue
+    }
+}
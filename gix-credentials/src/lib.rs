@@ -29,6 +29,13 @@ pub mod program;
 ///
 pub mod protocol;
 
+/// A native implementation of the `git credential-store` helper, keeping credentials in plain-text files.
+pub mod store;
+
+/// A native implementation of the `git credential-cache` helper, keeping credentials in memory of a daemon listening on a unix socket.
+#[cfg(unix)]
+pub mod cache;
+
 /// Call the `git credential` helper program performing the given `action`, which reads all context from the git configuration
 /// and does everything `git` typically does. The `action` should have been created with [`helper::Action::get_for_url()`] to
 /// contain only the URL to kick off the process, or should be created by [`helper::NextAction`].
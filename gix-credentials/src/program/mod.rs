@@ -1,6 +1,6 @@
 use std::process::{Command, Stdio};
 
-use bstr::{BString, ByteSlice, ByteVec};
+use bstr::{BStr, BString, ByteSlice, ByteVec};
 
 use crate::{helper, Program};
 
@@ -23,6 +23,11 @@ pub enum Kind {
     },
     /// A script to execute with `sh`.
     ExternalShellScript(BString),
+    /// The native implementation of `git credential-store`, which doesn't launch a program.
+    Store(crate::store::Options),
+    /// The native implementation of `git credential-cache`, which doesn't launch a program.
+    #[cfg(unix)]
+    Cache(crate::cache::Options),
 }
 
 /// Initialization
@@ -38,9 +43,13 @@ impl Program {
 
     /// Parse the given input as per the custom helper definition, supporting `!<script>`, `name` and `/absolute/name`, the latter two
     /// also support arguments which are ignored here.
+    ///
+    /// The `store` and `cache` helpers along with the arguments we understand are mapped to their [native][Kind::Store] [implementations][Kind::Cache].
     pub fn from_custom_definition(input: impl Into<BString>) -> Self {
         let mut input = input.into();
-        let kind = if input.starts_with(b"!") {
+        let kind = if let Some(kind) = native_kind(input.as_bstr()) {
+            kind
+        } else if input.starts_with(b"!") {
             input.remove(0);
             Kind::ExternalShellScript(input)
         } else {
@@ -81,6 +90,9 @@ impl Program {
     ) -> std::io::Result<(std::process::ChildStdin, Option<std::process::ChildStdout>)> {
         assert!(self.child.is_none(), "BUG: must not call `start()` twice");
         let mut cmd = match &self.kind {
+            Kind::Store(_) => unreachable!("BUG: native helpers are invoked without launching a program"),
+            #[cfg(unix)]
+            Kind::Cache(_) => unreachable!("BUG: native helpers are invoked without launching a program"),
             Kind::Builtin => {
                 let mut cmd = Command::new(cfg!(windows).then(|| "git.exe").unwrap_or("git"));
                 cmd.arg("credential").arg(action.as_arg(false));
@@ -126,6 +138,86 @@ impl Program {
     }
 }
 
+/// Parse `input` as invocation of the `store` or `cache` helper, or return `None` if it's something else or uses arguments or
+/// shell syntax we don't understand, to let the actual helper program deal with it.
+fn native_kind(input: &BStr) -> Option<Kind> {
+    if input.find_byteset(b"'\"\\$`").is_some() {
+        return None;
+    }
+    let mut tokens = input.split_str(" ").filter(|token| !token.is_empty());
+    let name = tokens.next()?;
+    let mut args = Vec::new();
+    while let Some(token) = tokens.next() {
+        let token = token.to_str().ok()?;
+        match token.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => args.push((flag, value.to_owned())),
+            _ if token.starts_with("--") => args.push((token, tokens.next()?.to_str().ok()?.to_owned())),
+            _ => return None,
+        }
+    }
+    let expand_home = |path: String| -> Option<std::path::PathBuf> {
+        match path.strip_prefix("~/") {
+            Some(rest) => gix_path::home_dir().map(|home| home.join(rest)),
+            None => Some(path.into()),
+        }
+    };
+    match name {
+        b"store" => {
+            let mut options = crate::store::Options::default();
+            for (flag, value) in args {
+                match flag {
+                    "--file" => options.files = vec![expand_home(value)?],
+                    _ => return None,
+                }
+            }
+            Some(Kind::Store(options))
+        }
+        #[cfg(unix)]
+        b"cache" => {
+            let mut options = crate::cache::Options::default();
+            for (flag, value) in args {
+                match flag {
+                    "--timeout" => options.timeout = std::time::Duration::from_secs(value.parse().ok()?),
+                    "--socket" => options.socket = Some(expand_home(value)?),
+                    _ => return None,
+                }
+            }
+            Some(Kind::Cache(options))
+        }
+        _ => None,
+    }
+}
+
+/// Perform `action` with the native helper implementation of `kind`, or return `None` if `kind` is an external program.
+pub(crate) fn invoke_native(kind: &Kind, action: &helper::Action) -> Option<Result<Option<Vec<u8>>, helper::Error>> {
+    fn to_helper_error(err: impl std::error::Error + Send + Sync + 'static) -> helper::Error {
+        helper::Error::CredentialsHelperFailed {
+            source: std::io::Error::new(std::io::ErrorKind::Other, err),
+        }
+    }
+    let (main_action, ctx) = match action {
+        helper::Action::Get(ctx) => (main::Action::Get, Ok(ctx.clone())),
+        helper::Action::Store(last) => (main::Action::Store, crate::protocol::Context::from_bytes(last)),
+        helper::Action::Erase(last) => (main::Action::Erase, crate::protocol::Context::from_bytes(last)),
+    };
+    let outcome = match kind {
+        Kind::Builtin | Kind::ExternalName { .. } | Kind::ExternalPath { .. } | Kind::ExternalShellScript(_) => {
+            return None
+        }
+        Kind::Store(options) => ctx
+            .map_err(helper::Error::from)
+            .and_then(|ctx| crate::store::invoke(main_action, ctx, options).map_err(to_helper_error)),
+        #[cfg(unix)]
+        Kind::Cache(options) => ctx
+            .map_err(helper::Error::from)
+            .and_then(|ctx| crate::cache::invoke(main_action, ctx, options).map_err(to_helper_error)),
+    };
+    Some(
+        outcome
+            .map(|ctx| matches!(main_action, main::Action::Get).then(|| ctx.unwrap_or_default().to_bstring().into())),
+    )
+}
+
 ///
 pub mod main;
 pub use main::function::main;
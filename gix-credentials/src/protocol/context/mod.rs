@@ -41,6 +41,18 @@ mod access {
                 None => format!("{field}: "),
             }
         }
+
+        /// Return `true` if `other` has the same protocol, host, path and username as we do, ignoring all fields we don't set,
+        /// the way `git` selects stored credentials.
+        pub(crate) fn matches(&self, other: &Context) -> bool {
+            fn check<T: PartialEq>(want: &Option<T>, have: &Option<T>) -> bool {
+                want.is_none() || want == have
+            }
+            check(&self.protocol, &other.protocol)
+                && check(&self.host, &other.host)
+                && check(&self.path, &other.path)
+                && check(&self.username, &other.username)
+        }
     }
 }
 
@@ -72,6 +84,20 @@ mod mutate {
             }
             Ok(self)
         }
+
+        /// Like [`destructure_url_in_place()`][Self::destructure_url_in_place()], without taking the path of http urls into account,
+        /// but only if the url is set and the protocol isn't known yet. A username we already have is kept if the url doesn't have one.
+        #[allow(clippy::result_large_err)]
+        pub(crate) fn destructure_url_if_needed(&mut self) -> Result<&mut Self, protocol::Error> {
+            if self.protocol.is_none() && self.url.is_some() {
+                let username = self.username.take();
+                self.destructure_url_in_place(false)?;
+                if self.username.is_none() {
+                    self.username = username;
+                }
+            }
+            Ok(self)
+        }
     }
 }
 
@@ -0,0 +1,70 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gix_credentials::{cache, program::main::Action, protocol::Context};
+
+#[test]
+fn store_get_and_erase_through_daemon() -> crate::Result {
+    let dir = gix_testtools::tempfile::tempdir()?;
+    let socket = dir.path().join("cache").join("socket");
+    let options = cache::Options {
+        socket: Some(socket.clone()),
+        daemon: None,
+        ..Default::default()
+    };
+    let url = |url: &str| Context {
+        url: Some(url.into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        cache::invoke(Action::Get, url("https://example.com"), &options)?,
+        None,
+        "nothing happens if the daemon isn't running"
+    );
+
+    let should_interrupt = AtomicBool::default();
+    std::thread::scope(|scope| -> crate::Result {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let daemon = scope.spawn(|| cache::daemon::serve(&socket, &should_interrupt, move || tx.send(()).unwrap()));
+        // Stop the daemon even if an assertion fails, as the scope waits for it before returning.
+        let _interrupt_daemon = InterruptOnDrop(&should_interrupt);
+        rx.recv()?;
+
+        cache::invoke(
+            Action::Store,
+            Context {
+                username: Some("user".into()),
+                password: Some("pass".into()),
+                ..url("https://example.com/repo")
+            },
+            &options,
+        )?;
+        let ctx = cache::invoke(Action::Get, url("https://example.com/repo"), &options)?.expect("stored");
+        assert_eq!(ctx.username.as_deref(), Some("user"));
+        assert_eq!(ctx.password.as_deref(), Some("pass"));
+        assert_eq!(
+            cache::invoke(Action::Get, url("https://other.com/repo"), &options)?,
+            None,
+            "hosts must match"
+        );
+
+        cache::invoke(Action::Erase, url("https://example.com/repo"), &options)?;
+        assert_eq!(
+            cache::invoke(Action::Get, url("https://example.com/repo"), &options)?,
+            None
+        );
+
+        drop(_interrupt_daemon);
+        daemon.join().expect("no panic")?;
+        Ok(())
+    })?;
+    assert!(!socket.exists(), "the socket is removed when the daemon stops");
+    Ok(())
+}
+
+struct InterruptOnDrop<'a>(&'a AtomicBool);
+
+impl Drop for InterruptOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
@@ -40,3 +40,36 @@ fn path() {
         matches!(Program::from_custom_definition(input).kind, Kind::ExternalPath{path_and_args} if path_and_args == input)
     );
 }
+
+#[test]
+fn store_is_native() {
+    assert!(matches!(Program::from_custom_definition("store").kind, Kind::Store(options) if options.files.is_empty()));
+    for input in ["store --file /abs/creds", "store --file=/abs/creds"] {
+        assert!(
+            matches!(Program::from_custom_definition(input).kind, Kind::Store(options) if options.files == [std::path::PathBuf::from("/abs/creds")])
+        );
+    }
+}
+
+#[test]
+fn store_with_unknown_arguments_or_quoting_is_external() {
+    for input in ["store --unknown", "store --file", "store --file \"with space\""] {
+        assert!(matches!(
+            Program::from_custom_definition(input).kind,
+            Kind::ExternalName { .. }
+        ));
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn cache_is_native() {
+    assert!(
+        matches!(Program::from_custom_definition("cache --timeout=60 --socket /abs/socket").kind, Kind::Cache(options)
+            if options.timeout == std::time::Duration::from_secs(60) && options.socket == Some("/abs/socket".into()))
+    );
+    assert!(matches!(
+        Program::from_custom_definition("cache --timeout=soon").kind,
+        Kind::ExternalName { .. }
+    ));
+}
@@ -0,0 +1,230 @@
+//! Three-way merges of text, similar to `git merge-file`.
+use std::ops::Range;
+
+use crate::blob::{diff, intern::InternedInput, sources::byte_lines_with_terminator, Algorithm, Sink};
+
+/// Determines how conflicts are written.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStyle {
+    /// Only write the conflicting lines of ours and theirs, moving lines they have in common at the beginning
+    /// and end of the conflict out of it.
+    #[default]
+    Merge,
+    /// Write the conflicting lines of ours and theirs along with the lines of the ancestor they are based on,
+    /// like the `diff3` conflict style.
+    Diff3,
+    /// Like [`Diff3`][ConflictStyle::Diff3], but lines that ours and theirs have in common at the beginning and end
+    /// of the conflict are moved out of it, like the `zdiff3` conflict style.
+    ZealousDiff3,
+}
+
+/// Options for use in [`text()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// The algorithm to use to find the changes of each side compared to the ancestor.
+    pub algorithm: Algorithm,
+    /// How to write conflicts.
+    pub conflict_style: ConflictStyle,
+    /// The amount of characters to use for conflict markers, like `7` for `<<<<<<<`.
+    pub marker_size: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            algorithm: Algorithm::Histogram,
+            conflict_style: ConflictStyle::default(),
+            marker_size: 7,
+        }
+    }
+}
+
+/// The labels to write after the conflict markers, typically the names of the revisions or files being merged.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Labels<'a> {
+    /// The label of the common ancestor, written after `|||||||` in the diff3 conflict styles.
+    pub ancestor: Option<&'a [u8]>,
+    /// The label of our side, written after `<<<<<<<`.
+    pub ours: Option<&'a [u8]>,
+    /// The label of their side, written after `>>>>>>>`.
+    pub theirs: Option<&'a [u8]>,
+}
+
+/// The outcome of [`text()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// The amount of conflicts that were written, or `0` if the merge was clean.
+    pub num_conflicts: usize,
+}
+
+/// Merge `ours` and `theirs`, which both are derived from `ancestor`, line by line and append the result to `out`.
+///
+/// Changes of only one side, or identical changes of both sides, are applied. Overlapping or adjacent changes that
+/// differ are written as conflict with markers as configured in `options`, using `labels` to identify each side.
+pub fn text(
+    out: &mut Vec<u8>,
+    ancestor: &[u8],
+    ours: &[u8],
+    theirs: &[u8],
+    labels: Labels<'_>,
+    options: Options,
+) -> Outcome {
+    let ancestor_lines = lines(ancestor);
+    let ours_lines = lines(ours);
+    let theirs_lines = lines(theirs);
+    let ours_hunks = hunks(options.algorithm, ancestor, ours);
+    let theirs_hunks = hunks(options.algorithm, ancestor, theirs);
+
+    let mut num_conflicts = 0;
+    let mut ancestor_pos = 0;
+    let (mut ours_idx, mut theirs_idx) = (0, 0);
+    loop {
+        let region_start = match (ours_hunks.get(ours_idx), theirs_hunks.get(theirs_idx)) {
+            (None, None) => break,
+            (Some(hunk), None) | (None, Some(hunk)) => hunk.ancestor.start,
+            (Some(ours), Some(theirs)) => ours.ancestor.start.min(theirs.ancestor.start),
+        };
+        let (ours_begin, theirs_begin) = (ours_idx, theirs_idx);
+        let mut region_end = region_start;
+        loop {
+            let mut extended = false;
+            for (hunks, idx) in [(&ours_hunks, &mut ours_idx), (&theirs_hunks, &mut theirs_idx)] {
+                while let Some(hunk) = hunks.get(*idx).filter(|hunk| hunk.ancestor.start <= region_end) {
+                    region_end = region_end.max(hunk.ancestor.end);
+                    *idx += 1;
+                    extended = true;
+                }
+            }
+            if !extended {
+                break;
+            }
+        }
+
+        write_lines(out, &ancestor_lines[ancestor_pos..region_start]);
+        let region = region_start..region_end;
+        let ours_region = side_region(&ours_hunks[ours_begin..ours_idx], &region).map(|r| &ours_lines[r]);
+        let theirs_region = side_region(&theirs_hunks[theirs_begin..theirs_idx], &region).map(|r| &theirs_lines[r]);
+        match (ours_region, theirs_region) {
+            (Some(ours), None) => write_lines(out, ours),
+            (None, Some(theirs)) => write_lines(out, theirs),
+            (Some(ours), Some(theirs)) if ours == theirs => write_lines(out, ours),
+            (Some(ours), Some(theirs)) => {
+                write_conflict(out, &ancestor_lines[region], ours, theirs, labels, options);
+                num_conflicts += 1;
+            }
+            (None, None) => unreachable!("a region always contains at least one hunk"),
+        }
+        ancestor_pos = region_end;
+    }
+    write_lines(out, &ancestor_lines[ancestor_pos..]);
+    Outcome { num_conflicts }
+}
+
+/// A change of one side, with line ranges in the ancestor and in the side.
+struct Hunk {
+    ancestor: Range<usize>,
+    side: Range<usize>,
+}
+
+struct Hunks(Vec<Hunk>);
+
+impl Sink for Hunks {
+    type Out = Vec<Hunk>;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        self.0.push(Hunk {
+            ancestor: before.start as usize..before.end as usize,
+            side: after.start as usize..after.end as usize,
+        });
+    }
+
+    fn finish(self) -> Self::Out {
+        self.0
+    }
+}
+
+fn hunks(algorithm: Algorithm, ancestor: &[u8], side: &[u8]) -> Vec<Hunk> {
+    let input = InternedInput::new(byte_lines_with_terminator(ancestor), byte_lines_with_terminator(side));
+    let mut hunks = diff(algorithm, &input, Hunks(Vec::new()));
+    hunks.sort_by_key(|hunk| hunk.ancestor.start);
+    hunks
+}
+
+/// Split `data` into lines the same way as the tokenizer does, with each line including its terminator.
+fn lines(data: &[u8]) -> Vec<&[u8]> {
+    data.split_inclusive(|b| *b == b'\n').collect()
+}
+
+/// Return the lines of a side that correspond to the `region` in the ancestor, or `None` if the side has no
+/// `hunks` in it and is thus unchanged.
+fn side_region(hunks: &[Hunk], region: &Range<usize>) -> Option<Range<usize>> {
+    let (first, last) = (hunks.first()?, hunks.last()?);
+    Some(first.side.start - (first.ancestor.start - region.start)..last.side.end + (region.end - last.ancestor.end))
+}
+
+fn write_conflict(
+    out: &mut Vec<u8>,
+    ancestor: &[&[u8]],
+    mut ours: &[&[u8]],
+    mut theirs: &[&[u8]],
+    labels: Labels<'_>,
+    options: Options,
+) {
+    let mut common_suffix: &[&[u8]] = &[];
+    if matches!(
+        options.conflict_style,
+        ConflictStyle::Merge | ConflictStyle::ZealousDiff3
+    ) {
+        let prefix_len = ours.iter().zip(theirs).take_while(|(a, b)| a == b).count();
+        write_lines(out, &ours[..prefix_len]);
+        ours = &ours[prefix_len..];
+        theirs = &theirs[prefix_len..];
+
+        let suffix_len = ours
+            .iter()
+            .rev()
+            .zip(theirs.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        common_suffix = &ours[ours.len() - suffix_len..];
+        ours = &ours[..ours.len() - suffix_len];
+        theirs = &theirs[..theirs.len() - suffix_len];
+    }
+
+    write_marker(out, b'<', options.marker_size, labels.ours);
+    write_conflict_lines(out, ours);
+    if matches!(
+        options.conflict_style,
+        ConflictStyle::Diff3 | ConflictStyle::ZealousDiff3
+    ) {
+        write_marker(out, b'|', options.marker_size, labels.ancestor);
+        write_conflict_lines(out, ancestor);
+    }
+    write_marker(out, b'=', options.marker_size, None);
+    write_conflict_lines(out, theirs);
+    write_marker(out, b'>', options.marker_size, labels.theirs);
+    write_lines(out, common_suffix);
+}
+
+fn write_marker(out: &mut Vec<u8>, marker: u8, size: usize, label: Option<&[u8]>) {
+    out.extend(std::iter::repeat(marker).take(size));
+    if let Some(label) = label {
+        out.push(b' ');
+        out.extend_from_slice(label);
+    }
+    out.push(b'\n');
+}
+
+fn write_lines(out: &mut Vec<u8>, lines: &[&[u8]]) {
+    for line in lines {
+        out.extend_from_slice(line);
+    }
+}
+
+/// Like [`write_lines()`], but assure the last line is terminated so the following marker starts on its own line.
+fn write_conflict_lines(out: &mut Vec<u8>, lines: &[&[u8]]) {
+    write_lines(out, lines);
+    if lines.last().map_or(false, |line| !line.ends_with(b"\n")) {
+        out.push(b'\n');
+    }
+}
@@ -1,2 +1,117 @@
-#[test]
-fn currently_there_is_no_api_surface_to_test_as_it_is_reexporting_imara_diff() {}
+mod merge {
+    use gix_diff::blob::merge::{text, ConflictStyle, Labels, Options, Outcome};
+
+    const ANCESTOR: &str = "a\nb\nc\nd\ne\n";
+
+    fn merge(ours: &str, theirs: &str, options: Options) -> (String, Outcome) {
+        let mut out = Vec::new();
+        let outcome = text(
+            &mut out,
+            ANCESTOR.as_bytes(),
+            ours.as_bytes(),
+            theirs.as_bytes(),
+            Labels {
+                ancestor: Some(b"base"),
+                ours: Some(b"ours"),
+                theirs: Some(b"theirs"),
+            },
+            options,
+        );
+        (String::from_utf8(out).expect("valid UTF-8 in and out"), outcome)
+    }
+
+    fn style(conflict_style: ConflictStyle) -> Options {
+        Options {
+            conflict_style,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn changes_of_either_side_are_combined() {
+        let (out, outcome) = merge("A\nb\nc\nd\ne\n", "a\nb\nc\nd\nE\nf\n", Default::default());
+        assert_eq!(out, "A\nb\nc\nd\nE\nf\n");
+        assert_eq!(outcome.num_conflicts, 0);
+    }
+
+    #[test]
+    fn identical_changes_are_applied_once() {
+        let (out, outcome) = merge("a\nB\nc\nd\ne\n", "a\nB\nc\nd\ne\n", Default::default());
+        assert_eq!(out, "a\nB\nc\nd\ne\n");
+        assert_eq!(outcome.num_conflicts, 0);
+    }
+
+    #[test]
+    fn unchanged_sides_yield_the_ancestor() {
+        let (out, outcome) = merge(ANCESTOR, ANCESTOR, Default::default());
+        assert_eq!(out, ANCESTOR);
+        assert_eq!(outcome.num_conflicts, 0);
+    }
+
+    #[test]
+    fn conflicts_in_each_style() {
+        let ours = "a\nX\nY\nd\ne\n";
+        let theirs = "a\nX\nZ\nd\ne\n";
+        for (conflict_style, expected) in [
+            (
+                ConflictStyle::Merge,
+                "a\nX\n<<<<<<< ours\nY\n=======\nZ\n>>>>>>> theirs\nd\ne\n",
+            ),
+            (
+                ConflictStyle::Diff3,
+                "a\n<<<<<<< ours\nX\nY\n||||||| base\nb\nc\n=======\nX\nZ\n>>>>>>> theirs\nd\ne\n",
+            ),
+            (
+                ConflictStyle::ZealousDiff3,
+                "a\nX\n<<<<<<< ours\nY\n||||||| base\nb\nc\n=======\nZ\n>>>>>>> theirs\nd\ne\n",
+            ),
+        ] {
+            let (out, outcome) = merge(ours, theirs, style(conflict_style));
+            assert_eq!(out, expected, "{conflict_style:?}");
+            assert_eq!(outcome.num_conflicts, 1);
+        }
+    }
+
+    #[test]
+    fn marker_size_and_labels_are_configurable() {
+        let mut out = Vec::new();
+        let outcome = text(
+            &mut out,
+            ANCESTOR.as_bytes(),
+            b"a\nb\nX\nd\ne\n",
+            b"a\nb\nY\nd\ne\n",
+            Labels::default(),
+            Options {
+                marker_size: 3,
+                ..Default::default()
+            },
+        );
+        assert_eq!(out, b"a\nb\n<<<\nX\n===\nY\n>>>\nd\ne\n");
+        assert_eq!(outcome.num_conflicts, 1);
+    }
+
+    #[test]
+    fn lines_without_trailing_newline_are_terminated_within_conflicts() {
+        let mut out = Vec::new();
+        let outcome = text(
+            &mut out,
+            b"a\nb",
+            b"a\nX",
+            b"a\nY",
+            Labels::default(),
+            Default::default(),
+        );
+        assert_eq!(out, b"a\n<<<<<<<\nX\n=======\nY\n>>>>>>>\n");
+        assert_eq!(outcome.num_conflicts, 1);
+    }
+
+    #[test]
+    fn each_conflicting_region_is_counted() {
+        let (out, outcome) = merge("X\nb\nc\nd\nX\n", "Y\nb\nc\nd\nY\n", Default::default());
+        assert_eq!(
+            out,
+            "<<<<<<< ours\nX\n=======\nY\n>>>>>>> theirs\nb\nc\nd\n<<<<<<< ours\nX\n=======\nY\n>>>>>>> theirs\n"
+        );
+        assert_eq!(outcome.num_conflicts, 2);
+    }
+}
@@ -0,0 +1,64 @@
+//! Run one-shot `clean` and `smudge` commands, which receive content on `stdin` and write the filtered content to `stdout`.
+use std::{
+    io::{Read, Write},
+    process::Stdio,
+};
+
+use bstr::{BStr, BString};
+
+/// The error returned by [`run()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not spawn filter command '{command}'")]
+    Spawn { command: BString, source: std::io::Error },
+    #[error("Could not exchange data with filter command '{command}'")]
+    Io { command: BString, source: std::io::Error },
+    #[error("Filter command '{command}' failed with {status}")]
+    Failed {
+        command: BString,
+        status: std::process::ExitStatus,
+    },
+}
+
+/// Run the shell `command` to filter `src` into `out`, after substituting `%f` with the quoted `rela_path` of the file.
+///
+/// `out` is cleared before use. Note that `src` is written from a separate thread to allow the command to produce output
+/// before it consumed all input, and commands which exit without reading all of their input aren't considered a failure.
+pub fn run(command: &BStr, rela_path: &BStr, src: &[u8], out: &mut Vec<u8>) -> Result<(), Error> {
+    let command = super::substitute_path(command, rela_path);
+    let mut child = gix_command::prepare(gix_path::from_bstr(command.as_ref()).into_owned().into_os_string())
+        .with_shell()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|source| Error::Spawn {
+            command: command.clone(),
+            source,
+        })?;
+    let mut stdin = child.stdin.take().expect("configured");
+    let mut stdout = child.stdout.take().expect("configured");
+
+    out.clear();
+    let (written, read) = std::thread::scope(|scope| {
+        // `stdin` is dropped when the thread ends, which signals the end of input to the command.
+        let writer = scope.spawn(move || match stdin.write_all(src) {
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+            res => res,
+        });
+        let read = stdout.read_to_end(out);
+        (writer.join().expect("no panic while writing"), read)
+    });
+    let status = child.wait();
+    let io_err = |source| Error::Io {
+        command: command.clone(),
+        source,
+    };
+    let status = status.map_err(io_err)?;
+    written.map_err(io_err)?;
+    read.map_err(io_err)?;
+    if !status.success() {
+        return Err(Error::Failed { command, status });
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use bstr::{BStr, BString, ByteSlice};
+
+///
+pub mod command;
+
+///
+pub mod process;
+
+/// Substitute `%f` in `command` with the quoted `rela_path`, and `%%` with `%`, while keeping all other placeholders as is.
+pub(crate) fn substitute_path(command: &BStr, rela_path: &BStr) -> BString {
+    let mut out = BString::from(Vec::with_capacity(command.len()));
+    let mut rest = command.as_bytes();
+    while let Some(pos) = rest.find_byte(b'%') {
+        out.extend_from_slice(&rest[..pos]);
+        match rest.get(pos + 1) {
+            Some(b'f') => out.extend_from_slice(&gix_quote::single(rela_path)),
+            Some(b'%') => out.push(b'%'),
+            Some(other) => out.extend_from_slice(&[b'%', *other]),
+            None => out.push(b'%'),
+        }
+        rest = &rest[(pos + 2).min(rest.len())..];
+    }
+    out.extend_from_slice(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::substitute_path;
+
+    #[test]
+    fn path_placeholders_are_substituted_with_the_quoted_path() {
+        assert_eq!(
+            substitute_path("filter --file=%f".into(), "dir/it's".into()),
+            "filter --file='dir/it'\\''s'"
+        );
+        assert_eq!(
+            substitute_path("%f %% %x %".into(), "a".into()),
+            "'a' % %x %",
+            "unknown placeholders are kept"
+        );
+    }
+}
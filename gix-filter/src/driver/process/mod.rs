@@ -0,0 +1,250 @@
+//! A client for filters that implement the [long-running filter protocol](https://git-scm.com/docs/gitattributes#_long_running_filter_process),
+//! which are started once to filter any amount of files.
+use std::{
+    io::{BufReader, BufWriter, Write},
+    process::{Child, ChildStdin, ChildStdout, Stdio},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::Operation;
+
+mod packetline;
+
+/// The error returned by [`Client::handshake()`] and [`Client::invoke()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not spawn filter process '{command}'")]
+    Spawn { command: BString, source: std::io::Error },
+    #[error("Could not exchange data with filter process")]
+    Io(#[from] std::io::Error),
+    #[error("The filter process sent {actual:?} when {expected:?} was expected")]
+    Protocol { expected: &'static str, actual: BString },
+    #[error("The filter process doesn't support version 2 of the protocol")]
+    UnsupportedVersion,
+    #[error("The filter process didn't send a status for '{path}'")]
+    MissingStatus { path: BString },
+}
+
+/// The capabilities a filter process announced during the handshake.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The process can [clean][Operation::Clean] content.
+    pub clean: bool,
+    /// The process can [smudge][Operation::Smudge] content.
+    pub smudge: bool,
+    /// The process can delay the delivery of smudged content.
+    pub delay: bool,
+}
+
+impl Capabilities {
+    /// Return `true` if `operation` is supported by the process.
+    pub fn supports(&self, operation: Operation) -> bool {
+        match operation {
+            Operation::Clean => self.clean,
+            Operation::Smudge => self.smudge,
+        }
+    }
+}
+
+/// The status of a single filter request as reported by the filter process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// The content was filtered successfully.
+    Success,
+    /// The filter will deliver the content later, see [`Client::list_available_blobs()`].
+    Delayed,
+    /// The content couldn't be filtered, but the process is ready to receive the next request.
+    Error,
+    /// The content couldn't be filtered, and the process doesn't want to receive any more requests for this operation.
+    Abort,
+    /// A status which isn't known to us.
+    Unknown(BString),
+}
+
+impl Status {
+    fn from_bytes(status: &[u8]) -> Self {
+        match status {
+            b"success" => Status::Success,
+            b"delayed" => Status::Delayed,
+            b"error" => Status::Error,
+            b"abort" => Status::Abort,
+            other => Status::Unknown(other.into()),
+        }
+    }
+}
+
+/// A running filter process which communicates with us via pkt-lines over its `stdin` and `stdout`.
+///
+/// The process is shut down when the client is dropped.
+pub struct Client {
+    child: Child,
+    input: Option<BufWriter<ChildStdin>>,
+    out: BufReader<ChildStdout>,
+    capabilities: Capabilities,
+    buf: Vec<u8>,
+}
+
+impl Client {
+    /// Spawn the shell `command` and perform the initial handshake, announcing all capabilities we support.
+    pub fn handshake(command: &BStr) -> Result<Self, Error> {
+        let mut child = gix_command::prepare(gix_path::from_bstr(command).into_owned().into_os_string())
+            .with_shell()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|source| Error::Spawn {
+                command: command.to_owned(),
+                source,
+            })?;
+        let mut client = Client {
+            input: Some(BufWriter::new(child.stdin.take().expect("configured"))),
+            out: BufReader::new(child.stdout.take().expect("configured")),
+            child,
+            capabilities: Default::default(),
+            buf: Vec::new(),
+        };
+
+        let input = client.input();
+        packetline::write_text(b"git-filter-client", input)?;
+        packetline::write_text(b"version=2", input)?;
+        packetline::write_flush(input)?;
+        input.flush()?;
+
+        client.expect_line("git-filter-server")?;
+        let mut has_version = false;
+        while let Some(line) = packetline::read_text(&mut client.out, &mut client.buf)? {
+            has_version |= line == b"version=2";
+        }
+        if !has_version {
+            return Err(Error::UnsupportedVersion);
+        }
+
+        let input = client.input();
+        for capability in ["clean", "smudge", "delay"] {
+            packetline::write_text(format!("capability={capability}").as_bytes(), input)?;
+        }
+        packetline::write_flush(input)?;
+        input.flush()?;
+
+        while let Some(line) = packetline::read_text(&mut client.out, &mut client.buf)? {
+            match line.strip_prefix(b"capability=") {
+                Some(b"clean") => client.capabilities.clean = true,
+                Some(b"smudge") => client.capabilities.smudge = true,
+                Some(b"delay") => client.capabilities.delay = true,
+                _ => {}
+            }
+        }
+        Ok(client)
+    }
+
+    /// Return the capabilities the process announced.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Ask the process to perform `operation` on `src`, the content of the file at `rela_path`, and write the result into `out`
+    /// which is cleared beforehand.
+    ///
+    /// If `can_delay` is `true` and the process has the `delay` capability, it may return [`Status::Delayed`] to deliver
+    /// the content later. To fetch it, call this method again with empty `src` once [`list_available_blobs()`][Self::list_available_blobs()]
+    /// contains `rela_path`, and `can_delay` set to `false`.
+    ///
+    /// Note that `out` only contains the complete result if [`Status::Success`] is returned.
+    pub fn invoke(
+        &mut self,
+        operation: Operation,
+        rela_path: &BStr,
+        src: &[u8],
+        can_delay: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<Status, Error> {
+        let can_delay = can_delay && self.capabilities.delay;
+        let input = self.input();
+        packetline::write_text(format!("command={}", operation.as_str()).as_bytes(), input)?;
+        let mut pathname = BString::from("pathname=");
+        pathname.extend_from_slice(rela_path);
+        packetline::write_text(&pathname, input)?;
+        if can_delay {
+            packetline::write_text(b"can-delay=1", input)?;
+        }
+        packetline::write_flush(input)?;
+        packetline::write_data(src, input)?;
+        packetline::write_flush(input)?;
+        input.flush()?;
+
+        out.clear();
+        let status = self.read_status()?.ok_or_else(|| Error::MissingStatus {
+            path: rela_path.to_owned(),
+        })?;
+        if status != Status::Success {
+            return Ok(status);
+        }
+        while let Some(data) = packetline::read(&mut self.out, &mut self.buf)? {
+            out.extend_from_slice(data);
+        }
+        // An empty list keeps the previous status.
+        Ok(self.read_status()?.unwrap_or(status))
+    }
+
+    /// Ask the process for the paths of all content whose delivery was delayed and which is now available.
+    ///
+    /// An empty list indicates that the process won't deliver any more delayed content.
+    pub fn list_available_blobs(&mut self) -> Result<Vec<BString>, Error> {
+        let input = self.input();
+        packetline::write_text(b"command=list_available_blobs", input)?;
+        packetline::write_flush(input)?;
+        input.flush()?;
+
+        let mut paths = Vec::new();
+        while let Some(line) = packetline::read_text(&mut self.out, &mut self.buf)? {
+            match line.strip_prefix(b"pathname=") {
+                Some(path) => paths.push(path.into()),
+                None => {
+                    return Err(Error::Protocol {
+                        expected: "pathname=<path>",
+                        actual: line.into(),
+                    })
+                }
+            }
+        }
+        match self.read_status()? {
+            Some(Status::Success) | None => Ok(paths),
+            Some(_) => Ok(Vec::new()),
+        }
+    }
+
+    fn input(&mut self) -> &mut BufWriter<ChildStdin> {
+        self.input.as_mut().expect("only taken on drop")
+    }
+
+    fn expect_line(&mut self, expected: &'static str) -> Result<(), Error> {
+        match packetline::read_text(&mut self.out, &mut self.buf)? {
+            Some(line) if line == expected.as_bytes() => Ok(()),
+            line => Err(Error::Protocol {
+                expected,
+                actual: line.map_or_else(|| "flush packet".into(), |line| line.as_bstr().to_owned()),
+            }),
+        }
+    }
+
+    /// Read a list of `key=value` pairs up to the next flush packet and return the value of the last `status` key, if present.
+    fn read_status(&mut self) -> Result<Option<Status>, Error> {
+        let mut status = None;
+        while let Some(line) = packetline::read_text(&mut self.out, &mut self.buf)? {
+            if let Some(value) = line.strip_prefix(b"status=") {
+                status = Some(Status::from_bytes(value));
+            }
+        }
+        Ok(status)
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        // Closing `stdin` tells the process to shut down.
+        drop(self.input.take());
+        self.child.wait().ok();
+    }
+}
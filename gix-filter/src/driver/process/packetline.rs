@@ -0,0 +1,60 @@
+//! A minimal, blocking implementation of the pkt-line format as needed by the long-running filter protocol.
+//!
+//! We don't use `gix-packetline` here as its `blocking-io` feature is mutually exclusive with `async-io`, and the latter
+//! may be needed by other crates in the same dependency graph.
+use std::io::{Read, Write};
+
+/// The maximum amount of bytes of data in a single packet line.
+const MAX_DATA_LEN: usize = 65516;
+
+/// Write `data` as a sequence of packet lines of the maximum size into `out`, without a trailing flush packet.
+///
+/// Note that nothing is written if `data` is empty.
+pub(crate) fn write_data(data: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+    for chunk in data.chunks(MAX_DATA_LEN) {
+        write!(out, "{:04x}", chunk.len() + 4)?;
+        out.write_all(chunk)?;
+    }
+    Ok(())
+}
+
+/// Write `line` followed by a newline as single packet line into `out`.
+pub(crate) fn write_text(line: &[u8], out: &mut impl Write) -> std::io::Result<()> {
+    write!(out, "{:04x}", line.len() + 5)?;
+    out.write_all(line)?;
+    out.write_all(b"\n")
+}
+
+/// Write a flush packet into `out`.
+pub(crate) fn write_flush(out: &mut impl Write) -> std::io::Result<()> {
+    out.write_all(b"0000")
+}
+
+/// Read a single packet line from `input` into `buf` and return its data, or `None` if it was a flush packet.
+pub(crate) fn read<'a>(input: &mut impl Read, buf: &'a mut Vec<u8>) -> std::io::Result<Option<&'a [u8]>> {
+    let mut hex_len = [0u8; 4];
+    input.read_exact(&mut hex_len)?;
+    let len = std::str::from_utf8(&hex_len)
+        .ok()
+        .and_then(|len| u16::from_str_radix(len, 16).ok())
+        .ok_or_else(|| invalid_data(format!("Invalid packet line length {:?}", bstr::BStr::new(&hex_len))))?
+        as usize;
+    match len {
+        0 => Ok(None),
+        1..=4 => Err(invalid_data(format!("Unexpected packet line length {len}"))),
+        _ => {
+            buf.resize(len - 4, 0);
+            input.read_exact(buf)?;
+            Ok(Some(buf.as_slice()))
+        }
+    }
+}
+
+/// Read a single packet line from `input` like [`read()`], but strip its trailing newline to obtain a text line.
+pub(crate) fn read_text<'a>(input: &mut impl Read, buf: &'a mut Vec<u8>) -> std::io::Result<Option<&'a [u8]>> {
+    Ok(read(input, buf)?.map(|line| line.strip_suffix(b"\n").unwrap_or(line)))
+}
+
+fn invalid_data(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
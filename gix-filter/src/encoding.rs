@@ -0,0 +1,231 @@
+use bstr::{BStr, BString, ByteSlice};
+
+/// The error returned by [`to_git()`] and [`to_worktree()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("BOM is prohibited in '{path}' if encoded as {encoding}")]
+    ProhibitedBom { path: BString, encoding: &'static str },
+    #[error("BOM is required in '{path}' if encoded as {encoding}")]
+    MissingBom { path: BString, encoding: &'static str },
+    #[error("Failed to encode '{path}' from {src} to {dest}")]
+    Conversion {
+        path: BString,
+        src: &'static str,
+        dest: &'static str,
+    },
+}
+
+/// An encoding that content can be converted to and from, as named by the `working-tree-encoding` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Encoding {
+    name: &'static str,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// UTF-16 or UTF-32, whose code units are `width` bytes wide.
+    Unicode {
+        width: usize,
+        /// If `None`, the byte order is determined by the BOM.
+        byte_order: Option<ByteOrder>,
+        bom: Bom,
+    },
+    Other(&'static encoding_rs::Encoding),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteOrder {
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bom {
+    /// The content must start with a BOM, which is also written.
+    Required,
+    /// The content must not start with a BOM, and none is written.
+    Prohibited,
+    /// The content may start with a BOM, which is always written.
+    Written,
+}
+
+const UTF8: &str = "UTF-8";
+
+impl Encoding {
+    /// Return the encoding for `label`, like `UTF-16` or `ISO-8859-1`, matched case-insensitively, or `None` if it is unknown.
+    ///
+    /// Besides the encodings known to the [WHATWG Encoding Standard](https://encoding.spec.whatwg.org), `UTF-16` and `UTF-32`
+    /// are supported along with their `LE` and `BE` variants, and `UTF-16LE-BOM`. Just like in git, `UTF-16` and `UTF-32` must
+    /// start with a byte order mark, and their variants with explicit byte order must not.
+    pub fn for_label(label: &BStr) -> Option<Self> {
+        let upper = label.trim().to_ascii_uppercase();
+        if let Some(rest) = upper.strip_prefix(b"UTF") {
+            let rest = rest.strip_prefix(b"-").unwrap_or(rest);
+            let unicode = |name, width, byte_order, bom| Encoding {
+                name,
+                kind: Kind::Unicode { width, byte_order, bom },
+            };
+            match rest {
+                b"8" => {
+                    return Some(Encoding {
+                        name: UTF8,
+                        kind: Kind::Other(encoding_rs::UTF_8),
+                    })
+                }
+                b"16" => return Some(unicode("UTF-16", 2, None, Bom::Required)),
+                b"16LE" => return Some(unicode("UTF-16LE", 2, Some(ByteOrder::Little), Bom::Prohibited)),
+                b"16BE" => return Some(unicode("UTF-16BE", 2, Some(ByteOrder::Big), Bom::Prohibited)),
+                b"16LE-BOM" => return Some(unicode("UTF-16LE-BOM", 2, Some(ByteOrder::Little), Bom::Written)),
+                b"32" => return Some(unicode("UTF-32", 4, None, Bom::Required)),
+                b"32LE" => return Some(unicode("UTF-32LE", 4, Some(ByteOrder::Little), Bom::Prohibited)),
+                b"32BE" => return Some(unicode("UTF-32BE", 4, Some(ByteOrder::Big), Bom::Prohibited)),
+                _ => {}
+            }
+        }
+        encoding_rs::Encoding::for_label(&upper)
+            .filter(|encoding| encoding.output_encoding() == *encoding)
+            .map(|encoding| Encoding {
+                name: encoding.name(),
+                kind: Kind::Other(encoding),
+            })
+    }
+
+    /// Return the canonical name of the encoding.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Return `true` if this is UTF-8, the encoding used in git, which means no conversion is necessary.
+    pub fn is_utf8(&self) -> bool {
+        self.name == UTF8
+    }
+}
+
+/// Decode `src`, the content of the file at `rela_path` in the worktree, from `encoding` and write it as UTF-8 into `out`,
+/// for storage in git.
+pub fn to_git(src: &[u8], rela_path: &BStr, encoding: Encoding, out: &mut Vec<u8>) -> Result<(), Error> {
+    let conversion_error = || Error::Conversion {
+        path: rela_path.to_owned(),
+        src: encoding.name,
+        dest: UTF8,
+    };
+    out.clear();
+    match encoding.kind {
+        Kind::Unicode { width, byte_order, bom } => {
+            let bom_order = byte_order_from_bom(src, width);
+            match (bom, bom_order) {
+                (Bom::Required, None) => {
+                    return Err(Error::MissingBom {
+                        path: rela_path.to_owned(),
+                        encoding: encoding.name,
+                    })
+                }
+                (Bom::Prohibited, Some(_)) => {
+                    return Err(Error::ProhibitedBom {
+                        path: rela_path.to_owned(),
+                        encoding: encoding.name,
+                    })
+                }
+                _ => {}
+            }
+            let byte_order = match (byte_order, bom_order) {
+                (Some(byte_order), _) => byte_order,
+                (None, Some(byte_order)) => byte_order,
+                (None, None) => unreachable!("a BOM is required if there is no byte order"),
+            };
+            let src = if bom_order.is_some() { &src[width..] } else { src };
+            let decoded = decode_unicode(src, width, byte_order).ok_or_else(conversion_error)?;
+            out.extend_from_slice(decoded.as_bytes());
+        }
+        Kind::Other(encoding) => {
+            let decoded = encoding
+                .decode_without_bom_handling_and_without_replacement(src)
+                .ok_or_else(conversion_error)?;
+            out.extend_from_slice(decoded.as_bytes());
+        }
+    }
+    Ok(())
+}
+
+/// Encode `src`, the UTF-8 content of the file at `rela_path` as stored in git, into `encoding` and write the result into `out`,
+/// for use in the worktree.
+pub fn to_worktree(src: &[u8], rela_path: &BStr, encoding: Encoding, out: &mut Vec<u8>) -> Result<(), Error> {
+    let conversion_error = || Error::Conversion {
+        path: rela_path.to_owned(),
+        src: UTF8,
+        dest: encoding.name,
+    };
+    let src = std::str::from_utf8(src).map_err(|_| conversion_error())?;
+    out.clear();
+    match encoding.kind {
+        Kind::Unicode { width, byte_order, bom } => {
+            // Like git on most platforms, we write little-endian if the byte order is determined by the BOM.
+            let byte_order = byte_order.unwrap_or(ByteOrder::Little);
+            let mut push_unit = |unit: u32| {
+                let bytes = match byte_order {
+                    ByteOrder::Little => unit.to_le_bytes(),
+                    ByteOrder::Big => unit.to_be_bytes(),
+                };
+                match (width, byte_order) {
+                    (2, ByteOrder::Little) => out.extend_from_slice(&bytes[..2]),
+                    (2, ByteOrder::Big) => out.extend_from_slice(&bytes[2..]),
+                    _ => out.extend_from_slice(&bytes),
+                }
+            };
+            if bom != Bom::Prohibited {
+                push_unit(0xfeff);
+            }
+            if width == 2 {
+                src.encode_utf16().for_each(|unit| push_unit(unit.into()));
+            } else {
+                src.chars().for_each(|c| push_unit(c.into()));
+            }
+        }
+        Kind::Other(encoding) => {
+            let (encoded, _, had_unmappable_characters) = encoding.encode(src);
+            if had_unmappable_characters {
+                return Err(conversion_error());
+            }
+            out.extend_from_slice(&encoded);
+        }
+    }
+    Ok(())
+}
+
+fn byte_order_from_bom(src: &[u8], width: usize) -> Option<ByteOrder> {
+    match width {
+        2 if src.starts_with(&[0xff, 0xfe]) => Some(ByteOrder::Little),
+        2 if src.starts_with(&[0xfe, 0xff]) => Some(ByteOrder::Big),
+        4 if src.starts_with(&[0xff, 0xfe, 0, 0]) => Some(ByteOrder::Little),
+        4 if src.starts_with(&[0, 0, 0xfe, 0xff]) => Some(ByteOrder::Big),
+        _ => None,
+    }
+}
+
+fn decode_unicode(src: &[u8], width: usize, byte_order: ByteOrder) -> Option<String> {
+    if src.len() % width != 0 {
+        return None;
+    }
+    let units = src.chunks_exact(width).map(|unit| {
+        let mut bytes = [0; 4];
+        match byte_order {
+            ByteOrder::Little => {
+                bytes[..width].copy_from_slice(unit);
+                u32::from_le_bytes(bytes)
+            }
+            ByteOrder::Big => {
+                bytes[4 - width..].copy_from_slice(unit);
+                u32::from_be_bytes(bytes)
+            }
+        }
+    });
+    if width == 2 {
+        char::decode_utf16(units.map(|unit| unit as u16))
+            .collect::<Result<String, _>>()
+            .ok()
+    } else {
+        units.map(char::from_u32).collect()
+    }
+}
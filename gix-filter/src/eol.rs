@@ -0,0 +1,283 @@
+use gix_attributes::StateRef;
+
+/// The value of `core.autocrlf`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AutoCrlf {
+    /// `input`: convert `CRLF` to `LF` when adding files to git, but never the other way around.
+    Input,
+    /// `true`: convert `LF` to `CRLF` on checkout and back again when adding files, if they appear to be text.
+    Enabled,
+    /// `false`: don't convert line endings unless the attributes of a path ask for it.
+    #[default]
+    Disabled,
+}
+
+/// The line ending to use in the worktree, as set by `core.eol` or the `eol` attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Use `LF` in the worktree.
+    Lf,
+    /// Use `CRLF` in the worktree.
+    CrLf,
+}
+
+impl Mode {
+    /// Return the line ending that is native to the platform we are compiled for, which is what `core.eol=native` means.
+    pub fn native() -> Self {
+        if cfg!(windows) {
+            Mode::CrLf
+        } else {
+            Mode::Lf
+        }
+    }
+}
+
+/// The configuration affecting the conversion of line endings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Configuration {
+    /// The value of `core.autocrlf`.
+    pub auto_crlf: AutoCrlf,
+    /// The value of `core.eol`, with `None` meaning it's unset, which is the same as `native`.
+    pub eol: Option<Mode>,
+}
+
+/// The line ending conversion of a path as determined by its `text`, `crlf` and `eol` attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AttributesDigest {
+    /// `-text`: the content is never converted.
+    Binary,
+    /// `text`: the content is text, and line endings in the worktree are controlled by the [`Configuration`].
+    Text,
+    /// `text eol=lf`, `eol=lf` or `crlf=input`: the content is text, and uses `LF` in the worktree.
+    TextInput,
+    /// `text eol=crlf` or `eol=crlf`: the content is text, and uses `CRLF` in the worktree.
+    TextCrlf,
+    /// `text=auto`: the content is converted if it appears to be text, and line endings in the worktree are controlled by
+    /// the [`Configuration`].
+    TextAuto,
+    /// `text=auto eol=lf`: the content is converted if it appears to be text, and uses `LF` in the worktree.
+    TextAutoInput,
+    /// `text=auto eol=crlf`: the content is converted if it appears to be text, and uses `CRLF` in the worktree.
+    TextAutoCrlf,
+}
+
+impl AttributesDigest {
+    /// Compute the digest from the state of the `text`, `crlf` and `eol` attributes of a path, or return `None`
+    /// if they don't specify how to convert it.
+    ///
+    /// Note that the `text` attribute takes precedence over the deprecated `crlf` attribute.
+    pub fn from_states(text: StateRef<'_>, crlf: StateRef<'_>, eol: StateRef<'_>) -> Option<Self> {
+        fn from_text(state: StateRef<'_>) -> Option<AttributesDigest> {
+            match state {
+                StateRef::Set => Some(AttributesDigest::Text),
+                StateRef::Unset => Some(AttributesDigest::Binary),
+                StateRef::Value(value) if value == "input" => Some(AttributesDigest::TextInput),
+                StateRef::Value(value) if value == "auto" => Some(AttributesDigest::TextAuto),
+                StateRef::Value(_) | StateRef::Unspecified => None,
+            }
+        }
+        let digest = from_text(text).or_else(|| from_text(crlf));
+        if digest == Some(AttributesDigest::Binary) {
+            return digest;
+        }
+        let eol = match eol {
+            StateRef::Value(value) if value == "lf" => Some(Mode::Lf),
+            StateRef::Value(value) if value == "crlf" => Some(Mode::CrLf),
+            _ => None,
+        };
+        match (digest, eol) {
+            (Some(AttributesDigest::TextAuto), Some(Mode::Lf)) => Some(AttributesDigest::TextAutoInput),
+            (Some(AttributesDigest::TextAuto), Some(Mode::CrLf)) => Some(AttributesDigest::TextAutoCrlf),
+            (_, Some(Mode::Lf)) => Some(AttributesDigest::TextInput),
+            (_, Some(Mode::CrLf)) => Some(AttributesDigest::TextCrlf),
+            (digest, None) => digest,
+        }
+    }
+
+    fn is_auto(&self) -> bool {
+        matches!(
+            self,
+            AttributesDigest::TextAuto | AttributesDigest::TextAutoInput | AttributesDigest::TextAutoCrlf
+        )
+    }
+}
+
+impl Configuration {
+    /// Return `true` if files that are known to be text should use `CRLF` in the worktree.
+    fn text_eol_is_crlf(&self) -> bool {
+        match self.auto_crlf {
+            AutoCrlf::Enabled => true,
+            AutoCrlf::Input => false,
+            AutoCrlf::Disabled => self.eol.unwrap_or_else(Mode::native) == Mode::CrLf,
+        }
+    }
+
+    /// Apply the configuration to `digest` to learn how to convert a path.
+    fn resolve(&self, digest: Option<AttributesDigest>) -> AttributesDigest {
+        match digest {
+            Some(AttributesDigest::Text) => {
+                if self.text_eol_is_crlf() {
+                    AttributesDigest::TextCrlf
+                } else {
+                    AttributesDigest::TextInput
+                }
+            }
+            Some(digest) => digest,
+            None => match self.auto_crlf {
+                AutoCrlf::Disabled => AttributesDigest::Binary,
+                AutoCrlf::Enabled => AttributesDigest::TextAutoCrlf,
+                AutoCrlf::Input => AttributesDigest::TextAutoInput,
+            },
+        }
+    }
+
+    /// Return the line ending to use in the worktree for the resolved `digest`, or `None` if it is left unchanged.
+    fn worktree_eol(&self, digest: AttributesDigest) -> Option<Mode> {
+        match digest {
+            AttributesDigest::Binary => None,
+            AttributesDigest::TextCrlf | AttributesDigest::TextAutoCrlf => Some(Mode::CrLf),
+            AttributesDigest::TextInput | AttributesDigest::TextAutoInput => Some(Mode::Lf),
+            AttributesDigest::Text | AttributesDigest::TextAuto => {
+                Some(if self.text_eol_is_crlf() { Mode::CrLf } else { Mode::Lf })
+            }
+        }
+    }
+}
+
+/// Statistics about the line endings and characters in a buffer, used to decide if it should be converted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Stats {
+    /// The amount of null bytes.
+    pub null: usize,
+    /// The amount of `CR` bytes that aren't followed by `LF`.
+    pub lone_cr: usize,
+    /// The amount of `LF` bytes that aren't preceded by `CR`.
+    pub lone_lf: usize,
+    /// The amount of `CRLF` pairs.
+    pub crlf: usize,
+    /// The amount of printable characters.
+    pub printable: usize,
+    /// The amount of non-printable characters.
+    pub non_printable: usize,
+}
+
+impl Stats {
+    /// Gather statistics about `data`.
+    pub fn from_bytes(data: &[u8]) -> Self {
+        let mut stats = Stats::default();
+        let mut bytes = data.iter().peekable();
+        while let Some(&byte) = bytes.next() {
+            match byte {
+                b'\r' => {
+                    if bytes.next_if_eq(&&b'\n').is_some() {
+                        stats.crlf += 1;
+                    } else {
+                        stats.lone_cr += 1;
+                    }
+                }
+                b'\n' => stats.lone_lf += 1,
+                127 => stats.non_printable += 1,
+                // BS, HT, ESC and FF
+                b'\x08' | b'\t' | b'\x1b' | b'\x0c' => stats.printable += 1,
+                0 => {
+                    stats.null += 1;
+                    stats.non_printable += 1;
+                }
+                byte if byte < 32 => stats.non_printable += 1,
+                _ => stats.printable += 1,
+            }
+        }
+        // A trailing DOS end-of-file marker doesn't count as non-printable.
+        if data.last() == Some(&b'\x1a') {
+            stats.non_printable -= 1;
+        }
+        stats
+    }
+
+    /// Return `true` if the content these statistics were gathered from is considered binary, using the same heuristic as git.
+    pub fn is_binary(&self) -> bool {
+        self.lone_cr > 0 || self.null > 0 || (self.printable >> 7) < self.non_printable
+    }
+}
+
+/// Convert `LF` to `CRLF` in `src`, content from git, according to `digest` and `config` and write the result into `out`.
+///
+/// Return `true` if `out` was written, or `false` if `src` should be used as is.
+/// Note that with `text=auto` or `core.autocrlf`, content that appears to be binary or that already contains `CR`
+/// is left unchanged.
+pub fn convert_to_worktree(
+    src: &[u8],
+    digest: Option<AttributesDigest>,
+    out: &mut Vec<u8>,
+    config: Configuration,
+) -> bool {
+    let digest = config.resolve(digest);
+    if config.worktree_eol(digest) != Some(Mode::CrLf) {
+        return false;
+    }
+    let stats = Stats::from_bytes(src);
+    if stats.lone_lf == 0 {
+        return false;
+    }
+    if digest.is_auto() && (stats.lone_cr > 0 || stats.crlf > 0 || stats.is_binary()) {
+        return false;
+    }
+
+    out.clear();
+    out.reserve(src.len() + stats.lone_lf);
+    let mut previous = None;
+    for &byte in src {
+        if byte == b'\n' && previous != Some(b'\r') {
+            out.push(b'\r');
+        }
+        out.push(byte);
+        previous = Some(byte);
+    }
+    true
+}
+
+/// Convert `CRLF` to `LF` in `src`, content from the worktree, according to `digest` and `config` and write the result into `out`.
+///
+/// With `text=auto` or `core.autocrlf`, `index_object(buf)` is called to write the content of the path as currently stored
+/// in the index into `buf`, or to return `None` if it's not present. If it contains `CRLF` already, `src` isn't converted
+/// so that files which were committed with `CRLF` don't change unexpectedly.
+///
+/// Return `true` if `out` was written, or `false` if `src` should be used as is.
+pub fn convert_to_git<E>(
+    src: &[u8],
+    digest: Option<AttributesDigest>,
+    out: &mut Vec<u8>,
+    index_object: impl FnOnce(&mut Vec<u8>) -> Result<Option<()>, E>,
+    config: Configuration,
+) -> Result<bool, E> {
+    let digest = config.resolve(digest);
+    if digest == AttributesDigest::Binary || src.is_empty() {
+        return Ok(false);
+    }
+    let stats = Stats::from_bytes(src);
+    if stats.crlf == 0 {
+        return Ok(false);
+    }
+    if digest.is_auto() {
+        if stats.is_binary() {
+            return Ok(false);
+        }
+        let mut buf = Vec::new();
+        if index_object(&mut buf)?.is_some() {
+            let index_stats = Stats::from_bytes(&buf);
+            if index_stats.crlf > 0 && !index_stats.is_binary() {
+                return Ok(false);
+            }
+        }
+    }
+
+    out.clear();
+    out.reserve(src.len() - stats.crlf);
+    let mut bytes = src.iter().peekable();
+    while let Some(&byte) = bytes.next() {
+        if !(byte == b'\r' && bytes.peek() == Some(&&b'\n')) {
+            out.push(byte);
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,105 @@
+use bstr::ByteSlice;
+
+/// Return `true` if `src` contains `$Id$` or `$Id: <anything but a dollar or newline>$`, which would be converted.
+pub fn has_ident(src: &[u8]) -> bool {
+    let mut rest = src;
+    while let Some(pos) = rest.find_byte(b'$') {
+        rest = &rest[pos + 1..];
+        if rest.len() < 3 || !rest.starts_with(b"Id") {
+            continue;
+        }
+        let kind = rest[2];
+        rest = &rest[3..];
+        match kind {
+            b'$' => return true,
+            b':' => match rest.find_byteset(b"$\n") {
+                Some(pos) if rest[pos] == b'$' => return true,
+                Some(pos) => rest = &rest[pos + 1..],
+                None => return false,
+            },
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Expand `$Id$` in `src`, the content of a blob as stored in git, to `$Id: <hex-hash> $` where `<hex-hash>` is the id of
+/// the blob as computed with `object_hash`, and write the result into `out`.
+///
+/// Already expanded identifiers are replaced, unless their content contains spaces which indicates they are from a
+/// different version control system.
+/// Return `true` if `out` was written, or `false` if there was nothing to expand and `src` should be used as is.
+pub fn apply(src: &[u8], object_hash: gix_hash::Kind, out: &mut Vec<u8>) -> bool {
+    if !has_ident(src) {
+        return false;
+    }
+    let mut hasher = gix_features::hash::hasher(object_hash);
+    hasher.update(&gix_object::encode::loose_header(gix_object::Kind::Blob, src.len()));
+    hasher.update(src);
+    let hex = gix_hash::ObjectId::from(hasher.digest()).to_hex().to_string();
+
+    out.clear();
+    let mut rest = src;
+    while let Some(pos) = rest.find_byte(b'$') {
+        out.extend_from_slice(&rest[..=pos]);
+        rest = &rest[pos + 1..];
+        if rest.len() < 3 || !rest.starts_with(b"Id") {
+            continue;
+        }
+        match rest[2] {
+            b'$' => rest = &rest[3..],
+            b':' => {
+                let dollar = match rest[3..].find_byte(b'$') {
+                    Some(pos) => pos + 3,
+                    None => break,
+                };
+                if rest[3..dollar].contains(&b'\n') {
+                    continue;
+                }
+                if rest
+                    .get(4..dollar)
+                    .and_then(|value| value.find_byte(b' '))
+                    .map_or(false, |space| space + 4 < dollar - 1)
+                {
+                    continue;
+                }
+                rest = &rest[dollar + 1..];
+            }
+            _ => continue,
+        }
+        out.extend_from_slice(b"Id: ");
+        out.extend_from_slice(hex.as_bytes());
+        out.extend_from_slice(b" $");
+    }
+    out.extend_from_slice(rest);
+    true
+}
+
+/// Collapse all `$Id: <anything> $` in `src`, the content of a file in the worktree, back to `$Id$` and write the result
+/// into `out`.
+///
+/// Return `true` if `out` was written, or `false` if there was nothing to collapse and `src` should be used as is.
+pub fn undo(src: &[u8], out: &mut Vec<u8>) -> bool {
+    if !has_ident(src) {
+        return false;
+    }
+    out.clear();
+    let mut rest = src;
+    while let Some(pos) = rest.find_byte(b'$') {
+        out.extend_from_slice(&rest[..=pos]);
+        rest = &rest[pos + 1..];
+        if rest.len() > 3 && rest.starts_with(b"Id:") {
+            let dollar = match rest[3..].find_byte(b'$') {
+                Some(pos) => pos + 3,
+                None => break,
+            };
+            if rest[3..dollar].contains(&b'\n') {
+                continue;
+            }
+            out.extend_from_slice(b"Id$");
+            rest = &rest[dollar + 1..];
+        }
+    }
+    out.extend_from_slice(rest);
+    true
+}
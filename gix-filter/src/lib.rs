@@ -1,2 +1,73 @@
-#![deny(rust_2018_idioms)]
+//! Run the filter drivers configured with `filter.<driver>.clean`, `filter.<driver>.smudge` and `filter.<driver>.process`
+//! to transform content as it moves between the object database and the worktree.
+//!
+//! One-shot `clean` and `smudge` commands are spawned once per file, whereas `process` filters are long-running programs
+//! which are started once and then talk to us using the [long-running filter protocol][driver::process], which also allows
+//! them to delay the delivery of content.
+//!
+//! Besides that, line endings are converted natively according to `core.autocrlf`, `core.eol` and the `text`, `crlf` and `eol`
+//! attributes with the [`eol`] module, and content is re-encoded according to the `working-tree-encoding` attribute with
+//! the [`encoding`] module. With the `ident` attribute, `$Id$` is expanded to the id of the blob with the [`ident`] module.
+//!
+//! Use a [`Pipeline`] to apply all conversions according to the [attributes][pipeline::Attributes] of a path.
+#![deny(rust_2018_idioms, missing_docs)]
 #![forbid(unsafe_code)]
+
+use bstr::BString;
+
+/// A filter driver as configured in the `filter.<name>` section of the git configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Driver {
+    /// The name of the driver, as used in the value of the `filter` attribute.
+    pub name: BString,
+    /// The shell command to run when converting worktree content for storage in git, from `filter.<name>.clean`.
+    ///
+    /// `%f` is substituted with the quoted path of the file being filtered.
+    pub clean: Option<BString>,
+    /// The shell command to run when converting content from git for use in the worktree, from `filter.<name>.smudge`.
+    ///
+    /// `%f` is substituted with the quoted path of the file being filtered.
+    pub smudge: Option<BString>,
+    /// The shell command to start a long-running filter process, from `filter.<name>.process`.
+    ///
+    /// If set, it takes precedence over `clean` and `smudge`.
+    pub process: Option<BString>,
+    /// If `true`, from `filter.<name>.required`, it's an error if the driver can't be run or fails, instead of
+    /// passing content through unchanged.
+    pub required: bool,
+}
+
+/// The direction in which content is transformed by a filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Convert content from the worktree for storage in git.
+    Clean,
+    /// Convert content from git for use in the worktree.
+    Smudge,
+}
+
+impl Operation {
+    /// Return the name of the operation as used in the configuration and in the filter protocol.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Clean => "clean",
+            Operation::Smudge => "smudge",
+        }
+    }
+}
+
+///
+pub mod driver;
+
+/// Conversions between `LF` line endings in git and `CRLF` line endings in the worktree.
+pub mod eol;
+
+/// Expansion of `$Id$` to `$Id: <blob-id> $` in the worktree, and back.
+pub mod ident;
+
+/// Conversions between UTF-8 in git and the encoding set with the `working-tree-encoding` attribute in the worktree.
+pub mod encoding;
+
+///
+pub mod pipeline;
+pub use pipeline::Pipeline;
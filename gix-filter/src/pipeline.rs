@@ -0,0 +1,459 @@
+use std::collections::VecDeque;
+
+use bstr::{BStr, BString};
+
+use crate::{
+    driver::{command, process},
+    encoding, eol, ident, Driver, Operation,
+};
+
+/// The error returned by [`Pipeline`] methods.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The required filter '{driver}' has no command or capability to {operation} '{path}'")]
+    MissingCommand {
+        driver: BString,
+        operation: &'static str,
+        path: BString,
+    },
+    #[error("The required filter '{driver}' failed to filter '{path}'")]
+    Command {
+        driver: BString,
+        path: BString,
+        source: command::Error,
+    },
+    #[error("The process of filter '{driver}' failed")]
+    Process { driver: BString, source: process::Error },
+    #[error("The process of the required filter '{driver}' isn't available as it failed previously")]
+    ProcessUnavailable { driver: BString },
+    #[error("The process of filter '{driver}' reported status {status:?} for '{path}'")]
+    ProcessStatus {
+        driver: BString,
+        path: BString,
+        status: process::Status,
+    },
+    #[error("The delayed content of {} path(s) wasn't delivered by filter processes: {paths:?}", paths.len())]
+    DelayedPathsMissing { paths: Vec<BString> },
+    #[error("The working-tree-encoding '{encoding}' of '{path}' is unknown")]
+    UnknownEncoding { path: BString, encoding: BString },
+    #[error(transparent)]
+    Encoding(#[from] encoding::Error),
+    #[error("Could not obtain the content of '{path}' from the index")]
+    IndexObject {
+        path: BString,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+}
+
+/// The attributes of a path which determine how its content is converted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Attributes<'a> {
+    /// The name of the filter driver, from the `filter` attribute.
+    pub driver: Option<&'a BStr>,
+    /// How to convert line endings, from the `text`, `crlf` and `eol` attributes, or `None` if they are unspecified.
+    pub eol: Option<eol::AttributesDigest>,
+    /// The label of the encoding to use in the worktree, from the `working-tree-encoding` attribute.
+    pub encoding: Option<&'a BStr>,
+    /// If `true`, from the `ident` attribute, `$Id$` is expanded to the id of the blob in the worktree.
+    pub ident: bool,
+}
+
+impl<'a> Attributes<'a> {
+    /// The names of all attributes which are used by [`from_states()`][Self::from_states()].
+    pub const NAMES: &'static [&'static str] = &["filter", "text", "crlf", "eol", "working-tree-encoding", "ident"];
+
+    /// Obtain the attributes relevant for converting content by calling `state_of(name)` for each attribute name
+    /// in [`NAMES`][Self::NAMES], which returns its state for the path in question.
+    pub fn from_states(mut state_of: impl FnMut(&str) -> gix_attributes::StateRef<'a>) -> Self {
+        let value = |state: gix_attributes::StateRef<'a>| match state {
+            gix_attributes::StateRef::Value(value) if !value.is_empty() => Some(value),
+            _ => None,
+        };
+        Attributes {
+            driver: value(state_of("filter")),
+            eol: eol::AttributesDigest::from_states(state_of("text"), state_of("crlf"), state_of("eol")),
+            encoding: value(state_of("working-tree-encoding")),
+            ident: matches!(state_of("ident"), gix_attributes::StateRef::Set),
+        }
+    }
+}
+
+/// The outcome of [`Pipeline::convert_to_worktree()`] and [`Pipeline::convert_to_git()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// No filter was applied, so the content should be used as is.
+    Unchanged,
+    /// The filtered content was written into the output buffer.
+    Changed,
+    /// A filter process will deliver the content later, which can be obtained with [`Pipeline::fetch_delayed()`].
+    Delayed,
+}
+
+struct Process {
+    driver: BString,
+    /// `None` if the process couldn't be started or failed, to avoid starting it over and over.
+    client: Option<process::Client>,
+    /// The operations for which the process aborted, and which thus won't be requested anymore.
+    aborted: Vec<Operation>,
+}
+
+/// Options for the built-in conversions of a [`Pipeline`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// How to convert line endings.
+    pub eol_config: eol::Configuration,
+    /// The kind of hash to compute the blob id with when expanding `$Id$` for paths with the `ident` attribute.
+    pub object_hash: gix_hash::Kind,
+}
+
+/// A set of filter drivers along with the long-running processes that were started for them, and the configuration of
+/// built-in conversions.
+///
+/// Processes are started on first use and shut down when the pipeline is dropped. Note that clones only contain the drivers
+/// and configuration, and will start their own processes as needed.
+pub struct Pipeline {
+    drivers: Vec<Driver>,
+    options: Options,
+    processes: Vec<Process>,
+    /// Paths along with the name of their driver whose content was delayed and isn't available yet.
+    delayed: Vec<(BString, BString)>,
+    /// Paths along with the name of their driver whose delayed content can now be fetched.
+    available: VecDeque<(BString, BString)>,
+}
+
+impl Clone for Pipeline {
+    fn clone(&self) -> Self {
+        Pipeline::new(self.drivers.clone(), self.options)
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline::new(Vec::new(), Default::default())
+    }
+}
+
+/// Lifecycle
+impl Pipeline {
+    /// Create a new instance to apply the given filter `drivers`, and to perform built-in conversions according to `options`.
+    pub fn new(drivers: Vec<Driver>, options: Options) -> Self {
+        Pipeline {
+            drivers,
+            options,
+            processes: Vec::new(),
+            delayed: Vec::new(),
+            available: VecDeque::new(),
+        }
+    }
+}
+
+/// Access
+impl Pipeline {
+    /// Return the drivers this pipeline was configured with.
+    pub fn drivers(&self) -> &[Driver] {
+        &self.drivers
+    }
+
+    /// Return the options for built-in conversions.
+    pub fn options(&self) -> Options {
+        self.options
+    }
+
+    /// Return `true` if there is delayed content that wasn't fetched yet with [`fetch_delayed()`][Self::fetch_delayed()].
+    pub fn has_delayed(&self) -> bool {
+        !(self.delayed.is_empty() && self.available.is_empty())
+    }
+}
+
+/// Conversion
+impl Pipeline {
+    /// Convert `src`, the content of the file at `rela_path` as stored in git, for use in the worktree according to its `attributes`
+    /// and write the result into `out`.
+    ///
+    /// `$Id$` is expanded first, then line endings are converted, the content is encoded into the `working-tree-encoding`,
+    /// and finally it is smudged by the filter driver. If `can_delay` is `true`, filter processes may choose to deliver the content later, which is then
+    /// obtained with [`fetch_delayed()`][Self::fetch_delayed()].
+    ///
+    /// Like in git, content that can't be encoded is used as is. If there is no driver of the given name, or if it failed but
+    /// isn't required, it's not applied.
+    /// Note that `out` only contains the converted content if [`Outcome::Changed`] is returned.
+    pub fn convert_to_worktree(
+        &mut self,
+        attributes: &Attributes<'_>,
+        rela_path: &BStr,
+        src: &[u8],
+        can_delay: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<Outcome, Error> {
+        let mut data = src;
+        let mut ident_buf = Vec::new();
+        if attributes.ident && ident::apply(data, self.options.object_hash, &mut ident_buf) {
+            data = &ident_buf;
+        }
+        let mut eol_buf = Vec::new();
+        if eol::convert_to_worktree(data, attributes.eol, &mut eol_buf, self.options.eol_config) {
+            data = &eol_buf;
+        }
+        let mut encoding_buf = Vec::new();
+        if let Some(encoding) = attributes
+            .encoding
+            .and_then(encoding::Encoding::for_label)
+            .filter(|encoding| !encoding.is_utf8())
+        {
+            if encoding::to_worktree(data, rela_path, encoding, &mut encoding_buf).is_ok() {
+                data = &encoding_buf;
+            }
+        }
+
+        if let Some(driver) = attributes.driver {
+            match self.apply(Operation::Smudge, driver, rela_path, data, can_delay, out)? {
+                Outcome::Unchanged => {}
+                outcome => return Ok(outcome),
+            }
+        }
+        Ok(copy_if_converted(src, data, out))
+    }
+
+    /// Convert `src`, the content of the file at `rela_path` in the worktree, for storage in git according to its `attributes`
+    /// and write the result into `out`.
+    ///
+    /// The conversions of [`convert_to_worktree()`][Self::convert_to_worktree()] are undone in reverse order, and content is
+    /// never delayed. If line endings are converted automatically, `index_object(buf)` is called to write the content of
+    /// `rela_path` as currently stored in the index into `buf`, or to return `None` if there is none, as files that were
+    /// committed with `CRLF` keep them.
+    ///
+    /// Unlike when converting to the worktree, content that can't be decoded from its `working-tree-encoding` is an error.
+    pub fn convert_to_git(
+        &mut self,
+        attributes: &Attributes<'_>,
+        rela_path: &BStr,
+        src: &[u8],
+        index_object: impl FnOnce(&mut Vec<u8>) -> Result<Option<()>, Box<dyn std::error::Error + Send + Sync + 'static>>,
+        out: &mut Vec<u8>,
+    ) -> Result<Outcome, Error> {
+        let mut data = src;
+        let mut driver_buf = Vec::new();
+        if let Some(driver) = attributes.driver {
+            if self.apply(Operation::Clean, driver, rela_path, data, false, &mut driver_buf)? == Outcome::Changed {
+                data = &driver_buf;
+            }
+        }
+        let mut encoding_buf = Vec::new();
+        if let Some(label) = attributes.encoding {
+            let encoding = encoding::Encoding::for_label(label).ok_or_else(|| Error::UnknownEncoding {
+                path: rela_path.to_owned(),
+                encoding: label.to_owned(),
+            })?;
+            if !encoding.is_utf8() {
+                encoding::to_git(data, rela_path, encoding, &mut encoding_buf)?;
+                data = &encoding_buf;
+            }
+        }
+
+        let mut eol_buf = Vec::new();
+        if eol::convert_to_git(
+            data,
+            attributes.eol,
+            &mut eol_buf,
+            index_object,
+            self.options.eol_config,
+        )
+        .map_err(|source| Error::IndexObject {
+            path: rela_path.to_owned(),
+            source,
+        })? {
+            data = &eol_buf;
+        }
+        let mut ident_buf = Vec::new();
+        if attributes.ident && ident::undo(data, &mut ident_buf) {
+            data = &ident_buf;
+        }
+        Ok(copy_if_converted(src, data, out))
+    }
+
+    /// Write the content of the next delayed file that is available into `out` and return its path, or `None` if there is no
+    /// more delayed content.
+    ///
+    /// Call it repeatedly until it returns `None`, which may block until filter processes are done. If an error occurs for
+    /// a single path, the content of the remaining paths can still be fetched.
+    pub fn fetch_delayed(&mut self, out: &mut Vec<u8>) -> Result<Option<BString>, Error> {
+        loop {
+            if let Some((driver, path)) = self.available.pop_front() {
+                let client = match self
+                    .processes
+                    .iter_mut()
+                    .find(|p| p.driver == driver)
+                    .and_then(|p| p.client.as_mut())
+                {
+                    Some(client) => client,
+                    None => return Err(Error::ProcessUnavailable { driver }),
+                };
+                return match client.invoke(Operation::Smudge, path.as_ref(), &[], false, out) {
+                    Ok(process::Status::Success) => Ok(Some(path)),
+                    Ok(status) => Err(Error::ProcessStatus { driver, path, status }),
+                    Err(source) => Err(Error::Process { driver, source }),
+                };
+            }
+            if self.delayed.is_empty() {
+                return Ok(None);
+            }
+
+            let mut made_progress = false;
+            for process in self.processes.iter_mut() {
+                if !self.delayed.iter().any(|(driver, _)| *driver == process.driver) {
+                    continue;
+                }
+                let paths = match process.client.as_mut() {
+                    Some(client) => client.list_available_blobs().map_err(|source| Error::Process {
+                        driver: process.driver.clone(),
+                        source,
+                    })?,
+                    None => continue,
+                };
+                for path in paths {
+                    if let Some(pos) = self
+                        .delayed
+                        .iter()
+                        .position(|(driver, delayed_path)| *driver == process.driver && *delayed_path == path)
+                    {
+                        self.available.push_back(self.delayed.remove(pos));
+                        made_progress = true;
+                    }
+                }
+            }
+            if !made_progress {
+                return Err(Error::DelayedPathsMissing {
+                    paths: std::mem::take(&mut self.delayed)
+                        .into_iter()
+                        .map(|(_, path)| path)
+                        .collect(),
+                });
+            }
+        }
+    }
+
+    /// Apply the driver named `driver_name` for `operation`.
+    fn apply(
+        &mut self,
+        operation: Operation,
+        driver_name: &BStr,
+        rela_path: &BStr,
+        src: &[u8],
+        can_delay: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<Outcome, Error> {
+        let Pipeline {
+            drivers,
+            processes,
+            delayed,
+            ..
+        } = self;
+        let driver = match drivers.iter().find(|d| d.name == driver_name) {
+            Some(driver) => driver,
+            None => return Ok(Outcome::Unchanged),
+        };
+        let command = match operation {
+            Operation::Clean => driver.clean.as_ref(),
+            Operation::Smudge => driver.smudge.as_ref(),
+        };
+        let unchanged_unless_required = |err: Error| {
+            if driver.required {
+                Err(err)
+            } else {
+                Ok(Outcome::Unchanged)
+            }
+        };
+        let missing_command = || Error::MissingCommand {
+            driver: driver.name.clone(),
+            operation: operation.as_str(),
+            path: rela_path.to_owned(),
+        };
+
+        if let Some(process_command) = driver.process.as_ref() {
+            let process = match processes.iter().position(|p| p.driver == driver.name) {
+                Some(idx) => &mut processes[idx],
+                None => {
+                    let res = process::Client::handshake(process_command.as_ref());
+                    processes.push(Process {
+                        driver: driver.name.clone(),
+                        client: None,
+                        aborted: Vec::new(),
+                    });
+                    let process = processes.last_mut().expect("just pushed");
+                    match res {
+                        Ok(client) => process.client = Some(client),
+                        Err(source) => {
+                            return unchanged_unless_required(Error::Process {
+                                driver: driver.name.clone(),
+                                source,
+                            })
+                        }
+                    }
+                    process
+                }
+            };
+            let client = match process.client.as_mut() {
+                Some(client) => client,
+                None => {
+                    return unchanged_unless_required(Error::ProcessUnavailable {
+                        driver: driver.name.clone(),
+                    })
+                }
+            };
+            if !client.capabilities().supports(operation) || process.aborted.contains(&operation) {
+                return unchanged_unless_required(missing_command());
+            }
+            let status = match client.invoke(operation, rela_path, src, can_delay, out) {
+                Ok(status) => status,
+                Err(source) => {
+                    // The process is in an unknown state, so don't use it anymore.
+                    process.client = None;
+                    return unchanged_unless_required(Error::Process {
+                        driver: driver.name.clone(),
+                        source,
+                    });
+                }
+            };
+            match status {
+                process::Status::Success => Ok(Outcome::Changed),
+                process::Status::Delayed if can_delay => {
+                    delayed.push((driver.name.clone(), rela_path.to_owned()));
+                    Ok(Outcome::Delayed)
+                }
+                status => {
+                    if status == process::Status::Abort {
+                        process.aborted.push(operation);
+                    }
+                    unchanged_unless_required(Error::ProcessStatus {
+                        driver: driver.name.clone(),
+                        path: rela_path.to_owned(),
+                        status,
+                    })
+                }
+            }
+        } else if let Some(command) = command {
+            match command::run(command.as_ref(), rela_path, src, out) {
+                Ok(()) => Ok(Outcome::Changed),
+                Err(source) => unchanged_unless_required(Error::Command {
+                    driver: driver.name.clone(),
+                    path: rela_path.to_owned(),
+                    source,
+                }),
+            }
+        } else {
+            unchanged_unless_required(missing_command())
+        }
+    }
+}
+
+/// Write `data` into `out` if it's not `src`, as conversions were applied to it.
+fn copy_if_converted(src: &[u8], data: &[u8], out: &mut Vec<u8>) -> Outcome {
+    if std::ptr::eq(src, data) {
+        Outcome::Unchanged
+    } else {
+        out.clear();
+        out.extend_from_slice(data);
+        Outcome::Changed
+    }
+}
@@ -0,0 +1,92 @@
+use bstr::ByteSlice;
+use gix_filter::encoding::{self, Encoding};
+
+fn encoding(label: &str) -> Encoding {
+    Encoding::for_label(label.into()).expect("known encoding")
+}
+
+#[test]
+fn for_label() {
+    for (label, name) in [
+        ("utf-16", "UTF-16"),
+        ("UTF16LE", "UTF-16LE"),
+        ("utf-16le-bom", "UTF-16LE-BOM"),
+        ("UTF-32BE", "UTF-32BE"),
+        ("latin1", "windows-1252"),
+        ("Shift_JIS", "Shift_JIS"),
+    ] {
+        assert_eq!(encoding(label).name(), name);
+    }
+    assert!(encoding("utf8").is_utf8());
+    assert!(!encoding("UTF-16").is_utf8());
+    assert_eq!(Encoding::for_label("unknown".into()), None);
+}
+
+#[test]
+fn utf16_round_trip() -> crate::Result {
+    let mut worktree = Vec::new();
+    let mut git = Vec::new();
+    for (label, expected) in [
+        ("UTF-16", &b"\xff\xfea\x00\xe4\x00"[..]),
+        ("UTF-16BE", b"\x00a\x00\xe4"),
+        ("UTF-16LE-BOM", b"\xff\xfea\x00\xe4\x00"),
+        ("UTF-32LE", b"a\x00\x00\x00\xe4\x00\x00\x00"),
+    ] {
+        encoding::to_worktree("aä".as_bytes(), "file".into(), encoding(label), &mut worktree)?;
+        assert_eq!(worktree, expected, "{label}");
+        encoding::to_git(&worktree, "file".into(), encoding(label), &mut git)?;
+        assert_eq!(git.as_bstr(), "aä", "{label}");
+    }
+
+    encoding::to_git(b"\xfe\xff\x00a", "file".into(), encoding("UTF-16"), &mut git)?;
+    assert_eq!(git.as_bstr(), "a", "the BOM determines the byte order");
+    Ok(())
+}
+
+#[test]
+fn byte_order_marks_are_validated() {
+    assert!(matches!(
+        encoding::to_git(b"a\x00", "file".into(), encoding("UTF-16"), &mut Vec::new()),
+        Err(encoding::Error::MissingBom { encoding: "UTF-16", .. })
+    ));
+    assert!(matches!(
+        encoding::to_git(b"\xff\xfea\x00", "file".into(), encoding("UTF-16LE"), &mut Vec::new()),
+        Err(encoding::Error::ProhibitedBom {
+            encoding: "UTF-16LE",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn other_encodings() -> crate::Result {
+    let mut worktree = Vec::new();
+    encoding::to_worktree("aä".as_bytes(), "file".into(), encoding("ISO-8859-1"), &mut worktree)?;
+    assert_eq!(worktree, b"a\xe4");
+    let mut git = Vec::new();
+    encoding::to_git(&worktree, "file".into(), encoding("ISO-8859-1"), &mut git)?;
+    assert_eq!(git.as_bstr(), "aä");
+
+    assert!(
+        matches!(
+            encoding::to_worktree("€".as_bytes(), "file".into(), encoding("ISO-8859-2"), &mut worktree),
+            Err(encoding::Error::Conversion { .. })
+        ),
+        "unmappable characters are an error"
+    );
+    assert!(
+        matches!(
+            encoding::to_worktree(b"\xff", "file".into(), encoding("ISO-8859-1"), &mut worktree),
+            Err(encoding::Error::Conversion { .. })
+        ),
+        "the source must be UTF-8"
+    );
+    assert!(
+        matches!(
+            encoding::to_git(b"\x82", "file".into(), encoding("Shift_JIS"), &mut git),
+            Err(encoding::Error::Conversion { .. })
+        ),
+        "malformed input is an error"
+    );
+    Ok(())
+}
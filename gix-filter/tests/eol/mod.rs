@@ -0,0 +1,196 @@
+use bstr::ByteSlice;
+use gix_attributes::StateRef;
+use gix_filter::eol::{self, AttributesDigest, AutoCrlf, Configuration, Mode, Stats};
+
+fn config(auto_crlf: AutoCrlf, eol: Option<Mode>) -> Configuration {
+    Configuration { auto_crlf, eol }
+}
+
+fn no_index(_buf: &mut Vec<u8>) -> Result<Option<()>, std::convert::Infallible> {
+    Ok(None)
+}
+
+mod stats {
+    use gix_filter::eol::Stats;
+
+    #[test]
+    fn line_endings_and_characters_are_counted() {
+        assert_eq!(
+            Stats::from_bytes(b"a\r\nb\nc\rd\0\x7f\t"),
+            Stats {
+                null: 1,
+                lone_cr: 1,
+                lone_lf: 1,
+                crlf: 1,
+                printable: 5,
+                non_printable: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn binary_detection() {
+        assert!(!Stats::from_bytes(b"text\r\nwith\nline endings\x1a").is_binary());
+        assert!(Stats::from_bytes(b"a\0b").is_binary(), "null bytes");
+        assert!(Stats::from_bytes(b"a\rb").is_binary(), "lone carriage returns");
+        assert!(
+            Stats::from_bytes(b"\x01\x02").is_binary(),
+            "more than one non-printable per 128 printable characters"
+        );
+    }
+}
+
+#[test]
+fn attributes_digest() {
+    let value = |v: &'static str| StateRef::Value(v.into());
+    use StateRef::{Set, Unset, Unspecified};
+    for (text, crlf, eol, expected) in [
+        (Unspecified, Unspecified, Unspecified, None),
+        (Set, Unspecified, Unspecified, Some(AttributesDigest::Text)),
+        (Unset, Unspecified, value("crlf"), Some(AttributesDigest::Binary)),
+        (
+            value("auto"),
+            Unspecified,
+            Unspecified,
+            Some(AttributesDigest::TextAuto),
+        ),
+        (
+            value("auto"),
+            Unspecified,
+            value("lf"),
+            Some(AttributesDigest::TextAutoInput),
+        ),
+        (
+            value("auto"),
+            Unspecified,
+            value("crlf"),
+            Some(AttributesDigest::TextAutoCrlf),
+        ),
+        (Set, Unspecified, value("lf"), Some(AttributesDigest::TextInput)),
+        (
+            Unspecified,
+            Unspecified,
+            value("crlf"),
+            Some(AttributesDigest::TextCrlf),
+        ),
+        (
+            Unspecified,
+            value("input"),
+            Unspecified,
+            Some(AttributesDigest::TextInput),
+        ),
+        (Unspecified, Unset, Unspecified, Some(AttributesDigest::Binary)),
+        (Set, Unset, Unspecified, Some(AttributesDigest::Text)),
+        (value("invalid"), Unspecified, value("invalid"), None),
+    ] {
+        assert_eq!(
+            AttributesDigest::from_states(text, crlf, eol),
+            expected,
+            "{text:?} {crlf:?} {eol:?}"
+        );
+    }
+}
+
+#[test]
+fn convert_to_worktree() {
+    let mut out = Vec::new();
+    let crlf = config(AutoCrlf::Enabled, None);
+    assert!(eol::convert_to_worktree(
+        b"a\nb\r\nc\n",
+        Some(AttributesDigest::Text),
+        &mut out,
+        crlf
+    ));
+    assert_eq!(out.as_bstr(), "a\r\nb\r\nc\r\n", "existing CRLF are kept");
+
+    assert!(eol::convert_to_worktree(b"a\nb\n", None, &mut out, crlf));
+    assert_eq!(out.as_bstr(), "a\r\nb\r\n", "auto-detected text is converted");
+    assert!(
+        !eol::convert_to_worktree(b"a\nb\r\n", None, &mut out, crlf),
+        "auto-detected text with CRLF isn't touched"
+    );
+    assert!(
+        !eol::convert_to_worktree(b"a\n\0", None, &mut out, crlf),
+        "binary content isn't converted automatically"
+    );
+    assert!(
+        !eol::convert_to_worktree(b"a\n", Some(AttributesDigest::Binary), &mut out, crlf),
+        "-text disables conversions"
+    );
+    assert!(
+        !eol::convert_to_worktree(b"a\n", Some(AttributesDigest::TextInput), &mut out, crlf),
+        "eol=lf overrides the configuration"
+    );
+
+    let lf = config(AutoCrlf::Disabled, Some(Mode::Lf));
+    assert!(!eol::convert_to_worktree(b"a\n", None, &mut out, lf));
+    assert!(!eol::convert_to_worktree(
+        b"a\n",
+        Some(AttributesDigest::Text),
+        &mut out,
+        lf
+    ));
+    assert!(
+        eol::convert_to_worktree(b"a\n", Some(AttributesDigest::TextCrlf), &mut out, lf),
+        "eol=crlf overrides the configuration"
+    );
+    assert_eq!(out.as_bstr(), "a\r\n");
+    assert!(
+        eol::convert_to_worktree(
+            b"a\n",
+            Some(AttributesDigest::Text),
+            &mut out,
+            config(AutoCrlf::Disabled, Some(Mode::CrLf))
+        ),
+        "core.eol applies to text files"
+    );
+    assert!(
+        !eol::convert_to_worktree(b"a\n", None, &mut out, config(AutoCrlf::Input, Some(Mode::CrLf))),
+        "core.autocrlf=input never converts to CRLF"
+    );
+}
+
+#[test]
+fn convert_to_git() -> crate::Result {
+    let mut out = Vec::new();
+    let input = config(AutoCrlf::Input, None);
+    assert!(eol::convert_to_git(b"a\r\nb\r\n", None, &mut out, no_index, input)?);
+    assert_eq!(out.as_bstr(), "a\nb\n");
+    assert!(
+        !eol::convert_to_git(b"a\r\nb\r", None, &mut out, no_index, input)?,
+        "lone CR make it binary"
+    );
+    assert!(
+        eol::convert_to_git(b"a\r\nb\r", Some(AttributesDigest::Text), &mut out, no_index, input)?,
+        "unless it's known to be text"
+    );
+    assert_eq!(out.as_bstr(), "a\nb\r", "lone CR are kept");
+    assert!(!eol::convert_to_git(
+        b"a\r\n",
+        None,
+        &mut out,
+        no_index,
+        Configuration::default()
+    )?);
+
+    let index_with_crlf = |buf: &mut Vec<u8>| -> Result<Option<()>, std::convert::Infallible> {
+        buf.extend_from_slice(b"a\r\n");
+        Ok(Some(()))
+    };
+    assert!(
+        !eol::convert_to_git(b"a\r\nb\r\n", None, &mut out, index_with_crlf, input)?,
+        "files that were committed with CRLF keep them"
+    );
+    assert!(
+        eol::convert_to_git(
+            b"a\r\nb\r\n",
+            Some(AttributesDigest::TextInput),
+            &mut out,
+            index_with_crlf,
+            input
+        )?,
+        "unless they are known to be text"
+    );
+    assert_eq!(Stats::from_bytes(&out).crlf, 0);
+    Ok(())
+}
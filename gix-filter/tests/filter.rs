@@ -0,0 +1,297 @@
+use bstr::{BString, ByteSlice};
+use gix_filter::{pipeline::Attributes, Driver, Pipeline};
+
+mod encoding;
+mod eol;
+mod ident;
+mod pipeline;
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn with_driver(name: &str) -> Attributes<'_> {
+    Attributes {
+        driver: Some(name.into()),
+        ..Default::default()
+    }
+}
+
+fn no_index(_buf: &mut Vec<u8>) -> std::result::Result<Option<()>, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(None)
+}
+
+fn driver(name: &str) -> Driver {
+    Driver {
+        name: name.into(),
+        clean: None,
+        smudge: None,
+        process: None,
+        required: false,
+    }
+}
+
+fn process_driver(name: &str, capabilities: &str) -> Driver {
+    let script = gix_testtools::fixture_path("process-filter.sh");
+    Driver {
+        process: Some(format!("bash '{}' {capabilities}", script.display()).into()),
+        ..driver(name)
+    }
+}
+
+mod command {
+    use bstr::ByteSlice;
+    use gix_filter::{pipeline::Outcome, Driver, Pipeline};
+
+    use crate::{driver, no_index, with_driver};
+
+    fn pipeline() -> Pipeline {
+        Pipeline::new(
+            vec![
+                Driver {
+                    smudge: Some("printf '%s:' %f; tr a-z n-za-m".into()),
+                    clean: Some("tr a-z n-za-m".into()),
+                    ..driver("rot13")
+                },
+                Driver {
+                    smudge: Some("exit 1".into()),
+                    ..driver("failing")
+                },
+                Driver {
+                    smudge: Some("exit 1".into()),
+                    required: true,
+                    ..driver("failing-required")
+                },
+                Driver {
+                    required: true,
+                    ..driver("required-without-command")
+                },
+            ],
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn smudge_and_clean_with_path_substitution() -> crate::Result {
+        let mut filters = pipeline();
+        let mut out = Vec::new();
+        assert_eq!(
+            filters.convert_to_worktree(
+                &with_driver("rot13"),
+                "dir/it's a file".into(),
+                b"hello",
+                false,
+                &mut out
+            )?,
+            Outcome::Changed
+        );
+        assert_eq!(
+            out.as_bstr(),
+            "dir/it's a file:uryyb",
+            "%f is passed as single argument"
+        );
+
+        assert_eq!(
+            filters.convert_to_git(&with_driver("rot13"), "a".into(), b"uryyb", no_index, &mut out)?,
+            Outcome::Changed
+        );
+        assert_eq!(out.as_bstr(), "hello");
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_drivers_and_failures_leave_content_unchanged_unless_required() -> crate::Result {
+        let mut filters = pipeline();
+        let mut out = Vec::new();
+        for driver in ["unknown", "failing"] {
+            assert_eq!(
+                filters.convert_to_worktree(&with_driver(driver), "a".into(), b"content", false, &mut out)?,
+                Outcome::Unchanged
+            );
+        }
+        assert_eq!(
+            filters.convert_to_git(&with_driver("rot13"), "a".into(), b"content", no_index, &mut out)?,
+            Outcome::Changed,
+            "clean commands are independent of smudge commands"
+        );
+        assert_eq!(
+            filters.convert_to_git(&with_driver("failing"), "a".into(), b"content", no_index, &mut out)?,
+            Outcome::Unchanged,
+            "there is no clean command"
+        );
+
+        assert!(matches!(
+            filters.convert_to_worktree(
+                &with_driver("failing-required"),
+                "a".into(),
+                b"content",
+                false,
+                &mut out
+            ),
+            Err(gix_filter::pipeline::Error::Command { .. })
+        ));
+        assert!(matches!(
+            filters.convert_to_git(
+                &with_driver("required-without-command"),
+                "a".into(),
+                b"content",
+                no_index,
+                &mut out
+            ),
+            Err(gix_filter::pipeline::Error::MissingCommand { .. })
+        ));
+        Ok(())
+    }
+}
+
+mod process {
+    use bstr::ByteSlice;
+    use gix_filter::{pipeline::Outcome, Driver, Pipeline};
+
+    use crate::{no_index, process_driver, with_driver};
+
+    #[test]
+    fn smudge_and_clean_with_one_process() -> crate::Result {
+        let mut filters = Pipeline::new(
+            vec![Driver {
+                smudge: Some("exit 1".into()),
+                ..process_driver("rot13", "")
+            }],
+            Default::default(),
+        );
+        let mut out = Vec::new();
+        for path in ["a", "dir/b"] {
+            assert_eq!(
+                filters.convert_to_worktree(&with_driver("rot13"), path.into(), b"hello\n", false, &mut out)?,
+                Outcome::Changed,
+                "the process takes precedence over the smudge command"
+            );
+            assert_eq!(out.as_bstr(), "uryyb\n");
+        }
+        assert_eq!(
+            filters.convert_to_git(&with_driver("rot13"), "a".into(), b"uryyb\n", no_index, &mut out)?,
+            Outcome::Changed
+        );
+        assert_eq!(out.as_bstr(), "hello\n");
+
+        assert_eq!(
+            filters.convert_to_worktree(&with_driver("rot13"), "empty".into(), b"", false, &mut out)?,
+            Outcome::Changed
+        );
+        assert!(out.is_empty());
+
+        let large = vec![b'a'; 100_000];
+        filters.convert_to_worktree(&with_driver("rot13"), "large".into(), &large, false, &mut out)?;
+        assert_eq!(out, vec![b'n'; 100_000], "content spanning multiple packet lines");
+        Ok(())
+    }
+
+    #[test]
+    fn errors_and_missing_capabilities_leave_content_unchanged_unless_required() -> crate::Result {
+        let mut filters = Pipeline::new(
+            vec![
+                process_driver("rot13", "smudge"),
+                Driver {
+                    required: true,
+                    ..process_driver("required", "smudge")
+                },
+            ],
+            Default::default(),
+        );
+        let mut out = Vec::new();
+        assert_eq!(
+            filters.convert_to_worktree(&with_driver("rot13"), "error".into(), b"a", false, &mut out)?,
+            Outcome::Unchanged
+        );
+        assert_eq!(
+            filters.convert_to_git(&with_driver("rot13"), "a".into(), b"a", no_index, &mut out)?,
+            Outcome::Unchanged,
+            "the process lacks the clean capability"
+        );
+        assert_eq!(
+            filters.convert_to_worktree(&with_driver("rot13"), "a".into(), b"a", false, &mut out)?,
+            Outcome::Changed,
+            "the process can still be used after errors"
+        );
+
+        assert!(matches!(
+            filters.convert_to_worktree(&with_driver("required"), "error".into(), b"a", false, &mut out),
+            Err(gix_filter::pipeline::Error::ProcessStatus {
+                status: gix_filter::driver::process::Status::Error,
+                ..
+            })
+        ));
+        assert!(matches!(
+            filters.convert_to_git(&with_driver("required"), "a".into(), b"a", no_index, &mut out),
+            Err(gix_filter::pipeline::Error::MissingCommand { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn delayed_content_is_fetched_later() -> crate::Result {
+        let mut filters = Pipeline::new(vec![process_driver("rot13", "")], Default::default());
+        let mut out = Vec::new();
+        assert_eq!(
+            filters.convert_to_worktree(&with_driver("rot13"), "a.delay".into(), b"hello", false, &mut out)?,
+            Outcome::Changed,
+            "delays are only possible if allowed"
+        );
+        assert!(!filters.has_delayed());
+
+        for path in ["a.delay", "dir/b.delay"] {
+            assert_eq!(
+                filters.convert_to_worktree(&with_driver("rot13"), path.into(), path.as_bytes(), true, &mut out)?,
+                Outcome::Delayed
+            );
+        }
+        assert_eq!(
+            filters.convert_to_worktree(&with_driver("rot13"), "c".into(), b"hello", true, &mut out)?,
+            Outcome::Changed,
+            "the filter decides what to delay"
+        );
+        assert!(filters.has_delayed());
+
+        let mut fetched = Vec::new();
+        while let Some(path) = filters.fetch_delayed(&mut out)? {
+            fetched.push((path, out.clone()));
+        }
+        assert_eq!(
+            fetched,
+            [
+                ("a.delay".into(), b"n.qrynl".to_vec()),
+                ("dir/b.delay".into(), b"qve/o.qrynl".to_vec())
+            ]
+        );
+        assert!(!filters.has_delayed());
+        Ok(())
+    }
+
+    #[test]
+    fn delays_require_the_capability() -> crate::Result {
+        let mut filters = Pipeline::new(vec![process_driver("rot13", "smudge")], Default::default());
+        let mut out = Vec::new();
+        assert_eq!(
+            filters.convert_to_worktree(&with_driver("rot13"), "a.delay".into(), b"hello", true, &mut out)?,
+            Outcome::Changed
+        );
+        assert!(!filters.has_delayed());
+        Ok(())
+    }
+}
+
+#[test]
+fn clones_only_contain_drivers() -> Result {
+    let mut filters = Pipeline::new(vec![process_driver("rot13", "")], Default::default());
+    let mut out = Vec::new();
+    filters.convert_to_worktree(&with_driver("rot13"), "a.delay".into(), b"a", true, &mut out)?;
+    assert!(filters.has_delayed());
+
+    let clone = filters.clone();
+    assert_eq!(clone.drivers(), filters.drivers());
+    assert!(
+        !clone.has_delayed(),
+        "delayed content belongs to processes which aren't cloned"
+    );
+    let fetched: Option<BString> = filters.fetch_delayed(&mut out)?;
+    assert_eq!(fetched.as_ref().map(|p| p.as_bstr()), Some("a.delay".into()));
+    Ok(())
+}
@@ -0,0 +1,134 @@
+#!/usr/bin/env bash
+# A filter process implementing the long-running filter protocol, which applies rot13 to the content it receives.
+#
+# Paths ending in `.delay` are delayed when smudging if possible, and paths containing `error` fail with `status=error`.
+# Capabilities can be limited by passing them as arguments, like `smudge delay`.
+set -eu -o pipefail
+export LC_ALL=C
+
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+wanted_capabilities=${*:-clean smudge delay}
+delayed=()
+
+# Read a packet line into `$line` without its trailing newline, and return 1 on flush. Exit on end of input.
+read_text() {
+  local len
+  len=$(dd bs=1 count=4 2>/dev/null)
+  [ -n "$len" ] || exit 0
+  [ "$len" != "0000" ] || return 1
+  line=$(dd bs=1 count=$((16#$len - 4)) 2>/dev/null)
+}
+
+# Read data packet lines up to the next flush into the file `$1`.
+read_data() {
+  local len
+  : > "$1"
+  while true; do
+    len=$(dd bs=1 count=4 2>/dev/null)
+    [ -n "$len" ] || exit 0
+    [ "$len" != "0000" ] || break
+    dd bs=1 count=$((16#$len - 4)) 2>/dev/null >> "$1"
+  done
+}
+
+write_text() {
+  printf '%04x%s\n' $((${#1} + 5)) "$1"
+}
+
+# Write the content of file `$1` as data packet lines of at most 65516 bytes each, followed by a flush.
+write_data() {
+  local size block=0 chunk max=65516
+  size=$(wc -c < "$1")
+  while [ $((block * max)) -lt "$size" ]; do
+    chunk=$((size - block * max))
+    [ "$chunk" -le "$max" ] || chunk=$max
+    printf '%04x' $((chunk + 4))
+    dd if="$1" bs=$max skip=$block count=1 2>/dev/null
+    block=$((block + 1))
+  done
+  flush
+}
+
+flush() {
+  printf '0000'
+}
+
+rot13() {
+  tr 'a-zA-Z' 'n-za-mN-ZA-M'
+}
+
+read_text && [ "$line" = "git-filter-client" ]
+read_text && [ "$line" = "version=2" ]
+while read_text; do :; done
+write_text git-filter-server
+write_text version=2
+flush
+
+touch "$tmp/capabilities"
+while read_text; do
+  capability=${line#capability=}
+  case " $wanted_capabilities " in
+    *" $capability "*) echo "$capability" >> "$tmp/capabilities" ;;
+  esac
+done
+while read -r capability; do
+  write_text "capability=$capability"
+done < "$tmp/capabilities"
+flush
+
+while true; do
+  command= pathname= can_delay=0
+  while read_text; do
+    case "$line" in
+      command=*) command=${line#command=} ;;
+      pathname=*) pathname=${line#pathname=} ;;
+      can-delay=1) can_delay=1 ;;
+    esac
+  done
+
+  if [ "$command" = "list_available_blobs" ]; then
+    for path in ${delayed[@]+"${delayed[@]}"}; do
+      write_text "pathname=$path"
+    done
+    delayed=()
+    flush
+    write_text status=success
+    flush
+    continue
+  fi
+
+  stored="$tmp/$(printf '%s' "$pathname" | od -An -tx1 | tr -d ' \n')"
+  read_data "$tmp/input"
+  case "$pathname" in
+    *error*)
+      write_text status=error
+      flush
+      continue
+      ;;
+  esac
+
+  if [ "$command" = "smudge" ] && [ -f "$stored" ]; then
+    write_text status=success
+    flush
+    write_data "$stored"
+    rm "$stored"
+    flush
+    continue
+  fi
+
+  rot13 < "$tmp/input" > "$tmp/output"
+  if [ "$command" = "smudge" ] && [ "$can_delay" = 1 ] && [[ "$pathname" == *.delay ]]; then
+    mv "$tmp/output" "$stored"
+    delayed+=("$pathname")
+    write_text status=delayed
+    flush
+    continue
+  fi
+
+  write_text status=success
+  flush
+  write_data "$tmp/output"
+  flush
+done
@@ -0,0 +1,40 @@
+use bstr::ByteSlice;
+use gix_filter::ident;
+
+const BLOB: &[u8] = b"a $Id$ b\n$Id$\n$Id: broken\n$ $I $Id:\n";
+const WORKTREE: &[u8] = b"a $Id: 5c74e0e4fb9a6b2cd1dfc64a20144167c2210532 $ b\n$Id: 5c74e0e4fb9a6b2cd1dfc64a20144167c2210532 $\n$Id: broken\n$ $I $Id:\n";
+
+#[test]
+fn has_ident() {
+    assert!(ident::has_ident(b"$Id$"));
+    assert!(ident::has_ident(b"$Id: anything $"));
+    assert!(!ident::has_ident(b"$Id: no closing dollar\n$"));
+    assert!(!ident::has_ident(b"$Id"));
+    assert!(!ident::has_ident(b"$id$ $ Id$"));
+}
+
+#[test]
+fn apply() {
+    let mut out = Vec::new();
+    assert!(ident::apply(BLOB, gix_hash::Kind::Sha1, &mut out));
+    assert_eq!(out.as_bstr(), WORKTREE.as_bstr(), "the id is the hash of the blob");
+
+    assert!(!ident::apply(b"$Id: broken\n", gix_hash::Kind::Sha1, &mut out));
+    assert!(ident::apply(b"$Id: from another vcs $", gix_hash::Kind::Sha1, &mut out));
+    assert_eq!(
+        out.as_bstr(),
+        "$Id: from another vcs $",
+        "identifiers with spaces are kept"
+    );
+}
+
+#[test]
+fn undo() {
+    let mut out = Vec::new();
+    assert!(ident::undo(WORKTREE, &mut out));
+    assert_eq!(out.as_bstr(), BLOB.as_bstr());
+
+    assert!(ident::undo(b"$Id: from another vcs $", &mut out));
+    assert_eq!(out.as_bstr(), "$Id$", "all expanded identifiers are collapsed");
+    assert!(!ident::undo(b"no identifier", &mut out));
+}
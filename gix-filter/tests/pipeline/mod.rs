@@ -0,0 +1,170 @@
+use bstr::ByteSlice;
+use gix_attributes::StateRef;
+use gix_filter::{
+    eol::{AttributesDigest, AutoCrlf, Configuration},
+    pipeline,
+    pipeline::{Attributes, Outcome},
+    Driver, Pipeline,
+};
+
+use crate::{driver, no_index};
+
+fn pipeline() -> Pipeline {
+    Pipeline::new(
+        vec![Driver {
+            smudge: Some("tr a-z n-za-m".into()),
+            clean: Some("tr a-z n-za-m".into()),
+            ..driver("rot13")
+        }],
+        pipeline::Options {
+            eol_config: Configuration {
+                auto_crlf: AutoCrlf::Enabled,
+                eol: None,
+            },
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn attributes_from_states() {
+    let attrs = Attributes::from_states(|name| match name {
+        "filter" => StateRef::Value("lfs".into()),
+        "text" => StateRef::Set,
+        "eol" => StateRef::Value("crlf".into()),
+        "working-tree-encoding" => StateRef::Value("UTF-16".into()),
+        "ident" => StateRef::Set,
+        _ => StateRef::Unspecified,
+    });
+    assert_eq!(
+        attrs,
+        Attributes {
+            driver: Some("lfs".into()),
+            eol: Some(AttributesDigest::TextCrlf),
+            encoding: Some("UTF-16".into()),
+            ident: true,
+        }
+    );
+    assert_eq!(
+        Attributes::from_states(|_| StateRef::Unspecified),
+        Attributes::default()
+    );
+}
+
+#[test]
+fn all_conversions_are_applied_in_order() -> crate::Result {
+    let mut filters = pipeline();
+    let attrs = Attributes {
+        driver: Some("rot13".into()),
+        eol: Some(AttributesDigest::Text),
+        encoding: Some("UTF-16LE".into()),
+        ident: false,
+    };
+    let mut worktree = Vec::new();
+    assert_eq!(
+        filters.convert_to_worktree(&attrs, "a".into(), b"a\n", false, &mut worktree)?,
+        Outcome::Changed
+    );
+    assert_eq!(
+        worktree, b"n\x00\r\x00\n\x00",
+        "line endings are converted and encoded, and only then smudged, which is why 'tr' sees UTF-16"
+    );
+
+    let mut git = Vec::new();
+    assert_eq!(
+        filters.convert_to_git(&attrs, "a".into(), &worktree, no_index, &mut git)?,
+        Outcome::Changed
+    );
+    assert_eq!(git.as_bstr(), "a\n");
+    Ok(())
+}
+
+#[test]
+fn ident_is_expanded_with_the_id_of_the_blob_before_other_conversions() -> crate::Result {
+    let mut filters = pipeline();
+    let attrs = Attributes {
+        eol: Some(AttributesDigest::Text),
+        ident: true,
+        ..Default::default()
+    };
+    let mut worktree = Vec::new();
+    assert_eq!(
+        filters.convert_to_worktree(&attrs, "a".into(), b"$Id$\n", false, &mut worktree)?,
+        Outcome::Changed
+    );
+    assert_eq!(
+        worktree.as_bstr(),
+        "$Id: 055c8729cdcc372500a08db659c045e16c4409fb $\r\n"
+    );
+
+    let mut git = Vec::new();
+    assert_eq!(
+        filters.convert_to_git(&attrs, "a".into(), &worktree, no_index, &mut git)?,
+        Outcome::Changed
+    );
+    assert_eq!(git.as_bstr(), "$Id$\n");
+    Ok(())
+}
+
+#[test]
+fn content_is_unchanged_without_conversions() -> crate::Result {
+    let mut filters = pipeline();
+    let mut out = Vec::new();
+    let binary = Attributes {
+        eol: Some(AttributesDigest::Binary),
+        ..Default::default()
+    };
+    assert_eq!(
+        filters.convert_to_worktree(&binary, "a".into(), b"a\n", false, &mut out)?,
+        Outcome::Unchanged
+    );
+    assert_eq!(
+        filters.convert_to_git(&binary, "a".into(), b"a\r\n", no_index, &mut out)?,
+        Outcome::Unchanged
+    );
+    assert_eq!(
+        filters.convert_to_worktree(&Attributes::default(), "a".into(), b"a\n", false, &mut out)?,
+        Outcome::Changed,
+        "core.autocrlf applies if there are no attributes"
+    );
+    assert_eq!(out.as_bstr(), "a\r\n");
+    Ok(())
+}
+
+#[test]
+fn unknown_encodings_are_only_an_error_when_converting_to_git() -> crate::Result {
+    let mut filters = pipeline();
+    let mut out = Vec::new();
+    let attrs = Attributes {
+        eol: Some(AttributesDigest::Binary),
+        encoding: Some("unknown".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        filters.convert_to_worktree(&attrs, "a".into(), b"a", false, &mut out)?,
+        Outcome::Unchanged
+    );
+    assert!(matches!(
+        filters.convert_to_git(&attrs, "a".into(), b"a", no_index, &mut out),
+        Err(gix_filter::pipeline::Error::UnknownEncoding { .. })
+    ));
+    assert!(
+        matches!(
+            filters.convert_to_git(
+                &Attributes {
+                    encoding: Some("UTF-16".into()),
+                    ..attrs
+                },
+                "a".into(),
+                b"a\0",
+                no_index,
+                &mut out
+            ),
+            Err(gix_filter::pipeline::Error::Encoding(
+                gix_filter::encoding::Error::MissingBom { .. }
+            ))
+        ),
+        "content that can't be decoded is an error"
+    );
+    Ok(())
+}
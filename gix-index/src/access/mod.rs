@@ -4,8 +4,6 @@ use bstr::{BStr, ByteSlice, ByteVec};
 
 use crate::{entry, extension, Entry, PathStorage, State, Version};
 
-// TODO: integrate this somehow, somewhere, depending on later usage.
-#[allow(dead_code)]
 mod sparse;
 
 /// General information and entries
@@ -162,6 +160,9 @@ impl State {
         mode: entry::Mode,
         path: &BStr,
     ) {
+        if let Some(untracked) = self.untracked.as_mut() {
+            untracked.invalidate_path(path);
+        }
         let path = {
             let path_start = self.path_backing.len();
             self.path_backing.push_str(path);
@@ -196,6 +197,47 @@ impl State {
                 .then_with(|| compare(a, b))
         });
     }
+
+    /// Remove all entries for which `should_remove(path, entry)` returns `true`, keeping the order of all other entries.
+    ///
+    /// Note that the paths of removed entries remain in the path backing.
+    pub fn remove_entries(&mut self, mut should_remove: impl FnMut(&BStr, &Entry) -> bool) {
+        let path_backing = &self.path_backing;
+        let untracked = &mut self.untracked;
+        self.entries.retain(|entry| {
+            let path = entry.path_in(path_backing);
+            let remove = should_remove(path, entry);
+            if remove {
+                if let Some(untracked) = untracked.as_mut() {
+                    untracked.invalidate_path(path);
+                }
+            }
+            !remove
+        });
+    }
+
+    /// Return the untracked cache extension for modification, if present.
+    pub fn untracked_mut(&mut self) -> Option<&mut extension::UntrackedCache> {
+        self.untracked.as_mut()
+    }
+
+    /// Set the untracked cache extension to `untracked`, or remove it if `None`, returning the previous one.
+    pub fn set_untracked(&mut self, untracked: Option<extension::UntrackedCache>) -> Option<extension::UntrackedCache> {
+        std::mem::replace(&mut self.untracked, untracked)
+    }
+
+    /// Return the filesystem monitor extension for modification, if present.
+    pub fn fs_monitor_mut(&mut self) -> Option<&mut extension::FsMonitor> {
+        self.fs_monitor.as_mut()
+    }
+
+    /// Set the filesystem monitor extension to `fs_monitor`, or remove it if `None`, returning the previous one.
+    ///
+    /// Note that the [`FSMONITOR_VALID`][entry::Flags::FSMONITOR_VALID] flag of entries determines which of them are considered
+    /// unchanged by the filesystem monitor when writing the extension.
+    pub fn set_fs_monitor(&mut self, fs_monitor: Option<extension::FsMonitor>) -> Option<extension::FsMonitor> {
+        std::mem::replace(&mut self.fs_monitor, fs_monitor)
+    }
 }
 
 /// Extensions
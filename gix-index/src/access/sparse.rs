@@ -1,59 +1,234 @@
-/// Configuration related to sparse indexes.
-#[derive(Debug, Default, Clone, Copy)]
-pub struct Options {
-    /// If true, certain entries in the index will be excluded / skipped for certain operations,
-    /// based on the ignore patterns in the `.git/info/sparse-checkout` file. These entries will
-    /// carry the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
-    ///
-    /// This typically is the value of `core.sparseCheckout` in the git configuration.
-    pub sparse_checkout: bool,
+use std::{collections::BTreeMap, ops::Range};
 
-    /// Interpret the `.git/info/sparse-checkout` file using _cone mode_.
-    ///
-    /// If true, _cone mode_ is active and entire directories will be included in the checkout, as well as files in the root
-    /// of the repository.
-    /// If false, non-cone mode is active and entries to _include_ will be matched with patterns like those found in `.gitignore` files.
+use bstr::{BStr, BString, ByteSlice};
+use gix_object::TreeRefIter;
+use gix_traverse::tree::breadthfirst;
+
+use crate::{entry, extension, init::from_tree::CollectEntries, Entry, State};
+
+/// Sparse indices
+impl State {
+    /// Replace all [`DIR` entries][entry::Mode::DIR] of a sparse index with the entries of the trees they point to,
+    /// using `find` to obtain trees, so the index is full again just like git does before operations that need all entries.
     ///
-    /// This typically is the value of `core.sparseCheckoutCone` in the git configuration.
-    pub directory_patterns_only: bool,
+    /// The new entries carry the [`SKIP_WORKTREE`][entry::Flags::SKIP_WORKTREE] flag, and the `TREE` extension is updated
+    /// to contain the expanded directories. Return the amount of directories that were expanded.
+    pub fn expand_sparse_directories<Find>(&mut self, mut find: Find) -> Result<usize, breadthfirst::Error>
+    where
+        Find: for<'a> FnMut(&gix_hash::oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+    {
+        let dirs: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.mode.is_sparse())
+            .map(|entry| (entry.path(self).to_owned(), entry.id, entry.flags))
+            .collect();
+        self.is_sparse = false;
+        if dirs.is_empty() {
+            return Ok(0);
+        }
+
+        self.entries.retain(|entry| !entry.mode.is_sparse());
+        let mut buf = Vec::new();
+        for (path, id, flags) in &dirs {
+            let dir = path.strip_suffix(b"/").unwrap_or(path.as_bytes()).as_bstr();
+            let root = find(id, &mut buf).ok_or(breadthfirst::Error::NotFound { oid: *id })?;
+            let mut delegate = CollectEntries::with_prefix_and_trees(dir);
+            breadthfirst(root, breadthfirst::State::default(), &mut find, &mut delegate)?;
 
-    /// If true, will attempt to write a sparse index file which only works in cone mode.
+            for entry in &delegate.entries {
+                self.dangerously_push_entry(
+                    entry.stat,
+                    entry.id,
+                    *flags | entry::Flags::SKIP_WORKTREE,
+                    entry.mode,
+                    entry.path_in(&delegate.path_backing),
+                );
+            }
+            if let Some(tree) = self.tree.as_mut() {
+                let subtrees = delegate.trees.unwrap_or_default();
+                let mut num_entries = BTreeMap::<&BStr, u32>::new();
+                for entry in &delegate.entries {
+                    let path = entry.path_in(&delegate.path_backing);
+                    for pos in path.find_iter(b"/") {
+                        *num_entries.entry(path[..pos].as_bstr()).or_default() += 1;
+                    }
+                }
+                let node = extension::Tree {
+                    name: dir.rsplit_str(b"/").next().expect("at least one component").into(),
+                    id: *id,
+                    num_entries: num_entries.get(dir).copied(),
+                    children: subtrees_of(dir, &subtrees, &num_entries),
+                };
+                replace_tree(tree, dir, node, (delegate.entries.len() as u32).saturating_sub(1));
+            }
+        }
+        self.sort_entries();
+        Ok(dirs.len())
+    }
+
+    /// Return the [`DIR` entry][entry::Mode::DIR] of a sparse index whose directory contains `path`, relative to the root
+    /// of the repository, or `None` if there is no such entry.
     ///
-    /// A sparse index has [`DIR` entries][crate::entry::Mode::DIR] that represent entire directories to be skipped
-    /// during checkout and other operations due to the added presence of
-    /// the [`SKIP_WORKTREE`][crate::entry::Flags::SKIP_WORKTREE] flag.
+    /// This is useful to find paths which are excluded from the sparse checkout, and thus aren't listed on their own.
+    pub fn sparse_directory_containing(&self, path: &BStr) -> Option<&Entry> {
+        if !self.is_sparse {
+            return None;
+        }
+        path.find_iter(b"/").find_map(|pos| {
+            self.entry_by_path_and_stage(path[..pos + 1].as_bstr(), 0)
+                .filter(|entry| entry.mode.is_sparse())
+        })
+    }
+
+    /// Replace the entries of each directory for which `is_excluded(directory)` returns `true` with a single
+    /// [`DIR` entry][entry::Mode::DIR] pointing to its tree, turning this into a sparse index just like git does if `index.sparse`
+    /// is enabled. `directory` is relative to the root of the repository and has no trailing slash.
     ///
-    /// This is typically the value of `index.sparse` in the git configuration.
-    pub write_sparse_index: bool,
+    /// Directories are only collapsed if the `TREE` extension has an up-to-date entry for them, and if all of their entries
+    /// are unconflicted and carry the [`SKIP_WORKTREE`][entry::Flags::SKIP_WORKTREE] flag. Sub-directories of excluded
+    /// directories that can't be collapsed are considered in their place. Return the amount of directories that were collapsed.
+    pub fn collapse_sparse_directories(&mut self, mut is_excluded: impl FnMut(&BStr) -> bool) -> usize {
+        let mut tree = match self.tree.take() {
+            Some(tree) => tree,
+            None => return 0,
+        };
+        let mut collapsed = Vec::new();
+        collapse_tree(
+            self,
+            &mut tree,
+            &mut BString::default(),
+            &mut is_excluded,
+            &mut collapsed,
+        );
+        self.tree = Some(tree);
+        if collapsed.is_empty() {
+            return 0;
+        }
+
+        collapsed.sort_by_key(|(range, _, _)| range.start);
+        let mut entries = Vec::with_capacity(self.entries.len());
+        let mut previous_end = 0;
+        for (range, dir, id) in &collapsed {
+            entries.extend_from_slice(&self.entries[previous_end..range.start]);
+            previous_end = range.end;
+
+            let path_start = self.path_backing.len();
+            self.path_backing.extend_from_slice(dir);
+            self.path_backing.push(b'/');
+            entries.push(Entry {
+                stat: entry::Stat::default(),
+                id: *id,
+                flags: entry::Flags::EXTENDED | entry::Flags::SKIP_WORKTREE,
+                mode: entry::Mode::DIR,
+                path: path_start..self.path_backing.len(),
+            });
+        }
+        entries.extend_from_slice(&self.entries[previous_end..]);
+        self.entries = entries;
+        self.is_sparse = true;
+        collapsed.len()
+    }
 }
 
-impl Options {
-    /// Derive a valid mode from all parameters that affect the 'sparseness' of the index.
-    ///
-    /// Some combinations of them degenerate to one particular mode.
-    pub fn sparse_mode(&self) -> Mode {
-        match (
-            self.sparse_checkout,
-            self.directory_patterns_only,
-            self.write_sparse_index,
-        ) {
-            (true, true, true) => Mode::IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
-            (true, true, false) => Mode::IncludeDirectoriesStoreAllEntriesSkipUnmatched,
-            (true, false, _) => Mode::IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
-            (false, _, _) => Mode::Disabled,
+/// Build the `TREE` extension entries for all direct sub-trees of `dir`.
+fn subtrees_of(
+    dir: &BStr,
+    subtrees: &[(BString, gix_hash::ObjectId)],
+    num_entries: &BTreeMap<&BStr, u32>,
+) -> Vec<extension::Tree> {
+    subtrees
+        .iter()
+        .filter_map(|(path, id)| {
+            let name = path.strip_prefix(dir.as_bytes())?.strip_prefix(b"/")?;
+            (!name.contains(&b'/')).then(|| extension::Tree {
+                name: name.into(),
+                id: *id,
+                num_entries: num_entries.get(path.as_bstr()).copied(),
+                children: subtrees_of(path.as_bstr(), subtrees, num_entries),
+            })
+        })
+        .collect()
+}
+
+/// Replace the entry of `dir` below `tree` with `node` and add `added` to the amount of entries of all of its parents,
+/// or invalidate them if there is no such entry. Return `true` if the entry was replaced.
+fn replace_tree(tree: &mut extension::Tree, dir: &[u8], node: extension::Tree, added: u32) -> bool {
+    let (name, rest) = match dir.find_byte(b'/') {
+        Some(pos) => (&dir[..pos], Some(&dir[pos + 1..])),
+        None => (dir, None),
+    };
+    let replaced = match tree.children.iter_mut().find(|child| child.name.as_slice() == name) {
+        Some(child) => match rest {
+            Some(rest) => replace_tree(child, rest, node, added),
+            None => {
+                *child = node;
+                true
+            }
+        },
+        None => false,
+    };
+    tree.num_entries = if replaced {
+        tree.num_entries.map(|num_entries| num_entries + added)
+    } else {
+        None
+    };
+    replaced
+}
+
+/// Find the directories below `tree` at `dir` which can be collapsed, store them in `collapsed` and update the `TREE`
+/// extension accordingly. Return the amount of entries that will be removed.
+fn collapse_tree(
+    state: &State,
+    tree: &mut extension::Tree,
+    dir: &mut BString,
+    is_excluded: &mut impl FnMut(&BStr) -> bool,
+    collapsed: &mut Vec<(Range<usize>, BString, gix_hash::ObjectId)>,
+) -> u32 {
+    let mut removed = 0;
+    for child in &mut tree.children {
+        let len = dir.len();
+        if len != 0 {
+            dir.push(b'/');
         }
+        dir.extend_from_slice(&child.name);
+        let range = match child.num_entries {
+            Some(num_entries) if is_excluded(dir.as_bstr()) => collapsible_range(state, dir.as_bstr(), num_entries),
+            _ => None,
+        };
+        removed += match range {
+            Some(range) => {
+                let removed = range.len() as u32 - 1;
+                collapsed.push((range, dir.clone(), child.id));
+                child.num_entries = Some(1);
+                child.children.clear();
+                removed
+            }
+            None => collapse_tree(state, child, dir, is_excluded, collapsed),
+        };
+        dir.truncate(len);
     }
+    tree.num_entries = tree.num_entries.map(|num_entries| num_entries - removed);
+    removed
 }
 
-/// Describes the configuration how a sparse index should be written, or if one should be written at all.
-#[derive(Debug)]
-pub enum Mode {
-    /// index with DIR entries for exclusion and included entries, directory-only include patterns in `.git/info/sparse-checkout` file.
-    IncludeDirectoriesStoreIncludedEntriesAndExcludedDirs,
-    /// index with all file entries and skip worktree flags for exclusion, directory-only include patterns in `.git/info/sparse-checkout` file.
-    IncludeDirectoriesStoreAllEntriesSkipUnmatched,
-    /// index with all file entries and skip-worktree flags for exclusion, `ignore` patterns to include entries in `.git/info/sparse-checkout` file.
-    IncludeByIgnorePatternStoreAllEntriesSkipUnmatched,
-    /// index with all entries, non is excluded, `.git/info/sparse-checkout` file is not considered, a regular index.
-    Disabled,
+/// Return the range of entries within `dir` if there are `num_entries` of them, as stored in the `TREE` extension, and if they
+/// can be replaced by a single directory entry.
+fn collapsible_range(state: &State, dir: &BStr, num_entries: u32) -> Option<Range<usize>> {
+    let mut prefix = dir.to_owned();
+    prefix.push(b'/');
+    let start = state
+        .entries
+        .partition_point(|entry| entry.path(state) < prefix.as_bstr());
+    let entries: Vec<_> = state.entries[start..]
+        .iter()
+        .take_while(|entry| entry.path(state).starts_with(&prefix))
+        .collect();
+    let is_collapsible = !entries.is_empty()
+        && entries.len() == num_entries as usize
+        && !(entries.len() == 1 && entries[0].mode.is_sparse())
+        && entries
+            .iter()
+            .all(|entry| entry.stage() == 0 && entry.flags.contains(entry::Flags::SKIP_WORKTREE));
+    is_collapsible.then(|| start..start + entries.len())
 }
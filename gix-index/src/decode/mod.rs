@@ -235,23 +235,25 @@ impl State {
         } = ext;
         is_sparse |= is_sparse_from_ext;
 
-        Ok((
-            State {
-                object_hash,
-                timestamp,
-                version,
-                entries,
-                path_backing,
-                is_sparse,
+        let mut state = State {
+            object_hash,
+            timestamp,
+            version,
+            entries,
+            path_backing,
+            is_sparse,
 
-                tree,
-                link,
-                resolve_undo,
-                untracked,
-                fs_monitor,
-            },
-            checksum,
-        ))
+            tree,
+            link,
+            resolve_undo,
+            untracked,
+            fs_monitor,
+        };
+        // The entries of split indices are only complete once the shared index was merged in.
+        if state.link.is_none() {
+            extension::fs_monitor::mark_valid_entries(&mut state);
+        }
+        Ok((state, checksum))
     }
 }
 
@@ -303,13 +305,13 @@ pub(crate) fn stat(data: &[u8]) -> Option<(entry::Stat, &[u8])> {
     Some((
         entry::Stat {
             mtime: entry::Time {
-                secs: ctime_secs,
-                nsecs: ctime_nsecs,
-            },
-            ctime: entry::Time {
                 secs: mtime_secs,
                 nsecs: mtime_nsecs,
             },
+            ctime: entry::Time {
+                secs: ctime_secs,
+                nsecs: ctime_nsecs,
+            },
             dev,
             ino,
             uid,
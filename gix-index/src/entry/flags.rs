@@ -62,6 +62,12 @@ impl Flags {
         (*self & Flags::STAGE_MASK).bits >> 12
     }
 
+    /// Return `true` if extended flags have to be written, either because the `EXTENDED` bit is set or because
+    /// one of the flags that are stored as extended flags is set, like git does.
+    pub(crate) fn needs_extended(&self) -> bool {
+        self.intersects(Flags::EXTENDED | Flags::INTENT_TO_ADD | Flags::SKIP_WORKTREE)
+    }
+
     /// Transform ourselves to a storage representation to keep all flags which are to be persisted,
     /// skipping all extended flags. Note that the caller has to check for the `EXTENDED` bit to be present
     /// and write extended flags as well if so.
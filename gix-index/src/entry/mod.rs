@@ -68,7 +68,51 @@ mod _impls {
 
     use bstr::BStr;
 
-    use crate::{entry::Time, Entry, State};
+    use crate::{
+        entry::{Stat, Time},
+        Entry, State,
+    };
+
+    impl Stat {
+        /// Obtain stat information from `meta`, the metadata of a file or directory, truncating all values to 32 bits
+        /// like `git` does.
+        pub fn from_fs(meta: &std::fs::Metadata) -> Self {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                Stat {
+                    mtime: Time {
+                        secs: meta.mtime() as u32,
+                        nsecs: meta.mtime_nsec() as u32,
+                    },
+                    ctime: Time {
+                        secs: meta.ctime() as u32,
+                        nsecs: meta.ctime_nsec() as u32,
+                    },
+                    dev: meta.dev() as u32,
+                    ino: meta.ino() as u32,
+                    uid: meta.uid(),
+                    gid: meta.gid(),
+                    size: meta.size() as u32,
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                let time = |time: std::io::Result<SystemTime>| {
+                    time.ok()
+                        .filter(|time| *time >= std::time::UNIX_EPOCH)
+                        .map(Time::from)
+                        .unwrap_or_default()
+                };
+                Stat {
+                    mtime: time(meta.modified()),
+                    ctime: time(meta.created()),
+                    size: meta.len() as u32,
+                    ..Default::default()
+                }
+            }
+        }
+    }
 
     impl From<SystemTime> for Time {
         fn from(s: SystemTime) -> Self {
@@ -5,6 +5,13 @@ use crate::{entry, Entry, State};
 impl Entry {
     /// Serialize ourselves to `out` with path access via `state`, without padding.
     pub fn write_to(&self, mut out: impl std::io::Write, state: &State) -> std::io::Result<()> {
+        self.write_without_path_to(&mut out, state)?;
+        out.write_all(self.path(state))?;
+        out.write_all(b"\0")
+    }
+
+    /// Serialize everything but our path to `out`, with the path length in the flags still obtained via `state`.
+    pub(crate) fn write_without_path_to(&self, mut out: impl std::io::Write, state: &State) -> std::io::Result<()> {
         let stat = self.stat;
         out.write_all(&stat.ctime.secs.to_be_bytes())?;
         out.write_all(&stat.ctime.nsecs.to_be_bytes())?;
@@ -25,15 +32,12 @@ impl Entry {
                 .try_into()
                 .expect("we just checked that the length is smaller than 0xfff")
         };
-        out.write_all(&(self.flags.to_storage().bits() | path_len).to_be_bytes())?;
-        if self.flags.contains(entry::Flags::EXTENDED) {
-            out.write_all(
-                &entry::at_rest::FlagsExtended::from_flags(self.flags)
-                    .bits()
-                    .to_be_bytes(),
-            )?;
+        let mut flags = self.flags;
+        flags.set(entry::Flags::EXTENDED, flags.needs_extended());
+        out.write_all(&(flags.to_storage().bits() | path_len).to_be_bytes())?;
+        if flags.contains(entry::Flags::EXTENDED) {
+            out.write_all(&entry::at_rest::FlagsExtended::from_flags(flags).bits().to_be_bytes())?;
         }
-        out.write_all(path)?;
-        out.write_all(b"\0")
+        Ok(())
     }
 }
@@ -1,18 +1,43 @@
+use std::convert::TryInto;
+
 use bstr::BString;
 
 use crate::{
+    entry,
     extension::{FsMonitor, Signature},
     util::{read_u32, read_u64, split_at_byte_exclusive},
+    State,
 };
 
-#[derive(Clone)]
+/// The token of the filesystem monitor that identifies the point in time up to which changes were seen.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token {
-    V1 { nanos_since_1970: u64 },
-    V2 { token: BString },
+    /// The token as written by version 1 of the extension, nanoseconds since the unix epoch.
+    V1 {
+        /// The amount of nanoseconds since 1970.
+        nanos_since_1970: u64,
+    },
+    /// An opaque token as written by version 2 of the extension, as provided by the filesystem monitor.
+    V2 {
+        /// The opaque token.
+        token: BString,
+    },
+}
+
+impl Token {
+    /// Return the token in the form that is passed to filesystem monitors to obtain all changes since.
+    pub fn to_bstring(&self) -> BString {
+        match self {
+            Token::V1 { nanos_since_1970 } => nanos_since_1970.to_string().into(),
+            Token::V2 { token } => token.clone(),
+        }
+    }
 }
 
+/// The signature of the filesystem monitor extension.
 pub const SIGNATURE: Signature = *b"FSMN";
 
+/// Decode an instance from `data`.
 pub fn decode(data: &[u8]) -> Option<FsMonitor> {
     let (version, data) = read_u32(data)?;
     let (token, data) = match version {
@@ -28,7 +53,7 @@ pub fn decode(data: &[u8]) -> Option<FsMonitor> {
     };
 
     let (ewah_size, data) = read_u32(data)?;
-    let (entry_dirty, data) = gix_bitmap::ewah::decode(&data[..ewah_size as usize]).ok()?;
+    let (entry_dirty, data) = gix_bitmap::ewah::decode(data.get(..ewah_size as usize)?).ok()?;
 
     if !data.is_empty() {
         return None;
@@ -36,3 +61,81 @@ pub fn decode(data: &[u8]) -> Option<FsMonitor> {
 
     FsMonitor { token, entry_dirty }.into()
 }
+
+impl FsMonitor {
+    /// Create a new instance to track changes since `token`.
+    pub fn new(token: Token) -> Self {
+        FsMonitor {
+            token,
+            entry_dirty: gix_bitmap::ewah::Vec::from_set_bits(std::iter::empty()),
+        }
+    }
+
+    /// Serialize this instance to `out`, with `entries` determining which entries are dirty instead of our `entry_dirty` bitmap
+    /// if provided.
+    pub(crate) fn write_to(
+        &self,
+        entries: Option<&[crate::Entry]>,
+        mut out: impl std::io::Write,
+    ) -> std::io::Result<()> {
+        let mut data = Vec::new();
+        match &self.token {
+            Token::V1 { nanos_since_1970 } => {
+                data.extend_from_slice(&1_u32.to_be_bytes());
+                data.extend_from_slice(&nanos_since_1970.to_be_bytes());
+            }
+            Token::V2 { token } => {
+                data.extend_from_slice(&2_u32.to_be_bytes());
+                data.extend_from_slice(token);
+                data.push(0);
+            }
+        }
+        let mut bitmap = Vec::new();
+        match entries {
+            Some(entries) => entry_dirty(entries).write_to(&mut bitmap)?,
+            None => self.entry_dirty.write_to(&mut bitmap)?,
+        }
+        let bitmap_len: u32 = bitmap.len().try_into().expect("bitmap never exceeds 4GB");
+        data.extend_from_slice(&bitmap_len.to_be_bytes());
+        data.extend_from_slice(&bitmap);
+
+        out.write_all(&SIGNATURE)?;
+        let data_len: u32 = data.len().try_into().expect("fsmonitor extension never exceeds 4GB");
+        out.write_all(&data_len.to_be_bytes())?;
+        out.write_all(&data)
+    }
+}
+
+/// Return a bitmap with a bit set for each entry that isn't marked as valid by the filesystem monitor, skipping entries
+/// that are to be removed as they won't be written.
+pub(crate) fn entry_dirty(entries: &[crate::Entry]) -> gix_bitmap::ewah::Vec {
+    gix_bitmap::ewah::Vec::from_set_bits(
+        entries
+            .iter()
+            .filter(|entry| !entry.flags.contains(entry::Flags::REMOVE))
+            .enumerate()
+            .filter_map(|(idx, entry)| (!entry.flags.contains(entry::Flags::FSMONITOR_VALID)).then_some(idx)),
+    )
+}
+
+/// Like `git`, mark all entries of `state` that aren't dirty according to its filesystem monitor extension as
+/// [valid][entry::Flags::FSMONITOR_VALID].
+///
+/// Nothing is done if the bitmap doesn't fit the entries.
+pub(crate) fn mark_valid_entries(state: &mut State) {
+    let fs_monitor = match state.fs_monitor.as_ref() {
+        Some(fs_monitor) => fs_monitor,
+        None => return,
+    };
+    if fs_monitor.entry_dirty.num_bits() > state.entries.len() {
+        return;
+    }
+    let mut is_dirty = vec![false; state.entries.len()];
+    fs_monitor.entry_dirty.for_each_set_bit(|idx| {
+        is_dirty[idx] = true;
+        Some(())
+    });
+    for (entry, is_dirty) in state.entries.iter_mut().zip(is_dirty) {
+        entry.flags.set(entry::Flags::FSMONITOR_VALID, !is_dirty);
+    }
+}
@@ -1,6 +1,11 @@
+use std::convert::TryInto;
+
 use crate::{
-    extension::{Link, Signature},
+    entry::Flags,
+    extension,
+    extension::{FsMonitor, Link, Signature},
     util::split_at_pos,
+    Entry, State,
 };
 
 /// The signature of the link extension.
@@ -88,10 +93,9 @@ impl Link {
             },
         )?;
 
-        if let Some(bitmaps) = self.bitmaps {
-            let mut split_entry_index = 0;
-
-            let mut err = None;
+        let mut split_entry_index = 0;
+        let mut err = None;
+        if let Some(bitmaps) = &self.bitmaps {
             bitmaps.replace.for_each_set_bit(|replace_index| {
                 let shared_entry = match shared_index.entries.get_mut(replace_index) {
                     Some(e) => e,
@@ -129,23 +133,25 @@ impl Link {
                 split_entry_index += 1;
                 Some(())
             });
-            if let Some(err) = err {
+            if let Some(err) = err.take() {
                 return Err(err.into());
             }
+        }
 
-            let split_index_path_backing = std::mem::take(&mut split_index.path_backing);
-            for mut split_entry in split_index.entries.drain(split_entry_index..) {
-                let start = shared_index.path_backing.len();
-                let split_index_path = split_entry.path.clone();
+        let split_index_path_backing = std::mem::take(&mut split_index.path_backing);
+        for mut split_entry in split_index.entries.drain(split_entry_index..) {
+            let start = shared_index.path_backing.len();
+            let split_index_path = split_entry.path.clone();
 
-                split_entry.path = start..start + split_entry.path.len();
-                shared_index.entries.push(split_entry);
+            split_entry.path = start..start + split_entry.path.len();
+            shared_index.entries.push(split_entry);
 
-                shared_index
-                    .path_backing
-                    .extend_from_slice(&split_index_path_backing[split_index_path]);
-            }
+            shared_index
+                .path_backing
+                .extend_from_slice(&split_index_path_backing[split_index_path]);
+        }
 
+        if let Some(bitmaps) = &self.bitmaps {
             bitmaps.delete.for_each_set_bit(|delete_index| {
                 let shared_entry = match shared_index.entries.get_mut(delete_index) {
                     Some(e) => e,
@@ -160,18 +166,116 @@ impl Link {
             if let Some(err) = err {
                 return Err(err.into());
             }
+        }
 
-            shared_index
-                .entries
-                .retain(|e| !e.flags.contains(crate::entry::Flags::REMOVE));
+        shared_index
+            .entries
+            .retain(|e| !e.flags.contains(crate::entry::Flags::REMOVE));
 
-            let mut shared_entries = std::mem::take(&mut shared_index.entries);
-            shared_entries.sort_by(|a, b| a.cmp(b, &shared_index.state));
+        let mut shared_entries = std::mem::take(&mut shared_index.entries);
+        shared_entries.sort_by(|a, b| a.cmp(b, &shared_index.state));
 
-            split_index.entries = shared_entries;
-            split_index.path_backing = std::mem::take(&mut shared_index.path_backing);
-        }
+        split_index.entries = shared_entries;
+        split_index.path_backing = std::mem::take(&mut shared_index.path_backing);
+        // Keep the link without bitmaps to remember that we are split, so the same shared index can be used when writing.
+        split_index.link = Some(Link {
+            shared_index_checksum: self.shared_index_checksum,
+            bitmaps: None,
+        });
 
         Ok(())
     }
+
+    /// Serialize this extension to `out`, which only writes bitmaps if present.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        let mut data = Vec::new();
+        data.extend_from_slice(self.shared_index_checksum.as_bytes());
+        if let Some(bitmaps) = &self.bitmaps {
+            bitmaps.delete.write_to(&mut data)?;
+            bitmaps.replace.write_to(&mut data)?;
+        }
+
+        out.write_all(&SIGNATURE)?;
+        let data_len: u32 = data.len().try_into().expect("link extension never exceeds 4GB");
+        out.write_all(&data_len.to_be_bytes())?;
+        out.write_all(&data)
+    }
+}
+
+/// Return a state to be written in place of `state` that only contains the changes compared to `shared`, the shared index
+/// with `shared_index_checksum`, along with a `link` extension that refers to it.
+///
+/// Like in `git`, entries of `shared` that are missing in `state` are marked for deletion, and entries that changed are
+/// stored without path and marked for replacement, while all entries that aren't in `shared` are stored with their path.
+pub(crate) fn split(state: &State, shared: &State, shared_index_checksum: gix_hash::ObjectId) -> State {
+    let persisted_flags = Flags::STAGE_MASK | Flags::ASSUME_VALID | Flags::INTENT_TO_ADD | Flags::SKIP_WORKTREE;
+    let mut is_in_shared_index = vec![false; state.entries.len()];
+    let mut delete = Vec::new();
+    let mut replace = Vec::new();
+    let mut entries = Vec::new();
+    let mut path_backing = Vec::new();
+
+    for (shared_index, shared_entry) in shared.entries.iter().enumerate() {
+        let entry_index = state
+            .entry_index_by_path_and_stage(shared_entry.path(shared), shared_entry.stage())
+            .filter(|idx| !state.entries[*idx].flags.contains(Flags::REMOVE));
+        let entry_index = match entry_index {
+            Some(idx) => idx,
+            None => {
+                delete.push(shared_index);
+                continue;
+            }
+        };
+        is_in_shared_index[entry_index] = true;
+        let entry = &state.entries[entry_index];
+        if entry.stat != shared_entry.stat
+            || entry.id != shared_entry.id
+            || entry.mode != shared_entry.mode
+            || (entry.flags & persisted_flags) != (shared_entry.flags & persisted_flags)
+        {
+            replace.push(shared_index);
+            entries.push(Entry {
+                path: 0..0,
+                ..entry.clone()
+            });
+        }
+    }
+
+    for (entry, _) in state
+        .entries
+        .iter()
+        .zip(is_in_shared_index)
+        .filter(|(entry, is_in_shared_index)| !is_in_shared_index && !entry.flags.contains(Flags::REMOVE))
+    {
+        let path = entry.path(state);
+        let start = path_backing.len();
+        path_backing.extend_from_slice(path);
+        entries.push(Entry {
+            path: start..path_backing.len(),
+            ..entry.clone()
+        });
+    }
+
+    State {
+        object_hash: state.object_hash,
+        timestamp: state.timestamp,
+        version: state.version,
+        entries,
+        path_backing,
+        is_sparse: state.is_sparse,
+        tree: state.tree.clone(),
+        link: Some(Link {
+            shared_index_checksum,
+            bitmaps: Some(Bitmaps {
+                delete: gix_bitmap::ewah::Vec::from_set_bits(delete),
+                replace: gix_bitmap::ewah::Vec::from_set_bits(replace),
+            }),
+        }),
+        resolve_undo: state.resolve_undo.clone(),
+        untracked: state.untracked.clone(),
+        fs_monitor: state.fs_monitor.as_ref().map(|fs_monitor| FsMonitor {
+            token: fs_monitor.token.clone(),
+            entry_dirty: extension::fs_monitor::entry_dirty(&state.entries),
+        }),
+    }
 }
@@ -1,12 +1,12 @@
 use std::convert::TryInto;
 
-use bstr::BString;
+use bstr::{BStr, BString, ByteSlice};
 use gix_hash::ObjectId;
 
 use crate::{
     entry,
     extension::{Signature, UntrackedCache},
-    util::{read_u32, split_at_byte_exclusive, split_at_pos, var_int},
+    util::{read_u32, split_at_byte_exclusive, split_at_pos, var_int, write_var_int},
 };
 
 /// A structure to track filesystem stat information along with an object id, linking a worktree file with what's in our ODB.
@@ -28,18 +28,21 @@ pub struct Directory {
     /// indices for sub-directories similar to this one.
     pub sub_directories: Vec<usize>,
 
-    /// The directories stat data, if available or valid // TODO: or is it the exclude file?
+    /// The directories stat data, if the untracked entries are valid, or `None` if the directory has to be read again.
     pub stat: Option<entry::Stat>,
     /// The oid of a .gitignore file, if it exists
     pub exclude_file_oid: Option<ObjectId>,
-    /// TODO: figure out what this really does
+    /// If `true`, the directory was only read to determine if it contains any untracked files, so it's not listed in full.
     pub check_only: bool,
 }
 
 /// Only used as an indicator
 pub const SIGNATURE: Signature = *b"UNTR";
 
-// #[allow(unused)]
+/// The flags `git` uses for the directory traversal when showing untracked files in `normal` mode, which is the only
+/// mode for which the cache is maintained.
+pub const DIR_FLAGS: u32 = 1 << 1 /* show other directories */ | 1 << 2 /* hide empty directories */;
+
 /// Decode an untracked cache extension from `data`, assuming object hashes are of type `object_hash`.
 pub fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Option<UntrackedCache> {
     if !data.last().map(|b| *b == 0).unwrap_or(false) {
@@ -49,9 +52,19 @@ pub fn decode(data: &[u8], object_hash: gix_hash::Kind) -> Option<UntrackedCache
     let (identifier, data) = split_at_pos(data, identifier_len.try_into().ok()?)?;
 
     let hash_len = object_hash.len_in_bytes();
-    let (info_exclude, data) = decode_oid_stat(data, hash_len)?;
-    let (excludes_file, data) = decode_oid_stat(data, hash_len)?;
+    let (info_exclude_stat, data) = crate::decode::stat(data)?;
+    let (excludes_file_stat, data) = crate::decode::stat(data)?;
     let (dir_flags, data) = read_u32(data)?;
+    let (info_exclude_id, data) = split_at_pos(data, hash_len)?;
+    let (excludes_file_id, data) = split_at_pos(data, hash_len)?;
+    let info_exclude = OidStat {
+        stat: info_exclude_stat,
+        id: ObjectId::from(info_exclude_id),
+    };
+    let excludes_file = OidStat {
+        stat: excludes_file_stat,
+        id: ObjectId::from(excludes_file_id),
+    };
     let (exclude_filename_per_dir, data) = split_at_byte_exclusive(data, 0)?;
 
     let (num_directory_blocks, data) = var_int(data)?;
@@ -143,14 +156,130 @@ fn decode_directory_block<'a>(data: &'a [u8], directories: &mut Vec<Directory>)
     data.into()
 }
 
-fn decode_oid_stat(data: &[u8], hash_len: usize) -> Option<(OidStat, &[u8])> {
-    let (stat, data) = crate::decode::stat(data)?;
-    let (hash, data) = split_at_pos(data, hash_len)?;
-    Some((
-        OidStat {
-            stat,
-            id: ObjectId::from(hash),
-        },
-        data,
-    ))
+impl UntrackedCache {
+    /// Create a new and empty cache for the worktree identified by `identifier`, using `exclude_filename_per_dir`
+    /// as name of the per-directory exclude files, typically `.gitignore`.
+    pub fn new(identifier: impl Into<BString>, exclude_filename_per_dir: impl Into<BString>) -> Self {
+        UntrackedCache {
+            identifier: identifier.into(),
+            info_exclude: None,
+            excludes_file: None,
+            exclude_filename_per_dir: exclude_filename_per_dir.into(),
+            dir_flags: DIR_FLAGS,
+            directories: Vec::new(),
+        }
+    }
+
+    /// Invalidate all directories leading to `path`, relative to the root of the worktree, so they have to be read again.
+    ///
+    /// Like in `git`, this should be done whenever an index entry at `path` is added or removed as the directory
+    /// itself may not have changed.
+    pub fn invalidate_path(&mut self, path: &BStr) {
+        if self.directories.is_empty() {
+            return;
+        }
+        let dirs = match path.rfind_byte(b'/') {
+            Some(pos) => path[..pos].split_str("/"),
+            None => path[..0].split_str("/"),
+        };
+        let mut idx = 0;
+        invalidate(&mut self.directories[idx]);
+        for name in dirs.filter(|name| !name.is_empty()) {
+            let directories = &self.directories;
+            idx = match directories[idx]
+                .sub_directories
+                .iter()
+                .find(|sub_idx| directories[**sub_idx].name == name)
+            {
+                Some(sub_idx) => *sub_idx,
+                None => break,
+            };
+            invalidate(&mut self.directories[idx]);
+        }
+
+        fn invalidate(dir: &mut Directory) {
+            dir.stat = None;
+            dir.untracked_entries.clear();
+        }
+    }
+
+    /// Serialize this instance to `out`, assuming `object_hash` to be used for all object ids.
+    pub fn write_to(&self, mut out: impl std::io::Write, object_hash: gix_hash::Kind) -> std::io::Result<()> {
+        let null = ObjectId::null(object_hash);
+        let mut data = Vec::new();
+        write_var_int(&mut data, self.identifier.len() as u64);
+        data.extend_from_slice(&self.identifier);
+        for oid_stat in [&self.info_exclude, &self.excludes_file] {
+            write_stat(&mut data, &oid_stat.as_ref().map(|s| s.stat).unwrap_or_default());
+        }
+        data.extend_from_slice(&self.dir_flags.to_be_bytes());
+        for oid_stat in [&self.info_exclude, &self.excludes_file] {
+            data.extend_from_slice(oid_stat.as_ref().map_or(&null, |s| &s.id).as_bytes());
+        }
+        data.extend_from_slice(&self.exclude_filename_per_dir);
+        data.push(0);
+
+        write_var_int(&mut data, self.directories.len() as u64);
+        if !self.directories.is_empty() {
+            let mut order = Vec::with_capacity(self.directories.len());
+            write_directory_block(&mut data, &self.directories, 0, &mut order);
+
+            let bitmap = |is_set: fn(&Directory) -> bool| {
+                gix_bitmap::ewah::Vec::from_set_bits(
+                    order
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(pos, idx)| is_set(&self.directories[*idx]).then_some(pos)),
+                )
+            };
+            bitmap(|dir| dir.stat.is_some()).write_to(&mut data)?;
+            bitmap(|dir| dir.check_only).write_to(&mut data)?;
+            bitmap(|dir| dir.exclude_file_oid.is_some()).write_to(&mut data)?;
+            for stat in order.iter().filter_map(|idx| self.directories[*idx].stat.as_ref()) {
+                write_stat(&mut data, stat);
+            }
+            for id in order
+                .iter()
+                .filter_map(|idx| self.directories[*idx].exclude_file_oid.as_ref())
+            {
+                data.extend_from_slice(id.as_bytes());
+            }
+        }
+        data.push(0);
+
+        out.write_all(&SIGNATURE)?;
+        let data_len: u32 = data.len().try_into().expect("untracked cache never exceeds 4GB");
+        out.write_all(&data_len.to_be_bytes())?;
+        out.write_all(&data)
+    }
+}
+
+fn write_directory_block(out: &mut Vec<u8>, directories: &[Directory], idx: usize, order: &mut Vec<usize>) {
+    let dir = &directories[idx];
+    order.push(idx);
+    write_var_int(out, dir.untracked_entries.len() as u64);
+    write_var_int(out, dir.sub_directories.len() as u64);
+    for name in std::iter::once(&dir.name).chain(dir.untracked_entries.iter()) {
+        out.extend_from_slice(name);
+        out.push(0);
+    }
+    for sub_idx in &dir.sub_directories {
+        write_directory_block(out, directories, *sub_idx, order);
+    }
+}
+
+fn write_stat(out: &mut Vec<u8>, stat: &entry::Stat) {
+    for field in [
+        stat.ctime.secs,
+        stat.ctime.nsecs,
+        stat.mtime.secs,
+        stat.mtime.nsecs,
+        stat.dev,
+        stat.ino,
+        stat.uid,
+        stat.gid,
+        stat.size,
+    ] {
+        out.extend_from_slice(&field.to_be_bytes());
+    }
 }
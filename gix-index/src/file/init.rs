@@ -63,6 +63,7 @@ impl File {
         };
         if let Some(mut link) = file.link.take() {
             link.dissolve_into(&mut file, object_hash, options)?;
+            extension::fs_monitor::mark_valid_entries(&mut file);
         }
 
         Ok(file)
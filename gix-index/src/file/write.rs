@@ -1,6 +1,6 @@
 use gix_features::hash;
 
-use crate::{write, File, Version};
+use crate::{extension, write, File, State, Version};
 
 /// The error produced by [`File::write()`].
 #[derive(Debug, thiserror::Error)]
@@ -19,27 +19,29 @@ impl File {
     /// to retain all information of this index.
     pub fn write_to(
         &self,
-        mut out: impl std::io::Write,
+        out: impl std::io::Write,
         options: write::Options,
     ) -> std::io::Result<(Version, gix_hash::ObjectId)> {
-        let mut hasher = hash::Write::new(&mut out, self.state.object_hash);
-        let version = self.state.write_to(&mut hasher, options)?;
-
-        let hash = hasher.hash.digest();
-        out.write_all(&hash)?;
-        Ok((version, gix_hash::ObjectId::from(hash)))
+        write_state_to(&self.state, out, options)
     }
 
     /// Write ourselves to the path we were read from after acquiring a lock, using `options`.
     ///
+    /// If we were read from a split index, only the changes compared to its shared index are written along with the `link`
+    /// extension, just like `git` does. Should the shared index not be readable anymore, a regular index is written instead.
+    ///
     /// Note that the hash produced will be stored which is why we need to be mutable.
     pub fn write(&mut self, options: write::Options) -> Result<(), Error> {
+        let split_state = self.split_state();
+        if split_state.is_none() {
+            self.state.link = None;
+        }
         let mut lock = std::io::BufWriter::new(gix_lock::File::acquire_to_update_resource(
             &self.path,
             gix_lock::acquire::Fail::Immediately,
             None,
         )?);
-        let (version, digest) = self.write_to(&mut lock, options)?;
+        let (version, digest) = write_state_to(split_state.as_ref().unwrap_or(&self.state), &mut lock, options)?;
         match lock.into_inner() {
             Ok(lock) => lock.commit()?,
             Err(err) => return Err(err.into_error().into()),
@@ -48,4 +50,36 @@ impl File {
         self.checksum = Some(digest);
         Ok(())
     }
+
+    /// Return the state to write instead of ours if we are linked to a shared index that can still be read.
+    fn split_state(&self) -> Option<State> {
+        let shared_index_checksum = self.state.link.as_ref()?.shared_index_checksum;
+        let shared_index = File::at(
+            self.path.parent()?.join(format!("sharedindex.{shared_index_checksum}")),
+            self.state.object_hash,
+            crate::decode::Options {
+                expected_checksum: Some(shared_index_checksum),
+                ..Default::default()
+            },
+        )
+        .ok()?;
+        Some(extension::link::split(
+            &self.state,
+            &shared_index.state,
+            shared_index_checksum,
+        ))
+    }
+}
+
+fn write_state_to(
+    state: &State,
+    mut out: impl std::io::Write,
+    options: write::Options,
+) -> std::io::Result<(Version, gix_hash::ObjectId)> {
+    let mut hasher = hash::Write::new(&mut out, state.object_hash);
+    let version = state.write_to(&mut hasher, options)?;
+
+    let hash = hasher.hash.digest();
+    out.write_all(&hash)?;
+    Ok((version, gix_hash::ObjectId::from(hash)))
 }
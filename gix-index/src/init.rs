@@ -1,4 +1,4 @@
-mod from_tree {
+pub(crate) mod from_tree {
     use std::collections::VecDeque;
 
     use bstr::{BStr, BString, ByteSlice, ByteVec};
@@ -50,6 +50,7 @@ mod from_tree {
                 path_backing,
                 path: _,
                 path_deque: _,
+                trees: _,
             } = delegate;
 
             entries.sort_by(|a, b| Entry::cmp_filepaths(a.path_in(&path_backing), b.path_in(&path_backing)));
@@ -70,11 +71,13 @@ mod from_tree {
         }
     }
 
-    struct CollectEntries {
-        entries: Vec<Entry>,
-        path_backing: PathStorage,
+    pub(crate) struct CollectEntries {
+        pub entries: Vec<Entry>,
+        pub path_backing: PathStorage,
         path: BString,
         path_deque: VecDeque<BString>,
+        /// The path and id of each visited tree, if these are to be collected.
+        pub trees: Option<Vec<(BString, gix_hash::ObjectId)>>,
     }
 
     impl CollectEntries {
@@ -84,6 +87,16 @@ mod from_tree {
                 path_backing: Vec::new(),
                 path: BString::default(),
                 path_deque: VecDeque::new(),
+                trees: None,
+            }
+        }
+
+        /// Collect the entries of a tree at `prefix`, along with all of its sub-trees.
+        pub fn with_prefix_and_trees(prefix: &BStr) -> CollectEntries {
+            CollectEntries {
+                path: prefix.to_owned(),
+                trees: Some(Vec::new()),
+                ..CollectEntries::new()
             }
         }
 
@@ -143,7 +156,10 @@ mod from_tree {
             }
         }
 
-        fn visit_tree(&mut self, _entry: &gix_object::tree::EntryRef<'_>) -> gix_traverse::tree::visit::Action {
+        fn visit_tree(&mut self, entry: &gix_object::tree::EntryRef<'_>) -> gix_traverse::tree::visit::Action {
+            if let Some(trees) = self.trees.as_mut() {
+                trees.push((self.path.clone(), entry.oid.into()));
+            }
             Action::Continue
         }
 
@@ -150,6 +150,23 @@ pub(crate) mod util {
         (num, data).into()
     }
 
+    /// Append `num` to `out` in the variable-length encoding understood by [`var_int()`].
+    pub fn write_var_int(out: &mut Vec<u8>, mut num: u64) {
+        let mut buf = [0u8; 10];
+        let mut pos = buf.len() - 1;
+        buf[pos] = num as u8 & 0x7f;
+        loop {
+            num >>= 7;
+            if num == 0 {
+                break;
+            }
+            num -= 1;
+            pos -= 1;
+            buf[pos] = 0x80 | (num as u8 & 0x7f);
+        }
+        out.extend_from_slice(&buf[pos..]);
+    }
+
     #[inline]
     pub fn read_u32(data: &[u8]) -> Option<(u32, &[u8])> {
         split_at_pos(data, 4).map(|(num, data)| (u32::from_be_bytes(num.try_into().unwrap()), data))
@@ -16,7 +16,12 @@ pub enum Extensions {
     Given {
         /// Write the tree-cache extension, if present.
         tree_cache: bool,
-        /// Write the end-of-index-entry extension.
+        /// Write the untracked-cache extension, if present.
+        untracked_cache: bool,
+        /// Write the filesystem-monitor extension, if present.
+        fs_monitor: bool,
+        /// Write the end-of-index-entry extension, along with the index-entry-offset-table extension if entries are split
+        /// into more than one block according to [`Options::thread_limit`].
         end_of_index_entry: bool,
     },
     /// Write no optional extension at all for what should be the smallest possible index
@@ -37,9 +42,13 @@ impl Extensions {
             Extensions::All => Some(signature),
             Extensions::Given {
                 tree_cache,
+                untracked_cache,
+                fs_monitor,
                 end_of_index_entry,
             } => match signature {
                 extension::tree::SIGNATURE => tree_cache,
+                extension::untracked_cache::SIGNATURE => untracked_cache,
+                extension::fs_monitor::SIGNATURE => fs_monitor,
                 extension::end_of_index_entry::SIGNATURE => end_of_index_entry,
                 _ => &false,
             }
@@ -55,12 +64,36 @@ impl Extensions {
 pub struct Options {
     /// Configures which extensions to write
     pub extensions: Extensions,
+    /// The version to write, or `None` to write the lowest version that can represent all entries.
+    ///
+    /// Just like `git`, `V2` or `V3` are always chosen depending on whether or not entries have extended flags,
+    /// so only `V4` makes a difference. It compresses paths relative to the path of the previous entry, which
+    /// considerably reduces the size of indices with many entries in deeply nested directories.
+    pub version: Option<Version>,
+    /// The amount of threads that are expected to read the index, to split its entries into as many blocks whose offsets
+    /// are stored in the index-entry-offset-table extension, which allows to read entries in parallel.
+    ///
+    /// If `Some(1)`, the extension isn't written. If `Some(0)` or `None`, there is one block per 10.000 entries, but at most
+    /// one less than there are logical cores to leave a thread for reading extensions, just like `git` does
+    /// with `index.threads=true`.
+    pub thread_limit: Option<usize>,
 }
 
 impl State {
     /// Serialize this instance to `out` with [`options`][Options].
-    pub fn write_to(&self, out: impl std::io::Write, Options { extensions }: Options) -> std::io::Result<Version> {
-        let version = self.detect_required_version();
+    pub fn write_to(
+        &self,
+        out: impl std::io::Write,
+        Options {
+            extensions,
+            version,
+            thread_limit,
+        }: Options,
+    ) -> std::io::Result<Version> {
+        let version = match version {
+            Some(Version::V4) => Version::V4,
+            _ => self.detect_required_version(),
+        };
 
         let mut write = CountBytes::new(out);
         let num_entries: u32 = self
@@ -76,9 +109,15 @@ impl State {
             .try_into()
             .expect("definitely not too many entries");
 
-        let offset_to_entries = header(&mut write, version, num_entries - removed_entries)?;
-        let offset_to_extensions = entries(&mut write, self, offset_to_entries)?;
-        let (extension_toc, out) = self.write_extensions(write, offset_to_extensions, extensions)?;
+        let num_entries_to_write = num_entries - removed_entries;
+        let entries_per_block = extensions
+            .should_write(extension::end_of_index_entry::SIGNATURE)
+            .and_then(|_| entries_per_block(num_entries_to_write as usize, thread_limit));
+
+        let offset_to_entries = header(&mut write, version, num_entries_to_write)?;
+        let (offset_to_extensions, entry_offsets) =
+            entries(&mut write, self, offset_to_entries, version, entries_per_block)?;
+        let (extension_toc, out) = self.write_extensions(write, offset_to_extensions, extensions, &entry_offsets)?;
 
         if num_entries > 0
             && extensions
@@ -97,17 +136,50 @@ impl State {
         mut write: CountBytes<T>,
         offset_to_extensions: u32,
         extensions: Extensions,
+        entry_offsets: &[extension::index_entry_offset_table::Offset],
     ) -> std::io::Result<(Vec<(extension::Signature, u32)>, T)>
     where
         T: std::io::Write,
     {
         type WriteExtFn<'a> = &'a dyn Fn(&mut dyn std::io::Write) -> Option<std::io::Result<extension::Signature>>;
         let extensions: &[WriteExtFn<'_>] = &[
+            &|write| {
+                (entry_offsets.len() > 1).then(|| {
+                    extension::index_entry_offset_table::write_to(write, entry_offsets)
+                        .map(|_| extension::index_entry_offset_table::SIGNATURE)
+                })
+            },
+            &|write| {
+                self.link()
+                    .filter(|link| link.bitmaps.is_some())
+                    .map(|link| link.write_to(write).map(|_| extension::link::SIGNATURE))
+            },
             &|write| {
                 extensions
                     .should_write(extension::tree::SIGNATURE)
                     .and_then(|signature| self.tree().map(|tree| tree.write_to(write).map(|_| signature)))
             },
+            &|write| {
+                extensions
+                    .should_write(extension::untracked_cache::SIGNATURE)
+                    .and_then(|signature| {
+                        self.untracked()
+                            .map(|untracked| untracked.write_to(write, self.object_hash).map(|_| signature))
+                    })
+            },
+            &|write| {
+                extensions
+                    .should_write(extension::fs_monitor::SIGNATURE)
+                    .and_then(|signature| {
+                        // A split index only contains some of the entries, so the bitmap was already computed from all of them.
+                        let is_split = self.link().map_or(false, |link| link.bitmaps.is_some());
+                        self.fs_monitor().map(|fs_monitor| {
+                            fs_monitor
+                                .write_to((!is_split).then_some(self.entries.as_slice()), write)
+                                .map(|_| signature)
+                        })
+                    })
+            },
             &|write| {
                 self.is_sparse()
                     .then(|| extension::sparse::write_to(write).map(|_| extension::sparse::SIGNATURE))
@@ -132,7 +204,7 @@ impl State {
     fn detect_required_version(&self) -> Version {
         self.entries
             .iter()
-            .find_map(|e| e.flags.contains(entry::Flags::EXTENDED).then_some(Version::V3))
+            .find_map(|e| e.flags.needs_extended().then_some(Version::V3))
             .unwrap_or(Version::V2)
     }
 }
@@ -155,11 +227,71 @@ fn header<T: std::io::Write>(
     Ok(out.count)
 }
 
-fn entries<T: std::io::Write>(out: &mut CountBytes<T>, state: &State, header_size: u32) -> Result<u32, std::io::Error> {
+/// Return the amount of entries to put into each block of the index-entry-offset-table extension, or `None` if there
+/// would only be a single block.
+fn entries_per_block(num_entries: usize, thread_limit: Option<usize>) -> Option<usize> {
+    /// The amount of entries that make it worth to read them in their own thread.
+    const MIN_ENTRIES_PER_BLOCK: usize = 10_000;
+    let num_blocks = match thread_limit {
+        None | Some(0) => {
+            (num_entries / MIN_ENTRIES_PER_BLOCK).min(gix_features::parallel::num_threads(None).saturating_sub(1))
+        }
+        Some(num_threads) => num_threads.min(num_entries),
+    };
+    (num_blocks > 1).then(|| (num_entries + num_blocks - 1) / num_blocks)
+}
+
+fn entries<T: std::io::Write>(
+    out: &mut CountBytes<T>,
+    state: &State,
+    header_size: u32,
+    version: Version,
+    entries_per_block: Option<usize>,
+) -> Result<(u32, Vec<extension::index_entry_offset_table::Offset>), std::io::Error> {
+    let mut prev_path: &[u8] = &[];
+    let mut buf = Vec::new();
+    let mut offsets = Vec::new();
+    let mut num_entries_in_block = 0;
+    let mut is_start_of_block = false;
     for entry in state.entries() {
         if entry.flags.contains(entry::Flags::REMOVE) {
             continue;
         }
+        if let Some(entries_per_block) = entries_per_block {
+            if num_entries_in_block == 0 {
+                offsets.push(extension::index_entry_offset_table::Offset {
+                    from_beginning_of_file: out.count,
+                    num_entries: 0,
+                });
+                is_start_of_block = true;
+            }
+            offsets.last_mut().expect("at least one block").num_entries += 1;
+            num_entries_in_block = (num_entries_in_block + 1) % entries_per_block;
+        }
+        if version == Version::V4 {
+            // Paths are stored as the amount of bytes to remove from the end of the previous path, followed by the bytes
+            // to append to it, and entries aren't padded.
+            let path: &[u8] = entry.path(state);
+            // Like git, the first path of each block removes all of the previous path so blocks can be read on their own,
+            // while readers that don't know about blocks still see a valid delta.
+            let common_prefix_len = if std::mem::take(&mut is_start_of_block) {
+                0
+            } else {
+                prev_path
+                    .iter()
+                    .zip(path.iter())
+                    .take_while(|(lhs, rhs)| lhs == rhs)
+                    .count()
+            };
+            buf.clear();
+            crate::util::write_var_int(&mut buf, (prev_path.len() - common_prefix_len) as u64);
+            buf.extend_from_slice(&path[common_prefix_len..]);
+            buf.push(0);
+            entry.write_without_path_to(&mut *out, state)?;
+            out.write_all(&buf)?;
+            prev_path = path;
+            continue;
+        }
         entry.write_to(&mut *out, state)?;
         match (out.count - header_size) % 8 {
             0 => {}
@@ -170,7 +302,7 @@ fn entries<T: std::io::Write>(out: &mut CountBytes<T>, state: &State, header_siz
         };
     }
 
-    Ok(out.count)
+    Ok((out.count, offsets))
 }
 
 mod util {
@@ -49,3 +49,99 @@ fn sort_entries() {
         "we can find the correct entry now"
     );
 }
+
+#[test]
+fn remove_entries() {
+    let mut file = Fixture::Generated("v4_more_files_IEOT").open();
+    let num_entries = file.entries().len();
+    let removed_path = file.entry(0).path(&file).to_owned();
+
+    file.remove_entries(|path, _entry| path == removed_path);
+    assert_eq!(file.entries().len(), num_entries - 1);
+    assert_eq!(file.entry_by_path_and_stage(removed_path.as_ref(), 0), None);
+    assert!(file.verify_entries().is_ok(), "the order of remaining entries is kept");
+}
+
+mod sparse {
+    use bstr::BStr;
+    use gix::prelude::FindExt;
+    use gix_index::entry::{Flags, Mode};
+
+    use crate::index::Fixture;
+
+    fn entries(state: &gix_index::State) -> Vec<(String, gix_hash::ObjectId, Flags, Mode)> {
+        state
+            .entries()
+            .iter()
+            .map(|e| (e.path(state).to_string(), e.id, e.flags, e.mode))
+            .collect()
+    }
+
+    fn is_outside_of_cone(dir: &BStr) -> bool {
+        dir != "c1" && dir != "c1/c2" && !dir.starts_with(b"c1/c2/")
+    }
+
+    #[test]
+    fn expand_sparse_directories() -> crate::Result {
+        let repo = gix::open(gix_testtools::scripted_fixture_read_only_standalone(
+            "make_index/v3_sparse_index.sh",
+        )?)?;
+        let mut sparse = Fixture::Generated("v3_sparse_index").open();
+        let full = Fixture::Generated("v3_skip_worktree").open();
+        assert!(sparse.is_sparse());
+
+        let expanded = sparse.expand_sparse_directories(|oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+        assert_eq!(expanded, 2, "c1/c3 and d");
+        assert!(!sparse.is_sparse());
+        assert!(sparse.verify_entries().is_ok());
+        assert_eq!(
+            entries(&sparse),
+            entries(&full),
+            "expanded entries are equivalent to those of a full index with the same sparse checkout"
+        );
+        assert_eq!(sparse.tree(), full.tree(), "the tree extension is expanded as well");
+
+        assert_eq!(
+            sparse.expand_sparse_directories(|_, _| unreachable!("there are no sparse directories"))?,
+            0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn collapse_sparse_directories() {
+        let mut full = Fixture::Generated("v3_skip_worktree").open();
+        let sparse = Fixture::Generated("v3_sparse_index").open();
+        assert!(!full.is_sparse());
+
+        assert_eq!(full.collapse_sparse_directories(is_outside_of_cone), 2, "c1/c3 and d");
+        assert!(full.is_sparse());
+        assert!(full.verify_entries().is_ok());
+        assert_eq!(
+            entries(&full),
+            entries(&sparse),
+            "just like git does with `index.sparse`"
+        );
+        assert_eq!(full.tree(), sparse.tree());
+
+        assert_eq!(
+            full.collapse_sparse_directories(is_outside_of_cone),
+            0,
+            "collapsed directories stay collapsed"
+        );
+    }
+
+    #[test]
+    fn directories_with_entries_in_the_worktree_are_not_collapsed() {
+        let mut full = Fixture::Generated("v3_skip_worktree").open();
+        let idx = full.entry_index_by_path_and_stage("d/c4/a".into(), 0).expect("present");
+        full.entries_mut()[idx].flags.remove(Flags::SKIP_WORKTREE);
+
+        assert_eq!(full.collapse_sparse_directories(is_outside_of_cone), 1, "only c1/c3");
+        assert!(full
+            .entries()
+            .iter()
+            .all(|e| e.mode == Mode::FILE || e.path(&full) == "c1/c3/"));
+        assert_eq!(full.tree().and_then(|tree| tree.num_entries), Some(12));
+    }
+}
@@ -175,7 +175,28 @@ fn untr_extension_with_oids() {
     let file = loose_file("UNTR-with-oids");
     assert_eq!(file.version(), Version::V2);
 
-    assert!(file.untracked().is_some());
+    let untracked = file.untracked().expect("present");
+    assert!(untracked.identifier.ends_with(b", system Darwin\0"));
+    assert_eq!(untracked.dir_flags, gix_index::extension::untracked_cache::DIR_FLAGS);
+    assert_eq!(untracked.exclude_filename_per_dir, ".gitignore");
+    assert_eq!(
+        untracked.info_exclude.as_ref().map(|oid_stat| oid_stat.id),
+        Some(hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"))
+    );
+    assert!(untracked.excludes_file.is_none());
+    assert_eq!(
+        untracked
+            .directories
+            .iter()
+            .map(|dir| (dir.name.to_str().expect("valid UTF-8"), dir.check_only))
+            .collect::<Vec<_>>(),
+        [("", false), ("done", false), ("dthree", true), ("dtwo", true)]
+    );
+    let root = &untracked.directories[0];
+    assert_eq!(root.untracked_entries, ["three", ".gitignore", "dtwo/", "dthree/"]);
+    assert_eq!(root.sub_directories, [1, 2, 3]);
+    assert!(root.exclude_file_oid.is_some());
+    assert!(untracked.directories.iter().all(|dir| dir.stat.is_some()));
 }
 
 #[test]
@@ -183,7 +204,19 @@ fn fsmn_v1() {
     let file = loose_file("FSMN");
     assert_eq!(file.version(), Version::V2);
 
-    assert!(file.fs_monitor().is_some());
+    let fs_monitor = file.fs_monitor().expect("present");
+    assert_eq!(
+        fs_monitor.token,
+        gix_index::extension::fs_monitor::Token::V2 {
+            token: "1642331326943378000".into()
+        }
+    );
+    assert!(
+        file.entries()
+            .iter()
+            .all(|e| !e.flags.contains(gix_index::entry::Flags::FSMONITOR_VALID)),
+        "all entries are marked dirty"
+    );
 }
 
 #[test]
@@ -310,9 +343,12 @@ fn split_index_and_regular_index_of_same_content_are_indeed_the_same() {
     let split =
         verify(gix_index::File::at(base.join("split/.git/index"), gix_hash::Kind::Sha1, Default::default()).unwrap());
 
+    let link = split
+        .link()
+        .expect("the link is kept to be able to write the split index again");
     assert!(
-        split.link().is_none(),
-        "link extension is dissolved, merging the shared index permanently into the split one (for now)"
+        link.bitmaps.is_none(),
+        "the bitmaps are applied, merging the shared index into the split one in memory"
     );
 
     let regular = verify(
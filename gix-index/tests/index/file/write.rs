@@ -14,12 +14,45 @@ fn roundtrips() -> crate::Result {
             Generated("v2"),
             options_with(write::Extensions::Given {
                 tree_cache: true,
+                untracked_cache: false,
+                fs_monitor: false,
                 end_of_index_entry: true,
             }),
         ),
         (Generated("V2_empty"), only_tree_ext()),
         (Generated("v2_more_files"), only_tree_ext()),
         (Generated("v2_all_file_kinds"), only_tree_ext()),
+        (Loose("UNTR"), only_untracked_cache_ext()),
+        (Loose("UNTR-with-oids"), only_untracked_cache_ext()),
+        (
+            Generated("v4_more_files"),
+            Options {
+                version: Some(Version::V4),
+                ..only_tree_ext()
+            },
+        ),
+        (
+            Generated("v4_more_files_IEOT"),
+            Options {
+                extensions: write::Extensions::Given {
+                    tree_cache: true,
+                    untracked_cache: false,
+                    fs_monitor: false,
+                    end_of_index_entry: true,
+                },
+                version: Some(Version::V4),
+                thread_limit: Some(2),
+            },
+        ),
+        (
+            Loose("FSMN"),
+            options_with(write::Extensions::Given {
+                tree_cache: true,
+                untracked_cache: false,
+                fs_monitor: true,
+                end_of_index_entry: false,
+            }),
+        ),
     ];
 
     for (fixture, options) in input {
@@ -92,9 +125,7 @@ fn state_comparisons_with_various_extension_configurations() {
         Generated("v2_more_files"),
         Generated("v2_all_file_kinds"),
         Generated("v2_split_index"),
-        // TODO: this fails because git allows to configure the index version while gitoxide doesn't
-        //       the fixture artificially sets the version to V4 and gitoxide writes it back out as the lowest required version, V2
-        // Generated("v4_more_files_IEOT"),
+        Generated("v4_more_files_IEOT"),
         Generated("v3_skip_worktree"),
         Generated("v3_sparse_index_non_cone"),
         Generated("v3_sparse_index"),
@@ -106,15 +137,23 @@ fn state_comparisons_with_various_extension_configurations() {
             options_with(write::Extensions::All),
             options_with(write::Extensions::Given {
                 tree_cache: true,
+                untracked_cache: false,
+                fs_monitor: false,
                 end_of_index_entry: false,
             }),
             options_with(write::Extensions::Given {
                 tree_cache: false,
+                untracked_cache: true,
+                fs_monitor: true,
                 end_of_index_entry: true,
             }),
         ] {
             let expected = fixture.open();
             let fixture = fixture.to_name();
+            let options = Options {
+                version: Some(expected.version()),
+                ..options
+            };
 
             let mut out = Vec::<u8>::new();
             let (actual_version, _digest) = expected.write_to(&mut out, options).unwrap();
@@ -139,6 +178,128 @@ fn extended_flags_automatically_upgrade_the_version_to_avoid_data_loss() -> crat
     Ok(())
 }
 
+#[test]
+fn v4_is_only_written_if_requested() -> crate::Result {
+    let expected = Generated("v4_more_files_IEOT").open();
+    assert_eq!(expected.version(), Version::V4);
+
+    let mut buf = Vec::new();
+    let (actual_version, _digest) = expected.write_to(&mut buf, Default::default())?;
+    assert_eq!(
+        actual_version,
+        Version::V2,
+        "by default, the lowest possible version is written"
+    );
+
+    buf.clear();
+    let (actual_version, _digest) = expected.write_to(
+        &mut buf,
+        Options {
+            version: Some(Version::V3),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        actual_version,
+        Version::V2,
+        "V3 is only written if entries have extended flags, just like git does"
+    );
+    Ok(())
+}
+
+#[test]
+fn entry_offsets_are_written_in_blocks_that_can_be_read_in_parallel() -> crate::Result {
+    for fixture in [Generated("v2_more_files"), Generated("v4_more_files_IEOT")] {
+        let expected = fixture.open();
+        for thread_limit in [1, 3, 10, 20] {
+            let options = Options {
+                version: Some(expected.version()),
+                thread_limit: Some(thread_limit),
+                ..Default::default()
+            };
+            let mut buf = Vec::new();
+            let (actual_version, _digest) = expected.write_to(&mut buf, options)?;
+
+            let (actual, _) = State::from_bytes(
+                &buf,
+                FileTime::now(),
+                gix_hash::Kind::Sha1,
+                gix_index::decode::Options {
+                    thread_limit: Some(4),
+                    ..Default::default()
+                },
+            )?;
+            compare_states(&actual, actual_version, &expected, options, fixture.to_name());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn skip_worktree_flag_is_written_without_extended_flag() -> crate::Result {
+    let mut expected = Generated("v2").open();
+    expected.entries_mut()[0].flags.insert(entry::Flags::SKIP_WORKTREE);
+
+    let mut buf = Vec::new();
+    let (actual_version, _digest) = expected.write_to(&mut buf, Default::default())?;
+    assert_eq!(
+        actual_version,
+        Version::V3,
+        "the skip-worktree flag is an extended flag"
+    );
+
+    let (actual, _) = State::from_bytes(&buf, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+    assert_eq!(
+        actual.entries()[0].flags,
+        entry::Flags::EXTENDED | entry::Flags::SKIP_WORKTREE
+    );
+    Ok(())
+}
+
+#[test]
+fn fs_monitor_valid_flags_are_stored_in_the_fs_monitor_extension() -> crate::Result {
+    let mut expected = Loose("FSMN").open();
+    for idx in [1, 3] {
+        expected.entries_mut()[idx].flags.insert(entry::Flags::FSMONITOR_VALID);
+    }
+    expected.entries_mut()[4].flags.insert(entry::Flags::REMOVE);
+
+    let mut buf = Vec::new();
+    expected.write_to(&mut buf, Default::default())?;
+    let (actual, _) = State::from_bytes(&buf, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+    assert_eq!(
+        actual
+            .entries()
+            .iter()
+            .map(|e| e.flags.contains(entry::Flags::FSMONITOR_VALID))
+            .collect::<Vec<_>>(),
+        [false, true, false, true, false],
+        "removed entries don't affect the bitmap"
+    );
+    assert_eq!(
+        actual.fs_monitor().expect("written").token,
+        expected.fs_monitor().expect("present").token
+    );
+
+    buf.clear();
+    expected.write_to(
+        &mut buf,
+        options_with(write::Extensions::Given {
+            tree_cache: true,
+            untracked_cache: true,
+            fs_monitor: false,
+            end_of_index_entry: true,
+        }),
+    )?;
+    let (actual, _) = State::from_bytes(&buf, FileTime::now(), gix_hash::Kind::Sha1, Default::default())?;
+    assert!(actual.fs_monitor().is_none());
+    assert!(actual
+        .entries()
+        .iter()
+        .all(|e| !e.flags.contains(entry::Flags::FSMONITOR_VALID)));
+    Ok(())
+}
+
 #[test]
 fn remove_flag_is_respected() -> crate::Result {
     let mut index = Generated("v4_more_files_IEOT").open();
@@ -168,6 +329,59 @@ fn remove_flag_is_respected() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn split_index_is_written_relative_to_its_shared_index() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable_standalone("make_index/v2_split_vs_regular_index.sh")?;
+    let index_path = tmp.path().join("split/.git/index");
+    let mut index = gix_index::File::at(&index_path, gix_hash::Kind::Sha1, Default::default())?;
+    assert!(index.link().is_some(), "the shared index is remembered");
+
+    // `git` also replaces racily clean entries, so the bytes it wrote can't be expected here.
+    index.write(only_tree_ext())?;
+    let (split, _) = State::from_bytes(
+        &std::fs::read(&index_path)?,
+        FileTime::now(),
+        gix_hash::Kind::Sha1,
+        Default::default(),
+    )?;
+    assert_eq!(
+        split.entries().len(),
+        3,
+        "the changed entry is replaced and only the new ones are stored with their path"
+    );
+    let written = gix_index::File::at(&index_path, gix_hash::Kind::Sha1, Default::default())?;
+    assert!(written.link().is_some());
+    assert_eq!(written.entries(), index.entries());
+    assert_eq!(written.path_backing(), index.path_backing());
+
+    let num_entries = index.entries().len();
+    let idx = index
+        .entry_index_by_path_and_stage("y".into(), 0)
+        .expect("present in shared index");
+    index.entries_mut()[idx].flags.insert(entry::Flags::REMOVE);
+    index.write(only_tree_ext())?;
+
+    let (split, _) = State::from_bytes(
+        &std::fs::read(&index_path)?,
+        FileTime::now(),
+        gix_hash::Kind::Sha1,
+        Default::default(),
+    )?;
+    assert_eq!(
+        split.entries().len(),
+        3,
+        "only the changed entries are stored, the deletion is in the link extension"
+    );
+    let index = gix_index::File::at(&index_path, gix_hash::Kind::Sha1, Default::default())?;
+    assert!(index.link().is_some());
+    assert_eq!(
+        index.entries().iter().map(|e| e.path(&index)).collect::<Vec<_>>(),
+        ["b", "d", "e", "z"]
+    );
+    assert_eq!(index.entries().len(), num_entries - 1);
+    Ok(())
+}
+
 fn compare_states_against_baseline(
     actual: &State,
     actual_version: Version,
@@ -263,10 +477,31 @@ fn only_tree_ext() -> Options {
         extensions: write::Extensions::Given {
             end_of_index_entry: false,
             tree_cache: true,
+            untracked_cache: false,
+            fs_monitor: false,
+        },
+        version: None,
+        thread_limit: None,
+    }
+}
+
+fn only_untracked_cache_ext() -> Options {
+    Options {
+        extensions: write::Extensions::Given {
+            end_of_index_entry: false,
+            tree_cache: false,
+            untracked_cache: true,
+            fs_monitor: false,
         },
+        version: None,
+        thread_limit: None,
     }
 }
 
 fn options_with(extensions: write::Extensions) -> Options {
-    Options { extensions }
+    Options {
+        extensions,
+        version: None,
+        thread_limit: None,
+    }
 }
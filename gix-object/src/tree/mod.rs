@@ -111,12 +111,21 @@ impl PartialOrd for Entry {
 }
 
 impl Ord for Entry {
-    /// Entries compare by the common portion of the filename. This is critical for proper functioning of algorithms working on trees.
+    /// Entries compare by their filename like git does, with the names of trees treated as if they ended in a slash.
+    /// This is critical for proper functioning of algorithms working on trees.
     fn cmp(&self, other: &Self) -> Ordering {
         let common_len = self.filename.len().min(other.filename.len());
         self.filename[..common_len]
             .cmp(&other.filename[..common_len])
-            .then_with(|| self.filename.len().cmp(&other.filename.len()))
+            .then_with(|| {
+                let next_byte = |e: &Entry| -> Option<u8> {
+                    e.filename
+                        .get(common_len)
+                        .copied()
+                        .or_else(|| e.mode.is_tree().then_some(b'/'))
+                };
+                next_byte(self).cmp(&next_byte(other))
+            })
     }
 }
 
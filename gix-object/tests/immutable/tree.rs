@@ -142,3 +142,30 @@ mod entry_mode {
         );
     }
 }
+
+mod entry {
+    use gix_object::tree::{Entry, EntryMode};
+
+    #[test]
+    fn ordering_treats_trees_as_if_they_ended_in_a_slash() {
+        let entry = |name: &str, mode| Entry {
+            mode,
+            filename: name.into(),
+            oid: gix_hash::Kind::Sha1.null(),
+        };
+        let mut entries = [
+            entry("a", EntryMode::Tree),
+            entry("a.txt", EntryMode::Blob),
+            entry("a-", EntryMode::Blob),
+            entry("b", EntryMode::Blob),
+            entry("a0", EntryMode::Blob),
+        ];
+        entries.sort();
+        assert_eq!(
+            entries.iter().map(|e| e.filename.to_string()).collect::<Vec<_>>(),
+            ["a-", "a.txt", "a", "a0", "b"],
+            "like git, the tree 'a' is sorted like 'a/'"
+        );
+        assert!(entry("a", EntryMode::Blob) < entry("a.txt", EntryMode::Blob));
+    }
+}
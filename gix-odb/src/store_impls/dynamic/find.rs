@@ -300,17 +300,15 @@ where
     pub(crate) fn clear_cache(&self) {
         self.packed_object_count.borrow_mut().take();
     }
-}
 
-impl<S> gix_pack::Find for super::Handle<S>
-where
-    S: Deref<Target = super::Store> + Clone,
-{
-    type Error = Error;
+    /// Call the missing object handler, if set, with `id` and return `true` if the object should now be available.
+    pub(crate) fn make_available(&self, id: &gix_hash::oid) -> bool {
+        self.missing_object_handler
+            .as_ref()
+            .map_or(false, |handler| handler(id))
+    }
 
-    // TODO: probably make this method fallible, but that would mean its own error type.
-    fn contains(&self, id: impl AsRef<gix_hash::oid>) -> bool {
-        let id = id.as_ref();
+    fn contains_inner(&self, id: &gix_hash::oid) -> bool {
         let mut snapshot = self.snapshot.borrow_mut();
         loop {
             for (idx, index) in snapshot.indices.iter().enumerate() {
@@ -338,6 +336,19 @@ where
             }
         }
     }
+}
+
+impl<S> gix_pack::Find for super::Handle<S>
+where
+    S: Deref<Target = super::Store> + Clone,
+{
+    type Error = Error;
+
+    // TODO: probably make this method fallible, but that would mean its own error type.
+    fn contains(&self, id: impl AsRef<gix_hash::oid>) -> bool {
+        let id = id.as_ref();
+        self.contains_inner(id) || (self.make_available(id) && self.contains_inner(id))
+    }
 
     fn try_find_cached<'a>(
         &self,
@@ -346,6 +357,10 @@ where
         pack_cache: &mut impl DecodeEntry,
     ) -> Result<Option<(gix_object::Data<'a>, Option<gix_pack::data::entry::Location>)>, Self::Error> {
         let id = id.as_ref();
+        if self.missing_object_handler.is_some() && !self.contains_inner(id) {
+            // Whether or not the object could be made available, the lookup below will tell.
+            self.make_available(id);
+        }
         let mut snapshot = self.snapshot.borrow_mut();
         self.try_find_cached_inner(id, buffer, pack_cache, &mut snapshot, None)
     }
@@ -184,6 +184,9 @@ where
     fn try_header(&self, id: impl AsRef<oid>) -> Result<Option<Header>, Self::Error> {
         let id = id.as_ref();
         let mut snapshot = self.snapshot.borrow_mut();
-        self.try_header_inner(id, &mut snapshot, None)
+        match self.try_header_inner(id, &mut snapshot, None)? {
+            None if self.make_available(id) => self.try_header_inner(id, &mut snapshot, None),
+            header => Ok(header),
+        }
     }
 }
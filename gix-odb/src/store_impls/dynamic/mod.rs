@@ -21,12 +21,21 @@ where
 
     /// If true, replacements will not be performed even if these are available.
     pub ignore_replacements: bool,
+    /// If set, it's called with the id of each object that couldn't be found to give it a chance to make it available,
+    /// after which the lookup is retried once.
+    pub missing_object_handler: Option<MissingObjectHandler>,
 
     pub(crate) token: Option<handle::Mode>,
     snapshot: RefCell<load_index::Snapshot>,
     packed_object_count: RefCell<Option<u64>>,
 }
 
+/// A function called with the id of an object that couldn't be found in any pack or loose object database.
+///
+/// It returns `true` if it made the object available, for instance by fetching it from a promisor remote into a new pack,
+/// so the lookup should be retried.
+pub type MissingObjectHandler = std::sync::Arc<dyn Fn(&gix_hash::oid) -> bool + Send + Sync>;
+
 /// Decide what happens when all indices are loaded.
 #[derive(Clone, Copy)]
 pub enum RefreshMode {
@@ -32,6 +32,14 @@ fn db_with_all_object_sources() -> crate::Result<(gix_odb::Handle, tempfile::Tem
         &std::sync::atomic::AtomicBool::default(),
         gix_odb::pack::multi_index::write::Options {
             object_hash: gix_hash::Kind::Sha1,
+            reverse_index: false,
+            checkpoint: None,
+            deterministic: false,
+            max_packs: None,
+            check_delta_bases: false,
+            base_layers: Vec::new(),
+            preferred_pack: None,
+            bitmap: None,
         },
     )?;
     Ok((gix_odb::at(objects_dir.path())?, objects_dir))
@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use crate::{bitmap::File, index::EntryIndex};
+
+/// Access
+impl File {
+    /// The path at which this file was opened.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The kind of hash used for object ids.
+    pub fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    /// The checksum of the pack data file this bitmap was written for.
+    pub fn pack_checksum(&self) -> &gix_hash::oid {
+        &self.pack_checksum
+    }
+
+    /// The amount of commits with a bitmap.
+    pub fn num_bitmaps(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The indices of all commits with a bitmap in the pack index, in the order they are stored.
+    pub fn commits(&self) -> impl Iterator<Item = EntryIndex> + '_ {
+        self.entries.iter().map(|entry| entry.commit)
+    }
+
+    /// The hash of the path at which each object was found when writing the bitmap, in the order of entries in the pack index,
+    /// or `None` if it wasn't stored.
+    pub fn hash_cache(&self) -> Option<&[u32]> {
+        self.hash_cache.as_deref()
+    }
+
+    /// Return a bitmap of all objects of `kind` in the pack.
+    pub fn objects_of_kind(&self, kind: gix_object::Kind) -> &gix_bitmap::ewah::Vec {
+        &self.objects_by_kind[match kind {
+            gix_object::Kind::Commit => 0,
+            gix_object::Kind::Tree => 1,
+            gix_object::Kind::Blob => 2,
+            gix_object::Kind::Tag => 3,
+        }]
+    }
+
+    /// Return all objects reachable from the commit at `commit` in the pack index, including the commit itself, or `None` if
+    /// it doesn't have a bitmap.
+    ///
+    /// The objects are returned as uncompressed bitmap, with the object at position `n` in pack order being contained if bit
+    /// `n % 64` of the word at `n / 64` is set.
+    pub fn reachable_from(&self, commit: EntryIndex) -> Option<Vec<u64>> {
+        let mut pos = self
+            .lookup
+            .binary_search_by_key(&commit, |(commit, _)| *commit)
+            .ok()
+            .map(|lookup_pos| self.lookup[lookup_pos].1)?;
+        let mut words = Vec::new();
+        loop {
+            let entry = &self.entries[pos];
+            entry.bitmap.for_each_set_bit(|index| {
+                let word = index / 64;
+                if words.len() <= word {
+                    words.resize(word + 1, 0);
+                }
+                words[word] ^= 1 << (index % 64);
+                Some(())
+            });
+            if entry.xor_offset == 0 {
+                break;
+            }
+            pos -= usize::from(entry.xor_offset);
+        }
+        Some(words)
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    convert::{TryFrom, TryInto},
+    path::Path,
+};
+
+use crate::bitmap::{self, options, Entry, File};
+
+mod error {
+    /// The error returned by [File::at()][super::File::at()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not open bitmap file at '{path}'")]
+        Io {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
+        #[error("{message}")]
+        Corrupt { message: &'static str },
+        #[error("Unsupported bitmap version: {version}")]
+        UnsupportedVersion { version: u16 },
+        #[error("The bitmap doesn't contain all objects reachable from its commits, which is unsupported")]
+        NotAFullDag,
+        #[error(transparent)]
+        Ewah(#[from] gix_bitmap::ewah::decode::Error),
+    }
+}
+
+pub use error::Error;
+
+/// Initialization
+impl File {
+    /// Open the bitmap file at `path`, whose object ids are expected to be hashed with `object_hash`.
+    ///
+    /// Note that the caller is responsible for assuring that the [pack checksum][File::pack_checksum()] matches the pack this
+    /// bitmap is used with.
+    pub fn at(path: impl AsRef<Path>, object_hash: gix_hash::Kind) -> Result<File, Error> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|source| Error::Io {
+            source,
+            path: path.to_owned(),
+        })?;
+        let hash_len = object_hash.len_in_bytes();
+        let header_len = 4 /* signature */ + 2 /* version */ + 2 /* options */ + 4 /* num entries */ + hash_len;
+        if data.len() < header_len + hash_len {
+            return Err(Error::Corrupt {
+                message: "bitmap file is too small",
+            });
+        }
+        let (header, data) = data.split_at(header_len);
+        let data = &data[..data.len() - hash_len];
+
+        let (signature, header) = header.split_at(4);
+        if signature != bitmap::SIGNATURE {
+            return Err(Error::Corrupt {
+                message: "Invalid signature",
+            });
+        }
+        let (version, header) = header.split_at(2);
+        let version = u16::from_be_bytes(version.try_into().expect("two bytes"));
+        if version != bitmap::VERSION {
+            return Err(Error::UnsupportedVersion { version });
+        }
+        let (flags, header) = header.split_at(2);
+        let flags = u16::from_be_bytes(flags.try_into().expect("two bytes"));
+        if flags & options::FULL_DAG == 0 {
+            return Err(Error::NotAFullDag);
+        }
+        let (num_entries, pack_checksum) = header.split_at(4);
+        let num_entries = u32::from_be_bytes(num_entries.try_into().expect("four bytes")) as usize;
+        let pack_checksum = gix_hash::ObjectId::from(pack_checksum);
+
+        let mut data = data;
+        let mut decode_bitmap = || -> Result<gix_bitmap::ewah::Vec, Error> {
+            let (bitmap, rest) = gix_bitmap::ewah::decode(data)?;
+            data = rest;
+            Ok(bitmap)
+        };
+        let objects_by_kind = [decode_bitmap()?, decode_bitmap()?, decode_bitmap()?, decode_bitmap()?];
+
+        let mut entries = Vec::with_capacity(num_entries);
+        for index in 0..num_entries {
+            if data.len() < 4 + 1 + 1 {
+                return Err(Error::Corrupt {
+                    message: "eof while reading bitmap entry",
+                });
+            }
+            let (entry_header, rest) = data.split_at(4 + 1 + 1);
+            let commit = u32::from_be_bytes(entry_header[..4].try_into().expect("four bytes"));
+            let xor_offset = entry_header[4];
+            if usize::from(xor_offset) > index {
+                return Err(Error::Corrupt {
+                    message: "bitmap entry refers to an entry before the first one",
+                });
+            }
+            let (bitmap, rest) = gix_bitmap::ewah::decode(rest)?;
+            data = rest;
+            entries.push(Entry {
+                commit,
+                xor_offset,
+                bitmap,
+            });
+        }
+
+        let mut lookup: Vec<_> = entries
+            .iter()
+            .enumerate()
+            .map(|(pos, entry)| (entry.commit, pos))
+            .collect();
+        lookup.sort_unstable();
+
+        let hash_cache = if flags & options::HASH_CACHE != 0 {
+            let lookup_table_len = if flags & options::LOOKUP_TABLE != 0 {
+                // Each row holds the position of the commit, the offset to its bitmap and the row of the bitmap to XOR with.
+                num_entries * (4 + 8 + 4)
+            } else {
+                0
+            };
+            let hash_cache = data
+                .len()
+                .checked_sub(lookup_table_len)
+                .map(|len| &data[..len])
+                .filter(|hash_cache| hash_cache.len() % 4 == 0)
+                .ok_or(Error::Corrupt {
+                    message: "hash cache has an invalid size",
+                })?;
+            Some(
+                hash_cache
+                    .chunks(4)
+                    .map(|hash| u32::from_be_bytes(<[u8; 4]>::try_from(hash).expect("four bytes")))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        Ok(File {
+            path: path.to_owned(),
+            object_hash,
+            pack_checksum,
+            objects_by_kind,
+            entries,
+            lookup,
+            hash_cache,
+        })
+    }
+}
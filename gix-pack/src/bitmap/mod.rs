@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use crate::index::EntryIndex;
+
+/// The signature at the beginning of each bitmap file.
+pub(crate) const SIGNATURE: &[u8] = b"BITM";
+
+/// The only supported version of bitmap files.
+pub(crate) const VERSION: u16 = 1;
+
+/// Flags stored in the header of a bitmap file to indicate which optional data it contains.
+pub mod options {
+    /// Bitmaps contain all objects reachable from their commit, which must always be set.
+    pub const FULL_DAG: u16 = 0x1;
+    /// The hash of the path of each object follows the bitmaps, see [`name_hash()`][super::name_hash()].
+    pub const HASH_CACHE: u16 = 0x4;
+    /// A table to quickly find the bitmap of a commit follows the hash cache, which is ignored as all bitmaps are read anyway.
+    pub const LOOKUP_TABLE: u16 = 0x10;
+}
+
+/// A representation of a `.bitmap` file next to a pack, which stores all objects reachable from a selection of commits
+/// in the pack as bitmaps.
+///
+/// Each bit refers to an object in the pack, with the position of the bit being the position of the object in the pack data
+/// file as given by [`index::File::indices_in_pack_order()`][crate::index::File::indices_in_pack_order()].
+pub struct File {
+    path: PathBuf,
+    object_hash: gix_hash::Kind,
+    pack_checksum: gix_hash::ObjectId,
+    /// The positions of commits, trees, blobs and tags, in that order.
+    objects_by_kind: [gix_bitmap::ewah::Vec; 4],
+    entries: Vec<Entry>,
+    /// The commits of all entries along with the position of their entry, sorted by commit.
+    lookup: Vec<(EntryIndex, usize)>,
+    hash_cache: Option<Vec<u32>>,
+}
+
+/// A bitmap of all objects reachable from a commit, which may have to be combined with a previous bitmap.
+struct Entry {
+    commit: EntryIndex,
+    /// If not 0, the bitmap has to be XORed with the bitmap of the entry this many entries before.
+    xor_offset: u8,
+    bitmap: gix_bitmap::ewah::Vec,
+}
+
+///
+pub mod init;
+
+mod access;
+
+/// Return the hash of the `path` at which an object was found, as used by `git` to find good delta bases for objects.
+///
+/// The hash is designed so that paths with the same file name sort closely together, favoring the last characters.
+pub fn name_hash(path: &[u8]) -> u32 {
+    path.iter()
+        .filter(|byte| !matches!(**byte, b' ' | b'\t' | b'\n' | b'\r'))
+        .fold(0, |hash, byte| (hash >> 2).wrapping_add(u32::from(*byte) << 24))
+}
+
+///
+pub mod write;
@@ -0,0 +1,421 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use gix_features::progress::Progress;
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+
+use crate::{bitmap, data, index::EntryIndex};
+
+mod error {
+    /// The error returned by [`Bundle::write_bitmap_to()`][crate::Bundle::write_bitmap_to()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+        #[error(transparent)]
+        DecodeEntry(#[from] crate::data::decode::Error),
+        #[error(transparent)]
+        DecodeObject(#[from] gix_object::decode::Error),
+        #[error("Object {id} is reachable from the tips but missing in the pack, which is required for bitmaps")]
+        NotInPack { id: gix_hash::ObjectId },
+    }
+}
+pub use error::Error;
+
+/// Options for use in [`Bundle::write_bitmap_to()`][crate::Bundle::write_bitmap_to()].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// If `true`, store the [hash of the path][bitmap::name_hash()] at which each object was first seen, which allows to
+    /// find good delta bases when sending objects directly from bitmaps. `git` does this by default.
+    pub hash_cache: bool,
+}
+
+/// The result of [`Bundle::write_bitmap_to()`][crate::Bundle::write_bitmap_to()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The commits for which a bitmap was written, sorted from most recent to oldest.
+    pub commits: Vec<ObjectId>,
+    /// The checksum of the bitmap file stored in its trailer.
+    pub checksum: ObjectId,
+}
+
+/// The progress ids used in [`Bundle::write_bitmap_to()`][crate::Bundle::write_bitmap_to()].
+///
+/// Use this information to selectively extract the progress of interest in case the parent application has custom visualization.
+#[derive(Debug, Copy, Clone)]
+pub enum ProgressId {
+    /// The amount of objects whose kind was determined.
+    ReadObjectKinds,
+    /// The amount of commits reachable from the tips, which are candidates to receive a bitmap.
+    CountCommits,
+    /// The amount of bitmaps computed for the selected commits.
+    ComputeBitmaps,
+}
+
+impl From<ProgressId> for gix_features::progress::Id {
+    fn from(v: ProgressId) -> Self {
+        match v {
+            ProgressId::ReadObjectKinds => *b"BWOK",
+            ProgressId::CountCommits => *b"BWCC",
+            ProgressId::ComputeBitmaps => *b"BWCB",
+        }
+    }
+}
+
+/// The objects a bitmap is written for, with indices referring to their position in the lexicographically sorted list of their ids.
+pub(crate) trait Objects {
+    /// The checksum of the pack or multi-index the bitmap belongs to.
+    fn checksum(&self) -> ObjectId;
+    fn object_hash(&self) -> gix_hash::Kind;
+    fn num_objects(&self) -> u32;
+    fn lookup(&self, id: &gix_hash::oid) -> Option<EntryIndex>;
+    fn oid_at_index(&self, index: EntryIndex) -> &gix_hash::oid;
+    /// The indices of all objects in the order of the bits referring to them.
+    fn pack_order(&self) -> Vec<EntryIndex>;
+    /// The pack containing the object at `index`, along with the offset of its entry in it.
+    fn pack_and_offset_at_index(&self, index: EntryIndex) -> (&crate::Bundle, data::Offset);
+}
+
+impl Objects for crate::Bundle {
+    fn checksum(&self) -> ObjectId {
+        self.pack.checksum()
+    }
+
+    fn object_hash(&self) -> gix_hash::Kind {
+        self.index.object_hash()
+    }
+
+    fn num_objects(&self) -> u32 {
+        self.index.num_objects()
+    }
+
+    fn lookup(&self, id: &gix_hash::oid) -> Option<EntryIndex> {
+        self.index.lookup(id)
+    }
+
+    fn oid_at_index(&self, index: EntryIndex) -> &gix_hash::oid {
+        self.index.oid_at_index(index)
+    }
+
+    fn pack_order(&self) -> Vec<EntryIndex> {
+        self.index.indices_in_pack_order()
+    }
+
+    fn pack_and_offset_at_index(&self, index: EntryIndex) -> (&crate::Bundle, data::Offset) {
+        (self, self.index.pack_offset_at_index(index))
+    }
+}
+
+/// A commit that is a candidate for receiving a bitmap.
+struct Commit {
+    index: EntryIndex,
+    tree: EntryIndex,
+    parents: Vec<EntryIndex>,
+    seconds: u32,
+    is_tip: bool,
+}
+
+impl crate::Bundle {
+    /// Write a reachability bitmap for this bundle to `out` in the format of `.bitmap` files placed next to the pack, which allows
+    /// to know all objects reachable from the commits with a bitmap without traversing the commit graph.
+    ///
+    /// `tips` are the objects references point to, with annotated tags being peeled, and tips that aren't contained in this pack
+    /// being ignored. A selection of the commits reachable from them receives a bitmap, with tips being preferred just like in `git`.
+    /// Note that all objects reachable from the tips must be contained in this pack, or [`Error::NotInPack`] is returned.
+    ///
+    /// Progress is sent to `progress` and interruptions checked via `should_interrupt`.
+    pub fn write_bitmap_to<P>(
+        &self,
+        tips: impl IntoIterator<Item = ObjectId>,
+        out: impl std::io::Write,
+        progress: P,
+        should_interrupt: &AtomicBool,
+        options: Options,
+    ) -> Result<Outcome, Error>
+    where
+        P: Progress,
+    {
+        write_to(self, tips, out, progress, should_interrupt, options)
+    }
+}
+
+/// Write a bitmap for `objects` to `out`, see [`Bundle::write_bitmap_to()`][crate::Bundle::write_bitmap_to()] for details.
+pub(crate) fn write_to<P>(
+    objects: &impl Objects,
+    tips: impl IntoIterator<Item = ObjectId>,
+    out: impl std::io::Write,
+    mut progress: P,
+    should_interrupt: &AtomicBool,
+    Options { hash_cache }: Options,
+) -> Result<Outcome, Error>
+where
+    P: Progress,
+{
+    let num_objects = objects.num_objects() as usize;
+    let pack_order = objects.pack_order();
+    let mut positions = vec![0; num_objects];
+    for (position, index) in pack_order.iter().enumerate() {
+        positions[*index as usize] = position;
+    }
+    let lookup = |id: &gix_hash::oid| objects.lookup(id).ok_or_else(|| Error::NotInPack { id: id.to_owned() });
+
+    let kinds = {
+        let mut progress = progress.add_child_with_id("read object kinds", ProgressId::ReadObjectKinds.into());
+        let start = Instant::now();
+        progress.init(Some(num_objects), gix_features::progress::count("objects"));
+        let mut kinds = Vec::with_capacity(num_objects);
+        for index in 0..objects.num_objects() {
+            let (bundle, pack_offset) = objects.pack_and_offset_at_index(index);
+            let header = bundle.pack.decode_header(bundle.pack.entry(pack_offset), |id| {
+                bundle.index.lookup(id).map(|index| {
+                    data::decode::header::ResolvedBase::InPack(
+                        bundle.pack.entry(bundle.index.pack_offset_at_index(index)),
+                    )
+                })
+            })?;
+            kinds.push(header.kind);
+            progress.inc();
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+        }
+        progress.show_throughput(start);
+        kinds
+    };
+
+    let mut buf = Vec::new();
+    let mut cache = crate::cache::Never;
+    let mut commits = Vec::new();
+    let mut commit_by_index = HashMap::new();
+    {
+        let mut progress = progress.add_child_with_id("count commits", ProgressId::CountCommits.into());
+        let start = Instant::now();
+        progress.init(None, gix_features::progress::count("commits"));
+        let mut stack = Vec::new();
+        for tip in tips {
+            let mut index = match objects.lookup(&tip) {
+                Some(index) => index,
+                None => continue,
+            };
+            while kinds[index as usize] == gix_object::Kind::Tag {
+                let tag = object_at_index(objects, index, &mut buf, &mut cache)?;
+                index = lookup(&gix_object::TagRefIter::from_bytes(tag.data).target_id()?)?;
+            }
+            if kinds[index as usize] == gix_object::Kind::Commit {
+                stack.push((index, true));
+            }
+        }
+
+        while let Some((index, is_tip)) = stack.pop() {
+            if let Some(&pos) = commit_by_index.get(&index) {
+                let commit: &mut Commit = &mut commits[pos];
+                commit.is_tip |= is_tip;
+                continue;
+            }
+            let object = object_at_index(objects, index, &mut buf, &mut cache)?;
+            let commit = gix_object::CommitRef::from_bytes(object.data)?;
+            let parents = commit.parents().map(|id| lookup(&id)).collect::<Result<Vec<_>, _>>()?;
+            stack.extend(parents.iter().map(|parent| (*parent, false)));
+            commit_by_index.insert(index, commits.len());
+            commits.push(Commit {
+                index,
+                tree: lookup(&commit.tree())?,
+                parents,
+                seconds: commit.committer.time.seconds_since_unix_epoch,
+                is_tip,
+            });
+            progress.inc();
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+        }
+        progress.show_throughput(start);
+    }
+
+    let selected = select_commits(&commits);
+    let mut names = hash_cache.then(|| vec![0_u32; num_objects]);
+    let mut bitmaps = HashMap::<usize, Vec<u64>>::new();
+    {
+        let mut progress = progress.add_child_with_id("compute bitmaps", ProgressId::ComputeBitmaps.into());
+        let start = Instant::now();
+        progress.init(Some(selected.len()), gix_features::progress::count("bitmaps"));
+
+        // Oldest commits first, so that the bitmaps of their descendants can reuse them instead of traversing all history.
+        let mut oldest_first = selected.clone();
+        oldest_first.sort_by_key(|pos| commits[*pos].seconds);
+        let mut trees = Vec::new();
+        for commit_pos in oldest_first {
+            let mut bits = vec![0_u64; (num_objects + 63) / 64];
+            let mut stack = vec![commit_pos];
+            while let Some(pos) = stack.pop() {
+                if let Some(bitmap) = bitmaps.get(&pos) {
+                    for (word, other) in bits.iter_mut().zip(bitmap) {
+                        *word |= other;
+                    }
+                    continue;
+                }
+                let commit = &commits[pos];
+                if !set_bit(&mut bits, positions[commit.index as usize]) {
+                    continue;
+                }
+                trees.push((commit.tree, Vec::new()));
+                stack.extend(commit.parents.iter().map(|parent| commit_by_index[parent]));
+            }
+
+            while let Some((index, path)) = trees.pop() {
+                if !set_bit(&mut bits, positions[index as usize]) {
+                    continue;
+                }
+                if let Some(names) = names.as_mut() {
+                    names[index as usize] = bitmap::name_hash(&path);
+                }
+                let tree = object_at_index(objects, index, &mut buf, &mut cache)?;
+                for entry in gix_object::TreeRefIter::from_bytes(tree.data) {
+                    let entry = entry?;
+                    if entry.mode == EntryMode::Commit {
+                        continue;
+                    }
+                    let entry_index = lookup(entry.oid)?;
+                    let mut entry_path = path.clone();
+                    if !entry_path.is_empty() {
+                        entry_path.push(b'/');
+                    }
+                    entry_path.extend_from_slice(entry.filename);
+                    if entry.mode.is_tree() {
+                        trees.push((entry_index, entry_path));
+                    } else if set_bit(&mut bits, positions[entry_index as usize]) {
+                        if let Some(names) = names.as_mut() {
+                            names[entry_index as usize] = bitmap::name_hash(&entry_path);
+                        }
+                    }
+                }
+            }
+
+            bitmaps.insert(commit_pos, bits);
+            progress.inc();
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+        }
+        progress.show_throughput(start);
+    }
+
+    let mut positions_by_kind = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+    for (position, index) in pack_order.iter().enumerate() {
+        let slot = match kinds[*index as usize] {
+            gix_object::Kind::Commit => 0,
+            gix_object::Kind::Tree => 1,
+            gix_object::Kind::Blob => 2,
+            gix_object::Kind::Tag => 3,
+        };
+        positions_by_kind[slot].push(position);
+    }
+
+    let mut out = gix_features::hash::Write::new(out, objects.object_hash());
+    let options = bitmap::options::FULL_DAG | if hash_cache { bitmap::options::HASH_CACHE } else { 0 };
+    out.write_all(bitmap::SIGNATURE)?;
+    out.write_all(&bitmap::VERSION.to_be_bytes())?;
+    out.write_all(&options.to_be_bytes())?;
+    out.write_all(&(selected.len() as u32).to_be_bytes())?;
+    out.write_all(objects.checksum().as_slice())?;
+    for positions in positions_by_kind {
+        gix_bitmap::ewah::Vec::from_set_bits(positions).write_to(&mut out)?;
+    }
+    for pos in &selected {
+        out.write_all(&commits[*pos].index.to_be_bytes())?;
+        out.write_all(&[0 /* xor offset */, 0 /* flags */])?;
+        gix_bitmap::ewah::Vec::from_set_bits(set_bits(&bitmaps[pos])).write_to(&mut out)?;
+    }
+    if let Some(names) = names {
+        for name in names {
+            out.write_all(&name.to_be_bytes())?;
+        }
+    }
+    let checksum: ObjectId = out.hash.digest().into();
+    out.inner.write_all(checksum.as_slice())?;
+
+    Ok(Outcome {
+        commits: selected
+            .iter()
+            .map(|pos| objects.oid_at_index(commits[*pos].index).to_owned())
+            .collect(),
+        checksum,
+    })
+}
+
+fn object_at_index<'a>(
+    objects: &impl Objects,
+    index: EntryIndex,
+    buf: &'a mut Vec<u8>,
+    cache: &mut impl crate::cache::DecodeEntry,
+) -> Result<gix_object::Data<'a>, data::decode::Error> {
+    let (bundle, pack_offset) = objects.pack_and_offset_at_index(index);
+    Ok(bundle.get_object_at_offset(pack_offset, buf, cache)?.0)
+}
+
+/// Select the positions of the commits to receive a bitmap from most recent to oldest like `git` does, which is all of them
+/// if there are only a few, or else with increasing distance between them the older they get, with tips being preferred.
+fn select_commits(commits: &[Commit]) -> Vec<usize> {
+    let mut most_recent_first: Vec<_> = (0..commits.len()).collect();
+    most_recent_first.sort_by_key(|pos| std::cmp::Reverse(commits[*pos].seconds));
+    if commits.len() < 100 {
+        return most_recent_first;
+    }
+
+    let mut selected = Vec::new();
+    let mut start = 0;
+    loop {
+        let distance = next_commit_distance(start);
+        if start + distance >= most_recent_first.len() {
+            break;
+        }
+        let candidates = &most_recent_first[start..=start + distance];
+        let chosen = candidates
+            .iter()
+            .find(|pos| commits[**pos].is_tip)
+            .unwrap_or(&candidates[distance]);
+        selected.push(*chosen);
+        start += distance + 1;
+    }
+    selected
+}
+
+/// Return the amount of commits to skip after the one at `index` in recency order before selecting the next one.
+fn next_commit_distance(index: usize) -> usize {
+    const MUST_REGION: usize = 100;
+    const MIN_REGION: usize = 20_000;
+    const MIN_COMMITS: usize = 100;
+    const MAX_COMMITS: usize = 5_000;
+
+    if index <= MUST_REGION {
+        0
+    } else if index <= MIN_REGION {
+        (index - MUST_REGION).min(MIN_COMMITS)
+    } else {
+        (index - MIN_REGION).clamp(MIN_COMMITS, MAX_COMMITS)
+    }
+}
+
+/// Set the bit at `position` and return `true` if it wasn't set before.
+fn set_bit(bits: &mut [u64], position: usize) -> bool {
+    let (word, mask) = (position / 64, 1 << (position % 64));
+    let was_unset = bits[word] & mask == 0;
+    bits[word] |= mask;
+    was_unset
+}
+
+fn set_bits(bits: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    bits.iter().enumerate().flat_map(|(word_index, word)| {
+        (0..64)
+            .filter(move |bit| word & (1 << bit) != 0)
+            .map(move |bit| word_index * 64 + bit)
+    })
+}
@@ -30,7 +30,16 @@ impl crate::Bundle {
         out: &'a mut Vec<u8>,
         cache: &mut impl crate::cache::DecodeEntry,
     ) -> Result<(gix_object::Data<'a>, crate::data::entry::Location), crate::data::decode::Error> {
-        let ofs = self.index.pack_offset_at_index(idx);
+        self.get_object_at_offset(self.index.pack_offset_at_index(idx), out, cache)
+    }
+
+    /// Like [`get_object_by_index()`][Self::get_object_by_index()], but for the object whose entry is at `ofs` in the pack.
+    pub(crate) fn get_object_at_offset<'a>(
+        &self,
+        ofs: crate::data::Offset,
+        out: &'a mut Vec<u8>,
+        cache: &mut impl crate::cache::DecodeEntry,
+    ) -> Result<(gix_object::Data<'a>, crate::data::entry::Location), crate::data::decode::Error> {
         let pack_entry = self.pack.entry(ofs);
         let header_size = pack_entry.header_size();
         self.pack
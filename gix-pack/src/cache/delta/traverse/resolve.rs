@@ -106,7 +106,8 @@ where
 
             let mut fully_resolved_delta_bytes = bytes_buf.borrow_mut();
             fully_resolved_delta_bytes.resize(result_size as usize, 0);
-            crate::data::delta::apply(&base_bytes, &mut fully_resolved_delta_bytes, &delta_bytes[header_ofs..]);
+            crate::data::delta::apply(&base_bytes, &mut fully_resolved_delta_bytes, &delta_bytes[header_ofs..])
+                .expect("delta instructions to fit their base and result");
 
             // FIXME: this actually invalidates the "pack_offset()" computation, which is not obvious to consumers
             //        at all
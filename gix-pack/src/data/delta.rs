@@ -15,56 +15,56 @@ pub fn decode_header_size(d: &[u8]) -> (u64, usize) {
     (size, consumed)
 }
 
-pub fn apply(base: &[u8], mut target: &mut [u8], data: &[u8]) {
+/// Apply the delta instructions in `data` to `base` to produce `target`, which must have the size of the result.
+///
+/// Fail if the instructions refer to data outside of `base` or `data`, or if they don't fill `target` exactly, as is the case
+/// for corrupt deltas.
+pub fn apply(base: &[u8], mut target: &mut [u8], data: &[u8]) -> Result<(), crate::data::decode::Error> {
+    use crate::data::decode::Error::CorruptDelta;
     let mut i = 0;
-    while let Some(cmd) = data.get(i) {
+    let next_byte = |i: &mut usize| {
+        let byte = data.get(*i).copied().ok_or(CorruptDelta);
+        *i += 1;
+        byte
+    };
+    while let Some(cmd) = data.get(i).copied() {
         i += 1;
         match cmd {
             cmd if cmd & 0b1000_0000 != 0 => {
                 let (mut ofs, mut size): (u32, u32) = (0, 0);
-                if cmd & 0b0000_0001 != 0 {
-                    ofs = data[i] as u32;
-                    i += 1;
+                for (nth, flag) in [0b0000_0001, 0b0000_0010, 0b0000_0100, 0b0000_1000].iter().enumerate() {
+                    if cmd & *flag != 0 {
+                        ofs |= (next_byte(&mut i)? as u32) << (nth * 8);
+                    }
                 }
-                if cmd & 0b0000_0010 != 0 {
-                    ofs |= (data[i] as u32) << 8;
-                    i += 1;
-                }
-                if cmd & 0b0000_0100 != 0 {
-                    ofs |= (data[i] as u32) << 16;
-                    i += 1;
-                }
-                if cmd & 0b0000_1000 != 0 {
-                    ofs |= (data[i] as u32) << 24;
-                    i += 1;
-                }
-                if cmd & 0b0001_0000 != 0 {
-                    size = data[i] as u32;
-                    i += 1;
-                }
-                if cmd & 0b0010_0000 != 0 {
-                    size |= (data[i] as u32) << 8;
-                    i += 1;
-                }
-                if cmd & 0b0100_0000 != 0 {
-                    size |= (data[i] as u32) << 16;
-                    i += 1;
+                for (nth, flag) in [0b0001_0000, 0b0010_0000, 0b0100_0000].iter().enumerate() {
+                    if cmd & *flag != 0 {
+                        size |= (next_byte(&mut i)? as u32) << (nth * 8);
+                    }
                 }
                 if size == 0 {
                     size = 0x10000; // 65536
                 }
                 let ofs = ofs as usize;
-                std::io::Write::write(&mut target, &base[ofs..ofs + size as usize])
-                    .expect("delta copy from base: byte slices must match");
+                let copy = base.get(ofs..ofs + size as usize).ok_or(CorruptDelta)?;
+                if copy.len() > target.len() {
+                    return Err(CorruptDelta);
+                }
+                std::io::Write::write(&mut target, copy).expect("delta copy from base: byte slices must match");
             }
-            0 => panic!("encountered unsupported command code: 0"),
+            0 => return Err(CorruptDelta),
             size => {
-                std::io::Write::write(&mut target, &data[i..i + *size as usize])
-                    .expect("delta copy data: slice sizes to match up");
-                i += *size as usize;
+                let insert = data.get(i..i + size as usize).ok_or(CorruptDelta)?;
+                if insert.len() > target.len() {
+                    return Err(CorruptDelta);
+                }
+                std::io::Write::write(&mut target, insert).expect("delta copy data: slice sizes to match up");
+                i += size as usize;
             }
         }
     }
-    assert_eq!(i, data.len());
-    assert_eq!(target.len(), 0);
+    if !target.is_empty() {
+        return Err(CorruptDelta);
+    }
+    Ok(())
 }
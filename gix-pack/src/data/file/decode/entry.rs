@@ -262,6 +262,13 @@ impl File {
         let total_delta_data_size: usize = total_delta_data_size.try_into().expect("delta data to fit in memory");
 
         let chain_len = chain.len();
+        let base_len = match base_buffer_size {
+            Some(end) => end,
+            None => cursor
+                .decompressed_size
+                .try_into()
+                .expect("size representable by machine"),
+        };
         let (first_buffer_end, second_buffer_end) = {
             let delta_start = base_buffer_size.unwrap_or(0);
             out.resize(delta_start + total_delta_data_size, 0);
@@ -351,6 +358,7 @@ impl File {
         let (mut source_buf, mut target_buf) = buffers.split_at_mut(first_buffer_end);
 
         let mut last_result_size = None;
+        let mut source_len = base_len;
         for (
             delta_idx,
             Delta {
@@ -365,7 +373,11 @@ impl File {
             if delta_idx + 1 == chain_len {
                 last_result_size = Some(result_size);
             }
-            delta::apply(&source_buf[..base_size], &mut target_buf[..result_size], data);
+            if base_size != source_len {
+                return Err(Error::CorruptDelta);
+            }
+            delta::apply(&source_buf[..base_size], &mut target_buf[..result_size], data)?;
+            source_len = result_size;
             // use the target as source for the next delta
             std::mem::swap(&mut source_buf, &mut target_buf);
         }
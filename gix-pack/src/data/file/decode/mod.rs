@@ -13,4 +13,6 @@ pub enum Error {
     ZlibInflate(#[from] gix_features::zlib::inflate::Error),
     #[error("A delta chain could not be followed as the ref base with id {0} could not be found")]
     DeltaBaseUnresolved(gix_hash::ObjectId),
+    #[error("A delta doesn't match the size of its base or refers to data outside of it")]
+    CorruptDelta,
 }
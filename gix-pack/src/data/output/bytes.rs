@@ -37,6 +37,9 @@ pub struct FromEntriesIter<I, W> {
     /// It stores the pack offsets at which objects begin.
     /// Additionally we store if an object was invalid, and if so we will not write it nor will we allow delta objects to it.
     pack_offsets_and_validity: Vec<(u64, bool)>,
+    /// The ids of all entries written thus far by their index, to allow deltas to refer to their base by id instead of
+    /// by offset, or `None` if offsets are used.
+    ids: Option<Vec<gix_hash::ObjectId>>,
     /// If we are done, no additional writes will occur
     is_done: bool,
 }
@@ -74,12 +77,23 @@ where
             trailer: None,
             entry_version: version,
             pack_offsets_and_validity: Vec::with_capacity(num_entries as usize),
+            ids: None,
             written: 0,
             header_info: Some((version, num_entries)),
             is_done: false,
         }
     }
 
+    /// If `ofs_delta` is `false`, write deltas that refer to their base object by id instead of by its offset in the pack,
+    /// which is what receivers need that don't support the `ofs-delta` capability. Offsets are used by default as they
+    /// are smaller.
+    ///
+    /// Note that this only affects deltas whose base is part of the pack, as all others refer to their base by id anyway.
+    pub fn with_ofs_delta(mut self, ofs_delta: bool) -> Self {
+        self.ids = (!ofs_delta).then(Vec::new);
+        self
+    }
+
     /// Consume this instance and return the `output` implementation.
     ///
     /// _Note_ that the `input` iterator can be moved out of this instance beforehand.
@@ -102,7 +116,13 @@ where
         }
         match self.input.next() {
             Some(entries) => {
-                for entry in entries.map_err(Error::Input)? {
+                for mut entry in entries.map_err(Error::Input)? {
+                    if let Some(ids) = self.ids.as_mut() {
+                        if let output::entry::Kind::DeltaRef { object_index } = entry.kind {
+                            entry.kind = output::entry::Kind::DeltaOid { id: ids[object_index] };
+                        }
+                        ids.push(entry.id);
+                    }
                     if entry.is_invalid() {
                         self.pack_offsets_and_validity.push((0, false));
                         continue;
@@ -254,6 +254,9 @@ mod expand {
                                     .map_err(Error::TreeTraverse)?;
                                     &traverse_delegate.non_trees
                                 } else {
+                                    // Objects are only recorded once as they are marked seen, so changes compared to
+                                    // all parents must be kept.
+                                    changes_delegate.clear();
                                     for commit_id in &parent_commit_ids {
                                         let parent_tree_id = {
                                             let (parent_commit_obj, location) = db.find(commit_id, buf2)?;
@@ -279,7 +282,6 @@ mod expand {
                                             gix_object::TreeRefIter::from_bytes(parent_tree_obj.data)
                                         };
 
-                                        changes_delegate.clear();
                                         gix_diff::tree::Changes::from(Some(parent_tree))
                                             .needed_to_obtain(
                                                 current_tree_iter.clone(),
@@ -154,6 +154,14 @@ impl index::File {
         }
     }
 
+    /// Return the indices of all entries sorted by their offset into the pack data file, which is the order in which
+    /// [bitmaps][crate::bitmap] refer to objects.
+    pub fn indices_in_pack_order(&self) -> Vec<EntryIndex> {
+        let mut indices: Vec<_> = (0..self.num_objects).collect();
+        indices.sort_by_key(|index| self.pack_offset_at_index(*index));
+        indices
+    }
+
     /// Return a vector of ascending offsets into our respective pack data file.
     ///
     /// Useful to control an iteration over all pack entries in a cache-friendly way.
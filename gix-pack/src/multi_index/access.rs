@@ -59,6 +59,20 @@ impl File {
     pub fn index_names(&self) -> &[PathBuf] {
         &self.index_names
     }
+    /// Returns the amount of layers below this one if it's part of an incremental multi-index, or 0 if it stands alone.
+    pub fn num_base_files(&self) -> u8 {
+        self.num_base_files
+    }
+    /// Return the checksums of all layers below this one, starting with the lowest one, which is empty unless it's part of an
+    /// incremental multi-index.
+    ///
+    /// Pack ids and entry indices of this layer are local to it, i.e. they don't account for the packs and objects in the layers below.
+    pub fn base_checksums(&self) -> impl Iterator<Item = &gix_hash::oid> + '_ {
+        let data = self.base_ofs.map_or(&[][..], |ofs| {
+            &self.data[ofs..][..self.num_base_files as usize * self.hash_len]
+        });
+        data.chunks(self.hash_len).map(gix_hash::oid::from_bytes_unchecked)
+    }
 }
 
 impl File {
@@ -274,3 +274,28 @@ pub mod large_offsets {
         8 * large_offsets as u64
     }
 }
+
+/// Information about the base layers of an incremental multi-index.
+pub mod base {
+    use std::ops::Range;
+
+    /// The id uniquely identifying the table of checksums of base layers.
+    pub const ID: gix_chunk::Id = *b"BASE";
+
+    /// Return the amount of bytes needed to store the checksums of `num_base_layers`.
+    pub fn storage_size(num_base_layers: usize, object_hash: gix_hash::Kind) -> u64 {
+        (num_base_layers * object_hash.len_in_bytes()) as u64
+    }
+
+    pub(crate) fn write(base_checksums: &[gix_hash::ObjectId], mut out: impl std::io::Write) -> std::io::Result<()> {
+        for checksum in base_checksums {
+            out.write_all(checksum.as_slice())?;
+        }
+        Ok(())
+    }
+
+    /// Return true if the size of the `offset` range matches the checksums of `num_base_files` hashed with `hash`.
+    pub fn is_valid(offset: &Range<usize>, hash: gix_hash::Kind, num_base_files: u8) -> bool {
+        offset.end - offset.start == num_base_files as usize * hash.len_in_bytes()
+    }
+}
@@ -66,7 +66,7 @@ impl TryFrom<&Path> for File {
             });
         }
 
-        let (version, object_hash, num_chunks, num_indices) = {
+        let (version, object_hash, num_chunks, num_base_files, num_indices) = {
             let (signature, data) = data.split_at(4);
             if signature != Self::SIGNATURE {
                 return Err(Error::Corrupt {
@@ -85,12 +85,13 @@ impl TryFrom<&Path> for File {
             let (num_chunks, data) = data.split_at(1);
             let num_chunks = num_chunks[0];
 
-            let (_num_base_files, data) = data.split_at(1); // TODO: handle base files once it's clear what this does
+            let (num_base_files, data) = data.split_at(1);
+            let num_base_files = num_base_files[0];
 
             let (num_indices, _) = data.split_at(4);
             let num_indices = crate::read_u32(num_indices);
 
-            (version, object_hash, num_chunks, num_indices)
+            (version, object_hash, num_chunks, num_base_files, num_indices)
         };
 
         let chunks = gix_chunk::file::Index::from_bytes(&data, Self::HEADER_LEN, num_chunks as u32)?;
@@ -129,6 +130,18 @@ impl TryFrom<&Path> for File {
             })
             .ok()
             .transpose()?;
+        let base = if num_base_files == 0 {
+            None
+        } else {
+            Some(chunks.validated_usize_offset_by_id(chunk::base::ID, |offset| {
+                chunk::base::is_valid(&offset, object_hash, num_base_files)
+                    .then_some(offset)
+                    .ok_or(Error::InvalidChunkSize {
+                        id: chunk::base::ID,
+                        message: "The chunk with checksums of base layers doesn't match their amount",
+                    })
+            })??)
+        };
 
         let checksum_offset = chunks.highest_offset() as usize;
         let trailer = &data[checksum_offset..];
@@ -150,6 +163,8 @@ impl TryFrom<&Path> for File {
             lookup_ofs: lookup.start,
             offsets_ofs: offsets.start,
             large_offsets_ofs: large_offsets.map(|r| r.start),
+            base_ofs: base.map(|r| r.start),
+            num_base_files,
             num_objects,
             num_indices,
         })
@@ -33,12 +33,15 @@ pub struct File {
     /// The amount of pack files contained within
     num_indices: u32,
     num_objects: u32,
+    /// The amount of layers of an incremental multi-index below this one.
+    num_base_files: u8,
 
     fan: [u32; 256],
     index_names: Vec<PathBuf>,
     lookup_ofs: usize,
     offsets_ofs: usize,
     large_offsets_ofs: Option<usize>,
+    base_ofs: Option<usize>,
 }
 
 ///
@@ -6,7 +6,7 @@ use crate::{index, multi_index::File};
 
 ///
 pub mod integrity {
-    use crate::multi_index::EntryIndex;
+    use crate::multi_index::{EntryIndex, PackIndex};
 
     /// Returned by [`multi_index::File::verify_integrity()`][crate::multi_index::File::verify_integrity()].
     #[derive(thiserror::Error, Debug)]
@@ -30,6 +30,12 @@ pub mod integrity {
         OidNotFound { id: gix_hash::ObjectId },
         #[error("The object id at multi-index entry {index} wasn't in order")]
         OutOfOrder { index: EntryIndex },
+        #[error("The multi-index entry {index} refers to pack {pack_id}, but there are only {num_indices} pack(s)")]
+        PackIdOutOfBounds {
+            index: EntryIndex,
+            pack_id: PackIndex,
+            num_indices: PackIndex,
+        },
         #[error("The fan at index {index} is out of order as it's larger then the following value.")]
         Fan { index: usize },
         #[error("The multi-index claims to have no objects")]
@@ -69,6 +75,54 @@ pub mod integrity {
     }
 }
 
+///
+pub mod object {
+    /// Returned by [`multi_index::File::verify_object()`][crate::multi_index::File::verify_object()].
+    #[derive(thiserror::Error, Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("{id} wasn't found in the multi-pack index")]
+        NotFound { id: gix_hash::ObjectId },
+        #[error(transparent)]
+        BundleInit(#[from] crate::bundle::init::Error),
+        #[error("{id} wasn't found in the index referenced in the multi-pack index")]
+        OidNotFoundInIndex { id: gix_hash::ObjectId },
+        #[error("Object {id} should be at pack-offset {expected_pack_offset} but was found at {actual_pack_offset}")]
+        PackOffsetMismatch {
+            id: gix_hash::ObjectId,
+            expected_pack_offset: u64,
+            actual_pack_offset: u64,
+        },
+        #[error(transparent)]
+        Decode(#[from] crate::data::decode::Error),
+        #[error("The {kind} object at pack-offset {offset} was expected to have id {expected} but hashed to {actual}")]
+        ObjectMismatch {
+            expected: gix_hash::ObjectId,
+            actual: gix_hash::ObjectId,
+            offset: u64,
+            kind: gix_object::Kind,
+        },
+        #[error("Interrupted")]
+        Interrupted,
+    }
+
+    /// Returned by [`multi_index::File::verify_object()`][crate::multi_index::File::verify_object()] if the object is intact.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Outcome {
+        /// The kind of the verified object.
+        pub kind: gix_object::Kind,
+        /// The size of the fully decompressed and delta-resolved object in bytes.
+        pub size: u64,
+        /// The location of the object within its pack.
+        pub location: crate::data::entry::Location,
+    }
+
+    /// The packs opened by [`multi_index::File::verify_object()`][crate::multi_index::File::verify_object()], to be reused
+    /// by subsequent calls for objects of the same multi-index.
+    #[derive(Default)]
+    pub struct Bundles(pub(crate) Vec<Option<crate::Bundle>>);
+}
+
 ///
 pub mod checksum {
     /// Returned by [`multi_index::File::verify_checksum()`][crate::multi_index::File::verify_checksum()].
@@ -93,6 +147,103 @@ impl File {
         )
     }
 
+    /// Verify only the object with `id` by locating it through this multi-index, decoding it from its pack while resolving
+    /// all deltas, and assuring its recomputed hash matches `id`.
+    ///
+    /// Use this for targeted checks of individual objects instead of verifying all packs in full with
+    /// [`verify_integrity()`][File::verify_integrity()].
+    /// Packs are opened on demand and kept in `bundles` for use in subsequent calls, and `cache` is used to accelerate
+    /// delta resolution. `should_interrupt` is checked before decoding the object and while hashing it.
+    /// Corrupt deltas are reported as [decode errors][object::Error::Decode].
+    pub fn verify_object(
+        &self,
+        id: impl AsRef<gix_hash::oid>,
+        bundles: &mut object::Bundles,
+        cache: &mut impl crate::cache::DecodeEntry,
+        should_interrupt: &AtomicBool,
+    ) -> Result<object::Outcome, object::Error> {
+        use crate::data::decode::entry::ResolvedBase;
+
+        let id = id.as_ref();
+        let entry_index = self
+            .lookup(id)
+            .ok_or_else(|| object::Error::NotFound { id: id.to_owned() })?;
+        let (pack_id, expected_pack_offset) = self.pack_id_and_pack_offset_at_index(entry_index);
+        if bundles.0.len() < self.index_names.len() {
+            bundles.0.resize_with(self.index_names.len(), || None);
+        }
+        let bundle = match &mut bundles.0[pack_id as usize] {
+            Some(bundle) => bundle,
+            slot @ None => {
+                let index_path = self
+                    .path
+                    .parent()
+                    .expect("must be in a directory")
+                    .join(&self.index_names[pack_id as usize]);
+                slot.insert(crate::Bundle::at(index_path, self.object_hash)?)
+            }
+        };
+        let index_entry = bundle
+            .index
+            .lookup(id)
+            .ok_or_else(|| object::Error::OidNotFoundInIndex { id: id.to_owned() })?;
+        let actual_pack_offset = bundle.index.pack_offset_at_index(index_entry);
+        if actual_pack_offset != expected_pack_offset {
+            return Err(object::Error::PackOffsetMismatch {
+                id: id.to_owned(),
+                expected_pack_offset,
+                actual_pack_offset,
+            });
+        }
+
+        if should_interrupt.load(std::sync::atomic::Ordering::Relaxed) {
+            return Err(object::Error::Interrupted);
+        }
+        let pack = &bundle.pack;
+        let index = &bundle.index;
+        let entry = pack.entry(actual_pack_offset);
+        let entry_header_size = entry.header_size();
+        let mut object = Vec::new();
+        let decoded = pack.decode_entry(
+            entry,
+            &mut object,
+            |id, _out| {
+                index
+                    .lookup(id)
+                    .map(|idx| ResolvedBase::InPack(pack.entry(index.pack_offset_at_index(idx))))
+            },
+            cache,
+        )?;
+        let kind = decoded.kind;
+
+        let mut hasher = gix_features::hash::hasher(self.object_hash);
+        hasher.update(&gix_object::encode::loose_header(kind, object.len()));
+        for chunk in object.chunks(64 * 1024) {
+            if should_interrupt.load(std::sync::atomic::Ordering::Relaxed) {
+                return Err(object::Error::Interrupted);
+            }
+            hasher.update(chunk);
+        }
+        let actual = gix_hash::ObjectId::from(hasher.digest());
+        if actual != id {
+            return Err(object::Error::ObjectMismatch {
+                expected: id.to_owned(),
+                actual,
+                offset: actual_pack_offset,
+                kind,
+            });
+        }
+        Ok(object::Outcome {
+            kind,
+            size: object.len() as u64,
+            location: crate::data::entry::Location {
+                pack_id: pack.id,
+                entry_size: entry_header_size + decoded.compressed_size,
+                pack_offset: actual_pack_offset,
+            },
+        })
+    }
+
     /// Similar to [`verify_integrity()`][File::verify_integrity()] but without any deep inspection of objects.
     ///
     /// Instead we only validate the contents of the multi-index itself.
@@ -134,6 +285,21 @@ impl File {
         self.verify_integrity_inner(progress, should_interrupt, true, options)
     }
 
+    fn checked_pack_id_at_index(
+        &self,
+        index: crate::multi_index::EntryIndex,
+    ) -> Result<crate::multi_index::PackIndex, index::traverse::Error<integrity::Error>> {
+        let (pack_id, _) = self.pack_id_and_pack_offset_at_index(index);
+        if pack_id >= self.num_indices {
+            return Err(index::traverse::Error::Processor(integrity::Error::PackIdOutOfBounds {
+                index,
+                pack_id,
+                num_indices: self.num_indices,
+            }));
+        }
+        Ok(pack_id)
+    }
+
     fn verify_integrity_inner<C, P, F>(
         &self,
         mut progress: P,
@@ -191,14 +357,12 @@ impl File {
                         index: entry_index,
                     }));
                 }
-                let (pack_id, _) = self.pack_id_and_pack_offset_at_index(entry_index);
-                pack_ids_and_offsets.push((pack_id, entry_index));
+                pack_ids_and_offsets.push((self.checked_pack_id_at_index(entry_index)?, entry_index));
                 progress.inc();
             }
             {
                 let entry_index = self.num_objects - 1;
-                let (pack_id, _) = self.pack_id_and_pack_offset_at_index(entry_index);
-                pack_ids_and_offsets.push((pack_id, entry_index));
+                pack_ids_and_offsets.push((self.checked_pack_id_at_index(entry_index)?, entry_index));
             }
             // sort by pack-id to allow handling all indices matching a pack while its open.
             pack_ids_and_offsets.sort_by(|l, r| l.0.cmp(&r.0));
@@ -1,6 +1,6 @@
 use std::{
     convert::TryInto,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::atomic::{AtomicBool, Ordering},
     time::{Instant, SystemTime},
 };
@@ -9,6 +9,8 @@ use gix_features::progress::Progress;
 
 use crate::multi_index;
 
+mod checkpoint;
+
 mod error {
     /// The error returned by [multi_index::File::write_from_index_paths()][super::multi_index::File::write_from_index_paths()]..
     #[derive(Debug, thiserror::Error)]
@@ -20,6 +22,29 @@ mod error {
         Interrupted,
         #[error(transparent)]
         OpenIndex(#[from] crate::index::init::Error),
+        #[error("Could not read or write the checkpoint file")]
+        Checkpoint(#[source] std::io::Error),
+        #[error("Refusing to index {count} packs as the limit is {limit} - consider repacking")]
+        TooManyPacks { count: usize, limit: usize },
+        #[error(transparent)]
+        OpenPack(#[from] crate::data::header::decode::Error),
+        #[error("{count} objects are ref-deltas whose base isn't contained in any of the indexed packs, the first one being {first_oid}")]
+        UnresolvedDeltas {
+            count: usize,
+            first_oid: gix_hash::ObjectId,
+        },
+        #[error("Could not open a base layer of the incremental multi-index")]
+        OpenBaseLayer(#[from] crate::multi_index::init::Error),
+        #[error("Refusing to write a layer on top of {count} base layers as at most 255 are supported - consider merging them")]
+        TooManyBaseLayers { count: usize },
+        #[error("Reverse indices and bitmaps can't be written for layers of an incremental multi-index")]
+        UnsupportedForLayers,
+        #[error("A bitmap can only be written along with the reverse index")]
+        BitmapWithoutReverseIndex,
+        #[error("The preferred pack with index '{}' isn't among the indexed packs", name.display())]
+        UnknownPreferredPack { name: std::path::PathBuf },
+        #[error(transparent)]
+        Bitmap(#[from] crate::bitmap::write::Error),
     }
 }
 pub use error::Error;
@@ -33,16 +58,89 @@ pub(crate) struct Entry {
     index_mtime: SystemTime,
 }
 
+/// The commits to write a reachability bitmap for along with the multi-index, for use in [`Options::bitmap`].
+pub struct Bitmap {
+    /// The commits, or tags pointing to them, whose reachable objects should be stored, typically the tips of all references.
+    ///
+    /// All objects reachable from them must be contained in the indexed packs.
+    pub tips: Vec<gix_hash::ObjectId>,
+    /// Options to configure how the bitmap is written.
+    pub options: crate::bitmap::write::Options,
+}
+
+/// Where and how often to store the entries collected so far, for use in [`Options::checkpoint`].
+pub struct Checkpoint {
+    /// The path to the checkpoint file.
+    pub path: PathBuf,
+    /// The amount of entries to read from indices before storing all entries collected so far again, which is done
+    /// only after an index was read completely. If `0`, they are stored after each index.
+    ///
+    /// Entries are always stored once all indices were read.
+    pub interval: usize,
+}
+
 /// Options for use in [`multi_index::File::write_from_index_paths()`].
 pub struct Options {
     /// The kind of hash to use for objects and to expect in the input files.
     pub object_hash: gix_hash::Kind,
+    /// If `true`, produce the reverse index for the multi-index as well, see [`Outcome::reverse_index`].
+    pub reverse_index: bool,
+    /// If set, the file to periodically store the sorted table of entries collected so far in, to be able to resume from it
+    /// after a crash or interruption, which helps with sets of indices that are very large.
+    ///
+    /// If the file exists and was created for the same set of indices, i.e. their names, sizes and modification times
+    /// are the same, the entries it contains are used instead of reading and sorting these indices again, and only the
+    /// remaining indices are read. Otherwise it is overwritten. The file is removed once the multi-index was written successfully.
+    pub checkpoint: Option<Checkpoint>,
+    /// If `true`, the output only depends on the content of the input indices and their names, but not on the order in which
+    /// they are passed or their modification times.
+    ///
+    /// Objects contained in multiple packs are then taken from the pack whose name sorts first, instead of from the pack with the
+    /// most recently modified index, making the output reproducible across machines.
+    pub deterministic: bool,
+    /// If set, the maximum amount of packs to put into the multi-index, with [`Error::TooManyPacks`] being returned if there are more.
+    ///
+    /// This is useful to signal that a repack is overdue instead of indexing an ever growing amount of packs.
+    /// If `None`, the amount of packs is unlimited.
+    pub max_packs: Option<usize>,
+    /// If `true`, read the header of each object in the pack data files next to the indices to assure that the bases of ref-deltas
+    /// are contained in one of the packs or in one of the [base layers][Options::base_layers], and fail with
+    /// [`Error::UnresolvedDeltas`] otherwise.
+    ///
+    /// This detects thin packs that were never completed, as objects in them couldn't be decoded from the resulting multi-index.
+    pub check_delta_bases: bool,
+    /// The paths to the layers of an incremental multi-index, starting with the lowest one, to write a new layer on top of,
+    /// or nothing to write a multi-index that stands alone.
+    ///
+    /// Packs and objects that are already contained in one of the layers are omitted, and the checksums of all layers are recorded
+    /// in the new one. It's meant to be written into a file named [`layer_file_name()`] whose checksum is then appended to the chain.
+    /// Note that [`reverse_index`][Options::reverse_index] and [`bitmap`][Options::bitmap] are unsupported for layers.
+    pub base_layers: Vec<PathBuf>,
+    /// The name of the index of the pack whose objects are preferred over duplicates in other packs, and which comes first in the
+    /// reverse index, or `None` to only prefer a pack if a bitmap is written.
+    ///
+    /// Bitmaps need a preferred pack, which is why it defaults to the pack with the least recently modified index then, just like
+    /// `git` does it, or the one whose name sorts first if the output is [`deterministic`][Options::deterministic].
+    pub preferred_pack: Option<PathBuf>,
+    /// If set, produce a reachability bitmap for the multi-index as well, see [`Outcome::bitmap`].
+    ///
+    /// This requires [`reverse_index`][Options::reverse_index] to be set as `git` uses it to map bits to objects.
+    pub bitmap: Option<Bitmap>,
 }
 
 /// The result of [`multi_index::File::write_from_index_paths()`].
 pub struct Outcome<P> {
     /// The calculated multi-index checksum of the file at `multi_index_path`.
     pub multi_index_checksum: gix_hash::ObjectId,
+    /// The content of the reverse index if [`Options::reverse_index`] was set, in the format git uses for `.rev` files.
+    ///
+    /// It's meant to be written side-by-side with the multi-index into a file named [`reverse_index_file_name()`], which
+    /// is best done atomically with a lock file.
+    pub reverse_index: Option<Vec<u8>>,
+    /// The content of the reachability bitmap if [`Options::bitmap`] was set, in the format git uses for `.bitmap` files.
+    ///
+    /// It's meant to be written into a file named [`bitmap_file_name()`] next to the multi-index and its reverse index.
+    pub bitmap: Option<Vec<u8>>,
     /// The input progress
     pub progress: P,
 }
@@ -67,8 +165,26 @@ impl From<ProgressId> for gix_features::progress::Id {
     }
 }
 
+/// Return the name of the reverse index file for the multi-index with `multi_index_checksum`, to be placed next to it.
+pub fn reverse_index_file_name(multi_index_checksum: &gix_hash::oid) -> PathBuf {
+    format!("multi-pack-index-{multi_index_checksum}.rev").into()
+}
+
+/// Return the name of the bitmap file for the multi-index with `multi_index_checksum`, to be placed next to it.
+pub fn bitmap_file_name(multi_index_checksum: &gix_hash::oid) -> PathBuf {
+    format!("multi-pack-index-{multi_index_checksum}.bitmap").into()
+}
+
+/// Return the name of the file for the layer of an incremental multi-index with `multi_index_checksum`, to be placed
+/// into the `multi-pack-index.d` directory next to the packs, along with the `multi-pack-index-chain` file listing all layers.
+pub fn layer_file_name(multi_index_checksum: &gix_hash::oid) -> PathBuf {
+    format!("multi-pack-index-{multi_index_checksum}.midx").into()
+}
+
 impl multi_index::File {
     pub(crate) const SIGNATURE: &'static [u8] = b"MIDX";
+    pub(crate) const REVERSE_INDEX_SIGNATURE: &'static [u8] = b"RIDX";
+    pub(crate) const REVERSE_INDEX_HEADER_LEN: usize = 4 /*signature*/ + 4 /*version*/ + 4 /*hash id*/;
     pub(crate) const HEADER_LEN: usize = 4 /*signature*/ +
         1 /*version*/ +
         1 /*object id version*/ +
@@ -84,34 +200,138 @@ impl multi_index::File {
         out: impl std::io::Write,
         mut progress: P,
         should_interrupt: &AtomicBool,
-        Options { object_hash }: Options,
+        Options {
+            object_hash,
+            reverse_index,
+            checkpoint,
+            deterministic,
+            max_packs,
+            check_delta_bases,
+            base_layers,
+            preferred_pack,
+            bitmap,
+        }: Options,
     ) -> Result<Outcome<P>, Error>
     where
         P: Progress,
     {
+        if base_layers.len() > u8::MAX as usize {
+            return Err(Error::TooManyBaseLayers {
+                count: base_layers.len(),
+            });
+        }
+        if !base_layers.is_empty() && (reverse_index || bitmap.is_some()) {
+            return Err(Error::UnsupportedForLayers);
+        }
+        if bitmap.is_some() && !reverse_index {
+            return Err(Error::BitmapWithoutReverseIndex);
+        }
+        let base_layers = base_layers
+            .iter()
+            .map(multi_index::File::at)
+            .collect::<Result<Vec<_>, _>>()?;
+        index_paths.retain(|path| {
+            let name = path.file_name().expect("file name present");
+            !base_layers
+                .iter()
+                .any(|layer| layer.index_names().iter().any(|n| n.as_os_str() == name))
+        });
+
+        if let Some(limit) = max_packs.filter(|limit| index_paths.len() > *limit) {
+            return Err(Error::TooManyPacks {
+                count: index_paths.len(),
+                limit,
+            });
+        }
         let out = gix_features::hash::Write::new(out, object_hash);
         let (index_paths_sorted, index_filenames_sorted) = {
-            index_paths.sort();
+            index_paths.sort_by(|lhs, rhs| lhs.file_name().cmp(&rhs.file_name()).then_with(|| lhs.cmp(rhs)));
             let file_names = index_paths
                 .iter()
                 .map(|p| PathBuf::from(p.file_name().expect("file name present")))
                 .collect::<Vec<_>>();
             (index_paths, file_names)
         };
+        let preferred_pack = match preferred_pack {
+            Some(name) => Some(
+                index_filenames_sorted
+                    .iter()
+                    .position(|n| *n == name)
+                    .ok_or(Error::UnknownPreferredPack { name })? as u32,
+            ),
+            None if bitmap.is_some() && !index_paths_sorted.is_empty() => Some(if deterministic {
+                0
+            } else {
+                index_paths_sorted
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, path)| {
+                        path.metadata()
+                            .and_then(|m| m.modified())
+                            .unwrap_or(SystemTime::UNIX_EPOCH)
+                    })
+                    .map(|(index_id, _)| index_id as u32)
+                    .expect("at least one index")
+            }),
+            None => None,
+        };
+
+        let checkpoint = checkpoint
+            .as_ref()
+            .map(|checkpoint| {
+                checkpoint::fingerprint(&index_paths_sorted, deterministic, preferred_pack, object_hash)
+                    .map(|fingerprint| (checkpoint, fingerprint))
+            })
+            .transpose()
+            .map_err(Error::Checkpoint)?;
+        let (mut entries, num_resumed_indices) = match &checkpoint {
+            Some((checkpoint, fingerprint)) => checkpoint::read(&checkpoint.path, fingerprint)
+                .map_err(Error::Checkpoint)?
+                .unwrap_or_default(),
+            None => Default::default(),
+        };
+        let index_mtime = |path: &Path| {
+            path.metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+        };
+        if num_resumed_indices != 0 {
+            // Duplicates in indices that are yet to be read are chosen by the modification time of the index.
+            let mtimes: Vec<_> = index_paths_sorted[..num_resumed_indices]
+                .iter()
+                .map(|path| index_mtime(path))
+                .collect();
+            for entry in &mut entries {
+                entry.index_mtime = mtimes[entry.pack_index as usize];
+            }
+        }
 
-        let entries = {
-            let mut entries = Vec::new();
+        let sort_and_deduplicate = |entries: &mut Vec<Entry>| {
+            entries.sort_by(|l, r| {
+                l.id.cmp(&r.id)
+                    .then_with(|| (Some(l.pack_index) != preferred_pack).cmp(&(Some(r.pack_index) != preferred_pack)))
+                    .then_with(|| {
+                        if deterministic {
+                            std::cmp::Ordering::Equal
+                        } else {
+                            l.index_mtime.cmp(&r.index_mtime).reverse()
+                        }
+                    })
+                    .then_with(|| l.pack_index.cmp(&r.pack_index))
+            });
+            entries.dedup_by_key(|e| e.id);
+        };
+        if num_resumed_indices < index_paths_sorted.len() {
             let start = Instant::now();
             let mut progress =
                 progress.add_child_with_id("Collecting entries", ProgressId::FromPathsCollectingEntries.into());
             progress.init(Some(index_paths_sorted.len()), gix_features::progress::count("indices"));
+            progress.inc_by(num_resumed_indices);
 
             // This could be parallelized… but it's probably not worth it unless you have 500mio objects.
-            for (index_id, index) in index_paths_sorted.iter().enumerate() {
-                let mtime = index
-                    .metadata()
-                    .and_then(|m| m.modified())
-                    .unwrap_or(SystemTime::UNIX_EPOCH);
+            let mut num_entries_since_checkpoint = 0;
+            for (index_id, index) in index_paths_sorted.iter().enumerate().skip(num_resumed_indices) {
+                let mtime = index_mtime(index);
                 let index = crate::index::File::at(index, object_hash)?;
 
                 entries.reserve(index.num_objects() as usize);
@@ -121,7 +341,18 @@ impl multi_index::File {
                     pack_offset: e.pack_offset,
                     index_mtime: mtime,
                 }));
+                num_entries_since_checkpoint += index.num_objects() as usize;
                 progress.inc();
+
+                let is_last_index = index_id + 1 == index_paths_sorted.len();
+                if let Some((checkpoint, fingerprint)) = &checkpoint {
+                    if !is_last_index && num_entries_since_checkpoint >= checkpoint.interval {
+                        sort_and_deduplicate(&mut entries);
+                        checkpoint::write(&checkpoint.path, fingerprint, index_id + 1, &entries)
+                            .map_err(Error::Checkpoint)?;
+                        num_entries_since_checkpoint = 0;
+                    }
+                }
                 if should_interrupt.load(Ordering::Relaxed) {
                     return Err(Error::Interrupted);
                 }
@@ -131,19 +362,55 @@ impl multi_index::File {
             let start = Instant::now();
             progress.set_name("Deduplicate");
             progress.init(Some(entries.len()), gix_features::progress::count("entries"));
-            entries.sort_by(|l, r| {
-                l.id.cmp(&r.id)
-                    .then_with(|| l.index_mtime.cmp(&r.index_mtime).reverse())
-                    .then_with(|| l.pack_index.cmp(&r.pack_index))
-            });
-            entries.dedup_by_key(|e| e.id);
+            sort_and_deduplicate(&mut entries);
             progress.inc_by(entries.len());
             progress.show_throughput(start);
+            if let Some((checkpoint, fingerprint)) = &checkpoint {
+                checkpoint::write(&checkpoint.path, fingerprint, index_paths_sorted.len(), &entries)
+                    .map_err(Error::Checkpoint)?;
+            }
             if should_interrupt.load(Ordering::Relaxed) {
                 return Err(Error::Interrupted);
             }
-            entries
-        };
+        }
+        if !base_layers.is_empty() {
+            entries.retain(|e| base_layers.iter().all(|layer| layer.lookup(e.id).is_none()));
+        }
+
+        if check_delta_bases {
+            let start = Instant::now();
+            let mut progress = progress.add_child("Check delta bases");
+            progress.init(Some(entries.len()), gix_features::progress::count("entries"));
+            let packs = index_paths_sorted
+                .iter()
+                .map(|path| crate::data::File::at(path.with_extension("pack"), object_hash))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut num_unresolved = 0;
+            let mut first_oid = None;
+            for entry in &entries {
+                if let crate::data::entry::Header::RefDelta { base_id } =
+                    packs[entry.pack_index as usize].entry(entry.pack_offset).header
+                {
+                    let is_resolvable = entries.binary_search_by(|e| e.id.cmp(&base_id)).is_ok()
+                        || base_layers.iter().any(|layer| layer.lookup(base_id).is_some());
+                    if !is_resolvable {
+                        num_unresolved += 1;
+                        first_oid.get_or_insert(entry.id);
+                    }
+                }
+                progress.inc();
+            }
+            progress.show_throughput(start);
+            if let Some(first_oid) = first_oid {
+                return Err(Error::UnresolvedDeltas {
+                    count: num_unresolved,
+                    first_oid,
+                });
+            }
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+        }
 
         let mut cf = gix_chunk::file::Index::for_writing();
         cf.plan_chunk(
@@ -160,6 +427,14 @@ impl multi_index::File {
             multi_index::chunk::offsets::storage_size(entries.len()),
         );
 
+        let base_checksums: Vec<_> = base_layers.iter().map(multi_index::File::checksum).collect();
+        if !base_checksums.is_empty() {
+            cf.plan_chunk(
+                multi_index::chunk::base::ID,
+                multi_index::chunk::base::storage_size(base_checksums.len(), object_hash),
+            );
+        }
+
         let num_large_offsets = multi_index::chunk::large_offsets::num_large_offsets(&entries);
         if let Some(num_large_offsets) = num_large_offsets {
             cf.plan_chunk(
@@ -182,6 +457,7 @@ impl multi_index::File {
         let bytes_written = Self::write_header(
             &mut out,
             cf.num_chunks().try_into().expect("BUG: wrote more than 256 chunks"),
+            base_checksums.len() as u8,
             index_paths_sorted.len() as u32,
             object_hash,
         )?;
@@ -206,6 +482,7 @@ impl multi_index::File {
                         num_large_offsets.expect("available if planned"),
                         &mut chunk_write,
                     )?,
+                    multi_index::chunk::base::ID => multi_index::chunk::base::write(&base_checksums, &mut chunk_write)?,
                     unknown => unreachable!("BUG: forgot to implement chunk {:?}", std::str::from_utf8(&unknown)),
                 }
                 progress.inc();
@@ -220,15 +497,97 @@ impl multi_index::File {
         out.inner.inner.write_all(multi_index_checksum.as_slice())?;
         out.progress.show_throughput(write_start);
 
+        if let Some((checkpoint, _)) = checkpoint {
+            match std::fs::remove_file(&checkpoint.path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(Error::Checkpoint(err)),
+                _ => {}
+            }
+        }
+
+        let pseudo_pack_order = reverse_index.then(|| Self::pseudo_pack_order(&entries, preferred_pack));
+        let bitmap = match bitmap {
+            Some(Bitmap { tips, options }) => {
+                let bundles = index_paths_sorted
+                    .iter()
+                    .map(|path| {
+                        Ok::<_, Error>(crate::Bundle {
+                            index: crate::index::File::at(path, object_hash)?,
+                            pack: crate::data::File::at(path.with_extension("pack"), object_hash)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let objects = BitmapObjects {
+                    entries: &entries,
+                    bundles,
+                    pack_order: pseudo_pack_order
+                        .as_ref()
+                        .expect("checked that reverse index is requested"),
+                    checksum: multi_index_checksum,
+                    object_hash,
+                };
+                let mut out = Vec::new();
+                crate::bitmap::write::write_to(
+                    &objects,
+                    tips,
+                    &mut out,
+                    progress.add_child("Writing bitmap"),
+                    should_interrupt,
+                    options,
+                )?;
+                Some(out)
+            }
+            None => None,
+        };
+
         Ok(Outcome {
+            reverse_index: pseudo_pack_order
+                .map(|order| Self::reverse_index(order, &multi_index_checksum, object_hash)),
+            bitmap,
             multi_index_checksum,
             progress,
         })
     }
 
+    /// Return the positions of all lexicographically sorted `entries` in pseudo-pack order, i.e. sorted by pack and pack offset,
+    /// with the objects of the `preferred_pack` coming first.
+    fn pseudo_pack_order(entries: &[Entry], preferred_pack: Option<u32>) -> Vec<u32> {
+        let mut positions: Vec<u32> = (0..entries.len() as u32).collect();
+        positions.sort_by_key(|&pos| {
+            let entry = &entries[pos as usize];
+            (
+                Some(entry.pack_index) != preferred_pack,
+                entry.pack_index,
+                entry.pack_offset,
+            )
+        });
+        positions
+    }
+
+    /// Produce a reverse index in git's `.rev` format from the `positions` of entries in pseudo-pack order.
+    fn reverse_index(
+        positions: Vec<u32>,
+        multi_index_checksum: &gix_hash::oid,
+        object_hash: gix_hash::Kind,
+    ) -> Vec<u8> {
+        let mut out =
+            Vec::with_capacity(Self::REVERSE_INDEX_HEADER_LEN + positions.len() * 4 + object_hash.len_in_bytes() * 2);
+        out.extend_from_slice(Self::REVERSE_INDEX_SIGNATURE);
+        out.extend_from_slice(&1_u32.to_be_bytes()); /* version */
+        out.extend_from_slice(&(object_hash as u32).to_be_bytes());
+        for pos in positions {
+            out.extend_from_slice(&pos.to_be_bytes());
+        }
+        out.extend_from_slice(multi_index_checksum.as_bytes());
+        let mut hasher = gix_features::hash::hasher(object_hash);
+        hasher.update(&out);
+        out.extend_from_slice(&hasher.digest());
+        out
+    }
+
     fn write_header(
         mut out: impl std::io::Write,
         num_chunks: u8,
+        num_base_files: u8,
         num_indices: u32,
         object_hash: gix_hash::Kind,
     ) -> std::io::Result<usize> {
@@ -236,9 +595,53 @@ impl multi_index::File {
         out.write_all(&[crate::multi_index::Version::V1 as u8])?;
         out.write_all(&[object_hash as u8])?;
         out.write_all(&[num_chunks])?;
-        out.write_all(&[0])?; /* unused number of base files */
+        out.write_all(&[num_base_files])?;
         out.write_all(&num_indices.to_be_bytes())?;
 
         Ok(Self::HEADER_LEN)
     }
 }
+
+/// The objects of a multi-index that was just written, for use when writing its bitmap.
+struct BitmapObjects<'a> {
+    entries: &'a [Entry],
+    bundles: Vec<crate::Bundle>,
+    pack_order: &'a [u32],
+    checksum: gix_hash::ObjectId,
+    object_hash: gix_hash::Kind,
+}
+
+impl crate::bitmap::write::Objects for BitmapObjects<'_> {
+    fn checksum(&self) -> gix_hash::ObjectId {
+        self.checksum
+    }
+
+    fn object_hash(&self) -> gix_hash::Kind {
+        self.object_hash
+    }
+
+    fn num_objects(&self) -> u32 {
+        self.entries.len() as u32
+    }
+
+    fn lookup(&self, id: &gix_hash::oid) -> Option<crate::index::EntryIndex> {
+        let id = id.to_owned();
+        self.entries
+            .binary_search_by(|e| e.id.cmp(&id))
+            .ok()
+            .map(|index| index as u32)
+    }
+
+    fn oid_at_index(&self, index: crate::index::EntryIndex) -> &gix_hash::oid {
+        &self.entries[index as usize].id
+    }
+
+    fn pack_order(&self) -> Vec<crate::index::EntryIndex> {
+        self.pack_order.to_vec()
+    }
+
+    fn pack_and_offset_at_index(&self, index: crate::index::EntryIndex) -> (&crate::Bundle, crate::data::Offset) {
+        let entry = &self.entries[index as usize];
+        (&self.bundles[entry.pack_index as usize], entry.pack_offset)
+    }
+}
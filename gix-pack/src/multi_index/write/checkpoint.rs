@@ -0,0 +1,139 @@
+use std::{
+    convert::TryInto,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use gix_tempfile::{AutoRemove, ContainingDirectory};
+
+use super::Entry;
+
+const SIGNATURE: &[u8] = b"MIDXCKPT";
+const VERSION: u32 = 1;
+/// pack index, pack offset
+const RECORD_LEN_WITHOUT_ID: usize = 4 + 8;
+
+/// Compute an id for the set of input indices from their file names, sizes and modification times, without opening them,
+/// along with `deterministic` and `preferred_pack` as they affect which duplicate entries are kept.
+pub(crate) fn fingerprint(
+    index_paths: &[PathBuf],
+    deterministic: bool,
+    preferred_pack: Option<u32>,
+    object_hash: gix_hash::Kind,
+) -> std::io::Result<gix_hash::ObjectId> {
+    let mut hasher = gix_features::hash::hasher(object_hash);
+    hasher.update(&[deterministic as u8]);
+    hasher.update(&[preferred_pack.is_some() as u8]);
+    hasher.update(&preferred_pack.unwrap_or_default().to_be_bytes());
+    for path in index_paths {
+        let meta = fs::metadata(path)?;
+        let mtime = meta
+            .modified()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        hasher.update(
+            path.file_name()
+                .expect("file name present")
+                .to_string_lossy()
+                .as_bytes(),
+        );
+        hasher.update(&[0]);
+        hasher.update(&meta.len().to_be_bytes());
+        hasher.update(&mtime.as_secs().to_be_bytes());
+        hasher.update(&mtime.subsec_nanos().to_be_bytes());
+    }
+    Ok(hasher.digest().into())
+}
+
+/// Read the sorted and deduplicated entries stored in the checkpoint at `path` along with the amount of indices they were
+/// collected from if it was created for the input identified by `fingerprint` and is complete, or return `None` otherwise.
+///
+/// Note that the modification time of the indices isn't stored, and has to be restored by the caller.
+pub(crate) fn read(path: &Path, fingerprint: &gix_hash::oid) -> std::io::Result<Option<(Vec<Entry>, usize)>> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    Ok(parse(&data, fingerprint))
+}
+
+/// Durably store the sorted and deduplicated `entries` collected from the first `num_indices` indices at `path`,
+/// replacing any previous checkpoint.
+///
+/// The checkpoint is written to a temporary file first so that the previous one remains intact if this fails.
+pub(crate) fn write(
+    path: &Path,
+    fingerprint: &gix_hash::oid,
+    num_indices: usize,
+    entries: &[Entry],
+) -> std::io::Result<()> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    let file = gix_tempfile::new(
+        dir.unwrap_or_else(|| Path::new(".")),
+        ContainingDirectory::Exists,
+        AutoRemove::Tempfile,
+    )?;
+    let mut out = gix_features::hash::Write::new(std::io::BufWriter::new(file), fingerprint.kind());
+    out.write_all(SIGNATURE)?;
+    out.write_all(&VERSION.to_be_bytes())?;
+    out.write_all(fingerprint.as_bytes())?;
+    out.write_all(&(num_indices as u32).to_be_bytes())?;
+    out.write_all(&(entries.len() as u64).to_be_bytes())?;
+    for entry in entries {
+        out.write_all(entry.id.as_bytes())?;
+        out.write_all(&entry.pack_index.to_be_bytes())?;
+        out.write_all(&entry.pack_offset.to_be_bytes())?;
+    }
+    let checksum: gix_hash::ObjectId = out.hash.digest().into();
+    let mut out = out.inner;
+    out.write_all(checksum.as_bytes())?;
+    let mut file = out.into_inner().map_err(|err| err.into_error())?;
+    file.with_mut(|file| file.as_file().sync_all())??;
+    file.persist(path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+fn parse(data: &[u8], fingerprint: &gix_hash::oid) -> Option<(Vec<Entry>, usize)> {
+    let hash_len = fingerprint.kind().len_in_bytes();
+    let (content, checksum) = data.split_at(data.len().checked_sub(hash_len)?);
+    let mut hasher = gix_features::hash::hasher(fingerprint.kind());
+    hasher.update(content);
+    if hasher.digest().as_slice() != checksum {
+        return None;
+    }
+
+    let content = content.strip_prefix(SIGNATURE)?;
+    let (version, content) = content.split_at(4.min(content.len()));
+    let content = content.strip_prefix(fingerprint.as_bytes())?;
+    if version != VERSION.to_be_bytes() || content.len() < 4 + 8 {
+        return None;
+    }
+    let (num_indices, content) = content.split_at(4);
+    let num_indices = u32::from_be_bytes(num_indices.try_into().expect("4 bytes")) as usize;
+    let (num_entries, records) = content.split_at(8);
+    let num_entries = u64::from_be_bytes(num_entries.try_into().expect("8 bytes")) as usize;
+    let record_len = hash_len + RECORD_LEN_WITHOUT_ID;
+    if num_entries.checked_mul(record_len)? != records.len() {
+        return None;
+    }
+
+    let entries = records
+        .chunks_exact(record_len)
+        .map(|record| {
+            let (id, record) = record.split_at(hash_len);
+            Entry {
+                id: gix_hash::ObjectId::from(id),
+                pack_index: u32::from_be_bytes(record[..4].try_into().expect("4 bytes")),
+                pack_offset: u64::from_be_bytes(record[4..].try_into().expect("8 bytes")),
+                index_mtime: SystemTime::UNIX_EPOCH,
+            }
+        })
+        .collect::<Vec<_>>();
+    entries
+        .iter()
+        .all(|entry| (entry.pack_index as usize) < num_indices)
+        .then_some((entries, num_indices))
+}
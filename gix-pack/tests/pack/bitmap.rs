@@ -0,0 +1,152 @@
+use std::{process::Command, sync::atomic::AtomicBool};
+
+use gix_features::progress;
+
+#[test]
+fn write_bitmap_to_produces_bitmaps_git_can_verify() -> crate::Result {
+    let dir = gix_testtools::scripted_fixture_writable_standalone("make_pack_gen_repo.sh")?;
+    let git_dir = dir.path().join(".git");
+    let index_path = std::fs::read_dir(git_dir.join("objects/pack"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .find(|path| path.extension().and_then(|ext| ext.to_str()) == Some("idx"))
+        .expect("the repository is fully packed");
+    let bundle = gix_pack::Bundle::at(&index_path, gix_hash::Kind::Sha1)?;
+
+    let refs = Command::new("git")
+        .args(["for-each-ref", "--format=%(objectname)"])
+        .current_dir(dir.path())
+        .output()?;
+    assert!(refs.status.success());
+    let tips = refs
+        .stdout
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(gix_hash::ObjectId::from_hex)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(tips.len(), 2, "the main branch and an annotated tag");
+
+    let mut out = Vec::new();
+    let outcome = bundle.write_bitmap_to(
+        tips,
+        &mut out,
+        progress::Discard,
+        &AtomicBool::new(false),
+        gix_pack::bitmap::write::Options { hash_cache: true },
+    )?;
+    assert_eq!(
+        outcome.commits.len(),
+        16,
+        "with only a few commits, all of them receive a bitmap, including the one only reachable through the tag"
+    );
+    assert_eq!(&out[..4], b"BITM");
+    assert_eq!(&out[out.len() - 20..], outcome.checksum.as_slice());
+    std::fs::write(index_path.with_extension("bitmap"), &out)?;
+
+    let bitmap = gix_pack::bitmap::File::at(index_path.with_extension("bitmap"), gix_hash::Kind::Sha1)?;
+    assert_eq!(bitmap.num_bitmaps(), outcome.commits.len());
+    assert_eq!(
+        bitmap.hash_cache().map(<[u32]>::len),
+        Some(bundle.index.num_objects() as usize)
+    );
+
+    for rev in ["main", "referrer^{commit}"] {
+        let status = Command::new("git")
+            .args(["rev-list", "--test-bitmap", rev])
+            .current_dir(dir.path())
+            .output()?;
+        assert!(
+            status.status.success(),
+            "git validates the bitmap of {rev} against a traversal: {}",
+            String::from_utf8_lossy(&status.stderr)
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn name_hash_matches_git() {
+    assert_eq!(gix_pack::bitmap::name_hash(b""), 0);
+    assert_eq!(gix_pack::bitmap::name_hash(b"a"), u32::from(b'a') << 24);
+    assert_eq!(
+        gix_pack::bitmap::name_hash(b"a b"),
+        gix_pack::bitmap::name_hash(b"ab"),
+        "whitespace is ignored"
+    );
+    assert_eq!(gix_pack::bitmap::name_hash(b"ab"), 0x7a40_0000);
+}
+
+fn num_set_bits(words: &[u64]) -> usize {
+    words.iter().map(|word| word.count_ones() as usize).sum()
+}
+
+fn git(dir: &std::path::Path, args: &[&str]) -> crate::Result<Vec<u8>> {
+    let output = Command::new("git").args(args).current_dir(dir).output()?;
+    assert!(output.status.success(), "{:?} failed", args);
+    Ok(output.stdout)
+}
+
+#[test]
+fn bitmaps_written_by_git_can_be_read() -> crate::Result {
+    for lookup_table in ["false", "true"] {
+        let dir = gix_testtools::scripted_fixture_writable_standalone("make_pack_gen_repo.sh")?;
+        let lookup_table = format!("pack.writeBitmapLookupTable={lookup_table}");
+        git(dir.path(), &["-c", &lookup_table, "repack", "-adbq"])?;
+        let index_path = std::fs::read_dir(dir.path().join(".git/objects/pack"))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .find(|path| path.extension().and_then(|ext| ext.to_str()) == Some("idx"))
+            .expect("the repository is fully packed");
+        let bundle = gix_pack::Bundle::at(&index_path, gix_hash::Kind::Sha1)?;
+        let bitmap = gix_pack::bitmap::File::at(index_path.with_extension("bitmap"), gix_hash::Kind::Sha1)?;
+        assert_eq!(bitmap.pack_checksum(), bundle.pack.checksum().as_ref());
+        assert_eq!(
+            bitmap.hash_cache().map(<[u32]>::len),
+            Some(bundle.index.num_objects() as usize),
+            "git writes the hash cache by default"
+        );
+
+        let mut num_objects_of_all_kinds = 0;
+        for kind in [
+            gix_object::Kind::Commit,
+            gix_object::Kind::Tree,
+            gix_object::Kind::Blob,
+            gix_object::Kind::Tag,
+        ] {
+            bitmap.objects_of_kind(kind).for_each_set_bit(|_| {
+                num_objects_of_all_kinds += 1;
+                Some(())
+            });
+        }
+        assert_eq!(num_objects_of_all_kinds, bundle.index.num_objects());
+
+        let main = gix_hash::ObjectId::from_hex(&git(dir.path(), &["rev-parse", "main"])?[..40])?;
+        let reachable = bitmap
+            .reachable_from(bundle.index.lookup(main).expect("packed"))
+            .expect("tips always receive a bitmap");
+        let num_reachable = git(dir.path(), &["rev-list", "--objects", "main"])?
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .count();
+        assert_eq!(num_set_bits(&reachable), num_reachable);
+
+        let commits_with_bitmap = bitmap
+            .commits()
+            .map(|index| bundle.index.oid_at_index(index).to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(commits_with_bitmap.len(), bitmap.num_bitmaps());
+        for commit in commits_with_bitmap {
+            let reachable = bitmap
+                .reachable_from(bundle.index.lookup(commit).expect("packed"))
+                .expect("present");
+            let num_reachable = git(dir.path(), &["rev-list", "--objects", &commit.to_string()])?
+                .split(|b| *b == b'\n')
+                .filter(|line| !line.is_empty())
+                .count();
+            assert_eq!(
+                num_set_bits(&reachable),
+                num_reachable,
+                "bitmaps XORed with previous ones are resolved"
+            );
+        }
+    }
+    Ok(())
+}
@@ -332,6 +332,67 @@ fn empty_pack_is_allowed() {
     .unwrap();
 }
 
+#[test]
+fn deltas_can_refer_to_their_base_by_id() -> crate::Result {
+    let db = db(DbKind::DeterministicGeneratedContent)?;
+    let head = hex_to_id("dfcb5e39ac6eb30179808bbab721e8a28ce1b52e");
+    let (counts, _) = output::count::objects(
+        db.clone(),
+        std::iter::once(Ok::<_, Infallible>(head)),
+        progress::Discard,
+        &AtomicBool::new(false),
+        count::objects::Options {
+            input_object_expansion: count::objects::ObjectExpansion::TreeAdditionsComparedToAncestor,
+            ..Default::default()
+        },
+    )?;
+    let num_entries = counts.len() as u32;
+    let entries = InOrderIter::from(output::entry::iter_from_counts(
+        counts,
+        db,
+        progress::Discard,
+        Default::default(),
+    ));
+
+    let tmp_dir = tempfile::TempDir::new()?;
+    let pack_file_path = tmp_dir.path().join("new.pack");
+    let mut pack_file = std::fs::File::create(&pack_file_path)?;
+    let mut pack_writer = output::bytes::FromEntriesIter::new(
+        entries,
+        &mut pack_file,
+        num_entries,
+        pack::data::Version::V2,
+        gix_hash::Kind::Sha1,
+    )
+    .with_ofs_delta(false);
+    for written in pack_writer.by_ref() {
+        written?;
+    }
+
+    let headers = pack::data::input::BytesToEntriesIter::new_from_header(
+        std::io::BufReader::new(std::fs::File::open(pack_file_path)?),
+        pack::data::input::Mode::Verify,
+        pack::data::input::EntryDataMode::Ignore,
+        gix_hash::Kind::Sha1,
+    )?
+    .map(|entry| entry.map(|entry| entry.header))
+    .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(headers.len(), num_entries as usize);
+    assert!(
+        headers
+            .iter()
+            .any(|header| matches!(header, pack::data::entry::Header::RefDelta { .. })),
+        "deltas are written"
+    );
+    assert!(
+        !headers
+            .iter()
+            .any(|header| matches!(header, pack::data::entry::Header::OfsDelta { .. })),
+        "none of them refers to its base by offset"
+    );
+    Ok(())
+}
+
 fn write_and_verify(
     db: gix_odb::HandleArc,
     entries: Vec<output::Entry>,
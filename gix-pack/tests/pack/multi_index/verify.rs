@@ -50,3 +50,282 @@ fn integrity() {
         }]
     );
 }
+
+#[test]
+fn integrity_fast() -> crate::Result {
+    let (file, _) = multi_index();
+    let (checksum, _progress) = file.verify_integrity_fast(progress::Discard, &AtomicBool::new(false))?;
+    assert_eq!(checksum, file.checksum());
+    Ok(())
+}
+
+mod corrupted {
+    use std::{convert::TryInto, path::PathBuf, sync::atomic::AtomicBool};
+
+    use gix_features::progress;
+    use gix_pack::multi_index::verify::integrity;
+
+    use crate::pack::multi_index::multi_index;
+
+    fn multi_index_with(corrupt: impl FnOnce(&mut Vec<u8>)) -> crate::Result<(PathBuf, tempfile::TempDir)> {
+        let (_, path) = multi_index();
+        let mut data = std::fs::read(path)?;
+        corrupt(&mut data);
+        let dir = tempfile::TempDir::new()?;
+        let path = dir.path().join("multi-pack-index");
+        std::fs::write(&path, data)?;
+        Ok((path, dir))
+    }
+
+    fn update_checksum(data: &mut [u8]) {
+        let hash_len = gix_hash::Kind::Sha1.len_in_bytes();
+        let (content, checksum) = data.split_at_mut(data.len() - hash_len);
+        let mut hasher = gix_features::hash::hasher(gix_hash::Kind::Sha1);
+        hasher.update(content);
+        checksum.copy_from_slice(&hasher.digest());
+    }
+
+    #[test]
+    fn truncated_files_cannot_be_opened() -> crate::Result {
+        let (path, _dir) = multi_index_with(|data| data.truncate(data.len() / 2))?;
+        assert!(gix_pack::multi_index::File::at(path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn checksum_mismatch() -> crate::Result {
+        let (path, _dir) = multi_index_with(|data| *data.last_mut().expect("not empty") ^= 0xff)?;
+        let file = gix_pack::multi_index::File::at(path)?;
+        assert!(matches!(
+            file.verify_integrity_fast(progress::Discard, &AtomicBool::new(false)),
+            Err(integrity::Error::MultiIndexChecksum(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn dangling_pack_reference() -> crate::Result {
+        let (path, _dir) = multi_index_with(|data| {
+            const HEADER_LEN: usize = 12;
+            const CHUNK_TABLE_ENTRY_LEN: usize = 4 + 8;
+            let num_chunks = data[6] as usize;
+            let offsets_ofs = data[HEADER_LEN..][..num_chunks * CHUNK_TABLE_ENTRY_LEN]
+                .chunks(CHUNK_TABLE_ENTRY_LEN)
+                .find_map(|entry| {
+                    (&entry[..4] == b"OOFF")
+                        .then(|| u64::from_be_bytes(entry[4..].try_into().expect("8 bytes")) as usize)
+                })
+                .expect("object offsets chunk is present");
+            data[offsets_ofs..][..4].copy_from_slice(&42u32.to_be_bytes());
+            update_checksum(data);
+        })?;
+        let file = gix_pack::multi_index::File::at(path)?;
+        assert!(matches!(
+            file.verify_integrity_fast(progress::Discard, &AtomicBool::new(false)),
+            Err(integrity::Error::PackIdOutOfBounds {
+                index: 0,
+                pack_id: 42,
+                num_indices: 1
+            })
+        ));
+        Ok(())
+    }
+}
+
+mod object {
+    use std::sync::atomic::AtomicBool;
+
+    use gix_pack::multi_index::verify::object;
+
+    use crate::pack::multi_index::multi_index;
+
+    #[test]
+    fn all_objects_match_their_decoded_counterparts() -> crate::Result {
+        let (file, path) = multi_index();
+        let bundle = gix_pack::Bundle::at(
+            path.parent().expect("in pack dir").join(&file.index_names()[0]),
+            file.object_hash(),
+        )?;
+        let mut bundles = object::Bundles::default();
+        let mut cache = gix_pack::cache::Never;
+        let mut buf = Vec::new();
+        let should_interrupt = AtomicBool::new(false);
+        for entry_index in 0..file.num_objects() {
+            let id = file.oid_at_index(entry_index).to_owned();
+            let outcome = file.verify_object(&id, &mut bundles, &mut cache, &should_interrupt)?;
+            let (object, location) = bundle
+                .find(&id, &mut buf, &mut gix_pack::cache::Never)?
+                .expect("object is present");
+            assert_eq!(outcome.kind, object.kind);
+            assert_eq!(outcome.size, object.data.len() as u64);
+            assert_eq!(outcome.location.pack_offset, location.pack_offset);
+            assert_eq!(outcome.location.entry_size, location.entry_size);
+        }
+
+        let missing = gix_hash::Kind::Sha1.null();
+        assert!(matches!(
+            file.verify_object(missing, &mut bundles, &mut cache, &should_interrupt),
+            Err(object::Error::NotFound { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn interrupt() {
+        let (file, _) = multi_index();
+        let id = file.oid_at_index(0).to_owned();
+        assert!(matches!(
+            file.verify_object(
+                id,
+                &mut object::Bundles::default(),
+                &mut gix_pack::cache::Never,
+                &AtomicBool::new(true)
+            ),
+            Err(object::Error::Interrupted)
+        ));
+    }
+
+    #[test]
+    fn hash_mismatch() -> crate::Result {
+        let (file, path) = multi_index();
+        let dir = tempfile::TempDir::new()?;
+        let index_name = &file.index_names()[0];
+        let pack_dir = path.parent().expect("in pack dir");
+        std::fs::copy(
+            pack_dir.join(index_name).with_extension("pack"),
+            dir.path().join(index_name).with_extension("pack"),
+        )?;
+
+        // Claim that an object has a slightly different id without changing the sort order of the index.
+        let hash_len = file.object_hash().len_in_bytes();
+        let (entry_index, tampered_id) = (0..file.num_objects() - 1)
+            .find_map(|entry_index| {
+                let mut id = file.oid_at_index(entry_index).to_owned();
+                let last_byte = id.as_mut_slice().last_mut().expect("non-empty");
+                *last_byte = last_byte.checked_add(1)?;
+                (id.as_ref() < file.oid_at_index(entry_index + 1)).then_some((entry_index, id))
+            })
+            .expect("there is an id to tamper with");
+        let original_id = file.oid_at_index(entry_index).to_owned();
+        let mut index = std::fs::read(pack_dir.join(index_name))?;
+        let oid_table_ofs = 8 /* header */ + 256 * 4 /* fan */;
+        let pos = index[oid_table_ofs..]
+            .chunks(hash_len)
+            .position(|id| id == original_id.as_slice())
+            .expect("id is in the index");
+        index[oid_table_ofs + pos * hash_len..][..hash_len].copy_from_slice(tampered_id.as_slice());
+        let index_path = dir.path().join(index_name);
+        std::fs::write(&index_path, index)?;
+
+        let multi_index_path = dir.path().join("multi-pack-index");
+        gix_pack::multi_index::File::write_from_index_paths(
+            vec![index_path],
+            &mut std::fs::File::create(&multi_index_path)?,
+            gix_features::progress::Discard,
+            &AtomicBool::new(false),
+            gix_pack::multi_index::write::Options {
+                object_hash: file.object_hash(),
+                reverse_index: false,
+                checkpoint: None,
+                deterministic: false,
+                max_packs: None,
+                check_delta_bases: false,
+                base_layers: Vec::new(),
+                preferred_pack: None,
+                bitmap: None,
+            },
+        )?;
+        let file = gix_pack::multi_index::File::at(multi_index_path)?;
+        match file.verify_object(
+            tampered_id,
+            &mut object::Bundles::default(),
+            &mut gix_pack::cache::Never,
+            &AtomicBool::new(false),
+        ) {
+            Err(object::Error::ObjectMismatch { expected, actual, .. }) => {
+                assert_eq!(expected, tampered_id);
+                assert_eq!(actual, original_id);
+            }
+            res => panic!("expected a hash mismatch, got {:?}", res),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_delta() -> crate::Result {
+        fn compress(data: &[u8]) -> std::io::Result<Vec<u8>> {
+            use std::io::Write;
+            let mut out = gix_features::zlib::stream::deflate::Write::new(Vec::new());
+            out.write_all(data)?;
+            out.flush()?;
+            Ok(out.into_inner())
+        }
+        let (base_id, delta_id) = ([0x11; 20], [0x22; 20]);
+        for (delta, what) in [
+            ([5, 3, 0x90, 3], "the base is claimed to have 5 bytes, but has 3"),
+            ([3, 5, 0x90, 5], "5 bytes are copied from a base with 3 bytes"),
+        ] {
+            let dir = tempfile::TempDir::new()?;
+            let mut pack = b"PACK".to_vec();
+            pack.extend_from_slice(&2u32.to_be_bytes());
+            pack.extend_from_slice(&2u32.to_be_bytes());
+            let base_offset = pack.len();
+            pack.push(0x33 /* blob of size 3 */);
+            pack.extend_from_slice(&compress(b"abc")?);
+            let delta_offset = pack.len();
+            pack.extend_from_slice(&[0x64 /* ofs-delta of size 4 */, (delta_offset - base_offset) as u8]);
+            pack.extend_from_slice(&compress(&delta)?);
+            pack.extend_from_slice(&[0; 20]);
+            std::fs::write(dir.path().join("pack-corrupt.pack"), pack)?;
+
+            let mut index = b"\xfftOc".to_vec();
+            index.extend_from_slice(&2u32.to_be_bytes());
+            for byte in 0..=255u8 {
+                let count = [base_id, delta_id].iter().filter(|id| id[0] <= byte).count() as u32;
+                index.extend_from_slice(&count.to_be_bytes());
+            }
+            index.extend_from_slice(&base_id);
+            index.extend_from_slice(&delta_id);
+            index.extend_from_slice(&[0; 4 * 2]);
+            index.extend_from_slice(&(base_offset as u32).to_be_bytes());
+            index.extend_from_slice(&(delta_offset as u32).to_be_bytes());
+            index.extend_from_slice(&[0; 20 * 2]);
+            let index_path = dir.path().join("pack-corrupt.idx");
+            std::fs::write(&index_path, index)?;
+
+            let multi_index_path = dir.path().join("multi-pack-index");
+            gix_pack::multi_index::File::write_from_index_paths(
+                vec![index_path],
+                &mut std::fs::File::create(&multi_index_path)?,
+                gix_features::progress::Discard,
+                &AtomicBool::new(false),
+                gix_pack::multi_index::write::Options {
+                    object_hash: gix_hash::Kind::Sha1,
+                    reverse_index: false,
+                    checkpoint: None,
+                    deterministic: false,
+                    max_packs: None,
+                    check_delta_bases: false,
+                    base_layers: Vec::new(),
+                    preferred_pack: None,
+                    bitmap: None,
+                },
+            )?;
+            let file = gix_pack::multi_index::File::at(multi_index_path)?;
+            let res = file.verify_object(
+                gix_hash::ObjectId::from(delta_id),
+                &mut object::Bundles::default(),
+                &mut gix_pack::cache::Never,
+                &AtomicBool::new(false),
+            );
+            assert!(
+                matches!(
+                    res,
+                    Err(object::Error::Decode(gix_pack::data::decode::Error::CorruptDelta))
+                ),
+                "{what}: corrupt deltas are reported instead of causing a panic, got {res:?}"
+            );
+        }
+        Ok(())
+    }
+}
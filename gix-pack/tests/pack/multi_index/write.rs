@@ -1,4 +1,4 @@
-use std::{path::PathBuf, sync::atomic::AtomicBool};
+use std::{convert::TryInto, path::PathBuf, sync::atomic::AtomicBool};
 
 use gix_features::progress;
 use gix_testtools::fixture_path_standalone;
@@ -28,6 +28,14 @@ fn from_paths() -> crate::Result {
         &AtomicBool::new(false),
         gix_pack::multi_index::write::Options {
             object_hash: gix_hash::Kind::Sha1,
+            reverse_index: false,
+            checkpoint: None,
+            deterministic: false,
+            max_packs: None,
+            check_delta_bases: false,
+            base_layers: Vec::new(),
+            preferred_pack: None,
+            bitmap: None,
         },
     )?;
 
@@ -66,3 +74,493 @@ fn from_paths() -> crate::Result {
     assert_eq!(outcome.0, file.checksum());
     Ok(())
 }
+
+#[test]
+fn from_paths_with_reverse_index() -> crate::Result {
+    let dir = tempfile::TempDir::new()?;
+    let input_indices = std::fs::read_dir(fixture_path_standalone("objects/pack"))?
+        .filter_map(|r| {
+            r.ok()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|e| e.to_str()).unwrap_or("") == "idx")
+        })
+        .collect::<Vec<_>>();
+    let output_path = dir.path().join("multi-pack-index");
+    let mut out = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&output_path)?;
+    let outcome = gix_pack::multi_index::File::write_from_index_paths(
+        input_indices,
+        &mut out,
+        progress::Discard,
+        &AtomicBool::new(false),
+        gix_pack::multi_index::write::Options {
+            object_hash: gix_hash::Kind::Sha1,
+            reverse_index: true,
+            checkpoint: None,
+            deterministic: false,
+            max_packs: None,
+            check_delta_bases: false,
+            base_layers: Vec::new(),
+            preferred_pack: None,
+            bitmap: None,
+        },
+    )?;
+    assert_eq!(
+        gix_pack::multi_index::write::reverse_index_file_name(&outcome.multi_index_checksum),
+        PathBuf::from("multi-pack-index-d34d327039a3554f8a644b29e07b903fa71ef269.rev"),
+        "the checksum is the same as without reverse index"
+    );
+
+    let file = gix_pack::multi_index::File::at(output_path)?;
+    let rev = outcome.reverse_index.expect("requested");
+    let hash_len = gix_hash::Kind::Sha1.len_in_bytes();
+    assert_eq!(rev.len(), 12 + file.num_objects() as usize * 4 + hash_len * 2);
+    assert_eq!(&rev[..4], b"RIDX");
+    assert_eq!(&rev[4..12], &[0, 0, 0, 1, 0, 0, 0, 1], "version 1 and SHA1");
+
+    let positions: Vec<u32> = rev[12..rev.len() - hash_len * 2]
+        .chunks(4)
+        .map(|b| u32::from_be_bytes(b.try_into().expect("4 bytes")))
+        .collect();
+    let mut sorted = positions.clone();
+    sorted.sort_unstable();
+    assert_eq!(
+        sorted,
+        (0..file.num_objects()).collect::<Vec<_>>(),
+        "each entry exactly once"
+    );
+
+    let pack_order: Vec<_> = positions
+        .iter()
+        .map(|&pos| file.pack_id_and_pack_offset_at_index(pos))
+        .collect();
+    assert!(
+        pack_order.windows(2).all(|w| w[0] < w[1]),
+        "positions are ordered by pack and offset"
+    );
+    assert_eq!(
+        &rev[rev.len() - hash_len * 2..rev.len() - hash_len],
+        outcome.multi_index_checksum.as_bytes()
+    );
+    Ok(())
+}
+
+#[test]
+fn from_paths_resumed_from_checkpoint() -> crate::Result {
+    struct FailingWrite;
+    impl std::io::Write for FailingWrite {
+        fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "simulated crash"))
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let dir = tempfile::TempDir::new()?;
+    let mut input_indices = std::fs::read_dir(fixture_path_standalone("objects/pack"))?
+        .filter_map(|r| {
+            r.ok()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|e| e.to_str()).unwrap_or("") == "idx")
+        })
+        .collect::<Vec<_>>();
+    input_indices.sort();
+    let checkpoint = dir.path().join("multi-pack-index.checkpoint");
+    let write_with = |indices: Vec<PathBuf>, interval: usize, should_interrupt: bool, out: &mut dyn std::io::Write| {
+        gix_pack::multi_index::File::write_from_index_paths(
+            indices,
+            out,
+            progress::Discard,
+            &AtomicBool::new(should_interrupt),
+            gix_pack::multi_index::write::Options {
+                object_hash: gix_hash::Kind::Sha1,
+                reverse_index: false,
+                checkpoint: Some(gix_pack::multi_index::write::Checkpoint {
+                    path: checkpoint.clone(),
+                    interval,
+                }),
+                deterministic: false,
+                max_packs: None,
+                check_delta_bases: false,
+                base_layers: Vec::new(),
+                preferred_pack: None,
+                bitmap: None,
+            },
+        )
+    };
+    let write = |indices: Vec<PathBuf>, out: &mut dyn std::io::Write| write_with(indices, usize::MAX, false, out);
+
+    assert!(
+        matches!(
+            write(input_indices[..2].to_vec(), &mut FailingWrite),
+            Err(gix_pack::multi_index::write::Error::Io(_))
+        ),
+        "the checkpoint is written before the multi-index"
+    );
+    let checkpoint_for_different_input = std::fs::read(&checkpoint)?;
+    assert!(write(input_indices.clone(), &mut FailingWrite).is_err());
+    assert_ne!(
+        std::fs::read(&checkpoint)?,
+        checkpoint_for_different_input,
+        "a checkpoint for another set of indices is replaced"
+    );
+
+    let expected_checksum = hex_to_id("d34d327039a3554f8a644b29e07b903fa71ef269");
+    let outcome = write(input_indices.clone(), &mut Vec::<u8>::new())?;
+    assert_eq!(
+        outcome.multi_index_checksum, expected_checksum,
+        "the result is the same as if written in one go"
+    );
+    assert!(!checkpoint.exists(), "the checkpoint is removed after success");
+
+    assert!(write(input_indices.clone(), &mut FailingWrite).is_err());
+    let data = std::fs::read(&checkpoint)?;
+    std::fs::write(&checkpoint, &data[..data.len() - 1])?;
+    let outcome = write(input_indices.clone(), &mut Vec::<u8>::new())?;
+    assert_eq!(
+        outcome.multi_index_checksum, expected_checksum,
+        "incomplete checkpoints are ignored"
+    );
+
+    assert!(matches!(
+        write_with(input_indices.clone(), usize::MAX, true, &mut Vec::<u8>::new()),
+        Err(gix_pack::multi_index::write::Error::Interrupted)
+    ));
+    assert!(
+        !checkpoint.exists(),
+        "nothing is stored while collecting entries until the interval is reached"
+    );
+    assert!(matches!(
+        write_with(input_indices.clone(), 0, true, &mut Vec::<u8>::new()),
+        Err(gix_pack::multi_index::write::Error::Interrupted)
+    ));
+    let size_after_first_index = std::fs::metadata(&checkpoint)?.len();
+    assert!(matches!(
+        write_with(input_indices.clone(), 0, true, &mut Vec::<u8>::new()),
+        Err(gix_pack::multi_index::write::Error::Interrupted)
+    ));
+    assert!(
+        std::fs::metadata(&checkpoint)?.len() > size_after_first_index,
+        "after an interruption, collection resumes with the next index and stores its entries as well"
+    );
+    let outcome = write_with(input_indices, 0, false, &mut Vec::<u8>::new())?;
+    assert_eq!(
+        outcome.multi_index_checksum, expected_checksum,
+        "the result is the same as if written in one go"
+    );
+    assert!(!checkpoint.exists());
+    Ok(())
+}
+
+#[test]
+fn from_paths_deterministic() -> crate::Result {
+    let index = fixture_path_standalone("objects/pack/pack-11fdfa9e156ab73caae3b6da867192221f2089c2.idx");
+    let write_with_duplicates = |copy_names: [&str; 2]| -> crate::Result<Vec<u8>> {
+        let dir = tempfile::TempDir::new()?;
+        let mut input_indices = Vec::new();
+        for name in copy_names {
+            let path = dir.path().join(name);
+            std::fs::copy(&index, &path)?;
+            input_indices.push(path);
+        }
+        let mut out = Vec::new();
+        gix_pack::multi_index::File::write_from_index_paths(
+            input_indices,
+            &mut out,
+            progress::Discard,
+            &AtomicBool::new(false),
+            gix_pack::multi_index::write::Options {
+                object_hash: gix_hash::Kind::Sha1,
+                reverse_index: false,
+                checkpoint: None,
+                deterministic: true,
+                max_packs: None,
+                check_delta_bases: false,
+                base_layers: Vec::new(),
+                preferred_pack: None,
+                bitmap: None,
+            },
+        )?;
+        Ok(out)
+    };
+
+    let a = write_with_duplicates(["pack-a.idx", "pack-b.idx"])?;
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    let b = write_with_duplicates(["pack-b.idx", "pack-a.idx"])?;
+    assert_eq!(
+        a, b,
+        "neither the order of inputs nor which index is more recent affect the output"
+    );
+    Ok(())
+}
+
+#[test]
+fn from_paths_with_too_many_packs() {
+    let input_indices = std::fs::read_dir(fixture_path_standalone("objects/pack"))
+        .unwrap()
+        .filter_map(|r| {
+            r.ok()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|e| e.to_str()).unwrap_or("") == "idx")
+        })
+        .collect::<Vec<_>>();
+    let count = input_indices.len();
+    let err = gix_pack::multi_index::File::write_from_index_paths(
+        input_indices,
+        std::io::sink(),
+        progress::Discard,
+        &AtomicBool::new(false),
+        gix_pack::multi_index::write::Options {
+            object_hash: gix_hash::Kind::Sha1,
+            reverse_index: false,
+            checkpoint: None,
+            deterministic: false,
+            max_packs: Some(count - 1),
+            check_delta_bases: false,
+            base_layers: Vec::new(),
+            preferred_pack: None,
+            bitmap: None,
+        },
+    )
+    .err()
+    .expect("too many packs");
+    assert!(
+        matches!(err, gix_pack::multi_index::write::Error::TooManyPacks { count: c, limit } if c == count && limit == count - 1),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn from_paths_with_unresolved_ref_deltas() -> crate::Result {
+    /// Write a pack with a single object with the given entry `header` and its index, returning the index path.
+    fn write_pack_and_index(
+        dir: &std::path::Path,
+        name: &str,
+        id: [u8; 20],
+        header: &[u8],
+    ) -> std::io::Result<PathBuf> {
+        let mut pack = b"PACK".to_vec();
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        pack.extend_from_slice(&1u32.to_be_bytes());
+        pack.extend_from_slice(header);
+        pack.extend_from_slice(&[0; 5 + 20]);
+        std::fs::write(dir.join(format!("{name}.pack")), pack)?;
+
+        let mut index = b"\xfftOc".to_vec();
+        index.extend_from_slice(&2u32.to_be_bytes());
+        for byte in 0..=255u8 {
+            index.extend_from_slice(&u32::from(byte >= id[0]).to_be_bytes());
+        }
+        index.extend_from_slice(&id);
+        index.extend_from_slice(&[0; 4]);
+        index.extend_from_slice(&12u32.to_be_bytes());
+        index.extend_from_slice(&[0; 20 * 2]);
+        let index_path = dir.join(format!("{name}.idx"));
+        std::fs::write(&index_path, index)?;
+        Ok(index_path)
+    }
+    let write_to = |input_indices: Vec<PathBuf>, base_layers: Vec<PathBuf>, out: &mut dyn std::io::Write| {
+        gix_pack::multi_index::File::write_from_index_paths(
+            input_indices,
+            out,
+            progress::Discard,
+            &AtomicBool::new(false),
+            gix_pack::multi_index::write::Options {
+                object_hash: gix_hash::Kind::Sha1,
+                reverse_index: false,
+                checkpoint: None,
+                deterministic: false,
+                max_packs: None,
+                check_delta_bases: true,
+                base_layers,
+                preferred_pack: None,
+                bitmap: None,
+            },
+        )
+    };
+    let write = |input_indices: Vec<PathBuf>| write_to(input_indices, Vec::new(), &mut std::io::sink());
+
+    let dir = tempfile::TempDir::new()?;
+    let (delta_id, base_id) = ([0x11; 20], [0x22; 20]);
+    let mut ref_delta_header = vec![0x75];
+    ref_delta_header.extend_from_slice(&base_id);
+    let thin = write_pack_and_index(dir.path(), "pack-thin", delta_id, &ref_delta_header)?;
+
+    let err = write(vec![thin.clone()]).err().expect("the base is missing");
+    assert!(
+        matches!(err, gix_pack::multi_index::write::Error::UnresolvedDeltas { count: 1, first_oid } if first_oid.as_bytes() == delta_id),
+        "{:?}",
+        err
+    );
+
+    let base = write_pack_and_index(dir.path(), "pack-base", base_id, &[0x35])?;
+    write(vec![thin.clone(), base.clone()])?;
+
+    let base_layer = dir.path().join("base.midx");
+    write_to(vec![base], Vec::new(), &mut std::fs::File::create(&base_layer)?)?;
+    write_to(vec![thin], vec![base_layer], &mut std::io::sink())
+        .map_err(|err| format!("bases in lower layers resolve deltas of the new layer: {err}"))?;
+    Ok(())
+}
+
+#[test]
+fn from_paths_as_incremental_layer() -> crate::Result {
+    let dir = tempfile::TempDir::new()?;
+    let mut input_indices = std::fs::read_dir(fixture_path_standalone("objects/pack"))?
+        .filter_map(|r| {
+            r.ok()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|e| e.to_str()).unwrap_or("") == "idx")
+        })
+        .collect::<Vec<_>>();
+    input_indices.sort();
+    let write = |indices: Vec<PathBuf>, base_layers: Vec<PathBuf>, reverse_index: bool, path: &std::path::Path| {
+        gix_pack::multi_index::File::write_from_index_paths(
+            indices,
+            std::fs::File::create(path)?,
+            progress::Discard,
+            &AtomicBool::new(false),
+            gix_pack::multi_index::write::Options {
+                object_hash: gix_hash::Kind::Sha1,
+                reverse_index,
+                checkpoint: None,
+                deterministic: false,
+                max_packs: None,
+                check_delta_bases: false,
+                base_layers,
+                preferred_pack: None,
+                bitmap: None,
+            },
+        )
+    };
+
+    let base_path = dir.path().join("base.midx");
+    let base_outcome = write(input_indices[..2].to_vec(), Vec::new(), false, &base_path)?;
+    let layer_path = dir.path().join("layer.midx");
+    let outcome = write(input_indices.clone(), vec![base_path.clone()], false, &layer_path)?;
+
+    let base = gix_pack::multi_index::File::at(&base_path)?;
+    assert_eq!(base.num_base_files(), 0);
+    assert_eq!(base.base_checksums().count(), 0);
+    let layer = gix_pack::multi_index::File::at(&layer_path)?;
+    assert_eq!(layer.checksum(), outcome.multi_index_checksum);
+    assert_eq!(layer.num_base_files(), 1);
+    assert_eq!(
+        layer.base_checksums().map(ToOwned::to_owned).collect::<Vec<_>>(),
+        vec![base_outcome.multi_index_checksum]
+    );
+    assert_eq!(
+        layer.index_names(),
+        vec![PathBuf::from(input_indices[2].file_name().expect("present"))],
+        "packs of the base layer are skipped"
+    );
+    let last_index = gix_pack::index::File::at(&input_indices[2], gix_hash::Kind::Sha1)?;
+    let num_new_objects = last_index.iter().filter(|e| base.lookup(e.oid).is_none()).count();
+    assert_eq!(layer.num_objects() as usize, num_new_objects);
+    assert!(
+        layer.iter().all(|e| base.lookup(e.oid).is_none()),
+        "objects of the base layer are skipped"
+    );
+
+    let err = write(input_indices, vec![base_path], true, &dir.path().join("unused.midx"))
+        .err()
+        .expect("unsupported");
+    assert!(
+        matches!(err, gix_pack::multi_index::write::Error::UnsupportedForLayers),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn from_paths_with_bitmap_git_can_verify() -> crate::Result {
+    fn git(dir: &std::path::Path, args: &[&str]) -> crate::Result<Vec<u8>> {
+        let output = std::process::Command::new("git").args(args).current_dir(dir).output()?;
+        assert!(
+            output.status.success(),
+            "{:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(output.stdout)
+    }
+    let dir = gix_testtools::scripted_fixture_writable_standalone("make_pack_gen_repo_multi_index.sh")?;
+    std::fs::write(dir.path().join("new-file"), "new content")?;
+    git(dir.path(), &["add", "new-file"])?;
+    git(
+        dir.path(),
+        &[
+            "-c",
+            "user.name=a",
+            "-c",
+            "user.email=a@example.com",
+            "commit",
+            "-qm",
+            "add a file to a second pack",
+        ],
+    )?;
+    git(dir.path(), &["repack", "-dq", "--no-write-bitmap-index"])?;
+
+    let pack_dir = dir.path().join(".git/objects/pack");
+    std::fs::remove_file(pack_dir.join("multi-pack-index"))?;
+    let input_indices = std::fs::read_dir(&pack_dir)?
+        .filter_map(|r| {
+            r.ok()
+                .map(|e| e.path())
+                .filter(|p| p.extension().and_then(|e| e.to_str()).unwrap_or("") == "idx")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(input_indices.len(), 2, "the old pack and the one with the new commit");
+    let tips = git(dir.path(), &["for-each-ref", "--format=%(objectname)"])?
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(gix_hash::ObjectId::from_hex)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut out = Vec::new();
+    let outcome = gix_pack::multi_index::File::write_from_index_paths(
+        input_indices,
+        &mut out,
+        progress::Discard,
+        &AtomicBool::new(false),
+        gix_pack::multi_index::write::Options {
+            object_hash: gix_hash::Kind::Sha1,
+            reverse_index: true,
+            checkpoint: None,
+            deterministic: false,
+            max_packs: None,
+            check_delta_bases: false,
+            base_layers: Vec::new(),
+            preferred_pack: None,
+            bitmap: Some(gix_pack::multi_index::write::Bitmap {
+                tips,
+                options: gix_pack::bitmap::write::Options { hash_cache: true },
+            }),
+        },
+    )?;
+    std::fs::write(pack_dir.join("multi-pack-index"), out)?;
+    let checksum = &outcome.multi_index_checksum;
+    std::fs::write(
+        pack_dir.join(gix_pack::multi_index::write::reverse_index_file_name(checksum)),
+        outcome.reverse_index.expect("requested"),
+    )?;
+    let bitmap = outcome.bitmap.expect("requested");
+    assert_eq!(&bitmap[..4], b"BITM");
+    let bitmap_path = pack_dir.join(gix_pack::multi_index::write::bitmap_file_name(checksum));
+    std::fs::write(&bitmap_path, bitmap)?;
+    assert_eq!(
+        gix_pack::bitmap::File::at(&bitmap_path, gix_hash::Kind::Sha1)?.pack_checksum(),
+        checksum.as_ref(),
+        "multi-index bitmaps refer to the multi-index instead of a pack"
+    );
+
+    git(dir.path(), &["multi-pack-index", "verify"])?;
+    for rev in ["main", "referrer^{commit}"] {
+        git(dir.path(), &["rev-list", "--test-bitmap", rev])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use bstr::{BStr, BString, ByteSlice};
+
+/// Determines how many of the bundles in a [`List`] are needed to obtain all objects it describes.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Mode {
+    /// All bundles have to be downloaded and unbundled.
+    All,
+    /// Any of the bundles is sufficient, they contain the same objects.
+    Any,
+}
+
+/// A single bundle as advertised by the server.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct Bundle {
+    /// The name of the bundle in the list, only meaningful to identify it.
+    pub id: BString,
+    /// The location to download the bundle from, which may be relative to the url of the remote.
+    pub uri: BString,
+    /// A number which increases with each bundle that is created, so that bundles with lower numbers should be applied first.
+    ///
+    /// It's only set if the `creationToken` heuristic is used.
+    pub creation_token: Option<u64>,
+}
+
+/// The list of bundles as received in response to the `bundle-uri` command.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct List {
+    /// The version of the list format, which is `1` for all lists we understand.
+    pub version: u32,
+    /// How many of the `bundles` are needed.
+    pub mode: Mode,
+    /// The heuristic to use for ordering the `bundles`, like `creationToken`, if set.
+    pub heuristic: Option<BString>,
+    /// All bundles in the order in which they were advertised.
+    pub bundles: Vec<Bundle>,
+}
+
+///
+pub mod decode {
+    use bstr::BString;
+
+    /// The error returned by [List::from_key_value_lines()][super::List::from_key_value_lines()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Bundle list line {line:?} isn't of the form 'bundle.<key>=<value>'")]
+        MalformedLine { line: BString },
+        #[error("Bundle list version {version:?} is not supported")]
+        UnsupportedVersion { version: BString },
+        #[error("Bundle list mode {mode:?} is not supported")]
+        UnsupportedMode { mode: BString },
+        #[error("The creation token {token:?} of bundle {id:?} isn't a number")]
+        InvalidCreationToken { id: BString, token: BString },
+        #[error("Bundle {id:?} doesn't have a uri")]
+        MissingUri { id: BString },
+    }
+}
+
+impl List {
+    /// Parse a bundle list from `lines` of the form `bundle.<key>=<value>`, as sent by the server in response to the
+    /// `bundle-uri` command, with keys being compared case-insensitively. Unknown keys are ignored.
+    pub fn from_key_value_lines<'a>(lines: impl IntoIterator<Item = &'a BStr>) -> Result<Self, decode::Error> {
+        let mut version = None;
+        let mut mode = None;
+        let mut heuristic = None;
+        let mut bundles = Vec::<Bundle>::new();
+        for line in lines {
+            let line = line.trim_end_with(|c| c == '\n');
+            let malformed = || decode::Error::MalformedLine { line: line.into() };
+            let (key, value) = line.split_once_str(b"=").ok_or_else(malformed)?;
+            const PREFIX: &[u8] = b"bundle.";
+            let key = (key.len() > PREFIX.len() && key[..PREFIX.len()].eq_ignore_ascii_case(PREFIX))
+                .then(|| &key[PREFIX.len()..])
+                .ok_or_else(malformed)?;
+            match key.rsplit_once_str(b".") {
+                None => {
+                    if key.eq_ignore_ascii_case(b"version") {
+                        if value != b"1" {
+                            return Err(decode::Error::UnsupportedVersion { version: value.into() });
+                        }
+                        version = Some(1);
+                    } else if key.eq_ignore_ascii_case(b"mode") {
+                        mode = Some(match value {
+                            b"all" => Mode::All,
+                            b"any" => Mode::Any,
+                            _ => return Err(decode::Error::UnsupportedMode { mode: value.into() }),
+                        });
+                    } else if key.eq_ignore_ascii_case(b"heuristic") {
+                        heuristic = Some(value.into());
+                    }
+                }
+                Some((id, key)) => {
+                    let bundle = match bundles.iter().position(|b| b.id == id) {
+                        Some(pos) => &mut bundles[pos],
+                        None => {
+                            bundles.push(Bundle {
+                                id: id.into(),
+                                uri: BString::default(),
+                                creation_token: None,
+                            });
+                            bundles.last_mut().expect("just pushed")
+                        }
+                    };
+                    if key.eq_ignore_ascii_case(b"uri") {
+                        bundle.uri = value.into();
+                    } else if key.eq_ignore_ascii_case(b"creationToken") {
+                        bundle.creation_token = Some(
+                            value
+                                .to_str()
+                                .ok()
+                                .and_then(|token| token.parse().ok())
+                                .ok_or_else(|| decode::Error::InvalidCreationToken {
+                                    id: id.into(),
+                                    token: value.into(),
+                                })?,
+                        );
+                    }
+                }
+            }
+        }
+        if let Some(bundle) = bundles.iter().find(|b| b.uri.is_empty()) {
+            return Err(decode::Error::MissingUri { id: bundle.id.clone() });
+        }
+        Ok(List {
+            version: version.unwrap_or(1),
+            mode: mode.unwrap_or(Mode::All),
+            heuristic,
+            bundles,
+        })
+    }
+}
+
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+mod error {
+    /// The error returned by [bundle_uri()][crate::bundle_uri()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Transport(#[from] gix_transport::client::Error),
+        #[error(transparent)]
+        LineDecode(#[from] gix_transport::packetline::decode::Error),
+        #[error(transparent)]
+        Decode(#[from] super::decode::Error),
+    }
+
+    impl gix_transport::IsSpuriousError for Error {
+        fn is_spurious(&self) -> bool {
+            match self {
+                Error::Io(err) => err.is_spurious(),
+                Error::Transport(err) => err.is_spurious(),
+                _ => false,
+            }
+        }
+    }
+}
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub use error::Error;
+
+#[cfg(any(feature = "blocking-client", feature = "async-client"))]
+pub(crate) mod function {
+    use bstr::BString;
+    use gix_features::progress::Progress;
+    use gix_transport::client::{Capabilities, Transport, TransportV2Ext};
+    use maybe_async::maybe_async;
+
+    use super::{Error, List};
+    use crate::Command;
+
+    /// Invoke the `bundle-uri` V2 command on `transport`, which requires a prior handshake that yielded
+    /// server `capabilities`, and return the list of bundles the server advertises, which may be empty.
+    /// `progress` is used to provide feedback.
+    ///
+    /// Callers should check that the server advertises the `bundle-uri` capability beforehand.
+    #[maybe_async]
+    pub async fn bundle_uri(
+        mut transport: impl Transport,
+        capabilities: &Capabilities,
+        progress: &mut impl Progress,
+    ) -> Result<List, Error> {
+        let bundle_uri = Command::BundleUri;
+        let features = bundle_uri.default_features(gix_transport::Protocol::V2, capabilities);
+        bundle_uri.validate_argument_prefixes_or_panic(gix_transport::Protocol::V2, capabilities, &[], &features);
+
+        progress.step();
+        progress.set_name("list bundles");
+        let mut lines = Vec::<BString>::new();
+        let mut reader = transport
+            .invoke(
+                bundle_uri.as_str(),
+                features.into_iter(),
+                None::<std::iter::Empty<BString>>,
+            )
+            .await?;
+        while let Some(line) = reader
+            .readline()
+            .await
+            .transpose()?
+            .transpose()?
+            .and_then(|l| l.as_bstr())
+        {
+            lines.push(line.to_owned());
+        }
+        Ok(List::from_key_value_lines(lines.iter().map(AsRef::as_ref))?)
+    }
+}
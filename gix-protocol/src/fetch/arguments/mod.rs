@@ -31,7 +31,12 @@ impl Arguments {
     /// This can happen if callers assure that they won't add 'wants' if their 'have' is the same, i.e. if the remote has nothing
     /// new for them.
     pub fn is_empty(&self) -> bool {
-        self.haves.is_empty() && !self.args.iter().rev().any(|arg| arg.starts_with_str("want "))
+        self.haves.is_empty()
+            && !self
+                .args
+                .iter()
+                .rev()
+                .any(|arg| arg.starts_with_str("want ") || arg.starts_with_str("want-ref "))
     }
     /// Return true if ref filters is supported.
     pub fn can_use_filter(&self) -> bool {
@@ -370,8 +370,11 @@ mod arguments {
             let mut out = Vec::new();
             let mut t = transport(&mut out, false);
             let mut arguments = arguments_v2(["ref-in-want"].iter().copied());
+            assert!(arguments.can_use_ref_in_want());
+            assert!(arguments.is_empty());
 
             arguments.want_ref(b"refs/heads/main".as_bstr());
+            assert!(!arguments.is_empty(), "wanted refs count as wants");
             arguments.send(&mut t, true).await.expect("sending to buffer to work");
             assert_eq!(
                 out.as_bstr(),
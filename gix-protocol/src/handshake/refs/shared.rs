@@ -121,6 +121,11 @@ pub(in crate::handshake::refs) fn parse_v1(
     if path.is_empty() {
         return Err(Error::MalformedV1RefLine(trimmed.to_owned().into()));
     }
+    if path == b"capabilities^{}" {
+        // A placeholder sent by servers without any refs to have a line to attach the capabilities to,
+        // like `receive-pack` in empty repositories.
+        return Ok(());
+    }
     match path.strip_suffix(b"^{}") {
         Some(stripped) => {
             let (previous_path, tag) =
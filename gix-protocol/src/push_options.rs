@@ -0,0 +1,28 @@
+use bstr::BString;
+
+use crate::transport::client::Capabilities;
+
+/// The error returned by [`validate()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The remote doesn't advertise the 'push-options' capability and can't receive push options")]
+    Unsupported,
+    #[error("The push option {option:?} must not contain newlines")]
+    Newline { option: BString },
+}
+
+/// Assure that all push `options`, strings for use by the hooks of the remote like those of `git push --push-option`, can
+/// be sent to a remote which advertised `capabilities` when connecting to its `receive-pack` service.
+///
+/// This is the case if there are no options, or if the remote advertises the `push-options` capability and none of the
+/// options contains a newline, as each of them is sent as a line of its own.
+pub fn validate(options: &[BString], capabilities: &Capabilities) -> Result<(), Error> {
+    if let Some(option) = options.iter().find(|option| option.contains(&b'\n')) {
+        return Err(Error::Newline { option: option.clone() });
+    }
+    if !options.is_empty() && !capabilities.contains("push-options") {
+        return Err(Error::Unsupported);
+    }
+    Ok(())
+}
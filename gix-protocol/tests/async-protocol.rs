@@ -5,5 +5,7 @@ pub fn fixture_bytes(path: &str) -> Vec<u8> {
         .expect("fixture to be present and readable")
 }
 
+mod bundle_uri;
 mod fetch;
+mod push_options;
 mod remote_progress;
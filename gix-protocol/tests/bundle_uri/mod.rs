@@ -0,0 +1,75 @@
+mod list {
+    use bstr::ByteSlice;
+    use gix_protocol::bundle_uri::{Bundle, List, Mode};
+
+    fn parse(input: &str) -> Result<List, gix_protocol::bundle_uri::decode::Error> {
+        List::from_key_value_lines(input.lines().map(|l| l.as_bytes().as_bstr()))
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(
+            parse("").expect("valid"),
+            List {
+                version: 1,
+                mode: Mode::All,
+                heuristic: None,
+                bundles: Vec::new()
+            }
+        );
+    }
+
+    #[test]
+    fn bundles_with_creation_tokens() {
+        let list = parse(
+            "bundle.version=1\n\
+             bundle.mode=any\n\
+             bundle.heuristic=creationToken\n\
+             bundle.base.uri=https://cdn.example.com/base.bundle\n\
+             bundle.base.creationToken=1\n\
+             bundle.2023.01.uri=incremental.bundle\n\
+             bundle.2023.01.CREATIONTOKEN=2\n",
+        )
+        .expect("valid");
+        assert_eq!(
+            list,
+            List {
+                version: 1,
+                mode: Mode::Any,
+                heuristic: Some("creationToken".into()),
+                bundles: vec![
+                    Bundle {
+                        id: "base".into(),
+                        uri: "https://cdn.example.com/base.bundle".into(),
+                        creation_token: Some(1),
+                    },
+                    Bundle {
+                        id: "2023.01".into(),
+                        uri: "incremental.bundle".into(),
+                        creation_token: Some(2),
+                    }
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_ignored() {
+        let list = parse("bundle.version=1\nbundle.future=1\nbundle.a.uri=a\nbundle.a.location=eu\n").expect("valid");
+        assert_eq!(list.bundles.len(), 1);
+    }
+
+    #[test]
+    fn invalid_input() {
+        for input in [
+            "bundle.version=2",
+            "bundle.mode=some",
+            "no-prefix=1",
+            "bundle.a.creationToken=1\nbundle.a.uri=a\nbundle.b.creationToken=2",
+            "bundle.a.uri=a\nbundle.a.creationToken=x",
+            "bundle.version",
+        ] {
+            assert!(parse(input).is_err(), "{input:?} should fail");
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use gix_protocol::{push_options, transport::client::Capabilities};
+
+fn capabilities(input: &str) -> Capabilities {
+    Capabilities::from_bytes(format!("\0{input}").as_bytes())
+        .expect("valid capabilities")
+        .0
+}
+
+#[test]
+fn no_options_are_always_valid() {
+    assert!(push_options::validate(&[], &capabilities("report-status")).is_ok());
+}
+
+#[test]
+fn options_need_the_push_options_capability() {
+    let options = ["merge_request.create".into()];
+    assert!(push_options::validate(&options, &capabilities("report-status push-options")).is_ok());
+    assert!(matches!(
+        push_options::validate(&options, &capabilities("report-status")),
+        Err(push_options::Error::Unsupported)
+    ));
+}
+
+#[test]
+fn options_must_not_contain_newlines() {
+    let options = ["valid".into(), "two\nlines".into()];
+    assert!(matches!(
+        push_options::validate(&options, &capabilities("push-options")),
+        Err(push_options::Error::Newline { option }) if option == "two\nlines"
+    ));
+}
@@ -9,7 +9,8 @@ pub(crate) mod function {
     ///
     /// This includes connections to
     /// [local repositories][crate::client::file::connect()],
-    /// [repositories over ssh][crate::client::ssh::connect()],
+    /// [repositories over ssh][crate::client::ssh::connect()], or without an ssh program using `ssh::native::connect()`
+    /// if compiled in and neither an ssh command nor a program kind is set in `options`,
     /// [git daemons][crate::client::git::connect()],
     /// and if compiled in connections to [git repositories over https][crate::client::http::connect()].
     ///
@@ -34,6 +35,11 @@ pub(crate) mod function {
                         .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
                 )
             }
+            #[cfg(feature = "ssh-client-libssh2")]
+            gix_url::Scheme::Ssh if options.ssh.command.is_none() && options.ssh.kind.is_none() => Box::new({
+                crate::client::blocking_io::ssh::native::connect(url, options.version, Default::default())
+                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?
+            }),
             gix_url::Scheme::Ssh => Box::new({
                 crate::client::blocking_io::ssh::connect(url, options.version, options.ssh)
                     .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?
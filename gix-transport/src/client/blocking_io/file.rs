@@ -112,6 +112,10 @@ impl client::TransportWithoutIO for SpawnProcessOnDemand {
         Cow::Owned(self.url.to_bstring())
     }
 
+    fn actual_protocol_version(&self) -> Option<Protocol> {
+        self.connection.as_ref().and_then(|c| c.actual_protocol_version())
+    }
+
     fn connection_persists_across_multiple_requests(&self) -> bool {
         true
     }
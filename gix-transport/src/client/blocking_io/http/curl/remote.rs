@@ -156,8 +156,13 @@ pub fn new() -> (
                     proxy_authenticate,
                     verbose,
                     ssl_ca_info,
+                    ssl_cert,
+                    ssl_key,
+                    ssl_verify,
                     ssl_version,
                     http_version,
+                    cookie_file,
+                    save_cookies,
                     backend,
                 },
         } in req_recv
@@ -176,6 +181,21 @@ pub fn new() -> (
             if let Some(ca_info) = ssl_ca_info {
                 handle.cainfo(ca_info)?;
             }
+            if let Some(cert) = ssl_cert {
+                handle.ssl_cert(cert)?;
+            }
+            if let Some(key) = ssl_key {
+                handle.ssl_key(key)?;
+            }
+            handle.ssl_verify_peer(ssl_verify)?;
+            handle.ssl_verify_host(ssl_verify)?;
+
+            if let Some(cookie_file) = cookie_file {
+                handle.cookie_file(&cookie_file)?;
+                if save_cookies {
+                    handle.cookie_jar(&cookie_file)?;
+                }
+            }
 
             if let Some(ref mut curl_options) = backend.as_ref().and_then(|backend| backend.lock().ok()) {
                 if let Some(opts) = curl_options.downcast_mut::<super::Options>() {
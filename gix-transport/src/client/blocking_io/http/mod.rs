@@ -130,7 +130,7 @@ pub mod options {
 
 /// Options to configure http requests.
 // TODO: testing most of these fields requires a lot of effort, unless special flags to introspect ongoing requests are added.
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct Options {
     /// Headers to be added to every request.
     /// They are applied unconditionally and are expected to be valid as they occur in an HTTP request, like `header: value`, without newlines.
@@ -160,6 +160,7 @@ pub struct Options {
     pub no_proxy: Option<String>,
     /// The way to authenticate against the proxy if the `proxy` field contains a username.
     ///
+    /// Note that the `reqwest` backend always uses basic authentication.
     /// Refers to `http.proxyAuthMethod`.
     pub proxy_auth_method: options::ProxyAuthMethod,
     /// If authentication is needed for the proxy as its URL contains a username, this method must be set to provide a password
@@ -187,14 +188,62 @@ pub struct Options {
     pub verbose: bool,
     /// If set, use this path to point to a file with CA certificates to verify peers.
     pub ssl_ca_info: Option<PathBuf>,
+    /// If set, the file with the client certificate to present to the server, typically in PEM format.
+    ///
+    /// Refers to `http.sslCert`.
+    pub ssl_cert: Option<PathBuf>,
+    /// If set, the file with the private key belonging to `ssl_cert`, typically in PEM format.
+    ///
+    /// Refers to `http.sslKey`.
+    pub ssl_key: Option<PathBuf>,
+    /// If `false`, neither the certificate of the server nor whether it belongs to the host is verified, which makes
+    /// connections insecure. It's `true` by default.
+    ///
+    /// Refers to `http.sslVerify`.
+    pub ssl_verify: bool,
     /// The SSL version or version range to use, or `None` to let the TLS backend determine which versions are acceptable.
     pub ssl_version: Option<SslVersionRangeInclusive>,
     /// The HTTP version to enforce. If unset, it is implementation defined.
     pub http_version: Option<HttpVersion>,
+    /// If set, the file to read cookies from, in the Netscape cookie file format, to send them along with each request.
+    ///
+    /// Refers to `http.cookieFile`.
+    pub cookie_file: Option<PathBuf>,
+    /// If `true` and `cookie_file` is set, cookies received from the server are written back to `cookie_file`.
+    ///
+    /// Refers to `http.saveCookies`.
+    pub save_cookies: bool,
     /// Backend specific options, if available.
     pub backend: Option<Arc<Mutex<dyn Any + Send + Sync + 'static>>>,
 }
 
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            extra_headers: Vec::new(),
+            follow_redirects: Default::default(),
+            low_speed_limit_bytes_per_second: 0,
+            low_speed_time_seconds: 0,
+            proxy: None,
+            no_proxy: None,
+            proxy_auth_method: Default::default(),
+            proxy_authenticate: None,
+            user_agent: None,
+            connect_timeout: None,
+            verbose: false,
+            ssl_ca_info: None,
+            ssl_cert: None,
+            ssl_key: None,
+            ssl_verify: true,
+            ssl_version: None,
+            http_version: None,
+            cookie_file: None,
+            save_cookies: false,
+            backend: None,
+        }
+    }
+}
+
 /// The actual http client implementation, using curl
 #[cfg(feature = "http-client-curl")]
 pub type Impl = curl::Curl;
@@ -273,6 +322,47 @@ impl<H: Http> Transport<H> {
         Ok(())
     }
 
+    /// Request the advertisement for `service` while asking for protocol `version` along with `extra_parameters`,
+    /// and return its body once the response was validated.
+    fn get_advertisement(
+        &mut self,
+        service: Service,
+        version: Protocol,
+        extra_parameters: &[(&str, Option<&str>)],
+    ) -> Result<H::ResponseBody, client::Error> {
+        let url = append_url(self.url.as_ref(), &format!("info/refs?service={}", service.as_str()));
+        let static_headers = [Cow::Borrowed(self.user_agent_header)];
+        let mut dynamic_headers = Vec::<Cow<'_, str>>::new();
+        if version != Protocol::V1 || !extra_parameters.is_empty() {
+            let mut parameters = if version != Protocol::V1 {
+                let mut p = format!("version={}", version as usize);
+                if !extra_parameters.is_empty() {
+                    p.push(':');
+                }
+                p
+            } else {
+                String::new()
+            };
+            parameters.push_str(
+                &extra_parameters
+                    .iter()
+                    .map(|(key, value)| match value {
+                        Some(value) => format!("{key}={value}"),
+                        None => key.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(":"),
+            );
+            dynamic_headers.push(format!("Git-Protocol: {parameters}").into());
+        }
+        self.add_basic_auth_if_present(&mut dynamic_headers)?;
+        let GetResponse { headers, body } =
+            self.http
+                .get(url.as_ref(), &self.url, static_headers.iter().chain(&dynamic_headers))?;
+        <Transport<H>>::check_content_type(service, "advertisement", headers)?;
+        Ok(body)
+    }
+
     #[allow(clippy::unnecessary_wraps, unknown_lints)]
     fn add_basic_auth_if_present(&self, headers: &mut Vec<Cow<'_, str>>) -> Result<(), client::Error> {
         if let Some(gix_sec::identity::Account { username, password }) = &self.identity {
@@ -358,6 +448,10 @@ impl<H: Http> client::TransportWithoutIO for Transport<H> {
         Cow::Borrowed(self.url.as_str().into())
     }
 
+    fn actual_protocol_version(&self) -> Option<Protocol> {
+        self.service.map(|_| self.actual_version)
+    }
+
     fn connection_persists_across_multiple_requests(&self) -> bool {
         false
     }
@@ -373,36 +467,16 @@ impl<H: Http> client::Transport for Transport<H> {
         service: Service,
         extra_parameters: &'a [(&'a str, Option<&'a str>)],
     ) -> Result<client::SetServiceResponse<'_>, client::Error> {
-        let url = append_url(self.url.as_ref(), &format!("info/refs?service={}", service.as_str()));
-        let static_headers = [Cow::Borrowed(self.user_agent_header)];
-        let mut dynamic_headers = Vec::<Cow<'_, str>>::new();
-        if self.desired_version != Protocol::V1 || !extra_parameters.is_empty() {
-            let mut parameters = if self.desired_version != Protocol::V1 {
-                let mut p = format!("version={}", self.desired_version as usize);
-                if !extra_parameters.is_empty() {
-                    p.push(':');
-                }
-                p
-            } else {
-                String::new()
-            };
-            parameters.push_str(
-                &extra_parameters
-                    .iter()
-                    .map(|(key, value)| match value {
-                        Some(value) => format!("{key}={value}"),
-                        None => key.to_string(),
-                    })
-                    .collect::<Vec<_>>()
-                    .join(":"),
-            );
-            dynamic_headers.push(format!("Git-Protocol: {parameters}").into());
-        }
-        self.add_basic_auth_if_present(&mut dynamic_headers)?;
-        let GetResponse { headers, body } =
-            self.http
-                .get(url.as_ref(), &self.url, static_headers.iter().chain(&dynamic_headers))?;
-        <Transport<H>>::check_content_type(service, "advertisement", headers)?;
+        let body = match self.get_advertisement(service, self.desired_version, extra_parameters) {
+            Err(client::Error::Io(err))
+                if self.desired_version != Protocol::V1 && err.kind() == std::io::ErrorKind::Other =>
+            {
+                // Some servers, or proxies in front of them, reject requests with a `Git-Protocol` header.
+                // Retry like a client that only knows protocol V0 would, so the server can respond with what it supports.
+                self.get_advertisement(service, Protocol::V1, &[])?
+            }
+            res => res?,
+        };
 
         let line_reader = self
             .line_provider
@@ -22,6 +22,8 @@ pub enum Error {
     ReadPostBody(#[from] std::io::Error),
     #[error("Request configuration failed")]
     ConfigureRequest(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error("Could not obtain the credentials to authenticate with the proxy")]
+    ProxyAuthenticate(#[from] gix_credentials::protocol::Error),
 }
 
 impl crate::IsSpuriousError for Error {
@@ -42,10 +44,7 @@ impl Default for Remote {
         let handle = std::thread::spawn(move || -> Result<(), Error> {
             // We may error while configuring, which is expected as part of the internal protocol. The error will be
             // received and the sender of the request might restart us.
-            let client = reqwest::blocking::ClientBuilder::new()
-                .connect_timeout(std::time::Duration::from_secs(20))
-                .http1_title_case_headers()
-                .build()?;
+            let mut cached_client: Option<(ClientConfig, reqwest::blocking::Client)> = None;
             for Request {
                 url,
                 headers,
@@ -53,6 +52,29 @@ impl Default for Remote {
                 config,
             } in req_recv
             {
+                let mut proxy_auth_action = None;
+                let client_config = ClientConfig {
+                    proxy_credentials: match (config.proxy.as_deref(), &config.proxy_authenticate) {
+                        (Some(proxy), Some((obtain_creds_action, authenticate))) if !proxy.is_empty() => {
+                            let creds =
+                                authenticate.lock().expect("no panics in other threads")(obtain_creds_action.clone())?
+                                    .expect("action to fetch credentials");
+                            proxy_auth_action = Some((creds.next, authenticate.clone()));
+                            Some((creds.identity.username, creds.identity.password))
+                        }
+                        _ => None,
+                    },
+                    proxy: config.proxy.clone(),
+                    no_proxy: config.no_proxy.clone(),
+                };
+                if cached_client
+                    .as_ref()
+                    .map_or(true, |(existing_config, _)| *existing_config != client_config)
+                {
+                    let client = client_config.build()?;
+                    cached_client = Some((client_config, client));
+                }
+                let (_, client) = cached_client.as_ref().expect("client was built");
                 let mut req_builder = if upload_body_kind.is_some() {
                     client.post(url)
                 } else {
@@ -91,7 +113,18 @@ impl Default for Remote {
                         }
                     }
                 }
-                let mut res = match client.execute(req).and_then(|res| res.error_for_status()) {
+                let res = client.execute(req);
+                if let Some((action, authenticate)) = proxy_auth_action {
+                    let is_authenticated = res.as_ref().map_or(false, |res| {
+                        res.status() != reqwest::StatusCode::PROXY_AUTHENTICATION_REQUIRED
+                    });
+                    authenticate.lock().expect("no panics in other threads")(if is_authenticated {
+                        action.store()
+                    } else {
+                        action.erase()
+                    })?;
+                }
+                let mut res = match res.and_then(|res| res.error_for_status()) {
                     Ok(res) => res,
                     Err(err) => {
                         let (kind, err) = match err.status() {
@@ -244,6 +277,66 @@ impl http::Http for Remote {
     }
 }
 
+/// The configuration used when building the client, which is rebuilt whenever it changes.
+#[derive(PartialEq, Eq)]
+struct ClientConfig {
+    /// See [`http::Options::proxy`].
+    proxy: Option<String>,
+    /// See [`http::Options::no_proxy`].
+    no_proxy: Option<String>,
+    /// The username and password to authenticate with the proxy, as obtained with [`http::Options::proxy_authenticate`].
+    proxy_credentials: Option<(String, String)>,
+}
+
+impl ClientConfig {
+    fn build(&self) -> Result<reqwest::blocking::Client, reqwest::Error> {
+        let mut builder = reqwest::blocking::ClientBuilder::new()
+            .connect_timeout(std::time::Duration::from_secs(20))
+            .http1_title_case_headers();
+        match self.proxy.as_deref() {
+            // An empty proxy disables proxying entirely, even if configured in the environment.
+            Some("") => builder = builder.no_proxy(),
+            Some(_) if self.no_proxy.as_deref().map(str::trim) == Some("*") => builder = builder.no_proxy(),
+            Some(proxy_url) => {
+                // Validate the url upfront as errors in custom proxy functions are ignored, which would silently bypass the proxy.
+                reqwest::Proxy::all(proxy_url)?;
+                let proxy_url = proxy_url.to_owned();
+                let no_proxy = self.no_proxy.clone();
+                let mut proxy = reqwest::Proxy::custom(move |url| {
+                    let bypass = no_proxy
+                        .as_deref()
+                        .zip(url.host_str())
+                        .map_or(false, |(no_proxy, host)| is_no_proxy_host(no_proxy, host));
+                    (!bypass).then(|| proxy_url.clone())
+                });
+                if let Some((username, password)) = &self.proxy_credentials {
+                    proxy = proxy.basic_auth(username, password);
+                }
+                builder = builder.proxy(proxy);
+            }
+            None => {}
+        }
+        builder.build()
+    }
+}
+
+/// Return `true` if `host` is matched by the comma-separated list of hosts in `no_proxy`, with each entry matching the host itself
+/// and all of its sub-domains, like curl does.
+fn is_no_proxy_host(no_proxy: &str, host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    no_proxy
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(|entry| entry.trim_start_matches('.'))
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            entry == "*"
+                || host.eq_ignore_ascii_case(entry)
+                || (host.len() > entry.len()
+                    && host.as_bytes()[host.len() - entry.len() - 1] == b'.'
+                    && host[host.len() - entry.len()..].eq_ignore_ascii_case(entry))
+        })
+}
+
 pub(crate) struct Request {
     pub url: String,
     pub headers: reqwest::header::HeaderMap,
@@ -31,6 +31,10 @@ pub enum ProgramKind {
 
 mod program_kind;
 
+///
+#[cfg(feature = "ssh-client-libssh2")]
+pub mod native;
+
 ///
 pub mod invocation {
     use std::ffi::OsString;
@@ -0,0 +1,384 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    net::{TcpStream, ToSocketAddrs},
+    path::PathBuf,
+    time::Duration,
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{
+    client::{self, git, MessageKind, RequestWriter, SetServiceResponse, WriteMode},
+    Protocol, Service,
+};
+
+/// Options for [`connect()`] which are also accepted by [`configure()`][client::TransportWithoutIO::configure()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The `known_hosts` files to verify the key of the host with.
+    ///
+    /// If empty, `~/.ssh/known_hosts` and `/etc/ssh/ssh_known_hosts` are used, like `ssh` does by default.
+    pub known_hosts_files: Vec<PathBuf>,
+    /// If `true`, connect to hosts whose key isn't known, instead of failing like `ssh` does with `StrictHostKeyChecking=yes`.
+    /// Hosts whose key is known but doesn't match, or whose key couldn't be checked, are always refused. It's `false` by default.
+    pub accept_unknown_hosts: bool,
+    /// The private keys to try if authentication through the ssh agent fails, in order.
+    ///
+    /// If empty, the default keys of `ssh` in `~/.ssh` are tried. Keys protected by a passphrase are skipped.
+    pub identity_files: Vec<PathBuf>,
+    /// The amount of time to wait until aborting a connection attempt, or `None` to wait as long as the system allows.
+    pub connect_timeout: Option<Duration>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            known_hosts_files: Vec::new(),
+            accept_unknown_hosts: false,
+            identity_files: Vec::new(),
+            connect_timeout: Some(Duration::from_secs(20)),
+        }
+    }
+}
+
+/// The error returned by [`connect()`] and used as source of the [`std::io::Error`] of failed handshakes.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The scheme in \"{}\" is not usable for an ssh connection", .0.to_bstring())]
+    UnsupportedScheme(gix_url::Url),
+    #[error("Could not connect to {host}:{port}")]
+    Connect {
+        host: String,
+        port: u16,
+        source: std::io::Error,
+    },
+    #[error("The ssh session with {host} could not be established")]
+    Session { host: String, source: ssh2::Error },
+    #[error("The host {host} didn't present a key")]
+    HostKeyMissing { host: String },
+    #[error("The key of host {host} isn't present in any of the known_hosts files")]
+    HostKeyUnknown { host: String },
+    #[error("The key of host {host} could not be checked against the known_hosts files")]
+    HostKeyCheck { host: String },
+    #[error("The key of host {host} doesn't match the one in the known_hosts files, which could indicate an attack")]
+    HostKeyMismatch { host: String },
+    #[error("Could not read the known_hosts file at '{}'", path.display())]
+    KnownHosts { path: PathBuf, source: ssh2::Error },
+    #[error("The user name to authenticate as is unknown, and could not be obtained from the environment")]
+    UserMissing,
+    #[error("Could not authenticate as {user} at {host} with the ssh agent, identity files or password")]
+    Authentication { user: String, host: String },
+    #[error("Could not run {command:?} on {host}")]
+    Exec {
+        command: String,
+        host: String,
+        source: ssh2::Error,
+    },
+}
+
+impl crate::IsSpuriousError for Error {
+    fn is_spurious(&self) -> bool {
+        match self {
+            Error::Connect { source, .. } => source.is_spurious(),
+            _ => false,
+        }
+    }
+}
+
+impl From<Error> for client::Error {
+    fn from(err: Error) -> Self {
+        let kind = match &err {
+            Error::Authentication { .. } | Error::UserMissing => std::io::ErrorKind::PermissionDenied,
+            Error::Connect { source, .. } => source.kind(),
+            _ => std::io::ErrorKind::Other,
+        };
+        client::Error::Io(std::io::Error::new(kind, err))
+    }
+}
+
+/// A transport talking to the `git` programs on a remote host through an ssh session established by `libssh2`,
+/// which doesn't require an `ssh` program to be installed.
+///
+/// It can only be instantiated using [`connect()`].
+pub struct Transport {
+    url: gix_url::Url,
+    path: BString,
+    desired_version: Protocol,
+    options: Options,
+    password: Option<String>,
+    connection: Option<git::Connection<ssh2::Stream, ssh2::Channel>>,
+}
+
+/// Connect to the host in `url` using `libssh2` to obtain data from the repository at its path, with the ssh session established
+/// during the handshake. Host keys are verified and authentication is performed according to `options`.
+///
+/// The `desired_version` is the preferred protocol version when establishing the connection, but note that it can be
+/// downgraded by servers not supporting it.
+#[allow(clippy::result_large_err)]
+pub fn connect(url: gix_url::Url, desired_version: Protocol, options: Options) -> Result<Transport, Error> {
+    if url.scheme != gix_url::Scheme::Ssh || url.host().is_none() {
+        return Err(Error::UnsupportedScheme(url));
+    }
+    Ok(Transport {
+        path: gix_url::expand_path::for_shell(url.path.clone()),
+        url,
+        desired_version,
+        options,
+        password: None,
+        connection: None,
+    })
+}
+
+impl Transport {
+    #[allow(clippy::result_large_err)]
+    fn establish(&self, service: Service) -> Result<git::Connection<ssh2::Stream, ssh2::Channel>, Error> {
+        let host = self.url.host().expect("checked when connecting").to_owned();
+        let port = self.url.port.unwrap_or(22);
+        let connect_err = |source| Error::Connect {
+            host: host.clone(),
+            port,
+            source,
+        };
+        let tcp = match self.options.connect_timeout {
+            Some(timeout) => {
+                let mut last_err = None;
+                let mut stream = None;
+                for addr in (host.as_str(), port).to_socket_addrs().map_err(connect_err)? {
+                    match TcpStream::connect_timeout(&addr, timeout) {
+                        Ok(s) => {
+                            stream = Some(s);
+                            break;
+                        }
+                        Err(err) => last_err = Some(err),
+                    }
+                }
+                stream.ok_or_else(|| {
+                    connect_err(last_err.unwrap_or_else(|| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            "the host name didn't resolve to any address",
+                        )
+                    }))
+                })?
+            }
+            None => TcpStream::connect((host.as_str(), port)).map_err(connect_err)?,
+        };
+
+        let session_err = |source| Error::Session {
+            host: host.clone(),
+            source,
+        };
+        let mut session = ssh2::Session::new().map_err(session_err)?;
+        session.set_tcp_stream(tcp);
+        session.handshake().map_err(session_err)?;
+        self.verify_host_key(&session, &host, port)?;
+        self.authenticate(&session, &host)?;
+
+        let command = format!(
+            "{} {}",
+            service.as_str(),
+            gix_quote::single(self.path.as_ref()).to_str_lossy()
+        );
+        let exec_err = |source| Error::Exec {
+            command: command.clone(),
+            host: host.clone(),
+            source,
+        };
+        let mut channel = session.channel_session().map_err(exec_err)?;
+        if self.desired_version != Protocol::V1 {
+            // Servers may not accept this variable, in which case they fall back to V1.
+            channel
+                .setenv("GIT_PROTOCOL", &format!("version={}", self.desired_version as usize))
+                .ok();
+        }
+        // Messages on stderr aren't used, and ignoring them prevents the remote from blocking once its buffer is full.
+        channel
+            .handle_extended_data(ssh2::ExtendedData::Ignore)
+            .map_err(exec_err)?;
+        channel.exec(&command).map_err(exec_err)?;
+        Ok(git::Connection::new_for_spawned_process(
+            channel.stream(0),
+            channel,
+            self.desired_version,
+            self.path.clone(),
+        ))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn verify_host_key(&self, session: &ssh2::Session, host: &str, port: u16) -> Result<(), Error> {
+        let (key, _kind) = session
+            .host_key()
+            .ok_or_else(|| Error::HostKeyMissing { host: host.into() })?;
+        let mut known_hosts = session.known_hosts().map_err(|source| Error::Session {
+            host: host.into(),
+            source,
+        })?;
+        let files = if self.options.known_hosts_files.is_empty() {
+            gix_path::home_dir()
+                .map(|home| home.join(".ssh").join("known_hosts"))
+                .into_iter()
+                .chain(Some(PathBuf::from("/etc/ssh/ssh_known_hosts")))
+                .collect()
+        } else {
+            self.options.known_hosts_files.clone()
+        };
+        for path in files.into_iter().filter(|path| path.is_file()) {
+            known_hosts
+                .read_file(&path, ssh2::KnownHostFileKind::OpenSSH)
+                .map_err(|source| Error::KnownHosts { path, source })?;
+        }
+        check_host_key(
+            known_hosts.check_port(host, port, key),
+            host,
+            self.options.accept_unknown_hosts,
+        )
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn authenticate(&self, session: &ssh2::Session, host: &str) -> Result<(), Error> {
+        let user = match self.url.user() {
+            Some(user) => user.to_owned(),
+            None => ["USER", "USERNAME"]
+                .iter()
+                .find_map(|var| std::env::var(var).ok().filter(|user| !user.is_empty()))
+                .ok_or(Error::UserMissing)?,
+        };
+        if session.userauth_agent(&user).is_ok() && session.authenticated() {
+            return Ok(());
+        }
+        let identity_files = if self.options.identity_files.is_empty() {
+            gix_path::home_dir()
+                .map(|home| {
+                    ["id_ed25519", "id_ecdsa", "id_rsa"]
+                        .iter()
+                        .map(|name| home.join(".ssh").join(name))
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            self.options.identity_files.clone()
+        };
+        for path in identity_files.iter().filter(|path| path.is_file()) {
+            if session.userauth_pubkey_file(&user, None, path, None).is_ok() && session.authenticated() {
+                return Ok(());
+            }
+        }
+        if let Some(password) = &self.password {
+            if session.userauth_password(&user, password).is_ok() && session.authenticated() {
+                return Ok(());
+            }
+        }
+        Err(Error::Authentication {
+            user,
+            host: host.into(),
+        })
+    }
+}
+
+impl client::TransportWithoutIO for Transport {
+    fn set_identity(&mut self, identity: gix_sec::identity::Account) -> Result<(), client::Error> {
+        self.url
+            .set_user((!identity.username.is_empty()).then_some(identity.username));
+        self.password = (!identity.password.is_empty()).then_some(identity.password);
+        Ok(())
+    }
+
+    fn request(
+        &mut self,
+        write_mode: WriteMode,
+        on_into_read: MessageKind,
+    ) -> Result<RequestWriter<'_>, client::Error> {
+        self.connection
+            .as_mut()
+            .expect("handshake() to have been called first")
+            .request(write_mode, on_into_read)
+    }
+
+    fn to_url(&self) -> Cow<'_, BStr> {
+        Cow::Owned(self.url.to_bstring())
+    }
+
+    fn actual_protocol_version(&self) -> Option<Protocol> {
+        self.connection.as_ref().and_then(|c| c.actual_protocol_version())
+    }
+
+    fn connection_persists_across_multiple_requests(&self) -> bool {
+        true
+    }
+
+    fn configure(&mut self, config: &dyn Any) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        if let Some(options) = config.downcast_ref::<Options>() {
+            self.options = options.clone();
+        }
+        Ok(())
+    }
+}
+
+impl client::Transport for Transport {
+    fn handshake<'a>(
+        &mut self,
+        service: Service,
+        extra_parameters: &'a [(&'a str, Option<&'a str>)],
+    ) -> Result<SetServiceResponse<'_>, client::Error> {
+        let connection = self.connection.insert(self.establish(service)?);
+        connection.handshake(service, extra_parameters)
+    }
+}
+
+/// Decide if a host whose key was looked up in the known hosts with `result` may be connected to, accepting hosts that aren't
+/// known only if `accept_unknown_hosts` is `true`.
+#[allow(clippy::result_large_err)]
+fn check_host_key(result: ssh2::CheckResult, host: &str, accept_unknown_hosts: bool) -> Result<(), Error> {
+    match result {
+        ssh2::CheckResult::Match => Ok(()),
+        ssh2::CheckResult::NotFound if accept_unknown_hosts => Ok(()),
+        ssh2::CheckResult::NotFound => Err(Error::HostKeyUnknown { host: host.into() }),
+        ssh2::CheckResult::Mismatch => Err(Error::HostKeyMismatch { host: host.into() }),
+        ssh2::CheckResult::Failure => Err(Error::HostKeyCheck { host: host.into() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_host_key, connect, Error};
+    use crate::Protocol;
+
+    #[test]
+    fn host_keys_must_match_unless_unknown_hosts_are_accepted() {
+        for accept_unknown_hosts in [false, true] {
+            assert!(check_host_key(ssh2::CheckResult::Match, "host", accept_unknown_hosts).is_ok());
+            assert!(matches!(
+                check_host_key(ssh2::CheckResult::Mismatch, "host", accept_unknown_hosts),
+                Err(Error::HostKeyMismatch { .. })
+            ));
+            assert!(
+                matches!(
+                    check_host_key(ssh2::CheckResult::Failure, "host", accept_unknown_hosts),
+                    Err(Error::HostKeyCheck { .. })
+                ),
+                "a key that couldn't be checked is never accepted"
+            );
+        }
+        assert!(matches!(
+            check_host_key(ssh2::CheckResult::NotFound, "host", false),
+            Err(Error::HostKeyUnknown { .. })
+        ));
+        assert!(check_host_key(ssh2::CheckResult::NotFound, "host", true).is_ok());
+    }
+
+    #[test]
+    fn only_ssh_urls_with_host_are_supported() {
+        for url in ["file:///path/to/repo", "https://host/repo"] {
+            let url = gix_url::parse(url.into()).expect("valid url");
+            assert!(connect(url, Protocol::V2, Default::default()).is_err());
+        }
+        let url = gix_url::parse("user@host.xy:~/repo".into()).expect("valid url");
+        let transport = connect(url, Protocol::V2, Default::default()).expect("ssh urls are fine");
+        assert_eq!(
+            transport.path, "~/repo",
+            "the path will be substituted by the remote shell"
+        );
+    }
+}
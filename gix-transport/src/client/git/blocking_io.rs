@@ -37,6 +37,10 @@ where
         )
     }
 
+    fn actual_protocol_version(&self) -> Option<Protocol> {
+        self.actual_version
+    }
+
     fn connection_persists_across_multiple_requests(&self) -> bool {
         true
     }
@@ -73,6 +77,7 @@ where
             refs,
             protocol: actual_protocol,
         } = Capabilities::from_lines_with_version_detection(&mut self.line_provider)?;
+        self.actual_version = Some(actual_protocol);
         Ok(SetServiceResponse {
             actual_protocol,
             capabilities,
@@ -104,6 +109,7 @@ where
             path: repository_path.into(),
             virtual_host: virtual_host.map(|(h, p)| (h.into(), p)),
             desired_version,
+            actual_version: None,
             custom_url: None,
             mode,
         }
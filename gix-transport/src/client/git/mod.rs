@@ -21,6 +21,7 @@ pub struct Connection<R, W> {
     pub(in crate::client) path: BString,
     pub(in crate::client) virtual_host: Option<(String, Option<u16>)>,
     pub(in crate::client) desired_version: Protocol,
+    pub(in crate::client) actual_version: Option<Protocol>,
     custom_url: Option<BString>,
     pub(in crate::client) mode: ConnectMode,
 }
@@ -47,6 +47,12 @@ pub trait TransportWithoutIO {
         &[]
     }
 
+    /// Return the protocol version the server responded with during the last handshake, which may be lower than the desired one,
+    /// or `None` if no handshake was performed yet or if the transport doesn't keep track of it.
+    fn actual_protocol_version(&self) -> Option<Protocol> {
+        None
+    }
+
     /// Returns true if the transport provides persistent connections across multiple requests, or false otherwise.
     /// Not being persistent implies that certain information has to be resent on each 'turn'
     /// of the fetch negotiation or that the end of interaction (i.e. no further request will be made) has to be indicated
@@ -78,6 +84,10 @@ impl<T: TransportWithoutIO + ?Sized> TransportWithoutIO for Box<T> {
         self.deref().supported_protocol_versions()
     }
 
+    fn actual_protocol_version(&self) -> Option<Protocol> {
+        self.deref().actual_protocol_version()
+    }
+
     fn connection_persists_across_multiple_requests(&self) -> bool {
         self.deref().connection_persists_across_multiple_requests()
     }
@@ -105,6 +115,10 @@ impl<T: TransportWithoutIO + ?Sized> TransportWithoutIO for &mut T {
         self.deref().supported_protocol_versions()
     }
 
+    fn actual_protocol_version(&self) -> Option<Protocol> {
+        self.deref().actual_protocol_version()
+    }
+
     fn connection_persists_across_multiple_requests(&self) -> bool {
         self.deref().connection_persists_across_multiple_requests()
     }
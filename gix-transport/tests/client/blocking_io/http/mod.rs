@@ -217,6 +217,52 @@ fn http_error_results_in_observable_error() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn http_proxy_is_used_with_its_credentials() -> crate::Result {
+    let server = mock::serve_once("v1/http-handshake.response");
+    let mut client = gix_transport::client::http::connect("http://example.com/repo".try_into()?, Protocol::V1);
+    client
+        .configure(&http::Options {
+            proxy: Some(format!("http://user:pass@{}", server.addr)),
+            proxy_auth_method: http::options::ProxyAuthMethod::Basic,
+            ..Default::default()
+        })
+        .map_err(|err| err as Box<dyn std::error::Error>)?;
+    client.handshake(Service::UploadPack, &[])?;
+
+    let received = server.received_as_string().to_lowercase();
+    assert!(
+        received.starts_with("get http://example.com/repo/info/refs?service=git-upload-pack http/1.1"),
+        "the proxy receives the full url: {received}"
+    );
+    assert!(
+        received.contains("proxy-authorization: basic dxnlcjpwyxnz"),
+        "credentials are sent to the proxy: {received}"
+    );
+    Ok(())
+}
+
+#[test]
+fn http_proxy_is_bypassed_for_hosts_in_no_proxy() -> crate::Result {
+    let (server, mut client) =
+        mock::serve_and_connect("v1/http-handshake.response", "path/not-important", Protocol::V1)?;
+    client
+        .configure(&http::Options {
+            proxy: Some("http://127.0.0.1:1".into()),
+            no_proxy: Some("example.com,127.0.0.1".into()),
+            ..Default::default()
+        })
+        .map_err(|err| err as Box<dyn std::error::Error>)?;
+    client.handshake(Service::UploadPack, &[])?;
+
+    let received = server.received_as_string();
+    assert!(
+        received.starts_with("GET /path/not-important/info/refs?service=git-upload-pack HTTP/1.1"),
+        "the request goes to the server directly: {received}"
+    );
+    Ok(())
+}
+
 #[test]
 fn handshake_v1() -> crate::Result {
     let (server, mut c) = mock::serve_and_connect(
@@ -673,3 +719,37 @@ fn check_content_type_is_case_insensitive() -> crate::Result {
     assert!(result.is_ok());
     Ok(())
 }
+
+#[test]
+fn handshake_falls_back_to_v0_if_the_v2_request_is_rejected() -> crate::Result {
+    let (server, mut client) = mock::serve_and_connect("http-400.response", "path/not-important", Protocol::V2)?;
+    assert_eq!(client.actual_protocol_version(), None, "no handshake was performed yet");
+    let handshake = std::thread::spawn(move || {
+        let actual_protocol = client
+            .handshake(Service::UploadPack, &[("object-format", Some("sha1"))])
+            .expect("the second attempt succeeds")
+            .actual_protocol;
+        (actual_protocol, client)
+    });
+
+    let first = server.received_as_string().to_lowercase();
+    assert!(
+        first.contains("git-protocol: version=2:object-format=sha1"),
+        "the desired version is requested first: {first}"
+    );
+    server.next_read_and_respond_with(fixture_bytes("v1/http-handshake.response"));
+    let second = server.received_as_string().to_lowercase();
+    assert!(
+        !second.contains("git-protocol"),
+        "the retry is performed like a V0 client would: {second}"
+    );
+
+    let (actual_protocol, client) = handshake.join().expect("no panic");
+    assert_eq!(actual_protocol, Protocol::V1);
+    assert_eq!(
+        client.actual_protocol_version(),
+        Some(Protocol::V1),
+        "the negotiated version is available on the transport"
+    );
+    Ok(())
+}
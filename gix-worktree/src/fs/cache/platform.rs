@@ -6,7 +6,7 @@ use gix_hash::oid;
 use crate::{
     fs,
     fs::{
-        cache::{Platform, State},
+        cache::{state::AttributeSource, Platform, State},
         PathOidMapping,
     },
 };
@@ -40,6 +40,19 @@ impl<'a> Platform<'a> {
             gix_path::to_unix_separators_on_windows(gix_path::into_bstr(self.parent.stack.current_relative.as_path()));
         ignore.matching_exclude_pattern(relative_path.as_bstr(), self.is_dir, self.parent.case)
     }
+
+    /// Append all attribute patterns matching the currently set path to `out`, from the highest priority to the lowest,
+    /// while honoring the case-sensitivity the cache was configured with. Returns true if at least one pattern matched.
+    ///
+    /// # Panics
+    ///
+    /// If the cache was configured without attributes.
+    pub fn matching_attributes(&self, out: &mut Vec<gix_attributes::Match<'a, gix_attributes::Value>>) -> bool {
+        let attributes = self.parent.state.attributes_or_panic();
+        let relative_path =
+            gix_path::to_unix_separators_on_windows(gix_path::into_bstr(self.parent.stack.current_relative.as_path()));
+        attributes.matching_attributes(relative_path.as_bstr(), self.is_dir, self.parent.case, out)
+    }
 }
 
 impl<'a> std::fmt::Debug for Platform<'a> {
@@ -63,11 +76,23 @@ where
 {
     fn push_directory(&mut self, stack: &fs::Stack) -> std::io::Result<()> {
         match &mut self.state {
-            State::CreateDirectoryAndAttributesStack { attributes: _, .. } => {
-                // TODO: attributes
-            }
-            State::AttributesAndIgnoreStack { ignore, attributes: _ } => {
-                // TODO: attributes
+            State::CreateDirectoryAndAttributesStack { attributes, .. } => attributes.push_directory(
+                &stack.root,
+                &stack.current,
+                self.buf,
+                self.attribute_files_in_index,
+                &mut self.find,
+                AttributeSource::IndexThenWorktree,
+            )?,
+            State::AttributesAndIgnoreStack { ignore, attributes } => {
+                attributes.push_directory(
+                    &stack.root,
+                    &stack.current,
+                    self.buf,
+                    self.attribute_files_in_index,
+                    &mut self.find,
+                    AttributeSource::WorktreeThenIndex,
+                )?;
                 ignore.push_directory(
                     &stack.root,
                     &stack.current,
@@ -117,11 +142,11 @@ where
 
     fn pop_directory(&mut self) {
         match &mut self.state {
-            State::CreateDirectoryAndAttributesStack { attributes: _, .. } => {
-                // TODO: attributes
+            State::CreateDirectoryAndAttributesStack { attributes, .. } => {
+                attributes.pop_directory();
             }
-            State::AttributesAndIgnoreStack { attributes: _, ignore } => {
-                // TODO: attributes
+            State::AttributesAndIgnoreStack { attributes, ignore } => {
+                attributes.pop_directory();
                 ignore.pop_directory();
             }
             State::IgnoreStack(ignore) => {
@@ -10,13 +10,21 @@ type AttributeMatchGroup = gix_attributes::MatchGroup<gix_attributes::Attributes
 type IgnoreMatchGroup = gix_attributes::MatchGroup<gix_attributes::Ignore>;
 
 /// State related to attributes associated with files in the repository.
-#[derive(Default, Clone)]
+#[derive(Clone)]
 #[allow(unused)]
 pub struct Attributes {
     /// Attribute patterns that match the currently set directory (in the stack).
     pub stack: AttributeMatchGroup,
     /// Attribute patterns which aren't tied to the repository root, hence are global. They are consulted last.
     pub globals: AttributeMatchGroup,
+    /// The name of the file to look for in directories.
+    file_name_for_directories: BString,
+}
+
+impl Default for Attributes {
+    fn default() -> Self {
+        Attributes::new(Default::default(), None)
+    }
 }
 
 /// State related to the exclusion of files.
@@ -34,7 +42,7 @@ pub struct Ignore {
     /// currently set directory had a pattern matched. Note that this one could be negated.
     /// (index into match groups, index into list of pattern lists, index into pattern list)
     matched_directory_patterns_stack: Vec<Option<(usize, usize, usize)>>,
-    ///  The name of the file to look for in directories.
+    /// The name of the file to look for in directories.
     exclude_file_name_for_directories: BString,
     /// The case to use when matching directories as they are pushed onto the stack. We run them against the exclude engine
     /// to know if an entire path can be ignored as a parent directory is ignored.
@@ -185,21 +193,99 @@ impl Ignore {
     }
 }
 
+/// Where to read `.gitattributes` files from first when pushing a directory onto the attribute stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AttributeSource {
+    /// Read the file from the index first, which is what git does during checkout when the worktree isn't up-to-date yet.
+    IndexThenWorktree,
+    /// Read the file from the worktree first, and fall back to the index if it doesn't exist there.
+    WorktreeThenIndex,
+}
+
 impl Attributes {
     /// Create a new instance from an attribute match group that represents `globals`.
     ///
-    /// A stack of attributes will be applied on top of it later.
-    pub fn new(globals: AttributeMatchGroup) -> Self {
+    /// A stack of attributes will be applied on top of it later, read from files named `file_name_for_directories`
+    /// within each directory, which defaults to `.gitattributes`.
+    pub fn new(globals: AttributeMatchGroup, file_name_for_directories: Option<&BStr>) -> Self {
         Attributes {
             globals,
             stack: Default::default(),
+            file_name_for_directories: file_name_for_directories
+                .map(ToOwned::to_owned)
+                .unwrap_or_else(|| ".gitattributes".into()),
         }
     }
+
+    pub(crate) fn pop_directory(&mut self) {
+        self.stack.patterns.pop().expect("something to pop");
+    }
+
+    pub(crate) fn push_directory<Find, E>(
+        &mut self,
+        root: &Path,
+        dir: &Path,
+        buf: &mut Vec<u8>,
+        attribute_files_in_index: &[PathOidMapping],
+        mut find: Find,
+        source: AttributeSource,
+    ) -> std::io::Result<()>
+    where
+        Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<gix_object::BlobRef<'b>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let file_name = gix_path::from_bstr(self.file_name_for_directories.as_bstr());
+        let rela_dir = dir.strip_prefix(root).expect("dir in root");
+        let attr_path_relative = rela_dir.join(&file_name);
+        let attr_path_relative = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(attr_path_relative));
+        let attr_file_in_index =
+            attribute_files_in_index.binary_search_by(|t| t.0.as_bstr().cmp(attr_path_relative.as_ref()));
+        let follow_symlinks = attr_file_in_index.is_err();
+        let read_from_index = (source == AttributeSource::IndexThenWorktree && attr_file_in_index.is_ok())
+            || !self
+                .stack
+                .add_patterns_file(dir.join(file_name), follow_symlinks, Some(root), buf)?;
+        if read_from_index {
+            match attr_file_in_index {
+                Ok(idx) => {
+                    let blob = find(&attribute_files_in_index[idx].1, buf)
+                        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+                    let attr_path = gix_path::from_bstring(attr_path_relative.into_owned());
+                    self.stack.add_patterns_buffer(blob.data, attr_path, Some(root));
+                }
+                Err(_) => {
+                    // Need one stack level per component so push and pop matches.
+                    self.stack.patterns.push(gix_attributes::PatternList {
+                        patterns: Vec::new(),
+                        source: None,
+                        base: None,
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Append all matches for `relative_path` to `out`, with patterns of the directory stack taking precedence over
+    /// the global ones, and return true if at least one pattern matched.
+    pub(crate) fn matching_attributes<'a>(
+        &'a self,
+        relative_path: &BStr,
+        is_dir: Option<bool>,
+        case: Case,
+        out: &mut Vec<gix_attributes::Match<'a, gix_attributes::Value>>,
+    ) -> bool {
+        let mut has_match = false;
+        for group in [&self.stack, &self.globals] {
+            has_match |= group.pattern_matching_relative_path_all(relative_path, is_dir, case, out);
+        }
+        has_match
+    }
 }
 
 impl From<AttributeMatchGroup> for Attributes {
     fn from(group: AttributeMatchGroup) -> Self {
-        Attributes::new(group)
+        Attributes::new(group, None)
     }
 }
 
@@ -244,15 +330,15 @@ impl State {
                 a1_backing = [(v.exclude_file_name_for_directories.as_bytes().as_bstr(), true)];
                 a1_backing.as_ref()
             }
-            State::AttributesAndIgnoreStack { ignore, .. } => {
+            State::AttributesAndIgnoreStack { ignore, attributes } => {
                 a2_backing = [
                     (ignore.exclude_file_name_for_directories.as_bytes().as_bstr(), true),
-                    (".gitattributes".into(), false),
+                    (attributes.file_name_for_directories.as_bytes().as_bstr(), false),
                 ];
                 a2_backing.as_ref()
             }
-            State::CreateDirectoryAndAttributesStack { .. } => {
-                a1_backing = [(".gitattributes".into(), true)];
+            State::CreateDirectoryAndAttributesStack { attributes, .. } => {
+                a1_backing = [(attributes.file_name_for_directories.as_bytes().as_bstr(), false)];
                 a1_backing.as_ref()
             }
         };
@@ -289,6 +375,16 @@ impl State {
             .collect()
     }
 
+    pub(crate) fn attributes_or_panic(&self) -> &Attributes {
+        match self {
+            State::AttributesAndIgnoreStack { attributes, .. }
+            | State::CreateDirectoryAndAttributesStack { attributes, .. } => attributes,
+            State::IgnoreStack(_) => {
+                unreachable!("BUG: must not try to check attributes without it being setup")
+            }
+        }
+    }
+
     pub(crate) fn ignore_or_panic(&self) -> &Ignore {
         match self {
             State::IgnoreStack(v) => v,
@@ -0,0 +1,292 @@
+use std::path::{Path, PathBuf};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_index::extension::{fs_monitor::Token, FsMonitor};
+
+/// The token to send to the builtin filesystem monitor daemon if we don't have one yet, which makes it respond with a new one.
+const FAKE_DAEMON_TOKEN: &str = "builtin:fake";
+
+/// The error returned by [`query()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not run the fsmonitor hook '{command}'")]
+    Spawn { command: BString, source: std::io::Error },
+    #[error("The fsmonitor hook '{command}' failed with {status}")]
+    HookFailed {
+        command: BString,
+        status: std::process::ExitStatus,
+    },
+    #[error("The fsmonitor hook version {version} isn't supported")]
+    UnsupportedHookVersion { version: u8 },
+    #[error("Could not communicate with the fsmonitor daemon at '{}'", socket.display())]
+    Daemon { socket: PathBuf, source: std::io::Error },
+}
+
+/// The source of information about changes in the worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// Run `command` with the shell in the root of the worktree, passing the protocol version and the token as arguments,
+    /// as configured with a path in `core.fsmonitor`.
+    Hook {
+        /// The command to run.
+        command: BString,
+        /// The version of the hook protocol, `1` or `2`, as configured with `core.fsmonitorHookVersion`.
+        ///
+        /// If `None`, version 2 is tried first before falling back to version 1, like `git` does.
+        version: Option<u8>,
+    },
+    /// Ask the builtin filesystem monitor daemon which listens at `socket`, as configured with `core.fsmonitor=true`.
+    ///
+    /// The socket is typically `.git/fsmonitor--daemon.ipc`.
+    Daemon {
+        /// The path to the unix domain socket of the daemon.
+        socket: PathBuf,
+    },
+}
+
+/// The changes reported by the filesystem monitor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changes {
+    /// Everything has to be considered changed, as the filesystem monitor didn't know what changed or was asked
+    /// for the first time.
+    All,
+    /// Only these paths relative to the root of the worktree changed, with directories possibly ending in a slash.
+    Paths(Vec<BString>),
+}
+
+/// The outcome of [`query()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The token to use in the next query, to obtain all changes since this one.
+    pub token: Token,
+    /// The changes since the token used in the query.
+    pub changes: Changes,
+}
+
+/// Ask `source` for all changes in the worktree at `worktree_root` since `token`, which is typically obtained from the
+/// filesystem monitor extension of the index.
+///
+/// If there is no `token`, hooks aren't queried as all paths have to be considered changed anyway.
+pub fn query(source: &Source, worktree_root: &Path, token: Option<&Token>) -> Result<Outcome, Error> {
+    match source {
+        Source::Hook { command, version } => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_nanos() as u64);
+            let token = match token {
+                Some(token) => token.to_bstring(),
+                None => {
+                    return Ok(Outcome {
+                        token: Token::V2 {
+                            token: now.to_string().into(),
+                        },
+                        changes: Changes::All,
+                    })
+                }
+            };
+            match version {
+                Some(1) => query_hook_v1(command.as_ref(), worktree_root, token.as_ref(), now),
+                Some(2) => query_hook_v2(command.as_ref(), worktree_root, token.as_ref()),
+                Some(version) => Err(Error::UnsupportedHookVersion { version: *version }),
+                None => query_hook_v2(command.as_ref(), worktree_root, token.as_ref())
+                    .or_else(|_| query_hook_v1(command.as_ref(), worktree_root, token.as_ref(), now)),
+            }
+        }
+        Source::Daemon { socket } => {
+            let token = token.map_or_else(|| FAKE_DAEMON_TOKEN.into(), Token::to_bstring);
+            let response = query_daemon(socket, token.as_ref()).map_err(|source| Error::Daemon {
+                socket: socket.to_owned(),
+                source,
+            })?;
+            Ok(parse_response_with_token(&response))
+        }
+    }
+}
+
+/// Apply the changes in `outcome` to `index` so entries of changed paths lose their
+/// [`FSMONITOR_VALID`][gix_index::entry::Flags::FSMONITOR_VALID] flag, and untracked-cache directories containing them
+/// are invalidated, before storing the new token in its filesystem monitor extension.
+///
+/// Note that all entries that aren't affected by the changes keep their flag, which should be set by whoever verified
+/// that an entry matches the worktree.
+pub fn apply(index: &mut gix_index::State, outcome: Outcome) {
+    match outcome.changes {
+        Changes::All => {
+            for entry in index.entries_mut() {
+                entry.flags.remove(gix_index::entry::Flags::FSMONITOR_VALID);
+            }
+            if let Some(untracked) = index.untracked_mut() {
+                for dir in &mut untracked.directories {
+                    dir.stat = None;
+                    dir.untracked_entries.clear();
+                }
+            }
+        }
+        Changes::Paths(paths) => {
+            for path in paths {
+                let path = path.as_bstr();
+                let path = path.strip_suffix(b"/").map_or(path, |path| path.as_bstr());
+                let mut dir = path.to_owned();
+                dir.push_byte(b'/');
+                invalidate_entries(index, path, false);
+                invalidate_entries(index, dir.as_bstr(), true);
+                if let Some(untracked) = index.untracked_mut() {
+                    // The path may be a directory, which itself needs to be read again in that case.
+                    untracked.invalidate_path(dir.as_bstr());
+                }
+            }
+        }
+    }
+    match index.fs_monitor_mut() {
+        Some(fs_monitor) => fs_monitor.token = outcome.token,
+        None => {
+            index.set_fs_monitor(Some(FsMonitor::new(outcome.token)));
+        }
+    }
+}
+
+/// Remove the valid flag from all entries whose path is `path`, or that start with `path` if `is_prefix` is `true`.
+fn invalidate_entries(index: &mut gix_index::State, path: &BStr, is_prefix: bool) {
+    let start = index.entries().partition_point(|entry| entry.path(index) < path);
+    let end = start
+        + index.entries()[start..]
+            .iter()
+            .take_while(|entry| {
+                let entry_path = entry.path(index);
+                if is_prefix {
+                    entry_path.starts_with(path)
+                } else {
+                    entry_path == path
+                }
+            })
+            .count();
+    for entry in &mut index.entries_mut()[start..end] {
+        entry.flags.remove(gix_index::entry::Flags::FSMONITOR_VALID);
+    }
+}
+
+fn query_hook_v1(command: &BStr, worktree_root: &Path, token: &BStr, now: u64) -> Result<Outcome, Error> {
+    // Version 1 only understands timestamps, so without one we can't know what changed.
+    if token
+        .to_str()
+        .ok()
+        .and_then(|token| token.parse::<u64>().ok())
+        .is_none()
+    {
+        return Ok(Outcome {
+            token: Token::V2 {
+                token: now.to_string().into(),
+            },
+            changes: Changes::All,
+        });
+    }
+    let output = run_hook(command, worktree_root, 1, token)?;
+    Ok(Outcome {
+        token: Token::V2 {
+            token: now.to_string().into(),
+        },
+        changes: parse_paths(output.split_str(b"\0")),
+    })
+}
+
+fn query_hook_v2(command: &BStr, worktree_root: &Path, token: &BStr) -> Result<Outcome, Error> {
+    let output = run_hook(command, worktree_root, 2, token)?;
+    Ok(parse_response_with_token(&output))
+}
+
+fn run_hook(command: &BStr, worktree_root: &Path, version: u8, token: &BStr) -> Result<Vec<u8>, Error> {
+    let mut cmd: std::process::Command =
+        gix_command::prepare(gix_path::from_bstr(command).into_owned().into_os_string())
+            .with_shell()
+            .arg(version.to_string())
+            .arg(gix_path::from_bstr(token).into_owned().into_os_string())
+            .into();
+    let output = cmd.current_dir(worktree_root).output().map_err(|source| Error::Spawn {
+        command: command.to_owned(),
+        source,
+    })?;
+    if !output.status.success() {
+        return Err(Error::HookFailed {
+            command: command.to_owned(),
+            status: output.status,
+        });
+    }
+    Ok(output.stdout)
+}
+
+/// Parse a response of the hook protocol version 2 or of the daemon, which starts with the new token.
+fn parse_response_with_token(response: &[u8]) -> Outcome {
+    let mut fields = response.split_str(b"\0");
+    let token = fields.next().unwrap_or_default();
+    Outcome {
+        token: Token::V2 { token: token.into() },
+        changes: parse_paths(fields),
+    }
+}
+
+/// Parse NUL separated `paths`, where a single slash means that everything has to be considered changed.
+fn parse_paths<'a>(paths: impl Iterator<Item = &'a [u8]>) -> Changes {
+    let mut out = Vec::new();
+    for path in paths.filter(|path| !path.is_empty()) {
+        if path == b"/" {
+            return Changes::All;
+        }
+        out.push(path.into());
+    }
+    Changes::Paths(out)
+}
+
+#[cfg(unix)]
+fn query_daemon(socket: &Path, token: &BStr) -> std::io::Result<Vec<u8>> {
+    use std::io::{Read, Write};
+
+    /// The maximum amount of bytes of data in a single packet line.
+    const MAX_DATA_LEN: usize = 65516;
+
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)?;
+    for chunk in token.chunks(MAX_DATA_LEN) {
+        write!(stream, "{:04x}", chunk.len() + 4)?;
+        stream.write_all(chunk)?;
+    }
+    stream.write_all(b"0000")?;
+    stream.flush()?;
+
+    let mut response = Vec::new();
+    loop {
+        let mut hex_len = [0u8; 4];
+        stream.read_exact(&mut hex_len)?;
+        let len = std::str::from_utf8(&hex_len)
+            .ok()
+            .and_then(|len| usize::from_str_radix(len, 16).ok())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid packet line length {:?}", hex_len.as_bstr()),
+                )
+            })?;
+        match len {
+            0 => break,
+            1..=4 => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Unexpected packet line length {len}"),
+                ))
+            }
+            _ => {
+                let start = response.len();
+                response.resize(start + len - 4, 0);
+                stream.read_exact(&mut response[start..])?;
+            }
+        }
+    }
+    Ok(response)
+}
+
+#[cfg(not(unix))]
+fn query_daemon(_socket: &Path, _token: &BStr) -> std::io::Result<Vec<u8>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "the fsmonitor daemon is only supported on unix",
+    ))
+}
@@ -1,6 +1,15 @@
 #![allow(missing_docs)]
-use bstr::BString;
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use bstr::{BStr, BString, ByteSlice};
 use gix_attributes::Attributes;
+use gix_features::progress::Progress;
+use gix_hash::oid;
+
+use crate::{fs, index::chunk};
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Collision {
@@ -61,6 +70,20 @@ pub struct Options {
     pub check_stat: bool,
     /// A group of attribute patterns that are applied globally, i.e. aren't rooted within the repository itself.
     pub attribute_globals: gix_attributes::MatchGroup<Attributes>,
+    /// The filter drivers to use for files whose `filter` attribute names them, typically from `filter.<driver>.*` configuration.
+    ///
+    /// Filter processes may only delay the content of files if a single thread is used.
+    pub filter_drivers: Vec<gix_filter::Driver>,
+    /// How to convert line endings of files, typically from `core.autocrlf` and `core.eol`.
+    ///
+    /// Along with the `text`, `crlf`, `eol` and `working-tree-encoding` attributes, it determines the conversions applied
+    /// before `filter` drivers.
+    pub eol_config: gix_filter::eol::Configuration,
+    /// The patterns of a sparse checkout, typically read from `.git/info/sparse-checkout` if `core.sparseCheckout` is set.
+    ///
+    /// If set, the [`SKIP_WORKTREE`][gix_index::entry::Flags::SKIP_WORKTREE] flag of all entries is updated to match
+    /// the patterns before checking out, so only included entries are written.
+    pub sparse_checkout: Option<crate::sparse::Patterns>,
 }
 
 impl Default for Options {
@@ -74,6 +97,9 @@ impl Default for Options {
             check_stat: true,
             overwrite_existing: false,
             attribute_globals: Default::default(),
+            filter_drivers: Vec::new(),
+            eol_config: Default::default(),
+            sparse_checkout: None,
         }
     }
 }
@@ -81,6 +107,8 @@ impl Default for Options {
 pub enum Error<E: std::error::Error + Send + Sync + 'static> {
     #[error("Could not convert path to UTF8: {}", .path)]
     IllformedUtf8 { path: BString },
+    #[error("The path '{}' did not match any entry in the tree", .path)]
+    PathNotInTree { path: BString },
     #[error("The clock was off when reading file related metadata after updating a file on disk")]
     Time(#[from] std::time::SystemTimeError),
     #[error("IO error while writing blob or reading file metadata or changing filetype")]
@@ -92,4 +120,158 @@ pub enum Error<E: std::error::Error + Send + Sync + 'static> {
         oid: gix_hash::ObjectId,
         path: std::path::PathBuf,
     },
+    #[error(transparent)]
+    Filter(#[from] gix_filter::pipeline::Error),
+}
+
+/// Update the entries of `index` at all `paths` to match their version in `tree`, and write them into the worktree at `dir`,
+/// similar to `git checkout <tree> -- <paths>`.
+///
+/// `tree` is a tree in its index representation, as created with [`gix_index::State::from_tree()`]. Each of `paths` matches the
+/// entry of the same name or all entries within the directory of that name, and it's an error if it doesn't match anything.
+/// Matching entries replace the ones at the same path in `index` along with their conflicting stages, or are added to it.
+/// Entries in `index` that are in the way of matching entries, like a file where the tree has a directory, are removed.
+///
+/// Files in the worktree are overwritten without checking them for modifications first, and the stat information of the
+/// updated entries is set to match the written files.
+#[allow(clippy::too_many_arguments)]
+pub fn paths_from_tree<Find, E>(
+    index: &mut gix_index::State,
+    tree: &gix_index::State,
+    paths: impl IntoIterator<Item = impl AsRef<BStr>>,
+    dir: impl Into<std::path::PathBuf>,
+    find: Find,
+    files: &mut impl Progress,
+    bytes: &mut impl Progress,
+    options: Options,
+) -> Result<Outcome, Error<E>>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E> + Clone,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut selected = BTreeMap::<&BStr, &gix_index::Entry>::new();
+    for path in paths {
+        let path = path.as_ref();
+        let path = path.strip_suffix(b"/").unwrap_or(path).as_bstr();
+        let mut matched = false;
+        for entry in tree.entries() {
+            let entry_path = entry.path(tree);
+            if entry_path == path || is_in_directory(entry_path, path) {
+                selected.insert(entry_path, entry);
+                matched = true;
+            }
+        }
+        if !matched {
+            return Err(Error::PathNotInTree { path: path.to_owned() });
+        }
+    }
+
+    let selected_dirs: HashSet<&BStr> = selected.keys().flat_map(|path| parent_directories(path)).collect();
+    index.remove_entries(|path, entry| {
+        (entry.stage() != 0 && selected.contains_key(path))
+            || selected_dirs.contains(path)
+            || parent_directories(path).any(|dir| selected.contains_key(dir))
+    });
+
+    let mut missing = Vec::new();
+    for (path, tree_entry) in &selected {
+        match index.entry_mut_by_path_and_stage(path, 0) {
+            Some(entry) => {
+                entry.id = tree_entry.id;
+                entry.mode = tree_entry.mode;
+                entry.stat = Default::default();
+                entry.flags.remove(gix_index::entry::Flags::SKIP_WORKTREE);
+            }
+            None => missing.push((*path, *tree_entry)),
+        }
+    }
+    if !missing.is_empty() {
+        for (path, entry) in missing {
+            index.dangerously_push_entry(Default::default(), entry.id, entry.flags, entry.mode, path);
+        }
+        index.sort_entries();
+    }
+
+    let paths = index.take_path_backing();
+    let res = paths_from_tree_inner(index, &paths, &selected, dir, find, files, bytes, options);
+    index.return_path_backing(paths);
+    res
+}
+
+#[allow(clippy::too_many_arguments)]
+fn paths_from_tree_inner<Find, E>(
+    index: &mut gix_index::State,
+    paths: &gix_index::PathStorage,
+    selected: &BTreeMap<&BStr, &gix_index::Entry>,
+    dir: impl Into<std::path::PathBuf>,
+    find: Find,
+    files: &mut impl Progress,
+    bytes: &mut impl Progress,
+    options: Options,
+) -> Result<Outcome, Error<E>>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E> + Clone,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let num_files = AtomicUsize::default();
+    let case = if options.fs.ignore_case {
+        gix_glob::pattern::Case::Fold
+    } else {
+        gix_glob::pattern::Case::Sensitive
+    };
+    let options = Options {
+        destination_is_initially_empty: false,
+        overwrite_existing: true,
+        ..options
+    };
+    let state = fs::cache::State::for_checkout(options.overwrite_existing, options.attribute_globals.clone().into());
+    let attribute_files = state.build_attribute_list(index, paths, case);
+    let mut ctx = chunk::Context {
+        buf: Vec::new(),
+        path_cache: fs::Cache::new(dir, state, case, Vec::with_capacity(512), attribute_files),
+        filters: gix_filter::Pipeline::new(
+            options.filter_drivers.clone(),
+            gix_filter::pipeline::Options {
+                eol_config: options.eol_config,
+                object_hash: index.object_hash(),
+            },
+        ),
+        filter_buf: Vec::new(),
+        allow_delay: true,
+        find,
+        options,
+        num_files: &num_files,
+    };
+
+    let outcome = chunk::process(
+        index
+            .entries_mut_with_paths_in(paths)
+            .filter(|(entry, path)| entry.stage() == 0 && selected.contains_key(path)),
+        files,
+        bytes,
+        &mut ctx,
+    )?;
+    let chunk::Outcome {
+        collisions,
+        errors,
+        bytes_written,
+        ..
+    } = chunk::process_delayed(outcome, files, bytes, &mut ctx)?;
+
+    Ok(Outcome {
+        files_updated: num_files.load(Ordering::Relaxed),
+        collisions,
+        errors,
+        bytes_written,
+    })
+}
+
+/// Return `true` if `path` is located within the directory `dir`.
+fn is_in_directory(path: &BStr, dir: &BStr) -> bool {
+    path.len() > dir.len() && path.starts_with(dir) && path[dir.len()] == b'/'
+}
+
+/// Return all directories leading up to `path`, from the innermost to the outermost one.
+fn parent_directories(path: &BStr) -> impl Iterator<Item = &BStr> {
+    path.rfind_iter("/").map(move |pos| path[..pos].as_bstr())
 }
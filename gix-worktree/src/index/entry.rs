@@ -11,24 +11,44 @@ pub struct Context<'a, Find> {
     pub find: &'a mut Find,
     pub path_cache: &'a mut fs::Cache,
     pub buf: &'a mut Vec<u8>,
+    pub filters: &'a mut gix_filter::Pipeline,
+    /// The buffer to hold the output of filters.
+    pub filter_buf: &'a mut Vec<u8>,
+    /// If `true`, filter processes may delay the delivery of the content of files.
+    pub allow_delay: bool,
+}
+
+/// What happened to an entry during checkout.
+pub enum Outcome {
+    /// The entry was written to disk, with `bytes` of content.
+    Written { bytes: usize },
+    /// A filter process delivers the content of the entry later, to be written with [`write_delayed()`].
+    Delayed,
+}
+
+impl Outcome {
+    /// Return the amount of bytes written, which is 0 if the entry was delayed.
+    pub fn bytes(&self) -> usize {
+        match self {
+            Outcome::Written { bytes } => *bytes,
+            Outcome::Delayed => 0,
+        }
+    }
 }
 
-#[cfg_attr(not(unix), allow(unused_variables))]
 pub fn checkout<Find, E>(
     entry: &mut Entry,
     entry_path: &BStr,
-    Context { find, path_cache, buf }: Context<'_, Find>,
-    index::checkout::Options {
-        fs: fs::Capabilities {
-            symlink,
-            executable_bit,
-            ..
-        },
-        destination_is_initially_empty,
-        overwrite_existing,
-        ..
-    }: index::checkout::Options,
-) -> Result<usize, index::checkout::Error<E>>
+    Context {
+        find,
+        path_cache,
+        buf,
+        filters,
+        filter_buf,
+        allow_delay,
+    }: Context<'_, Find>,
+    options: &index::checkout::Options,
+) -> Result<Outcome, index::checkout::Error<E>>
 where
     Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E>,
     E: std::error::Error + Send + Sync + 'static,
@@ -37,7 +57,17 @@ where
         path: entry_path.to_owned(),
     })?;
     let is_dir = Some(entry.mode == gix_index::entry::Mode::COMMIT || entry.mode == gix_index::entry::Mode::DIR);
-    let dest = path_cache.at_path(dest_relative, is_dir, &mut *find)?.path();
+    let platform = path_cache.at_path(dest_relative, is_dir, &mut *find)?;
+    let is_file = matches!(
+        entry.mode,
+        gix_index::entry::Mode::FILE | gix_index::entry::Mode::FILE_EXECUTABLE
+    );
+    let mut matches = Vec::new();
+    if is_file {
+        platform.matching_attributes(&mut matches);
+    }
+    let attributes = gix_filter::pipeline::Attributes::from_states(|name| attribute_state(&matches, name));
+    let dest = platform.path();
 
     let object_size = match entry.mode {
         gix_index::entry::Mode::FILE | gix_index::entry::Mode::FILE_EXECUTABLE => {
@@ -46,34 +76,12 @@ where
                 oid: entry.id,
                 path: dest.to_path_buf(),
             })?;
-
-            #[cfg_attr(not(unix), allow(unused_mut))]
-            let mut options = open_options(dest, destination_is_initially_empty, overwrite_existing);
-            let needs_executable_bit = executable_bit && entry.mode == gix_index::entry::Mode::FILE_EXECUTABLE;
-            #[cfg(unix)]
-            if needs_executable_bit && destination_is_initially_empty {
-                use std::os::unix::fs::OpenOptionsExt;
-                // Note that these only work if the file was newly created, but won't if it's already
-                // existing, possibly without the executable bit set. Thus we do this only if the file is new.
-                options.mode(0o777);
-            }
-
-            let mut file = try_write_or_unlink(dest, overwrite_existing, |p| options.open(p))?;
-            file.write_all(obj.data)?;
-
-            // For possibly existing, overwritten files, we must change the file mode explicitly.
-            #[cfg(unix)]
-            if needs_executable_bit && !destination_is_initially_empty {
-                use std::os::unix::fs::PermissionsExt;
-                let mut perm = std::fs::symlink_metadata(dest)?.permissions();
-                perm.set_mode(0o777);
-                std::fs::set_permissions(dest, perm)?;
-            }
-            // NOTE: we don't call `file.sync_all()` here knowing that some filesystems don't handle this well.
-            //       revisit this once there is a bug to fix.
-            update_fstat(entry, file.metadata()?)?;
-            file.close()?;
-            obj.data.len()
+            let data = match filters.convert_to_worktree(&attributes, entry_path, obj.data, allow_delay, filter_buf)? {
+                gix_filter::pipeline::Outcome::Unchanged => obj.data,
+                gix_filter::pipeline::Outcome::Changed => filter_buf.as_slice(),
+                gix_filter::pipeline::Outcome::Delayed => return Ok(Outcome::Delayed),
+            };
+            write_file(entry, dest, data, options)?
         }
         gix_index::entry::Mode::SYMLINK => {
             let obj = find(&entry.id, buf).map_err(|err| index::checkout::Error::Find {
@@ -84,11 +92,13 @@ where
             let symlink_destination = gix_path::try_from_byte_slice(obj.data)
                 .map_err(|_| index::checkout::Error::IllformedUtf8 { path: obj.data.into() })?;
 
-            if symlink {
-                try_write_or_unlink(dest, overwrite_existing, |p| os::create_symlink(symlink_destination, p))?;
+            if options.fs.symlink {
+                try_write_or_unlink(dest, options.overwrite_existing, |p| {
+                    os::create_symlink(symlink_destination, p)
+                })?;
             } else {
-                let mut file = try_write_or_unlink(dest, overwrite_existing, |p| {
-                    open_options(p, destination_is_initially_empty, overwrite_existing).open(dest)
+                let mut file = try_write_or_unlink(dest, options.overwrite_existing, |p| {
+                    open_options(p, options.destination_is_initially_empty, options.overwrite_existing).open(dest)
                 })?;
                 file.write_all(obj.data)?;
                 file.close()?;
@@ -101,7 +111,98 @@ where
         gix_index::entry::Mode::COMMIT => todo!(),
         _ => unreachable!(),
     };
-    Ok(object_size)
+    Ok(Outcome::Written { bytes: object_size })
+}
+
+/// Write `data`, the content of `entry` that was delayed by a filter process during [`checkout()`].
+pub fn write_delayed<Find, E>(
+    entry: &mut Entry,
+    entry_path: &BStr,
+    data: &[u8],
+    find: &mut Find,
+    path_cache: &mut fs::Cache,
+    options: &index::checkout::Options,
+) -> Result<Outcome, index::checkout::Error<E>>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let dest_relative = gix_path::try_from_bstr(entry_path).map_err(|_| index::checkout::Error::IllformedUtf8 {
+        path: entry_path.to_owned(),
+    })?;
+    let dest = path_cache.at_path(dest_relative, Some(false), &mut *find)?.path();
+    Ok(Outcome::Written {
+        bytes: write_file(entry, dest, data, options)?,
+    })
+}
+
+/// Return the state of the attribute `name` in `matches`, or [`Unspecified`][gix_attributes::StateRef::Unspecified] if
+/// it isn't assigned.
+fn attribute_state<'a>(
+    matches: &[gix_attributes::Match<'a, gix_attributes::Value>],
+    name: &str,
+) -> gix_attributes::StateRef<'a> {
+    matches
+        .iter()
+        .filter_map(|m| match m.value {
+            gix_attributes::Value::Assignments(assignments) => Some(assignments),
+            gix_attributes::Value::MacroAttributes(_) => None,
+        })
+        .flat_map(|assignments| assignments.iter().rev())
+        .find_map(|assignment| match assignment.name.as_str() {
+            attribute if attribute == name => Some(assignment.state.as_ref()),
+            // Macros aren't expanded yet, but the built-in `binary` macro is common and implies `-text`.
+            "binary" if name == "text" && matches!(assignment.state, gix_attributes::State::Set) => {
+                Some(gix_attributes::StateRef::Unset)
+            }
+            _ => None,
+        })
+        .unwrap_or(gix_attributes::StateRef::Unspecified)
+}
+
+/// Write `data` into the regular file `dest`, and update the stat information of `entry` accordingly.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn write_file<E>(
+    entry: &mut Entry,
+    dest: &Path,
+    data: &[u8],
+    &index::checkout::Options {
+        fs: fs::Capabilities { executable_bit, .. },
+        destination_is_initially_empty,
+        overwrite_existing,
+        ..
+    }: &index::checkout::Options,
+) -> Result<usize, index::checkout::Error<E>>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    #[cfg_attr(not(unix), allow(unused_mut))]
+    let mut options = open_options(dest, destination_is_initially_empty, overwrite_existing);
+    let needs_executable_bit = executable_bit && entry.mode == gix_index::entry::Mode::FILE_EXECUTABLE;
+    #[cfg(unix)]
+    if needs_executable_bit && destination_is_initially_empty {
+        use std::os::unix::fs::OpenOptionsExt;
+        // Note that these only work if the file was newly created, but won't if it's already
+        // existing, possibly without the executable bit set. Thus we do this only if the file is new.
+        options.mode(0o777);
+    }
+
+    let mut file = try_write_or_unlink(dest, overwrite_existing, |p| options.open(p))?;
+    file.write_all(data)?;
+
+    // For possibly existing, overwritten files, we must change the file mode explicitly.
+    #[cfg(unix)]
+    if needs_executable_bit && !destination_is_initially_empty {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perm = std::fs::symlink_metadata(dest)?.permissions();
+        perm.set_mode(0o777);
+        std::fs::set_permissions(dest, perm)?;
+    }
+    // NOTE: we don't call `file.sync_all()` here knowing that some filesystems don't handle this well.
+    //       revisit this once there is a bug to fix.
+    update_fstat(entry, file.metadata()?)?;
+    file.close()?;
+    Ok(data.len())
 }
 
 /// Note that this works only because we assume to not race ourselves when symlinks are involved, and we do this by
@@ -159,6 +260,7 @@ fn open_options(path: &Path, destination_is_initially_empty: bool, overwrite_exi
     options
         .create_new(destination_is_initially_empty && !overwrite_existing)
         .create(!destination_is_initially_empty || overwrite_existing)
+        .truncate(true)
         .write(true);
     options
 }
@@ -185,5 +287,6 @@ where
         .try_into()
         .expect("by 2038 we found a solution for this");
     stat.ctime.nsecs = ctime.subsec_nanos();
+    stat.size = meta.len() as u32;
     Ok(())
 }
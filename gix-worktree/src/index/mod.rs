@@ -25,6 +25,14 @@ where
     Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E> + Send + Clone,
     E: std::error::Error + Send + Sync + 'static,
 {
+    if let Some(patterns) = &options.sparse_checkout {
+        let case = if options.fs.ignore_case {
+            gix_glob::pattern::Case::Fold
+        } else {
+            gix_glob::pattern::Case::Sensitive
+        };
+        crate::sparse::apply(index, patterns, case);
+    }
     let paths = index.take_path_backing();
     let res = checkout_inner(index, &paths, dir, find, files, bytes, should_interrupt, options);
     index.return_path_backing(paths);
@@ -64,17 +72,22 @@ where
     let mut ctx = chunk::Context {
         buf: Vec::new(),
         path_cache: fs::Cache::new(dir, state, case, Vec::with_capacity(512), attribute_files),
+        filters: gix_filter::Pipeline::new(
+            options.filter_drivers.clone(),
+            gix_filter::pipeline::Options {
+                eol_config: options.eol_config,
+                object_hash: index.object_hash(),
+            },
+        ),
+        filter_buf: Vec::new(),
+        // Delayed content has to be fetched from the process it was delayed by, which is only possible on this thread.
+        allow_delay: num_threads == 1,
         find,
         options,
         num_files: &num_files,
     };
 
-    let chunk::Outcome {
-        mut collisions,
-        mut errors,
-        mut bytes_written,
-        delayed,
-    } = if num_threads == 1 {
+    let outcome = if num_threads == 1 {
         let entries_with_paths = interrupt::Iter::new(index.entries_mut_with_paths_in(paths), should_interrupt);
         chunk::process(entries_with_paths, files, bytes, &mut ctx)?
     } else {
@@ -99,18 +112,12 @@ where
             },
         )?
     };
-
-    for (entry, entry_path) in delayed {
-        bytes_written += chunk::checkout_entry_handle_result(
-            entry,
-            entry_path,
-            &mut errors,
-            &mut collisions,
-            files,
-            bytes,
-            &mut ctx,
-        )? as u64;
-    }
+    let chunk::Outcome {
+        collisions,
+        errors,
+        bytes_written,
+        ..
+    } = chunk::process_delayed(outcome, files, bytes, &mut ctx)?;
 
     Ok(checkout::Outcome {
         files_updated: num_files.load(Ordering::Relaxed),
@@ -167,11 +174,13 @@ mod chunk {
                 let super::Outcome {
                     bytes_written,
                     delayed,
+                    delayed_by_filter,
                     errors,
                     collisions,
                 } = item;
                 self.aggregate.bytes_written += bytes_written;
                 self.aggregate.delayed.extend(delayed);
+                self.aggregate.delayed_by_filter.extend(delayed_by_filter);
                 self.aggregate.errors.extend(errors);
                 self.aggregate.collisions.extend(collisions);
 
@@ -192,7 +201,10 @@ mod chunk {
     pub struct Outcome<'a> {
         pub collisions: Vec<checkout::Collision>,
         pub errors: Vec<checkout::ErrorRecord>,
+        /// Symlinks, which are written after all other entries.
         pub delayed: Vec<(&'a mut gix_index::Entry, &'a BStr)>,
+        /// Files whose content will be delivered later by a filter process.
+        pub delayed_by_filter: Vec<(&'a mut gix_index::Entry, &'a BStr)>,
         pub bytes_written: u64,
     }
 
@@ -201,6 +213,10 @@ mod chunk {
         pub find: Find,
         pub path_cache: fs::Cache,
         pub buf: Vec<u8>,
+        pub filters: gix_filter::Pipeline,
+        pub filter_buf: Vec<u8>,
+        /// If `true`, filter processes may delay the content of files, which is then written by [`process_delayed()`].
+        pub allow_delay: bool,
         pub options: checkout::Options,
         /// We keep these shared so that there is the chance for printing numbers that aren't looking like
         /// multiple of chunk sizes. Purely cosmetic. Otherwise it's the same as `files`.
@@ -218,6 +234,7 @@ mod chunk {
         E: std::error::Error + Send + Sync + 'static,
     {
         let mut delayed = Vec::new();
+        let mut delayed_by_filter = Vec::new();
         let mut collisions = Vec::new();
         let mut errors = Vec::new();
         let mut bytes_written = 0;
@@ -240,9 +257,10 @@ mod chunk {
                 continue;
             }
 
-            bytes_written +=
-                checkout_entry_handle_result(entry, entry_path, &mut errors, &mut collisions, files, bytes, ctx)?
-                    as u64;
+            match checkout_entry_handle_result(entry, entry_path, &mut errors, &mut collisions, files, bytes, ctx)? {
+                entry::Outcome::Written { bytes } => bytes_written += bytes as u64,
+                entry::Outcome::Delayed => delayed_by_filter.push((entry, entry_path)),
+            }
         }
 
         Ok(Outcome {
@@ -250,6 +268,65 @@ mod chunk {
             errors,
             collisions,
             delayed,
+            delayed_by_filter,
+        })
+    }
+
+    /// Write all files whose content was delayed by filter processes, followed by all delayed symlinks, and return
+    /// the updated `outcome` without delayed entries.
+    pub fn process_delayed<'entry, Find, E>(
+        Outcome {
+            mut collisions,
+            mut errors,
+            mut bytes_written,
+            delayed,
+            mut delayed_by_filter,
+        }: Outcome<'entry>,
+        files: &mut impl Progress,
+        bytes: &mut impl Progress,
+        ctx: &mut Context<'_, Find>,
+    ) -> Result<Outcome<'entry>, checkout::Error<E>>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E> + Clone,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        while let Some(path) = ctx.filters.fetch_delayed(&mut ctx.filter_buf)? {
+            let pos = match delayed_by_filter.iter().position(|(_, entry_path)| *entry_path == path) {
+                Some(pos) => pos,
+                None => continue,
+            };
+            let (entry, entry_path) = delayed_by_filter.swap_remove(pos);
+            let res = entry::write_delayed(
+                entry,
+                entry_path,
+                &ctx.filter_buf,
+                &mut ctx.find,
+                &mut ctx.path_cache,
+                &ctx.options,
+            );
+            bytes_written += handle_result(
+                res,
+                entry_path,
+                &mut errors,
+                &mut collisions,
+                files,
+                bytes,
+                &ctx.options,
+            )?
+            .bytes() as u64;
+        }
+
+        for (entry, entry_path) in delayed {
+            bytes_written +=
+                checkout_entry_handle_result(entry, entry_path, &mut errors, &mut collisions, files, bytes, ctx)?
+                    .bytes() as u64;
+        }
+        Ok(Outcome {
+            collisions,
+            errors,
+            delayed: Vec::new(),
+            delayed_by_filter: Vec::new(),
+            bytes_written,
         })
     }
 
@@ -264,10 +341,13 @@ mod chunk {
             find,
             path_cache,
             buf,
+            filters,
+            filter_buf,
+            allow_delay,
             options,
             num_files,
         }: &mut Context<'_, Find>,
-    ) -> Result<usize, checkout::Error<E>>
+    ) -> Result<entry::Outcome, checkout::Error<E>>
     where
         Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E> + Clone,
         E: std::error::Error + Send + Sync + 'static,
@@ -275,15 +355,37 @@ mod chunk {
         let res = entry::checkout(
             entry,
             entry_path,
-            entry::Context { find, path_cache, buf },
-            options.clone(),
+            entry::Context {
+                find,
+                path_cache,
+                buf,
+                filters,
+                filter_buf,
+                allow_delay: *allow_delay,
+            },
+            options,
         );
         files.inc();
         num_files.fetch_add(1, Ordering::SeqCst);
+        handle_result(res, entry_path, errors, collisions, files, bytes, options)
+    }
+
+    fn handle_result<E>(
+        res: Result<entry::Outcome, checkout::Error<E>>,
+        entry_path: &BStr,
+        errors: &mut Vec<checkout::ErrorRecord>,
+        collisions: &mut Vec<checkout::Collision>,
+        files: &mut impl Progress,
+        bytes: &mut impl Progress,
+        options: &checkout::Options,
+    ) -> Result<entry::Outcome, checkout::Error<E>>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
         match res {
-            Ok(object_size) => {
-                bytes.inc_by(object_size);
-                Ok(object_size)
+            Ok(outcome) => {
+                bytes.inc_by(outcome.bytes());
+                Ok(outcome)
             }
             Err(index::checkout::Error::Io(err)) if os::indicates_collision(&err) => {
                 // We are here because a file existed or was blocked by a directory which shouldn't be possible unless
@@ -293,7 +395,7 @@ mod chunk {
                     path: entry_path.into(),
                     error_kind: err.kind(),
                 });
-                Ok(0)
+                Ok(entry::Outcome::Written { bytes: 0 })
             }
             Err(err) => {
                 if options.keep_going {
@@ -301,7 +403,7 @@ mod chunk {
                         path: entry_path.into(),
                         error: Box::new(err),
                     });
-                    Ok(0)
+                    Ok(entry::Outcome::Written { bytes: 0 })
                 } else {
                     Err(err)
                 }
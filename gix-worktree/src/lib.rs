@@ -12,4 +12,13 @@ pub mod fs;
 ///
 pub mod index;
 
+/// Sparse checkouts, which only populate the worktree with the index entries matching a set of patterns.
+pub mod sparse;
+
+/// Discovery of untracked files, accelerated by the untracked cache of the index.
+pub mod untracked;
+
+/// Querying filesystem monitors for changes in the worktree, to avoid checking paths that didn't change.
+pub mod fsmonitor;
+
 pub(crate) mod os;
@@ -0,0 +1,273 @@
+use std::{collections::BTreeSet, path::PathBuf};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_attributes::{Ignore, Pattern, PatternList};
+use gix_glob::pattern::{Case, Mode};
+
+/// The patterns of a sparse checkout, typically read from `.git/info/sparse-checkout`, which determine the index entries
+/// that are present in the worktree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Patterns {
+    /// Patterns in the restricted format used if `core.sparseCheckoutCone` is set, which include all files in the root of
+    /// the worktree, all files in a set of directories recursively, and all files directly within the parents of these directories.
+    Cone(Cone),
+    /// Patterns in `.gitignore` format, where a matching pattern includes a path and a matching negated pattern excludes it.
+    /// Paths that aren't matched by any pattern are included if their closest matching parent directory is.
+    NonCone(PatternList<Ignore>),
+}
+
+/// The directories of a sparse checkout in cone mode.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cone {
+    /// Directories whose files are included recursively, without leading or trailing slash.
+    recursive: BTreeSet<BString>,
+    /// Directories whose files are included, but not the content of their sub-directories.
+    parents: BTreeSet<BString>,
+}
+
+impl Cone {
+    /// Create a cone that includes all files within each of `directories` recursively, as passed to `git sparse-checkout set`.
+    ///
+    /// Leading and trailing slashes are ignored.
+    pub fn from_directories(directories: impl IntoIterator<Item = impl AsRef<BStr>>) -> Self {
+        let mut cone = Cone::default();
+        for dir in directories {
+            let dir = dir.as_ref().trim_with(|c| c == '/');
+            if dir.is_empty() {
+                continue;
+            }
+            cone.parents
+                .extend(parent_directories(dir.as_bstr()).map(ToOwned::to_owned));
+            cone.recursive.insert(dir.as_bstr().to_owned());
+        }
+        cone
+    }
+
+    /// Parse `patterns` which must be in the format written by [`write_to()`][Patterns::write_to()], or return `None` if
+    /// there is a pattern that can't be represented in cone mode.
+    pub fn from_patterns(patterns: &PatternList<Ignore>) -> Option<Self> {
+        let mut cone = Cone::default();
+        for pattern in patterns.patterns.iter().map(|mapping| &mapping.pattern) {
+            let is_negative = pattern.is_negative();
+            let is_dir = pattern.mode.contains(Mode::MUST_BE_DIR);
+            if !pattern.mode.contains(Mode::ABSOLUTE) {
+                return None;
+            }
+            if pattern.text == "*" {
+                if is_negative != is_dir {
+                    return None;
+                }
+                continue;
+            }
+            if !is_dir || pattern.text.find(b"**").is_some() {
+                return None;
+            }
+            if is_negative {
+                let dir = unescape(pattern.text.strip_suffix(b"/*")?)?;
+                cone.recursive.take(&dir)?;
+                cone.parents.insert(dir);
+            } else {
+                let dir = unescape(&pattern.text)?;
+                cone.parents
+                    .extend(parent_directories(dir.as_bstr()).map(ToOwned::to_owned));
+                cone.recursive.insert(dir);
+            }
+        }
+        Some(cone)
+    }
+
+    /// Return the directories whose content is included recursively, sorted and without the ones contained in other directories.
+    pub fn directories(&self) -> impl Iterator<Item = &BStr> + '_ {
+        self.recursive
+            .iter()
+            .map(|dir| dir.as_bstr())
+            .filter(move |dir| !parent_directories(dir).any(|parent| self.recursive.contains(parent)))
+    }
+
+    /// Return `true` if the file at `path`, relative to the root of the worktree, is included in the cone.
+    pub fn is_included(&self, path: &BStr, case: Case) -> bool {
+        let parent = match path.rfind_byte(b'/') {
+            Some(pos) => path[..pos].as_bstr(),
+            None => return true,
+        };
+        contains(&self.parents, parent, case)
+            || std::iter::once(parent)
+                .chain(parent_directories(parent))
+                .any(|dir| contains(&self.recursive, dir, case))
+    }
+
+    /// Return `true` if none of the files within `dir`, relative to the root of the worktree, are included in the cone,
+    /// which allows to represent it with a single directory entry in a sparse index.
+    pub fn excludes_directory(&self, dir: &BStr, case: Case) -> bool {
+        !contains(&self.parents, dir, case)
+            && !std::iter::once(dir)
+                .chain(parent_directories(dir))
+                .any(|dir| contains(&self.recursive, dir, case))
+    }
+}
+
+impl Patterns {
+    /// Parse `bytes`, the content of a sparse checkout file at `source`, in cone mode if `cone` is `true`.
+    ///
+    /// Like `git`, non-cone mode is used instead if the patterns aren't in the format required for cone mode.
+    pub fn from_bytes(bytes: &[u8], source: impl Into<PathBuf>, cone: bool) -> Self {
+        Self::from_list(PatternList::from_bytes(bytes, source, None), cone)
+    }
+
+    /// Read the sparse checkout file at `source` using `buf` and parse it like [`from_bytes()`][Self::from_bytes()],
+    /// or return `None` if it doesn't exist.
+    pub fn from_file(source: impl Into<PathBuf>, cone: bool, buf: &mut Vec<u8>) -> std::io::Result<Option<Self>> {
+        Ok(PatternList::from_file(source, None, true, buf)?.map(|list| Self::from_list(list, cone)))
+    }
+
+    /// Create patterns for non-cone mode from `patterns` in `.gitignore` format, one per item.
+    pub fn non_cone(patterns: impl IntoIterator<Item = impl AsRef<BStr>>) -> Self {
+        let mut buf = BString::default();
+        for pattern in patterns {
+            buf.push_str(pattern.as_ref());
+            buf.push_byte(b'\n');
+        }
+        Patterns::NonCone(PatternList {
+            patterns: Ignore::bytes_to_patterns(&buf),
+            source: None,
+            base: None,
+        })
+    }
+
+    fn from_list(list: PatternList<Ignore>, cone: bool) -> Self {
+        match cone.then(|| Cone::from_patterns(&list)).flatten() {
+            Some(cone) => Patterns::Cone(cone),
+            None => Patterns::NonCone(list),
+        }
+    }
+
+    /// Return `true` if the file at `path`, relative to the root of the worktree, is included in the sparse checkout.
+    /// `case` determines if paths are compared case-insensitively.
+    pub fn is_included(&self, path: &BStr, case: Case) -> bool {
+        let list = match self {
+            Patterns::Cone(cone) => return cone.is_included(path, case),
+            Patterns::NonCone(list) => list,
+        };
+        let mut is_dir = false;
+        let mut path = path;
+        loop {
+            let basename_pos = path.rfind_byte(b'/').map(|pos| pos + 1);
+            if let Some(m) = list.pattern_matching_relative_path(path, basename_pos, Some(is_dir), case) {
+                return !m.pattern.is_negative();
+            }
+            match basename_pos {
+                Some(pos) => path = path[..pos - 1].as_bstr(),
+                None => return false,
+            }
+            is_dir = true;
+        }
+    }
+
+    /// Return `true` if none of the files within `dir` are included, which allows to represent it with a single directory
+    /// entry in a sparse index.
+    ///
+    /// Like `git`, this is only supported in cone mode, so it's always `false` otherwise.
+    pub fn excludes_directory(&self, dir: &BStr, case: Case) -> bool {
+        match self {
+            Patterns::Cone(cone) => cone.excludes_directory(dir, case),
+            Patterns::NonCone(_) => false,
+        }
+    }
+
+    /// Return the directories of a cone, or the patterns in non-cone mode, similar to `git sparse-checkout list`.
+    pub fn to_list(&self) -> Vec<BString> {
+        match self {
+            Patterns::Cone(cone) => cone.directories().map(ToOwned::to_owned).collect(),
+            Patterns::NonCone(list) => list
+                .patterns
+                .iter()
+                .map(|mapping| mapping.pattern.to_string().into())
+                .collect(),
+        }
+    }
+
+    /// Write the patterns in the format of the `.git/info/sparse-checkout` file to `out`.
+    ///
+    /// Cone mode patterns are written exactly like `git` does, so they can be read back as such.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        match self {
+            Patterns::Cone(cone) => {
+                out.write_all(b"/*\n!/*/\n")?;
+                for dir in cone.parents.iter().filter(|dir| {
+                    !std::iter::once(dir.as_bstr())
+                        .chain(parent_directories(dir.as_bstr()))
+                        .any(|dir| cone.recursive.contains(dir))
+                }) {
+                    let dir = escape(dir.as_bstr());
+                    writeln!(out, "/{dir}/\n!/{dir}/*/")?;
+                }
+                for dir in cone.directories() {
+                    writeln!(out, "/{}/", escape(dir))?;
+                }
+            }
+            Patterns::NonCone(list) => {
+                for mapping in &list.patterns {
+                    writeln!(out, "{}", mapping.pattern)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Set the [`SKIP_WORKTREE`][gix_index::entry::Flags::SKIP_WORKTREE] flag of all entries in `index` that aren't included
+/// by `patterns`, and remove it from all entries that are, with `case` determining if paths are compared case-insensitively.
+/// Return the amount of entries whose flag changed.
+///
+/// Conflicting entries are always included, and directory entries of a sparse index are left untouched, which is why
+/// they should be expanded first with [`expand_sparse_directories()`][gix_index::State::expand_sparse_directories()].
+pub fn apply(index: &mut gix_index::State, patterns: &Patterns, case: Case) -> usize {
+    let mut changed = 0;
+    for (entry, path) in index.entries_mut_with_paths() {
+        if entry.mode.is_sparse() {
+            continue;
+        }
+        let skip = entry.stage() == 0 && !patterns.is_included(path, case);
+        if entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE) != skip {
+            entry.flags.set(gix_index::entry::Flags::SKIP_WORKTREE, skip);
+            changed += 1;
+        }
+    }
+    changed
+}
+
+fn parent_directories(path: &BStr) -> impl Iterator<Item = &BStr> {
+    path.rfind_iter(b"/").map(move |pos| path[..pos].as_bstr())
+}
+
+fn contains(dirs: &BTreeSet<BString>, dir: &BStr, case: Case) -> bool {
+    match case {
+        Case::Sensitive => dirs.contains(dir),
+        Case::Fold => dirs.iter().any(|candidate| candidate.eq_ignore_ascii_case(dir)),
+    }
+}
+
+/// Remove escapes from `text`, or return `None` if it contains unescaped wildcards.
+fn unescape(text: &[u8]) -> Option<BString> {
+    let mut out = BString::default();
+    let mut bytes = text.iter();
+    while let Some(&b) = bytes.next() {
+        match b {
+            b'\\' => out.push(*bytes.next()?),
+            b'*' | b'?' | b'[' => return None,
+            _ => out.push(b),
+        }
+    }
+    Some(out)
+}
+
+fn escape(dir: &BStr) -> BString {
+    let mut out = BString::default();
+    for &b in dir.iter() {
+        if matches!(b, b'*' | b'?' | b'[' | b'\\') {
+            out.push(b'\\');
+        }
+        out.push(b);
+    }
+    out
+}
@@ -0,0 +1,375 @@
+use std::path::{Path, PathBuf};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use gix_hash::{oid, ObjectId};
+use gix_index::extension::untracked_cache::{self, Directory, OidStat};
+
+use crate::fs;
+
+/// The error returned by [`find()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("Could not determine if '{path}' is excluded")]
+    Excludes { path: BString, source: std::io::Error },
+}
+
+/// Options for use in [`find()`].
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// The identifier of the worktree as produced by [`identifier()`], which has to match the one stored in the untracked cache
+    /// for it to be used.
+    pub identifier: BString,
+    /// The path to the `.git/info/exclude` file, whose changes invalidate the entire untracked cache.
+    pub info_exclude: Option<PathBuf>,
+    /// The path to the file configured in `core.excludesFile`, whose changes invalidate the entire untracked cache.
+    pub excludes_file: Option<PathBuf>,
+    /// If `true`, directories of the untracked cache that weren't invalidated are trusted without checking if they changed
+    /// on disk, which requires that all changes reported by the filesystem monitor were applied to the index with
+    /// [`fsmonitor::apply()`][crate::fsmonitor::apply()] beforehand.
+    pub use_fs_monitor: bool,
+}
+
+/// The outcome of [`find()`].
+#[derive(Debug, Default, Clone)]
+pub struct Outcome {
+    /// Untracked files and directories relative to the root of the worktree in sorted order, with directories ending in a slash.
+    ///
+    /// Like `git status`, directories are only listed if they contain at least one file that isn't excluded, and files
+    /// within them aren't listed separately.
+    pub untracked: Vec<BString>,
+    /// The amount of directories which were read from disk.
+    pub directories_read: usize,
+    /// The amount of directories whose untracked entries were taken from the untracked cache.
+    pub directories_cached: usize,
+}
+
+/// Return the identifier `git` uses for the untracked cache of the worktree at `worktree_root`, which should be absolute.
+pub fn identifier(worktree_root: &Path) -> BString {
+    let system = match std::env::consts::OS {
+        "linux" => "Linux",
+        "macos" => "Darwin",
+        "windows" => "Windows",
+        "freebsd" => "FreeBSD",
+        "netbsd" => "NetBSD",
+        "openbsd" => "OpenBSD",
+        other => other,
+    };
+    let mut identifier: BString = format!("Location {}, system {system}", worktree_root.display()).into();
+    // `git` keeps the null-byte for backwards compatibility.
+    identifier.push_byte(0);
+    identifier
+}
+
+/// Find all untracked files and directories in the worktree of `index`, using `excludes` to skip those that are excluded and
+/// `find` to read `.gitignore` files from the object database.
+///
+/// If `index` has an [untracked cache][gix_index::State::untracked()] that was created for the worktree identified
+/// in `options`, directories that didn't change since the last traversal aren't read again, and the cache is updated
+/// with the result, just like `git` does.
+///
+/// `excludes` must be created with [`State::for_status()`][fs::cache::State::for_status()].
+pub fn find<Find, E>(
+    index: &mut gix_index::State,
+    excludes: &mut fs::Cache,
+    options: Options,
+    find: Find,
+) -> Result<Outcome, Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<gix_object::BlobRef<'a>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let object_hash = index.object_hash();
+    let mut untracked = index.set_untracked(None);
+    let cache = untracked.as_mut().filter(|cache| {
+        cache.identifier == options.identifier
+            && cache.dir_flags == untracked_cache::DIR_FLAGS
+            && cache.exclude_filename_per_dir == ".gitignore"
+    });
+    let use_cache = cache.is_some();
+    let mut old_directories = Vec::new();
+    if let Some(cache) = cache {
+        let info_exclude = oid_stat(options.info_exclude.as_deref(), object_hash)?;
+        let excludes_file = oid_stat(options.excludes_file.as_deref(), object_hash)?;
+        let is_unchanged = |a: &Option<OidStat>, b: &Option<OidStat>| {
+            a.as_ref().map(|s| (s.stat, s.id)) == b.as_ref().map(|s| (s.stat, s.id))
+        };
+        old_directories = std::mem::take(&mut cache.directories);
+        if !is_unchanged(&cache.info_exclude, &info_exclude) || !is_unchanged(&cache.excludes_file, &excludes_file) {
+            old_directories.clear();
+        }
+        cache.info_exclude = info_exclude;
+        cache.excludes_file = excludes_file;
+    }
+
+    let (res, new_directories, mut outcome) = {
+        let mut walk = Walk {
+            root: excludes.base().to_owned(),
+            index,
+            excludes,
+            find,
+            object_hash,
+            use_cache,
+            use_fs_monitor: options.use_fs_monitor,
+            old: &old_directories,
+            new: Vec::new(),
+            outcome: Outcome::default(),
+        };
+        let res = walk.directory(&mut BString::default(), (!old_directories.is_empty()).then_some(0));
+        (res, walk.new, walk.outcome)
+    };
+    if let Some(cache) = untracked.as_mut().filter(|_| use_cache) {
+        cache.directories = if res.is_ok() { new_directories } else { old_directories };
+    }
+    index.set_untracked(untracked);
+
+    res?;
+    outcome.untracked.sort();
+    Ok(outcome)
+}
+
+struct Walk<'a, Find> {
+    root: PathBuf,
+    index: &'a gix_index::State,
+    excludes: &'a mut fs::Cache,
+    find: Find,
+    object_hash: gix_hash::Kind,
+    use_cache: bool,
+    use_fs_monitor: bool,
+    old: &'a [Directory],
+    new: Vec<Directory>,
+    outcome: Outcome,
+}
+
+impl<'a, Find, E> Walk<'a, Find>
+where
+    Find: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Result<gix_object::BlobRef<'b>, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    /// Traverse the directory at `rela_dir`, using the cached directory at `old_idx` if it's still valid, and return
+    /// the index of its newly created cache entry, or `None` if it doesn't exist on disk.
+    fn directory(&mut self, rela_dir: &mut BString, old_idx: Option<usize>) -> Result<Option<usize>, Error> {
+        let path = self.root.join(gix_path::from_bstr(rela_dir.as_bstr()));
+        let old_directories = self.old;
+        let old = old_idx.map(|idx| &old_directories[idx]);
+        let trusted = old.filter(|old| self.use_cache && self.use_fs_monitor && old.stat.is_some() && !old.check_only);
+        let (stat, exclude_file_oid) = match trusted {
+            Some(old) => (old.stat.expect("checked"), old.exclude_file_oid),
+            None => {
+                let meta = match std::fs::symlink_metadata(&path) {
+                    Ok(meta) if meta.is_dir() => meta,
+                    Ok(_) => return Ok(None),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                    Err(err) => return Err(io_error(&path)(err)),
+                };
+                let stat = gix_index::entry::Stat::from_fs(&meta);
+                let exclude_file_oid = match std::fs::read(path.join(".gitignore")) {
+                    Ok(data) => Some(blob_id(&data, self.object_hash)),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(err) => return Err(io_error(&path.join(".gitignore"))(err)),
+                };
+                (stat, exclude_file_oid)
+            }
+        };
+
+        let is_valid = self.use_cache
+            && old.map_or(false, |old| {
+                old.stat == Some(stat) && old.exclude_file_oid == exclude_file_oid && !old.check_only
+            });
+        // Sub-directories are affected by our exclude file as well, so they can't be taken from the cache if it changed.
+        let old = old.filter(|old| old.exclude_file_oid == exclude_file_oid);
+        let idx = self.new.len();
+        self.new.push(Directory {
+            name: rela_dir
+                .rfind_byte(b'/')
+                .map_or(rela_dir.as_slice(), |pos| &rela_dir[pos + 1..])
+                .into(),
+            untracked_entries: Vec::new(),
+            sub_directories: Vec::new(),
+            stat: Some(stat),
+            exclude_file_oid,
+            check_only: false,
+        });
+
+        let (untracked_entries, sub_directories) = match old.filter(|_| is_valid) {
+            Some(old) => {
+                self.outcome.directories_cached += 1;
+                let sub_directories = old
+                    .sub_directories
+                    .iter()
+                    .map(|idx| old_directories[*idx].name.clone())
+                    .filter(|name| self.is_tracked_directory(join(rela_dir.as_bstr(), name.as_bstr()).as_bstr()))
+                    .collect();
+                (old.untracked_entries.clone(), sub_directories)
+            }
+            None => {
+                self.outcome.directories_read += 1;
+                self.read_directory(&path, rela_dir.as_bstr())?
+            }
+        };
+        self.outcome.untracked.extend(
+            untracked_entries
+                .iter()
+                .map(|name| join(rela_dir.as_bstr(), name.as_bstr())),
+        );
+        self.new[idx].untracked_entries = untracked_entries;
+
+        for name in sub_directories {
+            let old_sub_idx = old.and_then(|old| {
+                old.sub_directories
+                    .iter()
+                    .copied()
+                    .find(|idx| old_directories[*idx].name == name)
+            });
+            let prev_len = rela_dir.len();
+            if prev_len != 0 {
+                rela_dir.push_byte(b'/');
+            }
+            rela_dir.push_str(&name);
+            let sub_idx = self.directory(rela_dir, old_sub_idx);
+            rela_dir.truncate(prev_len);
+            if let Some(sub_idx) = sub_idx? {
+                self.new[idx].sub_directories.push(sub_idx);
+            }
+        }
+        Ok(Some(idx))
+    }
+
+    /// Read the directory at `path`, relative to the root at `rela_dir`, and return its untracked entries along with the names
+    /// of directories containing tracked files.
+    fn read_directory(&mut self, path: &Path, rela_dir: &BStr) -> Result<(Vec<BString>, Vec<BString>), Error> {
+        let mut untracked_entries = Vec::new();
+        let mut sub_directories = Vec::new();
+        for (name, is_dir) in sorted_directory_entries(path)? {
+            let rela_path = join(rela_dir, name.as_bstr());
+            if is_dir {
+                if self.is_tracked_directory(rela_path.as_bstr()) {
+                    sub_directories.push(name);
+                } else if !self.is_tracked(rela_path.as_bstr())
+                    && !self.is_excluded(rela_path.as_bstr(), true)?
+                    && self.contains_untracked_files(
+                        &path.join(gix_path::from_bstr(name.as_bstr())),
+                        rela_path.as_bstr(),
+                    )?
+                {
+                    let mut name = name;
+                    name.push_byte(b'/');
+                    untracked_entries.push(name);
+                }
+            } else if !self.is_tracked(rela_path.as_bstr()) && !self.is_excluded(rela_path.as_bstr(), false)? {
+                untracked_entries.push(name);
+            }
+        }
+        Ok((untracked_entries, sub_directories))
+    }
+
+    /// Return `true` if the untracked directory at `path` is a repository or contains at least one file that isn't excluded.
+    fn contains_untracked_files(&mut self, path: &Path, rela_dir: &BStr) -> Result<bool, Error> {
+        if path.join(".git").exists() {
+            return Ok(true);
+        }
+        for (name, is_dir) in sorted_directory_entries(path)? {
+            let rela_path = join(rela_dir, name.as_bstr());
+            if !self.is_excluded(rela_path.as_bstr(), is_dir)?
+                && (!is_dir
+                    || self.contains_untracked_files(
+                        &path.join(gix_path::from_bstr(name.as_bstr())),
+                        rela_path.as_bstr(),
+                    )?)
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn is_excluded(&mut self, rela_path: &BStr, is_dir: bool) -> Result<bool, Error> {
+        Ok(self
+            .excludes
+            .at_entry(rela_path, Some(is_dir), &mut self.find)
+            .map_err(|source| Error::Excludes {
+                path: rela_path.to_owned(),
+                source,
+            })?
+            .is_excluded())
+    }
+
+    fn is_tracked(&self, rela_path: &BStr) -> bool {
+        let entries = self.index.entries();
+        let pos = entries.partition_point(|entry| entry.path(self.index) < rela_path);
+        entries
+            .get(pos)
+            .map_or(false, |entry| entry.path(self.index) == rela_path)
+    }
+
+    fn is_tracked_directory(&self, rela_dir: &BStr) -> bool {
+        let mut prefix = rela_dir.to_owned();
+        prefix.push_byte(b'/');
+        let entries = self.index.entries();
+        let pos = entries.partition_point(|entry| entry.path(self.index) < prefix.as_bstr());
+        entries
+            .get(pos)
+            .map_or(false, |entry| entry.path(self.index).starts_with(prefix.as_slice()))
+    }
+}
+
+/// Return the names of all entries in the directory at `path` along with a flag telling if they are directories,
+/// without the `.git` directory.
+fn sorted_directory_entries(path: &Path) -> Result<Vec<(BString, bool)>, Error> {
+    let mut entries = std::fs::read_dir(path)
+        .and_then(|entries| {
+            entries
+                .map(|entry| {
+                    let entry = entry?;
+                    let name = gix_path::into_bstr(PathBuf::from(entry.file_name())).into_owned();
+                    Ok((name, entry.file_type()?.is_dir()))
+                })
+                .collect::<std::io::Result<Vec<_>>>()
+        })
+        .map_err(io_error(path))?;
+    entries.retain(|(name, _)| name != ".git");
+    entries.sort();
+    Ok(entries)
+}
+
+fn join(rela_dir: &BStr, name: &BStr) -> BString {
+    let mut path = rela_dir.to_owned();
+    if !path.is_empty() {
+        path.push_byte(b'/');
+    }
+    path.push_str(name);
+    path
+}
+
+fn oid_stat(path: Option<&Path>, object_hash: gix_hash::Kind) -> Result<Option<OidStat>, Error> {
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    let meta = match std::fs::metadata(path) {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(io_error(path)(err)),
+    };
+    let data = std::fs::read(path).map_err(io_error(path))?;
+    Ok(Some(OidStat {
+        stat: gix_index::entry::Stat::from_fs(&meta),
+        id: blob_id(&data, object_hash),
+    }))
+}
+
+fn blob_id(data: &[u8], object_hash: gix_hash::Kind) -> ObjectId {
+    let mut hasher = gix_features::hash::hasher(object_hash);
+    hasher.update(&gix_object::encode::loose_header(gix_object::Kind::Blob, data.len()));
+    hasher.update(data);
+    ObjectId::from(hasher.digest())
+}
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> Error + '_ {
+    move |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    }
+}
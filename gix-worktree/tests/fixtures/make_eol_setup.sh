@@ -0,0 +1,32 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config core.autocrlf false
+
+printf 'a\nb\n' > a.txt
+cp a.txt b.auto
+cp a.txt c.lf
+cp a.txt d.crlf
+cp a.txt e.bin
+cp a.txt plain
+printf 'a\r\nb\n' > mixed.auto
+printf '\xc3\xa4\n' > f.utf16
+printf '$Id$\n' > g.ident
+
+git add -A
+git commit -q -m "Content"
+
+# Attributes are added last to store content exactly as written above.
+cat <<EOF > .gitattributes
+*.txt text
+*.auto text=auto
+*.lf text eol=lf
+*.crlf eol=crlf
+*.bin binary
+*.utf16 -text working-tree-encoding=UTF-16
+*.ident ident
+EOF
+
+git add .gitattributes
+git commit -q -m "Attributes"
@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+cat <<EOF > .gitattributes
+*.rot13 filter=rot13
+*.process filter=process
+*.delay filter=process
+EOF
+mkdir dir
+echo "unset.rot13 -filter" > dir/.gitattributes
+
+echo -n "abc" > a.rot13
+echo -n "abc" > plain
+echo -n "hello" > dir/b.process
+echo -n "delayed" > dir/c.delay
+echo -n "abc" > dir/unset.rot13
+
+git add -A
+git commit -q -m "Commit"
@@ -0,0 +1,13 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+mkdir -p tracked/sub untracked/nested ignored empty only-ignored
+touch tracked/file tracked/sub/file
+git add tracked
+git commit -q -m "init"
+
+echo "*.o" > .gitignore
+echo "ignored/" >> .gitignore
+touch a.o new-file tracked/new tracked/sub/x.o untracked/nested/file ignored/file only-ignored/b.o
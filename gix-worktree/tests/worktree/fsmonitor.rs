@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use gix_index::{entry::Flags, extension::fs_monitor::Token};
+use gix_worktree::fsmonitor::{self, Changes, Outcome, Source};
+
+fn hook(dir: &Path, script: &str, version: Option<u8>) -> std::io::Result<Source> {
+    let path = dir.join("hook.sh");
+    std::fs::write(&path, script)?;
+    Ok(Source::Hook {
+        command: format!("sh {}", path.display()).into(),
+        version,
+    })
+}
+
+fn token(token: &str) -> Token {
+    Token::V2 { token: token.into() }
+}
+
+#[test]
+fn hooks_are_not_called_without_token() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let source = hook(dir.path(), "exit 1", None)?;
+    let outcome = fsmonitor::query(&source, dir.path(), None)?;
+    assert_eq!(outcome.changes, Changes::All, "without token, everything changed");
+    assert!(
+        matches!(&outcome.token, Token::V2 { token } if token.iter().all(u8::is_ascii_digit)),
+        "the current time is used as first token"
+    );
+    Ok(())
+}
+
+#[test]
+fn hook_version_2_provides_the_next_token() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let source = hook(
+        dir.path(),
+        r#"echo "$@" > invocation; printf 'next\0tracked/file\0tracked/sub/\0'"#,
+        None,
+    )?;
+    let outcome = fsmonitor::query(&source, dir.path(), Some(&token("previous")))?;
+    assert_eq!(
+        outcome,
+        Outcome {
+            token: token("next"),
+            changes: Changes::Paths(vec!["tracked/file".into(), "tracked/sub/".into()]),
+        }
+    );
+    assert_eq!(
+        std::fs::read(dir.path().join("invocation"))?,
+        b"2 previous\n",
+        "the hook runs in the worktree root with the version and token as arguments"
+    );
+
+    let source = hook(dir.path(), r#"printf 'next\0/\0'"#, Some(2))?;
+    let outcome = fsmonitor::query(&source, dir.path(), Some(&token("previous")))?;
+    assert_eq!(outcome.changes, Changes::All, "a single slash means everything changed");
+    Ok(())
+}
+
+#[test]
+fn hook_version_1_is_used_if_version_2_fails() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let source = hook(
+        dir.path(),
+        r#"test "$1" = 2 && exit 1; echo "$@" > invocation; printf 'tracked/new\0'"#,
+        None,
+    )?;
+    let outcome = fsmonitor::query(&source, dir.path(), Some(&Token::V1 { nanos_since_1970: 42 }))?;
+    assert_eq!(outcome.changes, Changes::Paths(vec!["tracked/new".into()]));
+    assert_ne!(
+        outcome.token,
+        token("42"),
+        "version 1 uses the time of the query as next token"
+    );
+    assert_eq!(std::fs::read(dir.path().join("invocation"))?, b"1 42\n");
+
+    let outcome = fsmonitor::query(&source, dir.path(), Some(&token("not-a-timestamp")))?;
+    assert_eq!(outcome.changes, Changes::All, "version 1 can only work with timestamps");
+
+    let source = hook(dir.path(), "exit 1", Some(1))?;
+    assert!(matches!(
+        fsmonitor::query(&source, dir.path(), Some(&token("42"))),
+        Err(fsmonitor::Error::HookFailed { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn apply_invalidates_changed_entries_and_stores_the_token() {
+    let worktree = crate::fixture_path("make_untracked_setup");
+    let mut index =
+        gix_index::File::at(worktree.join(".git/index"), gix_hash::Kind::Sha1, Default::default()).expect("valid");
+    for entry in index.entries_mut() {
+        entry.flags.insert(Flags::FSMONITOR_VALID);
+    }
+
+    fsmonitor::apply(
+        &mut index,
+        Outcome {
+            token: token("next"),
+            changes: Changes::Paths(vec!["tracked/file".into(), "tracked/sub/".into()]),
+        },
+    );
+    assert_eq!(
+        index
+            .entries()
+            .iter()
+            .map(|e| (e.path(&index).to_string(), e.flags.contains(Flags::FSMONITOR_VALID)))
+            .collect::<Vec<_>>(),
+        [
+            ("tracked/file".to_string(), false),
+            ("tracked/sub/file".to_string(), false)
+        ],
+        "directories invalidate all entries below them"
+    );
+    assert_eq!(index.fs_monitor().expect("created").token, token("next"));
+
+    for entry in index.entries_mut() {
+        entry.flags.insert(Flags::FSMONITOR_VALID);
+    }
+    fsmonitor::apply(
+        &mut index,
+        Outcome {
+            token: token("after-next"),
+            changes: Changes::Paths(vec!["tracked/sub".into()]),
+        },
+    );
+    assert_eq!(
+        index
+            .entries()
+            .iter()
+            .map(|e| e.flags.contains(Flags::FSMONITOR_VALID))
+            .collect::<Vec<_>>(),
+        [true, false],
+        "paths without trailing slash may be directories, too"
+    );
+
+    fsmonitor::apply(
+        &mut index,
+        Outcome {
+            token: token("last"),
+            changes: Changes::All,
+        },
+    );
+    assert!(index
+        .entries()
+        .iter()
+        .all(|e| !e.flags.contains(Flags::FSMONITOR_VALID)));
+    assert_eq!(index.fs_monitor().expect("still present").token, token("last"));
+}
@@ -321,6 +321,204 @@ fn collisions_are_detected_on_a_case_insensitive_filesystem() {
     }
 }
 
+#[test]
+fn paths_from_tree_overwrite_files_and_update_the_index() -> crate::Result {
+    let opts = opts_from_probe();
+    let (source_tree, destination, mut index, _outcome) =
+        checkout_index_in_tmp_dir(opts.clone(), "make_mixed_without_submodules")?;
+    let git_dir = source_tree.join(".git");
+    let odb = gix_odb::at(git_dir.join("objects"))?.into_inner().into_arc()?;
+    let tree = head_tree_as_index(&git_dir, &odb)?;
+
+    std::fs::write(
+        destination.path().join("dir/content"),
+        b"changed, and longer than the content in the tree",
+    )?;
+    std::fs::write(destination.path().join("executable"), b"changed")?;
+    std::fs::remove_file(destination.path().join("empty"))?;
+    index.remove_entries(|path, _entry| path == "empty");
+
+    let outcome = index::checkout::paths_from_tree(
+        &mut index,
+        &tree,
+        ["dir/content", "empty"],
+        destination.path(),
+        move |oid, buf| odb.find_blob(oid, buf),
+        &mut progress::Discard,
+        &mut progress::Discard,
+        opts,
+    )?;
+    assert_eq!(outcome.files_updated, 2);
+    assert!(outcome.collisions.is_empty());
+    assert!(outcome.errors.is_empty());
+
+    assert_eq!(std::fs::read(destination.path().join("dir/content"))?, b"other content");
+    assert_eq!(
+        std::fs::read(destination.path().join("executable"))?,
+        b"changed",
+        "paths that weren't selected are left alone"
+    );
+    assert!(
+        destination.path().join("empty").is_file(),
+        "missing files are recreated"
+    );
+
+    assert!(index.verify_entries().is_ok(), "added entries are sorted in");
+    let empty = index
+        .entry_by_path_and_stage("empty".into(), 0)
+        .expect("missing entries are added back");
+    assert_ne!(empty.stat.mtime.secs, 0, "stat information is updated");
+    let content = index.entry_by_path_and_stage("dir/content".into(), 0).expect("present");
+    assert_eq!(content.stat.size, 13, "stat information matches the written file");
+    Ok(())
+}
+
+#[test]
+fn paths_from_tree_must_match_an_entry() -> crate::Result {
+    let opts = opts_from_probe();
+    let (source_tree, destination, mut index, _outcome) =
+        checkout_index_in_tmp_dir(opts.clone(), "make_mixed_without_submodules")?;
+    let git_dir = source_tree.join(".git");
+    let odb = gix_odb::at(git_dir.join("objects"))?.into_inner().into_arc()?;
+    let tree = head_tree_as_index(&git_dir, &odb)?;
+
+    let err = index::checkout::paths_from_tree(
+        &mut index,
+        &tree,
+        ["does-not-exist"],
+        destination.path(),
+        move |oid, buf| odb.find_blob(oid, buf),
+        &mut progress::Discard,
+        &mut progress::Discard,
+        opts,
+    )
+    .err()
+    .expect("paths must be in the tree");
+    assert!(matches!(err, index::checkout::Error::PathNotInTree { .. }));
+    Ok(())
+}
+
+#[test]
+fn filters_are_applied_according_to_attributes() -> crate::Result {
+    let process = Path::new("..")
+        .join("gix-filter")
+        .join("tests")
+        .join("fixtures")
+        .join("process-filter.sh");
+    let driver = |name: &str| gix_filter::Driver {
+        name: name.into(),
+        clean: None,
+        smudge: None,
+        process: None,
+        required: true,
+    };
+    let opts = index::checkout::Options {
+        filter_drivers: vec![
+            gix_filter::Driver {
+                smudge: Some("tr a-z n-za-m".into()),
+                ..driver("rot13")
+            },
+            gix_filter::Driver {
+                process: Some(format!("bash '{}'", process.display()).into()),
+                ..driver("process")
+            },
+        ],
+        thread_limit: Some(1),
+        ..opts_from_probe()
+    };
+    let (_source_tree, destination, index, outcome) = checkout_index_in_tmp_dir(opts, "make_filter_setup")?;
+    assert_eq!(outcome.files_updated, 7);
+    assert!(outcome.errors.is_empty());
+
+    let read = |path: &str| std::fs::read(destination.path().join(path));
+    assert_eq!(read("a.rot13")?, b"nop", "one-shot filters are run");
+    assert_eq!(read("plain")?, b"abc", "files without filter are unchanged");
+    assert_eq!(read("dir/b.process")?, b"uryyb", "filter processes are used");
+    assert_eq!(
+        read("dir/c.delay")?,
+        b"qrynlrq",
+        "delayed content is written in the end"
+    );
+    assert_eq!(
+        read("dir/unset.rot13")?,
+        b"abc",
+        "attributes of nested .gitattributes files are read from the index"
+    );
+
+    let delayed = index.entry_by_path_and_stage("dir/c.delay".into(), 0).expect("present");
+    assert_ne!(
+        delayed.stat.mtime.secs, 0,
+        "stat information of delayed files is updated"
+    );
+    Ok(())
+}
+
+#[test]
+fn line_endings_encodings_and_idents_are_converted_according_to_attributes() -> crate::Result {
+    let opts = index::checkout::Options {
+        eol_config: gix_filter::eol::Configuration {
+            auto_crlf: gix_filter::eol::AutoCrlf::Enabled,
+            eol: None,
+        },
+        ..opts_from_probe()
+    };
+    let (_source_tree, destination, _index, outcome) = checkout_index_in_tmp_dir(opts, "make_eol_setup")?;
+    assert_eq!(outcome.files_updated, 10);
+    assert!(outcome.errors.is_empty());
+
+    let read = |path: &str| std::fs::read(destination.path().join(path));
+    for (path, expected, reason) in [
+        ("a.txt", &b"a\r\nb\r\n"[..], "text uses core.autocrlf"),
+        ("b.auto", b"a\r\nb\r\n", "auto-detected text is converted"),
+        ("mixed.auto", b"a\r\nb\n", "auto-detected text with CRLF is left alone"),
+        ("plain", b"a\r\nb\r\n", "core.autocrlf applies without attributes"),
+        ("c.lf", b"a\nb\n", "eol=lf overrides core.autocrlf"),
+        ("d.crlf", b"a\r\nb\r\n", "eol=crlf"),
+        ("e.bin", b"a\nb\n", "the binary macro unsets text"),
+        ("f.utf16", b"\xff\xfe\xe4\x00\n\x00", "working-tree-encoding"),
+        (
+            "g.ident",
+            b"$Id: 055c8729cdcc372500a08db659c045e16c4409fb $\r\n",
+            "ident is expanded before line endings are converted",
+        ),
+    ] {
+        assert_eq!(read(path)?.as_bstr(), expected.as_bstr(), "{path}: {reason}");
+    }
+    Ok(())
+}
+
+#[test]
+fn sparse_checkout_patterns_determine_which_entries_are_written() -> crate::Result {
+    let opts = index::checkout::Options {
+        sparse_checkout: Some(gix_worktree::sparse::Patterns::non_cone(["dir/"])),
+        ..opts_from_probe()
+    };
+    let (_source_tree, destination, index, outcome) = checkout_index_in_tmp_dir(opts, "make_mixed_without_submodules")?;
+    assert_eq!(outcome.files_updated, 2);
+    assert_eq!(
+        stripped_prefix(&destination, &dir_structure(&destination)),
+        paths(["dir/content", "dir/sub-dir/symlink"]),
+    );
+    for (entry, path) in index.entries().iter().map(|entry| (entry, entry.path(&index))) {
+        assert_eq!(
+            entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE),
+            !path.starts_with(b"dir/"),
+            "{path}: the skip-worktree flag is set for excluded entries"
+        );
+    }
+    Ok(())
+}
+
+fn head_tree_as_index(git_dir: &Path, odb: &impl gix_odb::Find) -> crate::Result<gix_index::State> {
+    let head = std::fs::read_to_string(git_dir.join("HEAD"))?;
+    let head_ref = head.trim().strip_prefix("ref: ").expect("HEAD points to a branch");
+    let commit_id = gix_hash::ObjectId::from_hex(std::fs::read(git_dir.join(head_ref))?.trim())?;
+    let tree_id = odb.find_commit(commit_id, &mut Vec::new())?.tree();
+    Ok(gix_index::State::from_tree(&tree_id, |oid, buf| {
+        odb.find_tree_iter(oid, buf).ok()
+    })?)
+}
+
 fn multi_threaded() -> bool {
     gix_features::parallel::num_threads(None) > 1
 }
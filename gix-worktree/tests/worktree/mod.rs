@@ -1,5 +1,8 @@
 mod fs;
+mod fsmonitor;
 mod index;
+mod sparse;
+mod untracked;
 
 use std::path::{Path, PathBuf};
 
@@ -0,0 +1,133 @@
+use bstr::ByteSlice;
+use gix_glob::pattern::Case;
+use gix_worktree::sparse::{Cone, Patterns};
+
+fn write(patterns: &Patterns) -> String {
+    let mut buf = Vec::new();
+    patterns.write_to(&mut buf).expect("in-memory writes work");
+    String::from_utf8(buf).expect("valid UTF-8")
+}
+
+fn included<'a>(patterns: &Patterns, paths: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    paths
+        .into_iter()
+        .filter(|path| patterns.is_included(path.as_bytes().as_bstr(), Case::Sensitive))
+        .collect()
+}
+
+const PATHS: [&str; 6] = ["r", "a/1", "a/b/2", "a/b/c/3", "d/4", "e*f/5"];
+
+mod cone {
+    use gix_glob::pattern::Case;
+    use gix_worktree::sparse::{Cone, Patterns};
+
+    use super::{included, write, PATHS};
+
+    #[test]
+    fn parent_directories_include_their_files() {
+        let patterns = Patterns::Cone(Cone::from_directories(["a/b/c", "/d/"]));
+        assert_eq!(
+            write(&patterns),
+            "/*\n!/*/\n/a/\n!/a/*/\n/a/b/\n!/a/b/*/\n/a/b/c/\n/d/\n",
+            "the same as written by `git sparse-checkout set a/b/c d`"
+        );
+        assert_eq!(included(&patterns, PATHS), ["r", "a/1", "a/b/2", "a/b/c/3", "d/4"]);
+        assert_eq!(patterns.to_list(), ["a/b/c", "d"]);
+        assert_eq!(
+            ["a", "a/b", "a/b/c", "a/b/c/x", "a/x", "a/b/x", "d/x", "e*f"]
+                .into_iter()
+                .filter(|dir| patterns.excludes_directory((*dir).into(), Case::Sensitive))
+                .collect::<Vec<_>>(),
+            ["a/x", "a/b/x", "e*f"],
+            "only directories without included files can be represented by a single entry in a sparse index"
+        );
+    }
+
+    #[test]
+    fn nested_directories_are_merged_and_special_characters_escaped() {
+        let patterns = Patterns::Cone(Cone::from_directories(["a", "a/b/c", "e*f"]));
+        assert_eq!(write(&patterns), "/*\n!/*/\n/a/\n/e\\*f/\n");
+        assert_eq!(included(&patterns, PATHS), ["r", "a/1", "a/b/2", "a/b/c/3", "e*f/5"]);
+        assert_eq!(patterns.to_list(), ["a", "e*f"]);
+    }
+
+    #[test]
+    fn written_patterns_can_be_read_back() {
+        for dirs in [&["a/b/c", "d"][..], &["a", "e*f"], &[]] {
+            let patterns = Patterns::Cone(Cone::from_directories(dirs));
+            assert_eq!(
+                Patterns::from_bytes(write(&patterns).as_bytes(), "sparse-checkout", true),
+                patterns,
+                "{dirs:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn other_patterns_fall_back_to_non_cone_mode() {
+        for input in [
+            "/*\n!/*/\n/a/*.txt\n",
+            "/*\n!/*/\n!/a/*/\n",
+            "/*\n!/*/\n/a/**/\n",
+            "*.md\n",
+        ] {
+            assert!(
+                matches!(
+                    Patterns::from_bytes(input.as_bytes(), "sparse-checkout", true),
+                    Patterns::NonCone(_)
+                ),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn case_folding() {
+        let patterns = Patterns::Cone(Cone::from_directories(["A"]));
+        assert!(!patterns.is_included("a/1".into(), Case::Sensitive));
+        assert!(patterns.is_included("a/1".into(), Case::Fold));
+    }
+}
+
+#[test]
+fn non_cone_patterns_include_matches_and_their_content() {
+    let patterns = Patterns::non_cone(["a/b/", "!a/b/c/", "*.md"]);
+    assert_eq!(write(&patterns), "a/b/\n!a/b/c/\n*.md\n");
+    assert_eq!(
+        included(&patterns, PATHS.into_iter().chain(Some("d/e/README.md"))),
+        ["a/b/2", "d/e/README.md"],
+        "like `git sparse-checkout set --no-cone 'a/b/' '!a/b/c/' '*.md'`"
+    );
+    assert_eq!(patterns.to_list(), ["a/b/", "!a/b/c/", "*.md"]);
+    assert_eq!(
+        Patterns::from_bytes(b"# comment\na/b/\n", "sparse-checkout", false).to_list(),
+        ["a/b/"]
+    );
+}
+
+#[test]
+fn apply_sets_and_clears_skip_worktree_flags() -> crate::Result {
+    let git_dir = crate::fixture_path("make_mixed_without_submodules").join(".git");
+    let mut index = gix_index::File::at(git_dir.join("index"), gix_hash::Kind::Sha1, Default::default())?;
+    let skipped = |index: &gix_index::File| {
+        index
+            .entries()
+            .iter()
+            .filter(|entry| entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE))
+            .map(|entry| entry.path(index).to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let changed = gix_worktree::sparse::apply(&mut index, &Patterns::non_cone(["dir/"]), Case::Sensitive);
+    assert_eq!(changed, 2);
+    assert_eq!(skipped(&index), ["empty", "executable"]);
+
+    let changed = gix_worktree::sparse::apply(
+        &mut index,
+        &Patterns::Cone(Cone::from_directories(["dir/sub-dir"])),
+        Case::Sensitive,
+    );
+    assert_eq!(changed, 2, "root files are always included in cone mode");
+    assert!(skipped(&index).is_empty());
+    Ok(())
+}
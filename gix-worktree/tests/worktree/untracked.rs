@@ -0,0 +1,185 @@
+use std::path::Path;
+
+use gix_glob::pattern::Case;
+use gix_index::extension::UntrackedCache;
+use gix_odb::FindExt;
+use gix_worktree::{fs, fsmonitor, untracked};
+
+fn find_untracked(
+    worktree: &Path,
+    index: &mut gix_index::State,
+    odb: &gix_odb::Handle,
+    use_fs_monitor: bool,
+) -> Result<untracked::Outcome, untracked::Error> {
+    let git_dir = worktree.join(".git");
+    let mut buf = Vec::new();
+    let state = fs::cache::State::for_status(fs::cache::state::Ignore::new(
+        Default::default(),
+        gix_attributes::MatchGroup::from_git_dir(&git_dir, None, &mut buf).expect("valid excludes"),
+        None,
+        Case::Sensitive,
+    ));
+    let attribute_files_in_index = state.build_attribute_list(index, index.path_backing(), Case::Sensitive);
+    let mut excludes = fs::Cache::new(worktree, state, Case::Sensitive, buf, attribute_files_in_index);
+    untracked::find(
+        index,
+        &mut excludes,
+        untracked::Options {
+            identifier: untracked::identifier(worktree),
+            info_exclude: Some(git_dir.join("info").join("exclude")),
+            excludes_file: None,
+            use_fs_monitor,
+        },
+        |oid, buf| odb.find_blob(oid, buf),
+    )
+}
+
+#[test]
+fn untracked_files_and_directories_are_found_like_git_does() -> crate::Result {
+    let worktree = crate::fixture_path("make_untracked_setup");
+    let mut index = gix_index::File::at(worktree.join(".git/index"), gix_hash::Kind::Sha1, Default::default())?;
+    let odb = gix_odb::at(worktree.join(".git/objects"))?;
+
+    let outcome = find_untracked(&worktree, &mut index, &odb, false)?;
+    assert_eq!(
+        outcome.untracked,
+        [".gitignore", "new-file", "tracked/new", "untracked/"],
+        "like `git status --porcelain`, excluded files and directories without untracked files aren't listed"
+    );
+    assert_eq!(outcome.directories_read, 3, "the root and both tracked directories");
+    assert_eq!(outcome.directories_cached, 0);
+    assert!(index.untracked().is_none(), "no cache is created on the fly");
+    Ok(())
+}
+
+#[test]
+fn untracked_cache_avoids_reading_unchanged_directories() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("make_untracked_setup.sh")?;
+    let worktree = gix_path::realpath(tmp.path())?;
+    let index_path = worktree.join(".git/index");
+    let mut index = gix_index::File::at(&index_path, gix_hash::Kind::Sha1, Default::default())?;
+    let odb = gix_odb::at(worktree.join(".git/objects"))?;
+    index.set_untracked(Some(UntrackedCache::new(
+        untracked::identifier(&worktree),
+        ".gitignore",
+    )));
+
+    let outcome = find_untracked(&worktree, &mut index, &odb, false)?;
+    assert_eq!(outcome.directories_read, 3);
+    assert_eq!(outcome.directories_cached, 0);
+    let expected = outcome.untracked;
+    assert_eq!(
+        index.untracked().expect("still present").directories.len(),
+        3,
+        "the cache was updated with all directories that were read"
+    );
+
+    index.write(Default::default())?;
+    let mut index = gix_index::File::at(&index_path, gix_hash::Kind::Sha1, Default::default())?;
+    let outcome = find_untracked(&worktree, &mut index, &odb, false)?;
+    assert_eq!(outcome.untracked, expected, "the result is the same");
+    assert_eq!(outcome.directories_read, 0, "nothing changed, so no directory is read");
+    assert_eq!(outcome.directories_cached, 3);
+
+    // Assure the modification time of the directory actually changes on filesystems with coarse timestamps.
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    std::fs::write(worktree.join("tracked/sub/new"), b"")?;
+    let outcome = find_untracked(&worktree, &mut index, &odb, false)?;
+    assert_eq!(
+        outcome.untracked,
+        [".gitignore", "new-file", "tracked/new", "tracked/sub/new", "untracked/"]
+    );
+    assert_eq!(outcome.directories_read, 1, "only the changed directory is read");
+    assert_eq!(outcome.directories_cached, 2);
+
+    index.remove_entries(|path, _| path == "tracked/file");
+    let outcome = find_untracked(&worktree, &mut index, &odb, false)?;
+    assert_eq!(
+        outcome.untracked,
+        [
+            ".gitignore",
+            "new-file",
+            "tracked/file",
+            "tracked/new",
+            "tracked/sub/new",
+            "untracked/"
+        ],
+        "removing entries invalidates the cache for all leading directories"
+    );
+    assert_eq!(outcome.directories_read, 2);
+    assert_eq!(outcome.directories_cached, 1);
+
+    std::fs::write(worktree.join(".git/info/exclude"), b"new-file\n")?;
+    let outcome = find_untracked(&worktree, &mut index, &odb, false)?;
+    assert_eq!(
+        outcome.untracked,
+        [
+            ".gitignore",
+            "tracked/file",
+            "tracked/new",
+            "tracked/sub/new",
+            "untracked/"
+        ]
+    );
+    assert_eq!(
+        outcome.directories_read, 3,
+        "changes to global exclude files invalidate the entire cache"
+    );
+
+    std::fs::write(worktree.join(".gitignore"), b"*.o\nignored/\nnew\n")?;
+    let outcome = find_untracked(&worktree, &mut index, &odb, false)?;
+    assert_eq!(outcome.untracked, [".gitignore", "tracked/file", "untracked/"]);
+    assert_eq!(
+        outcome.directories_read, 3,
+        "changes to an exclude file invalidate all directories below it"
+    );
+    Ok(())
+}
+
+#[test]
+fn untracked_cache_is_trusted_with_fs_monitor() -> crate::Result {
+    let tmp = gix_testtools::scripted_fixture_writable("make_untracked_setup.sh")?;
+    let worktree = gix_path::realpath(tmp.path())?;
+    let mut index = gix_index::File::at(worktree.join(".git/index"), gix_hash::Kind::Sha1, Default::default())?;
+    let odb = gix_odb::at(worktree.join(".git/objects"))?;
+    index.set_untracked(Some(UntrackedCache::new(
+        untracked::identifier(&worktree),
+        ".gitignore",
+    )));
+    let expected = find_untracked(&worktree, &mut index, &odb, true)?.untracked;
+
+    let token = |token: &str| gix_index::extension::fs_monitor::Token::V2 { token: token.into() };
+    fsmonitor::apply(
+        &mut index,
+        fsmonitor::Outcome {
+            token: token("1"),
+            changes: fsmonitor::Changes::Paths(Vec::new()),
+        },
+    );
+    std::fs::write(worktree.join("tracked/sub/new"), b"")?;
+    let outcome = find_untracked(&worktree, &mut index, &odb, true)?;
+    assert_eq!(outcome.directories_read, 0, "no directory is even checked for changes");
+    assert_eq!(
+        outcome.untracked, expected,
+        "changes that aren't reported by the filesystem monitor aren't seen"
+    );
+
+    fsmonitor::apply(
+        &mut index,
+        fsmonitor::Outcome {
+            token: token("2"),
+            changes: fsmonitor::Changes::Paths(vec!["tracked/sub/new".into()]),
+        },
+    );
+    let outcome = find_untracked(&worktree, &mut index, &odb, true)?;
+    assert_eq!(
+        outcome.untracked,
+        [".gitignore", "new-file", "tracked/new", "tracked/sub/new", "untracked/"]
+    );
+    assert_eq!(
+        outcome.directories_read, 3,
+        "all directories leading to the changed path are read again"
+    );
+    assert_eq!(index.fs_monitor().expect("created").token, token("2"));
+    Ok(())
+}
@@ -0,0 +1,45 @@
+//! Write trees into `tar`, `tar.gz` and `zip` archives like `git archive` does.
+pub use gix_archive::{Format, Outcome};
+
+use crate::bstr::BString;
+
+/// Options for use in [`Repository::write_archive()`][crate::Repository::write_archive()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The format of the archive.
+    pub format: Format,
+    /// A path to prepend to the path of all entries, like `project-1.0/` to put all of them into a directory.
+    pub tree_prefix: Option<BString>,
+    /// The modification time of all entries in seconds since epoch, or `None` to use the committer time if a commit
+    /// is archived, or the current time otherwise.
+    pub modification_time: Option<u32>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            format: Format::Tar,
+            tree_prefix: None,
+            modification_time: None,
+        }
+    }
+}
+
+/// The error returned by [`Repository::write_archive()`][crate::Repository::write_archive()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error("Object {id} is a {kind} which can't be archived as it doesn't point to a tree")]
+    NotATreeish {
+        id: gix_hash::ObjectId,
+        kind: gix_object::Kind,
+    },
+    #[error(transparent)]
+    AttributeGlobals(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    Write(#[from] gix_archive::write::Error),
+}
@@ -0,0 +1,359 @@
+//! Find the commit that introduced a change by binary search through history, similar to `git bisect`.
+use std::{
+    collections::{BinaryHeap, HashMap, HashSet},
+    io::Write,
+    path::PathBuf,
+};
+
+use gix_hash::ObjectId;
+use gix_ref::transaction::{Change, PreviousValue, RefEdit, RefLog};
+
+use crate::{bstr::BString, Repository};
+
+/// The prefix of all references that store the marks of an ongoing bisection.
+const REF_PREFIX: &str = "refs/bisect/";
+
+/// All files that git may write into the git directory during bisection.
+const STATE_FILES: &[&str] = &[
+    "BISECT_START",
+    "BISECT_LOG",
+    "BISECT_TERMS",
+    "BISECT_NAMES",
+    "BISECT_EXPECTED_REV",
+    "BISECT_ANCESTORS_OK",
+    "BISECT_RUN",
+    "BISECT_HEAD",
+];
+
+/// How a commit was judged during bisection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    /// The commit doesn't have the change that is searched for.
+    Good,
+    /// The commit has the change that is searched for, as do all of its descendants.
+    Bad,
+    /// The commit can't be tested, so its neighbours should be tested instead.
+    Skip,
+}
+
+impl Mark {
+    /// Return the name of the mark as used by git in `BISECT_LOG` and in the names of references.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Mark::Good => "good",
+            Mark::Bad => "bad",
+            Mark::Skip => "skip",
+        }
+    }
+}
+
+/// What to do next during bisection, as returned by [`Platform::next()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Next {
+    /// A commit needs to be marked as [bad][Mark::Bad] before candidates can be computed.
+    NeedsBad,
+    /// At least one commit needs to be marked as [good][Mark::Good] before candidates can be computed.
+    NeedsGood,
+    /// Test the commit with `id` and [mark][Platform::mark()] it accordingly.
+    Candidate {
+        /// The commit to test next, chosen so that marking it halves the amount of remaining commits.
+        id: ObjectId,
+        /// The amount of commits that may still be the first bad commit, including the bad commit itself.
+        num_remaining: usize,
+    },
+    /// The first bad commit was found.
+    FirstBad(ObjectId),
+    /// Only skipped commits are left to test, and the first bad commit is any of `candidates`, which includes the bad commit.
+    OnlySkippedLeft {
+        /// All commits that may be the first bad commit.
+        candidates: Vec<ObjectId>,
+    },
+}
+
+/// The error returned by methods on [`Platform`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("A bisection is already in progress")]
+    AlreadyInProgress,
+    #[error("No bisection is in progress")]
+    NotInProgress,
+    #[error("The bad commit {bad} is an ancestor of a good commit")]
+    BadIsAncestorOfGood { bad: ObjectId },
+    #[error("Could not access bisection state file at '{}'", path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+    #[error(transparent)]
+    HeadName(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    HeadId(#[from] crate::reference::head_id::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    PackedRefsOpen(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    IterReferences(#[from] crate::reference::iter::init::Error),
+    #[error(transparent)]
+    DecodeReference(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::object::commit::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+}
+
+/// A platform to drive a bisection, stored in the git directory in the same way as git does, returned by
+/// [`Repository::bisect()`][crate::Repository::bisect()].
+///
+/// Note that unlike `git bisect`, no commit is ever checked out, which is left to the caller.
+pub struct Platform<'repo> {
+    pub(crate) repo: &'repo Repository,
+}
+
+impl<'repo> Platform<'repo> {
+    /// Return `true` if a bisection was started and not yet [reset][Self::reset()].
+    pub fn is_in_progress(&self) -> bool {
+        self.state_path("BISECT_START").is_file()
+    }
+
+    /// Start a new bisection by remembering the current `HEAD` and creating `BISECT_LOG`, or fail if one
+    /// is already in progress.
+    pub fn start(&self) -> Result<(), Error> {
+        if self.is_in_progress() {
+            return Err(Error::AlreadyInProgress);
+        }
+        let mut head = match self.repo.head_name()? {
+            Some(name) => name.shorten().to_owned(),
+            None => self.repo.head_id()?.to_hex().to_string().into(),
+        };
+        head.push(b'\n');
+        self.write_state("BISECT_START", &head)?;
+        self.write_state("BISECT_TERMS", b"bad\ngood\n")?;
+        self.write_state("BISECT_NAMES", b"\n")?;
+        self.write_state("BISECT_LOG", b"git bisect start\n")
+    }
+
+    /// Mark the commit with `id` as `mark`, record it in `BISECT_LOG` and return what to do [next][Self::next()].
+    ///
+    /// Marking a commit as [bad][Mark::Bad] replaces the previous bad commit.
+    pub fn mark(&self, id: impl Into<ObjectId>, mark: Mark) -> Result<Next, Error> {
+        if !self.is_in_progress() {
+            return Err(Error::NotInProgress);
+        }
+        let id = id.into();
+        let commit = self.repo.find_object(id)?.try_into_commit()?;
+        let name = match mark {
+            Mark::Bad => format!("{REF_PREFIX}bad"),
+            Mark::Good | Mark::Skip => format!("{REF_PREFIX}{}-{}", mark.as_str(), id.to_hex()),
+        };
+        self.repo.reference(name, id, PreviousValue::Any, "")?;
+
+        let mut entry = Vec::<u8>::new();
+        writeln!(entry, "# {}: [{}] {}", mark.as_str(), id, commit.message()?.summary())
+            .and_then(|_| writeln!(entry, "git bisect {} {}", mark.as_str(), id))
+            .expect("writing to memory can't fail");
+        self.append_log(&entry)?;
+        self.next()
+    }
+
+    /// Compute what to do next, which typically is to test the commit that splits the commits between the bad commit and all
+    /// good commits most evenly, like git does.
+    ///
+    /// ### Deviation
+    ///
+    /// Git checks if the merge-bases of the good commits and the bad commit need testing, which isn't done here.
+    pub fn next(&self) -> Result<Next, Error> {
+        if !self.is_in_progress() {
+            return Err(Error::NotInProgress);
+        }
+        let (mut bad, mut good, mut skipped) = (None, Vec::new(), HashSet::new());
+        for reference in self.repo.references()?.prefixed(REF_PREFIX)? {
+            let reference = reference?;
+            let name = reference.name().as_bstr().to_string();
+            let id = reference.into_fully_peeled_id()?.detach();
+            match name.strip_prefix(REF_PREFIX) {
+                Some("bad") => bad = Some(id),
+                Some(name) if name.starts_with("good-") => good.push(id),
+                Some(name) if name.starts_with("skip-") => {
+                    skipped.insert(id);
+                }
+                _ => {}
+            }
+        }
+        let bad = match bad {
+            Some(bad) => bad,
+            None => return Ok(Next::NeedsBad),
+        };
+        if good.is_empty() {
+            return Ok(Next::NeedsGood);
+        }
+        self.next_candidate(bad, good, &skipped)
+    }
+
+    /// Stop the bisection by removing all of its references and state files, and return the name of the branch or the
+    /// commit that `HEAD` pointed to when it was [started][Self::start()], so the caller can restore it.
+    pub fn reset(&self) -> Result<BString, Error> {
+        if !self.is_in_progress() {
+            return Err(Error::NotInProgress);
+        }
+        let path = self.state_path("BISECT_START");
+        let mut head: BString = std::fs::read(&path)
+            .map_err(|source| Error::Io { source, path })?
+            .into();
+        if head.last() == Some(&b'\n') {
+            head.pop();
+        }
+
+        let mut edits = Vec::new();
+        for reference in self.repo.references()?.prefixed(REF_PREFIX)? {
+            edits.push(RefEdit {
+                change: Change::Delete {
+                    expected: PreviousValue::Any,
+                    log: RefLog::AndReference,
+                },
+                name: reference?.detach().name,
+                deref: false,
+            });
+        }
+        self.repo.edit_references(edits)?;
+
+        for name in STATE_FILES {
+            let path = self.state_path(name);
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(source) => return Err(Error::Io { source, path }),
+            }
+        }
+        Ok(head)
+    }
+}
+
+impl<'repo> Platform<'repo> {
+    fn state_path(&self, name: &str) -> PathBuf {
+        self.repo.path().join(name)
+    }
+
+    fn write_state(&self, name: &str, data: &[u8]) -> Result<(), Error> {
+        let path = self.state_path(name);
+        std::fs::write(&path, data).map_err(|source| Error::Io { source, path })
+    }
+
+    fn append_log(&self, data: &[u8]) -> Result<(), Error> {
+        let path = self.state_path("BISECT_LOG");
+        std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(data))
+            .map_err(|source| Error::Io { source, path })
+    }
+
+    /// Find the commit among all commits reachable from `bad` but not from any of `good` that has the amount of its ancestors
+    /// closest to half of all of these commits, skipping commits in `skipped`.
+    fn next_candidate(&self, bad: ObjectId, good: Vec<ObjectId>, skipped: &HashSet<ObjectId>) -> Result<Next, Error> {
+        let mut reachable_from_good = HashSet::new();
+        let mut stack = good;
+        while let Some(id) = stack.pop() {
+            if reachable_from_good.insert(id) {
+                let commit = self.repo.find_object(id)?.try_into_commit()?;
+                stack.extend(commit.parent_ids().map(|id| id.detach()));
+            }
+        }
+        if reachable_from_good.contains(&bad) {
+            return Err(Error::BadIsAncestorOfGood { bad });
+        }
+
+        // Collect all candidates from newest to oldest, which is the order in which git considers them.
+        let mut candidates = Vec::new();
+        let mut parents_of = HashMap::<ObjectId, Vec<ObjectId>>::new();
+        let mut queue = BinaryHeap::new();
+        let commit = self.repo.find_object(bad)?.try_into_commit()?;
+        queue.push((commit.time()?.seconds_since_unix_epoch, bad));
+        while let Some((_time, id)) = queue.pop() {
+            if parents_of.contains_key(&id) {
+                continue;
+            }
+            let commit = self.repo.find_object(id)?.try_into_commit()?;
+            let mut parents = Vec::new();
+            for parent_id in commit.parent_ids() {
+                let parent_id = parent_id.detach();
+                if reachable_from_good.contains(&parent_id) || parents.contains(&parent_id) {
+                    continue;
+                }
+                let parent = self.repo.find_object(parent_id)?.try_into_commit()?;
+                queue.push((parent.time()?.seconds_since_unix_epoch, parent_id));
+                parents.push(parent_id);
+            }
+            parents_of.insert(id, parents);
+            candidates.push(id);
+        }
+
+        let num_remaining = candidates.len();
+        if num_remaining == 1 {
+            return Ok(Next::FirstBad(bad));
+        }
+
+        let weights = ancestor_counts(&candidates, &parents_of);
+        let best = candidates
+            .iter()
+            .filter(|id| **id != bad && !skipped.contains(*id))
+            .fold(None, |best: Option<(ObjectId, usize)>, id| {
+                let weight = weights[id];
+                let score = weight.min(num_remaining - weight);
+                match best {
+                    Some((_, best_score)) if best_score >= score => best,
+                    _ => Some((*id, score)),
+                }
+            });
+        Ok(match best {
+            Some((id, _score)) => Next::Candidate { id, num_remaining },
+            None => Next::OnlySkippedLeft { candidates },
+        })
+    }
+}
+
+/// Return the amount of commits reachable from each of `candidates`, including itself, when only following `parents_of` each
+/// commit within the set of candidates.
+///
+/// Commits with a single parent simply add one to the count of their parent, and only merges require a traversal.
+fn ancestor_counts(candidates: &[ObjectId], parents_of: &HashMap<ObjectId, Vec<ObjectId>>) -> HashMap<ObjectId, usize> {
+    let mut counts = HashMap::<ObjectId, usize>::with_capacity(candidates.len());
+    let mut stack: Vec<(ObjectId, bool)> = candidates.iter().rev().map(|id| (*id, false)).collect();
+    while let Some((id, parents_done)) = stack.pop() {
+        if counts.contains_key(&id) {
+            continue;
+        }
+        let parents = &parents_of[&id];
+        if !parents_done {
+            stack.push((id, true));
+            stack.extend(
+                parents
+                    .iter()
+                    .filter(|parent| !counts.contains_key(*parent))
+                    .map(|parent| (*parent, false)),
+            );
+            continue;
+        }
+        let count = match parents.as_slice() {
+            [] => 1,
+            [parent] => counts[parent] + 1,
+            _ => {
+                let mut seen = HashSet::new();
+                let mut queue = vec![id];
+                while let Some(id) = queue.pop() {
+                    if seen.insert(id) {
+                        queue.extend(parents_of[&id].iter().copied());
+                    }
+                }
+                seen.len()
+            }
+        };
+        counts.insert(id, count);
+    }
+    counts
+}
@@ -0,0 +1,131 @@
+//! Attribute the lines of a file to the commits that introduced them, similar to `git blame`.
+use std::{ops::Range, path::PathBuf};
+
+/// Options for use in [`Repository::blame()`][crate::Repository::blame()].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// If `true`, lines that only differ in whitespace are considered unchanged, like `git blame -w`, so their
+    /// origin is searched further back in history.
+    pub ignore_whitespace: bool,
+}
+
+/// A range of lines in the blamed file along with the commit that introduced them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The commit that introduced the lines.
+    pub commit_id: gix_hash::ObjectId,
+    /// The zero-based range of lines in the blamed file.
+    pub range_in_blamed_file: Range<u32>,
+    /// The zero-based range of the same lines in the version of the file in `commit_id`.
+    pub range_in_source_file: Range<u32>,
+}
+
+/// The outcome of [`Repository::blame()`][crate::Repository::blame()].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// All entries ordered by their range in the blamed file, covering all of its lines.
+    pub entries: Vec<Entry>,
+    /// The contents of the blamed file.
+    pub blob: Vec<u8>,
+}
+
+/// The error returned by [`Repository::blame()`][crate::Repository::blame()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The file '{}' doesn't exist in commit {commit_id}", path.display())]
+    PathNotFound {
+        path: PathBuf,
+        commit_id: gix_hash::ObjectId,
+    },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::object::commit::Error),
+    #[error(transparent)]
+    DiffAlgorithm(#[from] crate::config::diff::algorithm::Error),
+}
+
+/// A range of lines in the blamed file whose origin is yet to be determined, and where they are located in the
+/// version of the file in the commit currently looked at.
+#[derive(Debug, Clone)]
+pub(crate) struct UnblamedHunk {
+    pub range_in_blamed_file: Range<u32>,
+    pub source_start: u32,
+}
+
+impl UnblamedHunk {
+    pub(crate) fn source_range(&self) -> Range<u32> {
+        self.source_start..self.source_start + self.range_in_blamed_file.len() as u32
+    }
+
+    /// Return the part of this hunk that corresponds to `source` lines, which must be contained in our source range.
+    fn sub_range(&self, source: Range<u32>) -> Self {
+        let offset = source.start - self.source_start;
+        let start = self.range_in_blamed_file.start + offset;
+        UnblamedHunk {
+            range_in_blamed_file: start..start + source.len() as u32,
+            source_start: source.start,
+        }
+    }
+}
+
+/// Split `hunks`, which are located in the version of a file in a commit, into those that are unchanged in the version of its parent,
+/// with their source moved to the lines of the parent, and those that were changed by the commit.
+///
+/// `changes` are the line ranges that were changed from the parent (`before`) to the commit (`after`), ordered by their position.
+pub(crate) fn split_by_changes(
+    hunks: Vec<UnblamedHunk>,
+    changes: &[(Range<u32>, Range<u32>)],
+) -> (Vec<UnblamedHunk>, Vec<UnblamedHunk>) {
+    let mut unchanged_ranges = Vec::with_capacity(changes.len() + 1);
+    let (mut pos, mut parent_pos) = (0, 0);
+    for (before, after) in changes {
+        if after.start > pos {
+            unchanged_ranges.push((pos..after.start, parent_pos));
+        }
+        pos = after.end;
+        parent_pos = before.end;
+    }
+    unchanged_ranges.push((pos..u32::MAX, parent_pos));
+
+    let (mut unchanged, mut changed) = (Vec::new(), Vec::new());
+    for hunk in hunks {
+        let source = hunk.source_range();
+        let mut pos = source.start;
+        for (range, parent_start) in unchanged_ranges
+            .iter()
+            .filter(|(range, _)| range.end > source.start && range.start < source.end)
+        {
+            let overlap = range.start.max(source.start)..range.end.min(source.end);
+            if pos < overlap.start {
+                changed.push(hunk.sub_range(pos..overlap.start));
+            }
+            let mut in_parent = hunk.sub_range(overlap.clone());
+            in_parent.source_start = parent_start + (overlap.start - range.start);
+            unchanged.push(in_parent);
+            pos = overlap.end;
+        }
+        if pos < source.end {
+            changed.push(hunk.sub_range(pos..source.end));
+        }
+    }
+    (unchanged, changed)
+}
+
+/// Return the amount of lines in `data`, including a last line without terminator.
+pub(crate) fn num_lines(data: &[u8]) -> u32 {
+    data.split_inclusive(|b| *b == b'\n').count() as u32
+}
+
+/// Return `data` with all whitespace removed from its lines, while keeping the amount of lines the same.
+pub(crate) fn without_whitespace(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    for line in data.split_inclusive(|b| *b == b'\n') {
+        out.extend(line.iter().filter(|b| !b.is_ascii_whitespace()));
+        out.push(b'\n');
+    }
+    out
+}
@@ -0,0 +1,96 @@
+//! Create [bundles](https://git-scm.com/docs/git-bundle) from objects in the repository and write the objects of bundles
+//! into the object database, for transferring history without a connection to a remote.
+pub use gix_bundle::*;
+
+///
+pub mod create {
+    use crate::bstr::BString;
+
+    /// Options for use in [`Repository::create_bundle()`][crate::Repository::create_bundle()].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Options {
+        /// The version of the bundle to write, with [`V2`][super::Version::V2] being the default as it's understood by all
+        /// versions of `git`.
+        pub version: super::Version,
+    }
+
+    impl Default for Options {
+        fn default() -> Self {
+            Options {
+                version: super::Version::V2,
+            }
+        }
+    }
+
+    /// The error returned by [`Repository::create_bundle()`][crate::Repository::create_bundle()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not parse the revision specification {spec:?}")]
+        RevParse {
+            spec: BString,
+            source: crate::revision::spec::parse::Error,
+        },
+        #[error("The revision specification {spec:?} isn't supported as it doesn't describe which objects to include or exclude")]
+        UnsupportedSpec { spec: BString },
+        #[error("{spec:?} doesn't name a reference, which is required to put the object it points to into the bundle")]
+        NotAReference { spec: BString },
+        #[error("Refusing to create an empty bundle as no reference was selected")]
+        Empty,
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error("Could not write the bundle header")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        WritePack(#[from] crate::pack::write::Error),
+    }
+
+    /// The outcome of [`Repository::create_bundle()`][crate::Repository::create_bundle()].
+    #[derive(Debug, Clone)]
+    pub struct Outcome {
+        /// The header that was written.
+        pub header: super::Header,
+        /// The amount of objects in the pack that follows the header.
+        pub num_objects: usize,
+    }
+}
+
+///
+pub mod unbundle {
+    use crate::config;
+
+    /// The error returned by [`Repository::unbundle()`][crate::Repository::unbundle()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Header(#[from] super::decode::Error),
+        #[error("Cannot unbundle a bundle that uses {bundle} while the repository uses {local} for object hashes")]
+        IncompatibleObjectHash {
+            local: gix_hash::Kind,
+            bundle: gix_hash::Kind,
+        },
+        #[error("The bundle was created with filter {filter:?} and can't be applied to a repository without a promisor remote")]
+        Filtered { filter: crate::bstr::BString },
+        #[error("The repository lacks the object {id} which is required to apply the bundle")]
+        MissingPrerequisite { id: gix_hash::ObjectId },
+        #[error("The value to configure pack threads should be 0 to auto-configure or the amount of threads to use")]
+        PackThreads(#[from] config::unsigned_integer::Error),
+        #[error("The value to configure the pack index version should be 1 or 2")]
+        PackIndexVersion(#[from] config::key::GenericError),
+        #[error(transparent)]
+        WritePack(#[from] gix_pack::bundle::write::Error),
+    }
+
+    /// The outcome of [`Repository::unbundle()`][crate::Repository::unbundle()].
+    #[derive(Debug, Clone)]
+    pub struct Outcome {
+        /// The header of the bundle, with the references it contains.
+        pub header: super::Header,
+        /// Information about the pack and index that were written into the object database.
+        ///
+        /// Note that the pack is protected from garbage collection by a `.keep` file at `keep_path`, which should be removed
+        /// once references to the objects in the bundle were written.
+        pub write_pack_bundle: gix_pack::bundle::write::Outcome,
+    }
+}
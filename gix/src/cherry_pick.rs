@@ -0,0 +1,64 @@
+//! Cherry-picking and reverting commits, similar to `git cherry-pick` and `git revert`.
+//!
+//! See [`Repository::cherry_pick()`][crate::Repository::cherry_pick()] and [`Repository::revert()`][crate::Repository::revert()].
+#![allow(clippy::result_large_err)]
+use gix_hash::ObjectId;
+
+/// Options for use in [`Repository::cherry_pick()`][crate::Repository::cherry_pick()] and
+/// [`Repository::revert()`][crate::Repository::revert()].
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// How to merge the contents of files. Labels that aren't set are chosen like `git` does, with `HEAD` for our side
+    /// and the abbreviated id and summary of the commit for their side.
+    pub merge: crate::merge::Options,
+    /// If `true`, append a `(cherry picked from commit <id>)` line to the message of a picked commit, like
+    /// `git cherry-pick -x` does. It has no effect when reverting.
+    pub record_origin: bool,
+}
+
+/// The outcome of [`Repository::cherry_pick()`][crate::Repository::cherry_pick()] and
+/// [`Repository::revert()`][crate::Repository::revert()].
+#[derive(Debug, Clone)]
+pub enum Outcome<'repo> {
+    /// The changes were applied cleanly and committed on top of `HEAD` as the commit with the given id.
+    Committed(crate::Id<'repo>),
+    /// The changes are already present in `HEAD`, so nothing was changed or committed.
+    Empty,
+    /// The changes couldn't be applied without conflicts, which are left in the index and the worktree for resolution.
+    ///
+    /// Like `git`, `CHERRY_PICK_HEAD` or `REVERT_HEAD` are written along with the commit message in `MERGE_MSG`, so
+    /// `git cherry-pick --continue` or `git revert --continue` can create the commit once the conflicts are resolved.
+    Conflicts(Vec<crate::merge::Conflict>),
+}
+
+/// The error returned by [`Repository::cherry_pick()`][crate::Repository::cherry_pick()] and
+/// [`Repository::revert()`][crate::Repository::revert()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot start a new operation while another one is in progress: {0:?}")]
+    InProgress(crate::state::InProgress),
+    #[error(transparent)]
+    HeadCommit(#[from] crate::reference::head_commit::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error("Commit {id} is a merge, which can't be applied without choosing one of its parents")]
+    MergeCommit { id: ObjectId },
+    #[error(transparent)]
+    Merge(#[from] crate::merge::Error),
+    #[error(transparent)]
+    Checkout(#[from] crate::merge::checkout::Error),
+    #[error(transparent)]
+    ParseTime(#[from] crate::config::time::Error),
+    #[error(transparent)]
+    Commit(#[from] crate::commit::Error),
+    #[error("Could not write '{}'", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+}
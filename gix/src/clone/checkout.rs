@@ -22,6 +22,8 @@ pub mod main_worktree {
             source: gix_traverse::tree::breadthfirst::Error,
         },
         #[error(transparent)]
+        IndexVersion(#[from] crate::config::key::GenericError),
+        #[error(transparent)]
         WriteIndex(#[from] gix_index::file::write::Error),
         #[error(transparent)]
         CheckoutOptions(#[from] crate::config::checkout_options::Error),
@@ -36,6 +38,10 @@ pub mod main_worktree {
         FindHead(#[from] crate::reference::find::existing::Error),
         #[error("The HEAD reference could not be located")]
         PeelHeadToId(#[from] crate::head::peel::Error),
+        #[error("Could not determine the hooks directory")]
+        HooksDir(#[from] gix_config::path::interpolate::Error),
+        #[error(transparent)]
+        PostCheckoutHook(#[from] crate::hook::run::Error),
     }
 
     /// The progress ids used in [`PrepareCheckout::main_worktree()`].
@@ -65,6 +71,8 @@ pub mod main_worktree {
         ///
         /// Note that this is a no-op if the remote was empty, leaving this repository empty as well. This can be validated by checking
         /// if the `head()` of the returned repository is not unborn.
+        ///
+        /// Like git, the `post-checkout` hook is run after the checkout. If it fails, the clone is kept but the error is returned.
         pub fn main_worktree(
             &mut self,
             mut progress: impl crate::Progress,
@@ -77,8 +85,8 @@ pub mod main_worktree {
             let workdir = repo.work_dir().ok_or_else(|| Error::BareRepository {
                 git_dir: repo.git_dir().to_owned(),
             })?;
-            let root_tree = match repo.head()?.peel_to_id_in_place().transpose()? {
-                Some(id) => id.object().expect("downloaded from remote").peel_to_tree()?.id,
+            let head_id = match repo.head()?.peel_to_id_in_place().transpose()? {
+                Some(id) => id,
                 None => {
                     return Ok((
                         self.repo.take().expect("still present"),
@@ -86,6 +94,7 @@ pub mod main_worktree {
                     ))
                 }
             };
+            let root_tree = head_id.object().expect("downloaded from remote").peel_to_tree()?.id;
             let index = gix_index::State::from_tree(&root_tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
                 .map_err(|err| Error::IndexFromTree {
                     id: root_tree,
@@ -118,8 +127,22 @@ pub mod main_worktree {
             files.show_throughput(start);
             bytes.show_throughput(start);
 
-            index.write(Default::default())?;
-            Ok((self.repo.take().expect("still present"), outcome))
+            index.write(gix_index::write::Options {
+                version: repo.config.index_version()?,
+                ..Default::default()
+            })?;
+
+            let head_id = head_id.detach();
+            let repo = self.repo.take().expect("still present");
+            if let Some(hook) = repo.hook("post-checkout")? {
+                hook.args([
+                    gix_hash::ObjectId::null(repo.object_hash()).to_string(),
+                    head_id.to_string(),
+                    "1".into(),
+                ])
+                .run()?;
+            }
+            Ok((repo, outcome))
         }
     }
 }
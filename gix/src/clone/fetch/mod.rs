@@ -30,6 +30,8 @@ pub enum Error {
     },
     #[error("Failed to update HEAD with values from remote")]
     HeadUpdate(#[from] crate::reference::edit::Error),
+    #[error("Could not obtain configuration to learn if bundles advertised by the remote should be used")]
+    BundleUriConfig(#[source] crate::config::boolean::Error),
 }
 
 /// Modification
@@ -100,7 +102,7 @@ impl PrepareFetch {
         )
         .expect("valid")
         .to_owned();
-        let pending_pack: remote::fetch::Prepare<'_, '_, _, _> =
+        let mut pending_pack: remote::fetch::Prepare<'_, '_, _, _> =
             remote.connect(remote::Direction::Fetch, progress)?.prepare_fetch({
                 let mut opts = self.fetch_options.clone();
                 if !opts.extra_refspecs.contains(&head_refspec) {
@@ -116,15 +118,34 @@ impl PrepareFetch {
             b.insert_str(0, "clone: from ");
             b
         };
+        if let Some(filter) = self.filter {
+            pending_pack = pending_pack.with_filter(filter);
+        }
+        let use_bundles = repo
+            .config
+            .resolved
+            .boolean_filter_by_key("transfer.bundleURI", &mut repo.filter_config_section())
+            .map(|val| crate::config::tree::Transfer::BUNDLE_URI.enrich_error(val))
+            .transpose()
+            .map_err(Error::BundleUriConfig)?
+            .unwrap_or(false);
         let outcome = pending_pack
             .with_write_packed_refs_only(true)
             .with_reflog_message(RefLogMessage::Override {
                 message: reflog_message.clone(),
             })
             .with_shallow(self.shallow.clone())
+            .with_bundle_uri(use_bundles)
             .receive(should_interrupt)?;
 
         util::append_config_to_repo_config(repo, config);
+        if let Some(filter) = self.filter {
+            // The promisor configuration was written to disk by the fetch, make it visible in memory as well so it's
+            // retained when the local configuration is written again.
+            let mut config = repo.config_snapshot_mut();
+            filter.write_promisor_config_to(remote_name.as_ref(), &mut config);
+            config.commit().expect("configuration we set is valid");
+        }
         util::update_head(
             repo,
             &outcome.ref_map.remote_refs,
@@ -191,6 +212,14 @@ impl PrepareFetch {
         self.shallow = shallow;
         self
     }
+
+    /// Make this clone a partial one by asking the remote to omit objects according to `filter`, like `git clone --filter`.
+    ///
+    /// The remote is configured as promisor so that later fetches use the same filter.
+    pub fn with_filter(mut self, filter: crate::remote::fetch::Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
 }
 
 /// Consumption
@@ -25,6 +25,9 @@ pub struct PrepareFetch {
     /// How to handle shallow clones
     #[cfg_attr(not(feature = "blocking-network-client"), allow(dead_code))]
     shallow: remote::fetch::Shallow,
+    /// The object filter to use for a partial clone, if any.
+    #[cfg_attr(not(feature = "blocking-network-client"), allow(dead_code))]
+    filter: Option<remote::fetch::Filter>,
 }
 
 /// The error returned by [`PrepareFetch::new()`].
@@ -103,6 +106,7 @@ impl PrepareFetch {
             remote_name: None,
             configure_remote: None,
             shallow: remote::fetch::Shallow::NoChange,
+            filter: None,
         })
     }
 }
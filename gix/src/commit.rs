@@ -19,6 +19,21 @@ pub enum Error {
     WriteObject(#[from] crate::object::write::Error),
     #[error(transparent)]
     ReferenceEdit(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    ConfigBoolean(#[from] crate::config::boolean::Error),
+    #[error(transparent)]
+    Signer(#[from] crate::config::signer::Error),
+    #[error("Could not sign the commit")]
+    Sign(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    HooksDir(#[from] gix_config::path::interpolate::Error),
+    #[error(transparent)]
+    Hook(#[from] crate::hook::run::Error),
+    #[error("Could not write or read the commit message for the 'commit-msg' hook at '{}'", path.display())]
+    CommitMessageFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
 }
 
 ///
@@ -4,12 +4,12 @@ use std::{borrow::Cow, path::PathBuf, time::Duration};
 use gix_lock::acquire::Fail;
 
 use crate::{
-    bstr::BStr,
+    bstr::{BStr, BString, ByteSlice},
     config,
     config::{
         cache::util::{ApplyLeniency, ApplyLeniencyDefault},
         checkout_options,
-        tree::{Checkout, Core, Key},
+        tree::{Checkout, CommitGraph, Core, Filter, Index, Key, Pack},
         Cache,
     },
     remote,
@@ -99,6 +99,62 @@ impl Cache {
         Ok((out[0], out[1]))
     }
 
+    /// The amount of threads to use when indexing a pack, or `None` to use as many threads as there are cores.
+    pub(crate) fn pack_index_threads(&self) -> Result<Option<usize>, config::unsigned_integer::Error> {
+        self.resolved
+            .integer_filter(
+                "pack",
+                None,
+                Pack::THREADS.name,
+                &mut self.filter_config_section.clone(),
+            )
+            .map(|threads| Pack::THREADS.try_into_usize(threads))
+            .transpose()
+            .with_leniency(self.lenient_config)
+    }
+
+    /// The version of the index to write for newly received packs.
+    pub(crate) fn pack_index_version(&self) -> Result<gix_pack::index::Version, config::key::GenericError> {
+        Ok(self
+            .resolved
+            .integer("pack", None, Pack::INDEX_VERSION.name)
+            .map(|value| Pack::INDEX_VERSION.try_into_index_version(value))
+            .transpose()
+            .with_leniency(self.lenient_config)?
+            .unwrap_or(gix_pack::index::Version::V2))
+    }
+
+    /// Whether or not to store the hashes of object paths in bitmaps, which `git` does by default.
+    pub(crate) fn pack_write_bitmap_hash_cache(&self) -> Result<bool, config::boolean::Error> {
+        Ok(self
+            .apply_leniency(
+                self.resolved
+                    .boolean_by_key(Pack::WRITE_BITMAP_HASH_CACHE.logical_name().as_str())
+                    .map(|value| Pack::WRITE_BITMAP_HASH_CACHE.enrich_error(value)),
+            )?
+            .unwrap_or(true))
+    }
+
+    /// Whether to write corrected commit dates, i.e. generation numbers v2, into commit-graph files.
+    pub(crate) fn commit_graph_corrected_commit_dates(&self) -> Result<bool, config::key::GenericError> {
+        Ok(self
+            .resolved
+            .integer("commitGraph", None, CommitGraph::GENERATION_VERSION.name)
+            .map(|value| CommitGraph::GENERATION_VERSION.try_into_corrected_commit_dates(value))
+            .transpose()
+            .with_leniency(self.lenient_config)?
+            .unwrap_or(true))
+    }
+
+    /// The version of the index to write when creating a new one, or `None` to write the lowest possible version.
+    pub(crate) fn index_version(&self) -> Result<Option<gix_index::Version>, config::key::GenericError> {
+        self.resolved
+            .integer("index", None, Index::VERSION.name)
+            .map(|value| Index::VERSION.try_into_index_version(value))
+            .transpose()
+            .with_leniency(self.lenient_config)
+    }
+
     /// The path to the user-level excludes file to ignore certain files in the worktree.
     pub(crate) fn excludes_file(&self) -> Option<Result<PathBuf, gix_config::path::interpolate::Error>> {
         self.trusted_file_path("core", None, Core::EXCLUDES_FILE.name)?
@@ -154,21 +210,6 @@ impl Cache {
                 .unwrap_or(default))
         }
 
-        fn assemble_attribute_globals(
-            me: &Cache,
-            _git_dir: &std::path::Path,
-        ) -> Result<gix_attributes::MatchGroup, checkout_options::Error> {
-            let _attributes_file = match me
-                .trusted_file_path("core", None, Core::ATTRIBUTES_FILE.name)
-                .transpose()?
-            {
-                Some(attributes) => Some(attributes.into_owned()),
-                None => me.xdg_config_path("attributes").ok().flatten(),
-            };
-            // TODO: implement gix_attributes::MatchGroup::<gix_attributes::Attributes>::from_git_dir(), similar to what's done for `Ignore`.
-            Ok(Default::default())
-        }
-
         let thread_limit = self.apply_leniency(
             self.resolved
                 .integer_filter_by_key("checkout.workers", &mut self.filter_config_section.clone())
@@ -193,9 +234,118 @@ impl Cache {
                         .map(|v| Core::CHECK_STAT.try_into_checkstat(v)),
                 )?
                 .unwrap_or(true),
-            attribute_globals: assemble_attribute_globals(self, git_dir)?,
+            attribute_globals: self.attribute_globals(git_dir)?,
+            filter_drivers: self.filter_drivers()?,
+            eol_config: gix_filter::eol::Configuration {
+                auto_crlf: self
+                    .apply_leniency(
+                        self.resolved
+                            .string_filter_by_key("core.autocrlf", &mut self.filter_config_section.clone())
+                            .map(|v| Core::AUTO_CRLF.try_into_autocrlf(v)),
+                    )?
+                    .unwrap_or_default(),
+                eol: self.apply_leniency(
+                    self.resolved
+                        .string_filter_by_key("core.eol", &mut self.filter_config_section.clone())
+                        .map(|v| Core::EOL.try_into_eol(v)),
+                )?,
+            },
+            sparse_checkout: self.sparse_checkout(git_dir)?,
         })
     }
+
+    /// Read the patterns of the sparse checkout from `info/sparse-checkout` in `git_dir` if `core.sparseCheckout` is enabled,
+    /// interpreting them in cone mode if `core.sparseCheckoutCone` is set as well.
+    pub(crate) fn sparse_checkout(
+        &self,
+        git_dir: &std::path::Path,
+    ) -> Result<Option<gix_worktree::sparse::Patterns>, checkout_options::Error> {
+        let boolean = |key: &'static config::tree::keys::Boolean| -> Result<bool, checkout_options::Error> {
+            Ok(self
+                .apply_leniency(
+                    self.resolved
+                        .boolean_by_key(key.logical_name().as_str())
+                        .map(|v| key.enrich_error(v)),
+                )?
+                .unwrap_or(false))
+        };
+        if !boolean(&Core::SPARSE_CHECKOUT)? {
+            return Ok(None);
+        }
+        let cone = boolean(&Core::SPARSE_CHECKOUT_CONE)?;
+        let path = git_dir.join("info").join("sparse-checkout");
+        gix_worktree::sparse::Patterns::from_file(&path, cone, &mut Vec::new())
+            .map_err(|source| checkout_options::Error::ReadSparseCheckout { path, source })
+    }
+
+    /// Collect the filter drivers configured in `filter.<driver>` sections of trusted configuration files.
+    pub(crate) fn filter_drivers(&self) -> Result<Vec<gix_filter::Driver>, checkout_options::Error> {
+        let mut names = Vec::<BString>::new();
+        for section in self
+            .resolved
+            .sections_by_name_and_filter("filter", &mut self.filter_config_section.clone())
+            .into_iter()
+            .flatten()
+        {
+            if let Some(name) = section.header().subsection_name() {
+                if !names.iter().any(|existing| existing == name) {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+
+        names
+            .into_iter()
+            .map(|name| {
+                let subsection = Some(name.as_bstr());
+                let program = |key: &config::tree::keys::Program| {
+                    self.resolved
+                        .string_filter("filter", subsection, key.name, &mut self.filter_config_section.clone())
+                        .map(Cow::into_owned)
+                };
+                Ok(gix_filter::Driver {
+                    clean: program(&Filter::CLEAN),
+                    smudge: program(&Filter::SMUDGE),
+                    process: program(&Filter::PROCESS),
+                    required: self
+                        .apply_leniency(
+                            self.resolved
+                                .boolean_filter(
+                                    "filter",
+                                    subsection,
+                                    Filter::REQUIRED.name,
+                                    &mut self.filter_config_section.clone(),
+                                )
+                                .map(|v| Filter::REQUIRED.enrich_error(v)),
+                        )?
+                        .unwrap_or(false),
+                    name,
+                })
+            })
+            .collect()
+    }
+
+    /// Load the attribute patterns that apply to all paths, from `core.attributesFile` or its default location,
+    /// and from `info/attributes` in `git_dir`.
+    pub(crate) fn attribute_globals(
+        &self,
+        git_dir: &std::path::Path,
+    ) -> Result<gix_attributes::MatchGroup, checkout_options::Error> {
+        let attributes_file = match self
+            .trusted_file_path("core", None, Core::ATTRIBUTES_FILE.name)
+            .transpose()?
+        {
+            Some(attributes) => Some(attributes.into_owned()),
+            None => self.xdg_config_path("attributes").ok().flatten(),
+        };
+        let mut buf = Vec::new();
+        Ok(gix_attributes::MatchGroup::attributes_from_git_dir(
+            git_dir,
+            attributes_file,
+            Some(std::path::Path::new("attributes")),
+            &mut buf,
+        )?)
+    }
     pub(crate) fn xdg_config_path(
         &self,
         resource_file_name: &str,
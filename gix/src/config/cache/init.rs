@@ -288,6 +288,14 @@ fn apply_environment_overrides(
                     let key = &Http::SSL_CA_INFO;
                     (env(key), key.name)
                 },
+                {
+                    let key = &Http::SSL_CERT;
+                    (env(key), key.name)
+                },
+                {
+                    let key = &Http::SSL_KEY;
+                    (env(key), key.name)
+                },
                 {
                     let key = &Http::SSL_VERSION;
                     (env(key), key.name)
@@ -326,6 +334,10 @@ fn apply_environment_overrides(
                     let key = &gitoxide::Http::VERBOSE;
                     (env(key), key.name)
                 },
+                {
+                    let key = &gitoxide::Http::SSL_NO_VERIFY;
+                    (env(key), key.name)
+                },
                 {
                     let key = &gitoxide::Http::PROXY_AUTH_METHOD;
                     (env(key), key.name)
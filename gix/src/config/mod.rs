@@ -115,6 +115,13 @@ pub mod checkout_options {
         CheckoutWorkers(#[from] super::checkout::workers::Error),
         #[error("Failed to interpolate the attribute file configured at `core.attributesFile`")]
         AttributesFileInterpolation(#[from] gix_config::path::interpolate::Error),
+        #[error("Could not read global attribute files")]
+        ReadAttributes(#[from] std::io::Error),
+        #[error("Could not read the sparse checkout patterns at '{}'", path.display())]
+        ReadSparseCheckout {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
     }
 }
 
@@ -144,6 +151,23 @@ pub mod ssh_connect_options {
     pub struct Error(#[from] super::key::GenericErrorWithValue);
 }
 
+///
+pub mod signer {
+    /// The error produced when obtaining the signer configured by `gpg.format` and `user.signingKey`.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Format(#[from] super::key::GenericErrorWithValue),
+        #[error("The `user.signingKey` must be set to sign with the ssh format")]
+        SigningKeyMissing,
+        #[error("Committer identity is not configured, but needed as fallback for `user.signingKey`")]
+        CommitterMissing,
+        #[error(transparent)]
+        ParseTime(#[from] super::time::Error),
+    }
+}
+
 ///
 pub mod key {
     use crate::bstr::BString;
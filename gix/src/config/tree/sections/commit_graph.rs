@@ -0,0 +1,63 @@
+use crate::{
+    config,
+    config::tree::{keys, CommitGraph, Key, Section},
+};
+
+impl CommitGraph {
+    /// The `commitGraph.generationVersion` key.
+    pub const GENERATION_VERSION: GenerationVersion = GenerationVersion::new_with_validate(
+        "generationVersion",
+        &config::Tree::COMMIT_GRAPH,
+        validate::GenerationVersion,
+    );
+}
+
+/// The `commitGraph.generationVersion` key.
+pub type GenerationVersion = keys::Any<validate::GenerationVersion>;
+
+mod generation_version {
+    use crate::{config, config::tree::sections::commit_graph::GenerationVersion};
+
+    impl GenerationVersion {
+        /// Try to interpret an integer value as generation version, returning `true` if corrected commit dates should be
+        /// written along with topological levels.
+        pub fn try_into_corrected_commit_dates(
+            &'static self,
+            value: Result<i64, gix_config::value::Error>,
+        ) -> Result<bool, config::key::GenericError> {
+            let value = value.map_err(|err| config::key::GenericError::from(self).with_source(err))?;
+            Ok(match value {
+                1 => false,
+                2 => true,
+                _ => return Err(config::key::GenericError::from(self)),
+            })
+        }
+    }
+}
+
+impl Section for CommitGraph {
+    fn name(&self) -> &str {
+        "commitGraph"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::GENERATION_VERSION]
+    }
+}
+
+mod validate {
+    use crate::{bstr::BStr, config::tree::keys};
+
+    pub struct GenerationVersion;
+    impl keys::Validate for GenerationVersion {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::CommitGraph::GENERATION_VERSION.try_into_corrected_commit_dates(
+                gix_config::Integer::try_from(value).and_then(|int| {
+                    int.to_decimal()
+                        .ok_or_else(|| gix_config::value::Error::new("integer out of range", value))
+                }),
+            )?;
+            Ok(())
+        }
+    }
+}
@@ -21,6 +21,15 @@ impl Core {
         Disambiguate::new_with_validate("disambiguate", &config::Tree::CORE, validate::Disambiguate);
     /// The `core.fileMode` key.
     pub const FILE_MODE: keys::Boolean = keys::Boolean::new_boolean("fileMode", &config::Tree::CORE);
+    /// The `core.fsmonitor` key.
+    pub const FS_MONITOR: FsMonitor =
+        FsMonitor::new_with_validate("fsmonitor", &config::Tree::CORE, validate::FsMonitor);
+    /// The `core.fsmonitorHookVersion` key.
+    pub const FS_MONITOR_HOOK_VERSION: keys::UnsignedInteger =
+        keys::UnsignedInteger::new_unsigned_integer("fsmonitorHookVersion", &config::Tree::CORE)
+            .with_note("values other than 1 or 2 try version 2 first and fall back to version 1");
+    /// The `core.hooksPath` key.
+    pub const HOOKS_PATH: keys::Path = keys::Path::new_path("hooksPath", &config::Tree::CORE);
     /// The `core.ignoreCase` key.
     pub const IGNORE_CASE: keys::Boolean = keys::Boolean::new_boolean("ignoreCase", &config::Tree::CORE);
     /// The `core.filesRefLockTimeout` key.
@@ -42,8 +51,16 @@ impl Core {
     /// The `core.repositoryFormatVersion` key.
     pub const REPOSITORY_FORMAT_VERSION: keys::UnsignedInteger =
         keys::UnsignedInteger::new_unsigned_integer("repositoryFormatVersion", &config::Tree::CORE);
+    /// The `core.sparseCheckout` key.
+    pub const SPARSE_CHECKOUT: keys::Boolean = keys::Boolean::new_boolean("sparseCheckout", &config::Tree::CORE);
+    /// The `core.sparseCheckoutCone` key.
+    pub const SPARSE_CHECKOUT_CONE: keys::Boolean =
+        keys::Boolean::new_boolean("sparseCheckoutCone", &config::Tree::CORE);
     /// The `core.symlinks` key.
     pub const SYMLINKS: keys::Boolean = keys::Boolean::new_boolean("symlinks", &config::Tree::CORE);
+    /// The `core.untrackedCache` key.
+    pub const UNTRACKED_CACHE: UntrackedCache =
+        UntrackedCache::new_with_validate("untrackedCache", &config::Tree::CORE, validate::UntrackedCache);
     /// The `core.trustCTime` key.
     pub const TRUST_C_TIME: keys::Boolean = keys::Boolean::new_boolean("trustCTime", &config::Tree::CORE);
     /// The `core.worktree` key.
@@ -63,6 +80,10 @@ impl Core {
     /// The `core.sshCommand` key.
     pub const SSH_COMMAND: keys::Executable = keys::Executable::new_executable("sshCommand", &config::Tree::CORE)
         .with_environment_override("GIT_SSH_COMMAND");
+    /// The `core.autocrlf` key.
+    pub const AUTO_CRLF: AutoCrlf = AutoCrlf::new_with_validate("autocrlf", &config::Tree::CORE, validate::AutoCrlf);
+    /// The `core.eol` key.
+    pub const EOL: Eol = Eol::new_with_validate("eol", &config::Tree::CORE, validate::Eol);
 }
 
 impl Section for Core {
@@ -78,6 +99,9 @@ impl Section for Core {
             &Self::DELTA_BASE_CACHE_LIMIT,
             &Self::DISAMBIGUATE,
             &Self::FILE_MODE,
+            &Self::FS_MONITOR,
+            &Self::FS_MONITOR_HOOK_VERSION,
+            &Self::HOOKS_PATH,
             &Self::IGNORE_CASE,
             &Self::FILES_REF_LOCK_TIMEOUT,
             &Self::PACKED_REFS_TIMEOUT,
@@ -85,13 +109,18 @@ impl Section for Core {
             &Self::LOG_ALL_REF_UPDATES,
             &Self::PRECOMPOSE_UNICODE,
             &Self::REPOSITORY_FORMAT_VERSION,
+            &Self::SPARSE_CHECKOUT,
+            &Self::SPARSE_CHECKOUT_CONE,
             &Self::SYMLINKS,
+            &Self::UNTRACKED_CACHE,
             &Self::TRUST_C_TIME,
             &Self::WORKTREE,
             &Self::ASKPASS,
             &Self::EXCLUDES_FILE,
             &Self::ATTRIBUTES_FILE,
             &Self::SSH_COMMAND,
+            &Self::AUTO_CRLF,
+            &Self::EOL,
         ]
     }
 }
@@ -108,6 +137,18 @@ pub type LogAllRefUpdates = keys::Any<validate::LogAllRefUpdates>;
 /// The `core.disambiguate` key.
 pub type Disambiguate = keys::Any<validate::Disambiguate>;
 
+/// The `core.autocrlf` key.
+pub type AutoCrlf = keys::Any<validate::AutoCrlf>;
+
+/// The `core.eol` key.
+pub type Eol = keys::Any<validate::Eol>;
+
+/// The `core.untrackedCache` key.
+pub type UntrackedCache = keys::Any<validate::UntrackedCache>;
+
+/// The `core.fsmonitor` key.
+pub type FsMonitor = keys::Any<validate::FsMonitor>;
+
 mod disambiguate {
     use std::borrow::Cow;
 
@@ -194,6 +235,101 @@ mod check_stat {
     }
 }
 
+mod eol {
+    use std::borrow::Cow;
+
+    use crate::{
+        bstr::BStr,
+        config,
+        config::tree::core::{AutoCrlf, Eol},
+    };
+
+    impl AutoCrlf {
+        /// Convert `value` into the mode for converting line endings, which is either a boolean or `input`.
+        pub fn try_into_autocrlf(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<gix_filter::eol::AutoCrlf, config::key::GenericErrorWithValue> {
+            if value.as_ref().eq_ignore_ascii_case(b"input") {
+                return Ok(gix_filter::eol::AutoCrlf::Input);
+            }
+            match gix_config::Boolean::try_from(value.as_ref()) {
+                Ok(gix_config::Boolean(true)) => Ok(gix_filter::eol::AutoCrlf::Enabled),
+                Ok(gix_config::Boolean(false)) => Ok(gix_filter::eol::AutoCrlf::Disabled),
+                Err(_) => Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned())),
+            }
+        }
+    }
+
+    impl Eol {
+        /// Convert `value` into the line ending to use in the worktree, with `native` resolving to the line ending
+        /// of the platform we are compiled for.
+        pub fn try_into_eol(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<gix_filter::eol::Mode, config::key::GenericErrorWithValue> {
+            Ok(match value.to_ascii_lowercase().as_slice() {
+                b"lf" => gix_filter::eol::Mode::Lf,
+                b"crlf" => gix_filter::eol::Mode::CrLf,
+                b"native" => gix_filter::eol::Mode::native(),
+                _ => return Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned())),
+            })
+        }
+    }
+}
+
+mod untracked_cache {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::core::UntrackedCache};
+
+    impl UntrackedCache {
+        /// Convert `value` into `Some(true)` if the untracked cache should be created and used, `Some(false)` if it should
+        /// be removed, or `None` if an existing untracked cache should be used and kept, as configured with `keep`.
+        pub fn try_into_untracked_cache(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<Option<bool>, config::key::GenericErrorWithValue> {
+            if value.as_ref().eq_ignore_ascii_case(b"keep") {
+                return Ok(None);
+            }
+            match gix_config::Boolean::try_from(value.as_ref()) {
+                Ok(gix_config::Boolean(value)) => Ok(Some(value)),
+                Err(_) => Err(config::key::GenericErrorWithValue::from_value(self, value.into_owned())),
+            }
+        }
+    }
+}
+
+mod fs_monitor {
+    use std::{borrow::Cow, path::Path};
+
+    use crate::{bstr::BStr, config::tree::core::FsMonitor};
+
+    impl FsMonitor {
+        /// Convert `value` into the source of filesystem changes, which is the builtin daemon listening in `git_dir` if it's `true`,
+        /// nothing if it's `false`, or the hook to run otherwise.
+        ///
+        /// The hook version is left unset, as it's configured separately in `core.fsmonitorHookVersion`.
+        pub fn try_into_fs_monitor(
+            &'static self,
+            value: Cow<'_, BStr>,
+            git_dir: &Path,
+        ) -> Option<gix_worktree::fsmonitor::Source> {
+            match gix_config::Boolean::try_from(value.as_ref()) {
+                Ok(gix_config::Boolean(true)) => Some(gix_worktree::fsmonitor::Source::Daemon {
+                    socket: git_dir.join("fsmonitor--daemon.ipc"),
+                }),
+                Ok(gix_config::Boolean(false)) => None,
+                Err(_) => Some(gix_worktree::fsmonitor::Source::Hook {
+                    command: value.into_owned(),
+                    version: None,
+                }),
+            }
+        }
+    }
+}
+
 mod abbrev {
     use std::borrow::Cow;
 
@@ -291,6 +427,38 @@ mod validate {
         }
     }
 
+    pub struct AutoCrlf;
+    impl keys::Validate for AutoCrlf {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::AUTO_CRLF.try_into_autocrlf(value.into())?;
+            Ok(())
+        }
+    }
+
+    pub struct Eol;
+    impl keys::Validate for Eol {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::EOL.try_into_eol(value.into())?;
+            Ok(())
+        }
+    }
+
+    pub struct UntrackedCache;
+    impl keys::Validate for UntrackedCache {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            super::Core::UNTRACKED_CACHE.try_into_untracked_cache(value.into())?;
+            Ok(())
+        }
+    }
+
+    pub struct FsMonitor;
+    impl keys::Validate for FsMonitor {
+        fn validate(&self, _value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            // Everything that isn't a boolean is the hook to run.
+            Ok(())
+        }
+    }
+
     pub struct Abbrev;
     impl keys::Validate for Abbrev {
         fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
@@ -0,0 +1,28 @@
+use crate::config::tree::{keys, traits::SubSectionRequirement, Filter, Key, Section};
+
+const DRIVER_PARAMETER: Option<SubSectionRequirement> = Some(SubSectionRequirement::Parameter("driver"));
+
+impl Filter {
+    /// The `filter.<driver>.clean` key.
+    pub const CLEAN: keys::Program =
+        keys::Program::new_program("clean", &crate::config::Tree::FILTER).with_subsection_requirement(DRIVER_PARAMETER);
+    /// The `filter.<driver>.smudge` key.
+    pub const SMUDGE: keys::Program = keys::Program::new_program("smudge", &crate::config::Tree::FILTER)
+        .with_subsection_requirement(DRIVER_PARAMETER);
+    /// The `filter.<driver>.process` key.
+    pub const PROCESS: keys::Program = keys::Program::new_program("process", &crate::config::Tree::FILTER)
+        .with_subsection_requirement(DRIVER_PARAMETER);
+    /// The `filter.<driver>.required` key.
+    pub const REQUIRED: keys::Boolean = keys::Boolean::new_boolean("required", &crate::config::Tree::FILTER)
+        .with_subsection_requirement(DRIVER_PARAMETER);
+}
+
+impl Section for Filter {
+    fn name(&self) -> &str {
+        "filter"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::CLEAN, &Self::SMUDGE, &Self::PROCESS, &Self::REQUIRED]
+    }
+}
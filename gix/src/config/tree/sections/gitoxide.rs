@@ -103,6 +103,12 @@ mod subsections {
         pub const VERBOSE: keys::Boolean = keys::Boolean::new_boolean("verbose", &Gitoxide::HTTP)
             .with_environment_override("GIT_CURL_VERBOSE")
             .with_deviation("we parse it as boolean for convenience (infallible) but git only checks the presence");
+        /// The `gitoxide.http.sslNoVerify` key.
+        ///
+        /// If set, the server certificates aren't verified, overriding `http.sslVerify`.
+        pub const SSL_NO_VERIFY: keys::Boolean = keys::Boolean::new_boolean("sslNoVerify", &Gitoxide::HTTP)
+            .with_environment_override("GIT_SSL_NO_VERIFY")
+            .with_deviation("we parse it as boolean for convenience (infallible) but git only checks the presence");
         /// The `gitoxide.http.noProxy` key.
         pub const NO_PROXY: keys::String = keys::String::new_string("noProxy", &Gitoxide::HTTP)
             .with_environment_override("no_proxy")
@@ -138,6 +144,7 @@ mod subsections {
                 &Self::PROXY,
                 &Self::ALL_PROXY,
                 &Self::VERBOSE,
+                &Self::SSL_NO_VERIFY,
                 &Self::NO_PROXY,
                 &Self::CONNECT_TIMEOUT,
                 &Self::SSL_VERSION_MIN,
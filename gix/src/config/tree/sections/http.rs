@@ -45,9 +45,27 @@ impl Http {
     /// The `http.sslCAInfo` key.
     pub const SSL_CA_INFO: keys::Path =
         keys::Path::new_path("sslCAInfo", &config::Tree::HTTP).with_environment_override("GIT_SSL_CAINFO");
+    /// The `http.sslCert` key.
+    pub const SSL_CERT: keys::Path = keys::Path::new_path("sslCert", &config::Tree::HTTP)
+        .with_environment_override("GIT_SSL_CERT")
+        .with_deviation("only supported by the curl backend");
+    /// The `http.sslKey` key.
+    pub const SSL_KEY: keys::Path = keys::Path::new_path("sslKey", &config::Tree::HTTP)
+        .with_environment_override("GIT_SSL_KEY")
+        .with_deviation("only supported by the curl backend");
+    /// The `http.sslVerify` key.
+    pub const SSL_VERIFY: keys::Boolean = keys::Boolean::new_boolean("sslVerify", &config::Tree::HTTP)
+        .with_note("can be disabled with the `GIT_SSL_NO_VERIFY` environment variable via `gitoxide.http.sslNoVerify`")
+        .with_deviation("only supported by the curl backend");
     /// The `http.schannelCheckRevoke` key.
     pub const SCHANNEL_CHECK_REVOKE: keys::Boolean =
         keys::Boolean::new_boolean("schannelCheckRevoke", &config::Tree::HTTP);
+    /// The `http.cookieFile` key.
+    pub const COOKIE_FILE: keys::Path =
+        keys::Path::new_path("cookieFile", &config::Tree::HTTP).with_deviation("only supported by the curl backend");
+    /// The `http.saveCookies` key.
+    pub const SAVE_COOKIES: keys::Boolean = keys::Boolean::new_boolean("saveCookies", &config::Tree::HTTP)
+        .with_deviation("only supported by the curl backend");
 }
 
 impl Section for Http {
@@ -68,7 +86,12 @@ impl Section for Http {
             &Self::LOW_SPEED_LIMIT,
             &Self::SCHANNEL_USE_SSL_CA_INFO,
             &Self::SSL_CA_INFO,
+            &Self::SSL_CERT,
+            &Self::SSL_KEY,
+            &Self::SSL_VERIFY,
             &Self::SCHANNEL_CHECK_REVOKE,
+            &Self::COOKIE_FILE,
+            &Self::SAVE_COOKIES,
         ]
     }
 }
@@ -12,6 +12,10 @@ impl Pack {
     /// The `pack.indexVersion` key.
     pub const INDEX_VERSION: IndexVersion =
         IndexVersion::new_with_validate("indexVersion", &config::Tree::PACK, validate::IndexVersion);
+
+    /// The `pack.writeBitmapHashCache` key.
+    pub const WRITE_BITMAP_HASH_CACHE: keys::Boolean =
+        keys::Boolean::new_boolean("writeBitmapHashCache", &config::Tree::PACK);
 }
 
 /// The `pack.indexVersion` key.
@@ -42,7 +46,7 @@ impl Section for Pack {
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::THREADS, &Self::INDEX_VERSION]
+        &[&Self::THREADS, &Self::INDEX_VERSION, &Self::WRITE_BITMAP_HASH_CACHE]
     }
 }
 
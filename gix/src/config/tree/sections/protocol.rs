@@ -7,6 +7,10 @@ impl Protocol {
     /// The `protocol.allow` key.
     pub const ALLOW: Allow = Allow::new_with_validate("allow", &config::Tree::PROTOCOL, validate::Allow);
 
+    /// The `protocol.version` key.
+    pub const VERSION: Version = Version::new_protocol_version("version", &config::Tree::PROTOCOL)
+        .with_note("version 0 is treated like version 1, which only differs by the server announcing its version");
+
     /// The `protocol.<name>` subsection
     pub const NAME_PARAMETER: NameParameter = NameParameter;
 }
@@ -14,6 +18,37 @@ impl Protocol {
 /// The `protocol.allow` key type.
 pub type Allow = keys::Any<validate::Allow>;
 
+/// The `protocol.version` key type.
+pub type Version = keys::Any<validate::Version>;
+
+mod version {
+    use crate::config::tree::{keys, protocol::Version, Section};
+
+    impl Version {
+        /// Create a new instance of a protocol version key in `section`.
+        pub const fn new_protocol_version(name: &'static str, section: &'static dyn Section) -> Self {
+            keys::Any::new_with_validate(name, section, super::validate::Version)
+        }
+    }
+
+    #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
+    impl Version {
+        /// Try to interpret an integer value as the protocol version to desire when connecting to a remote.
+        pub fn try_into_protocol_version(
+            &'static self,
+            value: Result<i64, gix_config::value::Error>,
+        ) -> Result<gix_protocol::transport::Protocol, crate::config::key::GenericError> {
+            use crate::config::key::GenericError;
+            let value = value.map_err(|err| GenericError::from(self).with_source(err))?;
+            Ok(match value {
+                0 | 1 => gix_protocol::transport::Protocol::V1,
+                2 => gix_protocol::transport::Protocol::V2,
+                _ => return Err(GenericError::from(self)),
+            })
+        }
+    }
+}
+
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
 mod allow {
     use std::borrow::Cow;
@@ -63,7 +98,7 @@ impl Section for Protocol {
     }
 
     fn keys(&self) -> &[&dyn Key] {
-        &[&Self::ALLOW]
+        &[&Self::ALLOW, &Self::VERSION]
     }
 
     fn sub_sections(&self) -> &[&dyn Section] {
@@ -82,4 +117,17 @@ mod validate {
             Ok(())
         }
     }
+
+    pub struct Version;
+    impl keys::Validate for Version {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+            let value = gix_config::Integer::try_from(value)?
+                .to_decimal()
+                .ok_or_else(|| format!("integer {value} cannot be represented as protocol version"))?;
+            if !(0..=2).contains(&value) {
+                return Err(format!("protocol version {value} is unknown, it must be 0, 1 or 2").into());
+            }
+            Ok(())
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
     config,
-    config::tree::{http, keys, Key, Remote, Section, SubSectionRequirement},
+    config::tree::{http, keys, protocol, Key, Remote, Section, SubSectionRequirement},
 };
 
 const NAME_PARAMETER: Option<SubSectionRequirement> = Some(SubSectionRequirement::Parameter("name"));
@@ -31,6 +31,22 @@ impl Remote {
         http::ProxyAuthMethod::new_proxy_auth_method("proxyAuthMethod", &config::Tree::REMOTE)
             .with_subsection_requirement(NAME_PARAMETER)
             .with_deviation("implemented like git, but never actually tried");
+    /// The `remote.<name>.promisor` key.
+    pub const PROMISOR: keys::Boolean =
+        keys::Boolean::new_boolean("promisor", &config::Tree::REMOTE).with_subsection_requirement(NAME_PARAMETER);
+    /// The `remote.<name>.partialCloneFilter` key.
+    pub const PARTIAL_CLONE_FILTER: PartialCloneFilter = PartialCloneFilter::new_with_validate(
+        "partialCloneFilter",
+        &config::Tree::REMOTE,
+        validate::PartialCloneFilter,
+    )
+    .with_subsection_requirement(NAME_PARAMETER)
+    .with_note("only `blob:none`, `blob:limit=<n>` and `tree:<depth>` are supported");
+    /// The `remote.<name>.protocolVersion` key.
+    pub const PROTOCOL_VERSION: protocol::Version =
+        protocol::Version::new_protocol_version("protocolVersion", &config::Tree::REMOTE)
+            .with_subsection_requirement(NAME_PARAMETER)
+            .with_note("gitoxide-specific, overrides `protocol.version` for connections to this remote only");
 }
 
 impl Section for Remote {
@@ -48,6 +64,9 @@ impl Section for Remote {
             &Self::PUSH,
             &Self::PROXY,
             &Self::PROXY_AUTH_METHOD,
+            &Self::PROMISOR,
+            &Self::PARTIAL_CLONE_FILTER,
+            &Self::PROTOCOL_VERSION,
         ]
     }
 }
@@ -55,6 +74,26 @@ impl Section for Remote {
 /// The `remote.<name>.tagOpt` key type.
 pub type TagOpt = keys::Any<validate::TagOpt>;
 
+/// The `remote.<name>.partialCloneFilter` key type.
+pub type PartialCloneFilter = keys::Any<validate::PartialCloneFilter>;
+
+mod partial_clone_filter {
+    use std::borrow::Cow;
+
+    use crate::{bstr::BStr, config, config::tree::remote::PartialCloneFilter, remote};
+
+    impl PartialCloneFilter {
+        /// Try to interpret `value` as object filter specification, like `blob:none`.
+        pub fn try_into_filter(
+            &'static self,
+            value: Cow<'_, BStr>,
+        ) -> Result<remote::fetch::Filter, config::key::GenericErrorWithValue> {
+            remote::fetch::Filter::from_bytes(value.as_ref())
+                .ok_or_else(|| config::key::GenericErrorWithValue::from_value(self, value.into_owned()))
+        }
+    }
+}
+
 mod tag_opts {
     use std::borrow::Cow;
 
@@ -98,4 +137,12 @@ pub mod validate {
             Ok(())
         }
     }
+
+    pub struct PartialCloneFilter;
+    impl Validate for PartialCloneFilter {
+        fn validate(&self, value: &BStr) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
+            super::Remote::PARTIAL_CLONE_FILTER.try_into_filter(Cow::Borrowed(value))?;
+            Ok(())
+        }
+    }
 }
@@ -0,0 +1,20 @@
+use crate::{
+    config,
+    config::tree::{keys, Key, Section, Transfer},
+};
+
+impl Transfer {
+    /// The `transfer.bundleURI` key.
+    pub const BUNDLE_URI: keys::Boolean = keys::Boolean::new_boolean("bundleURI", &config::Tree::TRANSFER)
+        .with_note("bundles are only used when cloning, and only if the server advertises them");
+}
+
+impl Section for Transfer {
+    fn name(&self) -> &str {
+        "transfer"
+    }
+
+    fn keys(&self) -> &[&dyn Key] {
+        &[&Self::BUNDLE_URI]
+    }
+}
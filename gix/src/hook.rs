@@ -0,0 +1,170 @@
+//! Run hooks, the programs in the hooks directory that git invokes at certain points of its operations.
+//!
+//! The following operations run hooks on their own:
+//!
+//! * [`Repository::commit()`][crate::Repository::commit()] and its variants run `pre-commit`, `commit-msg` and `post-commit`.
+//! * Checking out the worktree after a clone runs `post-checkout`.
+//! * [`Connection::push()`][crate::remote::Connection::push()] runs `pre-push` unless [`no_verify`][crate::remote::push::Options::no_verify] is set.
+//!
+//! Like in git, fetching doesn't run any hooks.
+use std::{
+    ffi::OsString,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// A hook found in the hooks directory, which can be [run][Prepare::run()] once its arguments, environment and input are configured.
+///
+/// It's obtained with [`Repository::hook()`][crate::Repository::hook()].
+#[derive(Debug, Clone)]
+pub struct Prepare {
+    /// The name of the hook, like `pre-commit`.
+    pub name: String,
+    /// The path to the executable hook.
+    pub path: PathBuf,
+    /// The directory to run the hook in, the root of the worktree or the git directory of bare repositories.
+    pub cwd: PathBuf,
+    /// The arguments to pass to the hook.
+    pub args: Vec<OsString>,
+    /// Environment variables to set for the hook, like `GIT_INDEX_FILE`.
+    pub env: Vec<(OsString, OsString)>,
+    /// The data to write to the standard input of the hook, or `None` to not provide any input.
+    pub stdin: Option<Vec<u8>>,
+}
+
+/// The outcome of a hook that ran successfully.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// Everything the hook wrote to its standard output.
+    pub stdout: Vec<u8>,
+    /// Everything the hook wrote to its standard error.
+    pub stderr: Vec<u8>,
+}
+
+///
+pub mod run {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Prepare::run()`][super::Prepare::run()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not run the '{name}' hook at '{}'", path.display())]
+        Spawn {
+            name: String,
+            path: PathBuf,
+            source: std::io::Error,
+        },
+        #[error("Could not communicate with the '{name}' hook")]
+        Io { name: String, source: std::io::Error },
+        #[error("The '{name}' hook failed with {status}")]
+        Failed {
+            name: String,
+            status: std::process::ExitStatus,
+            stdout: BString,
+            stderr: BString,
+        },
+    }
+}
+
+/// Builder
+impl Prepare {
+    /// Add `arg` to the arguments of the hook.
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add all `args` to the arguments of the hook.
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<OsString>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the environment variable `key` to `value` for the hook.
+    pub fn env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Write `data` to the standard input of the hook.
+    pub fn stdin(mut self, data: impl Into<Vec<u8>>) -> Self {
+        self.stdin = Some(data.into());
+        self
+    }
+}
+
+/// Execution
+impl Prepare {
+    /// Run the hook to completion while capturing its output, and fail if it doesn't exit successfully.
+    ///
+    /// Hooks that exit before reading all of their input don't cause an error.
+    pub fn run(self) -> Result<Outcome, run::Error> {
+        let Prepare {
+            name,
+            path,
+            cwd,
+            args,
+            env,
+            stdin,
+        } = self;
+        // Hooks are typically shell scripts, which can't be executed directly on windows.
+        let mut cmd = if cfg!(windows) {
+            let mut cmd = Command::new("sh");
+            cmd.arg(&path);
+            cmd
+        } else {
+            Command::new(&path)
+        };
+        let mut child = cmd
+            .args(args)
+            .envs(env)
+            .current_dir(cwd)
+            .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|source| run::Error::Spawn {
+                name: name.clone(),
+                path,
+                source,
+            })?;
+
+        // Write the input on another thread so hooks that produce a lot of output while reading can't block us.
+        let writer = child.stdin.take().zip(stdin).map(|(mut child_stdin, data)| {
+            std::thread::spawn(move || match child_stdin.write_all(&data) {
+                Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+                res => res,
+            })
+        });
+        let output = child.wait_with_output().map_err(|source| run::Error::Io {
+            name: name.clone(),
+            source,
+        })?;
+        if let Some(writer) = writer {
+            writer
+                .join()
+                .expect("writer thread doesn't panic")
+                .map_err(|source| run::Error::Io {
+                    name: name.clone(),
+                    source,
+                })?;
+        }
+
+        if !output.status.success() {
+            return Err(run::Error::Failed {
+                name,
+                status: output.status,
+                stdout: output.stdout.into(),
+                stderr: output.stderr.into(),
+            });
+        }
+        Ok(Outcome {
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+}
@@ -68,11 +68,13 @@
 // APIs/instances anyway.
 pub use gix_actor as actor;
 pub use gix_attributes as attrs;
+pub use gix_commitgraph as commitgraph;
 pub use gix_credentials as credentials;
 pub use gix_date as date;
 pub use gix_features as features;
 use gix_features::threading::OwnShared;
 pub use gix_features::{parallel, progress::Progress, threading};
+pub use gix_filter as filter;
 pub use gix_glob as glob;
 pub use gix_hash as hash;
 #[doc(inline)]
@@ -113,8 +115,8 @@ pub(crate) type Config = OwnShared<gix_config::File<'static>>;
 ///
 mod types;
 pub use types::{
-    Commit, Head, Id, Kind, Object, ObjectDetached, Reference, Remote, Repository, Tag, ThreadSafeRepository, Tree,
-    Worktree,
+    Commit, Head, Id, Kind, Object, ObjectDetached, Reference, Remote, Repository, Submodule, Tag,
+    ThreadSafeRepository, Tree, Worktree,
 };
 
 ///
@@ -130,9 +132,24 @@ pub mod tag;
 ///
 pub mod progress;
 
+///
+pub mod bisect;
+
+///
+pub mod blame;
+
+///
+pub mod cherry_pick;
+
 ///
 pub mod diff;
 
+///
+pub mod hook;
+
+///
+pub mod merge;
+
 /// See [ThreadSafeRepository::discover()], but returns a [`Repository`] instead.
 ///
 /// # Note
@@ -247,6 +264,20 @@ pub mod revision;
 ///
 pub mod remote;
 
+pub mod pack;
+
+pub mod bundle;
+
+pub mod maintenance;
+
+#[cfg(feature = "archive")]
+pub mod archive;
+
+pub mod promisor;
+
+///
+pub mod rebase;
+
 ///
 pub mod init;
 
@@ -256,6 +287,15 @@ pub mod state;
 ///
 pub mod shallow;
 
+pub mod sign;
+
+pub mod sparse_checkout;
+
+///
+pub mod stash;
+
+pub mod submodule;
+
 ///
 pub mod discover;
 
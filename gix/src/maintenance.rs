@@ -0,0 +1,127 @@
+//! Maintain the object database by consolidating loose objects and packs, similar to `git repack`.
+//!
+//! See [`Repository::maintenance()`][crate::Repository::maintenance()].
+use std::path::PathBuf;
+
+use crate::config;
+
+/// Options for use in [`Repository::maintenance()`][crate::Repository::maintenance()].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, write all objects of packs that aren't kept into the new pack along with all loose objects, like
+    /// `git repack -a` does. Otherwise, only loose objects that aren't packed yet are written into the new pack.
+    ///
+    /// Note that unlike `git`, unreachable objects are packed as well so no object is ever lost.
+    pub all_into_one: bool,
+    /// The file names of packs like `pack-<hash>.pack` to keep as they are, in addition to packs that have a `.keep`
+    /// or `.promisor` file next to them, similar to `git repack --keep-pack`.
+    ///
+    /// Objects in kept packs are never written into the new pack, and kept packs are never deleted.
+    pub keep_packs: Vec<PathBuf>,
+    /// If `true`, delete packs whose objects are now contained in the new pack along with the multi-pack-index referring
+    /// to them, and delete all loose objects that are packed, like `git repack -d` does.
+    pub delete_redundant: bool,
+    /// If `true` and [`all_into_one`][Options::all_into_one] is set, write a reachability bitmap for the new pack to speed up
+    /// counting objects, like `git repack -b` does.
+    ///
+    /// The bitmap is silently omitted if not all objects reachable from references end up in the new pack, which happens if
+    /// some of them are in kept packs or alternate object databases, or if the repository is shallow.
+    pub write_bitmap_index: bool,
+    /// If set, write a commit-graph with all commits reachable from references after packing, like
+    /// `git commit-graph write --reachable` does.
+    pub commit_graph: Option<commit_graph::Options>,
+}
+
+///
+pub mod commit_graph {
+    use std::path::PathBuf;
+
+    /// Options to control how the commit-graph is written by [`Repository::maintenance()`][crate::Repository::maintenance()].
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Options {
+        /// If `true`, only write commits that aren't yet in the chain of commit-graph files into a new file on top of it, like
+        /// `git commit-graph write --split` does.
+        ///
+        /// Files at the top of the chain are merged into the new file as long as they don't contain more than twice as many
+        /// commits, which keeps the amount of files logarithmic to the amount of commits.
+        /// Otherwise, all commits are written into a single file which replaces all existing ones.
+        pub split: bool,
+        /// If `true`, store bloom filters of the paths changed by each commit to speed up finding the commits that changed
+        /// a path, like `git commit-graph write --changed-paths` does.
+        ///
+        /// They are also written if the existing commit-graph contains them.
+        pub changed_paths: bool,
+    }
+
+    /// The commit-graph file written by [`Repository::maintenance()`][crate::Repository::maintenance()].
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Outcome {
+        /// The path to the newly written file.
+        pub path: PathBuf,
+        /// The amount of commits in the newly written file.
+        pub num_commits: u32,
+        /// The amount of files the commit-graph consists of, including the newly written one.
+        pub num_files: usize,
+    }
+}
+
+/// The outcome of [`Repository::maintenance()`][crate::Repository::maintenance()].
+#[derive(Debug)]
+pub struct Outcome {
+    /// The pack that was written along with its index, or `None` if there was nothing to pack.
+    pub pack: Option<gix_pack::bundle::write::Outcome>,
+    /// The bitmap that was written next to the new pack, if it was requested and possible.
+    pub bitmap: Option<gix_pack::bitmap::write::Outcome>,
+    /// The commit-graph file that was written if it was requested, or `None` if there was nothing to write.
+    ///
+    /// Commit-graphs are never written for shallow repositories.
+    pub commit_graph: Option<commit_graph::Outcome>,
+    /// The paths to the data files of all packs that were deleted as they are redundant.
+    pub deleted_packs: Vec<PathBuf>,
+    /// The amount of loose objects that were deleted as they are packed.
+    pub deleted_loose_objects: usize,
+}
+
+/// The error returned by [`Repository::maintenance()`][crate::Repository::maintenance()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not read or delete '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    IterateLooseObjects(#[from] gix_odb::loose::iter::Error),
+    #[error(transparent)]
+    OpenPackIndex(#[from] gix_pack::index::init::Error),
+    #[error(transparent)]
+    OpenMultiIndex(#[from] gix_pack::multi_index::init::Error),
+    #[error(transparent)]
+    CreatePack(#[from] crate::pack::write::Error),
+    #[error(transparent)]
+    WritePack(#[from] gix_pack::bundle::write::Error),
+    #[error(transparent)]
+    OpenPack(#[from] gix_pack::bundle::init::Error),
+    #[error(transparent)]
+    WriteBitmap(#[from] gix_pack::bitmap::write::Error),
+    #[error(transparent)]
+    ReferenceIter(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    ReferenceIterInit(#[from] crate::reference::iter::init::Error),
+    #[error(transparent)]
+    BitmapHashCache(#[from] config::boolean::Error),
+    #[error("The value to configure pack threads should be 0 to auto-configure or the amount of threads to use")]
+    PackThreads(#[from] config::unsigned_integer::Error),
+    #[error("The value to configure the pack index version should be 1 or 2")]
+    PackIndexVersion(#[from] config::key::GenericError),
+    #[error("The value to configure the commit-graph generation version should be 1 or 2")]
+    CommitGraphGenerationVersion(#[source] config::key::GenericError),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    DiffTree(#[from] gix_diff::tree::changes::Error),
+    #[error(transparent)]
+    WriteCommitGraph(#[from] gix_commitgraph::file::write::Error),
+    #[error(transparent)]
+    OpenCommitGraph(#[from] gix_commitgraph::graph::Error),
+}
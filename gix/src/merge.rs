@@ -0,0 +1,249 @@
+//! Three-way merges of trees, similar to `git merge-tree --write-tree`, and updating the worktree with their result.
+#![allow(clippy::result_large_err)]
+use gix_hash::ObjectId;
+use gix_object::tree::EntryMode;
+use gix_odb::FindExt;
+
+use crate::bstr::{BStr, BString, ByteSlice};
+
+/// Options for use in [`Repository::merge_trees()`][crate::Repository::merge_trees()].
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// How to merge the contents of files changed by both sides, and how to write their conflicts.
+    pub text: gix_diff::blob::merge::Options,
+    /// The label to write after the `|||||||` conflict marker when using one of the `diff3` conflict styles.
+    pub ancestor_label: Option<BString>,
+    /// The label to write after the `<<<<<<<` conflict marker, typically the name of our side, like `HEAD`.
+    pub our_label: Option<BString>,
+    /// The label to write after the `>>>>>>>` conflict marker, typically the name of their side.
+    pub their_label: Option<BString>,
+}
+
+/// An entry of one of the merged trees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    /// The kind of the entry.
+    pub mode: EntryMode,
+    /// The id of the object the entry points to.
+    pub id: ObjectId,
+}
+
+/// A path that couldn't be merged cleanly, along with the entries each side has at that path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The path relative to the root of the merged trees.
+    pub path: BString,
+    /// The entry in the common ancestor, or `None` if it didn't exist.
+    pub ancestor: Option<Entry>,
+    /// The entry on our side, or `None` if we deleted it.
+    pub ours: Option<Entry>,
+    /// The entry on their side, or `None` if they deleted it.
+    pub theirs: Option<Entry>,
+}
+
+impl Conflict {
+    /// Return the entries of this conflict that aren't trees along with their stage, `1` for the ancestor, `2` for ours and `3`
+    /// for theirs, as they are written into the index.
+    pub fn stages(&self) -> impl Iterator<Item = (gix_index::entry::Stage, &Entry)> {
+        [(1, &self.ancestor), (2, &self.ours), (3, &self.theirs)]
+            .into_iter()
+            .filter_map(|(stage, entry)| entry.as_ref().map(|entry| (stage, entry)))
+            .filter(|(_, entry)| !entry.mode.is_tree())
+    }
+}
+
+/// The outcome of [`Repository::merge_trees()`][crate::Repository::merge_trees()].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The merged tree, which was written to the object database.
+    ///
+    /// Files whose contents couldn't be merged cleanly contain conflict markers, and for all other conflicts it contains
+    /// the entry of our side, or the one of their side if we deleted it.
+    pub tree: ObjectId,
+    /// All conflicts in the order their paths appear in the merged tree, or an empty list if the merge was clean.
+    pub conflicts: Vec<Conflict>,
+}
+
+impl Outcome {
+    /// Return `true` if there were conflicts.
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+
+    /// Create an index from the merged tree in `repo`, with each conflicting path represented by the entries of
+    /// the [conflict stages][Conflict::stages()] instead, like `git merge` leaves it.
+    pub fn index(&self, repo: &crate::Repository) -> Result<gix_index::State, gix_traverse::tree::breadthfirst::Error> {
+        let mut index = gix_index::State::from_tree(&self.tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+        self.replace_conflicts_with_stages(&mut index);
+        Ok(index)
+    }
+
+    /// Replace the entries of all conflicting paths in `index` with the entries of their stages.
+    pub(crate) fn replace_conflicts_with_stages(&self, index: &mut gix_index::State) {
+        let conflicting_paths: std::collections::BTreeSet<&BStr> = self
+            .conflicts
+            .iter()
+            .filter(|conflict| conflict.stages().next().is_some())
+            .map(|conflict| conflict.path.as_bstr())
+            .collect();
+        index.remove_entries(|path, _| conflicting_paths.contains(path));
+        for conflict in &self.conflicts {
+            for (stage, entry) in conflict.stages() {
+                index.dangerously_push_entry(
+                    Default::default(),
+                    entry.id,
+                    gix_index::entry::Flags::from_bits_truncate(stage << 12),
+                    index_mode(entry.mode),
+                    conflict.path.as_ref(),
+                );
+            }
+        }
+        index.sort_entries();
+    }
+}
+
+fn index_mode(mode: EntryMode) -> gix_index::entry::Mode {
+    match mode {
+        EntryMode::Tree => gix_index::entry::Mode::DIR,
+        EntryMode::Blob => gix_index::entry::Mode::FILE,
+        EntryMode::BlobExecutable => gix_index::entry::Mode::FILE_EXECUTABLE,
+        EntryMode::Link => gix_index::entry::Mode::SYMLINK,
+        EntryMode::Commit => gix_index::entry::Mode::COMMIT,
+    }
+}
+
+/// The error returned by [`Repository::merge_trees()`][crate::Repository::merge_trees()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToTree(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeTree(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+}
+
+///
+pub mod checkout {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use gix_hash::ObjectId;
+    use gix_odb::FindExt;
+
+    use crate::bstr::{BStr, BString, ByteSlice};
+
+    /// The error returned by [`Repository::checkout_merge()`][crate::Repository::checkout_merge()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Repository at \"{}\" is a bare repository and has no worktree to update", git_dir.display())]
+        BareRepository { git_dir: std::path::PathBuf },
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error("Could not create an index from a tree, or expand the directories of the sparse index")]
+        IndexFromTree(#[from] gix_traverse::tree::breadthfirst::Error),
+        #[error("The index has unresolved conflicts")]
+        UnmergedIndex,
+        #[error("The local changes to '{path}' would be overwritten by the merge")]
+        LocalChanges { path: BString },
+        #[error("The untracked file '{path}' would be overwritten by the merge")]
+        UntrackedFile { path: BString },
+        #[error(transparent)]
+        CheckoutOptions(#[from] crate::config::checkout_options::Error),
+        #[error("Could not read or remove '{}'", path.display())]
+        Io {
+            path: std::path::PathBuf,
+            source: std::io::Error,
+        },
+        #[error(transparent)]
+        IndexCheckout(
+            #[from]
+            gix_worktree::index::checkout::Error<gix_odb::find::existing_object::Error<gix_odb::store::find::Error>>,
+        ),
+        #[error(transparent)]
+        WriteIndex(#[from] gix_index::file::write::Error),
+    }
+
+    /// Return the index representation of `tree`.
+    pub(crate) fn tree_index(
+        repo: &crate::Repository,
+        tree: ObjectId,
+    ) -> Result<gix_index::State, gix_traverse::tree::breadthfirst::Error> {
+        gix_index::State::from_tree(&tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
+    }
+
+    /// Return the id and mode of all entries in `state` by their path.
+    pub(crate) fn entries_by_path(state: &gix_index::State) -> BTreeMap<BString, (ObjectId, gix_index::entry::Mode)> {
+        state
+            .entries()
+            .iter()
+            .map(|entry| (entry.path(state).to_owned(), (entry.id, entry.mode)))
+            .collect()
+    }
+
+    /// Remove the entries at `removed` paths from `index` along with their files in `work_dir`, and update the entries and
+    /// files at `updated` paths to match `target`, a tree in its index representation, without checking for local changes.
+    pub(crate) fn update_paths(
+        repo: &crate::Repository,
+        index: &mut gix_index::State,
+        work_dir: &std::path::Path,
+        target: &gix_index::State,
+        removed: Vec<&BString>,
+        updated: Vec<&BString>,
+    ) -> Result<(), Error> {
+        let removed_paths: BTreeSet<&BStr> = removed.iter().map(|path| path.as_bstr()).collect();
+        index.remove_entries(|path, _| removed_paths.contains(path));
+        for path in removed {
+            let file = work_dir.join(gix_path::from_bstr(path.as_bstr()));
+            match std::fs::remove_file(&file) {
+                Ok(()) => {
+                    for dir in file.ancestors().skip(1).take_while(|dir| *dir != work_dir) {
+                        if std::fs::remove_dir(dir).is_err() {
+                            break;
+                        }
+                    }
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(source) => return Err(Error::Io { path: file, source }),
+            }
+        }
+        if !updated.is_empty() {
+            let mut opts = repo.config.checkout_options(repo.git_dir())?;
+            opts.sparse_checkout = None;
+            let objects = repo.objects.clone().into_arc().map_err(|source| Error::Io {
+                path: repo.objects.store_ref().path().to_owned(),
+                source,
+            })?;
+            gix_worktree::index::checkout::paths_from_tree(
+                index,
+                target,
+                updated,
+                work_dir,
+                move |oid, buf| objects.find_blob(oid, buf),
+                &mut crate::progress::Discard,
+                &mut crate::progress::Discard,
+                opts,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write `index` without its tree-cache extension, which doesn't match its entries anymore after they were changed.
+    pub(crate) fn write_index(index: &mut gix_index::File) -> Result<(), gix_index::file::write::Error> {
+        index.write(gix_index::write::Options {
+            extensions: gix_index::write::Extensions::Given {
+                tree_cache: false,
+                untracked_cache: true,
+                fs_monitor: true,
+                end_of_index_entry: true,
+            },
+            version: Some(index.version()),
+            ..Default::default()
+        })
+    }
+}
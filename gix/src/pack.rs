@@ -0,0 +1,349 @@
+//! Create packs from the objects in a repository.
+use std::{
+    convert::TryInto,
+    ffi::OsStr,
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_hash::{oid, ObjectId};
+use gix_odb::FindExt;
+
+use crate::{Progress, Repository};
+
+///
+pub mod write {
+    /// The error returned when writing a pack with all objects reachable from a set of tips.
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::Error),
+        #[error(transparent)]
+        Walk(#[from] crate::revision::walk::Error),
+        #[error(transparent)]
+        Traverse(#[from] gix_traverse::commit::ancestors::Error),
+        #[error(transparent)]
+        Decode(#[from] gix_object::decode::Error),
+        #[error("Could not prepare the object database for creating a pack")]
+        Io(#[from] std::io::Error),
+        #[error("Could not create the pack")]
+        CreatePack(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+        #[error("The operation was interrupted")]
+        Interrupted,
+    }
+}
+
+/// Write a pack with all objects reachable from `tips` that aren't reachable from `hidden_ids` to `out`,
+/// and return the amount of objects in it.
+///
+/// If a pack has a bitmap, it's used to find the reachable objects instead of traversing the whole commit graph.
+/// See [`write_objects()`] for the meaning of `ofs_delta`.
+pub(crate) fn write_reachable<P>(
+    repo: &Repository,
+    tips: &[ObjectId],
+    hidden_ids: impl IntoIterator<Item = ObjectId>,
+    ofs_delta: bool,
+    out: &mut dyn Write,
+    progress: &mut P,
+    should_interrupt: &AtomicBool,
+) -> Result<usize, write::Error>
+where
+    P: Progress,
+    P::SubProgress: 'static,
+{
+    if let Some(bitmapped) = Bitmapped::open(repo) {
+        let hidden = bitmapped.reachable(repo, hidden_ids, None, should_interrupt)?;
+        let input = bitmapped
+            .reachable(repo, tips.iter().copied(), Some(&hidden), should_interrupt)?
+            .ids()
+            .filter(|id| !hidden.contains(id))
+            .collect();
+        return write_objects(
+            repo,
+            input,
+            gix_pack::data::output::count::objects::ObjectExpansion::AsIs,
+            &Default::default(),
+            ofs_delta,
+            out,
+            progress,
+            should_interrupt,
+        );
+    }
+
+    let mut hidden = gix_hashtable::HashSet::default();
+    let mut hidden_commits = Vec::new();
+    for id in hidden_ids {
+        if let Some(object) = repo.try_find_object(id)? {
+            hidden.insert(id);
+            if let Ok(commit) = object.peel_to_kind(gix_object::Kind::Commit) {
+                hidden_commits.push(commit.id);
+            }
+        }
+    }
+    for id in repo.rev_walk(hidden_commits).all()? {
+        hidden.insert(id?.detach());
+    }
+
+    let mut input = Vec::new();
+    let mut commits = Vec::new();
+    for tip in tips.iter().filter(|tip| !hidden.contains(*tip)) {
+        let object = match repo.try_find_object(*tip)? {
+            Some(object) => object,
+            None => continue,
+        };
+        match object.kind {
+            gix_object::Kind::Commit => commits.push(*tip),
+            gix_object::Kind::Tag => {
+                input.push(*tip);
+                if let Ok(commit) = object.peel_to_kind(gix_object::Kind::Commit) {
+                    commits.push(commit.id);
+                }
+            }
+            gix_object::Kind::Tree | gix_object::Kind::Blob => input.push(*tip),
+        }
+    }
+    let walk = gix_traverse::commit::Ancestors::filtered(
+        commits,
+        gix_traverse::commit::ancestors::State::default(),
+        |oid, buf| repo.objects.find_commit_iter(oid, buf),
+        |id| !hidden.contains(id),
+    );
+    for id in walk {
+        input.push(id?);
+    }
+
+    write_objects(
+        repo,
+        input,
+        gix_pack::data::output::count::objects::ObjectExpansion::TreeAdditionsComparedToAncestor,
+        &hidden,
+        ofs_delta,
+        out,
+        progress,
+        should_interrupt,
+    )
+}
+
+/// Write a pack with all objects in `input`, expanded according to `expansion`, to `out` and return the amount of objects in it.
+/// Objects in `hidden` are left out, along with the trees of hidden commits.
+///
+/// Deltas of packed objects are copied if their base object is also part of the pack, so the pack is never thin.
+/// If `ofs_delta` is `true`, deltas refer to their base by its offset in the pack, which is smaller but has to be supported
+/// by the receiver, or by the id of their base otherwise.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn write_objects<P>(
+    repo: &Repository,
+    input: Vec<ObjectId>,
+    expansion: gix_pack::data::output::count::objects::ObjectExpansion,
+    hidden: &gix_hashtable::HashSet,
+    ofs_delta: bool,
+    out: &mut dyn Write,
+    progress: &mut P,
+    should_interrupt: &AtomicBool,
+) -> Result<usize, write::Error>
+where
+    P: Progress,
+    P::SubProgress: 'static,
+{
+    let mut db = repo.objects.clone().into_arc()?;
+    db.prevent_pack_unload();
+    db.ignore_replacements = true;
+    let (mut counts, _) = gix_pack::data::output::count::objects_unthreaded(
+        db.clone(),
+        input.into_iter().map(Ok::<_, std::convert::Infallible>),
+        progress.add_child("counting objects"),
+        should_interrupt,
+        expansion,
+    )
+    .map_err(|err| write::Error::CreatePack(err.into()))?;
+    // Counting additions compared to the ancestor also yields the parents of commits along with their trees,
+    // which aren't needed if the parents are hidden.
+    let mut excluded = gix_hashtable::HashSet::default();
+    for count in counts.iter().filter(|count| hidden.contains(&count.id)) {
+        excluded.insert(count.id);
+        if let Some(object) = repo.try_find_object(count.id)? {
+            if let Some(tree_id) = object.try_into_commit().ok().and_then(|commit| commit.tree_id().ok()) {
+                excluded.insert(tree_id.detach());
+            }
+        }
+    }
+    counts.retain(|count| !excluded.contains(&count.id));
+    let num_objects = counts.len();
+    let entries = gix_features::parallel::InOrderIter::from(gix_pack::data::output::entry::iter_from_counts(
+        counts,
+        db,
+        progress.add_child("creating entries"),
+        Default::default(),
+    ));
+    let pack = gix_pack::data::output::bytes::FromEntriesIter::new(
+        entries,
+        out,
+        num_objects
+            .try_into()
+            .map_err(|err: std::num::TryFromIntError| write::Error::CreatePack(err.into()))?,
+        gix_pack::data::Version::V2,
+        repo.object_hash(),
+    )
+    .with_ofs_delta(ofs_delta);
+    for written in pack {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(write::Error::Interrupted);
+        }
+        written.map_err(|err| write::Error::CreatePack(err.into()))?;
+    }
+    Ok(num_objects)
+}
+
+/// A pack along with its bitmap, to quickly find all objects reachable from the commits it has bitmaps for.
+struct Bitmapped {
+    bundle: gix_pack::Bundle,
+    bitmap: gix_pack::bitmap::File,
+    /// The indices of all objects in the pack index, in the order they appear in the pack and thus in bitmaps.
+    pack_order: Vec<gix_pack::index::EntryIndex>,
+    /// The position of each object in the pack, by its index in the pack index.
+    positions: Vec<usize>,
+}
+
+/// The set of objects reachable from a set of tips, with objects in the pack of a [`Bitmapped`] being stored as bits.
+struct Reachable<'a> {
+    bitmapped: &'a Bitmapped,
+    packed: Vec<u64>,
+    others: gix_hashtable::HashSet,
+}
+
+impl Bitmapped {
+    /// Open the first pack in the object database of `repo` with a bitmap that belongs to it, or return `None` if there is none.
+    ///
+    /// Bitmaps that can't be opened are ignored, just like `git` does.
+    fn open(repo: &Repository) -> Option<Self> {
+        let pack_dir = repo.objects.store_ref().path().join("pack");
+        let mut bitmap_paths: Vec<_> = std::fs::read_dir(pack_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension() == Some(OsStr::new("bitmap")))
+            .collect();
+        bitmap_paths.sort();
+        bitmap_paths.into_iter().find_map(|path| {
+            let bundle = gix_pack::Bundle::at(path.with_extension("idx"), repo.object_hash()).ok()?;
+            let bitmap = gix_pack::bitmap::File::at(&path, repo.object_hash()).ok()?;
+            if bitmap.pack_checksum() != bundle.pack.checksum().as_ref() {
+                return None;
+            }
+            let pack_order = bundle.index.indices_in_pack_order();
+            let mut positions = vec![0; pack_order.len()];
+            for (position, index) in pack_order.iter().enumerate() {
+                positions[*index as usize] = position;
+            }
+            Some(Bitmapped {
+                bundle,
+                bitmap,
+                pack_order,
+                positions,
+            })
+        })
+    }
+
+    /// Return all objects reachable from `tips`, using bitmaps wherever possible and traversing the objects in `repo` otherwise.
+    ///
+    /// Objects contained in `exclude` are neither added nor traversed. Objects that don't exist, like the parents of shallow commits,
+    /// are skipped.
+    fn reachable(
+        &self,
+        repo: &Repository,
+        tips: impl IntoIterator<Item = ObjectId>,
+        exclude: Option<&Reachable<'_>>,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Reachable<'_>, write::Error> {
+        let mut reachable = Reachable {
+            bitmapped: self,
+            packed: vec![0; (self.pack_order.len() + 63) / 64],
+            others: Default::default(),
+        };
+        let mut stack: Vec<_> = tips.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(write::Error::Interrupted);
+            }
+            if reachable.contains(&id) || exclude.map_or(false, |exclude| exclude.contains(&id)) {
+                continue;
+            }
+            if let Some(bitmap) = self
+                .bundle
+                .index
+                .lookup(id)
+                .and_then(|index| self.bitmap.reachable_from(index))
+            {
+                for (word, other) in reachable.packed.iter_mut().zip(bitmap) {
+                    *word |= other;
+                }
+                continue;
+            }
+            let object = match repo.try_find_object(id)? {
+                Some(object) => object,
+                None => continue,
+            };
+            reachable.insert(&id);
+            match object.kind {
+                gix_object::Kind::Commit => {
+                    let mut commit = gix_object::CommitRefIter::from_bytes(&object.data);
+                    stack.push(commit.tree_id()?);
+                    stack.extend(commit.parent_ids());
+                }
+                gix_object::Kind::Tag => {
+                    stack.push(gix_object::TagRefIter::from_bytes(&object.data).target_id()?);
+                }
+                gix_object::Kind::Tree => {
+                    for entry in gix_object::TreeRefIter::from_bytes(&object.data) {
+                        let entry = entry?;
+                        match entry.mode {
+                            gix_object::tree::EntryMode::Commit => {}
+                            gix_object::tree::EntryMode::Tree => stack.push(entry.oid.to_owned()),
+                            _ => {
+                                if exclude.map_or(true, |exclude| !exclude.contains(entry.oid)) {
+                                    reachable.insert(entry.oid);
+                                }
+                            }
+                        }
+                    }
+                }
+                gix_object::Kind::Blob => {}
+            }
+        }
+        Ok(reachable)
+    }
+}
+
+impl Reachable<'_> {
+    fn contains(&self, id: &oid) -> bool {
+        match self.bitmapped.bundle.index.lookup(id) {
+            Some(index) => {
+                let position = self.bitmapped.positions[index as usize];
+                self.packed[position / 64] & (1u64 << (position % 64)) != 0
+            }
+            None => self.others.contains(id),
+        }
+    }
+
+    fn insert(&mut self, id: &oid) {
+        match self.bitmapped.bundle.index.lookup(id) {
+            Some(index) => {
+                let position = self.bitmapped.positions[index as usize];
+                self.packed[position / 64] |= 1u64 << (position % 64);
+            }
+            None => {
+                self.others.insert(id.to_owned());
+            }
+        }
+    }
+
+    fn ids(&self) -> impl Iterator<Item = ObjectId> + '_ {
+        let packed = self.packed.iter().enumerate().flat_map(move |(word_index, word)| {
+            (0..64).filter(move |bit| word & (1u64 << bit) != 0).map(move |bit| {
+                let index = self.bitmapped.pack_order[word_index * 64 + bit];
+                self.bitmapped.bundle.index.oid_at_index(index).to_owned()
+            })
+        });
+        packed.chain(self.others.iter().copied())
+    }
+}
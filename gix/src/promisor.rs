@@ -0,0 +1,26 @@
+//! Obtain objects omitted from partial clones from the promisor remote they were cloned from, on demand.
+//!
+//! See [`Repository::promisor_remote()`][crate::Repository::promisor_remote()] and
+//! [`Repository::fetch_missing_objects()`][crate::Repository::fetch_missing_objects()].
+
+///
+#[cfg(feature = "blocking-network-client")]
+pub mod fetch {
+    /// The error returned by [`Repository::fetch_missing_objects()`][crate::Repository::fetch_missing_objects()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("There is no remote configured with 'remote.<name>.promisor = true' to fetch missing objects from")]
+        NoPromisorRemote,
+        #[error(transparent)]
+        FindRemote(#[from] crate::remote::find::Error),
+        #[error(transparent)]
+        RefSpec(#[from] gix_refspec::parse::Error),
+        #[error(transparent)]
+        Connect(#[from] crate::remote::connect::Error),
+        #[error(transparent)]
+        PrepareFetch(#[from] crate::remote::fetch::prepare::Error),
+        #[error(transparent)]
+        Fetch(#[from] crate::remote::fetch::Error),
+    }
+}
@@ -0,0 +1,83 @@
+//! Replaying commits onto a new base, similar to `git rebase` without `--interactive`.
+//!
+//! See [`Repository::rebase()`][crate::Repository::rebase()].
+#![allow(clippy::result_large_err)]
+use gix_hash::ObjectId;
+
+/// Options for use in [`Repository::rebase()`][crate::Repository::rebase()].
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// The commit to replay the commits onto, like `git rebase --onto`, or `None` to replay them onto the upstream commit.
+    pub onto: Option<ObjectId>,
+    /// How to merge the contents of files when replaying each commit. Labels that aren't set are chosen like `git` does,
+    /// with `HEAD` for the commits replayed so far and the abbreviated id and summary of the commit to replay.
+    pub merge: crate::merge::Options,
+}
+
+/// A commit that was replayed by [`Repository::rebase()`][crate::Repository::rebase()].
+#[derive(Debug, Clone)]
+pub struct Step<'repo> {
+    /// The id of the commit that was replayed.
+    pub commit: ObjectId,
+    /// The outcome of replaying it, which is [empty][crate::cherry_pick::Outcome::Empty] if its changes are already present,
+    /// in which case it is dropped.
+    pub outcome: crate::cherry_pick::Outcome<'repo>,
+}
+
+/// The outcome of [`Repository::rebase()`][crate::Repository::rebase()].
+#[derive(Debug, Clone)]
+pub enum Outcome<'repo> {
+    /// `HEAD` already contains the commit to replay onto and nothing else but the commits to replay, so nothing was changed.
+    UpToDate,
+    /// All commits were replayed, and the branch `HEAD` pointed to was updated to point to the last of them.
+    Finished {
+        /// The replayed commits in the order they were applied.
+        steps: Vec<Step<'repo>>,
+    },
+    /// The last of the `steps` couldn't be replayed without conflicts, which are left in the index and the worktree for
+    /// resolution, with `HEAD` detached at the commits replayed so far.
+    ///
+    /// Like `git`, the state of the rebase is written into the `rebase-merge` directory along with `REBASE_HEAD`, so
+    /// `git rebase --continue` can continue it once the conflicts are resolved, or `git rebase --abort` can undo it.
+    Stopped {
+        /// The replayed commits in the order they were applied, with the conflicting one last.
+        steps: Vec<Step<'repo>>,
+    },
+}
+
+/// The error returned by [`Repository::rebase()`][crate::Repository::rebase()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Cannot start a new operation while another one is in progress: {0:?}")]
+    InProgress(crate::state::InProgress),
+    #[error(transparent)]
+    HeadCommit(#[from] crate::reference::head_commit::Error),
+    #[error(transparent)]
+    HeadName(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    HeadId(#[from] crate::reference::head_id::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToCommit(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    Walk(#[from] crate::revision::walk::Error),
+    #[error(transparent)]
+    Traverse(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    Checkout(#[from] crate::merge::checkout::Error),
+    #[error(transparent)]
+    ApplyCommit(#[from] crate::cherry_pick::Error),
+    #[error(transparent)]
+    ReferenceEdit(#[from] crate::reference::edit::Error),
+    #[error("Could not write or remove '{}'", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+}
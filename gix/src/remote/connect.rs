@@ -24,8 +24,8 @@ mod error {
         Connect(#[from] gix_protocol::transport::client::connect::Error),
         #[error("The {} url was missing - don't know where to establish a connection to", direction.as_str())]
         MissingUrl { direction: remote::Direction },
-        #[error("Protocol named {given:?} is not a valid protocol. Choose between 1 and 2")]
-        UnknownProtocol { given: BString },
+        #[error("The configured protocol version is not valid. Choose between 0, 1 and 2")]
+        UnknownProtocol(#[from] config::key::GenericError),
         #[error("Could not verify that \"{}\" url is a valid git directory before attempting to use it", url.to_bstring())]
         FileUrl {
             source: Box<gix_discover::is_git::Error>,
@@ -51,6 +51,8 @@ impl<'repo> Remote<'repo> {
     ///
     /// Note that this method expects the `transport` to be created by the user, which would involve the [`url()`][Self::url()].
     /// It's meant to be used when async operation is needed with runtimes of the user's choice.
+    ///
+    /// The returned connection is meant for fetching, as its [direction][Connection::direction()] is [`Direction::Fetch`][crate::remote::Direction::Fetch].
     pub fn to_connection_with_transport<T, P>(&self, transport: T, progress: P) -> Connection<'_, 'repo, T, P>
     where
         T: Transport,
@@ -62,13 +64,57 @@ impl<'repo> Remote<'repo> {
             transport_options: None,
             transport,
             progress,
+            direction: crate::remote::Direction::Fetch,
         }
     }
 
+    /// Create a new connection which communicates through the given `read` and `write` channels, with `progress` to indicate changes.
+    ///
+    /// This allows to tunnel the git protocol through channels of the caller's choice, like the pipes of a `git upload-pack`
+    /// process spawned by other means, and is useful for deterministic protocol tests as well.
+    /// The other side is expected to serve the remote repository already, so no daemon request will be sent.
+    ///
+    /// As no connection is dialed, the url isn't checked against the `protocol.allow` configuration as the caller is in control.
+    /// If set, the url for `direction` is still passed to authentication helpers, and `protocol.version` is respected as well.
+    #[cfg(feature = "blocking-network-client")]
+    pub fn to_connection_with_io<R, W, P>(
+        &self,
+        direction: crate::remote::Direction,
+        read: R,
+        write: W,
+        progress: P,
+    ) -> Result<Connection<'_, 'repo, gix_protocol::transport::client::git::Connection<R, W>, P>, Error>
+    where
+        R: std::io::Read,
+        W: std::io::Write,
+        P: Progress,
+    {
+        use gix_protocol::transport::client::git;
+        let url = self.url(direction);
+        let transport = git::Connection::new(
+            read,
+            write,
+            self.protocol_version()?,
+            url.map(|url| url.path.clone()).unwrap_or_default(),
+            None::<(String, Option<u16>)>,
+            git::ConnectMode::Process,
+        )
+        .custom_url(url.map(|url| url.to_bstring()));
+        let mut con = self.to_connection_with_transport(transport, progress);
+        con.direction = direction;
+        Ok(con)
+    }
+
     /// Connect to the url suitable for `direction` and return a handle through which operations can be performed.
     ///
+    /// Only operations suitable for `direction` can be performed on the returned connection, so a connection for
+    /// [pushing][crate::remote::Direction::Push] will refuse to [fetch][Connection::prepare_fetch()] or to
+    /// obtain a [ref-map][Connection::ref_map()].
+    ///
     /// Note that the `protocol.version` configuration key affects the transport protocol used to connect,
-    /// with `2` being the default.
+    /// with `2` being the default. It can be overridden for named remotes with `remote.<name>.protocolVersion`.
+    /// Servers not supporting the desired version will downgrade it, and the version that was actually negotiated
+    /// is available through [`Connection::protocol_version()`] once the handshake is done.
     ///
     /// The transport used for connection can be configured via `transport_mut().configure()` assuming the actually
     /// used transport is well known. If that's not the case, the transport can be created by hand and passed to
@@ -98,7 +144,9 @@ impl<'repo> Remote<'repo> {
             },
         )
         .await?;
-        Ok(self.to_connection_with_transport(transport, progress))
+        let mut con = self.to_connection_with_transport(transport, progress);
+        con.direction = direction;
+        Ok(con)
     }
 
     /// Produce the sanitized URL and protocol version to use as obtained by querying the repository configuration.
@@ -134,26 +182,7 @@ impl<'repo> Remote<'repo> {
             Ok(url)
         }
 
-        use gix_protocol::transport::Protocol;
-        let version = self
-            .repo
-            .config
-            .resolved
-            .integer("protocol", None, "version")
-            .unwrap_or(Ok(2))
-            .map_err(|err| Error::UnknownProtocol { given: err.input })
-            .and_then(|num| {
-                Ok(match num {
-                    1 => Protocol::V1,
-                    2 => Protocol::V2,
-                    num => {
-                        return Err(Error::UnknownProtocol {
-                            given: num.to_string().into(),
-                        })
-                    }
-                })
-            })?;
-
+        let version = self.protocol_version()?;
         let url = self.url(direction).ok_or(Error::MissingUrl { direction })?.to_owned();
         if !self.repo.config.url_scheme()?.allow(&url.scheme) {
             return Err(Error::ProtocolDenied {
@@ -163,4 +192,29 @@ impl<'repo> Remote<'repo> {
         }
         Ok((sanitize(url)?, version))
     }
+
+    /// Return the protocol version to desire, with `remote.<name>.protocolVersion` taking precedence over `protocol.version`.
+    fn protocol_version(&self) -> Result<gix_protocol::transport::Protocol, Error> {
+        use crate::config::tree::{Key, Protocol, Remote};
+        let config = &self.repo.config.resolved;
+        let mut trusted_only = self.repo.filter_config_section();
+        let remote_version = self.name.as_ref().and_then(|name| {
+            config
+                .integer_filter(
+                    "remote",
+                    Some(name.as_bstr()),
+                    Remote::PROTOCOL_VERSION.name,
+                    &mut trusted_only,
+                )
+                .map(|value| (value, &Remote::PROTOCOL_VERSION))
+        });
+        match remote_version.or_else(|| {
+            config
+                .integer_filter_by_key(Protocol::VERSION.logical_name().as_str(), &mut trusted_only)
+                .map(|value| (value, &Protocol::VERSION))
+        }) {
+            Some((value, key)) => Ok(key.try_into_protocol_version(value)?),
+            None => Ok(Default::default()),
+        }
+    }
 }
@@ -52,6 +52,11 @@ impl<'a, 'repo, T, P> Connection<'a, 'repo, T, P> {
             self.remote.repo.config_snapshot().credential_helpers(url)?;
         Ok(Box::new(move |action| cascade.invoke(action, prompt_opts.clone())) as AuthenticateFn<'_>)
     }
+    /// Return the direction this connection was established for, which determines the operations it supports.
+    pub fn direction(&self) -> crate::remote::Direction {
+        self.direction
+    }
+
     /// Return the underlying remote that instantiate this connection.
     pub fn remote(&self) -> &Remote<'repo> {
         self.remote
@@ -65,3 +70,14 @@ impl<'a, 'repo, T, P> Connection<'a, 'repo, T, P> {
         &mut self.transport
     }
 }
+
+impl<'a, 'repo, T, P> Connection<'a, 'repo, T, P>
+where
+    T: gix_protocol::transport::client::TransportWithoutIO,
+{
+    /// Return the protocol version the remote responded with during the handshake, which may be lower than the
+    /// configured `protocol.version` if the server doesn't support it, or `None` if no handshake was performed yet.
+    pub fn protocol_version(&self) -> Option<gix_protocol::transport::Protocol> {
+        self.transport.actual_protocol_version()
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    io::BufRead,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_hash::ObjectId;
+use gix_protocol::{
+    bundle_uri::{Bundle, Mode},
+    transport::client::{Capabilities, Transport},
+};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    remote::fetch::Error,
+    Progress, Repository,
+};
+
+type BoxedError = Box<dyn std::error::Error>;
+/// The tips of an unbundled bundle along with the `.keep` file of its pack.
+type Unbundled = (Vec<ObjectId>, Option<PathBuf>);
+
+/// The result of applying all bundles advertised by the remote.
+#[derive(Default)]
+pub(crate) struct Outcome {
+    /// The tips of all bundles that were unbundled, which are known to be present in the object database along with their history.
+    pub tips: Vec<ObjectId>,
+    /// The `.keep` files of the packs written from bundles, to be removed once references are updated.
+    pub keep_paths: Vec<PathBuf>,
+}
+
+/// Obtain the list of bundles from the server via `transport` and unbundle them into the object database of `repo`,
+/// resolving relative bundle uris against `remote_url`.
+///
+/// Failing to download or unbundle a bundle isn't fatal as the objects will then be fetched from the remote instead,
+/// but failing to obtain the list or being interrupted is.
+#[gix_protocol::maybe_async::maybe_async]
+pub(crate) async fn apply(
+    transport: &mut impl Transport,
+    capabilities: &Capabilities,
+    repo: &Repository,
+    remote_url: &gix_url::Url,
+    remote_name: Option<&BStr>,
+    progress: &mut impl Progress,
+    should_interrupt: &AtomicBool,
+) -> Result<Outcome, Error> {
+    let list = gix_protocol::bundle_uri(transport, capabilities, progress).await?;
+    let mut out = Outcome::default();
+    for bundle in ordered(list.bundles, &list.heuristic) {
+        progress.set_name(format!("bundle {}", bundle.id));
+        let res = unbundle(&bundle, repo, remote_url, remote_name, progress, should_interrupt);
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        match res {
+            Ok(Some((tips, keep_path))) => {
+                out.tips.extend(tips);
+                out.keep_paths.extend(keep_path);
+                if list.mode == Mode::Any {
+                    break;
+                }
+            }
+            Ok(None) => {
+                progress.info(format!(
+                    "Skipped bundle at {:?} as it needs objects we don't have",
+                    bundle.uri
+                ));
+            }
+            Err(err) => {
+                progress.fail(format!("Could not apply bundle at {:?}: {err}", bundle.uri));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Put bundles with the lowest creation token first if the `creationToken` heuristic is used, as later bundles depend on them.
+fn ordered(mut bundles: Vec<Bundle>, heuristic: &Option<BString>) -> Vec<Bundle> {
+    if heuristic.as_ref().map_or(false, |h| h == "creationToken") {
+        bundles.sort_by_key(|b| b.creation_token.unwrap_or(u64::MAX));
+    }
+    bundles
+}
+
+/// Download `bundle` and write its pack into the object database, returning its tips and the path to the `.keep` file
+/// of the written pack, or `None` if its prerequisites aren't present.
+fn unbundle(
+    bundle: &Bundle,
+    repo: &Repository,
+    remote_url: &gix_url::Url,
+    remote_name: Option<&BStr>,
+    progress: &mut impl Progress,
+    should_interrupt: &AtomicBool,
+) -> Result<Option<Unbundled>, BoxedError> {
+    let read = open(bundle.uri.as_ref(), repo, remote_url, remote_name)?;
+    match repo.unbundle(
+        read,
+        progress.add_child(format!("unbundle {}", bundle.id)),
+        should_interrupt,
+    ) {
+        Ok(outcome) => Ok(Some((
+            outcome.header.refs.into_iter().map(|r| r.id).collect(),
+            outcome.write_pack_bundle.keep_path,
+        ))),
+        Err(crate::bundle::unbundle::Error::MissingPrerequisite { .. }) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Open the bundle at `uri` for reading, which is resolved relative to `remote_url` unless it's absolute.
+#[cfg_attr(
+    not(any(
+        feature = "blocking-http-transport-reqwest",
+        feature = "blocking-http-transport-curl"
+    )),
+    allow(unused_variables)
+)]
+fn open(
+    uri: &BStr,
+    repo: &Repository,
+    remote_url: &gix_url::Url,
+    remote_name: Option<&BStr>,
+) -> Result<Box<dyn BufRead>, BoxedError> {
+    let uri: BString = if uri.contains_str("://") {
+        uri.into()
+    } else {
+        let mut base = remote_url.to_bstring();
+        while base.ends_with(b"/") {
+            base.pop();
+        }
+        base.push_byte(b'/');
+        base.push_str(uri.trim_start_with(|c| c == '/'));
+        base
+    };
+    let url = gix_url::parse(uri.as_ref())?;
+    match url.scheme {
+        gix_url::Scheme::File => Ok(Box::new(std::io::BufReader::new(std::fs::File::open(
+            gix_path::from_bstr(url.path.as_bstr()),
+        )?))),
+        #[cfg(any(
+            feature = "blocking-http-transport-reqwest",
+            feature = "blocking-http-transport-curl"
+        ))]
+        gix_url::Scheme::Http | gix_url::Scheme::Https => {
+            use gix_protocol::transport::client::http::{self, Http};
+            let url = url.to_bstring().to_string();
+            let mut client = http::Impl::default();
+            if let Some(options) = repo.transport_options(url.as_bytes().as_bstr(), remote_name)? {
+                client.configure(&*options).map_err(|err| -> BoxedError { err })?;
+            }
+            let response = client.get(&url, &url, std::iter::empty::<&str>())?;
+            Ok(Box::new(response.body))
+        }
+        scheme => Err(format!("Bundles can't be downloaded via {}", scheme.as_str()).into()),
+    }
+}
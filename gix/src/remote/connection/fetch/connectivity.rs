@@ -0,0 +1,265 @@
+use std::{
+    collections::BinaryHeap,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_hash::ObjectId;
+use gix_odb::Find;
+
+use crate::Repository;
+
+/// The error returned by the connectivity check after receiving a pack.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Object {id} is missing even though it is reachable from {tip} which was just fetched - the received pack is incomplete")]
+    MissingObject { id: ObjectId, tip: ObjectId },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::Error),
+    #[error(transparent)]
+    FindExistingObject(#[from] crate::object::find::existing::Error),
+    #[error("Could not decode object {id}")]
+    Decode {
+        id: ObjectId,
+        source: gix_object::decode::Error,
+    },
+    #[error(transparent)]
+    ReferenceIter(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    ReferenceIterInit(#[from] crate::reference::iter::init::Error),
+    #[error("Interrupted")]
+    Interrupted,
+}
+
+/// Assure that all objects reachable from `tips` are present in `repo`, similar to what `git rev-list --objects <tips> --not --all`
+/// would do, to detect incomplete transfers before references are updated to point to them.
+///
+/// Objects reachable from existing references in `repo` are assumed to be complete and are hidden. To avoid traversing the whole
+/// history, commits are visited by date and the traversal stops once only hidden commits are left, so only the trees of the
+/// hidden commits seen until then are used to hide trees and blobs.
+/// The traversal doesn't go beyond the parents of `shallow_commits`. Blobs are only checked for existence, and if `repo` is a
+/// partial clone, trees and blobs may be missing as the promisor remote may have omitted them.
+pub(crate) fn check(
+    repo: &Repository,
+    tips: impl IntoIterator<Item = ObjectId>,
+    shallow_commits: &gix_hashtable::HashSet,
+    should_interrupt: &AtomicBool,
+) -> Result<(), Error> {
+    let mut walk = Walk {
+        repo,
+        commits: Default::default(),
+        queue: BinaryHeap::new(),
+        num_queued_visible: 0,
+        buf: Vec::new(),
+    };
+    let mut hidden = gix_hashtable::HashSet::default();
+    for reference in repo.references()?.all()?.filter_map(Result::ok) {
+        if let Some(id) = reference.target().try_id() {
+            walk.peel_tags(id.to_owned(), true, id.to_owned(), &mut hidden)?;
+        }
+    }
+    let mut objects = Vec::new();
+    for tip in tips {
+        if let Some((id, kind)) = walk.peel_tags(tip, false, tip, &mut hidden)? {
+            objects.push((id, kind, tip));
+        }
+    }
+
+    let mut hidden_trees = Vec::new();
+    while walk.num_queued_visible != 0 {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        let (_time, id) = walk.queue.pop().expect("visible commits are queued");
+        let commit = walk.commits.get_mut(&id).expect("queued commits are known");
+        commit.queued = false;
+        let (is_hidden, tip) = (commit.hidden, commit.tip);
+        if !is_hidden {
+            walk.num_queued_visible -= 1;
+        }
+        let object = repo.find_object(id)?;
+        let mut iter = object.to_commit_ref_iter();
+        let tree = iter.tree_id().map_err(|source| Error::Decode { id, source })?;
+        let parents: Vec<_> = iter.parent_ids().collect();
+        drop(object);
+        if is_hidden {
+            hidden_trees.push(tree);
+        } else {
+            objects.push((tree, gix_object::Kind::Tree, tip));
+        }
+        if is_hidden || !shallow_commits.contains(&id) {
+            for parent in parents {
+                walk.queue_commit(parent, is_hidden, tip)?;
+            }
+        }
+    }
+    hide_trees(repo, hidden_trees, &mut hidden, should_interrupt)?;
+
+    let may_omit_trees_and_blobs = repo.promisor_remote().is_some();
+    while let Some((id, kind, tip)) = objects.pop() {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        if !hidden.insert(id) {
+            continue;
+        }
+        if kind == gix_object::Kind::Blob || may_omit_trees_and_blobs {
+            if repo.objects.contains(id) {
+                if kind == gix_object::Kind::Blob {
+                    continue;
+                }
+            } else if may_omit_trees_and_blobs {
+                continue;
+            } else {
+                return Err(Error::MissingObject { id, tip });
+            }
+        }
+        let object = repo.try_find_object(id)?.ok_or(Error::MissingObject { id, tip })?;
+        let entries = gix_object::TreeRefIter::from_bytes(&object.data)
+            .entries()
+            .map_err(|source| Error::Decode { id, source })?;
+        objects.extend(entries.into_iter().filter_map(|entry| {
+            let kind = match entry.mode {
+                gix_object::tree::EntryMode::Commit => return None,
+                gix_object::tree::EntryMode::Tree => gix_object::Kind::Tree,
+                _ => gix_object::Kind::Blob,
+            };
+            Some((entry.oid.to_owned(), kind, tip))
+        }));
+    }
+    Ok(())
+}
+
+/// The state of a commit seen during the traversal.
+struct Commit {
+    /// If `true`, the commit is reachable from an existing reference.
+    hidden: bool,
+    /// If `true`, the commit is still in the queue.
+    queued: bool,
+    /// The fetched tip the commit was reached from.
+    tip: ObjectId,
+}
+
+/// A traversal of commits ordered by commit date.
+struct Walk<'repo> {
+    repo: &'repo Repository,
+    commits: gix_hashtable::HashMap<ObjectId, Commit>,
+    queue: BinaryHeap<(u32, ObjectId)>,
+    /// The amount of commits in `queue` which aren't hidden, the traversal is done once there are none.
+    num_queued_visible: usize,
+    buf: Vec<u8>,
+}
+
+impl Walk<'_> {
+    /// Follow the tag at `id` to the object it eventually points to and queue it if it's a commit,
+    /// or return it along with its kind otherwise. Tags are marked `hidden` as they are checked.
+    fn peel_tags(
+        &mut self,
+        mut id: ObjectId,
+        hidden: bool,
+        tip: ObjectId,
+        hidden_objects: &mut gix_hashtable::HashSet,
+    ) -> Result<Option<(ObjectId, gix_object::Kind)>, Error> {
+        loop {
+            if hidden_objects.contains(&id) {
+                return Ok(None);
+            }
+            let object = match self.repo.try_find_object(id)? {
+                Some(object) => object,
+                None if hidden => return Ok(None),
+                None => return Err(Error::MissingObject { id, tip }),
+            };
+            match object.kind {
+                gix_object::Kind::Tag => {
+                    hidden_objects.insert(id);
+                    id = object
+                        .to_tag_ref_iter()
+                        .target_id()
+                        .map_err(|source| Error::Decode { id, source })?;
+                }
+                gix_object::Kind::Commit => {
+                    drop(object);
+                    self.queue_commit(id, hidden, tip)?;
+                    return Ok(None);
+                }
+                kind => {
+                    if hidden {
+                        hidden_objects.insert(id);
+                        return Ok(None);
+                    }
+                    return Ok(Some((id, kind)));
+                }
+            }
+        }
+    }
+
+    /// Queue the commit `id` unless it's known already, in which case it is merely marked `hidden` if it is.
+    /// Missing commits are an error unless they are `hidden`.
+    fn queue_commit(&mut self, id: ObjectId, hidden: bool, tip: ObjectId) -> Result<(), Error> {
+        if let Some(commit) = self.commits.get_mut(&id) {
+            if hidden && !commit.hidden {
+                commit.hidden = true;
+                if commit.queued {
+                    self.num_queued_visible -= 1;
+                }
+            }
+            return Ok(());
+        }
+        let time = match self.repo.objects.try_find(id, &mut self.buf)? {
+            Some(data) => {
+                gix_object::CommitRefIter::from_bytes(data.data)
+                    .committer()
+                    .map_err(|source| Error::Decode { id, source })?
+                    .time
+                    .seconds_since_unix_epoch
+            }
+            None if hidden => return Ok(()),
+            None => return Err(Error::MissingObject { id, tip }),
+        };
+        self.commits.insert(
+            id,
+            Commit {
+                hidden,
+                queued: true,
+                tip,
+            },
+        );
+        if !hidden {
+            self.num_queued_visible += 1;
+        }
+        self.queue.push((time, id));
+        Ok(())
+    }
+}
+
+/// Add all trees and blobs reachable from `trees` to `hidden`, ignoring those that are missing.
+fn hide_trees(
+    repo: &Repository,
+    trees: Vec<ObjectId>,
+    hidden: &mut gix_hashtable::HashSet,
+    should_interrupt: &AtomicBool,
+) -> Result<(), Error> {
+    let mut queue: Vec<_> = trees.into_iter().filter(|id| hidden.insert(*id)).collect();
+    while let Some(id) = queue.pop() {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        // Trees omitted from a partial clone shouldn't be fetched just to hide them.
+        if !repo.objects.contains(id) {
+            continue;
+        }
+        let object = repo.find_object(id)?;
+        let entries = gix_object::TreeRefIter::from_bytes(&object.data)
+            .entries()
+            .map_err(|source| Error::Decode { id, source })?;
+        for entry in entries {
+            if entry.mode != gix_object::tree::EntryMode::Commit
+                && hidden.insert(entry.oid.to_owned())
+                && entry.mode == gix_object::tree::EntryMode::Tree
+            {
+                queue.push(entry.oid.to_owned());
+            }
+        }
+    }
+    Ok(())
+}
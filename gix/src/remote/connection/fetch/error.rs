@@ -1,4 +1,4 @@
-use crate::config;
+use crate::{bstr::BString, config};
 
 /// The error returned by [`receive()`](super::Prepare::receive()).
 #[derive(Debug, thiserror::Error)]
@@ -41,8 +41,32 @@ pub enum Error {
     LockShallowFile(#[from] gix_lock::acquire::Error),
     #[error("Could not obtain configuration to learn if shallow remotes should be rejected")]
     RejectShallowRemoteConfig(#[from] config::boolean::Error),
+    #[error(transparent)]
+    ConnectivityCheck(#[from] super::connectivity::Error),
+    #[error("The server did not provide the ref {name:?} which was explicitly wanted")]
+    WantedRefMissing { name: BString },
     #[error("Receiving objects from shallow remotes is prohibited due to the value of `clone.rejectShallow`")]
     RejectShallowRemote,
+    #[error("Could not obtain configuration to learn if the remote is a promisor")]
+    PromisorConfig(#[source] config::boolean::Error),
+    #[error("The configured partial clone filter is invalid or unsupported")]
+    PartialCloneFilterConfig(#[from] config::key::GenericErrorWithValue),
+    #[error("Failed to mark the received pack as coming from a promisor remote at \"{}\"", path.display())]
+    WritePromisorFile {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Failed to read the repository configuration to record the promisor remote")]
+    ReadPromisorConfig(#[from] gix_config::file::init::from_paths::Error),
+    #[error("Could not obtain the list of bundles from the remote")]
+    BundleUri(#[from] gix_protocol::bundle_uri::Error),
+    #[error("Interrupted")]
+    Interrupted,
+    #[error("Failed to write the promisor remote configuration to \"{}\"", path.display())]
+    WritePromisorConfig {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
 }
 
 impl gix_protocol::transport::IsSpuriousError for Error {
@@ -50,6 +74,7 @@ impl gix_protocol::transport::IsSpuriousError for Error {
         match self {
             Error::FetchResponse(err) => err.is_spurious(),
             Error::Client(err) => err.is_spurious(),
+            Error::BundleUri(err) => err.is_spurious(),
             _ => false,
         }
     }
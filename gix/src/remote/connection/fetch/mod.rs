@@ -70,6 +70,9 @@ pub struct Outcome {
     pub ref_map: RefMap,
     /// The status of the operation to indicate what happened.
     pub status: Status,
+    /// The refs the server sent in response to the names set via [`Prepare::with_wanted_refs()`], along with the object
+    /// they resolved to on the server side, in the order the server sent them.
+    pub wanted_refs: Vec<gix_protocol::fetch::response::WantedRef>,
 }
 
 /// The progress ids used in during various steps of the fetch operation.
@@ -94,6 +97,9 @@ impl From<ProgressId> for gix_features::progress::Id {
 ///
 pub mod negotiate;
 
+///
+pub mod connectivity;
+
 ///
 pub mod prepare {
     /// The error returned by [`prepare_fetch()`][super::Connection::prepare_fetch()].
@@ -150,6 +156,10 @@ where
             reflog_message: None,
             write_packed_refs: WritePackedRefs::Never,
             shallow: Default::default(),
+            filter: None,
+            wanted_refs: Vec::new(),
+            check_connectivity: true,
+            bundle_uri: false,
         })
     }
 }
@@ -164,7 +174,7 @@ where
     }
 }
 
-mod config;
+mod bundle_uri;
 mod receive_pack;
 ///
 #[path = "update_refs/mod.rs"]
@@ -181,6 +191,10 @@ where
     reflog_message: Option<RefLogMessage>,
     write_packed_refs: WritePackedRefs,
     shallow: remote::fetch::Shallow,
+    filter: Option<remote::fetch::Filter>,
+    wanted_refs: Vec<BString>,
+    check_connectivity: bool,
+    bundle_uri: bool,
 }
 
 /// Builder
@@ -222,6 +236,52 @@ where
         self.shallow = shallow;
         self
     }
+
+    /// Ask the remote to omit objects according to `filter`, turning this repository into a partial clone, which requires
+    /// protocol V2 and the `filter` capability.
+    ///
+    /// The received pack is marked as coming from a *promisor* remote, and `remote.<name>.promisor` and
+    /// `remote.<name>.partialCloneFilter` are written to the repository configuration so later fetches from the same
+    /// remote use the same filter.
+    /// Without a filter set here, the configured `remote.<name>.partialCloneFilter` is used if the remote is a promisor.
+    ///
+    /// Note that the connectivity check is skipped when fetching with a filter, as objects are expected to be missing.
+    pub fn with_filter(mut self, filter: remote::fetch::Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// If enabled, which is the default, assure that all objects reachable from the fetched tips are present after receiving a pack
+    /// and before updating references, to fail if the transfer was incomplete.
+    ///
+    /// It can be disabled for trusted remotes to save time.
+    pub fn with_connectivity_check(mut self, enabled: bool) -> Self {
+        self.check_connectivity = enabled;
+        self
+    }
+
+    /// Request the refs with the given full `names`, like `refs/ci/build-1`, by name using `want-ref` in addition to what the
+    /// ref-specs would fetch, which also works for refs the server doesn't advertise if it permits it.
+    ///
+    /// The object each of these resolved to is available in [`Outcome::wanted_refs`] after [receiving][Prepare::receive()].
+    /// Note that this requires protocol V2 and the `ref-in-want` capability, and that the wanted refs are not
+    /// written to the local repository as they don't participate in ref-spec mappings.
+    pub fn with_wanted_refs(mut self, names: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        self.wanted_refs = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// If enabled, ask the remote for bundles via the `bundle-uri` capability of protocol V2 and download and unbundle them
+    /// into the object database before negotiating, so that only objects not contained in them are received from the remote.
+    ///
+    /// Bundles are downloaded from `http(s)` and `file` urls, and failing to download or apply one isn't an error as the
+    /// remote will send the respective objects instead. When fetching into a repository that already has objects, bundles
+    /// whose prerequisites are missing are skipped, and the tips of applied bundles are announced as *haves* so the remote
+    /// only sends what's new. It's disabled by default, and clones enable it if `transfer.bundleURI` is set.
+    pub fn with_bundle_uri(mut self, enabled: bool) -> Self {
+        self.bundle_uri = enabled;
+        self
+    }
 }
 
 impl<'remote, 'repo, T, P> Drop for Prepare<'remote, 'repo, T, P>
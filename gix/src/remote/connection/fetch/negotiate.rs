@@ -1,4 +1,4 @@
-use crate::remote::fetch;
+use crate::{bstr::BString, remote::fetch};
 
 /// The way the negotiation is performed
 #[derive(Copy, Clone)]
@@ -16,6 +16,9 @@ pub enum Error {
 }
 
 /// Negotiate one round with `algo` by looking at `ref_map` and adjust `arguments` to contain the haves and wants.
+/// Additionally, each of `wanted_refs` is requested by name using `want-ref`.
+/// `bundle_tips` are objects known to be present along with their history, typically as they were unbundled, which
+/// are not wanted and advertised as haves instead.
 /// If this is not the first round, the `previous_response` is set with the last recorded server response.
 /// Returns `true` if the negotiation is done from our side so the server won't keep asking.
 #[allow(clippy::too_many_arguments)]
@@ -24,6 +27,8 @@ pub(crate) fn one_round(
     round: usize,
     repo: &crate::Repository,
     ref_map: &crate::remote::fetch::RefMap,
+    wanted_refs: &[BString],
+    bundle_tips: &[gix_hash::ObjectId],
     fetch_tags: crate::remote::fetch::Tags,
     arguments: &mut gix_protocol::fetch::Arguments,
     _previous_response: Option<&gix_protocol::fetch::Response>,
@@ -42,6 +47,10 @@ pub(crate) fn one_round(
         Algorithm::Naive => {
             assert_eq!(round, 1, "Naive always finishes after the first round, it claims.");
             let mut has_missing_tracking_branch = false;
+            let mut has_wants = !wanted_refs.is_empty();
+            for name in wanted_refs {
+                arguments.want_ref(name.as_ref());
+            }
             for mapping in &ref_map.mappings {
                 if tag_refspec_to_ignore.map_or(false, |tag_spec| {
                     mapping
@@ -60,16 +69,20 @@ pub(crate) fn one_round(
                 match have_id {
                     Some(have_id) => {
                         if let Some(want_id) = mapping.remote.as_id() {
-                            if want_id != have_id {
+                            if want_id != have_id && !bundle_tips.iter().any(|tip| *tip == want_id) {
                                 arguments.want(want_id);
                                 arguments.have(have_id);
+                                has_wants = true;
                             }
                         }
                     }
                     None => {
                         if let Some(want_id) = mapping.remote.as_id() {
-                            arguments.want(want_id);
-                            has_missing_tracking_branch = true;
+                            if !bundle_tips.iter().any(|tip| *tip == want_id) {
+                                arguments.want(want_id);
+                                has_wants = true;
+                                has_missing_tracking_branch = true;
+                            }
                         }
                     }
                 }
@@ -80,9 +93,15 @@ pub(crate) fn one_round(
                     if let Some(id) = r.target().try_id() {
                         arguments.have(id);
                         arguments.want(id);
+                        has_wants = true;
                     }
                 }
             }
+            if has_wants {
+                for tip in bundle_tips {
+                    arguments.have(tip);
+                }
+            }
             Ok(true)
         }
     }
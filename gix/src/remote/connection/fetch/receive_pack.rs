@@ -7,10 +7,10 @@ use gix_protocol::{
 };
 
 use crate::{
-    config::tree::Clone,
+    config::tree::{Clone, Remote},
     remote,
     remote::{
-        connection::fetch::config,
+        connection::fetch::{bundle_uri, connectivity},
         fetch,
         fetch::{negotiate, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage, Shallow, Status},
     },
@@ -95,7 +95,32 @@ where
             }
             arguments.use_include_tag();
         }
+        if !self.wanted_refs.is_empty() && !arguments.can_use_ref_in_want() {
+            return Err(Error::MissingServerFeature {
+                feature: "ref-in-want",
+                description:
+                    "Refs can only be wanted by name if the server supports protocol V2 and advertises this feature",
+            });
+        }
         let (shallow_commits, mut shallow_lock) = add_shallow_args(&mut arguments, &self.shallow, repo)?;
+        let remote_name = con.remote.name().and_then(|name| name.as_symbol());
+        let filter = match self.filter {
+            Some(filter) => Some(filter),
+            None => remote_name
+                .map(|name| configured_filter(repo, name))
+                .transpose()?
+                .flatten(),
+        };
+        if let Some(filter) = filter {
+            if !arguments.can_use_filter() {
+                return Err(Error::MissingServerFeature {
+                    feature: "filter",
+                    description:
+                        "Partial clones need server support to omit objects, otherwise all objects would be received",
+                });
+            }
+            arguments.filter(&filter.to_string());
+        }
 
         let mut previous_response = None::<gix_protocol::fetch::Response>;
         let mut round = 1;
@@ -107,6 +132,27 @@ where
             });
         }
 
+        let bundles = match con.remote.url(remote::Direction::Fetch).filter(|_| {
+            self.bundle_uri
+                && matches!(self.dry_run, fetch::DryRun::No)
+                && protocol_version == gix_protocol::transport::Protocol::V2
+                && handshake.capabilities.contains("bundle-uri")
+        }) {
+            Some(url) => {
+                bundle_uri::apply(
+                    &mut con.transport,
+                    &handshake.capabilities,
+                    repo,
+                    url,
+                    con.remote.name().map(|name| name.as_bstr()),
+                    progress,
+                    should_interrupt,
+                )
+                .await?
+            }
+            None => Default::default(),
+        };
+
         let reader = 'negotiation: loop {
             progress.step();
             progress.set_name(format!("negotiate (round {round})"));
@@ -116,6 +162,8 @@ where
                 round,
                 repo,
                 &self.ref_map,
+                &self.wanted_refs,
+                &bundles.tips,
                 con.remote.fetch_tags,
                 &mut arguments,
                 previous_response.as_ref(),
@@ -135,9 +183,11 @@ where
                         self.dry_run,
                         self.write_packed_refs,
                     )?;
+                    remove_keep_files(bundles.keep_paths)?;
                     return Ok(Outcome {
                         ref_map: std::mem::take(&mut self.ref_map),
                         status: Status::NoPackReceived { update_refs },
+                        wanted_refs: Vec::new(),
                     });
                 }
                 Ok(is_done) => is_done,
@@ -165,6 +215,14 @@ where
             }
         };
         let previous_response = previous_response.expect("knowledge of a pack means a response was received");
+        if let Some(name) = self.wanted_refs.iter().find(|name| {
+            !previous_response
+                .wanted_refs()
+                .iter()
+                .any(|wanted| &wanted.path == *name)
+        }) {
+            return Err(Error::WantedRefMissing { name: name.clone() });
+        }
         if !previous_response.shallow_updates().is_empty() && shallow_lock.is_none() {
             let reject_shallow_remote = repo
                 .config
@@ -180,8 +238,8 @@ where
         }
 
         let options = gix_pack::bundle::write::Options {
-            thread_limit: config::index_threads(repo)?,
-            index_version: config::pack_index_version(repo)?,
+            thread_limit: repo.config.pack_index_threads()?,
+            index_version: repo.config.pack_index_version()?,
             iteration_mode: gix_pack::data::input::Mode::Verify,
             object_hash: con.remote.repo.object_hash(),
         };
@@ -214,6 +272,55 @@ where
             gix_protocol::indicate_end_of_interaction(&mut con.transport).await.ok();
         }
 
+        if let Some(bundle) = write_pack_bundle.as_ref().filter(|_| filter.is_some()) {
+            if let Some(data_path) = bundle.data_path.as_ref() {
+                let path = data_path.with_extension("promisor");
+                std::fs::write(&path, b"").map_err(|err| Error::WritePromisorFile { path, source: err })?;
+            }
+        }
+        if let Some((filter, name)) = self.filter.zip(remote_name).filter(|_| write_pack_bundle.is_some()) {
+            write_promisor_config(repo, name, filter)?;
+        }
+
+        if self.check_connectivity && filter.is_none() && write_pack_bundle.is_some() {
+            // Tags matched by the implicit tag refspec are only sent by the remote if they point to objects we fetched,
+            // so they aren't expected to be present.
+            let implicit_tag_refspec = con
+                .remote
+                .fetch_tags
+                .to_refspec()
+                .filter(|_| matches!(con.remote.fetch_tags, crate::remote::fetch::Tags::Included));
+            let refspecs = con.remote.refspecs(remote::Direction::Fetch);
+            let mut shallow_commits: gix_hashtable::HashSet = shallow_commits
+                .as_ref()
+                .map(|commits| commits.iter().copied().collect())
+                .unwrap_or_default();
+            for update in previous_response.shallow_updates() {
+                match update {
+                    gix_protocol::fetch::response::ShallowUpdate::Shallow(id) => shallow_commits.insert(*id),
+                    gix_protocol::fetch::response::ShallowUpdate::Unshallow(id) => shallow_commits.remove(id),
+                };
+            }
+            connectivity::check(
+                repo,
+                self.ref_map
+                    .mappings
+                    .iter()
+                    .filter(|mapping| {
+                        implicit_tag_refspec.map_or(true, |tag_spec| {
+                            mapping
+                                .spec_index
+                                .get(refspecs, &self.ref_map.extra_refspecs)
+                                .map_or(true, |spec| spec.to_ref() != tag_spec)
+                        })
+                    })
+                    .filter_map(|mapping| mapping.remote.as_id().map(ToOwned::to_owned))
+                    .chain(previous_response.wanted_refs().iter().map(|wanted| wanted.id)),
+                &shallow_commits,
+                should_interrupt,
+            )?;
+        }
+
         if let Some(shallow_lock) = shallow_lock {
             if !previous_response.shallow_updates().is_empty() {
                 crate::shallow::write(shallow_lock, shallow_commits, previous_response.shallow_updates())?;
@@ -240,6 +347,7 @@ where
                 }
             }
         }
+        remove_keep_files(bundles.keep_paths)?;
 
         Ok(Outcome {
             ref_map: std::mem::take(&mut self.ref_map),
@@ -250,10 +358,46 @@ where
                 },
                 None => Status::DryRun { update_refs },
             },
+            wanted_refs: previous_response.wanted_refs().to_vec(),
         })
     }
 }
 
+/// Return the `remote.<name>.partialCloneFilter` of the remote called `name` if it's a promisor remote.
+fn configured_filter(repo: &Repository, name: &str) -> Result<Option<remote::fetch::Filter>, Error> {
+    let config = &repo.config.resolved;
+    let is_promisor = config
+        .boolean("remote", Some(name.into()), Remote::PROMISOR.name)
+        .map(|val| Remote::PROMISOR.enrich_error(val))
+        .transpose()
+        .map_err(Error::PromisorConfig)?
+        .unwrap_or(false);
+    if !is_promisor {
+        return Ok(None);
+    }
+    config
+        .string("remote", Some(name.into()), Remote::PARTIAL_CLONE_FILTER.name)
+        .map(|value| Remote::PARTIAL_CLONE_FILTER.try_into_filter(value))
+        .transpose()
+        .map_err(Into::into)
+}
+
+/// Write the configuration that marks the remote called `name` as promisor using `filter`, like `git` does after a partial clone.
+fn write_promisor_config(repo: &Repository, name: &str, filter: remote::fetch::Filter) -> Result<(), Error> {
+    let path = repo.common_dir().join("config");
+    let mut config = gix_config::File::from_path_no_includes(path.clone(), gix_config::Source::Local)?;
+    filter.write_promisor_config_to(name.into(), &mut config);
+    std::fs::write(&path, config.to_bstring()).map_err(|err| Error::WritePromisorConfig { path, source: err })
+}
+
+/// Remove the `.keep` files of packs written from bundles, which are protected by references now.
+fn remove_keep_files(paths: Vec<std::path::PathBuf>) -> Result<(), Error> {
+    for path in paths {
+        std::fs::remove_file(&path).map_err(|err| Error::RemovePackKeepFile { path, source: err })?;
+    }
+    Ok(())
+}
+
 fn acquire_shallow_lock(repo: &Repository) -> Result<gix_lock::File, Error> {
     gix_lock::File::acquire_to_update_resource(repo.shallow_file(), gix_lock::acquire::Fail::Immediately, None)
         .map_err(Into::into)
@@ -18,6 +18,7 @@ pub struct Connection<'a, 'repo, T, P> {
     pub(crate) transport_options: Option<Box<dyn std::any::Any>>,
     pub(crate) transport: T,
     pub(crate) progress: P,
+    pub(crate) direction: crate::remote::Direction,
 }
 
 mod access;
@@ -27,3 +28,6 @@ pub mod ref_map;
 
 ///
 pub mod fetch;
+
+#[cfg(feature = "blocking-network-client")]
+mod push;
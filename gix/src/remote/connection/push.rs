@@ -0,0 +1,697 @@
+#![allow(clippy::result_large_err)]
+use std::{
+    convert::TryInto,
+    io::{BufRead, Write},
+    sync::atomic::AtomicBool,
+};
+
+use gix_hash::ObjectId;
+use gix_odb::{Find, FindExt};
+use gix_protocol::transport::client::{MessageKind, Transport, WriteMode};
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target, TargetRef,
+};
+use gix_refspec::instruction::Push;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice, ByteVec},
+    ext::ObjectIdExt,
+    remote::{
+        push::{Error, Lease, Options, Outcome, Signed, Status, Update},
+        Connection, Direction,
+    },
+    sign::Sign,
+    Progress, Remote, Repository,
+};
+
+impl<'remote, 'repo, T, P> Connection<'remote, 'repo, T, P>
+where
+    T: Transport,
+    P: Progress,
+    P::SubProgress: 'static,
+{
+    /// Update the references of the remote as determined by the push [ref-specs][crate::Remote::refspecs()] of the remote,
+    /// or push the branch `HEAD` points to into the remote branch of the same name if there are none, and send all
+    /// objects the remote needs for that. `options` allow to do a dry run, use leases and to push atomically.
+    ///
+    /// Updates that are rejected locally, for instance because they aren't fast-forward, aren't sent to the remote and
+    /// can be found in the returned [outcome][Outcome] along with those rejected by the remote. The remote tracking branches
+    /// of all successfully updated references are updated as well.
+    ///
+    /// Like `git push`, the `pre-push` [hook][crate::Repository::hook()] is run with the updates to send before anything is
+    /// sent, even in a dry run, and the push is aborted if it fails unless [`no_verify`][Options::no_verify] is set.
+    ///
+    /// Note that this connection must have been established for [pushing][Direction::Push].
+    ///
+    /// ### Deviation
+    ///
+    /// - `push.default` isn't respected if there are no push ref-specs, and neither are `push.followTags` or `push.recurseSubmodules`.
+    /// - The pack sent to the remote is never thin, and only contains the deltas already present in local packs.
+    pub fn push(mut self, should_interrupt: &AtomicBool, options: Options) -> Result<Outcome, Error> {
+        if self.direction != Direction::Push {
+            return Err(Error::UnsupportedDirection {
+                direction: self.direction,
+            });
+        }
+        let handshake = self.handshake()?;
+        let remote_refs: Vec<(BString, ObjectId)> = handshake
+            .refs
+            .as_ref()
+            .ok_or(Error::MissingRefAdvertisement)?
+            .iter()
+            .filter_map(|r| match r.unpack() {
+                (name, Some(id), _) => Some((name.to_owned(), id.to_owned())),
+                (_, None, _) => None,
+            })
+            .collect();
+        // Like git, check push options before anything else so that dry runs fail for them as well.
+        gix_protocol::push_options::validate(&options.push_options, &handshake.capabilities)?;
+
+        let remote = self.remote;
+        let repo = remote.repo;
+        let mut updates = updates_from_refspecs(remote, &remote_refs)?;
+        for update in &mut updates {
+            update.old = remote_refs
+                .iter()
+                .find_map(|(name, id)| (*name == update.remote).then_some(*id));
+            assign_status(remote, update, &options.force_with_lease)?;
+        }
+        if options.atomic && updates.iter().any(|update| update.status.is_rejected()) {
+            for update in updates.iter_mut().filter(|update| update.status == Status::Ok) {
+                update.status = Status::RejectedAtomic;
+            }
+        }
+
+        let to_send: Vec<usize> = updates
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, update)| (update.status == Status::Ok).then_some(idx))
+            .collect();
+        if !options.no_verify {
+            run_pre_push_hook(
+                remote,
+                self.transport.to_url().as_ref(),
+                to_send.iter().map(|idx| &updates[*idx]),
+            )?;
+        }
+        if options.dry_run || to_send.is_empty() {
+            gix_protocol::indicate_end_of_interaction(&mut self.transport)?;
+            return Ok(Outcome {
+                handshake,
+                updates,
+                num_objects: None,
+            });
+        }
+
+        let server = &handshake.capabilities;
+        let ofs_delta = server.contains("ofs-delta");
+        let report_status = if server.contains("report-status-v2") {
+            Some("report-status-v2")
+        } else if server.contains("report-status") {
+            Some("report-status")
+        } else {
+            None
+        };
+        if options.atomic && !server.contains("atomic") {
+            return Err(Error::MissingServerFeature {
+                feature: "atomic",
+                description: "update all references atomically",
+            });
+        }
+        if to_send.iter().any(|idx| updates[*idx].new.is_none()) && !server.contains("delete-refs") {
+            return Err(Error::MissingServerFeature {
+                feature: "delete-refs",
+                description: "delete references",
+            });
+        }
+        let mut capabilities: Vec<String> = report_status.map(ToOwned::to_owned).into_iter().collect();
+        if options.atomic {
+            capabilities.push("atomic".into());
+        }
+        if server.contains("object-format") {
+            capabilities.push(format!(
+                "object-format={}",
+                match repo.object_hash() {
+                    gix_hash::Kind::Sha1 => "sha1",
+                }
+            ));
+        }
+        if server.contains("agent") {
+            let (name, value) = repo.config.user_agent_tuple();
+            capabilities.push(format!("{name}={}", value.unwrap_or_default()));
+        }
+        if !options.push_options.is_empty() {
+            capabilities.push("push-options".into());
+        }
+        let nonce = server
+            .capability("push-cert")
+            .and_then(|cap| cap.value().map(ToOwned::to_owned));
+        let certificate = match (options.signed, nonce) {
+            (Signed::No, _) | (Signed::IfAsked, None) => None,
+            (Signed::Yes, None) => {
+                return Err(Error::MissingServerFeature {
+                    feature: "push-cert",
+                    description: "sign the push",
+                })
+            }
+            (Signed::Yes | Signed::IfAsked, Some(nonce)) => Some(push_certificate(
+                remote,
+                nonce.as_bstr(),
+                &options.push_options,
+                to_send.iter().map(|idx| &updates[*idx]),
+            )?),
+        };
+
+        let null = ObjectId::null(repo.object_hash());
+        let mut writer = self.transport.request(WriteMode::Binary, MessageKind::Flush)?;
+        match &certificate {
+            Some(certificate) => {
+                let mut line = BString::from("push-cert\0");
+                line.push_str(capabilities.join(" "));
+                line.push(b'\n');
+                writer.write_all(&line)?;
+                for line in certificate.lines_with_terminator() {
+                    writer.write_all(line)?;
+                }
+                writer.write_all(b"push-cert-end\n")?;
+            }
+            None => {
+                for (nth, idx) in to_send.iter().enumerate() {
+                    let update = &updates[*idx];
+                    let mut line = BString::from(format!(
+                        "{} {} ",
+                        update.old.unwrap_or(null),
+                        update.new.unwrap_or(null)
+                    ));
+                    line.push_str(&update.remote);
+                    if nth == 0 {
+                        line.push(0);
+                        line.push_str(capabilities.join(" "));
+                    }
+                    writer.write_all(&line)?;
+                }
+            }
+        }
+        writer.write_message(MessageKind::Flush)?;
+        if !options.push_options.is_empty() {
+            for option in &options.push_options {
+                writer.write_all(option)?;
+            }
+            writer.write_message(MessageKind::Flush)?;
+        }
+        let (mut writer, mut reader) = writer.into_parts();
+        let tips: Vec<_> = to_send.iter().filter_map(|idx| updates[*idx].new).collect();
+        let num_objects = if tips.is_empty() {
+            None
+        } else {
+            Some(crate::pack::write_reachable(
+                repo,
+                &tips,
+                remote_refs.iter().map(|(_, id)| *id),
+                ofs_delta,
+                &mut writer,
+                &mut self.progress,
+                should_interrupt,
+            )?)
+        };
+        writer.flush()?;
+        drop(writer);
+
+        if report_status.is_some() {
+            // The reader still considers the flush packet terminating the ref advertisement.
+            reader.reset(gix_protocol::transport::Protocol::V1);
+            let mut lines = Vec::new();
+            let mut line = Vec::new();
+            while reader.read_until(b'\n', &mut line)? != 0 {
+                lines.push(line.trim_end().as_bstr().to_owned());
+                line.clear();
+            }
+            apply_status_report(&lines, &mut updates, &to_send)?;
+        }
+        drop(reader);
+
+        update_tracking_branches(remote, &updates)?;
+        Ok(Outcome {
+            handshake,
+            updates,
+            num_objects,
+        })
+    }
+
+    fn handshake(&mut self) -> Result<gix_protocol::handshake::Outcome, Error> {
+        let mut credentials_storage;
+        let url = self.transport.to_url();
+        let authenticate = match self.authenticate.as_mut() {
+            Some(f) => f,
+            None => {
+                let url = self
+                    .remote
+                    .url(Direction::Push)
+                    .map(ToOwned::to_owned)
+                    .unwrap_or_else(|| gix_url::parse(url.as_ref()).expect("valid URL to be provided by transport"));
+                credentials_storage = self.configured_credentials(url)?;
+                &mut credentials_storage
+            }
+        };
+
+        if self.transport_options.is_none() {
+            self.transport_options = self
+                .remote
+                .repo
+                .transport_options(url.as_ref(), self.remote.name().map(|n| n.as_bstr()))
+                .map_err(|err| Error::GatherTransportConfig {
+                    source: err,
+                    url: url.into_owned(),
+                })?;
+        }
+        if let Some(config) = self.transport_options.as_ref() {
+            self.transport.configure(&**config)?;
+        }
+        Ok(gix_protocol::handshake(
+            &mut self.transport,
+            gix_protocol::transport::Service::ReceivePack,
+            authenticate,
+            Vec::new(),
+            &mut self.progress,
+        )?)
+    }
+}
+
+impl<'repo> Remote<'repo> {
+    /// Connect to this remote for pushing and [push][Connection::push()] with `options`, using `progress` to learn
+    /// about what's happening and `should_interrupt` to stop early.
+    ///
+    /// Use [`connect()`][Remote::connect()] and [`Connection::push()`] directly for more control over the connection.
+    pub fn push<P>(&self, progress: P, should_interrupt: &AtomicBool, options: Options) -> Result<Outcome, Error>
+    where
+        P: Progress,
+        P::SubProgress: 'static,
+    {
+        self.connect(Direction::Push, progress)?.push(should_interrupt, options)
+    }
+}
+
+/// Derive the reference updates on the remote from the push ref-specs of `remote`, without `old` value and status.
+fn updates_from_refspecs(remote: &Remote<'_>, remote_refs: &[(BString, ObjectId)]) -> Result<Vec<Update>, Error> {
+    let repo = remote.repo;
+    let default_spec;
+    let mut specs = remote.refspecs(Direction::Push);
+    if specs.is_empty() {
+        default_spec = [gix_refspec::parse("HEAD".into(), gix_refspec::parse::Operation::Push)?.to_owned()];
+        specs = &default_spec;
+    }
+
+    let mut updates = Vec::new();
+    for spec in specs {
+        match spec.to_ref().instruction() {
+            gix_refspec::Instruction::Push(Push::Matching {
+                src,
+                dst,
+                allow_non_fast_forward,
+            }) => {
+                if src.find_byte(b'*').is_some() {
+                    for reference in repo.references()?.all()? {
+                        let reference = reference.map_err(Error::ReadReference)?;
+                        let id = match reference.target() {
+                            TargetRef::Peeled(id) => id.to_owned(),
+                            TargetRef::Symbolic(_) => continue,
+                        };
+                        let name = reference.name().as_bstr();
+                        if let Some(matched) = match_glob(src, name) {
+                            push_unique(
+                                &mut updates,
+                                Some(name.to_owned()),
+                                dst.replacen("*", matched, 1).into(),
+                                Some(id),
+                                allow_non_fast_forward,
+                            );
+                        }
+                    }
+                } else {
+                    let (local, id) = resolve_source(repo, src)?;
+                    let name = if dst == src {
+                        local
+                            .clone()
+                            .ok_or_else(|| Error::UnqualifiedDestination { name: dst.to_owned() })?
+                    } else {
+                        qualify_destination(dst, local.as_ref(), remote_refs)?
+                    };
+                    push_unique(&mut updates, local, name, Some(id), allow_non_fast_forward);
+                }
+            }
+            gix_refspec::Instruction::Push(Push::Delete { ref_or_pattern }) => {
+                if ref_or_pattern.find_byte(b'*').is_some() {
+                    for (name, _) in remote_refs {
+                        if match_glob(ref_or_pattern, name.as_ref()).is_some() {
+                            push_unique(&mut updates, None, name.clone(), None, false);
+                        }
+                    }
+                } else {
+                    let name = qualify_destination(ref_or_pattern, None, remote_refs)?;
+                    push_unique(&mut updates, None, name, None, false);
+                }
+            }
+            gix_refspec::Instruction::Push(Push::AllMatchingBranches { allow_non_fast_forward }) => {
+                for reference in repo.references()?.local_branches()? {
+                    let reference = reference.map_err(Error::ReadReference)?;
+                    let id = match reference.target() {
+                        TargetRef::Peeled(id) => id.to_owned(),
+                        TargetRef::Symbolic(_) => continue,
+                    };
+                    let name = reference.name().as_bstr();
+                    if remote_refs.iter().any(|(remote_name, _)| remote_name == name) {
+                        push_unique(
+                            &mut updates,
+                            Some(name.to_owned()),
+                            name.to_owned(),
+                            Some(id),
+                            allow_non_fast_forward,
+                        );
+                    }
+                }
+            }
+            gix_refspec::Instruction::Fetch(_) => unreachable!("BUG: only push ref-specs are obtained"),
+        }
+    }
+    Ok(updates)
+}
+
+fn push_unique(
+    updates: &mut Vec<Update>,
+    local: Option<BString>,
+    remote: BString,
+    new: Option<ObjectId>,
+    forced: bool,
+) {
+    if updates.iter().any(|update| update.remote == remote) {
+        return;
+    }
+    updates.push(Update {
+        local,
+        remote,
+        old: None,
+        new,
+        forced,
+        status: Status::Ok,
+    });
+}
+
+/// Return the part of `name` matched by the single `*` in `pattern`, or `None` if it doesn't match.
+fn match_glob<'a>(pattern: &BStr, name: &'a BStr) -> Option<&'a BStr> {
+    let star = pattern.find_byte(b'*')?;
+    let (prefix, suffix) = (&pattern[..star], &pattern[star + 1..]);
+    (name.len() >= prefix.len() + suffix.len() && name.starts_with(prefix) && name.ends_with(suffix))
+        .then(|| name[prefix.len()..name.len() - suffix.len()].as_bstr())
+}
+
+/// Resolve `spec` to the object to push, along with the full name of the local reference it was read from, if any.
+fn resolve_source(repo: &Repository, spec: &BStr) -> Result<(Option<BString>, ObjectId), Error> {
+    if let Ok(Some(mut reference)) = repo.try_find_reference(spec) {
+        loop {
+            match reference.target() {
+                TargetRef::Symbolic(name) => {
+                    let name = name.to_owned();
+                    reference = repo.find_reference(&name)?;
+                }
+                TargetRef::Peeled(id) => {
+                    let name = reference.name().as_bstr();
+                    return Ok((name.starts_with(b"refs/").then(|| name.to_owned()), id.to_owned()));
+                }
+            }
+        }
+    }
+    let id = repo.rev_parse_single(spec).map_err(|source| Error::ResolveSource {
+        spec: spec.to_owned(),
+        source,
+    })?;
+    Ok((None, id.detach()))
+}
+
+/// Turn the possibly abbreviated reference name `name` into a full name, either by finding it on the remote
+/// or by using the category of the `local` reference it is pushed from.
+fn qualify_destination(
+    name: &BStr,
+    local: Option<&BString>,
+    remote_refs: &[(BString, ObjectId)],
+) -> Result<BString, Error> {
+    const CATEGORIES: [&str; 2] = ["refs/heads/", "refs/tags/"];
+    if name.starts_with(b"refs/") {
+        return Ok(name.to_owned());
+    }
+    let mut candidates = remote_refs
+        .iter()
+        .map(|(remote_name, _)| remote_name)
+        .filter(|remote_name| {
+            CATEGORIES
+                .iter()
+                .any(|prefix| remote_name.strip_prefix(prefix.as_bytes()) == Some(name.as_bytes()))
+        });
+    if let (Some(candidate), None) = (candidates.next(), candidates.next()) {
+        return Ok(candidate.clone());
+    }
+    local
+        .and_then(|local| CATEGORIES.iter().find(|prefix| local.starts_with(prefix.as_bytes())))
+        .map(|prefix| {
+            let mut full_name = BString::from(*prefix);
+            full_name.push_str(name);
+            full_name
+        })
+        .ok_or_else(|| Error::UnqualifiedDestination { name: name.to_owned() })
+}
+
+/// Return the full name of the remote tracking branch of the reference `name` on `remote`, if there is one.
+fn tracking_branch(remote: &Remote<'_>, name: &BStr) -> Option<BString> {
+    let null = ObjectId::null(remote.repo.object_hash());
+    gix_refspec::MatchGroup::from_fetch_specs(remote.refspecs(Direction::Fetch).iter().map(|spec| spec.to_ref()))
+        .match_remotes(std::iter::once(gix_refspec::match_group::Item {
+            full_ref_name: name,
+            target: &null,
+            object: None,
+        }))
+        .mappings
+        .into_iter()
+        .find_map(|mapping| mapping.rhs.map(|rhs| rhs.into_owned()))
+}
+
+/// Decide if `update` can be performed without consulting the remote.
+fn assign_status(remote: &Remote<'_>, update: &mut Update, leases: &[Lease]) -> Result<(), Error> {
+    let repo = remote.repo;
+    if let Some(lease) = leases.iter().find(|lease| lease.name == update.remote) {
+        let expected = match lease.expected {
+            Some(id) => Some(id),
+            None => match tracking_branch(remote, update.remote.as_ref()) {
+                Some(name) => repo
+                    .try_find_reference(name.as_bstr())?
+                    .and_then(|reference| reference.try_id().map(|id| id.detach())),
+                None => None,
+            },
+        };
+        if expected != update.old {
+            update.status = Status::RejectedStale;
+            return Ok(());
+        }
+        update.forced = true;
+    }
+    update.status = match (update.old, update.new) {
+        (old, new) if old == new => {
+            if new.is_none() {
+                Status::RejectedNoSuchRef
+            } else {
+                Status::UpToDate
+            }
+        }
+        (None, _) | (_, None) => Status::Ok,
+        (Some(old), Some(new)) => {
+            if update.forced {
+                Status::Ok
+            } else if update.remote.starts_with(b"refs/tags/") {
+                Status::RejectedAlreadyExists
+            } else if !repo.objects.contains(old) {
+                Status::RejectedFetchFirst
+            } else if is_ancestor(repo, old, new)? {
+                Status::Ok
+            } else {
+                Status::RejectedNonFastForward
+            }
+        }
+    };
+    Ok(())
+}
+
+/// Return `true` if the commit `ancestor` is reachable from the commit `descendant`.
+///
+/// Only commits at least as recent as `ancestor` are traversed, as older ones can't lead to it unless clocks were skewed.
+fn is_ancestor(repo: &Repository, ancestor: ObjectId, descendant: ObjectId) -> Result<bool, Error> {
+    let commit_time = |id: ObjectId| -> Result<Option<u32>, Error> {
+        Ok(match repo.try_find_object(id)? {
+            Some(object) if object.kind == gix_object::Kind::Commit => {
+                Some(object.into_commit().committer()?.time.seconds_since_unix_epoch)
+            }
+            _ => None,
+        })
+    };
+    let ancestor_time = match (commit_time(ancestor)?, commit_time(descendant)?) {
+        (Some(ancestor_time), Some(_)) => ancestor_time,
+        _ => return Ok(false),
+    };
+    for id in descendant
+        .ancestors(|id, buf| repo.objects.find_commit_iter(id, buf))
+        .sorting(gix_traverse::commit::Sorting::ByCommitTimeNewestFirstCutoffOlderThan {
+            time_in_seconds_since_epoch: ancestor_time,
+        })?
+    {
+        if id? == ancestor {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Run the `pre-push` hook of the repository of `remote` with all `updates` to send to `url`, and fail if it fails,
+/// like `git push` does.
+fn run_pre_push_hook<'a>(
+    remote: &Remote<'_>,
+    url: &BStr,
+    updates: impl IntoIterator<Item = &'a Update>,
+) -> Result<(), Error> {
+    let repo = remote.repo;
+    let hook = match repo.hook("pre-push")? {
+        Some(hook) => hook,
+        None => return Ok(()),
+    };
+    let null = ObjectId::null(repo.object_hash());
+    let mut input = Vec::new();
+    for update in updates {
+        match update.new {
+            Some(new) => match &update.local {
+                Some(local) => input.extend_from_slice(local),
+                None => write!(input, "{new}")?,
+            },
+            None => input.extend_from_slice(b"(delete)"),
+        }
+        write!(input, " {} ", update.new.unwrap_or(null))?;
+        input.extend_from_slice(&update.remote);
+        writeln!(input, " {}", update.old.unwrap_or(null))?;
+    }
+    let url = gix_path::from_bstr(url).into_owned();
+    let name = remote
+        .name()
+        .map_or_else(|| url.clone(), |name| gix_path::from_bstr(name.as_bstr()).into_owned());
+    hook.arg(name).arg(url).stdin(input).run()?;
+    Ok(())
+}
+
+/// Create a push certificate for `updates` with the `nonce` provided by the remote and sign it.
+fn push_certificate<'a>(
+    remote: &Remote<'_>,
+    nonce: &BStr,
+    push_options: &[BString],
+    updates: impl IntoIterator<Item = &'a Update>,
+) -> Result<BString, Error> {
+    let repo = remote.repo;
+    let null = ObjectId::null(repo.object_hash());
+    let mut cert = b"certificate version 0.1\npusher ".to_vec();
+    repo.committer().ok_or(Error::CommitterMissing)??.write_to(&mut cert)?;
+    cert.push(b'\n');
+    if let Some(url) = remote.url(Direction::Push) {
+        let mut url = url.to_owned();
+        url.set_user(None);
+        cert.extend_from_slice(b"pushee ");
+        url.write_to(&mut cert)?;
+        cert.push(b'\n');
+    }
+    writeln!(cert, "nonce {nonce}")?;
+    for option in push_options {
+        writeln!(cert, "push-option {option}")?;
+    }
+    cert.push(b'\n');
+    for update in updates {
+        writeln!(
+            cert,
+            "{} {} {}",
+            update.old.unwrap_or(null),
+            update.new.unwrap_or(null),
+            update.remote
+        )?;
+    }
+    let signature = repo.signer()?.sign(&cert).map_err(Error::Sign)?;
+    cert.extend_from_slice(&signature);
+    Ok(cert.into())
+}
+
+/// Apply the `report-status` or `report-status-v2` response in `lines` to the updates at the indices `sent`.
+fn apply_status_report(lines: &[BString], updates: &mut [Update], sent: &[usize]) -> Result<(), Error> {
+    let malformed = |line: &BString| Error::MalformedStatusReport { line: line.clone() };
+    let mut lines = lines.iter();
+    let first = lines.next().ok_or_else(|| Error::MalformedStatusReport {
+        line: Default::default(),
+    })?;
+    let unpack = first.strip_prefix(b"unpack ").ok_or_else(|| malformed(first))?;
+    if unpack != b"ok" {
+        return Err(Error::Unpack {
+            message: unpack.as_bstr().to_owned(),
+        });
+    }
+    for line in lines {
+        // `report-status-v2` may provide details about the previous update, which we don't need.
+        if line.starts_with(b"option ") {
+            continue;
+        }
+        let (status, rest) = line.split_once_str(" ").ok_or_else(|| malformed(line))?;
+        let (name, reason) = match status {
+            b"ok" => (rest, None),
+            b"ng" => {
+                let (name, reason) = rest.split_once_str(" ").unwrap_or((rest, b""));
+                (name, Some(reason.as_bstr().to_owned()))
+            }
+            _ => return Err(malformed(line)),
+        };
+        let update = sent
+            .iter()
+            .copied()
+            .find(|idx| updates[*idx].remote == name)
+            .ok_or_else(|| malformed(line))?;
+        if let Some(reason) = reason {
+            updates[update].status = Status::RemoteRejected(reason);
+        }
+    }
+    Ok(())
+}
+
+/// Make the remote tracking branches of all successfully updated references point to their new value.
+fn update_tracking_branches(remote: &Remote<'_>, updates: &[Update]) -> Result<(), Error> {
+    let edits = updates
+        .iter()
+        .filter(|update| update.status == Status::Ok)
+        .filter_map(|update| tracking_branch(remote, update.remote.as_ref()).map(|name| (name, update.new)))
+        .map(|(name, new)| {
+            Ok(RefEdit {
+                change: match new {
+                    Some(id) => Change::Update {
+                        log: LogChange {
+                            mode: RefLog::AndReference,
+                            force_create_reflog: false,
+                            message: "update by push".into(),
+                        },
+                        expected: PreviousValue::Any,
+                        new: Target::Peeled(id),
+                    },
+                    None => Change::Delete {
+                        expected: PreviousValue::Any,
+                        log: RefLog::AndReference,
+                    },
+                },
+                name: name
+                    .clone()
+                    .try_into()
+                    .map_err(|source| Error::InvalidTrackingBranchName { name, source })?,
+                deref: false,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    if !edits.is_empty() {
+        remote.repo.edit_references(edits)?;
+    }
+    Ok(())
+}
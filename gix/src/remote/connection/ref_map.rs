@@ -32,6 +32,8 @@ pub enum Error {
     ConfigureCredentials(#[from] crate::config::credential_helpers::Error),
     #[error(transparent)]
     MappingValidation(#[from] gix_refspec::match_group::validate::Error),
+    #[error("Cannot fetch through a connection established for the {} direction", direction.as_str())]
+    UnsupportedDirection { direction: Direction },
 }
 
 impl gix_protocol::transport::IsSpuriousError for Error {
@@ -112,6 +114,11 @@ where
             mut extra_refspecs,
         }: Options,
     ) -> Result<fetch::RefMap, Error> {
+        if self.direction != Direction::Fetch {
+            return Err(Error::UnsupportedDirection {
+                direction: self.direction,
+            });
+        }
         let null = gix_hash::ObjectId::null(gix_hash::Kind::Sha1); // OK to hardcode Sha1, it's not supposed to match, ever.
 
         if let Some(tag_spec) = self.remote.fetch_tags.to_refspec().map(|spec| spec.to_owned()) {
@@ -52,6 +52,78 @@ impl Tags {
     }
 }
 
+/// An object filter to ask the remote to omit objects from the pack it sends, which makes the fetching repository a
+/// *partial clone* that relies on the remote as *promisor* to provide the omitted objects later.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// Omit all blobs, like `blob:none`.
+    BlobNone,
+    /// Omit all blobs of the given size in bytes or larger, like `blob:limit=<n>`.
+    BlobLimit(u64),
+    /// Omit all trees and blobs whose depth from the root tree is the given depth or larger, like `tree:<depth>`.
+    ///
+    /// A depth of `0` omits all trees and blobs.
+    TreeDepth(u64),
+}
+
+impl Filter {
+    /// Parse a filter specification like `blob:none`, `blob:limit=1m` or `tree:0`, or return `None` if it's invalid or unsupported.
+    ///
+    /// Like in `git`, the size limit may have a `k`, `m` or `g` suffix.
+    pub fn from_bytes(spec: &crate::bstr::BStr) -> Option<Self> {
+        let spec = std::str::from_utf8(spec).ok()?;
+        Some(if spec == "blob:none" {
+            Filter::BlobNone
+        } else if let Some(limit) = spec.strip_prefix("blob:limit=") {
+            let (digits, factor) = match limit.char_indices().last()? {
+                (pos, 'k' | 'K') => (&limit[..pos], 1 << 10),
+                (pos, 'm' | 'M') => (&limit[..pos], 1 << 20),
+                (pos, 'g' | 'G') => (&limit[..pos], 1 << 30),
+                _ => (limit, 1),
+            };
+            Filter::BlobLimit(digits.parse::<u64>().ok()?.checked_mul(factor)?)
+        } else if let Some(depth) = spec.strip_prefix("tree:") {
+            Filter::TreeDepth(depth.parse().ok()?)
+        } else {
+            return None;
+        })
+    }
+
+    /// Configure the remote named `remote_name` in the local sections of `config` as promisor using this filter, and
+    /// bump the repository format version like `git` does as the repository may now be missing objects.
+    #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
+    pub(crate) fn write_promisor_config_to(&self, remote_name: &crate::bstr::BStr, config: &mut gix_config::File<'_>) {
+        use crate::config::tree::{Core, Remote};
+        let mut is_local = |meta: &gix_config::file::Metadata| meta.source == gix_config::Source::Local;
+        let subsection = Some(remote_name);
+        config
+            .set_raw_value_filter("remote", subsection, Remote::PROMISOR.name, "true", &mut is_local)
+            .expect("works - statically known");
+        config
+            .set_raw_value_filter(
+                "remote",
+                subsection,
+                Remote::PARTIAL_CLONE_FILTER.name,
+                self.to_string().as_str(),
+                &mut is_local,
+            )
+            .expect("works - statically known");
+        config
+            .set_raw_value_filter("core", None, Core::REPOSITORY_FORMAT_VERSION.name, "1", &mut is_local)
+            .expect("works - statically known");
+    }
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::BlobNone => f.write_str("blob:none"),
+            Filter::BlobLimit(limit) => write!(f, "blob:limit={limit}"),
+            Filter::TreeDepth(depth) => write!(f, "tree:{depth}"),
+        }
+    }
+}
+
 /// Describe how shallow clones are handled when fetching, with variants defining how the *shallow boundary* is handled.
 ///
 /// The *shallow boundary* is a set of commits whose parents are not present in the repository.
@@ -208,5 +280,5 @@ pub struct Mapping {
 
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
 pub use super::connection::fetch::{
-    negotiate, prepare, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage, Status,
+    connectivity, negotiate, prepare, refs, Error, Outcome, Prepare, ProgressId, RefLogMessage, Status,
 };
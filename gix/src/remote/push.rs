@@ -0,0 +1,209 @@
+use gix_hash::ObjectId;
+
+use crate::{bstr::BString, remote::Direction};
+
+/// A reference on the remote which may only be overwritten if it still points to the expected object, like
+/// `git push --force-with-lease=<name>:<expected>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lease {
+    /// The full name of the reference on the remote, like `refs/heads/main`.
+    pub name: BString,
+    /// The object the remote reference is expected to point to, or `None` to expect the object its remote tracking branch
+    /// points to. If there is no such branch either, the remote reference is expected to not exist.
+    pub expected: Option<ObjectId>,
+}
+
+/// Whether to sign a push with a push certificate, like `git push --signed`.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Signed {
+    /// Never sign the push.
+    #[default]
+    No,
+    /// Sign the push if the remote supports push certificates, like `git push --signed=if-asked`.
+    IfAsked,
+    /// Sign the push, and fail if the remote doesn't support push certificates.
+    Yes,
+}
+
+/// Options for [`Connection::push()`][crate::remote::Connection::push()] and [`Remote::push()`][crate::Remote::push()].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, determine the reference updates and check them locally, but neither send anything to the remote
+    /// nor update remote tracking branches, like `git push --dry-run`.
+    pub dry_run: bool,
+    /// If `true`, either all reference updates succeed or none of them is performed, like `git push --atomic`.
+    ///
+    /// The remote has to support this.
+    pub atomic: bool,
+    /// Allow the references on the remote to be overwritten with objects that don't have their current value in
+    /// their history, but only if they still point to the expected object, like `git push --force-with-lease`.
+    pub force_with_lease: Vec<Lease>,
+    /// Whether to send a push certificate signed with the [signer configured][crate::Repository::signer()] for the repository,
+    /// which the remote may record to be able to prove who pushed what.
+    pub signed: Signed,
+    /// Strings to transmit to the remote for use by its hooks, like `git push --push-option`.
+    ///
+    /// The remote has to support this, even for dry runs, and the strings must not contain newlines.
+    pub push_options: Vec<BString>,
+    /// If `true`, don't run the `pre-push` [hook][crate::Repository::hook()], like `git push --no-verify`.
+    ///
+    /// Otherwise the hook receives the name and url of the remote as arguments and a line like
+    /// `<local ref> <local object> <remote ref> <remote object>` for each reference update to send on its standard input,
+    /// and the push is aborted if it fails.
+    pub no_verify: bool,
+}
+
+/// The status of an [`Update`] of a reference on the remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// The remote reference already points to the object to push, so there is nothing to do.
+    UpToDate,
+    /// The update was performed on the remote, or would have been sent to the remote in a dry run.
+    Ok,
+    /// The update was rejected as the remote reference doesn't point to an ancestor of the object to push, and the update wasn't forced.
+    RejectedNonFastForward,
+    /// The update was rejected as the object the remote reference points to isn't present locally, which is why it's impossible to
+    /// know if the update would be fast-forward. Fetching first resolves this.
+    RejectedFetchFirst,
+    /// The update was rejected as the remote reference is a tag that exists already, and the update wasn't forced.
+    RejectedAlreadyExists,
+    /// The update was rejected as the remote reference doesn't point to the object expected by its [`Lease`].
+    RejectedStale,
+    /// The deletion was rejected as the remote reference doesn't exist.
+    RejectedNoSuchRef,
+    /// The update was acceptable, but wasn't performed as another update in the same atomic push was rejected.
+    RejectedAtomic,
+    /// The remote refused to perform the update, with its reason attached.
+    RemoteRejected(BString),
+}
+
+impl Status {
+    /// Return `true` if the update was rejected, either locally or by the remote.
+    pub fn is_rejected(&self) -> bool {
+        !matches!(self, Status::UpToDate | Status::Ok)
+    }
+}
+
+/// A reference update on the remote, as derived from the push ref-specs of the remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Update {
+    /// The full name of the local reference the object to push was obtained from, or `None` if the source was an object id
+    /// or another kind of revision specification, or if the remote reference is deleted.
+    pub local: Option<BString>,
+    /// The full name of the reference on the remote to update.
+    pub remote: BString,
+    /// The object the remote reference pointed to when the push started, or `None` if it didn't exist.
+    pub old: Option<ObjectId>,
+    /// The object the remote reference should point to, or `None` if it should be deleted.
+    pub new: Option<ObjectId>,
+    /// If `true`, the update doesn't have to be a fast-forward, either as the ref-spec was forced or due to a [`Lease`].
+    pub forced: bool,
+    /// The status of the update.
+    pub status: Status,
+}
+
+/// The outcome of a [push][crate::remote::Connection::push()].
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    /// The result of the handshake with the remote, including the references it advertised.
+    pub handshake: gix_protocol::handshake::Outcome,
+    /// All reference updates on the remote, in the order they were derived from the push ref-specs.
+    pub updates: Vec<Update>,
+    /// The amount of objects in the pack sent to the remote, or `None` if no pack was sent.
+    pub num_objects: Option<usize>,
+}
+
+/// The error returned by [`Connection::push()`][crate::remote::Connection::push()] and [`Remote::push()`][crate::Remote::push()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    Connect(#[from] crate::remote::connect::Error),
+    #[error("Cannot push through a connection established for the {} direction", direction.as_str())]
+    UnsupportedDirection { direction: Direction },
+    #[error("Failed to configure the transport before connecting to {url:?}")]
+    GatherTransportConfig {
+        url: BString,
+        source: crate::config::transport::Error,
+    },
+    #[error("Failed to configure the transport layer")]
+    ConfigureTransport(#[from] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    ConfigureCredentials(#[from] crate::config::credential_helpers::Error),
+    #[error(transparent)]
+    Handshake(#[from] gix_protocol::handshake::Error),
+    #[error("The remote didn't advertise its references, which is required for pushing")]
+    MissingRefAdvertisement,
+    #[error(transparent)]
+    Transport(#[from] gix_protocol::transport::client::Error),
+    #[error("The remote doesn't support the '{feature}' capability which is required to {description}")]
+    MissingServerFeature {
+        feature: &'static str,
+        description: &'static str,
+    },
+    #[error(transparent)]
+    PushOptions(#[from] gix_protocol::push_options::Error),
+    #[error("A committer is required to sign the push certificate")]
+    CommitterMissing,
+    #[error(transparent)]
+    CommitterTime(#[from] crate::config::time::Error),
+    #[error(transparent)]
+    Signer(#[from] crate::config::signer::Error),
+    #[error("Could not sign the push certificate")]
+    Sign(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    ParseRefSpec(#[from] gix_refspec::parse::Error),
+    #[error("Could not resolve '{spec}' to an object to push")]
+    ResolveSource {
+        spec: BString,
+        source: crate::revision::spec::parse::single::Error,
+    },
+    #[error("The destination '{name}' isn't a full reference name and couldn't be derived from its source or the remote references")]
+    UnqualifiedDestination { name: BString },
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    TryFindReference(#[from] crate::reference::find::Error),
+    #[error(transparent)]
+    IterateReferences(#[from] crate::reference::iter::Error),
+    #[error(transparent)]
+    IterateReferencesInit(#[from] crate::reference::iter::init::Error),
+    #[error("Could not read a local reference")]
+    ReadReference(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::Error),
+    #[error(transparent)]
+    Traverse(#[from] gix_traverse::commit::ancestors::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    HooksDir(#[from] gix_config::path::interpolate::Error),
+    #[error("The pre-push hook prevented the push")]
+    Hook(#[from] crate::hook::run::Error),
+    #[error("Could not create the pack to send")]
+    WritePack(#[from] crate::pack::write::Error),
+    #[error("Could not send the pack or receive the status report")]
+    Io(#[from] std::io::Error),
+    #[error("The remote failed to unpack the pack we sent: {message}")]
+    Unpack { message: BString },
+    #[error("Could not parse the status report line {line:?}")]
+    MalformedStatusReport { line: BString },
+    #[error("The name of the remote tracking branch {name:?} is invalid")]
+    InvalidTrackingBranchName {
+        name: BString,
+        source: gix_validate::refname::Error,
+    },
+    #[error(transparent)]
+    UpdateTrackingBranches(#[from] crate::reference::edit::Error),
+}
+
+impl gix_protocol::transport::IsSpuriousError for Error {
+    fn is_spurious(&self) -> bool {
+        match self {
+            Error::Connect(err) => err.is_spurious(),
+            Error::Transport(err) => err.is_spurious(),
+            Error::Handshake(err) => err.is_spurious(),
+            _ => false,
+        }
+    }
+}
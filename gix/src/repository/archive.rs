@@ -0,0 +1,83 @@
+use std::io::{Seek, Write};
+
+use gix_odb::FindExt;
+
+use crate::{archive, ObjectId};
+
+impl crate::Repository {
+    /// Write the tree `treeish` points to into `out` as archive, like `git archive` does, where `treeish` may be a tree, or
+    /// a commit or tag pointing to one.
+    ///
+    /// Entries with the `export-ignore` attribute are skipped, and `$Format:…$` placeholders in files with the `export-subst`
+    /// attribute are expanded if `treeish` leads to a commit. Attributes are read from the `.gitattributes` files in the tree,
+    /// along with `info/attributes` and `core.attributesFile`.
+    ///
+    /// `zip` archives can't be written into `out` as it can't seek, use [`write_archive_seek()`][Self::write_archive_seek()]
+    /// instead.
+    pub fn write_archive(
+        &self,
+        treeish: impl Into<ObjectId>,
+        out: &mut dyn Write,
+        options: archive::Options,
+    ) -> Result<archive::Outcome, archive::Error> {
+        let (tree, options) = self.archive_options(treeish.into(), options)?;
+        Ok(gix_archive::write_to(
+            &tree,
+            |id, buf| self.objects.find(id, buf),
+            out,
+            options,
+        )?)
+    }
+
+    /// Like [`write_archive()`][Self::write_archive()], but supports all formats as `out` can seek.
+    pub fn write_archive_seek(
+        &self,
+        treeish: impl Into<ObjectId>,
+        out: impl Write + Seek,
+        options: archive::Options,
+    ) -> Result<archive::Outcome, archive::Error> {
+        let (tree, options) = self.archive_options(treeish.into(), options)?;
+        Ok(gix_archive::write_to_seek(
+            &tree,
+            |id, buf| self.objects.find(id, buf),
+            out,
+            options,
+        )?)
+    }
+
+    fn archive_options(
+        &self,
+        id: ObjectId,
+        options: archive::Options,
+    ) -> Result<(ObjectId, gix_archive::Options), archive::Error> {
+        let object = self.find_object(id)?.peel_tags_to_end()?;
+        let (tree, commit) = match object.kind {
+            gix_object::Kind::Commit => {
+                let commit = object.into_commit();
+                let time = commit.committer()?.time.seconds_since_unix_epoch;
+                (commit.tree_id()?.detach(), Some((commit.id, time)))
+            }
+            gix_object::Kind::Tree => (object.id, None),
+            kind => return Err(archive::Error::NotATreeish { id, kind }),
+        };
+        let modification_time = options
+            .modification_time
+            .or_else(|| commit.map(|(_, time)| time))
+            .unwrap_or_else(|| gix_date::Time::now_utc().seconds_since_unix_epoch);
+        Ok((
+            tree,
+            gix_archive::Options {
+                format: options.format,
+                tree_prefix: options.tree_prefix,
+                modification_time,
+                commit_id: commit.map(|(id, _)| id),
+                attribute_globals: self.config.attribute_globals(self.git_dir())?,
+                case: if self.config.ignore_case {
+                    gix_glob::pattern::Case::Fold
+                } else {
+                    Default::default()
+                },
+            },
+        ))
+    }
+}
@@ -0,0 +1,9 @@
+use crate::bisect;
+
+/// Bisection
+impl crate::Repository {
+    /// Return a platform to start, drive and reset a bisection to find the commit that introduced a change, similar to `git bisect`.
+    pub fn bisect(&self) -> bisect::Platform<'_> {
+        bisect::Platform { repo: self }
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    borrow::Cow,
+    collections::{hash_map, BinaryHeap, HashMap},
+    ops::Range,
+    path::Path,
+};
+
+use gix_hash::ObjectId;
+
+use crate::{
+    blame::{self, Error, UnblamedHunk},
+    Commit,
+};
+
+/// Blame
+impl crate::Repository {
+    /// Attribute each line of the file at `path` in the commit with `commit_id` to the commit that introduced it, similar to `git blame`.
+    ///
+    /// See [`blame_incremental()`][Self::blame_incremental()] for details.
+    pub fn blame(
+        &self,
+        commit_id: impl Into<ObjectId>,
+        path: impl AsRef<Path>,
+        options: blame::Options,
+    ) -> Result<blame::Outcome, Error> {
+        let commit_id = commit_id.into();
+        let path = path.as_ref();
+        let mut entries = Vec::new();
+        self.blame_incremental(commit_id, path, options, |entry| entries.push(entry.clone()))?;
+        entries.sort_by_key(|entry| entry.range_in_blamed_file.start);
+
+        let commit = self.find_object(commit_id)?.try_into_commit()?;
+        let blob_id = file_id(&commit, path)?.expect("blame succeeded so the file exists");
+        Ok(blame::Outcome {
+            entries,
+            blob: self.find_object(blob_id)?.detach().data,
+        })
+    }
+
+    /// Like [`blame()`][Self::blame()], but call `on_entry` with each range of lines as soon as its origin is known, similar to
+    /// `git blame --incremental`.
+    ///
+    /// Commits are visited from newest to oldest. Unchanged lines are passed on to the parent that has the exact same version of the
+    /// file, or otherwise to each parent in order, so all lines that a commit didn't change are attributed to one of its ancestors.
+    /// Entries are thus not ordered by their position in the file.
+    ///
+    /// ### Deviation
+    ///
+    /// Lines are never followed across renames or copies.
+    pub fn blame_incremental(
+        &self,
+        commit_id: impl Into<ObjectId>,
+        path: impl AsRef<Path>,
+        options: blame::Options,
+        mut on_entry: impl FnMut(&blame::Entry),
+    ) -> Result<(), Error> {
+        let commit_id = commit_id.into();
+        let path = path.as_ref();
+        let algorithm = self.config.diff_algorithm()?;
+
+        let commit = self.find_object(commit_id)?.try_into_commit()?;
+        let blob_id = file_id(&commit, path)?.ok_or_else(|| Error::PathNotFound {
+            path: path.to_owned(),
+            commit_id,
+        })?;
+        let num_lines = blame::num_lines(&self.find_object(blob_id)?.data);
+        if num_lines == 0 {
+            return Ok(());
+        }
+
+        let mut pending = HashMap::<ObjectId, (ObjectId, Vec<UnblamedHunk>)>::new();
+        let mut queue = BinaryHeap::new();
+        pending.insert(
+            commit_id,
+            (
+                blob_id,
+                vec![UnblamedHunk {
+                    range_in_blamed_file: 0..num_lines,
+                    source_start: 0,
+                }],
+            ),
+        );
+        queue.push((commit.time()?.seconds_since_unix_epoch, commit_id));
+
+        while let Some((_time, commit_id)) = queue.pop() {
+            let (blob_id, mut hunks) = match pending.remove(&commit_id) {
+                Some(pending) => pending,
+                None => continue,
+            };
+            let commit = self.find_object(commit_id)?.try_into_commit()?;
+            let mut parents = Vec::new();
+            for parent_id in commit.parent_ids() {
+                let parent = parent_id.object()?.try_into_commit()?;
+                if let Some(parent_blob_id) = file_id(&parent, path)? {
+                    parents.push((parent.id, parent_blob_id, parent.time()?.seconds_since_unix_epoch));
+                }
+            }
+
+            let mut pass_to_parent = |(parent_id, parent_blob_id, time): (ObjectId, ObjectId, u32),
+                                      hunks: Vec<UnblamedHunk>| {
+                match pending.entry(parent_id) {
+                    hash_map::Entry::Occupied(mut entry) => entry.get_mut().1.extend(hunks),
+                    hash_map::Entry::Vacant(entry) => {
+                        entry.insert((parent_blob_id, hunks));
+                        queue.push((time, parent_id));
+                    }
+                }
+            };
+
+            if let Some(parent) = parents.iter().find(|(_, parent_blob_id, _)| *parent_blob_id == blob_id) {
+                pass_to_parent(*parent, hunks);
+                continue;
+            }
+
+            if !parents.is_empty() {
+                let data = self.find_object(blob_id)?.detach().data;
+                for parent in parents {
+                    if hunks.is_empty() {
+                        break;
+                    }
+                    let parent_data = self.find_object(parent.1)?.detach().data;
+                    let changes = line_changes(algorithm, &parent_data, &data, options);
+                    let (unchanged, changed) = blame::split_by_changes(hunks, &changes);
+                    hunks = changed;
+                    if !unchanged.is_empty() {
+                        pass_to_parent(parent, unchanged);
+                    }
+                }
+            }
+
+            for hunk in hunks {
+                on_entry(&blame::Entry {
+                    commit_id,
+                    range_in_source_file: hunk.source_range(),
+                    range_in_blamed_file: hunk.range_in_blamed_file,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Return the id of the blob at `path` in the tree of `commit`, if there is one.
+fn file_id(commit: &Commit<'_>, path: &Path) -> Result<Option<ObjectId>, Error> {
+    Ok(commit
+        .tree()?
+        .lookup_entry_by_path(path)?
+        .filter(|entry| entry.mode().is_blob_or_symlink())
+        .map(|entry| entry.object_id()))
+}
+
+/// Return the line ranges changed from `before` to `after`.
+fn line_changes(
+    algorithm: gix_diff::blob::Algorithm,
+    before: &[u8],
+    after: &[u8],
+    options: blame::Options,
+) -> Vec<(Range<u32>, Range<u32>)> {
+    let (before, after): (Cow<'_, [u8]>, Cow<'_, [u8]>) = if options.ignore_whitespace {
+        (
+            blame::without_whitespace(before).into(),
+            blame::without_whitespace(after).into(),
+        )
+    } else {
+        (before.into(), after.into())
+    };
+    let input = gix_diff::blob::intern::InternedInput::new(
+        gix_diff::blob::sources::byte_lines_with_terminator(&before),
+        gix_diff::blob::sources::byte_lines_with_terminator(&after),
+    );
+    let mut changes = gix_diff::blob::diff(algorithm, &input, Changes(Vec::new()));
+    changes.sort_by_key(|(_, after)| after.start);
+    changes
+}
+
+struct Changes(Vec<(Range<u32>, Range<u32>)>);
+
+impl gix_diff::blob::Sink for Changes {
+    type Out = Vec<(Range<u32>, Range<u32>)>;
+
+    fn process_change(&mut self, before: Range<u32>, after: Range<u32>) {
+        self.0.push((before, after));
+    }
+
+    fn finish(self) -> Self::Out {
+        self.0
+    }
+}
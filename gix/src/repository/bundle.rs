@@ -0,0 +1,165 @@
+#![allow(clippy::result_large_err)]
+use std::{io::Write, sync::atomic::AtomicBool};
+
+use gix_odb::{Find, FindExt};
+
+use crate::{
+    bstr::{BStr, ByteSlice},
+    bundle::{create, unbundle, Header, Prerequisite, Ref},
+    Progress,
+};
+
+impl crate::Repository {
+    /// Write a bundle to `out` which contains all references named by the revision `specs` along with all objects reachable
+    /// from them, except for those reachable from excluded objects, which become prerequisites of the bundle.
+    ///
+    /// For example, `["main", "^v1.0"]` or `["v1.0..main"]` create a bundle with `refs/heads/main` that can be applied to
+    /// repositories that have `v1.0`, and `["main", "refs/tags/v1.0"]` creates a bundle without prerequisites.
+    /// Included objects must be named by a reference, while ranges like `a...b` aren't supported.
+    ///
+    /// `progress` and `should_interrupt` are used while creating the pack.
+    pub fn create_bundle<P>(
+        &self,
+        specs: impl IntoIterator<Item = impl AsRef<BStr>>,
+        out: &mut dyn Write,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+        options: create::Options,
+    ) -> Result<create::Outcome, create::Error>
+    where
+        P: Progress,
+        P::SubProgress: 'static,
+    {
+        let mut refs = Vec::<Ref>::new();
+        let mut hidden = Vec::new();
+        for spec in specs {
+            let spec = spec.as_ref();
+            let parsed = self.rev_parse(spec).map_err(|source| create::Error::RevParse {
+                spec: spec.into(),
+                source,
+            })?;
+            let name_of = |reference: Option<&gix_ref::Reference>| {
+                reference
+                    .map(|r| r.name.as_bstr().to_owned())
+                    .ok_or_else(|| create::Error::NotAReference { spec: spec.into() })
+            };
+            let (id, name) = match parsed.inner {
+                gix_revision::Spec::Include(id) => (id, name_of(parsed.first_reference())?),
+                gix_revision::Spec::Exclude(id) => {
+                    hidden.push(id);
+                    continue;
+                }
+                gix_revision::Spec::Range { from, to } => {
+                    hidden.push(from);
+                    (to, name_of(parsed.second_reference())?)
+                }
+                gix_revision::Spec::ExcludeParents(id) => {
+                    if let Ok(commit) = self.find_object(id)?.try_into_commit() {
+                        hidden.extend(commit.parent_ids().map(|id| id.detach()));
+                    }
+                    (id, name_of(parsed.first_reference())?)
+                }
+                gix_revision::Spec::Merge { .. } | gix_revision::Spec::IncludeOnlyParents(_) => {
+                    return Err(create::Error::UnsupportedSpec { spec: spec.into() })
+                }
+            };
+            if !refs.iter().any(|r| r.name == name) {
+                refs.push(Ref { id, name });
+            }
+        }
+        if refs.is_empty() {
+            return Err(create::Error::Empty);
+        }
+
+        let mut prerequisites = Vec::<Prerequisite>::new();
+        for id in hidden {
+            let commit = match self.find_object(id)?.peel_to_kind(gix_object::Kind::Commit) {
+                Ok(commit) => commit.into_commit(),
+                Err(_) => continue,
+            };
+            if prerequisites.iter().any(|p| p.id == commit.id) {
+                continue;
+            }
+            let comment = commit
+                .message_raw_sloppy()
+                .lines()
+                .next()
+                .map(|subject| subject.as_bstr().to_owned());
+            prerequisites.push(Prerequisite { id: commit.id, comment });
+        }
+
+        let header = Header {
+            version: options.version,
+            object_hash: self.object_hash(),
+            filter: None,
+            prerequisites,
+            refs,
+        };
+        header.write_to(out)?;
+        let tips: Vec<_> = header.refs.iter().map(|r| r.id).collect();
+        let num_objects = crate::pack::write_reachable(
+            self,
+            &tips,
+            header.prerequisites.iter().map(|p| p.id),
+            true,
+            out,
+            &mut progress,
+            should_interrupt,
+        )?;
+        Ok(create::Outcome { header, num_objects })
+    }
+
+    /// Read a bundle from `read` and write the pack it contains into the object database, returning the bundle header
+    /// with the references it contains so the caller can decide which references to create or update.
+    ///
+    /// This fails if the prerequisites of the bundle aren't present in the object database.
+    /// `progress` and `should_interrupt` are used while writing the pack.
+    ///
+    /// ### Configuration
+    ///
+    /// - `pack.threads` and `pack.indexVersion` are used when writing the index of the pack.
+    pub fn unbundle<P>(
+        &self,
+        mut read: impl std::io::BufRead,
+        progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<unbundle::Outcome, unbundle::Error>
+    where
+        P: Progress,
+    {
+        let header = Header::from_read(&mut read)?;
+        if header.object_hash != self.object_hash() {
+            return Err(unbundle::Error::IncompatibleObjectHash {
+                local: self.object_hash(),
+                bundle: header.object_hash,
+            });
+        }
+        if let Some(filter) = &header.filter {
+            return Err(unbundle::Error::Filtered { filter: filter.clone() });
+        }
+        if let Some(prerequisite) = header.prerequisites.iter().find(|p| !self.objects.contains(p.id)) {
+            return Err(unbundle::Error::MissingPrerequisite { id: prerequisite.id });
+        }
+
+        let write_pack_bundle = gix_pack::Bundle::write_to_directory(
+            read,
+            Some(self.objects.store_ref().path().join("pack")),
+            progress,
+            should_interrupt,
+            Some(Box::new({
+                let repo = self.clone();
+                move |oid, buf| repo.objects.find(oid, buf).ok()
+            })),
+            gix_pack::bundle::write::Options {
+                thread_limit: self.config.pack_index_threads()?,
+                index_version: self.config.pack_index_version()?,
+                iteration_mode: gix_pack::data::input::Mode::Verify,
+                object_hash: self.object_hash(),
+            },
+        )?;
+        Ok(unbundle::Outcome {
+            header,
+            write_pack_bundle,
+        })
+    }
+}
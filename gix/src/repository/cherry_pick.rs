@@ -0,0 +1,135 @@
+#![allow(clippy::result_large_err)]
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::ByteSlice,
+    cherry_pick::{Error, Options, Outcome},
+};
+
+/// Cherry-picking and reverting
+impl crate::Repository {
+    /// Apply the changes introduced by `commit` to `HEAD`, its index and its worktree, and commit them with the author and
+    /// message of `commit`, similar to `git cherry-pick`.
+    ///
+    /// The changes are applied with a [three-way merge][crate::Repository::merge_trees()] of the tree of `HEAD` with the
+    /// tree of `commit`, using the tree of its parent as ancestor, and the index and worktree are [updated to match the
+    /// merged tree][crate::Repository::checkout_merge()]. If there are conflicts, nothing is committed and the conflicts
+    /// are left in the index and worktree for resolution, along with the state `git` needs to continue.
+    ///
+    /// ### Deviation
+    ///
+    /// Merge commits aren't supported as `git cherry-pick --mainline` isn't.
+    pub fn cherry_pick(&self, commit: impl Into<ObjectId>, options: Options) -> Result<Outcome<'_>, Error> {
+        if let Some(state) = self.state() {
+            return Err(Error::InProgress(state));
+        }
+        self.apply_commit(commit.into(), false, options, "CHERRY_PICK_HEAD")
+    }
+
+    /// Undo the changes introduced by `commit` in `HEAD`, its index and its worktree, and commit this with a message
+    /// referring to `commit`, similar to `git revert`.
+    ///
+    /// This works like [`cherry_pick()`][crate::Repository::cherry_pick()], but uses the tree of `commit` as ancestor for
+    /// merging the tree of its parent into the tree of `HEAD`.
+    ///
+    /// ### Deviation
+    ///
+    /// Merge commits aren't supported as `git revert --mainline` isn't.
+    pub fn revert(&self, commit: impl Into<ObjectId>, options: Options) -> Result<Outcome<'_>, Error> {
+        if let Some(state) = self.state() {
+            return Err(Error::InProgress(state));
+        }
+        self.apply_commit(commit.into(), true, options, "REVERT_HEAD")
+    }
+
+    /// Apply the changes of the commit with `id` to `HEAD`, or undo them if `revert` is `true`, and commit them. If there are
+    /// conflicts, the id is written into the file `head_name` in the git directory instead, like `CHERRY_PICK_HEAD`.
+    pub(crate) fn apply_commit(
+        &self,
+        id: ObjectId,
+        revert: bool,
+        mut options: Options,
+        head_name: &str,
+    ) -> Result<Outcome<'_>, Error> {
+        let head = self.head_commit()?;
+        let commit = self.find_object(id)?.try_into_commit()?;
+        let parent = {
+            let mut parents = commit.parent_ids();
+            match (parents.next(), parents.next()) {
+                (_, Some(_)) => return Err(Error::MergeCommit { id }),
+                (parent, None) => parent.map_or_else(|| ObjectId::empty_tree(self.object_hash()), |id| id.detach()),
+            }
+        };
+
+        let summary = commit.message()?.summary().into_owned();
+        let commit_label = format!("{} ({})", commit.id().shorten_or_id(), summary);
+        let parent_label = format!("parent of {commit_label}");
+        let (ancestor, theirs, ancestor_label, their_label) = if revert {
+            (id, parent, commit_label, parent_label)
+        } else {
+            (parent, id, parent_label, commit_label)
+        };
+        options
+            .merge
+            .ancestor_label
+            .get_or_insert_with(|| ancestor_label.into());
+        options.merge.our_label.get_or_insert_with(|| "HEAD".into());
+        options.merge.their_label.get_or_insert_with(|| their_label.into());
+        let outcome = self.merge_trees(ancestor, head.id, theirs, options.merge)?;
+
+        let head_tree = head.tree_id()?.detach();
+        if !outcome.has_conflicts() && outcome.tree == head_tree {
+            return Ok(Outcome::Empty);
+        }
+        self.checkout_merge(head_tree, &outcome)?;
+
+        let message = if revert {
+            format!("Revert \"{summary}\"\n\nThis reverts commit {id}.\n")
+        } else {
+            let mut message = commit.message_raw()?.to_str_lossy().trim_end().to_owned();
+            message.push('\n');
+            if options.record_origin {
+                if !ends_with_trailers(&message) {
+                    message.push('\n');
+                }
+                message.push_str(&format!("(cherry picked from commit {id})\n"));
+            }
+            message
+        };
+        if outcome.has_conflicts() {
+            let mut merge_message = format!("{message}\n# Conflicts:\n");
+            for conflict in &outcome.conflicts {
+                merge_message.push_str(&format!("#\t{}\n", conflict.path));
+            }
+            for (name, content) in [("MERGE_MSG", merge_message), (head_name, format!("{id}\n"))] {
+                let path = self.git_dir().join(name);
+                std::fs::write(&path, content).map_err(|source| Error::Io { path, source })?;
+            }
+            return Ok(Outcome::Conflicts(outcome.conflicts));
+        }
+
+        let committer = self.committer().ok_or(crate::commit::Error::CommitterMissing)??;
+        let author = if revert {
+            self.author().ok_or(crate::commit::Error::AuthorMissing)??
+        } else {
+            commit.author()?
+        };
+        let id = self.commit_as(committer, author, "HEAD", message, outcome.tree, Some(head.id))?;
+        Ok(Outcome::Committed(id))
+    }
+}
+
+/// Return `true` if the last paragraph of `message`, which isn't its subject, consists of trailers like `Signed-off-by: …`
+/// or a previous `(cherry picked from commit …)` line, so more of them can be appended without separating them by an
+/// empty line.
+fn ends_with_trailers(message: &str) -> bool {
+    let message = message.trim_end();
+    message.rfind("\n\n").map_or(false, |pos| {
+        message[pos + 2..].lines().all(|line| {
+            line.starts_with("(cherry picked from commit ")
+                || line.split_once(": ").map_or(false, |(token, _)| {
+                    !token.is_empty() && token.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                })
+        })
+    })
+}
@@ -64,6 +64,59 @@ impl crate::Repository {
         Ok(opts)
     }
 
+    /// Obtain the signer configured by `gpg.format`, the program to use for it in `gpg.program` or `gpg.<format>.program`, and
+    /// the key to sign with in `user.signingKey`.
+    ///
+    /// If `user.signingKey` isn't set, the [committer][Self::committer()] name and email are used to select the key like in git,
+    /// except for the `ssh` format which requires the key to be set.
+    ///
+    /// Note that the program is only obtained from trusted configuration files.
+    pub fn signer(&self) -> Result<crate::sign::Program, config::signer::Error> {
+        use crate::{
+            config::{
+                cache::util::ApplyLeniency,
+                tree::{gpg, Gpg},
+            },
+            sign::Format,
+        };
+
+        let config = &self.config.resolved;
+        let mut trusted = self.filter_config_section();
+        let format = config
+            .string_by_key("gpg.format")
+            .map(|value| Gpg::FORMAT.try_into_format(value))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+            .unwrap_or_default();
+        let (subsection, program_key) = match format {
+            Format::OpenPgp => ("openpgp", gpg::OpenPgp::PROGRAM.name),
+            Format::X509 => ("x509", gpg::X509::PROGRAM.name),
+            Format::Ssh => ("ssh", gpg::Ssh::PROGRAM.name),
+        };
+        let program = config
+            .string_filter("gpg", Some(subsection.into()), program_key, &mut trusted)
+            .or_else(|| {
+                (format == Format::OpenPgp)
+                    .then(|| config.string_filter("gpg", None, Gpg::PROGRAM.name, &mut trusted))
+                    .flatten()
+            })
+            .map(|program| gix_path::from_bstr(program).into_owned().into_os_string())
+            .unwrap_or_else(|| format.default_program().into());
+        let key = match config.string_by_key("user.signingKey") {
+            Some(key) => key.into_owned(),
+            None if format == Format::Ssh => return Err(config::signer::Error::SigningKeyMissing),
+            None => {
+                let committer = self.committer().ok_or(config::signer::Error::CommitterMissing)??;
+                let mut key = committer.name.to_owned();
+                key.extend_from_slice(b" <");
+                key.extend_from_slice(committer.email);
+                key.push(b'>');
+                key
+            }
+        };
+        Ok(crate::sign::Program { format, program, key })
+    }
+
     /// The kind of object hash the repository is configured to use.
     pub fn object_hash(&self) -> gix_hash::Kind {
         self.config.object_hash
@@ -121,6 +121,18 @@ impl crate::Repository {
                             .map_err(Into::into)
                     }
 
+                    /// Return the value of `key` in the most specific `http.<url>` section matching `url`, or the last value in `http` sections.
+                    fn url_specific_value(
+                        config: &gix_config::File<'static>,
+                        url: &gix_url::Url,
+                        key: &str,
+                        filter: &mut gix_config::file::MetadataFilter,
+                    ) -> Option<Cow<'static, BStr>> {
+                        config
+                            .string_by_url_filter("http", key, url, filter)
+                            .map(|value| Cow::Owned(value.into_owned()))
+                    }
+
                     fn proxy(
                         value: Option<(Cow<'_, BStr>, Cow<'static, BStr>, &'static config::tree::keys::String)>,
                         lenient: bool,
@@ -138,6 +150,41 @@ impl crate::Repository {
                             }))
                     }
 
+                    /// Like [`url_specific_value()`], but interpolate the value as path, ignoring empty values.
+                    #[allow(clippy::too_many_arguments)]
+                    fn url_specific_path(
+                        repo: &crate::Repository,
+                        config: &gix_config::File<'static>,
+                        url: &gix_url::Url,
+                        key_str: &'static str,
+                        key: &'static config::tree::keys::Path,
+                        filter: &mut gix_config::file::MetadataFilter,
+                        lenient: bool,
+                    ) -> Result<Option<std::path::PathBuf>, config::transport::Error> {
+                        debug_assert_eq!(
+                            key_str,
+                            key.logical_name(),
+                            "BUG: hardcoded and generated key names must match"
+                        );
+                        url_specific_value(config, url, key.name, filter)
+                            .filter(|v| !v.is_empty())
+                            .map(|v| {
+                                use crate::config::cache::interpolate_context;
+                                gix_config::Path::from(v)
+                                    .interpolate(interpolate_context(
+                                        repo.install_dir().ok().as_deref(),
+                                        repo.config.home_dir().as_deref(),
+                                    ))
+                                    .map(|cow| cow.into_owned())
+                            })
+                            .transpose()
+                            .with_leniency(lenient)
+                            .map_err(|err| config::transport::Error::InterpolatePath {
+                                source: err,
+                                key: key_str,
+                            })
+                    }
+
                     let mut opts = http::Options::default();
                     let config = &self.config.resolved;
                     let mut trusted_only = self.filter_config_section();
@@ -349,21 +396,84 @@ impl crate::Repository {
                     };
 
                     if may_use_cainfo {
-                        let key = "http.sslCAInfo";
-                        debug_assert_eq!(key, config::tree::Http::SSL_CA_INFO.logical_name());
-                        opts.ssl_ca_info = config
-                            .path_filter_by_key(key, &mut trusted_only)
-                            .map(|p| {
-                                use crate::config::cache::interpolate_context;
-                                p.interpolate(interpolate_context(
-                                    self.install_dir().ok().as_deref(),
-                                    self.config.home_dir().as_deref(),
-                                ))
-                                .map(|cow| cow.into_owned())
-                            })
-                            .transpose()
-                            .with_leniency(lenient)
-                            .map_err(|err| config::transport::Error::InterpolatePath { source: err, key })?;
+                        opts.ssl_ca_info = url_specific_path(
+                            self,
+                            config,
+                            &url,
+                            "http.sslCAInfo",
+                            &config::tree::Http::SSL_CA_INFO,
+                            &mut trusted_only,
+                            lenient,
+                        )?;
+                    }
+                    opts.ssl_cert = url_specific_path(
+                        self,
+                        config,
+                        &url,
+                        "http.sslCert",
+                        &config::tree::Http::SSL_CERT,
+                        &mut trusted_only,
+                        lenient,
+                    )?;
+                    opts.ssl_key = url_specific_path(
+                        self,
+                        config,
+                        &url,
+                        "http.sslKey",
+                        &config::tree::Http::SSL_KEY,
+                        &mut trusted_only,
+                        lenient,
+                    )?;
+                    {
+                        let no_verify = config
+                            .boolean_filter(
+                                "gitoxide",
+                                Some("http".into()),
+                                gitoxide::Http::SSL_NO_VERIFY.name,
+                                &mut trusted_only,
+                            )
+                            .and_then(Result::ok)
+                            .unwrap_or_default();
+                        opts.ssl_verify = !no_verify
+                            && url_specific_value(config, &url, config::tree::Http::SSL_VERIFY.name, &mut trusted_only)
+                                .map(|v| {
+                                    config::tree::Http::SSL_VERIFY
+                                        .enrich_error(gix_config::Boolean::try_from(v).map(|b| b.0))
+                                })
+                                .transpose()
+                                .with_leniency(lenient)
+                                .map_err(config::transport::http::Error::from)?
+                                .unwrap_or(true);
+                    }
+
+                    {
+                        let key = "http.cookieFile";
+                        debug_assert_eq!(key, config::tree::Http::COOKIE_FILE.logical_name());
+                        opts.cookie_file =
+                            url_specific_value(config, &url, config::tree::Http::COOKIE_FILE.name, &mut trusted_only)
+                                .filter(|v| !v.is_empty())
+                                .map(|v| {
+                                    use crate::config::cache::interpolate_context;
+                                    gix_config::Path::from(v)
+                                        .interpolate(interpolate_context(
+                                            self.install_dir().ok().as_deref(),
+                                            self.config.home_dir().as_deref(),
+                                        ))
+                                        .map(|cow| cow.into_owned())
+                                })
+                                .transpose()
+                                .with_leniency(lenient)
+                                .map_err(|err| config::transport::Error::InterpolatePath { source: err, key })?;
+                        opts.save_cookies =
+                            url_specific_value(config, &url, config::tree::Http::SAVE_COOKIES.name, &mut trusted_only)
+                                .map(|v| {
+                                    config::tree::Http::SAVE_COOKIES
+                                        .enrich_error(gix_config::Boolean::try_from(v).map(|b| b.0))
+                                })
+                                .transpose()
+                                .with_leniency(lenient)
+                                .map_err(config::transport::http::Error::from)?
+                                .unwrap_or_default();
                     }
 
                     {
@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+use crate::{config::tree::Core, hook};
+
+/// Hooks
+impl crate::Repository {
+    /// Return the directory containing hooks, which is `core.hooksPath` if set, or the `hooks` directory in the git directory
+    /// shared by all worktrees.
+    ///
+    /// Like in git, a relative `core.hooksPath` is relative to the root of the worktree, or to the git directory in bare repositories.
+    pub fn hooks_dir(&self) -> Result<PathBuf, gix_config::path::interpolate::Error> {
+        Ok(
+            match self
+                .config
+                .trusted_file_path("core", None, Core::HOOKS_PATH.name)
+                .transpose()?
+            {
+                Some(path) => self.hook_cwd().join(path),
+                None => self.common_dir().join("hooks"),
+            },
+        )
+    }
+
+    /// Return the hook called `name`, like `pre-commit`, ready to be configured and run, or `None` if there is no executable
+    /// file of that name in the [hooks directory][Self::hooks_dir()].
+    pub fn hook(&self, name: &str) -> Result<Option<hook::Prepare>, gix_config::path::interpolate::Error> {
+        let path = self.hooks_dir()?.join(name);
+        if !is_executable(&path) {
+            return Ok(None);
+        }
+        Ok(Some(hook::Prepare {
+            name: name.into(),
+            path,
+            cwd: self.hook_cwd().to_owned(),
+            args: Vec::new(),
+            env: Vec::new(),
+            stdin: None,
+        }))
+    }
+
+    fn hook_cwd(&self) -> &Path {
+        self.work_dir().unwrap_or_else(|| self.git_dir())
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).map_or(false, |meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
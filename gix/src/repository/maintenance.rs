@@ -0,0 +1,597 @@
+use std::{
+    ffi::OsStr,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use gix_commitgraph::{bloom, file::write};
+use gix_hash::ObjectId;
+use gix_odb::FindExt;
+
+use crate::{
+    maintenance::{commit_graph, Error, Options, Outcome},
+    Progress,
+};
+
+/// The files that belong to a pack and are deleted along with it.
+const PACK_EXTENSIONS: &[&str] = &["pack", "idx", "rev", "bitmap", "mtimes"];
+
+impl crate::Repository {
+    /// Consolidate the objects of this repository by writing loose objects, and possibly the objects of all packs, into a new
+    /// pack, similar to `git repack`, configured by `options`.
+    ///
+    /// With [`delete_redundant`][Options::delete_redundant] set, packs and loose objects which became redundant are deleted
+    /// afterwards. Note that objects of alternate object databases are neither packed nor deleted.
+    /// `progress` and `should_interrupt` are used while writing the pack.
+    ///
+    /// ### Configuration
+    ///
+    /// - `pack.threads` and `pack.indexVersion` are used when writing the index of the pack.
+    /// - `pack.writeBitmapHashCache` determines if path hashes are stored in the bitmap, if one is written.
+    /// - `commitGraph.generationVersion` determines if corrected commit dates are stored in the commit-graph, if one is written.
+    pub fn maintenance<P>(
+        &self,
+        options: Options,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error>
+    where
+        P: Progress,
+        P::SubProgress: 'static,
+    {
+        let objects_dir = self.objects.store_ref().path().to_owned();
+        let pack_dir = objects_dir.join("pack");
+        let object_hash = self.object_hash();
+
+        let mut kept = Vec::new();
+        let mut redundant = Vec::new();
+        for index_path in pack_index_paths(&pack_dir)? {
+            let data_path = index_path.with_extension("pack");
+            let is_kept = ["keep", "promisor"]
+                .iter()
+                .any(|extension| index_path.with_extension(extension).is_file())
+                || data_path.file_name().map_or(false, |name| {
+                    options.keep_packs.iter().any(|keep| keep == Path::new(name))
+                });
+            let index = gix_pack::index::File::at(&index_path, object_hash)?;
+            if is_kept {
+                kept.push(index);
+            } else {
+                redundant.push((data_path, index));
+            }
+        }
+
+        let mut loose_objects = Vec::new();
+        let mut input = Vec::new();
+        for id in gix_odb::loose::Store::at(&objects_dir, object_hash).iter() {
+            let id = id?;
+            loose_objects.push(id);
+            let is_packed = |index: &gix_pack::index::File| index.lookup(id).is_some();
+            let skip = kept.iter().any(is_packed)
+                || (!options.all_into_one && redundant.iter().any(|(_, index)| is_packed(index)));
+            if !skip {
+                input.push(id);
+            }
+        }
+        if options.all_into_one {
+            for (_, index) in &redundant {
+                input.extend(index.iter().map(|entry| entry.oid));
+            }
+        }
+
+        let mut bitmap = None;
+        let pack = if input.is_empty() {
+            None
+        } else {
+            let mut pack_data = Vec::new();
+            crate::pack::write_objects(
+                self,
+                input,
+                gix_pack::data::output::count::objects::ObjectExpansion::AsIs,
+                &Default::default(),
+                true,
+                &mut pack_data,
+                &mut progress,
+                should_interrupt,
+            )?;
+            let outcome = gix_pack::Bundle::write_to_directory(
+                pack_data.as_slice(),
+                Some(&pack_dir),
+                progress.add_child("writing pack"),
+                should_interrupt,
+                None,
+                gix_pack::bundle::write::Options {
+                    thread_limit: self.config.pack_index_threads()?,
+                    index_version: self.config.pack_index_version()?,
+                    iteration_mode: gix_pack::data::input::Mode::Verify,
+                    object_hash,
+                },
+            )?;
+            if options.write_bitmap_index && options.all_into_one {
+                bitmap = self.write_bitmap_index(&outcome, &kept, &mut progress, should_interrupt)?;
+            }
+            if let Some(keep_path) = outcome.keep_path.as_ref() {
+                remove_file(keep_path)?;
+            }
+            Some(outcome)
+        };
+
+        let mut deleted_packs = Vec::new();
+        let mut deleted_loose_objects = 0;
+        if options.delete_redundant {
+            let mut remaining = kept;
+            if options.all_into_one {
+                let new_data_path = pack.as_ref().and_then(|pack| pack.data_path.as_ref());
+                for (data_path, index) in redundant {
+                    if Some(&data_path) == new_data_path {
+                        continue;
+                    }
+                    drop(index);
+                    for extension in PACK_EXTENSIONS {
+                        let path = data_path.with_extension(extension);
+                        if path.is_file() {
+                            remove_file(&path)?;
+                        }
+                    }
+                    deleted_packs.push(data_path);
+                }
+                remove_stale_multi_index(&pack_dir, &deleted_packs)?;
+            } else {
+                remaining.extend(redundant.into_iter().map(|(_, index)| index));
+            }
+            if let Some(index_path) = pack.as_ref().and_then(|pack| pack.index_path.as_ref()) {
+                remaining.push(gix_pack::index::File::at(index_path, object_hash)?);
+            }
+
+            for id in loose_objects {
+                if remaining.iter().any(|index| index.lookup(id).is_some()) {
+                    remove_file(&loose_object_path(&objects_dir, &id))?;
+                    deleted_loose_objects += 1;
+                }
+            }
+            remove_empty_fan_out_directories(&objects_dir);
+        }
+
+        let commit_graph = match options.commit_graph {
+            Some(commit_graph_options) if !self.is_shallow() => {
+                self.write_commit_graph(commit_graph_options, &mut progress, should_interrupt)?
+            }
+            _ => None,
+        };
+
+        Ok(Outcome {
+            pack,
+            bitmap,
+            commit_graph,
+            deleted_packs,
+            deleted_loose_objects,
+        })
+    }
+
+    /// Write a bitmap for all references next to the newly written `pack`, unless some of the objects reachable from them
+    /// aren't contained in it, for instance because they are in one of the `kept` packs.
+    fn write_bitmap_index<P>(
+        &self,
+        pack: &gix_pack::bundle::write::Outcome,
+        kept: &[gix_pack::index::File],
+        progress: &mut P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Option<gix_pack::bitmap::write::Outcome>, Error>
+    where
+        P: Progress,
+    {
+        let (bundle, index_path) = match (pack.to_bundle(), pack.index_path.as_ref()) {
+            (Some(bundle), Some(index_path)) => (bundle?, index_path),
+            _ => return Ok(None),
+        };
+        let tips = self.reference_tips()?;
+        // Tips that aren't in the pack are ignored when writing the bitmap, which would then be incomplete.
+        if tips
+            .iter()
+            .any(|tip| kept.iter().any(|index| index.lookup(tip).is_some()))
+        {
+            return Ok(None);
+        }
+
+        let mut data = Vec::new();
+        let outcome = match bundle.write_bitmap_to(
+            tips,
+            &mut data,
+            progress.add_child("writing bitmap"),
+            should_interrupt,
+            gix_pack::bitmap::write::Options {
+                hash_cache: self.config.pack_write_bitmap_hash_cache()?,
+            },
+        ) {
+            Ok(outcome) => outcome,
+            Err(gix_pack::bitmap::write::Error::NotInPack { .. }) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        write_file_atomically(&index_path.with_extension("bitmap"), &data)?;
+        Ok(Some(outcome))
+    }
+
+    /// The objects all references and `HEAD` point to.
+    fn reference_tips(&self) -> Result<Vec<ObjectId>, Error> {
+        let mut tips: Vec<_> = self
+            .references()?
+            .all()?
+            .filter_map(Result::ok)
+            .filter_map(|reference| reference.target().try_id().map(ToOwned::to_owned))
+            .collect();
+        tips.extend(self.head_id().ok().map(crate::Id::detach));
+        Ok(tips)
+    }
+
+    /// Write a commit-graph with all commits reachable from references, either as single file or as new file on top of
+    /// the existing chain of files, as configured by `options`.
+    fn write_commit_graph<P>(
+        &self,
+        options: commit_graph::Options,
+        progress: &mut P,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Option<commit_graph::Outcome>, Error>
+    where
+        P: Progress,
+    {
+        let info_dir = self.objects.store_ref().path().join("info");
+        let graphs_dir = info_dir.join("commit-graphs");
+        let single_file_path = info_dir.join("commit-graph");
+        let chain_path = graphs_dir.join("commit-graph-chain");
+        let open = |paths: &[PathBuf]| -> Option<Vec<gix_commitgraph::file::File>> {
+            paths
+                .iter()
+                .map(|path| gix_commitgraph::file::File::at(path).ok())
+                .collect()
+        };
+
+        // Files that can't be read are ignored, and their commits will be written anew.
+        let single_file = gix_commitgraph::file::File::at(&single_file_path).ok();
+        let mut chain_paths: Vec<_> = std::fs::read_to_string(&chain_path)
+            .map(|chain| {
+                chain
+                    .lines()
+                    .map(|hash| graphs_dir.join(format!("graph-{hash}.graph")))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut chain = open(&chain_paths).unwrap_or_else(|| {
+            chain_paths.clear();
+            Vec::new()
+        });
+        let changed_paths = options.changed_paths
+            || single_file
+                .iter()
+                .chain(&chain)
+                .any(|file| file.bloom_filter_settings().is_some());
+        let corrected_commit_dates = self
+            .config
+            .commit_graph_corrected_commit_dates()
+            .map_err(Error::CommitGraphGenerationVersion)?;
+
+        let mut tips = Vec::new();
+        for tip in self.reference_tips()? {
+            let object = self.find_object(tip)?.peel_tags_to_end()?;
+            if object.kind == gix_object::Kind::Commit {
+                tips.push(object.id);
+            }
+        }
+
+        let mut progress = progress.add_child("writing commit-graph");
+        progress.init(None, gix_features::progress::count("commits"));
+        let (commits, num_kept_files) = if options.split {
+            let num_files = chain.len();
+            let chain_counts: Vec<_> = chain.iter().map(gix_commitgraph::file::File::num_commits).collect();
+            let existing = (!chain.is_empty())
+                .then(|| gix_commitgraph::Graph::new(std::mem::take(&mut chain)))
+                .transpose()?;
+            let mut commits = self.commits_to_write(&tips, existing.as_ref(), &mut progress, should_interrupt)?;
+            drop(existing);
+
+            let mut num_commits = commits.len() as u64;
+            let mut num_kept_files = num_files;
+            for top in chain_counts.iter().rev().map(|count| u64::from(*count)) {
+                if top > 2 * num_commits {
+                    break;
+                }
+                num_commits += top;
+                num_kept_files -= 1;
+            }
+            if num_kept_files != num_files {
+                let kept = open(&chain_paths[..num_kept_files])
+                    .filter(|files| !files.is_empty())
+                    .map(gix_commitgraph::Graph::new)
+                    .transpose()?;
+                commits = self.commits_to_write(&tips, kept.as_ref(), &mut progress, should_interrupt)?;
+            }
+            (commits, num_kept_files)
+        } else {
+            (self.commits_to_write(&tips, None, &mut progress, should_interrupt)?, 0)
+        };
+        if commits.is_empty() {
+            return Ok(None);
+        }
+
+        let replaced_files = open(&chain_paths[num_kept_files..])
+            .unwrap_or_default()
+            .into_iter()
+            .chain(single_file)
+            .collect::<Vec<_>>();
+        let commits = if changed_paths {
+            self.add_bloom_filters(commits, &replaced_files, should_interrupt)?
+        } else {
+            commits
+        };
+        drop(replaced_files);
+
+        let base = open(&chain_paths[..num_kept_files])
+            .filter(|files| !files.is_empty())
+            .map(gix_commitgraph::Graph::new)
+            .transpose()?;
+        let mut data = Vec::new();
+        let outcome = gix_commitgraph::file::File::write_to(
+            commits,
+            base.as_ref(),
+            &mut data,
+            should_interrupt,
+            write::Options {
+                object_hash: self.object_hash(),
+                corrected_commit_dates,
+                changed_paths,
+            },
+        )?;
+        drop(base);
+
+        let create_dir = |dir: &Path| {
+            std::fs::create_dir_all(dir).map_err(|source| Error::Io {
+                path: dir.to_owned(),
+                source,
+            })
+        };
+        let (path, num_files) = if options.split {
+            create_dir(&graphs_dir)?;
+            let path = graphs_dir.join(format!("graph-{}.graph", outcome.checksum));
+            write_file_atomically(&path, &data)?;
+            let mut chain = chain_paths[..num_kept_files]
+                .iter()
+                .filter_map(|path| path.file_stem()?.to_str()?.strip_prefix("graph-"))
+                .fold(String::new(), |mut chain, hash| {
+                    chain.push_str(hash);
+                    chain.push('\n');
+                    chain
+                });
+            chain.push_str(&format!("{}\n", outcome.checksum));
+            write_file_atomically(&chain_path, chain.as_bytes())?;
+            (path, num_kept_files + 1)
+        } else {
+            create_dir(&info_dir)?;
+            write_file_atomically(&single_file_path, &data)?;
+            if chain_path.is_file() {
+                remove_file(&chain_path)?;
+            }
+            (single_file_path.clone(), 1)
+        };
+
+        for replaced in &chain_paths[num_kept_files..] {
+            if replaced.is_file() && *replaced != path {
+                remove_file(replaced)?;
+            }
+        }
+        if options.split && single_file_path.is_file() {
+            remove_file(&single_file_path)?;
+        }
+        if !options.split {
+            std::fs::remove_dir(&graphs_dir).ok();
+        }
+
+        Ok(Some(commit_graph::Outcome {
+            path,
+            num_commits: outcome.num_commits,
+            num_files,
+        }))
+    }
+
+    /// Return all commits reachable from `tips` which aren't contained in `base`, without bloom filters.
+    fn commits_to_write(
+        &self,
+        tips: &[ObjectId],
+        base: Option<&gix_commitgraph::Graph>,
+        progress: &mut impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Vec<write::Commit>, Error> {
+        let mut seen = gix_hashtable::HashSet::default();
+        let mut stack = tips.to_vec();
+        let mut commits = Vec::new();
+        while let Some(id) = stack.pop() {
+            if !seen.insert(id) || base.map_or(false, |base| base.lookup(id).is_some()) {
+                continue;
+            }
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(write::Error::Interrupted.into());
+            }
+            let commit = self.find_object(id)?;
+            let tree = gix_object::CommitRefIter::from_bytes(&commit.data).tree_id()?;
+            let parents: Vec<_> = gix_object::CommitRefIter::from_bytes(&commit.data)
+                .parent_ids()
+                .collect();
+            let committer = gix_object::CommitRefIter::from_bytes(&commit.data).committer()?;
+            stack.extend(parents.iter().copied());
+            commits.push(write::Commit {
+                id,
+                tree,
+                parents,
+                committer_timestamp: committer.time.seconds_since_unix_epoch.into(),
+                bloom_filter: None,
+            });
+            progress.inc();
+        }
+        Ok(commits)
+    }
+
+    /// Set the bloom filter of all `commits`, taking it from the `replaced_files` of the commit-graph if possible, and
+    /// computing it by diffing their tree with the one of their first parent otherwise.
+    fn add_bloom_filters(
+        &self,
+        mut commits: Vec<write::Commit>,
+        replaced_files: &[gix_commitgraph::file::File],
+        should_interrupt: &AtomicBool,
+    ) -> Result<Vec<write::Commit>, Error> {
+        let settings = bloom::Settings::default();
+        let trees: gix_hashtable::HashMap<_, _> = commits.iter().map(|commit| (commit.id, commit.tree)).collect();
+        let mut state = gix_diff::tree::State::default();
+        for commit in &mut commits {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(write::Error::Interrupted.into());
+            }
+            let existing = replaced_files
+                .iter()
+                .filter(|file| file.bloom_filter_settings() == Some(&settings))
+                .find_map(|file| file.bloom_filter_at(file.lookup(commit.id)?))
+                .filter(|filter| !filter.as_bytes().is_empty());
+            if let Some(filter) = existing {
+                commit.bloom_filter = Some(filter.as_bytes().to_owned());
+                continue;
+            }
+
+            let parent_tree = match commit.parents.first() {
+                Some(parent) => Some(match trees.get(parent) {
+                    Some(tree) => *tree,
+                    None => {
+                        let parent = self.find_object(*parent)?;
+                        gix_object::CommitRefIter::from_bytes(&parent.data).tree_id()?
+                    }
+                }),
+                None => None,
+            };
+            let lhs = parent_tree.map(|tree| self.find_object(tree)).transpose()?;
+            let lhs = gix_object::TreeRefIter::from_bytes(lhs.as_ref().map_or(&[], |tree| &tree.data));
+            let rhs = self.find_object(commit.tree)?;
+            let mut recorder =
+                gix_diff::tree::Recorder::default().track_location(Some(gix_diff::tree::recorder::Location::Path));
+            gix_diff::tree::Changes::from(lhs).needed_to_obtain(
+                gix_object::TreeRefIter::from_bytes(&rhs.data),
+                &mut state,
+                |id, buf| self.objects.find_tree_iter(id, buf),
+                &mut recorder,
+            )?;
+            let changed_files = recorder.records.iter().filter_map(|change| {
+                use gix_diff::tree::recorder::Change::*;
+                match change {
+                    Addition { entry_mode, path, .. }
+                    | Deletion { entry_mode, path, .. }
+                    | Modification { entry_mode, path, .. } => (!entry_mode.is_tree()).then(|| path.as_slice()),
+                }
+            });
+            commit.bloom_filter = Some(bloom::filter_data(changed_files, &settings));
+        }
+        Ok(commits)
+    }
+}
+
+fn pack_index_paths(pack_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let entries = match std::fs::read_dir(pack_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => {
+            return Err(Error::Io {
+                path: pack_dir.to_owned(),
+                source,
+            })
+        }
+    };
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|source| Error::Io {
+                path: pack_dir.to_owned(),
+                source,
+            })?
+            .path();
+        let is_pack_index = path.extension() == Some(OsStr::new("idx"))
+            && path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .map_or(false, |name| name.starts_with("pack-"));
+        if is_pack_index {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Remove the multi-pack-index and its auxiliary files if it refers to any of the `deleted_packs`, as it would be invalid
+/// otherwise.
+fn remove_stale_multi_index(pack_dir: &Path, deleted_packs: &[PathBuf]) -> Result<(), Error> {
+    let path = pack_dir.join("multi-pack-index");
+    if !path.is_file() {
+        return Ok(());
+    }
+    let is_stale = {
+        let multi_index = gix_pack::multi_index::File::at(&path)?;
+        multi_index.index_names().iter().any(|name| {
+            deleted_packs
+                .iter()
+                .any(|deleted| deleted.with_extension("idx").file_name() == name.file_name())
+        })
+    };
+    if !is_stale {
+        return Ok(());
+    }
+    remove_file(&path)?;
+    for path in std::fs::read_dir(pack_dir)
+        .map_err(|source| Error::Io {
+            path: pack_dir.to_owned(),
+            source,
+        })?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+    {
+        let is_auxiliary_file = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map_or(false, |name| name.starts_with("multi-pack-index-"));
+        if is_auxiliary_file {
+            remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+fn loose_object_path(objects_dir: &Path, id: &ObjectId) -> PathBuf {
+    let hex = id.to_hex().to_string();
+    objects_dir.join(&hex[..2]).join(&hex[2..])
+}
+
+/// Like `git prune-packed`, try to remove all fan-out directories of loose objects, which only works if they are empty.
+fn remove_empty_fan_out_directories(objects_dir: &Path) {
+    for byte in 0..=u8::MAX {
+        std::fs::remove_dir(objects_dir.join(format!("{byte:02x}"))).ok();
+    }
+}
+
+fn write_file_atomically(path: &Path, data: &[u8]) -> Result<(), Error> {
+    let io_err = |source: std::io::Error| Error::Io {
+        path: path.to_owned(),
+        source,
+    };
+    let mut file = gix_tempfile::new(
+        path.parent().expect("files are always in a directory"),
+        gix_tempfile::ContainingDirectory::Exists,
+        gix_tempfile::AutoRemove::Tempfile,
+    )
+    .map_err(io_err)?;
+    file.with_mut(|file| file.write_all(data))
+        .map_err(io_err)?
+        .map_err(io_err)?;
+    file.persist(path).map_err(|err| io_err(err.error))?;
+    Ok(())
+}
+
+fn remove_file(path: &Path) -> Result<(), Error> {
+    std::fs::remove_file(path).map_err(|source| Error::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
@@ -0,0 +1,284 @@
+#![allow(clippy::result_large_err)]
+use std::collections::{BTreeMap, BTreeSet};
+
+use gix_hash::ObjectId;
+use gix_odb::FindExt;
+
+use crate::{
+    bstr::{BString, ByteSlice, ByteVec},
+    merge::{self, checkout, Conflict, Entry, Error},
+};
+
+/// Merging
+impl crate::Repository {
+    /// Merge the changes from the tree of `ancestor` to the tree of `theirs` into the tree of `ours`, similar to
+    /// `git merge-tree --write-tree`, and write the merged tree along with all blobs it needs into the object database.
+    /// Each of `ancestor`, `ours` and `theirs` may be a tree or something that peels to one, like a commit.
+    ///
+    /// Changes made by only one side, or identical changes of both sides, are taken. Files changed differently by both sides
+    /// are merged line by line with `options`, with conflicts written into the file using conflict markers. All other
+    /// conflicts, like a file deleted by one side but modified by the other, keep the entry of our side, or of their side
+    /// if we deleted it. Each conflict is reported in the returned [outcome][merge::Outcome] along with the entries of each side,
+    /// ready to be [written into the index][merge::Outcome::index()].
+    ///
+    /// ### Deviation
+    ///
+    /// - Renames aren't detected, so a file renamed by one side and modified by the other is treated as deleted and added.
+    /// - Files considered binary as they contain a null byte aren't merged, and keep our side in case of conflict.
+    /// - When one side has a file where the other side has a directory, the directory isn't moved out of the way.
+    pub fn merge_trees(
+        &self,
+        ancestor: impl Into<ObjectId>,
+        ours: impl Into<ObjectId>,
+        theirs: impl Into<ObjectId>,
+        options: merge::Options,
+    ) -> Result<merge::Outcome, Error> {
+        let [ancestor, ours, theirs] =
+            [ancestor.into(), ours.into(), theirs.into()].map(|id| self.find_object(id)?.peel_to_tree().map(|t| t.id));
+        let mut conflicts = Vec::new();
+        let tree = merge_tree(
+            self,
+            [Some(ancestor?), Some(ours?), Some(theirs?)],
+            &mut BString::default(),
+            &options,
+            &mut conflicts,
+        )?;
+        Ok(merge::Outcome { tree, conflicts })
+    }
+
+    /// Update the index and the worktree, which are expected to match the tree `ours`, to the merged tree of `outcome` like
+    /// `git merge` does. The entries of conflicting paths are replaced with their [stages][merge::Conflict::stages()] in
+    /// the index, while their files contain the merged content with conflict markers, or the entry that was kept.
+    ///
+    /// Only paths changed by the merge are written, and nothing is changed if the index differs from `ours`, if one of
+    /// these paths has modifications in the worktree or if an untracked file is in the way, so no local changes are lost.
+    ///
+    /// ### Deviation
+    ///
+    /// - Files are considered modified if their content differs from the blob in the index, which is also the case if
+    ///   conversions like line ending changes were applied when they were checked out.
+    /// - A sparse index is written with its directories expanded.
+    pub fn checkout_merge(
+        &self,
+        ours: impl Into<ObjectId>,
+        outcome: &merge::Outcome,
+    ) -> Result<(), merge::checkout::Error> {
+        use merge::checkout::Error;
+
+        let work_dir = self.work_dir().ok_or_else(|| Error::BareRepository {
+            git_dir: self.git_dir().to_owned(),
+        })?;
+        let ours = checkout::tree_index(self, ours.into())?;
+        let merged_tree = checkout::tree_index(self, outcome.tree)?;
+        let mut index = self.open_index()?;
+        index.expand_sparse_directories(|oid, buf| self.objects.find_tree_iter(oid, buf).ok())?;
+
+        if index.entries().iter().any(|entry| entry.stage() != 0) {
+            return Err(Error::UnmergedIndex);
+        }
+        let (ours, merged, current) = (
+            checkout::entries_by_path(&ours),
+            checkout::entries_by_path(&merged_tree),
+            checkout::entries_by_path(&index),
+        );
+        if let Some(path) = ours
+            .keys()
+            .chain(current.keys())
+            .find(|path| ours.get(*path) != current.get(*path))
+        {
+            return Err(Error::LocalChanges { path: path.clone() });
+        }
+
+        let removed: Vec<&BString> = ours.keys().filter(|path| !merged.contains_key(*path)).collect();
+        let updated: Vec<&BString> = merged
+            .iter()
+            .filter(|(path, entry)| ours.get(*path) != Some(*entry))
+            .map(|(path, _)| path)
+            .collect();
+        let conflicting = outcome.conflicts.iter().map(|conflict| &conflict.path);
+        for path in removed
+            .iter()
+            .copied()
+            .chain(updated.iter().copied())
+            .chain(conflicting)
+        {
+            let file = work_dir.join(gix_path::from_bstr(path.as_bstr()));
+            let skips_worktree = index
+                .entry_by_path_and_stage(path.as_ref(), 0)
+                .map(|entry| entry.flags.contains(gix_index::entry::Flags::SKIP_WORKTREE));
+            let is_unchanged = match (ours.get(path), skips_worktree) {
+                (_, Some(true)) => true,
+                (Some((id, mode)), _) => crate::worktree::file_matches_blob(&file, *mode, id)
+                    .map_err(|source| Error::Io {
+                        path: file.clone(),
+                        source,
+                    })?
+                    .unwrap_or(true),
+                (None, _) => {
+                    if std::fs::symlink_metadata(&file).map_or(false, |meta| !meta.is_dir()) {
+                        return Err(Error::UntrackedFile { path: path.clone() });
+                    }
+                    true
+                }
+            };
+            if !is_unchanged {
+                return Err(Error::LocalChanges { path: path.clone() });
+            }
+        }
+
+        checkout::update_paths(self, &mut index, work_dir, &merged_tree, removed, updated)?;
+        outcome.replace_conflicts_with_stages(&mut index);
+        checkout::write_index(&mut index)?;
+        Ok(())
+    }
+}
+
+/// Merge the trees of the ancestor, ours and theirs, where `None` is an empty tree, and write the result, with `path` being
+/// the location of the trees.
+fn merge_tree(
+    repo: &crate::Repository,
+    trees: [Option<ObjectId>; 3],
+    path: &mut BString,
+    options: &merge::Options,
+    conflicts: &mut Vec<Conflict>,
+) -> Result<ObjectId, Error> {
+    let [ancestor, ours, theirs] = trees.map(|tree| entries(repo, tree));
+    let (ancestor, ours, theirs) = (ancestor?, ours?, theirs?);
+    let names: BTreeSet<&BString> = ancestor.keys().chain(ours.keys()).chain(theirs.keys()).collect();
+
+    let mut tree = gix_object::Tree::empty();
+    for name in names {
+        let prev_len = path.len();
+        if !path.is_empty() {
+            path.push_byte(b'/');
+        }
+        path.push_str(name);
+        let merged = merge_entry(
+            repo,
+            [ancestor.get(name), ours.get(name), theirs.get(name)].map(Option::<&Entry>::copied),
+            path,
+            options,
+            conflicts,
+        )?;
+        path.truncate(prev_len);
+        if let Some(entry) = merged {
+            tree.entries.push(gix_object::tree::Entry {
+                mode: entry.mode,
+                filename: name.clone(),
+                oid: entry.id,
+            });
+        }
+    }
+    tree.entries.sort();
+    Ok(repo.write_object(&tree)?.detach())
+}
+
+/// Merge the entries at `path` and return the merged one, or `None` if it was deleted.
+fn merge_entry(
+    repo: &crate::Repository,
+    [ancestor, ours, theirs]: [Option<Entry>; 3],
+    path: &mut BString,
+    options: &merge::Options,
+    conflicts: &mut Vec<Conflict>,
+) -> Result<Option<Entry>, Error> {
+    if ours == theirs || ancestor == theirs {
+        return Ok(ours);
+    }
+    if ancestor == ours {
+        return Ok(theirs);
+    }
+    let tree_or_none = |entry: Option<Entry>| entry.map_or(true, |entry| entry.mode.is_tree());
+    let tree_id = |entry: Option<Entry>| entry.filter(|entry| entry.mode.is_tree()).map(|entry| entry.id);
+    if tree_or_none(ours) && tree_or_none(theirs) {
+        let id = merge_tree(
+            repo,
+            [tree_id(ancestor), tree_id(ours), tree_id(theirs)],
+            path,
+            options,
+            conflicts,
+        )?;
+        return Ok((id != ObjectId::empty_tree(repo.object_hash())).then_some(Entry {
+            mode: gix_object::tree::EntryMode::Tree,
+            id,
+        }));
+    }
+
+    let conflict = Conflict {
+        path: path.clone(),
+        ancestor,
+        ours,
+        theirs,
+    };
+    match (ours, theirs) {
+        (Some(our_entry), Some(their_entry)) if our_entry.mode.is_blob() && their_entry.mode.is_blob() => {
+            let mode = if our_entry.mode == their_entry.mode || ancestor.map(|e| e.mode) == Some(their_entry.mode) {
+                Some(our_entry.mode)
+            } else if ancestor.map(|e| e.mode) == Some(our_entry.mode) {
+                Some(their_entry.mode)
+            } else {
+                None
+            };
+            let ancestor_data = match ancestor.filter(|e| e.mode.is_blob()) {
+                Some(entry) => repo.find_object(entry.id)?.detach().data,
+                None => Vec::new(),
+            };
+            let our_data = repo.find_object(our_entry.id)?.detach().data;
+            let their_data = repo.find_object(their_entry.id)?.detach().data;
+            if [&ancestor_data, &our_data, &their_data]
+                .iter()
+                .any(|data| is_binary(data))
+            {
+                conflicts.push(conflict);
+                return Ok(ours);
+            }
+
+            let mut merged = Vec::new();
+            let outcome = gix_diff::blob::merge::text(
+                &mut merged,
+                &ancestor_data,
+                &our_data,
+                &their_data,
+                gix_diff::blob::merge::Labels {
+                    ancestor: options.ancestor_label.as_ref().map(|l| l.as_slice()),
+                    ours: options.our_label.as_ref().map(|l| l.as_slice()),
+                    theirs: options.their_label.as_ref().map(|l| l.as_slice()),
+                },
+                options.text,
+            );
+            if outcome.num_conflicts != 0 || mode.is_none() {
+                conflicts.push(conflict);
+            }
+            Ok(Some(Entry {
+                mode: mode.unwrap_or(our_entry.mode),
+                id: repo.write_blob(merged)?.detach(),
+            }))
+        }
+        _ => {
+            conflicts.push(conflict);
+            Ok(ours.or(theirs))
+        }
+    }
+}
+
+/// Return all entries of `tree` by name, or no entry if it's `None`.
+fn entries(repo: &crate::Repository, tree: Option<ObjectId>) -> Result<BTreeMap<BString, Entry>, Error> {
+    let mut out = BTreeMap::new();
+    if let Some(tree) = tree {
+        let tree = repo.find_object(tree)?.try_into_tree()?;
+        for entry in tree.decode()?.entries {
+            out.insert(
+                entry.filename.to_owned(),
+                Entry {
+                    mode: entry.mode,
+                    id: entry.oid.to_owned(),
+                },
+            );
+        }
+    }
+    Ok(out)
+}
+
+/// Return `true` if `data` is considered binary as git would, which is when a null byte is found near its beginning.
+fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(8000)].contains(&0)
+}
@@ -19,19 +19,33 @@ impl crate::Repository {
     }
 }
 
+#[cfg(feature = "archive")]
+mod archive;
+mod bisect;
+mod blame;
+mod bundle;
 mod cache;
+mod cherry_pick;
 mod config;
+mod hook;
 pub(crate) mod identity;
 mod impls;
 mod init;
 mod location;
+mod maintenance;
+mod merge;
 mod object;
 pub(crate) mod permissions;
+mod promisor;
+mod rebase;
 mod reference;
 mod remote;
 mod revision;
 mod shallow;
 mod snapshots;
+mod sparse_checkout;
+mod stash;
 mod state;
+mod submodule;
 mod thread_safe;
 mod worktree;
@@ -8,7 +8,7 @@ use gix_ref::{
     FullName,
 };
 
-use crate::{commit, ext::ObjectIdExt, object, tag, Id, Object, Reference, Tree};
+use crate::{commit, ext::ObjectIdExt, object, sign::Sign, tag, Id, Object, Reference, Tree};
 
 /// Methods related to object creation.
 impl crate::Repository {
@@ -116,6 +116,8 @@ impl crate::Repository {
     /// Similar to [`commit(…)`][crate::Repository::commit()], but allows to create the commit with `committer` and `author` specified.
     ///
     /// This forces setting the commit time and author time by hand. Note that typically, committer and author are the same.
+    ///
+    /// If `commit.gpgSign` is true, the commit is signed with the [configured signer][crate::Repository::signer()].
     pub fn commit_as<'a, 'c, Name, E>(
         &self,
         committer: impl Into<gix_actor::SignatureRef<'c>>,
@@ -129,6 +131,73 @@ impl crate::Repository {
         Name: TryInto<FullName, Error = E>,
         commit::Error: From<E>,
     {
+        use crate::config::{cache::util::ApplyLeniency, tree::Commit};
+
+        let gpg_sign = self
+            .config
+            .resolved
+            .boolean_by_key("commit.gpgSign")
+            .map(|value| Commit::GPG_SIGN.enrich_error(value))
+            .transpose()
+            .with_leniency(self.options.lenient_config)?
+            .unwrap_or(false);
+        let signer = gpg_sign.then(|| self.signer()).transpose()?;
+        self.commit_as_inner(
+            committer.into(),
+            author.into(),
+            reference,
+            message.as_ref(),
+            tree.into(),
+            parents.into_iter().map(Into::into).collect(),
+            signer.as_ref().map(|signer| signer as &dyn Sign),
+        )
+    }
+
+    /// Like [`commit(…)`][crate::Repository::commit()], but signs the commit with `signer` and stores the signature in its `gpgsig` header,
+    /// independently of the value of `commit.gpgSign`.
+    ///
+    /// Use [`signer()`][crate::Repository::signer()] to obtain the signer configured with `gpg.format` and `user.signingKey`.
+    pub fn commit_signed<Name, E>(
+        &self,
+        reference: Name,
+        message: impl AsRef<str>,
+        tree: impl Into<ObjectId>,
+        parents: impl IntoIterator<Item = impl Into<ObjectId>>,
+        signer: &dyn Sign,
+    ) -> Result<Id<'_>, commit::Error>
+    where
+        Name: TryInto<FullName, Error = E>,
+        commit::Error: From<E>,
+    {
+        let author = self.author().ok_or(commit::Error::AuthorMissing)??;
+        let committer = self.committer().ok_or(commit::Error::CommitterMissing)??;
+        self.commit_as_inner(
+            committer,
+            author,
+            reference,
+            message.as_ref(),
+            tree.into(),
+            parents.into_iter().map(Into::into).collect(),
+            Some(signer),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn commit_as_inner<Name, E>(
+        &self,
+        committer: gix_actor::SignatureRef<'_>,
+        author: gix_actor::SignatureRef<'_>,
+        reference: Name,
+        message: &str,
+        tree: ObjectId,
+        parents: smallvec::SmallVec<[ObjectId; 1]>,
+        signer: Option<&dyn Sign>,
+    ) -> Result<Id<'_>, commit::Error>
+    where
+        Name: TryInto<FullName, Error = E>,
+        commit::Error: From<E>,
+    {
+        use gix_object::WriteTo;
         use gix_ref::{
             transaction::{Change, RefEdit},
             Target,
@@ -137,15 +206,40 @@ impl crate::Repository {
         // TODO: possibly use CommitRef to save a few allocations (but will have to allocate for object ids anyway.
         //       This can be made vastly more efficient though if we wanted to, so we lie in the API
         let reference = reference.try_into()?;
-        let commit = gix_object::Commit {
-            message: message.as_ref().into(),
-            tree: tree.into(),
-            author: author.into().to_owned(),
-            committer: committer.into().to_owned(),
+        if let Some(hook) = self.hook("pre-commit")? {
+            hook.env("GIT_INDEX_FILE", self.index_path()).run()?;
+        }
+        let message = match self.hook("commit-msg")? {
+            Some(hook) => {
+                let path = self.git_dir().join("COMMIT_EDITMSG");
+                let to_err = |source| commit::Error::CommitMessageFile {
+                    path: path.clone(),
+                    source,
+                };
+                std::fs::write(&path, message).map_err(to_err)?;
+                hook.arg(&path).run()?;
+                std::fs::read(&path).map_err(to_err)?.into()
+            }
+            None => message.into(),
+        };
+        let mut commit = gix_object::Commit {
+            message,
+            tree,
+            author: author.to_owned(),
+            committer: committer.to_owned(),
             encoding: None,
-            parents: parents.into_iter().map(|id| id.into()).collect(),
+            parents,
             extra_headers: Default::default(),
         };
+        if let Some(signer) = signer {
+            let mut buf = Vec::with_capacity(commit.size());
+            commit.write_to(&mut buf).expect("write to memory works");
+            let mut signature = signer.sign(&buf).map_err(commit::Error::Sign)?;
+            while signature.last() == Some(&b'\n') {
+                signature.pop();
+            }
+            commit.extra_headers.push(("gpgsig".into(), signature));
+        }
 
         let commit_id = self.write_object(&commit)?;
         self.edit_reference(RefEdit {
@@ -170,6 +264,10 @@ impl crate::Repository {
             name: reference,
             deref: true,
         })?;
+        if let Some(hook) = self.hook("post-commit")? {
+            // Like in git, the commit is done and a failing hook can't change that.
+            hook.run().ok();
+        }
         Ok(commit_id)
     }
 
@@ -186,6 +284,11 @@ impl crate::Repository {
     /// If there is no parent, the `reference` is expected to not exist yet.
     ///
     /// The method fails immediately if a `reference` lock can't be acquired.
+    ///
+    /// Like `git commit`, the `pre-commit` and `commit-msg` [hooks][crate::Repository::hook()] are run before the commit is created,
+    /// and the commit isn't created if one of them fails. The `commit-msg` hook receives the path to a file with `message`, and
+    /// the commit uses the message in the file once the hook is done, so it can be changed by the hook. The `post-commit` hook
+    /// runs once `reference` was updated, and its failure is ignored.
     pub fn commit<Name, E>(
         &self,
         reference: Name,
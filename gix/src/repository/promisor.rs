@@ -0,0 +1,95 @@
+#![allow(clippy::result_large_err)]
+use crate::{bstr::BStr, config::tree::Remote, remote};
+
+/// Partial clones
+impl crate::Repository {
+    /// Return the first remote, in order of their names, that is configured with `remote.<name>.promisor = true`, which is
+    /// the remote a partial clone was created from, or `None` if there is no such remote.
+    ///
+    /// Objects omitted from the partial clone can be obtained from it with [`fetch_missing_objects()`][Self::fetch_missing_objects()].
+    pub fn promisor_remote(&self) -> Option<Result<crate::Remote<'_>, remote::find::Error>> {
+        let name = self.remote_names().into_iter().find(|name| {
+            self.config
+                .resolved
+                .boolean_filter(
+                    "remote",
+                    Some(BStr::new(name)),
+                    Remote::PROMISOR.name,
+                    &mut self.filter_config_section(),
+                )
+                .and_then(Result::ok)
+                .unwrap_or(false)
+        })?;
+        self.try_find_remote(name)
+    }
+
+    /// Fetch the objects with the given `ids` from the [promisor remote][Self::promisor_remote()], similar to what `git` does
+    /// when it encounters objects that are missing in a partial clone.
+    ///
+    /// The objects are requested with the `remote.<name>.partialCloneFilter` the repository was cloned with, which makes the remote
+    /// send the requested objects themselves, but nothing that can be reached from them and would be filtered.
+    /// Neither references nor tags are updated.
+    #[cfg(feature = "blocking-network-client")]
+    pub fn fetch_missing_objects<P>(
+        &self,
+        ids: impl IntoIterator<Item = gix_hash::ObjectId>,
+        progress: P,
+        should_interrupt: &std::sync::atomic::AtomicBool,
+    ) -> Result<Option<remote::fetch::Outcome>, crate::promisor::fetch::Error>
+    where
+        P: crate::Progress,
+        P::SubProgress: 'static,
+    {
+        use crate::promisor::fetch::Error;
+
+        let specs: Vec<_> = ids.into_iter().map(|id| id.to_string()).collect();
+        if specs.is_empty() {
+            return Ok(None);
+        }
+        let mut remote = self.promisor_remote().ok_or(Error::NoPromisorRemote)??;
+        remote.replace_refspecs(specs.iter().map(String::as_str), remote::Direction::Fetch)?;
+        let outcome = remote
+            .with_fetch_tags(remote::fetch::Tags::None)
+            .connect(remote::Direction::Fetch, progress)?
+            .prepare_fetch(Default::default())?
+            // Missing objects are typically blobs or trees, so there is nothing to check the connectivity of.
+            .with_connectivity_check(false)
+            .receive(should_interrupt)?;
+        Ok(Some(outcome))
+    }
+
+    /// If `enabled`, let the object database handle in [`objects`][crate::Repository::objects] fetch each object it can't find
+    /// from the [promisor remote][Self::promisor_remote()] before giving up, like `git` does in partial clones.
+    /// This makes objects omitted from a partial clone available transparently to all users of this instance.
+    /// If disabled, which is the default, missing objects are reported as such.
+    ///
+    /// Each missing object is fetched individually, which can make operations that touch many of them slow.
+    /// Prefer to fetch them in bulk with [`fetch_missing_objects()`][Self::fetch_missing_objects()] where possible.
+    ///
+    /// ### Deviation
+    ///
+    /// Errors that occur when fetching objects aren't reported, and the object will appear to be missing instead.
+    #[cfg(feature = "blocking-network-client")]
+    pub fn fetch_missing_objects_on_demand(&mut self, enabled: bool) {
+        self.objects.missing_object_handler = enabled.then(|| {
+            let git_dir = self.git_dir().to_owned();
+            let options = self.options.clone();
+            let handler: gix_odb::store::MissingObjectHandler = std::sync::Arc::new(move |id: &gix_hash::oid| {
+                // Use a new instance to not fetch recursively, as its object database doesn't fetch missing objects.
+                crate::open_opts(git_dir.clone(), options.clone())
+                    .ok()
+                    .and_then(|repo| {
+                        repo.fetch_missing_objects(
+                            Some(id.to_owned()),
+                            crate::progress::Discard,
+                            &crate::interrupt::IS_INTERRUPTED,
+                        )
+                        .ok()
+                    })
+                    .flatten()
+                    .is_some()
+            });
+            handler
+        });
+    }
+}
@@ -0,0 +1,222 @@
+#![allow(clippy::result_large_err)]
+use std::{collections::HashSet, convert::TryInto, path::Path};
+
+use gix_hash::ObjectId;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    bstr::ByteSlice,
+    cherry_pick, merge,
+    rebase::{Error, Options, Outcome, Step},
+};
+
+/// Rebasing
+impl crate::Repository {
+    /// Replay the commits reachable from `HEAD` but not from `upstream` onto `upstream`, or onto
+    /// [another commit][Options::onto], and point the branch `HEAD` refers to at the last replayed commit, similar to
+    /// `git rebase [--onto <onto>] <upstream>`.
+    ///
+    /// Each commit is replayed like [`cherry_pick()`][crate::Repository::cherry_pick()] does, while `HEAD` is detached, and
+    /// commits whose changes are already present are dropped. `ORIG_HEAD` is set to the commit `HEAD` pointed to before.
+    /// The index and the worktree are expected to match `HEAD`, and nothing is changed otherwise.
+    ///
+    /// If a commit can't be replayed without conflicts, the rebase [stops][Outcome::Stopped] and leaves the conflicts for
+    /// resolution, along with the state `git rebase --continue` and `git rebase --abort` need.
+    ///
+    /// ### Deviation
+    ///
+    /// - Merge commits are dropped, as `--rebase-merges` isn't supported.
+    /// - Commits aren't dropped if an equivalent patch is already present upstream, but only if replaying them doesn't change
+    ///   anything.
+    /// - The `pre-rebase` and `post-rewrite` hooks aren't run.
+    pub fn rebase(&self, upstream: impl Into<ObjectId>, options: Options) -> Result<Outcome<'_>, Error> {
+        if let Some(state) = self.state() {
+            return Err(Error::InProgress(state));
+        }
+        let head = self.head_commit()?;
+        let head_name = self.head_name()?;
+        let peel_to_commit = |id: ObjectId| -> Result<ObjectId, Error> {
+            Ok(self.find_object(id)?.peel_to_kind(gix_object::Kind::Commit)?.id)
+        };
+        let upstream = peel_to_commit(upstream.into())?;
+        let onto = peel_to_commit(options.onto.unwrap_or(upstream))?;
+
+        let commits = self.commits_to_rebase(head.id, upstream)?;
+        let mut tip = onto;
+        let is_up_to_date = commits.iter().all(|(id, parents)| {
+            let is_on_tip = parents.as_slice() == [tip];
+            tip = *id;
+            is_on_tip
+        }) && tip == head.id;
+        if is_up_to_date {
+            return Ok(Outcome::UpToDate);
+        }
+        let todo: Vec<ObjectId> = commits
+            .into_iter()
+            .filter(|(_, parents)| parents.len() < 2)
+            .map(|(id, _)| id)
+            .collect();
+
+        let onto_tree = self.find_object(onto)?.try_into_commit()?.tree_id()?.detach();
+        self.checkout_merge(
+            head.tree_id()?,
+            &merge::Outcome {
+                tree: onto_tree,
+                conflicts: Vec::new(),
+            },
+        )?;
+        self.set_reference(
+            "ORIG_HEAD",
+            Target::Peeled(head.id),
+            "rebase: updating ORIG_HEAD".into(),
+        )?;
+        self.set_reference("HEAD", Target::Peeled(onto), format!("rebase (start): checkout {onto}"))?;
+
+        let state_dir = self.git_dir().join("rebase-merge");
+        std::fs::create_dir_all(&state_dir).map_err(|source| Error::Io {
+            path: state_dir.clone(),
+            source,
+        })?;
+        let head_name_content = head_name.as_ref().map_or_else(
+            || "detached HEAD".into(),
+            |name| name.as_bstr().to_str_lossy().into_owned(),
+        );
+        let mut todo_lines = Vec::with_capacity(todo.len());
+        for id in &todo {
+            let summary = self
+                .find_object(*id)?
+                .try_into_commit()?
+                .message()?
+                .summary()
+                .into_owned();
+            todo_lines.push(format!("pick {id} {summary}\n"));
+        }
+        for (name, content) in [
+            ("head-name", format!("{head_name_content}\n")),
+            ("onto", format!("{onto}\n")),
+            ("orig-head", format!("{}\n", head.id)),
+            ("interactive", String::new()),
+            ("drop_redundant_commits", String::new()),
+            ("end", format!("{}\n", todo.len())),
+        ] {
+            write_state(&state_dir, name, content)?;
+        }
+
+        let mut steps = Vec::with_capacity(todo.len());
+        for (step, id) in todo.iter().enumerate() {
+            write_state(&state_dir, "msgnum", format!("{}\n", step + 1))?;
+            write_state(&state_dir, "done", todo_lines[..=step].concat())?;
+            write_state(&state_dir, "git-rebase-todo", todo_lines[step + 1..].concat())?;
+
+            let outcome = self.apply_commit(
+                *id,
+                false,
+                cherry_pick::Options {
+                    merge: options.merge.clone(),
+                    record_origin: false,
+                },
+                "REBASE_HEAD",
+            )?;
+            let is_stopped = matches!(outcome, cherry_pick::Outcome::Conflicts(_));
+            steps.push(Step { commit: *id, outcome });
+            if is_stopped {
+                let commit = self.find_object(*id)?.try_into_commit()?;
+                let author = commit.author()?;
+                let author_script = format!(
+                    "GIT_AUTHOR_NAME={}\nGIT_AUTHOR_EMAIL={}\nGIT_AUTHOR_DATE={}\n",
+                    shell_quote(&author.name.to_str_lossy()),
+                    shell_quote(&author.email.to_str_lossy()),
+                    shell_quote(&format!("@{}", author.time.to_bstring())),
+                );
+                let message = commit.message_raw()?.to_str_lossy().into_owned();
+                write_state(&state_dir, "author-script", author_script)?;
+                write_state(&state_dir, "message", message)?;
+                write_state(&state_dir, "stopped-sha", format!("{id}\n"))?;
+                return Ok(Outcome::Stopped { steps });
+            }
+        }
+
+        let new_head = self.head_id()?.detach();
+        if let Some(name) = head_name {
+            let branch = name.as_bstr().to_str_lossy().into_owned();
+            self.set_reference(
+                branch.as_str(),
+                Target::Peeled(new_head),
+                format!("rebase (finish): {branch} onto {onto}"),
+            )?;
+            self.set_reference(
+                "HEAD",
+                Target::Symbolic(name),
+                format!("rebase (finish): returning to {branch}"),
+            )?;
+        }
+        std::fs::remove_dir_all(&state_dir).map_err(|source| Error::Io {
+            path: state_dir,
+            source,
+        })?;
+        Ok(Outcome::Finished { steps })
+    }
+
+    /// Return the commits reachable from `head` but not from `upstream` along with their parents, with parents before their
+    /// children.
+    fn commits_to_rebase(&self, head: ObjectId, upstream: ObjectId) -> Result<Vec<(ObjectId, Vec<ObjectId>)>, Error> {
+        let mut hidden = HashSet::new();
+        for id in self.rev_walk(Some(upstream)).all()? {
+            hidden.insert(id?.detach());
+        }
+
+        let mut out = Vec::new();
+        let mut stack = vec![(head, None)];
+        while let Some((id, parents)) = stack.pop() {
+            match parents {
+                Some(parents) => out.push((id, parents)),
+                None => {
+                    if !hidden.insert(id) {
+                        continue;
+                    }
+                    let parents: Vec<ObjectId> = self
+                        .find_object(id)?
+                        .try_into_commit()?
+                        .parent_ids()
+                        .map(|id| id.detach())
+                        .collect();
+                    let unvisited: Vec<_> = parents.iter().rev().map(|parent| (*parent, None)).collect();
+                    stack.push((id, Some(parents)));
+                    stack.extend(unvisited);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Point the reference `name` at `target` without dereferencing it, writing `log_message` into its reflog if it has one.
+    fn set_reference(&self, name: &str, target: Target, log_message: String) -> Result<(), Error> {
+        self.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: log_message.into(),
+                },
+                expected: PreviousValue::Any,
+                new: target,
+            },
+            name: name.try_into().map_err(crate::reference::edit::Error::from)?,
+            deref: false,
+        })?;
+        Ok(())
+    }
+}
+
+fn write_state(dir: &Path, name: &str, content: String) -> Result<(), Error> {
+    let path = dir.join(name);
+    std::fs::write(&path, content).map_err(|source| Error::Io { path, source })
+}
+
+/// Quote `value` for use in a shell script like the `author-script` of `git`, which sources it.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
@@ -0,0 +1,12 @@
+use crate::sparse_checkout;
+
+/// Sparse checkouts
+impl crate::Repository {
+    /// Return a platform to list, set, add to or disable the patterns of the sparse checkout of this repository's worktree,
+    /// which determine the files that are present in it.
+    ///
+    /// Changing the sparse checkout also updates the configuration of this instance.
+    pub fn sparse_checkout(&mut self) -> sparse_checkout::Platform<'_> {
+        sparse_checkout::Platform { repo: self }
+    }
+}
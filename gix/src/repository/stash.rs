@@ -0,0 +1,10 @@
+use crate::stash;
+
+/// Stashing
+impl crate::Repository {
+    /// Return a platform to save changes of the index and the worktree as new stash entry, and to list, apply and drop
+    /// existing entries.
+    pub fn stash(&self) -> stash::Platform<'_> {
+        stash::Platform { repo: self }
+    }
+}
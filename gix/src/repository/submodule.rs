@@ -0,0 +1,98 @@
+use std::borrow::Cow;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    submodule, Submodule,
+};
+
+/// Submodules
+impl crate::Repository {
+    /// Return all submodules configured in the `.gitmodules` file at the root of the worktree, or in the tree of `HEAD` if
+    /// this repository is bare, in the order of their configuration. Return `None` if there is no `.gitmodules` file.
+    ///
+    /// The `url` and `branch` of each submodule can be overridden in the configuration of this repository, which is where
+    /// `git submodule init` copies the url to.
+    pub fn submodules(&self) -> Result<Option<Vec<Submodule<'_>>>, submodule::modules::Error> {
+        let (path, data) = match self.work_dir() {
+            Some(work_dir) => {
+                let path = work_dir.join(".gitmodules");
+                match std::fs::read(&path) {
+                    Ok(data) => (Some(path), data),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                    Err(source) => return Err(submodule::modules::Error::Io { path, source }),
+                }
+            }
+            None => {
+                let entry = match submodule::head_tree(self)? {
+                    Some(tree) => tree.lookup_entry_by_path(".gitmodules")?,
+                    None => None,
+                };
+                match entry {
+                    Some(entry) => (None, entry.object()?.detach().data),
+                    None => return Ok(None),
+                }
+            }
+        };
+        let modules = gix_config::File::from_bytes_no_includes(
+            &data,
+            gix_config::file::Metadata {
+                path,
+                source: gix_config::Source::Worktree,
+                level: 0,
+                trust: gix_sec::Trust::Reduced,
+            },
+            Default::default(),
+        )?;
+
+        let mut names = Vec::<BString>::new();
+        for section in modules.sections_by_name("submodule").into_iter().flatten() {
+            if let Some(name) = section.header().subsection_name() {
+                if !names.iter().any(|existing| existing == name) {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+
+        let config = &self.config.resolved;
+        names
+            .into_iter()
+            .map(|name| {
+                if has_parent_component(name.as_ref()) {
+                    return Err(submodule::modules::Error::InvalidName { name });
+                }
+                let subsection = Some(name.as_bstr());
+                let path = modules
+                    .string("submodule", subsection, "path")
+                    .map(Cow::into_owned)
+                    .ok_or_else(|| submodule::modules::Error::MissingPath { name: name.clone() })?;
+                if path.is_empty()
+                    || gix_path::from_bstr(path.as_bstr()).is_absolute()
+                    || has_parent_component(path.as_ref())
+                {
+                    return Err(submodule::modules::Error::InvalidPath { name, path });
+                }
+                let local_url = config.string("submodule", subsection, "url").map(Cow::into_owned);
+                let is_initialized = local_url.is_some();
+                let url = local_url.or_else(|| modules.string("submodule", subsection, "url").map(Cow::into_owned));
+                let branch = config
+                    .string("submodule", subsection, "branch")
+                    .or_else(|| modules.string("submodule", subsection, "branch"))
+                    .map(Cow::into_owned);
+                Ok(Submodule {
+                    name,
+                    path,
+                    url,
+                    branch,
+                    is_initialized,
+                    repo: self,
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+}
+
+fn has_parent_component(path: &BStr) -> bool {
+    path.split(|b| *b == b'/' || *b == b'\\')
+        .any(|component| component == b"..")
+}
@@ -27,6 +27,56 @@ impl crate::Repository {
     }
 }
 
+/// Worktree management
+impl crate::Repository {
+    /// Create a new linked worktree at `path`, which must not exist or be an empty directory, check out what's configured in
+    /// `options` into it and return it as repository, like `git worktree add`.
+    ///
+    /// Its administrative files are placed into `worktrees/<name>` of the common git directory, where `<name>` is the last
+    /// component of `path`, with a number appended if it is taken already.
+    ///
+    /// ### Deviation
+    ///
+    /// No reflog is written for the `HEAD` of the new worktree, and no filters are applied to the files written into the worktree.
+    #[allow(clippy::result_large_err)]
+    pub fn worktree_add(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        options: worktree::add::Options,
+    ) -> Result<crate::Repository, worktree::add::Error> {
+        worktree::add::worktree_add(self, path.as_ref(), options)
+    }
+
+    /// Remove the administrative files of all linked worktrees which have a [prune reason][worktree::Proxy::prune_reason()],
+    /// typically because their checkout was deleted, and return the ids of the pruned worktrees, like `git worktree prune`.
+    ///
+    /// Locked worktrees are never pruned.
+    pub fn worktree_prune(&self) -> std::io::Result<Vec<crate::bstr::BString>> {
+        let mut pruned = Vec::new();
+        let worktrees = self.common_dir().join("worktrees");
+        let iter = match std::fs::read_dir(&worktrees) {
+            Ok(iter) => iter,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(pruned),
+            Err(err) => return Err(err),
+        };
+        for entry in iter {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let proxy = worktree::Proxy::new(self, entry.path());
+            if proxy.prune_reason().is_some() {
+                std::fs::remove_dir_all(proxy.git_dir())?;
+                pruned.push(proxy.id().to_owned());
+            }
+        }
+        // Like git, remove the directory once the last worktree is gone, which fails if there are worktrees left.
+        std::fs::remove_dir(worktrees).ok();
+        pruned.sort();
+        Ok(pruned)
+    }
+}
+
 /// Interact with individual worktrees and their information.
 impl crate::Repository {
     /// Return the repository owning the main worktree, typically from a linked worktree.
@@ -302,11 +302,13 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
     fn index_lookup(&mut self, path: &BStr, stage: u8) -> Option<()> {
         self.unset_disambiguate_call();
         match self.repo.index() {
-            Ok(index) => match index.entry_by_path_and_stage(path, stage.into()) {
-                Some(entry) => {
-                    self.objs[self.idx]
-                        .get_or_insert_with(HashSet::default)
-                        .insert(entry.id);
+            Ok(index) => match index
+                .entry_by_path_and_stage(path, stage.into())
+                .map(|entry| entry.id)
+                .or_else(|| (stage == 0).then(|| lookup_in_sparse_directory(self.repo, &index, path))?)
+            {
+                Some(id) => {
+                    self.objs[self.idx].get_or_insert_with(HashSet::default).insert(id);
                     Some(())
                 }
                 None => {
@@ -338,3 +340,16 @@ impl<'repo> delegate::Navigate for Delegate<'repo> {
         }
     }
 }
+
+/// Find `path` in the tree of the directory entry of a sparse index that contains it, as it isn't listed on its own.
+fn lookup_in_sparse_directory(repo: &crate::Repository, index: &gix_index::State, path: &BStr) -> Option<ObjectId> {
+    let dir = index.sparse_directory_containing(path)?;
+    let path_in_dir = &path[dir.path(index).len()..];
+    repo.find_object(dir.id)
+        .ok()?
+        .try_into_tree()
+        .ok()?
+        .lookup_entry_by_path(gix_path::from_bstr(path_in_dir))
+        .ok()?
+        .map(|entry| entry.object_id())
+}
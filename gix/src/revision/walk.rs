@@ -1,7 +1,10 @@
-use gix_hash::ObjectId;
+use gix_hash::{oid, ObjectId};
 use gix_odb::FindExt;
 
-use crate::{revision, Repository};
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    revision, Repository,
+};
 
 /// The error returned by [`Platform::all()`].
 #[derive(Debug, thiserror::Error)]
@@ -20,6 +23,7 @@ pub struct Platform<'repo> {
     pub(crate) tips: Vec<ObjectId>,
     pub(crate) sorting: gix_traverse::commit::Sorting,
     pub(crate) parents: gix_traverse::commit::Parents,
+    pub(crate) paths: Vec<BString>,
 }
 
 impl<'repo> Platform<'repo> {
@@ -29,6 +33,7 @@ impl<'repo> Platform<'repo> {
             tips: tips.into_iter().map(Into::into).collect(),
             sorting: Default::default(),
             parents: Default::default(),
+            paths: Vec::new(),
         }
     }
 }
@@ -46,6 +51,27 @@ impl<'repo> Platform<'repo> {
         self.parents = gix_traverse::commit::Parents::First;
         self
     }
+
+    /// Only yield commits which changed any of the given `paths` compared to their first parent, or which contain any of them
+    /// if they have no parent, similar to `git log -- <paths>`.
+    ///
+    /// Paths are relative to the root of the repository and use slashes as separators, and paths to directories match all
+    /// changes to files within them. If the repository has a commit-graph with changed-path bloom filters, these are consulted
+    /// first to avoid looking at the trees of most commits that didn't change any of the `paths`.
+    ///
+    /// ### Deviation
+    ///
+    /// History isn't simplified, so all commits are still traversed, and merge commits are only compared to their first parent.
+    pub fn paths(mut self, paths: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        self.paths = paths
+            .into_iter()
+            .map(|path| {
+                let path: BString = path.into();
+                path.trim_with(|c| c == '/').into()
+            })
+            .collect();
+        self
+    }
 }
 
 /// Produce the iterator
@@ -62,10 +88,10 @@ impl<'repo> Platform<'repo> {
             tips,
             sorting,
             parents,
+            paths,
         } = self;
-        Ok(revision::Walk {
-            repo,
-            inner: Box::new(
+        let mut inner: Box<dyn Iterator<Item = Result<ObjectId, gix_traverse::commit::ancestors::Error>> + 'repo> =
+            Box::new(
                 gix_traverse::commit::Ancestors::filtered(
                     tips,
                     gix_traverse::commit::ancestors::State::default(),
@@ -95,11 +121,126 @@ impl<'repo> Platform<'repo> {
                 )
                 .sorting(sorting)?
                 .parents(parents),
-            ),
-        })
+            );
+        if !paths.is_empty() {
+            let mut filter = PathFilter {
+                repo,
+                paths,
+                // A missing or unreadable commit-graph only means that all trees have to be looked at.
+                graph: gix_commitgraph::Graph::from_info_dir(repo.objects.store_ref().path().join("info")).ok(),
+                keys: Vec::new(),
+                buf: Vec::new(),
+            };
+            inner = Box::new(inner.filter_map(move |res| {
+                res.and_then(|id| Ok(filter.changes_paths(&id)?.then_some(id)))
+                    .transpose()
+            }));
+        }
+        Ok(revision::Walk { repo, inner })
     }
 }
 
+/// Decides if commits changed any of the paths a walk is limited to.
+struct PathFilter<'repo> {
+    repo: &'repo Repository,
+    paths: Vec<BString>,
+    graph: Option<gix_commitgraph::Graph>,
+    /// The keys of all `paths` for each of the filter settings encountered so far.
+    keys: Vec<(gix_commitgraph::bloom::Settings, Vec<gix_commitgraph::bloom::Key>)>,
+    buf: Vec<u8>,
+}
+
+impl PathFilter<'_> {
+    fn changes_paths(&mut self, id: &oid) -> Result<bool, gix_traverse::commit::ancestors::Error> {
+        let filter = self
+            .graph
+            .as_ref()
+            .and_then(|graph| graph.commit_by_id(id))
+            .and_then(|commit| commit.bloom_filter());
+        if let Some((filter, settings)) = filter {
+            let keys = match self.keys.iter().position(|(existing, _)| existing == settings) {
+                Some(pos) => &self.keys[pos].1,
+                None => {
+                    let keys = self
+                        .paths
+                        .iter()
+                        .map(|path| gix_commitgraph::bloom::Key::new(path, settings))
+                        .collect();
+                    self.keys.push((*settings, keys));
+                    &self.keys[self.keys.len() - 1].1
+                }
+            };
+            if !keys.iter().any(|key| filter.contains(key)) {
+                return Ok(false);
+            }
+        }
+
+        let repo = self.repo;
+        let buf = &mut self.buf;
+        let mut commit = find_commit_iter(repo, id, buf)?;
+        let tree = commit.tree_id()?;
+        let parent = commit.parent_ids().next();
+        let parent_tree = match parent {
+            Some(parent) => Some(find_commit_iter(repo, &parent, buf)?.tree_id()?),
+            None => None,
+        };
+        if Some(tree) == parent_tree {
+            return Ok(false);
+        }
+        for path in &self.paths {
+            let parent_entry = match parent_tree {
+                Some(parent_tree) => entry_at(repo, parent_tree, path.as_ref(), buf)?,
+                None => None,
+            };
+            if entry_at(repo, tree, path.as_ref(), buf)? != parent_entry {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Return the mode and id of the entry at `path` in `tree`, if there is one.
+fn entry_at(
+    repo: &Repository,
+    mut tree: ObjectId,
+    path: &BStr,
+    buf: &mut Vec<u8>,
+) -> Result<Option<(gix_object::tree::EntryMode, ObjectId)>, gix_traverse::commit::ancestors::Error> {
+    let mut components = path.split(|b| *b == b'/').peekable();
+    while let Some(component) = components.next() {
+        let entry = repo
+            .objects
+            .find_tree_iter(tree, buf)
+            .map_err(|err| gix_traverse::commit::ancestors::Error::FindExisting {
+                oid: tree,
+                source: err.into(),
+            })?
+            .filter_map(Result::ok)
+            .find(|entry| entry.filename == component)
+            .map(|entry| (entry.mode, entry.oid.to_owned()));
+        match entry {
+            Some(entry) if components.peek().is_none() => return Ok(Some(entry)),
+            Some((mode, id)) if mode.is_tree() => tree = id,
+            _ => break,
+        }
+    }
+    Ok(None)
+}
+
+fn find_commit_iter<'a>(
+    repo: &Repository,
+    id: &oid,
+    buf: &'a mut Vec<u8>,
+) -> Result<gix_object::CommitRefIter<'a>, gix_traverse::commit::ancestors::Error> {
+    repo.objects
+        .find_commit_iter(id, buf)
+        .map_err(|err| gix_traverse::commit::ancestors::Error::FindExisting {
+            oid: id.to_owned(),
+            source: err.into(),
+        })
+}
+
 pub(crate) mod iter {
     use crate::{ext::ObjectIdExt, Id};
 
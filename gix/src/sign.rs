@@ -0,0 +1,184 @@
+//! Sign objects, like commits, to produce signatures suitable for the `gpgsig` header.
+use std::{
+    ffi::OsString,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::bstr::{BString, ByteSlice};
+
+/// A type able to produce a detached, ASCII-armored signature over arbitrary data.
+///
+/// Use it with [`Repository::commit_signed()`][crate::Repository::commit_signed()] to sign commits with custom signing backends,
+/// or obtain the implementation configured via `gpg.format` and `user.signingKey` with [`Repository::signer()`][crate::Repository::signer()].
+pub trait Sign {
+    /// Return the detached signature of `data`, as it should be placed into the `gpgsig` header.
+    fn sign(&self, data: &[u8]) -> Result<BString, Box<dyn std::error::Error + Send + Sync + 'static>>;
+}
+
+impl<T> Sign for &T
+where
+    T: Sign + ?Sized,
+{
+    fn sign(&self, data: &[u8]) -> Result<BString, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        (*self).sign(data)
+    }
+}
+
+/// The format of signatures, as configured by `gpg.format`.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Format {
+    /// Create OpenPGP signatures, with `gpg` by default.
+    #[default]
+    OpenPgp,
+    /// Create X.509 signatures, with `gpgsm` by default.
+    X509,
+    /// Create SSH signatures, with `ssh-keygen` by default.
+    Ssh,
+}
+
+impl Format {
+    /// Return the name of the program used to sign data in this format if no program is configured.
+    pub fn default_program(&self) -> &'static str {
+        match self {
+            Format::OpenPgp => "gpg",
+            Format::X509 => "gpgsm",
+            Format::Ssh => "ssh-keygen",
+        }
+    }
+}
+
+/// A signer which drives an external program, like `gpg` or `ssh-keygen`, to produce signatures the same way git does.
+///
+/// ### SSH Signatures
+///
+/// When signing in the [`Ssh`][Format::Ssh] format, `key` is either the path to a private or public key file, or a literal public key
+/// prefixed with `key::` or starting with `ssh-`. Public keys make `ssh-keygen` use the respective private key held by `ssh-agent`.
+#[derive(Debug, Clone)]
+pub struct Program {
+    /// The format of the signatures to produce.
+    pub format: Format,
+    /// The program to invoke.
+    pub program: OsString,
+    /// The key to sign with, as understood by `program`.
+    pub key: BString,
+}
+
+///
+pub mod program {
+    use crate::bstr::BString;
+
+    /// The error returned by the [`Sign`][super::Sign] implementation of [`Program`][super::Program].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not run the signing program {program:?}")]
+        Spawn {
+            program: std::ffi::OsString,
+            source: std::io::Error,
+        },
+        #[error("Could not communicate with the signing program or write the signing key to a temporary file")]
+        Io(#[from] std::io::Error),
+        #[error("The signing program failed with {status}: {stderr}")]
+        Failed {
+            status: std::process::ExitStatus,
+            stderr: BString,
+        },
+        #[error("The signing program didn't report that it created a signature")]
+        NoSignatureCreated,
+    }
+}
+
+impl Program {
+    fn sign_inner(&self, data: &[u8]) -> Result<BString, program::Error> {
+        let mut cmd = Command::new(&self.program);
+        // Keep the literal key in a tempfile which lives until the program is done with it.
+        let mut _literal_key_file = None;
+        match self.format {
+            Format::OpenPgp | Format::X509 => {
+                cmd.arg("--status-fd=2")
+                    .arg("-bsau")
+                    .arg(gix_path::from_bstr(self.key.as_bstr()).as_os_str());
+            }
+            Format::Ssh => {
+                cmd.args(["-Y", "sign", "-n", "git", "-f"]);
+                match literal_ssh_key(self.key.as_bstr()) {
+                    Some(key) => {
+                        let mut file = gix_tempfile::new(
+                            std::env::temp_dir(),
+                            gix_tempfile::ContainingDirectory::Exists,
+                            gix_tempfile::AutoRemove::Tempfile,
+                        )?;
+                        let path = file.with_mut(|file| {
+                            file.write_all(key)?;
+                            file.write_all(b"\n")?;
+                            Ok::<_, std::io::Error>(file.path().to_owned())
+                        })??;
+                        cmd.arg(path).arg("-U");
+                        _literal_key_file = Some(file);
+                    }
+                    None => {
+                        cmd.arg(gix_path::from_bstr(self.key.as_bstr()).as_os_str());
+                    }
+                }
+            }
+        }
+
+        let mut child = cmd
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|source| program::Error::Spawn {
+                program: self.program.clone(),
+                source,
+            })?;
+        let mut stdin = child.stdin.take().expect("configured");
+        let writer = std::thread::scope(|scope| {
+            let writer = scope.spawn(move || match stdin.write_all(data) {
+                Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+                res => res,
+            });
+            let output = child.wait_with_output();
+            (writer.join().expect("writer thread doesn't panic"), output)
+        });
+        let output = match writer {
+            (_, Err(err)) | (Err(err), _) => return Err(err.into()),
+            (Ok(()), Ok(output)) => output,
+        };
+        if !output.status.success() {
+            return Err(program::Error::Failed {
+                status: output.status,
+                stderr: output.stderr.into(),
+            });
+        }
+        let created_signature = match self.format {
+            Format::OpenPgp | Format::X509 => output
+                .stderr
+                .lines()
+                .any(|line| line.starts_with(b"[GNUPG:] SIG_CREATED ")),
+            Format::Ssh => true,
+        };
+        if !created_signature || output.stdout.is_empty() {
+            return Err(program::Error::NoSignatureCreated);
+        }
+        Ok(output.stdout.into())
+    }
+}
+
+impl Sign for Program {
+    fn sign(&self, data: &[u8]) -> Result<BString, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.sign_inner(data).map_err(Into::into)
+    }
+}
+
+/// Return the literal public key contained in `key`, or `None` if it's a path to a key file.
+fn literal_ssh_key(key: &crate::bstr::BStr) -> Option<&[u8]> {
+    if let Some(key) = key.strip_prefix(b"key::") {
+        Some(key)
+    } else if key.starts_with(b"ssh-") {
+        Some(key.as_bytes())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,334 @@
+//! Sparse checkouts, which only populate the worktree with the index entries that match the patterns in
+//! `.git/info/sparse-checkout`.
+//!
+//! See [`Repository::sparse_checkout()`][crate::Repository::sparse_checkout()].
+#![allow(clippy::result_large_err)]
+use std::{path::Path, sync::atomic::AtomicBool};
+
+use gix_index::entry::Flags;
+use gix_odb::FindExt;
+use gix_worktree::sparse::{Cone, Patterns};
+
+use crate::{
+    bstr::{BStr, BString},
+    config::tree::{Core, Extensions, Index, Key},
+    Repository,
+};
+
+/// The error returned by the methods of [`Platform`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Repository at \"{}\" is a bare repository and cannot have a sparse checkout", git_dir.display())]
+    BareRepository { git_dir: std::path::PathBuf },
+    #[error("The worktree is not sparse, so there are no patterns to add to")]
+    NotSparse,
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error("Could not read or write '{}'", path.display())]
+    Io {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Could not read or write the configuration file at '{}'", path.display())]
+    ConfigFile {
+        path: std::path::PathBuf,
+        source: gix_config::file::init::from_paths::Error,
+    },
+    #[error(transparent)]
+    ConfigOverride(#[from] crate::config::overrides::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    IndexCheckout(
+        #[from]
+        gix_worktree::index::checkout::Error<gix_odb::find::existing_object::Error<gix_odb::store::find::Error>>,
+    ),
+    #[error("Could not expand the directories of the sparse index")]
+    ExpandSparseIndex(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+}
+
+/// The result of changing the sparse checkout of a worktree.
+#[derive(Default)]
+pub struct Outcome {
+    /// The amount of index entries whose [`SKIP_WORKTREE`][Flags::SKIP_WORKTREE] flag changed.
+    pub entries_changed: usize,
+    /// The amount of files that were removed from the worktree as they aren't included anymore.
+    pub files_removed: usize,
+    /// The paths of files that aren't included anymore, but were kept in the worktree and remain included as they
+    /// were modified, like `git` does.
+    pub modified_files_kept: Vec<BString>,
+    /// The outcome of checking out the files that are included now.
+    pub checkout: gix_worktree::index::checkout::Outcome,
+}
+
+/// A platform to query and change the sparse checkout of a repository's worktree, obtained with
+/// [`Repository::sparse_checkout()`][crate::Repository::sparse_checkout()].
+pub struct Platform<'repo> {
+    pub(crate) repo: &'repo mut Repository,
+}
+
+/// Access
+impl<'repo> Platform<'repo> {
+    /// Return the sparse checkout patterns of the worktree, or `None` if `core.sparseCheckout` isn't enabled or if there
+    /// is no `.git/info/sparse-checkout` file.
+    pub fn patterns(&self) -> Result<Option<Patterns>, Error> {
+        Ok(self.repo.config.sparse_checkout(self.repo.git_dir())?)
+    }
+
+    /// Return the directories of the sparse checkout in cone mode, or its patterns otherwise, or `None` if the worktree
+    /// isn't sparse, like `git sparse-checkout list`.
+    pub fn list(&self) -> Result<Option<Vec<BString>>, Error> {
+        Ok(self.patterns()?.map(|patterns| patterns.to_list()))
+    }
+}
+
+/// Modification
+impl<'repo> Platform<'repo> {
+    /// Make the worktree sparse, containing only the files in the root directory and in the given `directories` recursively
+    /// if `cone` is `true`, or only the files that match `patterns` in `.gitignore` format otherwise, like
+    /// `git sparse-checkout set [--[no-]cone]`.
+    ///
+    /// This enables `core.sparseCheckout` and sets `core.sparseCheckoutCone` in the repository configuration, writes the
+    /// patterns to `.git/info/sparse-checkout` and updates the worktree and the index accordingly.
+    pub fn set(&mut self, patterns: impl IntoIterator<Item = impl AsRef<BStr>>, cone: bool) -> Result<Outcome, Error> {
+        let patterns = if cone {
+            Patterns::Cone(Cone::from_directories(patterns))
+        } else {
+            Patterns::non_cone(patterns)
+        };
+        self.write_config(true, Some(cone))?;
+        self.write_patterns(&patterns)?;
+        self.update_worktree(Some(&patterns))
+    }
+
+    /// Add the given `directories` in cone mode, or `patterns` otherwise, to the patterns of the sparse checkout and update
+    /// the worktree and the index accordingly, like `git sparse-checkout add`.
+    ///
+    /// It's an error if the worktree isn't sparse yet.
+    pub fn add(&mut self, patterns: impl IntoIterator<Item = impl AsRef<BStr>>) -> Result<Outcome, Error> {
+        let patterns = match self.patterns()?.ok_or(Error::NotSparse)? {
+            Patterns::Cone(cone) => Patterns::Cone(Cone::from_directories(
+                cone.directories()
+                    .map(ToOwned::to_owned)
+                    .chain(patterns.into_iter().map(|dir| dir.as_ref().to_owned())),
+            )),
+            previous @ Patterns::NonCone(_) => Patterns::non_cone(
+                previous
+                    .to_list()
+                    .into_iter()
+                    .chain(patterns.into_iter().map(|pattern| pattern.as_ref().to_owned())),
+            ),
+        };
+        self.write_patterns(&patterns)?;
+        self.update_worktree(Some(&patterns))
+    }
+
+    /// Check out all files of the index and disable `core.sparseCheckout`, like `git sparse-checkout disable`.
+    ///
+    /// The `.git/info/sparse-checkout` file is kept so the same patterns can be used when enabling the sparse checkout again.
+    pub fn disable(&mut self) -> Result<Outcome, Error> {
+        self.write_config(false, None)?;
+        self.update_worktree(None)
+    }
+}
+
+/// Utilities
+impl<'repo> Platform<'repo> {
+    /// Like `git`, write the configuration to `config.worktree` if `extensions.worktreeConfig` is enabled, so it only
+    /// affects the current worktree.
+    fn write_config(&mut self, enabled: bool, cone: Option<bool>) -> Result<(), Error> {
+        let per_worktree = self
+            .repo
+            .config
+            .resolved
+            .boolean_by_key(Extensions::WORKTREE_CONFIG.logical_name().as_str())
+            .and_then(Result::ok)
+            .unwrap_or(false);
+        let (path, source) = if per_worktree {
+            (
+                self.repo.git_dir().join("config.worktree"),
+                gix_config::Source::Worktree,
+            )
+        } else {
+            (self.repo.common_dir().join("config"), gix_config::Source::Local)
+        };
+        let mut config = if path.is_file() {
+            gix_config::File::from_path_no_includes(path.clone(), source).map_err(|source| Error::ConfigFile {
+                path: path.clone(),
+                source,
+            })?
+        } else {
+            gix_config::File::new(gix_config::file::Metadata::from(source))
+        };
+        let mut values = vec![(Core::SPARSE_CHECKOUT.name, enabled)];
+        values.extend(cone.map(|cone| (Core::SPARSE_CHECKOUT_CONE.name, cone)));
+        for (key, value) in &values {
+            config
+                .set_raw_value("core", None, *key, if *value { "true" } else { "false" })
+                .expect("works - statically known");
+        }
+        std::fs::write(&path, config.to_bstring()).map_err(|source| Error::Io { path, source })?;
+
+        let mut snapshot = self.repo.config_snapshot_mut();
+        snapshot.append_config(
+            values
+                .iter()
+                .map(|(key, value)| BString::from(format!("core.{key}={value}"))),
+            source,
+        )?;
+        snapshot.commit().expect("configuration is still valid");
+        Ok(())
+    }
+
+    /// Return `true` if the index should be written as sparse index, with directory entries for directories outside
+    /// of the cone.
+    fn is_sparse_index(&self) -> bool {
+        self.repo
+            .config
+            .resolved
+            .boolean_by_key(Index::SPARSE.logical_name().as_str())
+            .and_then(Result::ok)
+            .unwrap_or(false)
+    }
+
+    fn write_patterns(&self, patterns: &Patterns) -> Result<(), Error> {
+        let path = self.repo.git_dir().join("info").join("sparse-checkout");
+        let io_err = |source| Error::Io {
+            path: path.clone(),
+            source,
+        };
+        let mut buf = Vec::new();
+        patterns.write_to(&mut buf).expect("writing to memory works");
+        std::fs::create_dir_all(path.parent().expect("parent")).map_err(io_err)?;
+        std::fs::write(&path, buf).map_err(io_err)
+    }
+
+    /// Update the [`SKIP_WORKTREE`][Flags::SKIP_WORKTREE] flags of all index entries to match `patterns`, or clear them all
+    /// if `None`, and remove files that aren't included anymore from the worktree while checking out those that are
+    /// included now. A sparse index is expanded for that, and written as sparse index again if `index.sparse` is enabled.
+    ///
+    /// ### Deviation
+    ///
+    /// Files are considered modified if their content differs from the blob in the index, which is also the case if
+    /// conversions like line ending changes were applied when they were checked out.
+    fn update_worktree(&self, patterns: Option<&Patterns>) -> Result<Outcome, Error> {
+        let repo = &*self.repo;
+        let work_dir = repo.work_dir().ok_or_else(|| Error::BareRepository {
+            git_dir: repo.git_dir().to_owned(),
+        })?;
+        let mut index = match repo.open_index() {
+            Ok(index) => index,
+            Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                if err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                return Ok(Outcome::default())
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let mut opts = repo.config.checkout_options(repo.git_dir())?;
+        opts.sparse_checkout = None;
+        let case = if opts.fs.ignore_case {
+            gix_glob::pattern::Case::Fold
+        } else {
+            gix_glob::pattern::Case::Sensitive
+        };
+        index.expand_sparse_directories(|oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?;
+
+        let was_skipped: Vec<_> = index
+            .entries()
+            .iter()
+            .map(|entry| entry.flags.contains(Flags::SKIP_WORKTREE))
+            .collect();
+        let mut outcome = Outcome::default();
+        match patterns {
+            Some(patterns) => {
+                outcome.entries_changed = gix_worktree::sparse::apply(&mut index, patterns, case);
+            }
+            None => {
+                for entry in index.entries_mut() {
+                    if entry.flags.contains(Flags::SKIP_WORKTREE) {
+                        entry.flags.remove(Flags::SKIP_WORKTREE);
+                        outcome.entries_changed += 1;
+                    }
+                }
+            }
+        }
+
+        let mut included = Vec::new();
+        for ((idx, (entry, path)), was_skipped) in index.entries_mut_with_paths().enumerate().zip(was_skipped) {
+            let is_skipped = entry.flags.contains(Flags::SKIP_WORKTREE);
+            if was_skipped && !is_skipped {
+                included.push(idx);
+            } else if !was_skipped && is_skipped {
+                let file = work_dir.join(gix_path::from_bstr(path));
+                match remove_unmodified(&file, work_dir, entry) {
+                    Ok(Some(true)) => outcome.files_removed += 1,
+                    Ok(None) => {}
+                    Ok(Some(false)) => {
+                        entry.flags.remove(Flags::SKIP_WORKTREE);
+                        outcome.modified_files_kept.push(path.to_owned());
+                    }
+                    Err(source) => return Err(Error::Io { path: file, source }),
+                }
+            }
+        }
+
+        if !included.is_empty() {
+            let skipped: Vec<_> = index
+                .entries_mut()
+                .iter_mut()
+                .map(|entry| {
+                    let skipped = entry.flags.contains(Flags::SKIP_WORKTREE);
+                    entry.flags.insert(Flags::SKIP_WORKTREE);
+                    skipped
+                })
+                .collect();
+            for idx in &included {
+                index.entries_mut()[*idx].flags.remove(Flags::SKIP_WORKTREE);
+            }
+            let objects = repo.objects.clone().into_arc().map_err(|source| Error::Io {
+                path: repo.objects.store_ref().path().to_owned(),
+                source,
+            })?;
+            outcome.checkout = gix_worktree::index::checkout(
+                &mut index,
+                work_dir,
+                move |oid, buf| objects.find_blob(oid, buf),
+                &mut crate::progress::Discard,
+                &mut crate::progress::Discard,
+                &AtomicBool::default(),
+                opts,
+            )?;
+            for (entry, skipped) in index.entries_mut().iter_mut().zip(skipped) {
+                entry.flags.set(Flags::SKIP_WORKTREE, skipped);
+            }
+        }
+        if let Some(patterns) = patterns.filter(|_| self.is_sparse_index()) {
+            index.collapse_sparse_directories(|dir| patterns.excludes_directory(dir, case));
+        }
+        index.write(gix_index::write::Options {
+            version: Some(index.version()),
+            ..Default::default()
+        })?;
+        Ok(outcome)
+    }
+}
+
+/// Remove `file` of `entry` if it exists and is unmodified, along with its parent directories up to `work_dir` that
+/// are empty afterwards. Return `None` if there is no such file, or `Some(false)` if it's modified and was kept.
+fn remove_unmodified(file: &Path, work_dir: &Path, entry: &gix_index::Entry) -> std::io::Result<Option<bool>> {
+    match crate::worktree::file_matches_blob(file, entry.mode, &entry.id)? {
+        Some(true) => {}
+        unmodified_or_missing => return Ok(unmodified_or_missing),
+    }
+    std::fs::remove_file(file)?;
+    for dir in file.ancestors().skip(1).take_while(|dir| *dir != work_dir) {
+        if std::fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+    Ok(Some(true))
+}
@@ -0,0 +1,499 @@
+//! Stashing away changes of the index and the worktree to restore them later, similar to `git stash`.
+//!
+//! See [`Repository::stash()`][crate::Repository::stash()].
+#![allow(clippy::result_large_err)]
+use std::path::{Path, PathBuf};
+
+use gix_hash::ObjectId;
+use gix_index::entry::{Flags, Mode};
+use gix_object::tree::EntryMode;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    FullName, Target,
+};
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    ext::ObjectIdExt,
+    merge,
+    merge::checkout,
+    Repository,
+};
+
+/// The error returned by the methods of [`Platform`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Repository at \"{}\" is a bare repository and has no changes to stash", git_dir.display())]
+    BareRepository { git_dir: PathBuf },
+    #[error("There is no stash entry at index {index}")]
+    NoSuchEntry { index: usize },
+    #[error("The stash entry {id} is not a stash commit as it has no parent")]
+    NotAStash { id: ObjectId },
+    #[error("The index has unresolved conflicts and can't be stashed")]
+    UnmergedIndex,
+    #[error(transparent)]
+    HeadCommit(#[from] crate::reference::head_commit::Error),
+    #[error(transparent)]
+    HeadName(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error("Could not create an index from a tree, or expand the directories of the sparse index")]
+    IndexFromTree(#[from] gix_traverse::tree::breadthfirst::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    ObjectKind(#[from] crate::object::try_into::Error),
+    #[error(transparent)]
+    DecodeCommit(#[from] gix_object::decode::Error),
+    #[error(transparent)]
+    WriteObject(#[from] crate::object::write::Error),
+    #[error(transparent)]
+    ParseTime(#[from] crate::config::time::Error),
+    #[error("Committer identity is not configured")]
+    CommitterMissing,
+    #[error("Author identity is not configured")]
+    AuthorMissing,
+    #[error(transparent)]
+    ReferenceEdit(#[from] crate::reference::edit::Error),
+    #[error("Could not parse line {line_number} of the stash log at '{}'", path.display())]
+    DecodeLog {
+        path: PathBuf,
+        line_number: usize,
+        source: gix_ref::file::log::iter::decode::Error,
+    },
+    #[error(transparent)]
+    LockLog(#[from] gix_lock::acquire::Error),
+    #[error("Could not read or write '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    Merge(#[from] merge::Error),
+    #[error(transparent)]
+    Checkout(#[from] checkout::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+}
+
+/// An entry of the stash, as obtained with [`Platform::list()`].
+#[derive(Debug, Clone)]
+pub struct Entry<'repo> {
+    /// The id of the stash commit, whose tree contains the stashed worktree and whose second parent is a commit of the
+    /// stashed index.
+    pub id: crate::Id<'repo>,
+    /// The message of the entry, like `WIP on main: 1234567 subject`.
+    pub message: BString,
+}
+
+/// A platform to create, list, apply and drop entries of the stash of a repository, obtained with
+/// [`Repository::stash()`][crate::Repository::stash()].
+///
+/// Entries are identified by their index, with `0` being the most recent one, just like `stash@{0}` in `git`.
+pub struct Platform<'repo> {
+    pub(crate) repo: &'repo Repository,
+}
+
+/// Access
+impl<'repo> Platform<'repo> {
+    /// Return all entries of the stash, most recent first, or an empty list if there is no stash.
+    pub fn list(&self) -> Result<Vec<Entry<'repo>>, Error> {
+        let mut entries: Vec<_> = self
+            .read_log()?
+            .into_iter()
+            .map(|line| Entry {
+                id: line.new_oid.attach(self.repo),
+                message: line.message,
+            })
+            .collect();
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Return the lines of the reflog of `refs/stash`, oldest first.
+    fn read_log(&self) -> Result<Vec<gix_ref::log::Line>, Error> {
+        let path = self.log_path();
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => return Err(Error::Io { path, source }),
+        };
+        gix_ref::file::log::iter::forward(&data)
+            .enumerate()
+            .map(|(line_number, line)| {
+                line.map(|line| line.to_owned()).map_err(|source| Error::DecodeLog {
+                    path: path.clone(),
+                    line_number: line_number + 1,
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    fn log_path(&self) -> PathBuf {
+        self.repo.common_dir().join("logs").join("refs").join("stash")
+    }
+}
+
+/// Modification
+impl<'repo> Platform<'repo> {
+    /// Save the changes of the index and of tracked files in the worktree relative to `HEAD` as a new stash entry, and
+    /// reset the index and these files to `HEAD`, similar to `git stash push`. `message` describes the entry, and
+    /// defaults to the abbreviated id and summary of `HEAD`.
+    ///
+    /// Return the id of the new stash commit, or `None` if there are no changes to stash.
+    ///
+    /// ### Deviation
+    ///
+    /// - Untracked files aren't stashed as `git stash --include-untracked` isn't supported.
+    /// - Files are stored as they are, without conversions like line ending changes that would apply when adding them.
+    /// - A sparse index is written with its directories expanded.
+    pub fn push(&self, message: Option<&str>) -> Result<Option<crate::Id<'repo>>, Error> {
+        let repo = self.repo;
+        let work_dir = repo.work_dir().ok_or_else(|| Error::BareRepository {
+            git_dir: repo.git_dir().to_owned(),
+        })?;
+        let head = repo.head_commit()?;
+        let head_tree = head.tree_id()?.detach();
+        let mut index = repo.open_index()?;
+        index.expand_sparse_directories(|oid, buf| {
+            use gix_odb::FindExt;
+            repo.objects.find_tree_iter(oid, buf).ok()
+        })?;
+        if index.entries().iter().any(|entry| entry.stage() != 0) {
+            return Err(Error::UnmergedIndex);
+        }
+
+        let executable_bit = repo.config.checkout_options(repo.git_dir())?.fs.executable_bit;
+        let mut index_entries = Vec::with_capacity(index.entries().len());
+        let mut worktree_entries = Vec::with_capacity(index.entries().len());
+        for entry in index.entries() {
+            let path = entry.path(&index);
+            index_entries.push((path, entry.mode, entry.id));
+            if entry.flags.contains(Flags::SKIP_WORKTREE) || entry.mode == Mode::COMMIT {
+                worktree_entries.push((path, entry.mode, entry.id));
+            } else if let Some((mode, id)) =
+                worktree_entry(repo, &work_dir.join(gix_path::from_bstr(path)), entry, executable_bit)?
+            {
+                worktree_entries.push((path, mode, id));
+            }
+        }
+        let index_tree = write_tree(repo, &index_entries)?;
+        let worktree_tree = write_tree(repo, &worktree_entries)?;
+        if index_tree == head_tree && worktree_tree == head_tree {
+            return Ok(None);
+        }
+
+        let branch = match repo.head_name()? {
+            Some(name) => name.as_ref().shorten().to_string(),
+            None => "(no branch)".into(),
+        };
+        let summary = format!("{} {}", head.id().shorten_or_id(), head.message()?.summary());
+        let committer = repo.committer().ok_or(Error::CommitterMissing)??;
+        let author = repo.author().ok_or(Error::AuthorMissing)??;
+        let mut commit = gix_object::Commit {
+            tree: index_tree,
+            parents: Some(head.id).into_iter().collect(),
+            author: author.to_owned(),
+            committer: committer.to_owned(),
+            encoding: None,
+            message: format!("index on {branch}: {summary}\n").into(),
+            extra_headers: Vec::new(),
+        };
+        let index_commit = repo.write_object(&commit)?.detach();
+        let message = match message {
+            Some(message) => format!("On {branch}: {message}"),
+            None => format!("WIP on {branch}: {summary}"),
+        };
+        commit.tree = worktree_tree;
+        commit.parents.push(index_commit);
+        commit.message = format!("{message}\n").into();
+        let id = repo.write_object(&commit)?;
+        repo.edit_reference(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: true,
+                    message: message.into(),
+                },
+                expected: PreviousValue::Any,
+                new: Target::Peeled(id.detach()),
+            },
+            name: stash_ref_name(),
+            deref: false,
+        })?;
+
+        let head_index = checkout::tree_index(repo, head_tree)?;
+        let head_entries = checkout::entries_by_path(&head_index);
+        let changed: Vec<BString> = index_entries
+            .iter()
+            .chain(worktree_entries.iter())
+            .map(|(path, ..)| *path)
+            .chain(head_entries.keys().map(|path| path.as_bstr()))
+            .filter(|path| {
+                let in_head = head_entries.get(*path).map(|(id, mode)| (*mode, *id));
+                let find = |entries: &[(&BStr, Mode, ObjectId)]| {
+                    entries
+                        .binary_search_by(|(entry_path, ..)| entry_path.cmp(path))
+                        .ok()
+                        .map(|idx| (entries[idx].1, entries[idx].2))
+                };
+                find(&index_entries) != in_head || find(&worktree_entries) != in_head
+            })
+            .map(ToOwned::to_owned)
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let (updated, removed): (Vec<&BString>, Vec<&BString>) =
+            changed.iter().partition(|path| head_entries.contains_key(*path));
+        checkout::update_paths(repo, &mut index, work_dir, &head_index, removed, updated)?;
+        checkout::write_index(&mut index)?;
+        Ok(Some(id))
+    }
+
+    /// Apply the changes of the stash entry at `index` to the index and worktree, which are expected to match `HEAD` in
+    /// all paths it changes, similar to `git stash apply`. The entry is kept.
+    ///
+    /// The changes are applied with a [three-way merge][crate::Repository::merge_trees()] of the tree of `HEAD` with the
+    /// stashed worktree, using the commit the stash was created on as ancestor. Like in `git`, the changes of files
+    /// known to `HEAD` are left unstaged if there are no conflicts, while new files are added to the index.
+    ///
+    /// Return the conflicts, which are left in the index and the worktree for resolution, or an empty list if the changes
+    /// were applied cleanly.
+    ///
+    /// ### Deviation
+    ///
+    /// The stashed index isn't restored as `git stash apply --index` isn't supported.
+    pub fn apply(&self, index: usize) -> Result<Vec<merge::Conflict>, Error> {
+        let repo = self.repo;
+        let entry = self
+            .list()?
+            .into_iter()
+            .nth(index)
+            .ok_or(Error::NoSuchEntry { index })?;
+        let stash = entry.id.object()?.try_into_commit()?;
+        let base = stash
+            .parent_ids()
+            .next()
+            .ok_or(Error::NotAStash { id: entry.id.detach() })?;
+        let head_tree = repo.head_commit()?.tree_id()?.detach();
+        let outcome = repo.merge_trees(
+            base,
+            head_tree,
+            entry.id,
+            merge::Options {
+                ancestor_label: Some("Stash base".into()),
+                our_label: Some("Updated upstream".into()),
+                their_label: Some("Stashed changes".into()),
+                ..Default::default()
+            },
+        )?;
+        repo.checkout_merge(head_tree, &outcome)?;
+        if outcome.has_conflicts() {
+            return Ok(outcome.conflicts);
+        }
+
+        let mut index = repo.open_index()?;
+        let head_entries = checkout::entries_by_path(&checkout::tree_index(repo, head_tree)?);
+        for (entry, path) in index.entries_mut_with_paths() {
+            if let Some((id, mode)) = head_entries.get(path) {
+                if (entry.id, entry.mode) != (*id, *mode) {
+                    entry.id = *id;
+                    entry.mode = *mode;
+                    entry.stat = Default::default();
+                }
+            }
+        }
+        let removed: Vec<_> = head_entries
+            .iter()
+            .filter(|(path, _)| index.entry_by_path_and_stage(path.as_bstr(), 0).is_none())
+            .collect();
+        for (path, (id, mode)) in removed {
+            index.dangerously_push_entry(Default::default(), *id, Flags::empty(), *mode, path.as_bstr());
+        }
+        index.sort_entries();
+        checkout::write_index(&mut index)?;
+        Ok(Vec::new())
+    }
+
+    /// Like [`apply()`][Self::apply()], but also [drop][Self::drop()] the entry at `index` if it was applied without
+    /// conflicts, similar to `git stash pop`.
+    pub fn pop(&self, index: usize) -> Result<Vec<merge::Conflict>, Error> {
+        let conflicts = self.apply(index)?;
+        if conflicts.is_empty() {
+            self.drop(index)?;
+        }
+        Ok(conflicts)
+    }
+
+    /// Remove the entry at `index` from the stash and return the id of its commit, similar to `git stash drop`.
+    ///
+    /// The entry is removed from the reflog of `refs/stash`, which is updated to point to the most recent remaining entry,
+    /// or deleted along with its reflog if there is none.
+    pub fn drop(&self, index: usize) -> Result<crate::Id<'repo>, Error> {
+        let path = self.log_path();
+        let mut lock = gix_lock::File::acquire_to_update_resource(&path, gix_lock::acquire::Fail::Immediately, None)?;
+        let mut lines = self.read_log()?;
+        if index >= lines.len() {
+            return Err(Error::NoSuchEntry { index });
+        }
+        let pos = lines.len() - 1 - index;
+        let dropped = lines.remove(pos).new_oid;
+        let top = match lines.last() {
+            Some(line) => line.new_oid,
+            None => {
+                std::mem::drop(lock);
+                self.repo.edit_reference(RefEdit {
+                    change: Change::Delete {
+                        expected: PreviousValue::MustExistAndMatch(Target::Peeled(dropped)),
+                        log: RefLog::AndReference,
+                    },
+                    name: stash_ref_name(),
+                    deref: false,
+                })?;
+                return Ok(dropped.attach(self.repo));
+            }
+        };
+
+        if pos < lines.len() {
+            lines[pos].previous_oid = match pos.checked_sub(1) {
+                Some(prev) => lines[prev].new_oid,
+                None => ObjectId::null(dropped.kind()),
+            };
+        }
+        if index == 0 {
+            // This appends to the log, which is rewritten entirely afterwards.
+            self.repo.edit_reference(RefEdit {
+                change: Change::Update {
+                    log: LogChange {
+                        mode: RefLog::AndReference,
+                        force_create_reflog: false,
+                        message: Default::default(),
+                    },
+                    expected: PreviousValue::MustExistAndMatch(Target::Peeled(dropped)),
+                    new: Target::Peeled(top),
+                },
+                name: stash_ref_name(),
+                deref: false,
+            })?;
+        }
+        let io_err = |source| Error::Io {
+            path: path.clone(),
+            source,
+        };
+        for line in &lines {
+            line.write_to(&mut lock).map_err(io_err)?;
+        }
+        lock.commit().map_err(|err| io_err(err.error))?;
+        Ok(dropped.attach(self.repo))
+    }
+}
+
+fn stash_ref_name() -> FullName {
+    "refs/stash".try_into().expect("valid")
+}
+
+/// Return the mode and the id of the blob of the file at `path` that is tracked by `entry`, writing the blob if the file
+/// was modified, or `None` if there is no such file anymore.
+fn worktree_entry(
+    repo: &Repository,
+    path: &Path,
+    entry: &gix_index::Entry,
+    executable_bit: bool,
+) -> Result<Option<(Mode, ObjectId)>, Error> {
+    let io_err = |source| Error::Io {
+        path: path.to_owned(),
+        source,
+    };
+    let meta = match std::fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(io_err(err)),
+    };
+    let mode = if meta.file_type().is_symlink() {
+        Mode::SYMLINK
+    } else if meta.is_dir() {
+        return Ok(None);
+    } else if executable_bit && entry.mode != Mode::SYMLINK {
+        if is_executable(&meta) {
+            Mode::FILE_EXECUTABLE
+        } else {
+            Mode::FILE
+        }
+    } else {
+        entry.mode
+    };
+    if mode == entry.mode
+        && crate::worktree::file_matches_blob(path, entry.mode, &entry.id).map_err(io_err)? == Some(true)
+    {
+        return Ok(Some((mode, entry.id)));
+    }
+    let data = if mode == Mode::SYMLINK {
+        gix_path::into_bstr(std::fs::read_link(path).map_err(io_err)?)
+            .into_owned()
+            .into()
+    } else {
+        std::fs::read(path).map_err(io_err)?
+    };
+    Ok(Some((mode, repo.write_blob(data)?.detach())))
+}
+
+#[cfg(unix)]
+fn is_executable(meta: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_meta: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Write the trees for `entries`, which are sorted like in the index, and return the id of the root tree.
+fn write_tree(repo: &Repository, entries: &[(&BStr, Mode, ObjectId)]) -> Result<ObjectId, Error> {
+    let mut tree = gix_object::Tree::empty();
+    let mut idx = 0;
+    while let Some((path, mode, id)) = entries.get(idx).copied() {
+        match path.find_byte(b'/') {
+            None => {
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: tree_mode(mode),
+                    filename: path.to_owned(),
+                    oid: id,
+                });
+                idx += 1;
+            }
+            Some(slash) => {
+                let dir = &path[..=slash];
+                let end = entries[idx..]
+                    .iter()
+                    .position(|(path, ..)| !path.starts_with(dir))
+                    .map_or(entries.len(), |len| idx + len);
+                let children: Vec<_> = entries[idx..end]
+                    .iter()
+                    .map(|(path, mode, id)| (path[slash + 1..].as_bstr(), *mode, *id))
+                    .collect();
+                tree.entries.push(gix_object::tree::Entry {
+                    mode: EntryMode::Tree,
+                    filename: path[..slash].into(),
+                    oid: write_tree(repo, &children)?,
+                });
+                idx = end;
+            }
+        }
+    }
+    tree.entries.sort();
+    Ok(repo.write_object(&tree)?.detach())
+}
+
+fn tree_mode(mode: Mode) -> EntryMode {
+    if mode == Mode::FILE_EXECUTABLE {
+        EntryMode::BlobExecutable
+    } else if mode == Mode::SYMLINK {
+        EntryMode::Link
+    } else if mode == Mode::COMMIT {
+        EntryMode::Commit
+    } else {
+        EntryMode::Blob
+    }
+}
@@ -0,0 +1,411 @@
+//! Submodules, repositories whose commits are recorded in the tree of their superproject, and which are checked out
+//! into a directory of the superproject's worktree.
+use std::path::PathBuf;
+
+use gix_hash::ObjectId;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    remote, Repository, Submodule,
+};
+
+/// The state of a submodule on disk, as far as it can be determined without opening its repository.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct State {
+    /// If `true`, the repository of the submodule exists, either in `.git/modules/<name>` of the superproject
+    /// or as `.git` directory in the worktree of the submodule.
+    pub repository_exists: bool,
+    /// If `true`, the repository of the submodule is a `.git` directory in its worktree, instead of being located in the
+    /// `modules` directory of the superproject.
+    pub is_old_form: bool,
+    /// If `true`, the worktree of the submodule contains a `.git` file or directory, which means it's checked out.
+    pub worktree_checkout: bool,
+    /// If `true`, the configuration of the superproject contains a url for the submodule, as done by `git submodule init`.
+    pub superproject_configuration: bool,
+}
+
+/// The status of a submodule, as obtained by [`Submodule::status()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Status {
+    /// The state of the submodule on disk.
+    pub state: State,
+    /// The commit recorded for the submodule in the index of the superproject, or `None` if there is no such entry.
+    pub index_id: Option<ObjectId>,
+    /// The commit the `HEAD` of the submodule points to, or `None` if it's not checked out or its `HEAD` is unborn.
+    pub checked_out_head_id: Option<ObjectId>,
+    /// `Some(true)` if the tracked files of the checked out submodule were changed in its index or in its worktree,
+    /// or `None` if it's not checked out.
+    pub is_dirty: Option<bool>,
+}
+
+impl Status {
+    /// Return `true` if the submodule wasn't initialized in the configuration of the superproject, like `git submodule status`
+    /// indicates with a leading `-`.
+    pub fn is_uninitialized(&self) -> bool {
+        !self.state.superproject_configuration
+    }
+
+    /// Return `true` if the submodule is checked out, but its `HEAD` doesn't point to the commit recorded in the superproject's index,
+    /// like `git submodule status` indicates with a leading `+`.
+    pub fn is_head_mismatch(&self) -> bool {
+        self.state.worktree_checkout && self.index_id.is_some() && self.index_id != self.checked_out_head_id
+    }
+}
+
+///
+#[cfg(feature = "blocking-network-client")]
+pub mod update;
+#[cfg(feature = "blocking-network-client")]
+pub use update::update;
+
+///
+pub mod modules {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Repository::submodules()`][crate::Repository::submodules()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not read the submodule configuration at '{}'", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+        #[error("Could not parse the submodule configuration")]
+        Parse(#[from] gix_config::file::init::Error),
+        #[error(transparent)]
+        HeadTree(#[from] super::head_id::Error),
+        #[error("Could not find the '.gitmodules' file in the tree of HEAD")]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error("The name of submodule '{name}' must not contain '..' components")]
+        InvalidName { name: BString },
+        #[error("Submodule '{name}' has no path configured")]
+        MissingPath { name: BString },
+        #[error("The path '{path}' of submodule '{name}' must be relative and must not contain '..' components")]
+        InvalidPath { name: BString, path: BString },
+    }
+}
+
+///
+pub mod head_id {
+    /// The error returned by [`Submodule::head_id()`][crate::Submodule::head_id()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        FindHead(#[from] crate::reference::find::existing::Error),
+        #[error(transparent)]
+        PeelHead(#[from] crate::head::peel::Error),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::existing::Error),
+        #[error(transparent)]
+        PeelToTree(#[from] crate::object::peel::to_kind::Error),
+    }
+}
+
+///
+pub mod index_id {
+    /// The error returned by [`Submodule::index_id()`][crate::Submodule::index_id()].
+    #[derive(Debug, thiserror::Error)]
+    #[error(transparent)]
+    pub struct Error(#[from] crate::worktree::open_index::Error);
+}
+
+///
+pub mod url {
+    /// The error returned by [`Submodule::url()`][crate::Submodule::url()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not find the default remote of the superproject to resolve the relative submodule url against")]
+        FindRemote(#[from] crate::remote::find::existing::Error),
+        #[error(transparent)]
+        Parse(#[from] gix_url::parse::Error),
+    }
+}
+
+///
+pub mod status {
+    use std::path::PathBuf;
+
+    /// The error returned by [`Submodule::status()`][crate::Submodule::status()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        IndexId(#[from] super::index_id::Error),
+        #[error(transparent)]
+        Open(#[from] crate::open::Error),
+        #[error(transparent)]
+        HeadId(#[from] super::head_id::Error),
+        #[error(transparent)]
+        OpenIndex(#[from] crate::worktree::open_index::Error),
+        #[error("Could not create an index from the tree of HEAD")]
+        IndexFromTree(#[from] gix_traverse::tree::breadthfirst::Error),
+        #[error("Could not read '{}' to compare it with the index", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
+/// Access
+impl<'repo> Submodule<'repo> {
+    /// Return the name of the submodule, which identifies it in the configuration and names its repository in `.git/modules`.
+    pub fn name(&self) -> &BStr {
+        self.name.as_ref()
+    }
+
+    /// Return the path of the submodule relative to the root of the superproject's worktree.
+    pub fn path(&self) -> &BStr {
+        self.path.as_ref()
+    }
+
+    /// Return the branch of the submodule's remote to track with `git submodule update --remote`, if configured.
+    ///
+    /// Note that `.` is returned as is, and means that the branch has the same name as the current branch of the superproject.
+    pub fn branch(&self) -> Option<&BStr> {
+        self.branch.as_ref().map(AsRef::as_ref)
+    }
+
+    /// Return `true` if the superproject's configuration contains a url for this submodule, as done by `git submodule init`.
+    pub fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+
+    /// Return the superproject this submodule belongs to.
+    pub fn superproject(&self) -> &'repo Repository {
+        self.repo
+    }
+
+    /// Return the url to clone the submodule from, or `None` if it isn't configured.
+    ///
+    /// Relative urls, which start with `./` or `../`, are resolved against the url of the superproject's default remote,
+    /// or against its worktree if it has no remote, like `git` does.
+    #[allow(clippy::result_large_err)]
+    pub fn url(&self) -> Result<Option<gix_url::Url>, url::Error> {
+        let url = match self.url.as_ref() {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+        let url = if url.starts_with(b"./") || url.starts_with(b"../") {
+            let base = match self.repo.find_default_remote(remote::Direction::Fetch).transpose()? {
+                Some(remote) => remote.url(remote::Direction::Fetch).map(|url| url.to_bstring()),
+                None => None,
+            }
+            .unwrap_or_else(|| {
+                gix_path::into_bstr(self.repo.work_dir().unwrap_or_else(|| self.repo.git_dir())).into_owned()
+            });
+            resolve_relative_url(base.as_ref(), url.as_ref())
+        } else {
+            url.clone()
+        };
+        Ok(Some(gix_url::parse(url.as_ref())?))
+    }
+
+    /// Return the directory the submodule is checked out in, or `None` if the superproject is bare.
+    pub fn work_dir(&self) -> Option<PathBuf> {
+        self.repo
+            .work_dir()
+            .map(|dir| dir.join(gix_path::from_bstr(self.path.as_bstr())))
+    }
+
+    /// Return the location of the submodule repository in the superproject's `modules` directory, which is where `git`
+    /// places it when cloning submodules.
+    ///
+    /// Note that this directory may not exist, and that submodules in the old form keep their repository in the `.git`
+    /// directory of their worktree instead.
+    pub fn git_dir(&self) -> PathBuf {
+        self.repo
+            .common_dir()
+            .join("modules")
+            .join(gix_path::from_bstr(self.name.as_bstr()))
+    }
+
+    /// Return the commit recorded for this submodule in the superproject's index, or `None` if there is no index or no such entry.
+    pub fn index_id(&self) -> Result<Option<ObjectId>, index_id::Error> {
+        let index = match self.repo.index() {
+            Ok(index) => index,
+            Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+                if err.kind() == std::io::ErrorKind::NotFound =>
+            {
+                return Ok(None)
+            }
+            Err(err) => return Err(err.into()),
+        };
+        Ok(index
+            .entry_by_path_and_stage(self.path.as_ref(), 0)
+            .filter(|entry| entry.mode == gix_index::entry::Mode::COMMIT)
+            .map(|entry| entry.id))
+    }
+
+    /// Return the commit recorded for this submodule in the tree of the superproject's `HEAD`, or `None` if `HEAD` is unborn
+    /// or doesn't contain the submodule.
+    pub fn head_id(&self) -> Result<Option<ObjectId>, head_id::Error> {
+        let tree = match head_tree(self.repo)? {
+            Some(tree) => tree,
+            None => return Ok(None),
+        };
+        Ok(tree
+            .lookup_entry_by_path(gix_path::from_bstr(self.path.as_bstr()))?
+            .filter(|entry| entry.mode() == gix_object::tree::EntryMode::Commit)
+            .map(|entry| entry.object_id()))
+    }
+}
+
+/// State
+impl<'repo> Submodule<'repo> {
+    /// Determine the state of the submodule on disk.
+    pub fn state(&self) -> State {
+        let dot_git = self.work_dir().map(|dir| dir.join(gix_discover::DOT_GIT_DIR));
+        let is_old_form = dot_git.as_ref().map_or(false, |dot_git| dot_git.is_dir());
+        let worktree_checkout = dot_git.as_ref().map_or(false, |dot_git| dot_git.exists());
+        State {
+            repository_exists: is_old_form || self.git_dir().is_dir(),
+            is_old_form,
+            worktree_checkout,
+            superproject_configuration: self.is_initialized,
+        }
+    }
+
+    /// Open the repository of the submodule with the options of the superproject, or return `None` if it doesn't exist.
+    ///
+    /// If the submodule is checked out, the repository is opened through its worktree, otherwise it's opened from the
+    /// superproject's `modules` directory.
+    #[allow(clippy::result_large_err)]
+    pub fn open(&self) -> Result<Option<Repository>, crate::open::Error> {
+        let state = self.state();
+        let path = if state.worktree_checkout {
+            self.work_dir().expect("checkouts need a worktree")
+        } else if state.repository_exists {
+            self.git_dir()
+        } else {
+            return Ok(None);
+        };
+        crate::open_opts(path, self.repo.options.clone()).map(Some)
+    }
+
+    /// Determine the status of the submodule, which involves opening its repository if it's checked out.
+    ///
+    /// # Deviation
+    ///
+    /// To determine if a submodule is dirty, its index is compared to the tree of its `HEAD`, and the content of each tracked file
+    /// in its worktree is hashed and compared to its index entry. Untracked files and nested submodules are not considered, and no
+    /// filters are applied to file contents.
+    #[allow(clippy::result_large_err)]
+    pub fn status(&self) -> Result<Status, status::Error> {
+        let mut status = Status {
+            state: self.state(),
+            index_id: self.index_id()?,
+            checked_out_head_id: None,
+            is_dirty: None,
+        };
+        if !status.state.worktree_checkout {
+            return Ok(status);
+        }
+        let repo = match self.open()? {
+            Some(repo) => repo,
+            None => return Ok(status),
+        };
+        status.checked_out_head_id = repo
+            .head()
+            .map_err(head_id::Error::from)?
+            .peel_to_id_in_place()
+            .transpose()
+            .map_err(head_id::Error::from)?
+            .map(crate::Id::detach);
+        status.is_dirty = Some(is_dirty(&repo)?);
+        Ok(status)
+    }
+}
+
+pub(crate) fn head_tree(repo: &Repository) -> Result<Option<crate::Tree<'_>>, head_id::Error> {
+    let id = match repo.head()?.peel_to_id_in_place() {
+        Some(id) => id?,
+        None => return Ok(None),
+    };
+    Ok(Some(id.object()?.peel_to_tree()?))
+}
+
+#[allow(clippy::result_large_err)]
+fn is_dirty(repo: &Repository) -> Result<bool, status::Error> {
+    use gix_index::entry::Mode;
+    use gix_odb::FindExt;
+
+    let work_dir = match repo.work_dir() {
+        Some(dir) => dir,
+        None => return Ok(false),
+    };
+    let index = repo.index()?;
+    let index: &gix_index::State = &index;
+    let head = match head_tree(repo)? {
+        Some(tree) => gix_index::State::from_tree(&tree.id, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())?,
+        None => gix_index::State::new(repo.object_hash()),
+    };
+    fn entries(state: &gix_index::State) -> impl Iterator<Item = (&BStr, ObjectId, gix_index::entry::Mode, u32)> {
+        state
+            .entries()
+            .iter()
+            .map(move |entry| (entry.path(state), entry.id, entry.mode, entry.stage()))
+    }
+    if !entries(index).eq(entries(&head)) {
+        return Ok(true);
+    }
+
+    for entry in index.entries() {
+        if entry.mode == Mode::COMMIT {
+            continue;
+        }
+        let path = work_dir.join(gix_path::from_bstr(entry.path(index)));
+        let io_err = |source| status::Error::Io {
+            path: path.clone(),
+            source,
+        };
+        let meta = match std::fs::symlink_metadata(&path) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+            Err(err) => return Err(io_err(err)),
+        };
+        let data = if entry.mode == Mode::SYMLINK && meta.file_type().is_symlink() {
+            let target = std::fs::read_link(&path).map_err(io_err)?;
+            gix_path::into_bstr(target).into_owned().into()
+        } else if meta.is_file() {
+            if meta.len() as u32 != entry.stat.size {
+                return Ok(true);
+            }
+            std::fs::read(&path).map_err(io_err)?
+        } else {
+            return Ok(true);
+        };
+        let mut hasher = gix_features::hash::hasher(repo.object_hash());
+        hasher.update(&gix_object::encode::loose_header(gix_object::Kind::Blob, data.len()));
+        hasher.update(&data);
+        if ObjectId::from(hasher.digest()) != entry.id {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Resolve `relative`, which starts with `./` or `../`, against `base` like `git` does, by removing one path component
+/// of `base` for each `../`.
+fn resolve_relative_url(base: &BStr, mut relative: &BStr) -> BString {
+    let mut base = base.trim_end_with(|c| c == '/');
+    loop {
+        if let Some(rest) = relative.strip_prefix(b"./") {
+            relative = rest.as_bstr();
+        } else if let Some(rest) = relative.strip_prefix(b"../") {
+            relative = rest.as_bstr();
+            base = match base.rfind_byteset(b"/:") {
+                Some(pos) if base[pos] == b':' => &base[..=pos],
+                Some(pos) => &base[..pos],
+                None => b"".as_slice(),
+            };
+        } else {
+            break;
+        }
+    }
+    let mut url = BString::from(base);
+    if !url.is_empty() && !url.ends_with(b":") {
+        url.push(b'/');
+    }
+    url.extend_from_slice(relative);
+    url
+}
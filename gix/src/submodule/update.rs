@@ -0,0 +1,398 @@
+//! Clone and check out submodules, similar to `git submodule update --init --recursive`.
+#![allow(clippy::result_large_err)]
+use std::{
+    convert::TryInto,
+    path::{Component, Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use gix_hash::ObjectId;
+use gix_odb::FindExt;
+use gix_ref::{
+    transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+    Target,
+};
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    config::tree::Core,
+    remote, Progress, Repository, Submodule,
+};
+
+/// A function to obtain credentials for connections to the remotes of submodules, as passed to
+/// [`Connection::with_credentials()`][crate::remote::Connection::with_credentials()].
+pub type AuthenticateFn<'a> = dyn FnMut(gix_credentials::helper::Action) -> gix_credentials::protocol::Result + 'a;
+
+/// Options for [`update()`][super::update()].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// If `true`, submodules that aren't initialized yet are initialized by writing their url into the configuration of the
+    /// superproject, like `git submodule update --init` does. Otherwise they are skipped.
+    pub init: bool,
+    /// If `true`, the submodules of each updated submodule are updated as well, using the same options.
+    pub recursive: bool,
+}
+
+/// What happened to a submodule during an [`update()`][super::update()].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Kind {
+    /// The submodule was skipped as it isn't initialized, or because it's not recorded in the index of the superproject.
+    Skipped,
+    /// The `HEAD` of the submodule was already pointing to the commit recorded in the superproject.
+    UpToDate,
+    /// The submodule was cloned and its recorded commit was checked out.
+    Cloned,
+    /// The recorded commit of an existing submodule was checked out, after fetching it if needed.
+    CheckedOut,
+}
+
+/// The outcome of updating a single submodule with [`update()`][super::update()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The name of the submodule.
+    pub name: BString,
+    /// The path of the submodule relative to the worktree of its superproject.
+    pub path: BString,
+    /// What happened to the submodule.
+    pub kind: Kind,
+    /// The commit that is now checked out, or `None` if the submodule was skipped.
+    pub id: Option<ObjectId>,
+    /// The outcome of updating the submodules of this submodule, if the update is recursive.
+    pub submodules: Vec<Outcome>,
+}
+
+/// The error returned by [`update()`][super::update()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Submodules can only be updated in repositories with a worktree")]
+    BareRepository,
+    #[error(transparent)]
+    Modules(#[from] super::modules::Error),
+    #[error(transparent)]
+    IndexId(#[from] super::index_id::Error),
+    #[error(transparent)]
+    Url(#[from] super::url::Error),
+    #[error("Submodule '{name}' has no url to clone it from")]
+    MissingUrl { name: BString },
+    #[error(transparent)]
+    Status(#[from] super::status::Error),
+    #[error("Submodule '{name}' has local modifications which would be overwritten by checking out {id}")]
+    Dirty { name: BString, id: ObjectId },
+    #[error("Could not read or write the configuration file at '{}'", path.display())]
+    ConfigFile {
+        path: PathBuf,
+        source: gix_config::file::init::from_paths::Error,
+    },
+    #[error(transparent)]
+    SetConfigValue(#[from] gix_config::file::set_raw_value::Error),
+    #[error(transparent)]
+    SaveRemote(#[from] crate::remote::save::AsError),
+    #[error("Could not write '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    Init(#[from] crate::init::Error),
+    #[error(transparent)]
+    Open(#[from] crate::open::Error),
+    #[error(transparent)]
+    RemoteInit(#[from] crate::remote::init::Error),
+    #[error(transparent)]
+    FindRemote(#[from] crate::remote::find::existing::Error),
+    #[error(transparent)]
+    Connect(#[from] crate::remote::connect::Error),
+    #[error(transparent)]
+    PrepareFetch(#[from] crate::remote::fetch::prepare::Error),
+    #[error(transparent)]
+    Fetch(#[from] crate::remote::fetch::Error),
+    #[error("The commit {id} recorded for submodule '{name}' could not be found in its remote")]
+    CommitNotFound { name: BString, id: ObjectId },
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToTree(#[from] crate::object::peel::to_kind::Error),
+    #[error("Could not create an index from the tree of commit {id}")]
+    IndexFromTree {
+        id: ObjectId,
+        source: gix_traverse::tree::breadthfirst::Error,
+    },
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    IndexCheckout(
+        #[from]
+        gix_worktree::index::checkout::Error<gix_odb::find::existing_object::Error<gix_odb::store::find::Error>>,
+    ),
+    #[error(transparent)]
+    IndexVersion(#[from] crate::config::key::GenericError),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+    #[error(transparent)]
+    EditHead(#[from] crate::reference::edit::Error),
+}
+
+/// Update all submodules of `repo` so their worktrees contain the commit recorded in the index of `repo`, by cloning missing
+/// submodules into the `modules` directory of `repo`, fetching from their remote if the commit isn't present yet, and
+/// checking it out with a detached `HEAD`, similar to `git submodule update`.
+///
+/// `progress` is used for each connection to a remote, and `should_interrupt` is checked to stop fetching and checking out.
+/// If `authenticate` is set, it's used to obtain credentials for the remotes of all submodules instead of the configured
+/// credential helpers.
+///
+/// Submodules whose worktree has local modifications aren't changed and cause an error, like `git` does without `--force`.
+///
+/// ### Deviation
+///
+/// The commit to check out is looked up in the references fetched with the configured refspecs only, and `git` would
+/// additionally try to fetch it by its id. `submodule.<name>.update` is ignored, and recorded commits are always checked out.
+/// No filters are applied to the files written into the worktree.
+pub fn update<P>(
+    repo: &Repository,
+    progress: &mut P,
+    should_interrupt: &AtomicBool,
+    options: Options,
+    mut authenticate: Option<&mut AuthenticateFn<'_>>,
+) -> Result<Vec<Outcome>, Error>
+where
+    P: Progress,
+    P::SubProgress: 'static,
+{
+    let work_dir = repo.work_dir().ok_or(Error::BareRepository)?;
+    let submodules = match repo.submodules()? {
+        Some(submodules) => submodules,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut out = Vec::with_capacity(submodules.len());
+    for sm in submodules {
+        let mut outcome = Outcome {
+            name: sm.name().to_owned(),
+            path: sm.path().to_owned(),
+            kind: Kind::Skipped,
+            id: None,
+            submodules: Vec::new(),
+        };
+        let id = match sm.index_id()? {
+            Some(id) if sm.is_initialized() || options.init => id,
+            _ => {
+                out.push(outcome);
+                continue;
+            }
+        };
+        if !sm.is_initialized() {
+            initialize(&sm)?;
+        }
+
+        let state = sm.state();
+        let (sm_repo, cloned) = if state.worktree_checkout {
+            let status = sm.status()?;
+            if status.checked_out_head_id == Some(id) {
+                outcome.kind = Kind::UpToDate;
+            } else if status.checked_out_head_id.is_some() && status.is_dirty == Some(true) {
+                return Err(Error::Dirty { name: outcome.name, id });
+            }
+            (sm.open()?.expect("checked out submodules exist"), false)
+        } else {
+            progress.info(format!("Cloning submodule '{}'", outcome.name));
+            (clone(&sm, work_dir)?, true)
+        };
+
+        if outcome.kind != Kind::UpToDate {
+            if sm_repo.objects.find_commit_iter(id, &mut Vec::new()).is_err() {
+                let remote = sm_repo.find_remote("origin")?;
+                let mut connection = remote.connect(remote::Direction::Fetch, &mut *progress)?;
+                if let Some(authenticate) = authenticate.as_mut() {
+                    connection = connection.with_credentials(authenticate);
+                }
+                connection
+                    .prepare_fetch(Default::default())?
+                    .receive(should_interrupt)?;
+                if sm_repo.objects.find_commit_iter(id, &mut Vec::new()).is_err() {
+                    return Err(Error::CommitNotFound { name: outcome.name, id });
+                }
+            }
+            checkout(&sm_repo, id, should_interrupt)?;
+            outcome.kind = if cloned { Kind::Cloned } else { Kind::CheckedOut };
+        }
+        outcome.id = Some(id);
+
+        if options.recursive {
+            outcome.submodules = update(
+                &sm_repo,
+                progress,
+                should_interrupt,
+                options,
+                authenticate.as_deref_mut(),
+            )?;
+        }
+        out.push(outcome);
+    }
+    Ok(out)
+}
+
+/// Write the resolved url of `sm` into the local configuration of its superproject, like `git submodule init`.
+fn initialize(sm: &Submodule<'_>) -> Result<(), Error> {
+    let url = sm.url()?.ok_or_else(|| Error::MissingUrl {
+        name: sm.name().to_owned(),
+    })?;
+    let path = sm.superproject().git_dir().join("config");
+    let mut config =
+        gix_config::File::from_path_no_includes(path.clone(), gix_config::Source::Local).map_err(|source| {
+            Error::ConfigFile {
+                path: path.clone(),
+                source,
+            }
+        })?;
+    config.set_raw_value("submodule", Some(sm.name()), "url", url.to_bstring().as_bstr())?;
+    std::fs::write(&path, config.to_bstring()).map_err(|source| Error::Io { path, source })
+}
+
+/// Initialize the repository of `sm` in the `modules` directory of its superproject, configured to use the worktree of `sm`
+/// and to fetch from its url as `origin`, and return it opened through its worktree.
+fn clone(sm: &Submodule<'_>, super_work_dir: &Path) -> Result<Repository, Error> {
+    let url = sm.url()?.ok_or_else(|| Error::MissingUrl {
+        name: sm.name().to_owned(),
+    })?;
+    let git_dir = sm.git_dir();
+    let work_dir = super_work_dir.join(gix_path::from_bstr(sm.path()));
+    let options = sm.superproject().options.clone();
+    if let Some(parent) = git_dir.parent() {
+        std::fs::create_dir_all(parent).map_err(|source| Error::Io {
+            path: parent.to_owned(),
+            source,
+        })?;
+    }
+    let repo: Repository = crate::ThreadSafeRepository::init_opts(
+        &git_dir,
+        crate::create::Kind::Bare,
+        Default::default(),
+        options.clone(),
+    )?
+    .into();
+    std::fs::create_dir_all(&work_dir).map_err(|source| Error::Io {
+        path: work_dir.clone(),
+        source,
+    })?;
+
+    let config_path = repo.git_dir().join("config");
+    let mut config =
+        gix_config::File::from_path_no_includes(config_path.clone(), gix_config::Source::Local).map_err(|source| {
+            Error::ConfigFile {
+                path: config_path.clone(),
+                source,
+            }
+        })?;
+    config.set_raw_value("core", None, Core::BARE.name, "false")?;
+    config.set_raw_value(
+        "core",
+        None,
+        Core::WORKTREE.name,
+        gix_path::into_bstr(relative_path(&git_dir, &work_dir)).as_ref(),
+    )?;
+    repo.remote_at(url)?
+        .with_refspecs(Some("+refs/heads/*:refs/remotes/origin/*"), remote::Direction::Fetch)
+        .expect("valid static spec")
+        .save_as_to("origin", &mut config)?;
+    std::fs::write(&config_path, config.to_bstring()).map_err(|source| Error::Io {
+        path: config_path,
+        source,
+    })?;
+
+    let dot_git = work_dir.join(gix_discover::DOT_GIT_DIR);
+    let mut content = BString::from("gitdir: ");
+    content.extend_from_slice(&gix_path::into_bstr(relative_path(&work_dir, &git_dir)));
+    content.push(b'\n');
+    std::fs::write(&dot_git, content).map_err(|source| Error::Io { path: dot_git, source })?;
+
+    Ok(crate::open_opts(work_dir, options)?)
+}
+
+/// Check out the tree of `id` into the worktree of `repo`, deleting files that were tracked before but aren't part of
+/// the new tree, and detach `HEAD` at `id`.
+fn checkout(repo: &Repository, id: ObjectId, should_interrupt: &AtomicBool) -> Result<(), Error> {
+    let work_dir = repo.work_dir().expect("submodules have a worktree");
+    let tree = repo.find_object(id)?.peel_to_tree()?.id;
+    let state = gix_index::State::from_tree(&tree, |oid, buf| repo.objects.find_tree_iter(oid, buf).ok())
+        .map_err(|source| Error::IndexFromTree { id, source })?;
+
+    let previous = match repo.index() {
+        Ok(index) => Some(index),
+        Err(crate::worktree::open_index::Error::IndexFile(gix_index::file::init::Error::Io(err)))
+            if err.kind() == std::io::ErrorKind::NotFound =>
+        {
+            None
+        }
+        Err(err) => return Err(err.into()),
+    };
+    if let Some(previous) = previous {
+        for entry in previous.entries() {
+            let path = entry.path(&previous);
+            if state.entry_by_path_and_stage(path, 0).is_some() || entry.mode == gix_index::entry::Mode::COMMIT {
+                continue;
+            }
+            let path = work_dir.join(gix_path::from_bstr(path));
+            match std::fs::remove_file(&path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(Error::Io { path, source: err });
+                }
+                _ => {}
+            }
+            for dir in path.ancestors().skip(1).take_while(|dir| *dir != work_dir) {
+                if std::fs::remove_dir(dir).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut index = gix_index::File::from_state(state, repo.index_path());
+    let mut opts = repo.config.checkout_options(repo.git_dir())?;
+    opts.destination_is_initially_empty = false;
+    opts.overwrite_existing = true;
+    gix_worktree::index::checkout(
+        &mut index,
+        work_dir,
+        {
+            let objects = repo.objects.clone().into_arc().map_err(|source| Error::Io {
+                path: repo.objects.store_ref().path().into(),
+                source,
+            })?;
+            move |oid, buf| objects.find_blob(oid, buf)
+        },
+        &mut crate::progress::Discard,
+        &mut crate::progress::Discard,
+        should_interrupt,
+        opts,
+    )?;
+    index.write(gix_index::write::Options {
+        version: repo.config.index_version()?,
+        ..Default::default()
+    })?;
+
+    repo.edit_reference(RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: format!("submodule update: checkout {id}").into(),
+            },
+            expected: PreviousValue::Any,
+            new: Target::Peeled(id),
+        },
+        name: "HEAD".try_into().expect("valid"),
+        deref: false,
+    })?;
+    Ok(())
+}
+
+/// Return the path leading from the directory `from` to `to`, assuming both are relative to the same directory or absolute.
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<_> = from.components().filter(|c| *c != Component::CurDir).collect();
+    let to: Vec<_> = to.components().filter(|c| *c != Component::CurDir).collect();
+    let common = from.iter().zip(to.iter()).take_while(|(a, b)| a == b).count();
+    std::iter::repeat(Component::ParentDir)
+        .take(from.len() - common)
+        .chain(to[common..].iter().copied())
+        .collect()
+}
@@ -2,7 +2,7 @@ use std::{cell::RefCell, path::PathBuf};
 
 use gix_hash::ObjectId;
 
-use crate::{head, remote};
+use crate::{bstr::BString, head, remote};
 
 /// The kind of repository.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -179,6 +179,24 @@ pub struct ThreadSafeRepository {
     pub(crate) shallow_commits: crate::shallow::CommitsStorage,
 }
 
+/// A submodule of a superproject, as configured in its `.gitmodules` file and possibly overridden by the
+/// superproject's configuration.
+#[derive(Debug, Clone)]
+pub struct Submodule<'repo> {
+    /// The name of the submodule, the subsection of `submodule.<name>`.
+    pub(crate) name: BString,
+    /// The path of the submodule relative to the root of the superproject's worktree.
+    pub(crate) path: BString,
+    /// The url to clone the submodule from, possibly relative to the superproject's default remote.
+    pub(crate) url: Option<BString>,
+    /// The branch to track in the submodule's remote.
+    pub(crate) branch: Option<BString>,
+    /// If `true`, the submodule's url is set in the superproject's configuration, as done by `git submodule init`.
+    pub(crate) is_initialized: bool,
+    /// The superproject.
+    pub(crate) repo: &'repo Repository,
+}
+
 /// A remote which represents a way to interact with hosts for remote clones of the parent repository.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Remote<'repo> {
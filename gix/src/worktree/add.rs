@@ -0,0 +1,236 @@
+#![allow(clippy::result_large_err)]
+use std::path::{Path, PathBuf};
+
+use gix_hash::ObjectId;
+use gix_odb::FindExt;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    Repository,
+};
+
+/// What to check out in a new linked worktree, and what its `HEAD` should point to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Head {
+    /// Create a new branch of the given name at the commit `HEAD` currently points to and check it out,
+    /// or use the last component of the worktree path as name if `None`, like `git worktree add <path>`.
+    NewBranch(Option<BString>),
+    /// Check out the existing local branch of the given name, like `main`, which must not be checked out in another worktree.
+    Branch(BString),
+    /// Check out the given commit with a detached `HEAD`, like `git worktree add --detach <path> <commit>`.
+    Detached(ObjectId),
+}
+
+impl Default for Head {
+    fn default() -> Self {
+        Head::NewBranch(None)
+    }
+}
+
+/// Options for [`Repository::worktree_add()`][crate::Repository::worktree_add()].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// What to check out in the new worktree.
+    pub head: Head,
+    /// If set, lock the new worktree with the given reason to prevent it from being pruned, moved or deleted,
+    /// like `git worktree add --lock --reason <reason>`. The reason may be empty.
+    pub lock_reason: Option<BString>,
+}
+
+/// The error returned by [`Repository::worktree_add()`][crate::Repository::worktree_add()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("The worktree path '{}' already exists and isn't an empty directory", path.display())]
+    PathExists { path: PathBuf },
+    #[error("Could not derive a branch name from the worktree path '{}'", path.display())]
+    NoName { path: PathBuf },
+    #[error("Could not create or write '{}'", path.display())]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error(transparent)]
+    InvalidBranchName(#[from] gix_validate::refname::Error),
+    #[error("The branch '{}' is already checked out in another worktree", name.as_bstr())]
+    BranchCheckedOut { name: gix_ref::FullName },
+    #[error(transparent)]
+    FindReference(#[from] crate::reference::find::existing::Error),
+    #[error(transparent)]
+    HeadCommit(#[from] crate::reference::head_commit::Error),
+    #[error(transparent)]
+    PeelReference(#[from] crate::reference::peel::Error),
+    #[error(transparent)]
+    EditReference(#[from] crate::reference::edit::Error),
+    #[error(transparent)]
+    FindObject(#[from] crate::object::find::existing::Error),
+    #[error(transparent)]
+    PeelToTree(#[from] crate::object::peel::to_kind::Error),
+    #[error(transparent)]
+    Open(#[from] crate::open::Error),
+    #[error("Could not create an index from the tree of commit {id}")]
+    IndexFromTree {
+        id: ObjectId,
+        source: gix_traverse::tree::breadthfirst::Error,
+    },
+    #[error(transparent)]
+    CheckoutOptions(#[from] crate::config::checkout_options::Error),
+    #[error(transparent)]
+    IndexCheckout(
+        #[from]
+        gix_worktree::index::checkout::Error<gix_odb::find::existing_object::Error<gix_odb::store::find::Error>>,
+    ),
+    #[error(transparent)]
+    IndexVersion(#[from] crate::config::key::GenericError),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+}
+
+pub(crate) fn worktree_add(
+    repo: &Repository,
+    path: &Path,
+    Options { head, lock_reason }: Options,
+) -> Result<Repository, Error> {
+    let io_err = |path: &Path| {
+        let path = path.to_owned();
+        move |source| Error::Io { path, source }
+    };
+    let realpath = |path: &Path| {
+        gix_path::realpath(path).map_err(|err| Error::Io {
+            path: path.to_owned(),
+            source: std::io::Error::new(std::io::ErrorKind::Other, err),
+        })
+    };
+    if path.exists() {
+        let is_empty_dir = std::fs::read_dir(path).map_or(false, |mut entries| entries.next().is_none());
+        if !is_empty_dir {
+            return Err(Error::PathExists { path: path.to_owned() });
+        }
+    } else {
+        std::fs::create_dir_all(path).map_err(io_err(path))?;
+    }
+    let path = realpath(path)?;
+    let basename = path
+        .file_name()
+        .and_then(|name| gix_path::os_str_into_bstr(name).ok())
+        .ok_or_else(|| Error::NoName { path: path.clone() })?
+        .to_owned();
+
+    let (head_ref, commit) = match head {
+        Head::NewBranch(name) => {
+            let name = branch_name(name.unwrap_or(basename.clone()).as_ref())?;
+            let commit = repo.head_commit()?.id;
+            repo.reference(
+                name.clone(),
+                commit,
+                gix_ref::transaction::PreviousValue::MustNotExist,
+                format!("branch: Created from {commit}"),
+            )?;
+            (Some(name), commit)
+        }
+        Head::Branch(name) => {
+            let name = branch_name(name.as_ref())?;
+            if checked_out_branches(repo).contains(&name) {
+                return Err(Error::BranchCheckedOut { name });
+            }
+            let commit = repo.find_reference(&name)?.peel_to_id_in_place()?.detach();
+            (Some(name), commit)
+        }
+        Head::Detached(commit) => (None, commit),
+    };
+
+    let admin_dir = unique_admin_dir(&realpath(repo.common_dir())?, basename.as_ref());
+    std::fs::create_dir_all(&admin_dir).map_err(io_err(&admin_dir))?;
+    let dot_git = path.join(gix_discover::DOT_GIT_DIR);
+    let mut files = vec![
+        ("gitdir", line(gix_path::into_bstr(&dot_git).as_ref())),
+        ("commondir", line("../..".into())),
+        (
+            "HEAD",
+            match &head_ref {
+                Some(name) => line(format!("ref: {}", name.as_bstr()).as_str().into()),
+                None => line(commit.to_string().as_str().into()),
+            },
+        ),
+    ];
+    if let Some(reason) = lock_reason {
+        files.push(("locked", line(reason.as_ref())));
+    }
+    for (name, content) in files {
+        let file = admin_dir.join(name);
+        std::fs::write(&file, content).map_err(io_err(&file))?;
+    }
+    let mut content = BString::from("gitdir: ");
+    content.extend_from_slice(&gix_path::into_bstr(&admin_dir));
+    std::fs::write(&dot_git, line(content.as_ref())).map_err(io_err(&dot_git))?;
+
+    let worktree_repo = crate::open_opts(&path, repo.options.clone())?;
+    let tree = worktree_repo.find_object(commit)?.peel_to_tree()?.id;
+    let index = gix_index::State::from_tree(&tree, |oid, buf| worktree_repo.objects.find_tree_iter(oid, buf).ok())
+        .map_err(|source| Error::IndexFromTree { id: commit, source })?;
+    let mut index = gix_index::File::from_state(index, worktree_repo.index_path());
+    let mut opts = worktree_repo.config.checkout_options(worktree_repo.git_dir())?;
+    opts.destination_is_initially_empty = true;
+    gix_worktree::index::checkout(
+        &mut index,
+        &path,
+        {
+            let objects = worktree_repo
+                .objects
+                .clone()
+                .into_arc()
+                .map_err(io_err(worktree_repo.objects.store_ref().path()))?;
+            move |oid, buf| objects.find_blob(oid, buf)
+        },
+        &mut crate::progress::Discard,
+        &mut crate::progress::Discard,
+        &std::sync::atomic::AtomicBool::default(),
+        opts,
+    )?;
+    index.write(gix_index::write::Options {
+        version: worktree_repo.config.index_version()?,
+        ..Default::default()
+    })?;
+    Ok(worktree_repo)
+}
+
+fn line(content: &BStr) -> BString {
+    let mut line = content.to_owned();
+    line.push(b'\n');
+    line
+}
+
+fn branch_name(name: &BStr) -> Result<gix_ref::FullName, gix_validate::refname::Error> {
+    let mut full_name = BString::from("refs/heads/");
+    full_name.extend_from_slice(name);
+    gix_ref::FullName::try_from(full_name)
+}
+
+/// Return the `worktrees/<name>` directory in `common_dir`, with a number appended to `name` if it's already taken,
+/// the way `git` does.
+fn unique_admin_dir(common_dir: &Path, name: &BStr) -> PathBuf {
+    let worktrees = common_dir.join("worktrees");
+    let name = gix_path::from_bstr(name);
+    let mut dir = worktrees.join(&name);
+    let mut counter = 1;
+    while dir.exists() {
+        let mut candidate = name.as_os_str().to_owned();
+        candidate.push(counter.to_string());
+        dir = worktrees.join(candidate);
+        counter += 1;
+    }
+    dir
+}
+
+/// Return the names of the branches that are checked out in the main worktree and all linked worktrees of `repo`.
+pub(crate) fn checked_out_branches(repo: &Repository) -> Vec<gix_ref::FullName> {
+    let mut head_files = vec![repo.common_dir().join("HEAD")];
+    if let Ok(entries) = std::fs::read_dir(repo.common_dir().join("worktrees")) {
+        head_files.extend(entries.filter_map(Result::ok).map(|entry| entry.path().join("HEAD")));
+    }
+    head_files
+        .into_iter()
+        .filter_map(|path| std::fs::read(path).ok())
+        .filter_map(|content| {
+            let name = content.trim().strip_prefix(b"ref:")?.trim().as_bstr().to_owned();
+            gix_ref::FullName::try_from(name).ok()
+        })
+        .collect()
+}
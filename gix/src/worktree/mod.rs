@@ -66,9 +66,42 @@ pub(crate) fn id(git_dir: &std::path::Path, has_common_dir: bool) -> Option<&BSt
     (maybe_worktrees.file_name()?.to_str()? == "worktrees").then_some(candidate)
 }
 
+/// Return `true` if the file at `path` has the content of the blob `id`, which is checked out with `mode`, or `None` if there
+/// is no such file.
+///
+/// No conversions are applied to the file, so it's considered modified if line endings were changed when checking it out.
+pub(crate) fn file_matches_blob(
+    path: &std::path::Path,
+    mode: gix_index::entry::Mode,
+    id: &gix_hash::oid,
+) -> std::io::Result<Option<bool>> {
+    let meta = match std::fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let data = if mode == gix_index::entry::Mode::SYMLINK && meta.file_type().is_symlink() {
+        gix_path::into_bstr(std::fs::read_link(path)?).into_owned().into()
+    } else if meta.is_file() && mode != gix_index::entry::Mode::SYMLINK {
+        std::fs::read(path)?
+    } else {
+        return Ok(Some(false));
+    };
+    let mut hasher = gix_features::hash::hasher(id.kind());
+    hasher.update(&gix_object::encode::loose_header(gix_object::Kind::Blob, data.len()));
+    hasher.update(&data);
+    Ok(Some(gix_hash::ObjectId::from(hasher.digest()) == id))
+}
+
 ///
 pub mod proxy;
 
+///
+pub mod add;
+
+///
+pub mod untracked;
+
 ///
 pub mod open_index {
     use crate::bstr::BString;
@@ -24,6 +24,27 @@ pub mod into_repo {
     }
 }
 
+///
+pub mod move_to {
+    use std::path::PathBuf;
+
+    use crate::bstr::BString;
+
+    /// The error returned by [`Proxy::move_to()`][super::Proxy::move_to()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The worktree is locked and cannot be moved: {reason}")]
+        Locked { reason: BString },
+        #[error("Cannot move the worktree to '{}' as it already exists", path.display())]
+        DestinationExists { path: PathBuf },
+        #[error("Could not read the location of the worktree")]
+        Base(#[source] std::io::Error),
+        #[error("Could not move the worktree to '{}' or update its administrative files", path.display())]
+        Io { path: PathBuf, source: std::io::Error },
+    }
+}
+
 impl<'repo> Proxy<'repo> {
     pub(crate) fn new(parent: &'repo Repository, git_dir: impl Into<PathBuf>) -> Self {
         Proxy {
@@ -74,6 +95,54 @@ impl<'repo> Proxy<'repo> {
             .map(|contents| contents.trim().into())
     }
 
+    /// Return the reason for which the administrative files of this worktree would be removed by
+    /// [`Repository::worktree_prune()`][crate::Repository::worktree_prune()], or `None` if it's locked or its checkout still exists.
+    ///
+    /// The reasons are the same as the ones shown by `git worktree list --porcelain`.
+    pub fn prune_reason(&self) -> Option<BString> {
+        if self.is_locked() {
+            return None;
+        }
+        match gix_discover::path::from_plain_file(self.git_dir.join("gitdir")) {
+            None => Some("gitdir file does not exist".into()),
+            Some(Err(_)) => Some("unable to read gitdir file".into()),
+            Some(Ok(path)) if path.as_os_str().is_empty() => Some("invalid gitdir file".into()),
+            Some(Ok(path)) if !path.exists() => Some("gitdir file points to non-existent location".into()),
+            Some(Ok(_)) => None,
+        }
+    }
+
+    /// Move the checkout of this worktree to `new_base`, which must not exist yet, and update the administrative files
+    /// of the worktree to point to the new location, like `git worktree move`.
+    ///
+    /// Locked worktrees cannot be moved.
+    pub fn move_to(&self, new_base: impl AsRef<Path>) -> Result<(), move_to::Error> {
+        let new_base = new_base.as_ref();
+        if let Some(reason) = self.lock_reason() {
+            return Err(move_to::Error::Locked { reason });
+        }
+        if new_base.exists() {
+            return Err(move_to::Error::DestinationExists {
+                path: new_base.to_owned(),
+            });
+        }
+        let base = self.base().map_err(move_to::Error::Base)?;
+        let io_err = |path: &Path| {
+            let path = path.to_owned();
+            move |source| move_to::Error::Io { path, source }
+        };
+        std::fs::rename(&base, new_base).map_err(io_err(new_base))?;
+        let new_base = gix_path::realpath(new_base).map_err(|err| move_to::Error::Io {
+            path: new_base.to_owned(),
+            source: std::io::Error::new(std::io::ErrorKind::Other, err),
+        })?;
+
+        let gitdir = self.git_dir.join("gitdir");
+        let mut content = gix_path::into_bstr(new_base.join(gix_discover::DOT_GIT_DIR)).into_owned();
+        content.push(b'\n');
+        std::fs::write(&gitdir, content).map_err(io_err(&gitdir))
+    }
+
     /// Transform this proxy into a [`Repository`] while ignoring issues reading `base()` and ignoring that it might not exist.
     ///
     /// Most importantly, the `Repository` might be initialized with a non-existing work tree directory as the checkout
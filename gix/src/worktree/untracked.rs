@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+
+use gix_index::extension::UntrackedCache;
+use gix_odb::FindExt;
+
+use crate::config::tree::Core;
+
+pub use gix_worktree::untracked::Outcome;
+
+/// The error returned by [`Worktree::untracked_files()`][crate::Worktree::untracked_files()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    OpenIndex(#[from] crate::worktree::open_index::Error),
+    #[error(transparent)]
+    Excludes(#[from] crate::worktree::excludes::Error),
+    #[error(transparent)]
+    ConfigUntrackedCache(#[from] crate::config::key::GenericErrorWithValue),
+    #[error(transparent)]
+    ConfigFsMonitorHookVersion(#[from] crate::config::unsigned_integer::Error),
+    #[error(transparent)]
+    FsMonitor(#[from] gix_worktree::fsmonitor::Error),
+    #[error("Could not obtain the real path of the worktree at '{}'", path.display())]
+    RealPath {
+        path: PathBuf,
+        source: gix_path::realpath::Error,
+    },
+    #[error(transparent)]
+    Find(#[from] gix_worktree::untracked::Error),
+    #[error(transparent)]
+    WriteIndex(#[from] gix_index::file::write::Error),
+}
+
+impl<'repo> crate::Worktree<'repo> {
+    /// Find all untracked files and directories in this worktree, like `git status` does, with directories being listed
+    /// by their name followed by a slash if they contain at least one file that isn't excluded.
+    ///
+    /// The untracked cache of the index is used to avoid reading directories that didn't change, and it's created or
+    /// removed as configured in `core.untrackedCache`.
+    /// If a filesystem monitor is configured in `core.fsmonitor`, it's asked for changes since the last query so that
+    /// only the directories of the untracked cache containing changed paths are read again.
+    ///
+    /// Whenever the untracked cache or the filesystem monitor extension is present or was removed, the index is written back
+    /// to keep them updated.
+    pub fn untracked_files(&self) -> Result<Outcome, Error> {
+        let repo = self.parent;
+        let mut index = self.open_index()?;
+        let mut excludes = self.excludes(&index, None)?;
+        let use_untracked_cache = repo
+            .config
+            .apply_leniency(
+                repo.config
+                    .resolved
+                    .string("core", None, Core::UNTRACKED_CACHE.name)
+                    .map(|value| Core::UNTRACKED_CACHE.try_into_untracked_cache(value)),
+            )?
+            .flatten();
+
+        let worktree_root = gix_path::realpath(self.path).map_err(|source| Error::RealPath {
+            path: self.path.to_owned(),
+            source,
+        })?;
+        let identifier = gix_worktree::untracked::identifier(&worktree_root);
+        let mut cache_removed = false;
+        match use_untracked_cache {
+            Some(true) if index.untracked().map_or(true, |cache| cache.identifier != identifier) => {
+                index.set_untracked(Some(UntrackedCache::new(identifier.clone(), ".gitignore")));
+            }
+            Some(false) => cache_removed = index.set_untracked(None).is_some(),
+            Some(true) | None => {}
+        }
+
+        let fs_monitor = repo
+            .config
+            .resolved
+            .string("core", None, Core::FS_MONITOR.name)
+            .and_then(|value| Core::FS_MONITOR.try_into_fs_monitor(value, repo.git_dir()));
+        let fs_monitor_removed = match fs_monitor {
+            Some(mut source) => {
+                if let gix_worktree::fsmonitor::Source::Hook { version, .. } = &mut source {
+                    *version = repo
+                        .config
+                        .apply_leniency(
+                            repo.config
+                                .resolved
+                                .integer("core", None, Core::FS_MONITOR_HOOK_VERSION.name)
+                                .map(|value| Core::FS_MONITOR_HOOK_VERSION.try_into_u64(value)),
+                        )?
+                        .filter(|version| *version == 1 || *version == 2)
+                        .map(|version| version as u8);
+                }
+                let outcome = gix_worktree::fsmonitor::query(
+                    &source,
+                    self.path,
+                    index.fs_monitor().map(|fs_monitor| &fs_monitor.token),
+                )?;
+                gix_worktree::fsmonitor::apply(&mut index, outcome);
+                false
+            }
+            None => index.set_fs_monitor(None).is_some(),
+        };
+
+        let excludes_file = match repo
+            .config
+            .excludes_file()
+            .transpose()
+            .map_err(crate::worktree::excludes::Error::from)?
+        {
+            Some(user_path) => Some(user_path),
+            None => repo
+                .config
+                .xdg_config_path("ignore")
+                .map_err(crate::worktree::excludes::Error::from)?,
+        };
+        let use_fs_monitor = index.fs_monitor().is_some();
+        let outcome = gix_worktree::untracked::find(
+            &mut index,
+            &mut excludes,
+            gix_worktree::untracked::Options {
+                identifier,
+                info_exclude: Some(repo.git_dir().join("info").join("exclude")),
+                excludes_file,
+                use_fs_monitor,
+            },
+            |oid, buf| repo.objects.find_blob(oid, buf),
+        )?;
+        if index.untracked().is_some() || index.fs_monitor().is_some() || cache_removed || fs_monitor_removed {
+            index.write(gix_index::write::Options {
+                version: Some(index.version()),
+                ..Default::default()
+            })?;
+        }
+        Ok(outcome)
+    }
+}
@@ -474,6 +474,40 @@ mod blocking_io {
         Ok(())
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn fetch_and_checkout_runs_the_post_checkout_hook() -> crate::Result {
+        use std::os::unix::fs::PermissionsExt;
+
+        let hooks = gix_testtools::tempfile::TempDir::new()?;
+        let hook = hooks.path().join("post-checkout");
+        std::fs::write(&hook, "#!/bin/sh\necho \"$@\" > ../post-checkout-args\n")?;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            remote::repo("base").path(),
+            tmp.path().join("clone"),
+            gix::create::Kind::WithWorktree,
+            Default::default(),
+            restricted().config_overrides([
+                "user.name=gitoxide".to_string(),
+                "user.email=gitoxide@localhost".into(),
+                format!("core.hooksPath={}", hooks.path().display()),
+            ]),
+        )?;
+        let (mut checkout, _out) =
+            prepare.fetch_then_checkout(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        let (repo, _) = checkout.main_worktree(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("post-checkout-args"))?,
+            format!("{} {} 1\n", gix::ObjectId::null(repo.object_hash()), repo.head_id()?),
+            "the hook runs in the worktree and sees that a branch was checked out, from nothing to HEAD"
+        );
+        Ok(())
+    }
+
     #[test]
     fn fetch_and_checkout_empty_remote_repo() -> crate::Result {
         let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -523,6 +557,104 @@ mod blocking_io {
         Ok(())
     }
 
+    #[test]
+    fn fetch_only_with_filter_makes_partial_clone() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let mut prepare = gix::clone::PrepareFetch::new(
+            remote::repo("base.allow-filter").path(),
+            tmp.path(),
+            gix::create::Kind::Bare,
+            Default::default(),
+            restricted(),
+        )?
+        .with_filter(gix::remote::fetch::Filter::BlobNone);
+        let (repo, out) = prepare.fetch_only(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+        drop(prepare);
+        match out.status {
+            gix::remote::fetch::Status::Change { write_pack_bundle, .. } => {
+                let data_path = write_pack_bundle.data_path.expect("pack was written");
+                assert!(
+                    data_path.with_extension("promisor").is_file(),
+                    "the pack is marked as coming from a promisor remote"
+                );
+            }
+            _ => unreachable!("a clone always carries a change"),
+        }
+
+        let blob = repo
+            .head_commit()?
+            .tree()?
+            .lookup_entry_by_path("file")?
+            .expect("present")
+            .object_id();
+        assert!(repo.try_find_object(blob)?.is_none(), "blobs were filtered");
+
+        let config =
+            gix::config::File::from_path_no_includes(repo.git_dir().join("config"), gix::config::Source::Local)?;
+        assert_eq!(
+            config
+                .boolean("remote", Some("origin".into()), "promisor")
+                .transpose()?,
+            Some(true)
+        );
+        assert_eq!(
+            config
+                .string("remote", Some("origin".into()), "partialCloneFilter")
+                .expect("set")
+                .as_ref(),
+            "blob:none"
+        );
+        assert_eq!(
+            config.integer("core", None, "repositoryFormatVersion").transpose()?,
+            Some(1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_objects_of_partial_clones_can_be_fetched_on_demand() -> crate::Result {
+        let tmp = gix_testtools::tempfile::TempDir::new()?;
+        let (repo, _out) = gix::clone::PrepareFetch::new(
+            remote::repo("base.allow-filter").path(),
+            tmp.path(),
+            gix::create::Kind::Bare,
+            Default::default(),
+            restricted(),
+        )?
+        .with_filter(gix::remote::fetch::Filter::BlobNone)
+        .fetch_only(gix::progress::Discard, &std::sync::atomic::AtomicBool::default())?;
+
+        let mut repo = gix::open_opts(repo.git_dir(), restricted())?;
+        assert_eq!(
+            repo.promisor_remote()
+                .expect("present")?
+                .name()
+                .map(|name| name.as_bstr()),
+            Some("origin".into())
+        );
+        let blob = repo
+            .head_commit()?
+            .tree()?
+            .lookup_entry_by_path("file")?
+            .expect("present")
+            .object_id();
+        assert!(repo.try_find_object(blob)?.is_none(), "blobs were filtered");
+
+        repo.fetch_missing_objects_on_demand(true);
+        assert_eq!(
+            repo.find_object(blob)?.kind,
+            gix::object::Kind::Blob,
+            "the blob is fetched from the promisor remote when it's needed"
+        );
+
+        repo.fetch_missing_objects_on_demand(false);
+        assert!(
+            repo.try_find_object(blob)?.is_some(),
+            "it was stored locally and remains available"
+        );
+        Ok(())
+    }
+
     #[test]
     fn fetch_only_without_configuration() -> crate::Result {
         let tmp = gix_testtools::tempfile::TempDir::new()?;
@@ -194,6 +194,7 @@ mod core {
 
     use gix::{
         config::tree::{Core, Key},
+        filter::eol::{AutoCrlf, Mode},
         revision::spec::parse::ObjectKindHint,
     };
     use gix_lock::acquire::Fail;
@@ -349,6 +350,81 @@ mod core {
         assert!(Core::CHECK_STAT.validate("foo".into()).is_err());
         Ok(())
     }
+
+    #[test]
+    fn autocrlf() -> crate::Result {
+        for (value, expected) in [
+            ("input", AutoCrlf::Input),
+            ("Input", AutoCrlf::Input),
+            ("true", AutoCrlf::Enabled),
+            ("false", AutoCrlf::Disabled),
+        ] {
+            assert_eq!(Core::AUTO_CRLF.try_into_autocrlf(bcow(value))?, expected);
+            assert!(Core::AUTO_CRLF.validate(value.into()).is_ok());
+        }
+        assert_eq!(
+            Core::AUTO_CRLF.try_into_autocrlf(bcow("auto")).unwrap_err().to_string(),
+            "The key \"core.autocrlf=auto\" was invalid"
+        );
+        assert!(Core::AUTO_CRLF.validate("auto".into()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn eol() -> crate::Result {
+        assert_eq!(Core::EOL.try_into_eol(bcow("lf"))?, Mode::Lf);
+        assert_eq!(Core::EOL.try_into_eol(bcow("CRLF"))?, Mode::CrLf);
+        assert_eq!(Core::EOL.try_into_eol(bcow("native"))?, Mode::native());
+        assert_eq!(
+            Core::EOL.try_into_eol(bcow("cr")).unwrap_err().to_string(),
+            "The key \"core.eol=cr\" was invalid"
+        );
+        assert!(Core::EOL.validate("native".into()).is_ok());
+        assert!(Core::EOL.validate("cr".into()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn fs_monitor() {
+        let git_dir = std::path::Path::new(".git");
+        assert_eq!(
+            Core::FS_MONITOR.try_into_fs_monitor(bcow("true"), git_dir),
+            Some(gix::worktree::fsmonitor::Source::Daemon {
+                socket: git_dir.join("fsmonitor--daemon.ipc")
+            })
+        );
+        assert_eq!(Core::FS_MONITOR.try_into_fs_monitor(bcow("false"), git_dir), None);
+        assert_eq!(
+            Core::FS_MONITOR.try_into_fs_monitor(bcow(".git/hooks/query-watchman"), git_dir),
+            Some(gix::worktree::fsmonitor::Source::Hook {
+                command: ".git/hooks/query-watchman".into(),
+                version: None
+            })
+        );
+        assert!(Core::FS_MONITOR.validate("anything".into()).is_ok());
+    }
+
+    #[test]
+    fn untracked_cache() -> crate::Result {
+        for (value, expected) in [
+            ("keep", None),
+            ("Keep", None),
+            ("true", Some(true)),
+            ("false", Some(false)),
+        ] {
+            assert_eq!(Core::UNTRACKED_CACHE.try_into_untracked_cache(bcow(value))?, expected);
+            assert!(Core::UNTRACKED_CACHE.validate(value.into()).is_ok());
+        }
+        assert_eq!(
+            Core::UNTRACKED_CACHE
+                .try_into_untracked_cache(bcow("sometimes"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"core.untrackedCache=sometimes\" was invalid"
+        );
+        assert!(Core::UNTRACKED_CACHE.validate("sometimes".into()).is_err());
+        Ok(())
+    }
 }
 
 mod extensions {
@@ -400,6 +476,100 @@ mod checkout {
     }
 }
 
+mod filter {
+    use gix::config::tree::{Filter, Key};
+
+    #[test]
+    fn keys_require_a_driver() {
+        for key in [
+            &Filter::CLEAN as &dyn Key,
+            &Filter::SMUDGE,
+            &Filter::PROCESS,
+            &Filter::REQUIRED,
+        ] {
+            assert!(key.full_name(None).is_err());
+        }
+        assert_eq!(
+            Filter::CLEAN.full_name(Some("lfs".into())).expect("valid"),
+            "filter.lfs.clean"
+        );
+        assert!(Filter::REQUIRED.validate("true".into()).is_ok());
+        assert!(Filter::REQUIRED.validate("nope".into()).is_err());
+    }
+}
+
+mod gpg {
+    use gix::{
+        config::tree::{Gpg, Key},
+        sign::Format,
+    };
+
+    use crate::config::tree::bcow;
+
+    #[test]
+    fn format() -> crate::Result {
+        for (value, expected) in [
+            ("openpgp", Format::OpenPgp),
+            ("x509", Format::X509),
+            ("ssh", Format::Ssh),
+        ] {
+            assert_eq!(Gpg::FORMAT.try_into_format(bcow(value))?, expected);
+            assert!(Gpg::FORMAT.validate(value.into()).is_ok());
+        }
+        assert_eq!(
+            Gpg::FORMAT.try_into_format(bcow("gpg")).unwrap_err().to_string(),
+            "The key \"gpg.format=gpg\" was invalid"
+        );
+        assert!(Gpg::FORMAT.validate("SSH".into()).is_err());
+        Ok(())
+    }
+}
+
+mod index {
+    use gix::config::tree::{Index, Key};
+
+    #[test]
+    fn version() -> crate::Result {
+        for (value, expected) in [
+            (2, gix::index::Version::V2),
+            (3, gix::index::Version::V3),
+            (4, gix::index::Version::V4),
+        ] {
+            assert_eq!(Index::VERSION.try_into_index_version(Ok(value))?, expected);
+            assert!(Index::VERSION.validate(value.to_string().as_str().into()).is_ok());
+        }
+        assert_eq!(
+            Index::VERSION.try_into_index_version(Ok(1)).unwrap_err().to_string(),
+            "The value of key \"index.version\" was invalid"
+        );
+        assert!(Index::VERSION.validate("5".into()).is_err());
+        assert!(Index::VERSION.validate("-1".into()).is_err());
+        Ok(())
+    }
+}
+
+mod commit_graph {
+    use gix::config::tree::{CommitGraph, Key};
+
+    #[test]
+    fn generation_version() -> crate::Result {
+        assert!(!CommitGraph::GENERATION_VERSION.try_into_corrected_commit_dates(Ok(1))?);
+        assert!(CommitGraph::GENERATION_VERSION.validate("1".into()).is_ok());
+        assert!(CommitGraph::GENERATION_VERSION.try_into_corrected_commit_dates(Ok(2))?);
+        assert!(CommitGraph::GENERATION_VERSION.validate("2".into()).is_ok());
+        assert_eq!(
+            CommitGraph::GENERATION_VERSION
+                .try_into_corrected_commit_dates(Ok(3))
+                .unwrap_err()
+                .to_string(),
+            "The value of key \"commitGraph.generationVersion\" was invalid"
+        );
+        assert!(CommitGraph::GENERATION_VERSION.validate("3".into()).is_err());
+        assert!(CommitGraph::GENERATION_VERSION.validate("0".into()).is_err());
+        Ok(())
+    }
+}
+
 mod pack {
     use gix::config::tree::{Key, Pack};
 
@@ -426,6 +596,13 @@ mod pack {
         assert!(Pack::INDEX_VERSION.validate("-1".into()).is_err());
         Ok(())
     }
+
+    #[test]
+    fn write_bitmap_hash_cache() {
+        assert!(Pack::WRITE_BITMAP_HASH_CACHE.validate("true".into()).is_ok());
+        assert!(Pack::WRITE_BITMAP_HASH_CACHE.validate("false".into()).is_ok());
+        assert!(Pack::WRITE_BITMAP_HASH_CACHE.validate("foo".into()).is_err());
+    }
 }
 
 #[cfg(any(feature = "blocking-network-client", feature = "async-network-client"))]
@@ -465,6 +642,30 @@ mod protocol {
         }
         Ok(())
     }
+
+    #[test]
+    fn version() -> crate::Result {
+        use gix::config::tree::Remote;
+        use gix::protocol::transport::Protocol as Version;
+        for key in [&Protocol::VERSION, &Remote::PROTOCOL_VERSION] {
+            for (input, expected) in [(0, Version::V1), (1, Version::V1), (2, Version::V2)] {
+                assert_eq!(key.try_into_protocol_version(Ok(input))?, expected);
+                assert!(key.validate(input.to_string().as_str().into()).is_ok());
+            }
+            assert!(key.try_into_protocol_version(Ok(3)).is_err());
+            assert!(key.validate("3".into()).is_err());
+            assert!(key.validate("-1".into()).is_err());
+            assert!(key.validate("two".into()).is_err());
+        }
+        assert_eq!(
+            Protocol::VERSION
+                .try_into_protocol_version(Ok(3))
+                .unwrap_err()
+                .to_string(),
+            "The value of key \"protocol.version\" was invalid"
+        );
+        Ok(())
+    }
 }
 
 mod gitoxide {
@@ -734,6 +935,47 @@ mod remote {
         Ok(())
     }
 
+    #[test]
+    fn partial_clone_filter() -> crate::Result {
+        use remote::fetch::Filter;
+        for (spec, expected) in [
+            ("blob:none", Filter::BlobNone),
+            ("blob:limit=0", Filter::BlobLimit(0)),
+            ("blob:limit=1k", Filter::BlobLimit(1024)),
+            ("blob:limit=2m", Filter::BlobLimit(2 * 1024 * 1024)),
+            ("blob:limit=1g", Filter::BlobLimit(1024 * 1024 * 1024)),
+            ("tree:0", Filter::TreeDepth(0)),
+            ("tree:3", Filter::TreeDepth(3)),
+        ] {
+            assert_eq!(Remote::PARTIAL_CLONE_FILTER.try_into_filter(bcow(spec))?, expected);
+            assert!(Remote::PARTIAL_CLONE_FILTER.validate(spec.into()).is_ok());
+        }
+        assert_eq!(
+            Filter::BlobLimit(1024).to_string(),
+            "blob:limit=1024",
+            "sizes are sent in bytes"
+        );
+        assert_eq!(Filter::TreeDepth(1).to_string(), "tree:1");
+
+        for invalid in [
+            "blob:limit=",
+            "blob:limit=k",
+            "tree:",
+            "sparse:oid=HEAD",
+            "combine:blob:none+tree:0",
+        ] {
+            assert!(Remote::PARTIAL_CLONE_FILTER.validate(invalid.into()).is_err());
+        }
+        assert_eq!(
+            Remote::PARTIAL_CLONE_FILTER
+                .try_into_filter(bcow("blob:some"))
+                .unwrap_err()
+                .to_string(),
+            "The key \"remote.<name>.partialCloneFilter=blob:some\" was invalid"
+        );
+        Ok(())
+    }
+
     #[test]
     fn url_and_push_url() {
         assert!(Remote::URL.try_into_url(bcow("http://example.org")).is_ok());
@@ -0,0 +1,11 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+for n in 0 1 2 3 4 5 6 7 8 9; do
+  echo "$n" > file
+  git add file
+  GIT_COMMITTER_DATE="2000-01-0$((n + 1)) 00:00:00 +0000" git commit -q -m "c$n"
+  git tag "c$n"
+done
@@ -0,0 +1,29 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+printf '1\n2\n3\n' > file
+git add file
+git commit -q -m c1
+git tag c1
+
+printf '1\n2\n3\n4\n' > file
+git commit -q -am c2
+git tag c2
+
+printf '1\nTWO\n3\n4\n' > file
+git commit -q -am c3
+git tag c3
+
+git checkout -q -b side c2
+printf '0\n1\n2\n3\n4\n' > file
+git commit -q -am side
+git tag side
+
+git checkout -q -
+git merge -q --no-ff -m merge side
+
+printf '0\n1\n  TWO\n3\n4\n' > file
+git commit -q -am "c4 - whitespace only"
+git tag c4
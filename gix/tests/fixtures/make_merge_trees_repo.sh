@@ -0,0 +1,48 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+seq 1 10 > numbers
+echo unchanged > unchanged
+echo base > modified-by-ours
+echo base > modified-by-theirs
+echo base > deleted-by-theirs
+echo base > to-be-executable
+mkdir dir
+echo base > dir/file
+git add .
+git commit -q -m base
+git tag base
+
+git checkout -q -b ours
+sed -i.bak 's/^2$/two/' numbers && rm numbers.bak
+echo ours > modified-by-ours
+echo same > added-by-both
+git add .
+git commit -q -m ours
+
+git checkout -q -b theirs base
+sed -i.bak 's/^9$/nine/' numbers && rm numbers.bak
+echo theirs > modified-by-theirs
+git rm -q deleted-by-theirs
+chmod +x to-be-executable
+echo same > added-by-both
+echo theirs > dir/new
+git add .
+git commit -q -m theirs
+
+git checkout -q -b conflicting base
+sed -i.bak 's/^2$/zwei/' numbers && rm numbers.bak
+echo conflicting > modified-by-ours
+echo different > added-by-both
+git rm -q dir/file
+git add .
+git commit -q -m conflicting
+
+git checkout -q -b modify-delete base
+echo modified > deleted-by-theirs
+git commit -q -am modify-delete
+
+git checkout -q main
@@ -0,0 +1,30 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+seq 1 10 > numbers
+echo base > file
+mkdir dir
+echo base > dir/removed
+git add .
+git commit -q -m base
+
+git checkout -q -b other
+seq 1 11 > numbers
+git commit -q -am "append to numbers"
+echo added > added
+git add added
+GIT_AUTHOR_NAME="Other Author" GIT_AUTHOR_EMAIL=other@example.com git commit -q -m "add a file" -m "Signed-off-by: Other Author <other@example.com>"
+git rm -q dir/removed
+git commit -q -m "remove a directory"
+echo other > file
+git commit -q -am "change the file"
+
+git checkout -q main
+{ echo one; seq 2 10; } > numbers
+git commit -q -am "spell out one"
+echo main > file
+git commit -q -am "change the file on main"
+git cherry-pick other~3 >/dev/null
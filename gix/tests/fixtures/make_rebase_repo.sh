@@ -0,0 +1,43 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+seq 1 10 > numbers
+echo base > file
+git add .
+git commit -q -m base
+git branch base
+
+git checkout -q -b topic
+seq 1 11 > numbers
+git commit -q -am "append to numbers"
+echo added > added
+git add added
+GIT_AUTHOR_NAME="Other Author" GIT_AUTHOR_EMAIL=other@example.com git commit -q -m "add a file"
+
+git checkout -q -b conflicting base
+echo conflicting > file
+git commit -q -am "change the file"
+echo another > another
+git add another
+git commit -q -m "add another file"
+
+git checkout -q -b redundant base
+{ echo one; seq 2 10; } > numbers
+git commit -q -am "spell out one as well"
+echo redundant > redundant
+git add redundant
+git commit -q -m "add a redundant file"
+
+git checkout -q main
+{ echo one; seq 2 10; } > numbers
+git commit -q -am "spell out one"
+echo main > file
+git commit -q -am "change the file on main"
+
+for branch in topic conflicting redundant; do
+  git clone -q . $branch
+  (cd $branch && git checkout -q $branch && git branch -q base origin/base)
+done
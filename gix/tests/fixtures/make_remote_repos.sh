@@ -109,6 +109,9 @@ git init base
 
 git clone --shared --depth 2 file://$PWD/base base.shallow
 
+git clone --bare --shared base base.allow-filter
+git -C base.allow-filter config uploadpack.allowFilter true
+
 
 git clone --shared base clone
 (cd clone
@@ -217,6 +220,11 @@ git clone --shared base protocol_file_user
     git config protocol.file.allow user
 )
 
+git clone --shared base protocol_user
+(cd protocol_user
+    git config protocol.allow user
+)
+
 git clone --shared base remote-as-url
 (cd remote-as-url
   cat <<EOF >> .git/config
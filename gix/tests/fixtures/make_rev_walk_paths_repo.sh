@@ -0,0 +1,40 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q no-commit-graph
+(cd no-commit-graph
+  mkdir dir
+  echo a > a
+  echo b > dir/b
+  git add .
+  git commit -q -m c1
+  git tag c1
+
+  echo a2 > a
+  git commit -q -am c2
+  git tag c2
+
+  echo b2 > dir/b
+  git commit -q -am c3
+  git tag c3
+
+  git checkout -q -b topic c2
+  mkdir dir/sub
+  echo c > dir/sub/c
+  git add .
+  git commit -q -m side
+  git tag side
+
+  git checkout -q -
+  git merge -q --no-ff -m merge topic
+  git tag merge
+
+  echo a3 > a
+  git commit -q -am c4
+  git tag c4
+)
+
+cp -R no-commit-graph with-commit-graph
+(cd with-commit-graph
+  git commit-graph write --no-progress --reachable --changed-paths
+)
@@ -0,0 +1,13 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+
+mkdir -p a/b/c d
+for file in r a/1 a/b/2 a/b/c/3 d/4; do
+  echo "$file" > "$file"
+done
+git add -A
+git commit -q -m "c1"
+
+git sparse-checkout set a/b/c
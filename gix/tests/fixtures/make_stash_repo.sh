@@ -0,0 +1,26 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git checkout -q -b main
+
+echo base > file
+mkdir dir
+echo base > dir/nested
+echo base > removed
+echo base > unchanged
+git add .
+git commit -q -m base
+
+git checkout -q -b other
+echo other > file
+git commit -q -am "change the file"
+git checkout -q main
+
+echo modified > file
+echo staged > dir/nested
+git add dir/nested
+echo added > added
+git add added
+rm removed
+echo untracked > untracked
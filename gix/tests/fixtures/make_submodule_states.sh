@@ -0,0 +1,32 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q module1
+(cd module1
+  echo content > file
+  git add file && git commit -q -m c1
+  echo more >> file && git commit -q -am c2
+)
+
+git init -q with-submodules
+(cd with-submodules
+  echo content > file
+  git add file && git commit -q -m "init"
+  git -c protocol.file.allow=always submodule --quiet add ../module1 m1
+  git -c protocol.file.allow=always submodule --quiet add ../module1 dir/m1
+  git commit -q -m "add submodules"
+)
+
+git clone -q with-submodules not-initialized
+
+git clone -q with-submodules dirty
+(cd dirty
+  git -c protocol.file.allow=always submodule --quiet update --init
+  echo change >> m1/file
+)
+
+git clone -q with-submodules head-mismatch
+(cd head-mismatch
+  git -c protocol.file.allow=always submodule --quiet update --init
+  git -C m1 checkout -q HEAD~1
+)
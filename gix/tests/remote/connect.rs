@@ -25,27 +25,123 @@ mod blocking_io {
         #[test]
         #[serial]
         fn user() -> crate::Result {
-            for (env_value, should_allow) in [(None, true), (Some("0"), false), (Some("1"), true)] {
-                let _env = env_value.map(|value| gix_testtools::Env::new().set("GIT_PROTOCOL_FROM_USER", value));
-                let repo = gix::open_opts(
-                    remote::repo("protocol_file_user").git_dir(),
-                    gix::open::Options::isolated().permissions(gix::Permissions {
-                        env: gix::permissions::Environment {
-                            git_prefix: gix_sec::Permission::Allow,
-                            ..gix::permissions::Environment::all()
-                        },
-                        ..gix::Permissions::isolated()
-                    }),
-                )?;
-                let remote = repo.find_remote("origin")?;
-                assert_eq!(
-                    remote.connect(Fetch, progress::Discard).is_ok(),
-                    should_allow,
-                    "Value = {:?}",
-                    env_value
-                );
+            for name in ["protocol_file_user", "protocol_user"] {
+                for (env_value, should_allow) in [(None, true), (Some("0"), false), (Some("1"), true)] {
+                    let _env = env_value.map(|value| gix_testtools::Env::new().set("GIT_PROTOCOL_FROM_USER", value));
+                    let repo = gix::open_opts(
+                        remote::repo(name).git_dir(),
+                        gix::open::Options::isolated().permissions(gix::Permissions {
+                            env: gix::permissions::Environment {
+                                git_prefix: gix_sec::Permission::Allow,
+                                ..gix::permissions::Environment::all()
+                            },
+                            ..gix::Permissions::isolated()
+                        }),
+                    )?;
+                    let remote = repo.find_remote("origin")?;
+                    assert_eq!(
+                        remote.connect(Fetch, progress::Discard).is_ok(),
+                        should_allow,
+                        "{}: Value = {:?}",
+                        name,
+                        env_value
+                    );
+                }
             }
             Ok(())
         }
     }
+
+    mod protocol_version {
+        use gix::{protocol::transport::Protocol, remote::Direction::Fetch};
+
+        use crate::remote;
+
+        #[allow(clippy::result_large_err)]
+        fn version(repo: &gix::Repository) -> Result<Protocol, gix::remote::connect::Error> {
+            Ok(repo
+                .find_remote("origin")
+                .expect("present")
+                .sanitized_url_and_version(Fetch)?
+                .1)
+        }
+
+        #[test]
+        fn remote_specific_configuration_takes_precedence() -> crate::Result {
+            let mut repo = remote::repo("clone");
+            assert_eq!(version(&repo)?, Protocol::V2, "V2 is the default");
+
+            repo.config_snapshot_mut()
+                .set_raw_value("protocol", None, "version", "0")?;
+            assert_eq!(version(&repo)?, Protocol::V1, "V0 is handled like V1");
+
+            repo.config_snapshot_mut()
+                .set_raw_value("remote", Some("origin".into()), "protocolVersion", "2")?;
+            assert_eq!(
+                version(&repo)?,
+                Protocol::V2,
+                "the remote specific value overrides protocol.version"
+            );
+
+            repo.config_snapshot_mut()
+                .set_raw_value("remote", Some("origin".into()), "protocolVersion", "3")?;
+            assert!(matches!(
+                version(&repo),
+                Err(gix::remote::connect::Error::UnknownProtocol(_))
+            ));
+            Ok(())
+        }
+    }
+
+    mod with_io {
+        use gix::remote::Direction::{Fetch, Push};
+        use gix_features::progress;
+
+        use crate::remote;
+
+        #[test]
+        fn bypasses_dialing_and_protocol_permissions() -> crate::Result {
+            let repo = remote::repo("protocol_denied");
+            let remote = repo.find_remote("origin")?;
+            let mut upload_pack = std::process::Command::new("git")
+                .arg("upload-pack")
+                .arg(remote::repo_path("base"))
+                .env("GIT_PROTOCOL", "version=2")
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .spawn()?;
+            let connection = remote.to_connection_with_io(
+                Fetch,
+                upload_pack.stdout.take().expect("configured"),
+                upload_pack.stdin.take().expect("configured"),
+                progress::Discard,
+            )?;
+            let map = connection.ref_map(Default::default())?;
+            assert_eq!(
+                map.handshake.server_protocol_version,
+                gix::protocol::transport::Protocol::V2
+            );
+            assert!(
+                !map.remote_refs.is_empty(),
+                "refs were listed through the provided channels"
+            );
+            assert!(!map.mappings.is_empty());
+            upload_pack.wait()?;
+            Ok(())
+        }
+
+        #[test]
+        fn push_connections_refuse_to_fetch() -> crate::Result {
+            let repo = remote::repo("protocol_denied");
+            let remote = repo.find_remote("origin")?;
+            let connection =
+                remote.to_connection_with_io(Push, std::io::empty(), std::io::sink(), progress::Discard)?;
+            assert_eq!(connection.direction(), Push);
+            assert!(matches!(
+                connection.ref_map(Default::default()).unwrap_err(),
+                gix::remote::ref_map::Error::UnsupportedDirection { direction: Push }
+            ));
+            Ok(())
+        }
+    }
 }
@@ -246,6 +246,67 @@ mod blocking_and_async_io {
         Ok(())
     }
 
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_fails_connectivity_check_if_objects_are_missing_locally() -> crate::Result {
+        let (repo, _tmp) = repo_rw("two-origins");
+        // The remote won't send what it thinks we have, but without the alternate these objects are gone.
+        std::fs::remove_file(repo.objects.store_ref().path().join("info").join("alternates"))?;
+        let repo = gix::open_opts(repo.path(), crate::restricted())?;
+        let mut remote = repo.find_remote("changes-on-top-of-origin")?;
+        remote.replace_refspecs(Some("refs/heads/main:refs/remotes/changes/main"), Fetch)?;
+
+        let err = remote
+            .connect(Fetch, gix::progress::Discard)?
+            .prepare_fetch(Default::default())?
+            .receive(&AtomicBool::default())
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                fetch::Error::ConnectivityCheck(fetch::connectivity::Error::MissingObject { .. })
+            ),
+            "{err:?}"
+        );
+        assert!(
+            repo.try_find_reference("refs/remotes/changes/main")?.is_none(),
+            "references aren't updated if the received pack is incomplete"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "blocking-network-client")]
+    fn fetch_with_bundle_uri_fetches_everything_if_the_remote_advertises_no_bundles() -> crate::Result {
+        let (repo, _tmp) = repo_rw("two-origins");
+        let mut remote = repo.find_remote("changes-on-top-of-origin")?;
+        remote.replace_refspecs(Some("refs/heads/main:refs/remotes/changes/main"), Fetch)?;
+
+        let outcome = remote
+            .connect(Fetch, gix::progress::Discard)?
+            .prepare_fetch(Default::default())?
+            .with_bundle_uri(true)
+            .receive(&AtomicBool::default())?;
+        assert!(
+            !outcome.ref_map.handshake.capabilities.contains("bundle-uri"),
+            "the remote doesn't know about bundles"
+        );
+        assert!(
+            matches!(outcome.status, Status::Change { .. }),
+            "the pack is received as usual"
+        );
+        let tracking = repo.find_reference("refs/remotes/changes/main")?.id().detach();
+        assert_eq!(
+            Some(tracking),
+            outcome.ref_map.remote_refs.iter().find_map(|r| match r.unpack() {
+                (name, Some(id), _) if name == "refs/heads/main" => Some(id.to_owned()),
+                _ => None,
+            })
+        );
+        repo.find_object(tracking)?.peel_to_tree()?;
+        Ok(())
+    }
+
     #[maybe_async::test(
         feature = "blocking-network-client",
         async(feature = "async-network-client-async-std", async_std::test)
@@ -304,6 +365,66 @@ mod blocking_and_async_io {
         Ok(())
     }
 
+    #[maybe_async::test(
+        feature = "blocking-network-client",
+        async(feature = "async-network-client-async-std", async_std::test)
+    )]
+    async fn fetch_pack_with_write_packed_refs_only() -> crate::Result {
+        let daemon = spawn_git_daemon_if_async({
+            let mut p = repo_path("base");
+            p.pop();
+            p
+        })?;
+        let (repo, _tmp) = repo_rw("two-origins");
+        let remote = into_daemon_remote_if_async(
+            repo.find_remote("changes-on-top-of-origin")?
+                .with_fetch_tags(gix::remote::fetch::Tags::None),
+            daemon.as_ref(),
+            "clone-as-base-with-changes",
+        );
+        let outcome: gix::remote::fetch::Outcome = remote
+            .connect(Fetch, progress::Discard)
+            .await?
+            .prepare_fetch(Default::default())
+            .await?
+            .with_write_packed_refs_only(true)
+            .receive(&AtomicBool::default())
+            .await?;
+        let update_refs = match outcome.status {
+            fetch::Status::Change { update_refs, .. } => update_refs,
+            _ => unreachable!("we firmly expect changes here"),
+        };
+        assert_eq!(update_refs.edits.len(), 2);
+
+        let packed = repo
+            .refs
+            .cached_packed_buffer()?
+            .expect("packed-refs were written as part of the fetch");
+        for edit in &update_refs.edits {
+            assert!(
+                !repo.git_dir().join(gix::path::from_bstr(edit.name.as_bstr())).exists(),
+                "no loose reference is written for {}",
+                edit.name
+            );
+            let packed_ref = packed.find(edit.name.as_ref())?;
+            assert_eq!(
+                Some(packed_ref.target()),
+                edit.change
+                    .new_value()
+                    .expect("no deletion")
+                    .try_id()
+                    .map(ToOwned::to_owned),
+                "the packed reference points to the fetched object"
+            );
+            assert_eq!(
+                repo.find_reference(edit.name.as_ref())?.id(),
+                packed_ref.target(),
+                "the reference is visible through the repository"
+            );
+        }
+        Ok(())
+    }
+
     #[maybe_async::test(
         feature = "blocking-network-client",
         async(feature = "async-network-client-async-std", async_std::test)
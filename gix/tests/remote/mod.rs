@@ -70,6 +70,8 @@ pub(crate) fn cow_str(s: &str) -> Cow<str> {
 
 mod connect;
 pub(crate) mod fetch;
+#[cfg(feature = "blocking-network-client")]
+mod push;
 mod ref_map;
 mod save;
 mod name {
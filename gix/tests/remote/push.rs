@@ -0,0 +1,445 @@
+use std::{io::Write, sync::atomic::AtomicBool};
+
+use gix::remote::{
+    push,
+    push::{Lease, Options, Outcome, Signed, Status},
+    Direction,
+};
+use gix_testtools::tempfile::TempDir;
+
+fn repo_and_empty_target() -> crate::Result<(gix::Repository, gix::Repository, TempDir)> {
+    let tmp = gix_testtools::scripted_fixture_writable("make_remote_repos.sh")?;
+    let repo = gix::open_opts(tmp.path().join("clone"), crate::util::restricted())?;
+    let target = gix::init_bare(tmp.path().join("push-target"))?;
+    Ok((repo, target, tmp))
+}
+
+fn push(repo: &gix::Repository, target: &gix::Repository, specs: &[&str], options: Options) -> crate::Result<Outcome> {
+    Ok(repo
+        .remote_at(target.git_dir())?
+        .with_refspecs(specs.iter().copied(), Direction::Push)?
+        .push(gix::progress::Discard, &AtomicBool::default(), options)?)
+}
+
+fn statuses(outcome: &Outcome) -> Vec<Status> {
+    outcome.updates.iter().map(|update| update.status.clone()).collect()
+}
+
+fn target_id(target: &gix::Repository, name: &str) -> crate::Result<Option<gix::ObjectId>> {
+    Ok(target
+        .try_find_reference(name)?
+        .map(|mut reference| reference.peel_to_id_in_place().map(|id| id.detach()))
+        .transpose()?)
+}
+
+/// Add commits with similar big files to `repo` and pack them, so that its packs contain deltas.
+fn add_deltas(repo: &gix::Repository) -> crate::Result<gix::Repository> {
+    let status = std::process::Command::new("sh")
+        .args([
+            "-c",
+            "for i in 1 2 3; do seq 1000 > big && echo $i >> big && git add big && git commit -qm big-$i; done && git repack -adfq",
+        ])
+        .env("GIT_AUTHOR_NAME", "a")
+        .env("GIT_AUTHOR_EMAIL", "a@example.com")
+        .env("GIT_COMMITTER_NAME", "a")
+        .env("GIT_COMMITTER_EMAIL", "a@example.com")
+        .current_dir(repo.work_dir().expect("non-bare"))
+        .status()?;
+    assert!(status.success());
+    Ok(gix::open_opts(repo.git_dir(), crate::util::restricted())?)
+}
+
+/// Return the headers of all entries in all packs of `repo`.
+fn pack_entry_headers(repo: &gix::Repository) -> crate::Result<Vec<gix::odb::pack::data::entry::Header>> {
+    let mut headers = Vec::new();
+    for entry in std::fs::read_dir(repo.objects.store_ref().path().join("pack"))? {
+        let path = entry?.path();
+        if path.extension().map_or(true, |ext| ext != "idx") {
+            continue;
+        }
+        let bundle = gix::odb::pack::Bundle::at(&path, repo.object_hash())?;
+        headers.extend(
+            bundle
+                .index
+                .iter()
+                .map(|entry| bundle.pack.entry(entry.pack_offset).header),
+        );
+    }
+    Ok(headers)
+}
+
+#[test]
+fn all_objects_are_sent_to_an_empty_repository_and_tracking_branches_are_updated() -> crate::Result {
+    let (repo, target, _tmp) = repo_and_empty_target()?;
+    let outcome = repo
+        .remote_at(target.git_dir())?
+        .with_refspecs(Some("refs/heads/*:refs/remotes/target/*"), Direction::Fetch)?
+        .with_refspecs(["main", "refs/tags/b-tag"], Direction::Push)?
+        .push(gix::progress::Discard, &AtomicBool::default(), Default::default())?;
+
+    let main = repo.head_id()?.detach();
+    assert_eq!(statuses(&outcome), [Status::Ok, Status::Ok]);
+    assert_eq!(outcome.updates[0].remote, "refs/heads/main");
+    assert_eq!(
+        outcome.updates[0].local.as_ref().expect("from a reference"),
+        "refs/heads/main"
+    );
+    assert_eq!(outcome.updates[0].old, None);
+    assert_eq!(outcome.updates[0].new, Some(main));
+    assert_eq!(outcome.updates[1].remote, "refs/tags/b-tag");
+    assert!(outcome.num_objects.expect("a pack was sent") > 0);
+
+    assert_eq!(target_id(&target, "refs/heads/main")?, Some(main));
+    assert_eq!(
+        target.rev_walk(Some(main)).all()?.count(),
+        repo.rev_walk(Some(main)).all()?.count(),
+        "the entire history is present"
+    );
+    target.find_object(main)?.peel_to_tree()?;
+    assert_eq!(
+        target_id(&target, "refs/tags/b-tag")?,
+        target_id(&repo, "refs/tags/b-tag")?,
+        "annotated tags are sent along with their commits"
+    );
+    assert_eq!(
+        target_id(&repo, "refs/remotes/target/main")?,
+        Some(main),
+        "remote tracking branches are updated according to the fetch ref-specs"
+    );
+
+    let outcome = push(&repo, &target, &["main"], Default::default())?;
+    assert_eq!(statuses(&outcome), [Status::UpToDate]);
+    assert_eq!(outcome.num_objects, None, "nothing to send");
+    Ok(())
+}
+
+#[test]
+fn dry_run_changes_nothing() -> crate::Result {
+    let (repo, target, _tmp) = repo_and_empty_target()?;
+    let outcome = push(
+        &repo,
+        &target,
+        &["main"],
+        Options {
+            dry_run: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(statuses(&outcome), [Status::Ok]);
+    assert_eq!(outcome.num_objects, None);
+    assert_eq!(target_id(&target, "refs/heads/main")?, None);
+    Ok(())
+}
+
+#[test]
+fn non_fast_forward_updates_need_to_be_forced_or_leased() -> crate::Result {
+    let (repo, target, _tmp) = repo_and_empty_target()?;
+    push(&repo, &target, &["main"], Default::default())?;
+    let main = repo.head_id()?.detach();
+    let parent = repo.rev_parse_single("main~1")?.detach();
+    let rewind = format!("{parent}:refs/heads/main");
+
+    let outcome = push(&repo, &target, &[&rewind], Default::default())?;
+    assert_eq!(statuses(&outcome), [Status::RejectedNonFastForward]);
+    assert_eq!(outcome.updates[0].old, Some(main));
+    assert_eq!(target_id(&target, "refs/heads/main")?, Some(main));
+
+    let lease = |expected| Options {
+        force_with_lease: vec![Lease {
+            name: "refs/heads/main".into(),
+            expected: Some(expected),
+        }],
+        ..Default::default()
+    };
+    let outcome = push(&repo, &target, &[&rewind], lease(parent))?;
+    assert_eq!(statuses(&outcome), [Status::RejectedStale]);
+    assert_eq!(target_id(&target, "refs/heads/main")?, Some(main));
+
+    let outcome = push(&repo, &target, &[&rewind], lease(main))?;
+    assert_eq!(statuses(&outcome), [Status::Ok]);
+    assert!(outcome.updates[0].forced);
+    assert_eq!(target_id(&target, "refs/heads/main")?, Some(parent));
+
+    let outcome = push(&repo, &target, &["main"], Default::default())?;
+    assert_eq!(statuses(&outcome), [Status::Ok], "fast-forwards are fine");
+    assert_eq!(target_id(&target, "refs/heads/main")?, Some(main));
+
+    let outcome = push(&repo, &target, &[&format!("+{rewind}")], Default::default())?;
+    assert_eq!(statuses(&outcome), [Status::Ok]);
+    assert_eq!(target_id(&target, "refs/heads/main")?, Some(parent));
+    Ok(())
+}
+
+#[test]
+fn delete() -> crate::Result {
+    let (repo, target, _tmp) = repo_and_empty_target()?;
+    push(&repo, &target, &["main", "main:refs/heads/other"], Default::default())?;
+
+    let outcome = push(&repo, &target, &[":other", ":refs/heads/missing"], Default::default())?;
+    assert_eq!(statuses(&outcome), [Status::Ok, Status::RejectedNoSuchRef]);
+    assert_eq!(
+        outcome.updates[0].remote, "refs/heads/other",
+        "names are qualified by the remote"
+    );
+    assert_eq!(outcome.updates[0].new, None);
+    assert_eq!(outcome.num_objects, None);
+    assert_eq!(target_id(&target, "refs/heads/other")?, None);
+    assert!(
+        target_id(&target, "refs/heads/main")?.is_some(),
+        "the current branch of the remote is left alone as it can't be deleted"
+    );
+    Ok(())
+}
+
+#[test]
+fn atomic_pushes_perform_no_update_if_one_is_rejected() -> crate::Result {
+    let (repo, target, _tmp) = repo_and_empty_target()?;
+    push(&repo, &target, &["main"], Default::default())?;
+    let parent = repo.rev_parse_single("main~1")?.detach();
+    let rewind = format!("{parent}:refs/heads/main");
+
+    let atomic = Options {
+        atomic: true,
+        ..Default::default()
+    };
+    let outcome = push(&repo, &target, &[&rewind, "main:refs/heads/other"], atomic.clone())?;
+    assert_eq!(
+        statuses(&outcome),
+        [Status::RejectedNonFastForward, Status::RejectedAtomic]
+    );
+    assert_eq!(target_id(&target, "refs/heads/other")?, None);
+
+    let outcome = push(&repo, &target, &["main:refs/heads/other"], atomic)?;
+    assert_eq!(statuses(&outcome), [Status::Ok]);
+    assert_eq!(target_id(&target, "refs/heads/other")?, Some(repo.head_id()?.detach()));
+    Ok(())
+}
+
+#[test]
+fn push_options_are_transmitted_if_supported() -> crate::Result {
+    let (repo, target, _tmp) = repo_and_empty_target()?;
+    let options = Options {
+        push_options: vec!["ci.skip".into()],
+        ..Default::default()
+    };
+    let err = repo
+        .remote_at(target.git_dir())?
+        .with_refspecs(Some("main"), Direction::Push)?
+        .push(gix::progress::Discard, &AtomicBool::default(), options.clone())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        push::Error::PushOptions(gix_protocol::push_options::Error::Unsupported)
+    ));
+    let err = repo
+        .remote_at(target.git_dir())?
+        .with_refspecs(Some("main"), Direction::Push)?
+        .push(
+            gix::progress::Discard,
+            &AtomicBool::default(),
+            Options {
+                dry_run: true,
+                ..options.clone()
+            },
+        )
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            push::Error::PushOptions(gix_protocol::push_options::Error::Unsupported)
+        ),
+        "dry runs fail as well"
+    );
+
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(target.git_dir().join("config"))?
+        .write_all(b"[receive]\n\tadvertisePushOptions = true\n")?;
+    let err = repo
+        .remote_at(target.git_dir())?
+        .with_refspecs(Some("main"), Direction::Push)?
+        .push(
+            gix::progress::Discard,
+            &AtomicBool::default(),
+            Options {
+                push_options: vec!["two\nlines".into()],
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        push::Error::PushOptions(gix_protocol::push_options::Error::Newline { .. })
+    ));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let hooks = target.git_dir().join("hooks");
+        std::fs::create_dir_all(&hooks)?;
+        let hook = hooks.join("pre-receive");
+        std::fs::write(
+            &hook,
+            "#!/bin/sh\necho \"$GIT_PUSH_OPTION_COUNT $GIT_PUSH_OPTION_0\" > push-options\n",
+        )?;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+    }
+    let outcome = push(&repo, &target, &["main"], options)?;
+    assert_eq!(statuses(&outcome), [Status::Ok]);
+    #[cfg(unix)]
+    assert_eq!(
+        std::fs::read_to_string(target.git_dir().join("push-options"))?,
+        "1 ci.skip\n",
+        "hooks of the remote can see the options"
+    );
+    Ok(())
+}
+
+#[test]
+fn signed_pushes_need_server_support_unless_only_signed_if_asked() -> crate::Result {
+    let (repo, target, _tmp) = repo_and_empty_target()?;
+    let err = repo
+        .remote_at(target.git_dir())?
+        .with_refspecs(Some("main"), Direction::Push)?
+        .push(
+            gix::progress::Discard,
+            &AtomicBool::default(),
+            Options {
+                signed: Signed::Yes,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        push::Error::MissingServerFeature {
+            feature: "push-cert",
+            ..
+        }
+    ));
+
+    let outcome = push(
+        &repo,
+        &target,
+        &["main"],
+        Options {
+            signed: Signed::IfAsked,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(statuses(&outcome), [Status::Ok], "the remote didn't ask");
+    Ok(())
+}
+
+#[test]
+fn deltas_refer_to_their_base_by_offset_if_the_remote_supports_it() -> crate::Result {
+    use gix::odb::pack::data::entry::Header;
+    let (repo, target, _tmp) = repo_and_empty_target()?;
+    let repo = add_deltas(&repo)?;
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(target.git_dir().join("config"))?
+        .write_all(b"[receive]\n\tunpackLimit = 1\n")?;
+
+    let outcome = push(&repo, &target, &["main"], Default::default())?;
+    assert!(outcome.handshake.capabilities.contains("ofs-delta"));
+    assert_eq!(statuses(&outcome), [Status::Ok]);
+    let headers = pack_entry_headers(&target)?;
+    assert!(
+        headers.iter().any(|header| matches!(header, Header::OfsDelta { .. })),
+        "deltas refer to their base with the smaller offset"
+    );
+    assert!(!headers.iter().any(|header| matches!(header, Header::RefDelta { .. })));
+    Ok(())
+}
+
+#[test]
+fn deltas_refer_to_their_base_by_id_if_the_remote_does_not_support_offsets() -> crate::Result {
+    use gix::odb::pack::data::entry::Header;
+    let (repo, target, _tmp) = repo_and_empty_target()?;
+    let repo = add_deltas(&repo)?;
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(target.git_dir().join("config"))?
+        .write_all(b"[repack]\n\tuseDeltaBaseOffset = false\n[receive]\n\tunpackLimit = 1\n")?;
+
+    let outcome = push(&repo, &target, &["main"], Default::default())?;
+    assert!(
+        !outcome.handshake.capabilities.contains("ofs-delta"),
+        "the remote doesn't advertise offset deltas"
+    );
+    assert_eq!(statuses(&outcome), [Status::Ok]);
+    let headers = pack_entry_headers(&target)?;
+    assert!(
+        headers.iter().any(|header| matches!(header, Header::RefDelta { .. })),
+        "deltas are sent"
+    );
+    assert!(
+        !headers.iter().any(|header| matches!(header, Header::OfsDelta { .. })),
+        "but none refers to its base by offset"
+    );
+    let main = repo.head_id()?.detach();
+    assert_eq!(target_id(&target, "refs/heads/main")?, Some(main));
+    target.find_object(main)?.peel_to_tree()?;
+    Ok(())
+}
+
+#[cfg(unix)]
+mod pre_push_hook {
+    use std::os::unix::fs::PermissionsExt;
+
+    use gix::remote::push::{Error, Options, Status};
+
+    use super::{repo_and_empty_target, statuses, target_id};
+
+    fn install(repo: &gix::Repository, script: &str) -> crate::Result {
+        let hooks = repo.git_dir().join("hooks");
+        std::fs::create_dir_all(&hooks)?;
+        let hook = hooks.join("pre-push");
+        std::fs::write(&hook, format!("#!/bin/sh\n{script}"))?;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755))?;
+        Ok(())
+    }
+
+    #[test]
+    fn receives_remote_and_updates() -> crate::Result {
+        let (repo, target, _tmp) = repo_and_empty_target()?;
+        install(&repo, "echo \"$1 $2\" > pre-push-input\ncat >> pre-push-input\n")?;
+        let outcome = super::push(&repo, &target, &["main"], Default::default())?;
+        assert_eq!(statuses(&outcome), [Status::Ok]);
+
+        let url = target.git_dir().to_str().expect("valid UTF-8");
+        let main = repo.head_id()?;
+        let null = gix::ObjectId::null(repo.object_hash());
+        assert_eq!(
+            std::fs::read_to_string(repo.work_dir().expect("non-bare").join("pre-push-input"))?,
+            format!("{url} {url}\nrefs/heads/main {main} refs/heads/main {null}\n"),
+            "unnamed remotes are passed by url, and each update is a line on stdin"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn failure_aborts_the_push_unless_verification_is_disabled() -> crate::Result {
+        let (repo, target, _tmp) = repo_and_empty_target()?;
+        install(&repo, "exit 1\n")?;
+        let err = super::push(&repo, &target, &["main"], Default::default()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::Hook(gix::hook::run::Error::Failed { .. }))
+        ));
+        assert_eq!(target_id(&target, "refs/heads/main")?, None, "nothing was pushed");
+
+        let outcome = super::push(
+            &repo,
+            &target,
+            &["main"],
+            Options {
+                no_verify: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(statuses(&outcome), [Status::Ok]);
+        assert_eq!(target_id(&target, "refs/heads/main")?, Some(repo.head_id()?.detach()));
+        Ok(())
+    }
+}
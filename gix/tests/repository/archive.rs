@@ -0,0 +1,69 @@
+use gix::archive;
+
+fn tar_with_prefix(
+    repo: &gix::Repository,
+    treeish: gix::ObjectId,
+) -> Result<(Vec<u8>, archive::Outcome), archive::Error> {
+    let mut buf = Vec::new();
+    let outcome = repo.write_archive(
+        treeish,
+        &mut buf,
+        archive::Options {
+            tree_prefix: Some("prefix/".into()),
+            ..Default::default()
+        },
+    )?;
+    Ok((buf, outcome))
+}
+
+#[test]
+fn commits_are_archived_with_their_committer_time() -> crate::Result {
+    let repo = crate::named_repo("make_bisect_history_repo.sh")?;
+    let commit = repo.rev_parse_single("c9")?.detach();
+    let (tar, outcome) = tar_with_prefix(&repo, commit)?;
+    assert_eq!(outcome.num_entries, 2, "the prefix directory and the file");
+    assert_eq!(
+        std::str::from_utf8(&tar[136..147])?,
+        format!("{:011o}", 947462400),
+        "the modification time of the first tar header is the time of the commit"
+    );
+    assert_eq!(
+        tar_with_prefix(&repo, commit)?.0,
+        tar,
+        "archives of the same commit are identical"
+    );
+    Ok(())
+}
+
+#[test]
+fn only_treeish_objects_can_be_archived() -> crate::Result {
+    let repo = crate::named_repo("make_bisect_history_repo.sh")?;
+    let blob = repo.rev_parse_single("c9:file")?.detach();
+    assert!(matches!(
+        tar_with_prefix(&repo, blob),
+        Err(archive::Error::NotATreeish {
+            kind: gix::objs::Kind::Blob,
+            ..
+        })
+    ));
+    Ok(())
+}
+
+#[test]
+fn zip_needs_output_that_can_seek() -> crate::Result {
+    let repo = crate::named_repo("make_bisect_history_repo.sh")?;
+    let commit = repo.rev_parse_single("c9")?.detach();
+    let options = archive::Options {
+        format: archive::Format::Zip {
+            compression_level: None,
+        },
+        ..Default::default()
+    };
+    assert!(matches!(
+        repo.write_archive(commit, &mut Vec::new(), options.clone()),
+        Err(archive::Error::Write(gix_archive::write::Error::ZipWithoutSeek))
+    ));
+    let outcome = repo.write_archive_seek(commit, std::io::Cursor::new(Vec::new()), options)?;
+    assert_eq!(outcome.num_entries, 1);
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use gix::bisect::{Error, Mark, Next};
+
+fn id(repo: &gix::Repository, rev: &str) -> gix::ObjectId {
+    repo.rev_parse_single(rev).expect("tag exists").detach()
+}
+
+#[test]
+fn marks_are_required_before_candidates_are_computed() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_bisect_history_repo.sh")?;
+    let bisect = repo.bisect();
+    assert!(matches!(bisect.next(), Err(Error::NotInProgress)));
+
+    bisect.start()?;
+    assert_eq!(repo.state(), Some(gix::state::InProgress::Bisect));
+    assert!(matches!(bisect.start(), Err(Error::AlreadyInProgress)));
+    assert_eq!(bisect.next()?, Next::NeedsBad);
+    assert_eq!(bisect.mark(id(&repo, "c9"), Mark::Bad)?, Next::NeedsGood);
+    Ok(())
+}
+
+#[test]
+fn candidates_halve_the_remaining_commits_until_the_first_bad_one_is_found() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_bisect_history_repo.sh")?;
+    let first_bad = 3;
+    let bisect = repo.bisect();
+    bisect.start()?;
+    bisect.mark(id(&repo, "c9"), Mark::Bad)?;
+    let mut next = bisect.mark(id(&repo, "c0"), Mark::Good)?;
+
+    let mut tested = Vec::new();
+    while let Next::Candidate {
+        id: candidate,
+        num_remaining,
+    } = next
+    {
+        let n = (0..10)
+            .find(|n| id(&repo, &format!("c{n}")) == candidate)
+            .expect("candidates are part of the history");
+        tested.push((n, num_remaining));
+        next = bisect.mark(candidate, if n < first_bad { Mark::Good } else { Mark::Bad })?;
+    }
+    assert_eq!(tested, [(5, 9), (3, 5), (2, 3)]);
+    assert_eq!(next, Next::FirstBad(id(&repo, "c3")));
+
+    let log = std::fs::read_to_string(repo.path().join("BISECT_LOG"))?;
+    assert!(log.starts_with("git bisect start\n# bad: ["));
+    assert!(log.ends_with(&format!("] c2\ngit bisect good {}\n", id(&repo, "c2"))));
+    Ok(())
+}
+
+#[test]
+fn only_skipped_commits_left() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_bisect_history_repo.sh")?;
+    let bisect = repo.bisect();
+    bisect.start()?;
+    bisect.mark(id(&repo, "c9"), Mark::Bad)?;
+    assert_eq!(
+        bisect.mark(id(&repo, "c7"), Mark::Good)?,
+        Next::Candidate {
+            id: id(&repo, "c8"),
+            num_remaining: 2
+        }
+    );
+    assert_eq!(
+        bisect.mark(id(&repo, "c8"), Mark::Skip)?,
+        Next::OnlySkippedLeft {
+            candidates: vec![id(&repo, "c9"), id(&repo, "c8")]
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn bad_commits_must_not_be_ancestors_of_good_ones() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_bisect_history_repo.sh")?;
+    let bisect = repo.bisect();
+    bisect.start()?;
+    bisect.mark(id(&repo, "c2"), Mark::Bad)?;
+    assert!(matches!(
+        bisect.mark(id(&repo, "c5"), Mark::Good),
+        Err(Error::BadIsAncestorOfGood { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn reset_removes_all_state_and_returns_the_original_head() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_bisect_history_repo.sh")?;
+    let head_name = repo.head_name()?.expect("on a branch");
+    let bisect = repo.bisect();
+    bisect.start()?;
+    bisect.mark(id(&repo, "c9"), Mark::Bad)?;
+    bisect.mark(id(&repo, "c0"), Mark::Good)?;
+    bisect.mark(id(&repo, "c5"), Mark::Skip)?;
+
+    assert_eq!(bisect.reset()?, head_name.shorten());
+    assert!(!bisect.is_in_progress());
+    assert_eq!(repo.state(), None);
+    assert_eq!(repo.references()?.prefixed("refs/bisect/")?.count(), 0);
+    assert!(matches!(bisect.reset(), Err(Error::NotInProgress)));
+    Ok(())
+}
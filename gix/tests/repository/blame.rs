@@ -0,0 +1,79 @@
+use gix::blame;
+
+use crate::util::named_repo;
+
+fn id(repo: &gix::Repository, rev: &str) -> gix::ObjectId {
+    repo.rev_parse_single(rev).expect("tag exists").detach()
+}
+
+/// The commit, the lines in the blamed file and the lines in the file of that commit.
+type Entry = (gix::ObjectId, std::ops::Range<u32>, std::ops::Range<u32>);
+
+fn entries(repo: &gix::Repository, options: blame::Options) -> crate::Result<Vec<Entry>> {
+    let outcome = repo.blame(repo.head_id()?, "file", options)?;
+    assert_eq!(outcome.blob, b"0\n1\n  TWO\n3\n4\n");
+    Ok(outcome
+        .entries
+        .into_iter()
+        .map(|e| (e.commit_id, e.range_in_blamed_file, e.range_in_source_file))
+        .collect())
+}
+
+#[test]
+fn lines_are_attributed_to_the_commits_that_introduced_them() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    assert_eq!(
+        entries(&repo, Default::default())?,
+        vec![
+            (id(&repo, "side"), 0..1, 0..1),
+            (id(&repo, "c1"), 1..2, 0..1),
+            (id(&repo, "c4"), 2..3, 2..3),
+            (id(&repo, "c1"), 3..4, 2..3),
+            (id(&repo, "c2"), 4..5, 3..4),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn whitespace_changes_can_be_ignored() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    assert_eq!(
+        entries(
+            &repo,
+            blame::Options {
+                ignore_whitespace: true
+            }
+        )?,
+        vec![
+            (id(&repo, "side"), 0..1, 0..1),
+            (id(&repo, "c1"), 1..2, 0..1),
+            (id(&repo, "c3"), 2..3, 1..2),
+            (id(&repo, "c1"), 3..4, 2..3),
+            (id(&repo, "c2"), 4..5, 3..4),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn incremental_entries_cover_all_lines() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let mut lines = Vec::new();
+    repo.blame_incremental(repo.head_id()?, "file", Default::default(), |entry| {
+        lines.extend(entry.range_in_blamed_file.clone())
+    })?;
+    lines.sort_unstable();
+    assert_eq!(lines, (0..5).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn missing_paths_are_an_error() -> crate::Result {
+    let repo = named_repo("make_blame_repo.sh")?;
+    let err = repo
+        .blame(id(&repo, "c1"), "does-not-exist", Default::default())
+        .unwrap_err();
+    assert!(matches!(err, blame::Error::PathNotFound { .. }));
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::sync::atomic::AtomicBool;
+
+use gix::bundle::{create, unbundle};
+
+fn id(repo: &gix::Repository, rev: &str) -> gix::ObjectId {
+    repo.rev_parse_single(rev).expect("tag exists").detach()
+}
+
+#[allow(clippy::result_large_err)]
+fn create_bundle(repo: &gix::Repository, specs: &[&str]) -> Result<(Vec<u8>, create::Outcome), create::Error> {
+    let mut buf = Vec::new();
+    let outcome = repo.create_bundle(
+        specs.iter().copied(),
+        &mut buf,
+        gix::progress::Discard,
+        &AtomicBool::default(),
+        Default::default(),
+    )?;
+    Ok((buf, outcome))
+}
+
+#[test]
+fn create_with_prerequisites_and_unbundle() -> crate::Result {
+    let repo = crate::named_repo("make_bisect_history_repo.sh")?;
+    let (bundle, outcome) = create_bundle(&repo, &["c5..c9"])?;
+    assert_eq!(outcome.header.refs.len(), 1);
+    assert_eq!(outcome.header.refs[0].name, "refs/tags/c9");
+    assert_eq!(outcome.header.refs[0].id, id(&repo, "c9"));
+    assert_eq!(outcome.header.prerequisites.len(), 1);
+    assert_eq!(outcome.header.prerequisites[0].id, id(&repo, "c5"));
+    assert_eq!(outcome.header.prerequisites[0].comment.as_ref().expect("subject"), "c5");
+    assert_eq!(
+        outcome.num_objects,
+        4 * 3,
+        "a commit, tree and blob for each of c6 to c9"
+    );
+
+    let tmp = gix_testtools::tempfile::tempdir()?;
+    let empty = gix::init_bare(tmp.path())?;
+    assert!(matches!(
+        empty.unbundle(bundle.as_slice(), gix::progress::Discard, &AtomicBool::default()),
+        Err(unbundle::Error::MissingPrerequisite { id: missing }) if missing == id(&repo, "c5")
+    ));
+
+    let (full_bundle, outcome) = create_bundle(&repo, &["c9"])?;
+    assert!(outcome.header.prerequisites.is_empty());
+    assert_eq!(outcome.num_objects, 10 * 3);
+    let unbundled = empty.unbundle(full_bundle.as_slice(), gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(unbundled.header, outcome.header);
+    assert_eq!(unbundled.write_pack_bundle.index.num_objects, 30);
+    assert!(
+        empty.find_object(id(&repo, "c9")).is_ok(),
+        "the objects are now present"
+    );
+
+    empty.unbundle(bundle.as_slice(), gix::progress::Discard, &AtomicBool::default())?;
+    Ok(())
+}
+
+#[test]
+fn included_objects_must_be_named_by_a_reference() -> crate::Result {
+    let repo = crate::named_repo("make_bisect_history_repo.sh")?;
+    let hex = id(&repo, "c9").to_string();
+    assert!(matches!(
+        create_bundle(&repo, &[hex.as_str()]),
+        Err(create::Error::NotAReference { .. })
+    ));
+    assert!(matches!(create_bundle(&repo, &["^c9"]), Err(create::Error::Empty)));
+    Ok(())
+}
+
+#[test]
+fn create_with_bitmaps_yields_the_same_objects() -> crate::Result {
+    let (repo, _tmp) = crate::util::repo_rw("make_bisect_history_repo.sh")?;
+    let outcome = repo.maintenance(
+        gix::maintenance::Options {
+            all_into_one: true,
+            delete_redundant: true,
+            write_bitmap_index: true,
+            ..Default::default()
+        },
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert!(outcome.bitmap.is_some(), "all objects are packed");
+    assert_eq!(create_bundle(&repo, &["c5..c9"])?.1.num_objects, 4 * 3);
+    assert_eq!(create_bundle(&repo, &["c9"])?.1.num_objects, 10 * 3);
+
+    let work_dir = repo.work_dir().expect("non-bare");
+    for args in [&["commit", "-q", "--allow-empty", "-m", "c10"][..], &["tag", "c10"]] {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(work_dir)
+            .env("GIT_AUTHOR_NAME", "author")
+            .env("GIT_AUTHOR_EMAIL", "author@example.com")
+            .env("GIT_COMMITTER_NAME", "committer")
+            .env("GIT_COMMITTER_EMAIL", "committer@example.com")
+            .status()?;
+        assert!(status.success());
+    }
+    let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+    assert_eq!(
+        create_bundle(&repo, &["c9..c10"])?.1.num_objects,
+        1,
+        "only the new commit is loose, its tree is in the bitmapped pack"
+    );
+    assert_eq!(
+        create_bundle(&repo, &["c10"])?.1.num_objects,
+        10 * 3 + 1,
+        "objects reachable through bitmaps and loose objects are combined"
+    );
+    Ok(())
+}
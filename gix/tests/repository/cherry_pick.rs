@@ -0,0 +1,202 @@
+use gix::{cherry_pick, prelude::ObjectIdExt};
+
+use crate::util::{hex_to_id, repo_rw};
+
+fn id(repo: &gix::Repository, rev: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(rev)?.detach())
+}
+
+fn committed(outcome: cherry_pick::Outcome<'_>) -> gix::Id<'_> {
+    match outcome {
+        cherry_pick::Outcome::Committed(id) => id,
+        other => panic!("expected a commit, got {other:?}"),
+    }
+}
+
+fn index_entries(repo: &gix::Repository) -> crate::Result<Vec<(String, gix::ObjectId, u32)>> {
+    let index = repo.open_index()?;
+    Ok(index
+        .entries()
+        .iter()
+        .map(|e| (e.path(&index).to_string(), e.id, e.stage()))
+        .collect())
+}
+
+fn tree_entries(repo: &gix::Repository, tree: gix::ObjectId) -> crate::Result<Vec<(String, gix::ObjectId, u32)>> {
+    let state = gix::index::State::from_tree(&tree, |oid, buf| {
+        use gix::odb::FindExt;
+        repo.objects.find_tree_iter(oid, buf).ok()
+    })?;
+    Ok(state
+        .entries()
+        .iter()
+        .map(|e| (e.path(&state).to_string(), e.id, e.stage()))
+        .collect())
+}
+
+fn read(repo: &gix::Repository, path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(repo.work_dir().expect("non-bare").join(path))
+}
+
+#[test]
+fn clean_changes_are_committed_with_the_original_author_and_message() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_pick_and_revert_repo.sh")?;
+    let head = id(&repo, "HEAD")?;
+    let picked = id(&repo, "other~2")?;
+    let new_id = committed(repo.cherry_pick(
+        picked,
+        cherry_pick::Options {
+            record_origin: true,
+            ..Default::default()
+        },
+    )?);
+
+    let commit = new_id.object()?.into_commit();
+    assert_eq!(
+        commit.tree_id()?,
+        hex_to_id("5032ab6dfdd7d5838b759ab9f334f157ee73c3f0"),
+        "the same tree as `git cherry-pick` writes"
+    );
+    assert_eq!(commit.parent_ids().collect::<Vec<_>>(), [head]);
+    assert_eq!(commit.author()?.name, "Other Author", "the author is kept");
+    assert_eq!(commit.committer()?.name, "gitoxide");
+    assert_eq!(
+        commit.message_raw()?,
+        format!(
+            "add a file\n\nSigned-off-by: Other Author <other@example.com>\n(cherry picked from commit {picked})\n"
+        ),
+        "the origin is appended to existing trailers"
+    );
+    assert_eq!(repo.head_id()?, new_id);
+    assert_eq!(read(&repo, "added")?, "added\n");
+    assert_eq!(
+        index_entries(&repo)?,
+        tree_entries(&repo, commit.tree_id()?.detach())?,
+        "the index matches the new commit"
+    );
+    assert_eq!(repo.state(), None);
+
+    let new_id = committed(repo.cherry_pick(id(&repo, "other~1")?, Default::default())?);
+    let commit = new_id.object()?.into_commit();
+    assert_eq!(
+        commit.tree_id()?,
+        hex_to_id("245b144c7e0563869b348dcdeb8b1b3b638efbe3"),
+        "removals are applied as well"
+    );
+    assert_eq!(commit.message_raw()?, "remove a directory\n");
+    assert!(
+        !repo.work_dir().expect("non-bare").join("dir").exists(),
+        "the file and its empty directory are gone"
+    );
+    assert_eq!(index_entries(&repo)?, tree_entries(&repo, commit.tree_id()?.detach())?);
+    Ok(())
+}
+
+#[test]
+fn reverting_commits_the_inverse_changes() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_pick_and_revert_repo.sh")?;
+    let reverted = id(&repo, "main~2")?;
+    let new_id = committed(repo.revert(reverted, Default::default())?);
+
+    let commit = new_id.object()?.into_commit();
+    assert_eq!(
+        commit.tree_id()?,
+        hex_to_id("d88de6d37db9ade7edf7d0ff8c3de56efd3a831e"),
+        "the same tree as `git revert` writes"
+    );
+    assert_eq!(commit.author()?.name, "gitoxide", "the reverting user is the author");
+    assert_eq!(
+        commit.message_raw()?,
+        format!("Revert \"spell out one\"\n\nThis reverts commit {reverted}.\n")
+    );
+    assert_eq!(read(&repo, "numbers")?.lines().next(), Some("1"));
+    Ok(())
+}
+
+#[test]
+fn changes_already_present_in_head_are_not_committed() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_pick_and_revert_repo.sh")?;
+    let head = id(&repo, "HEAD")?;
+    let outcome = repo.cherry_pick(id(&repo, "other~3")?, Default::default())?;
+    assert!(matches!(outcome, cherry_pick::Outcome::Empty));
+    assert_eq!(repo.head_id()?, head);
+    Ok(())
+}
+
+#[test]
+fn conflicts_are_left_for_resolution_with_git() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_pick_and_revert_repo.sh")?;
+    let head = id(&repo, "HEAD")?;
+    let picked = id(&repo, "other")?;
+    let conflicts = match repo.cherry_pick(picked, Default::default())? {
+        cherry_pick::Outcome::Conflicts(conflicts) => conflicts,
+        other => panic!("expected conflicts, got {other:?}"),
+    };
+    assert_eq!(
+        conflicts.iter().map(|c| c.path.to_string()).collect::<Vec<_>>(),
+        ["file"]
+    );
+    assert_eq!(repo.head_id()?, head, "nothing was committed");
+    assert_eq!(repo.state(), Some(gix::state::InProgress::CherryPick));
+    assert_eq!(
+        std::fs::read_to_string(repo.git_dir().join("CHERRY_PICK_HEAD"))?,
+        format!("{picked}\n")
+    );
+    assert_eq!(
+        std::fs::read_to_string(repo.git_dir().join("MERGE_MSG"))?,
+        "change the file\n\n# Conflicts:\n#\tfile\n"
+    );
+    assert_eq!(
+        read(&repo, "file")?,
+        format!(
+            "<<<<<<< HEAD\nmain\n=======\nother\n>>>>>>> {} (change the file)\n",
+            picked.attach(&repo).shorten_or_id()
+        ),
+        "the labels are the ones git uses"
+    );
+    assert_eq!(
+        index_entries(&repo)?,
+        [
+            ("dir/removed", hex_to_id("df967b96a579e45a18b8251732d16804b2e56a55"), 0),
+            ("file", hex_to_id("df967b96a579e45a18b8251732d16804b2e56a55"), 1),
+            ("file", hex_to_id("ba2906d0666cf726c7eaadd2cd3db615dedfdf3a"), 2),
+            ("file", hex_to_id("e45c9c2666d44e0327c1f9c239a74c508336053e"), 3),
+            ("numbers", hex_to_id("43300293c363f1828f8ac65b17dcd83db423aeaf"), 0),
+        ]
+        .map(|(path, id, stage)| (path.to_owned(), id, stage)),
+        "the same index as `git cherry-pick` leaves"
+    );
+
+    assert!(
+        matches!(
+            repo.cherry_pick(id(&repo, "other~2")?, Default::default()),
+            Err(cherry_pick::Error::InProgress(gix::state::InProgress::CherryPick))
+        ),
+        "another operation can't be started before this one is concluded"
+    );
+    Ok(())
+}
+
+#[test]
+fn local_changes_are_not_overwritten() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_pick_and_revert_repo.sh")?;
+    let work_dir = repo.work_dir().expect("non-bare");
+    std::fs::write(work_dir.join("added"), "untracked\n")?;
+    let err = repo.cherry_pick(id(&repo, "other~2")?, Default::default()).unwrap_err();
+    assert!(matches!(
+        err,
+        cherry_pick::Error::Checkout(gix::merge::checkout::Error::UntrackedFile { ref path }) if path == "added"
+    ));
+
+    std::fs::write(work_dir.join("numbers"), "modified\n")?;
+    let head = id(&repo, "HEAD")?;
+    let err = repo.revert(id(&repo, "main~2")?, Default::default()).unwrap_err();
+    assert!(matches!(
+        err,
+        cherry_pick::Error::Checkout(gix::merge::checkout::Error::LocalChanges { ref path }) if path == "numbers"
+    ));
+    assert_eq!(read(&repo, "numbers")?, "modified\n");
+    assert_eq!(read(&repo, "added")?, "untracked\n");
+    assert_eq!(repo.head_id()?, head);
+    Ok(())
+}
@@ -54,8 +54,13 @@ mod http {
             connect_timeout,
             verbose,
             ssl_ca_info,
+            ssl_cert,
+            ssl_key,
+            ssl_verify,
             ssl_version,
             http_version,
+            cookie_file,
+            save_cookies,
             backend,
         } = http_options(&repo, None, "https://example.com/does/not/matter");
         assert_eq!(
@@ -77,6 +82,11 @@ mod http {
         assert_eq!(no_proxy, None);
         assert!(!verbose, "verbose is disabled by default");
         assert_eq!(ssl_ca_info.as_deref(), Some(std::path::Path::new("./CA.pem")));
+        assert_eq!(ssl_cert, None);
+        assert_eq!(ssl_key, None);
+        assert!(ssl_verify, "ssl verification is enabled by default");
+        assert_eq!(cookie_file, None);
+        assert!(!save_cookies, "cookies aren't saved by default");
         #[cfg(feature = "blocking-http-transport-reqwest")]
         {
             assert!(
@@ -152,6 +162,102 @@ mod http {
         assert!(opts.ssl_version.is_none(), "empty strings reset what was there");
     }
 
+    #[test]
+    fn http_cookie_file_with_url_specific_override() {
+        let repo = repo_opts("http-config", |opts| {
+            opts.config_overrides([
+                "http.https://example.com.cookieFile=./example-cookies.txt",
+                "http.cookieFile=./cookies.txt",
+                "http.saveCookies=true",
+            ])
+        });
+        let opts = http_options(&repo, None, "https://example.com/does/not/matter");
+        assert_eq!(
+            opts.cookie_file.as_deref(),
+            Some(std::path::Path::new("./example-cookies.txt")),
+            "url-specific values take precedence"
+        );
+        assert!(opts.save_cookies);
+
+        let opts = http_options(&repo, None, "https://other.com/repo");
+        assert_eq!(
+            opts.cookie_file.as_deref(),
+            Some(std::path::Path::new("./cookies.txt")),
+            "otherwise the general value is used"
+        );
+    }
+
+    #[test]
+    fn http_cookie_file_with_url_specific_path_prefixes() {
+        let repo = repo_opts("http-config", |opts| {
+            opts.config_overrides([
+                "http.https://example.com/org/repo.git.cookieFile=./repo-cookies.txt",
+                "http.https://example.com/org.cookieFile=./org-cookies.txt",
+                "http.https://user@example.com.cookieFile=./user-cookies.txt",
+            ])
+        });
+        let opts = http_options(&repo, None, "https://example.com/org/other.git");
+        assert_eq!(
+            opts.cookie_file.as_deref(),
+            Some(std::path::Path::new("./org-cookies.txt")),
+            "paths of patterns are prefixes of the url path"
+        );
+
+        let opts = http_options(&repo, None, "https://user@example.com/org/repo.git");
+        assert_eq!(
+            opts.cookie_file.as_deref(),
+            Some(std::path::Path::new("./repo-cookies.txt")),
+            "the longest matching path wins over shorter paths and patterns with user, no matter the order"
+        );
+
+        let opts = http_options(&repo, None, "https://user@example.com/organization");
+        assert_eq!(
+            opts.cookie_file.as_deref(),
+            Some(std::path::Path::new("./user-cookies.txt")),
+            "prefixes end at path component boundaries"
+        );
+    }
+
+    #[test]
+    fn http_ssl_client_certificate_and_verification_with_url_specific_overrides() {
+        let repo = repo_opts("http-config", |opts| {
+            opts.config_overrides([
+                "http.https://example.com.sslCAInfo=./example-CA.pem",
+                "http.https://example.com.sslCert=./example-cert.pem",
+                "http.https://example.com.sslKey=./example-key.pem",
+                "http.https://example.com.sslVerify=false",
+                "http.sslCert=./cert.pem",
+            ])
+        });
+        let opts = http_options(&repo, None, "https://example.com/does/not/matter");
+        assert_eq!(
+            opts.ssl_ca_info.as_deref(),
+            Some(std::path::Path::new("./example-CA.pem")),
+            "url-specific values take precedence"
+        );
+        assert_eq!(
+            opts.ssl_cert.as_deref(),
+            Some(std::path::Path::new("./example-cert.pem"))
+        );
+        assert_eq!(opts.ssl_key.as_deref(), Some(std::path::Path::new("./example-key.pem")));
+        assert!(!opts.ssl_verify);
+
+        let opts = http_options(&repo, None, "https://other.com/repo");
+        assert_eq!(opts.ssl_ca_info.as_deref(), Some(std::path::Path::new("./CA.pem")));
+        assert_eq!(opts.ssl_cert.as_deref(), Some(std::path::Path::new("./cert.pem")));
+        assert_eq!(opts.ssl_key, None);
+        assert!(opts.ssl_verify, "otherwise the general values are used");
+    }
+
+    #[test]
+    fn http_ssl_no_verify_overrides_ssl_verify() {
+        let repo = repo_opts("http-config", |opts| {
+            opts.config_overrides(["http.sslVerify=true", "gitoxide.http.sslNoVerify=true"])
+        });
+        let opts = http_options(&repo, None, "https://example.com/does/not/matter");
+        assert!(!opts.ssl_verify, "like GIT_SSL_NO_VERIFY in git");
+    }
+
     #[test]
     fn http_verbose() {
         let repo = repo("http-verbose");
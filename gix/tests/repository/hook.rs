@@ -0,0 +1,190 @@
+use crate::util::restricted;
+
+#[cfg(unix)]
+fn write_hook(dir: &std::path::Path, name: &str, script: &str) -> crate::Result {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{script}"))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[test]
+fn hooks_dir_defaults_to_the_git_dir_and_can_be_configured() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    assert_eq!(repo.hooks_dir()?, repo.common_dir().join("hooks"));
+
+    let repo = gix::open_opts(
+        repo.path(),
+        restricted().config_overrides(["core.hooksPath=custom-hooks"]),
+    )?;
+    assert_eq!(
+        repo.hooks_dir()?,
+        repo.work_dir().expect("non-bare").join("custom-hooks"),
+        "relative paths are relative to the worktree"
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_hooks_are_not_found() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    assert!(repo.hook("pre-commit")?.is_none());
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn non_executable_hooks_are_ignored() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    let hooks_dir = repo.hooks_dir()?;
+    std::fs::create_dir_all(&hooks_dir)?;
+    std::fs::write(hooks_dir.join("pre-commit"), "#!/bin/sh\n")?;
+    assert!(repo.hook("pre-commit")?.is_none());
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn hooks_receive_arguments_environment_and_input() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    write_hook(
+        &repo.hooks_dir()?,
+        "pre-push",
+        "echo \"$@ $VALUE\"\ncat\necho error >&2\n",
+    )?;
+    let outcome = repo
+        .hook("pre-push")?
+        .expect("hook exists")
+        .args(["origin", "url"])
+        .env("VALUE", "from-env")
+        .stdin("input\n")
+        .run()?;
+    assert_eq!(outcome.stdout, b"origin url from-env\ninput\n");
+    assert_eq!(outcome.stderr, b"error\n");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn hooks_run_in_the_worktree_root() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    write_hook(&repo.hooks_dir()?, "post-commit", "pwd\n")?;
+    let outcome = repo.hook("post-commit")?.expect("hook exists").run()?;
+    let cwd = String::from_utf8(outcome.stdout)?;
+    assert_eq!(
+        std::path::Path::new(cwd.trim_end()).canonicalize()?,
+        repo.work_dir().expect("non-bare").canonicalize()?
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn failures_contain_the_output_of_the_hook() -> crate::Result {
+    let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+    write_hook(
+        &repo.hooks_dir()?,
+        "pre-commit",
+        "cat >/dev/null\necho rejected >&2\nexit 3\n",
+    )?;
+    let err = repo
+        .hook("pre-commit")?
+        .expect("hook exists")
+        .stdin(vec![b'x'; 1024 * 1024])
+        .run()
+        .unwrap_err();
+    match err {
+        gix::hook::run::Error::Failed {
+            name, status, stderr, ..
+        } => {
+            assert_eq!(name, "pre-commit");
+            assert_eq!(status.code(), Some(3));
+            assert_eq!(stderr, "rejected\n");
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+mod commit {
+    use super::write_hook;
+
+    #[allow(clippy::result_large_err)]
+    fn commit_on_head(repo: &gix::Repository, message: &str) -> Result<gix::ObjectId, gix::commit::Error> {
+        let head = repo.head_commit().expect("head is a commit");
+        let tree = head.tree_id().expect("commit has tree");
+        repo.commit("HEAD", message, tree, Some(head.id)).map(gix::Id::detach)
+    }
+
+    #[test]
+    fn failing_pre_commit_hook_aborts_the_commit() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+        let previous_head = repo.head_id()?.detach();
+        write_hook(
+            &repo.hooks_dir()?,
+            "pre-commit",
+            "test -f \"$GIT_INDEX_FILE\" || exit 2\necho rejected >&2\nexit 1\n",
+        )?;
+        match commit_on_head(&repo, "message").unwrap_err() {
+            gix::commit::Error::Hook(gix::hook::run::Error::Failed {
+                name, status, stderr, ..
+            }) => {
+                assert_eq!(name, "pre-commit");
+                assert_eq!(status.code(), Some(1), "the index file is passed to the hook");
+                assert_eq!(stderr, "rejected\n");
+            }
+            err => panic!("unexpected error: {err}"),
+        }
+        assert_eq!(repo.head_id()?, previous_head, "no commit was created");
+        Ok(())
+    }
+
+    #[test]
+    fn failing_commit_msg_hook_aborts_the_commit() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+        let previous_head = repo.head_id()?.detach();
+        write_hook(&repo.hooks_dir()?, "commit-msg", "grep -q Signed-off-by \"$1\"\n")?;
+        assert!(matches!(
+            commit_on_head(&repo, "message").unwrap_err(),
+            gix::commit::Error::Hook(gix::hook::run::Error::Failed { .. })
+        ));
+        assert_eq!(repo.head_id()?, previous_head, "no commit was created");
+
+        let id = commit_on_head(&repo, "message\n\nSigned-off-by: me")?;
+        assert_eq!(repo.head_id()?, id);
+        Ok(())
+    }
+
+    #[test]
+    fn commit_msg_hook_can_change_the_message() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+        write_hook(&repo.hooks_dir()?, "commit-msg", "echo 'Change-Id: 42' >>\"$1\"\n")?;
+        let id = commit_on_head(&repo, "message\n")?;
+        assert_eq!(
+            repo.find_object(id)?.into_commit().decode()?.message,
+            "message\nChange-Id: 42\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn post_commit_hook_runs_after_the_commit_and_its_failure_is_ignored() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_basic_repo.sh")?;
+        write_hook(
+            &repo.hooks_dir()?,
+            "post-commit",
+            "git rev-parse HEAD >post-commit-head\nexit 1\n",
+        )?;
+        let id = commit_on_head(&repo, "message")?;
+        let work_dir = repo.work_dir().expect("non-bare");
+        assert_eq!(
+            std::fs::read_to_string(work_dir.join("post-commit-head"))?.trim_end(),
+            id.to_string(),
+            "the hook sees the new commit"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use std::sync::atomic::AtomicBool;
+
+use gix::maintenance::Options;
+
+use crate::util::basic_rw_repo;
+
+fn num_loose_objects(repo: &gix::Repository) -> usize {
+    gix::odb::loose::Store::at(repo.objects.store_ref().path(), repo.object_hash())
+        .iter()
+        .count()
+}
+
+fn pack_names(repo: &gix::Repository) -> std::io::Result<Vec<String>> {
+    let mut names = std::fs::read_dir(repo.objects.store_ref().path().join("pack"))?
+        .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<Vec<_>, _>>()?;
+    names.retain(|name| name.ends_with(".pack"));
+    names.sort();
+    Ok(names)
+}
+
+#[test]
+fn loose_objects_are_packed_and_pruned() -> crate::Result {
+    let (repo, _tmp) = basic_rw_repo()?;
+    assert_eq!(num_loose_objects(&repo), 6);
+    assert!(pack_names(&repo)?.is_empty());
+
+    let outcome = repo.maintenance(Options::default(), gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(outcome.pack.expect("written").index.num_objects, 6);
+    assert!(outcome.deleted_packs.is_empty());
+    assert_eq!(outcome.deleted_loose_objects, 0, "nothing is deleted by default");
+    assert_eq!(num_loose_objects(&repo), 6);
+    assert_eq!(pack_names(&repo)?.len(), 1);
+
+    let outcome = repo.maintenance(
+        Options {
+            delete_redundant: true,
+            ..Default::default()
+        },
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert!(outcome.pack.is_none(), "all loose objects are already packed");
+    assert_eq!(outcome.deleted_loose_objects, 6);
+    assert_eq!(num_loose_objects(&repo), 0);
+    assert!(
+        !repo.objects.store_ref().path().join("00").exists(),
+        "empty fan-out directories are removed"
+    );
+
+    let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+    assert_eq!(
+        repo.head_commit()?.tree()?.decode()?.entries.len(),
+        1,
+        "objects can still be found"
+    );
+    Ok(())
+}
+
+#[test]
+fn all_objects_are_packed_into_one_except_for_kept_packs() -> crate::Result {
+    let (repo, _tmp) = basic_rw_repo()?;
+    let delete_all = Options {
+        all_into_one: true,
+        delete_redundant: true,
+        ..Default::default()
+    };
+    repo.maintenance(delete_all.clone(), gix::progress::Discard, &AtomicBool::default())?;
+    let first_pack = pack_names(&repo)?;
+    assert_eq!(first_pack.len(), 1);
+
+    let new_blob = repo.write_blob("new")?.detach();
+    let outcome = repo.maintenance(delete_all.clone(), gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(
+        outcome.pack.expect("written").index.num_objects,
+        7,
+        "the objects of the previous pack and the new loose object"
+    );
+    assert_eq!(outcome.deleted_packs.len(), 1, "the previous pack is redundant");
+    assert_eq!(outcome.deleted_loose_objects, 1);
+    let second_pack = pack_names(&repo)?;
+    assert_eq!(second_pack.len(), 1);
+    assert_ne!(second_pack, first_pack);
+
+    let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+    assert_eq!(repo.find_object(new_blob)?.data, b"new");
+    assert_eq!(repo.head_commit()?.tree()?.decode()?.entries.len(), 1);
+
+    repo.write_blob("newer")?;
+    let outcome = repo.maintenance(
+        Options {
+            keep_packs: vec![second_pack[0].clone().into()],
+            ..delete_all
+        },
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert_eq!(
+        outcome.pack.expect("written").index.num_objects,
+        1,
+        "objects in kept packs aren't packed again"
+    );
+    assert!(outcome.deleted_packs.is_empty(), "kept packs are never deleted");
+    assert_eq!(pack_names(&repo)?.len(), 2);
+    assert_eq!(num_loose_objects(&repo), 0);
+    Ok(())
+}
+
+#[test]
+fn bitmaps_are_written_if_all_reachable_objects_are_packed() -> crate::Result {
+    let (repo, _tmp) = basic_rw_repo()?;
+    let with_bitmap = Options {
+        all_into_one: true,
+        delete_redundant: true,
+        write_bitmap_index: true,
+        ..Default::default()
+    };
+    let outcome = repo.maintenance(with_bitmap.clone(), gix::progress::Discard, &AtomicBool::default())?;
+    assert_eq!(
+        outcome.bitmap.expect("all objects are in the new pack").commits.len(),
+        2,
+        "with only a few commits, all of them get a bitmap"
+    );
+    let bitmap_path = outcome
+        .pack
+        .expect("written")
+        .index_path
+        .expect("written to a directory")
+        .with_extension("bitmap");
+    assert!(bitmap_path.is_file());
+
+    let output = std::process::Command::new("git")
+        .args(["rev-list", "--test-bitmap", "HEAD"])
+        .current_dir(repo.work_dir().expect("non-bare"))
+        .output()?;
+    assert!(
+        output.status.success(),
+        "git validates the bitmap against a traversal: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    repo.write_blob("new")?;
+    let outcome = repo.maintenance(
+        Options {
+            keep_packs: vec![pack_names(&repo)?[0].clone().into()],
+            ..with_bitmap
+        },
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    assert!(outcome.pack.is_some());
+    assert!(
+        outcome.bitmap.is_none(),
+        "objects reachable from references are in a kept pack, so a bitmap for the new pack would be incomplete"
+    );
+    assert!(bitmap_path.is_file(), "the bitmap of the kept pack remains");
+    Ok(())
+}
+
+#[test]
+fn commit_graphs_are_written_as_single_file_or_as_chain() -> crate::Result {
+    let (repo, _tmp) = basic_rw_repo()?;
+    let info_dir = repo.objects.store_ref().path().join("info");
+    let outcome = repo.maintenance(
+        Options {
+            commit_graph: Some(gix::maintenance::commit_graph::Options {
+                split: false,
+                changed_paths: true,
+            }),
+            ..Default::default()
+        },
+        gix::progress::Discard,
+        &AtomicBool::default(),
+    )?;
+    let commit_graph = outcome.commit_graph.expect("written");
+    assert_eq!(commit_graph.path, info_dir.join("commit-graph"));
+    assert_eq!(commit_graph.num_commits, 2);
+    assert_eq!(commit_graph.num_files, 1);
+
+    let file = gix::commitgraph::file::File::at(&commit_graph.path)?;
+    assert!(file.has_corrected_commit_dates(), "the default generation version is 2");
+    let head = repo.head_id()?.detach();
+    let filter = file
+        .bloom_filter_at(file.lookup(head).expect("present"))
+        .expect("present");
+    let settings = file.bloom_filter_settings().expect("present");
+    assert!(filter.contains(&gix::commitgraph::bloom::Key::new(b"this", settings)));
+
+    let status = std::process::Command::new("git")
+        .args(["-c", "user.name=a", "-c", "user.email=a@example.com"])
+        .args(["commit", "--allow-empty", "-qm", "c3"])
+        .current_dir(repo.work_dir().expect("non-bare"))
+        .status()?;
+    assert!(status.success());
+    let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+    let split = Options {
+        commit_graph: Some(gix::maintenance::commit_graph::Options {
+            split: true,
+            changed_paths: false,
+        }),
+        ..Default::default()
+    };
+    let outcome = repo.maintenance(split.clone(), gix::progress::Discard, &AtomicBool::default())?;
+    let commit_graph = outcome.commit_graph.expect("written");
+    assert_eq!(
+        commit_graph.num_commits, 3,
+        "the commits of the single file are written into the chain as well"
+    );
+    assert_eq!(commit_graph.num_files, 1);
+    assert!(!info_dir.join("commit-graph").exists(), "the single file is replaced");
+    let chain = std::fs::read_to_string(info_dir.join("commit-graphs/commit-graph-chain"))?;
+    assert_eq!(chain.lines().count(), 1);
+
+    let graph = gix::commitgraph::Graph::from_info_dir(&info_dir)?;
+    assert_eq!(graph.num_commits(), 3);
+    let file = gix::commitgraph::file::File::at(&commit_graph.path)?;
+    assert!(
+        file.bloom_filter_settings().is_some(),
+        "changed-path filters are kept once they were written"
+    );
+
+    let outcome = repo.maintenance(split, gix::progress::Discard, &AtomicBool::default())?;
+    assert!(outcome.commit_graph.is_none(), "there is nothing new to write");
+
+    let output = std::process::Command::new("git")
+        .args(["commit-graph", "verify"])
+        .current_dir(repo.work_dir().expect("non-bare"))
+        .output()?;
+    assert!(
+        output.status.success(),
+        "git can read and verify the chain: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(())
+}
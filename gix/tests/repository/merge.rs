@@ -0,0 +1,109 @@
+use gix::merge;
+
+use crate::util::{hex_to_id, repo_rw};
+
+fn merge(repo: &gix::Repository, ours: &str, theirs: &str) -> crate::Result<merge::Outcome> {
+    let id = |rev: &str| repo.rev_parse_single(rev).map(|id| id.detach());
+    Ok(repo.merge_trees(
+        id("base")?,
+        id(ours)?,
+        id(theirs)?,
+        merge::Options {
+            our_label: Some(ours.into()),
+            their_label: Some(theirs.into()),
+            ..Default::default()
+        },
+    )?)
+}
+
+fn conflicting_paths(outcome: &merge::Outcome) -> Vec<String> {
+    outcome.conflicts.iter().map(|c| c.path.to_string()).collect()
+}
+
+#[test]
+fn changes_of_both_sides_are_combined() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_merge_trees_repo.sh")?;
+    let outcome = merge(&repo, "ours", "theirs")?;
+    assert!(!outcome.has_conflicts());
+    assert_eq!(
+        outcome.tree,
+        hex_to_id("dd27b0b89b0cc98b01dc00df2345f2fec3973fc6"),
+        "the same tree as written by `git merge-tree --write-tree ours theirs`"
+    );
+    Ok(())
+}
+
+#[test]
+fn conflicts_are_written_with_markers_and_reported_with_stages() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_merge_trees_repo.sh")?;
+    let outcome = merge(&repo, "ours", "conflicting")?;
+    assert_eq!(
+        outcome.tree,
+        hex_to_id("0f01b7da85196b1e342d943802210e13d3486526"),
+        "the same tree as written by `git merge-tree --write-tree ours conflicting`"
+    );
+    assert_eq!(
+        conflicting_paths(&outcome),
+        ["added-by-both", "modified-by-ours", "numbers"]
+    );
+    let numbers = &outcome.conflicts[2];
+    assert_eq!(
+        numbers.stages().map(|(stage, e)| (stage, e.id)).collect::<Vec<_>>(),
+        [
+            (1, hex_to_id("f00c965d8307308469e537302baa73048488f162")),
+            (2, hex_to_id("ed75e4e57b725dde926e9f9c453a5cbebb016ab0")),
+            (3, hex_to_id("32237e49f8e390d0cc658accd50b5b085e1ada05"))
+        ]
+    );
+    assert_eq!(
+        outcome.conflicts[0]
+            .stages()
+            .map(|(stage, _)| stage)
+            .collect::<Vec<_>>(),
+        [2, 3],
+        "files added by both sides have no ancestor"
+    );
+
+    let index = outcome.index(&repo)?;
+    let entries: Vec<_> = index
+        .entries()
+        .iter()
+        .map(|e| (e.path(&index).to_string(), e.stage()))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("added-by-both", 2),
+            ("added-by-both", 3),
+            ("deleted-by-theirs", 0),
+            ("modified-by-ours", 1),
+            ("modified-by-ours", 2),
+            ("modified-by-ours", 3),
+            ("modified-by-theirs", 0),
+            ("numbers", 1),
+            ("numbers", 2),
+            ("numbers", 3),
+            ("to-be-executable", 0),
+            ("unchanged", 0),
+        ]
+        .map(|(path, stage)| (path.to_owned(), stage)),
+        "conflicting paths only have their stages, and the deleted directory is gone"
+    );
+    Ok(())
+}
+
+#[test]
+fn modifications_of_deleted_files_are_kept_and_reported() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_merge_trees_repo.sh")?;
+    let outcome = merge(&repo, "modify-delete", "theirs")?;
+    assert_eq!(
+        outcome.tree,
+        hex_to_id("1fb6d7b571dd17fdd98ada382a760dd244e05d24"),
+        "the same tree as written by `git merge-tree --write-tree modify-delete theirs`"
+    );
+    assert_eq!(conflicting_paths(&outcome), ["deleted-by-theirs"]);
+    let conflict = &outcome.conflicts[0];
+    assert!(conflict.ancestor.is_some() && conflict.ours.is_some());
+    assert_eq!(conflict.theirs, None);
+    Ok(())
+}
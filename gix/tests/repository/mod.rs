@@ -1,12 +1,25 @@
 use gix::Repository;
 
+#[cfg(feature = "archive")]
+mod archive;
+mod bisect;
+mod blame;
+mod bundle;
+mod cherry_pick;
 mod config;
+mod hook;
+mod maintenance;
+mod merge;
 mod object;
 mod open;
+mod rebase;
 mod reference;
 mod remote;
 mod shallow;
+mod sparse_checkout;
+mod stash;
 mod state;
+mod submodule;
 mod worktree;
 
 #[test]
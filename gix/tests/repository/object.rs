@@ -178,6 +178,91 @@ mod commit_as {
     }
 }
 
+mod commit_signed {
+    use std::cell::RefCell;
+
+    use gix::bstr::{BString, ByteSlice};
+    use gix_testtools::tempfile;
+
+    #[derive(Default)]
+    struct Recorder {
+        signed: RefCell<Vec<u8>>,
+    }
+
+    impl gix::sign::Sign for Recorder {
+        fn sign(&self, data: &[u8]) -> Result<BString, Box<dyn std::error::Error + Send + Sync + 'static>> {
+            *self.signed.borrow_mut() = data.to_owned();
+            Ok("-----BEGIN SIGNATURE-----\nabc\n\ndef\n-----END SIGNATURE-----\n".into())
+        }
+    }
+
+    #[test]
+    fn signature_is_stored_in_gpgsig_header_and_covers_the_unsigned_commit() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let repo = gix::open_opts(gix::init(&tmp)?.path(), crate::restricted())?;
+        let signer = Recorder::default();
+        let commit_id = repo.commit_signed(
+            "HEAD",
+            "initial",
+            repo.empty_tree().id,
+            gix::commit::NO_PARENT_IDS,
+            &signer,
+        )?;
+
+        let mut commit: gix::objs::Commit = commit_id.object()?.into_commit().decode()?.into();
+        assert_eq!(
+            commit.extra_headers,
+            vec![(
+                "gpgsig".into(),
+                "-----BEGIN SIGNATURE-----\nabc\n\ndef\n-----END SIGNATURE-----".into()
+            )],
+            "the trailing newline is removed, and empty lines survive the round-trip"
+        );
+
+        commit.extra_headers.clear();
+        let mut unsigned = Vec::new();
+        gix::objs::WriteTo::write_to(&commit, &mut unsigned)?;
+        assert_eq!(
+            signer.signed.borrow().as_bstr(),
+            unsigned.as_bstr(),
+            "the signature is created over the commit without its signature"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn configured_signer() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let mut repo = gix::open_opts(gix::init(&tmp)?.path(), crate::restricted())?;
+
+        let signer = repo.signer()?;
+        assert_eq!(signer.format, gix::sign::Format::OpenPgp, "openpgp is the default");
+        assert_eq!(signer.program, "gpg");
+        assert_eq!(
+            signer.key, "gitoxide <gitoxide@localhost>",
+            "the committer identity selects the key by default"
+        );
+
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value("gpg", None, "format", "ssh")?;
+        let repo = config.commit()?;
+        assert!(
+            matches!(repo.signer(), Err(gix::config::signer::Error::SigningKeyMissing)),
+            "ssh signing needs a key"
+        );
+
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value("user", None, "signingKey", "key::ssh-ed25519 AAAA")?;
+        config.set_raw_value("gpg", Some("ssh".into()), "program", "my-ssh-keygen")?;
+        let repo = config.commit()?;
+        let signer = repo.signer()?;
+        assert_eq!(signer.format, gix::sign::Format::Ssh);
+        assert_eq!(signer.program, "my-ssh-keygen");
+        assert_eq!(signer.key, "key::ssh-ed25519 AAAA");
+        Ok(())
+    }
+}
+
 mod commit {
     use gix_testtools::tempfile;
 
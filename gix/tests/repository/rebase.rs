@@ -0,0 +1,170 @@
+use gix::{cherry_pick, rebase};
+use gix_testtools::tempfile;
+
+use crate::util::{hex_to_id, repo_rw, restricted};
+
+/// Return the clone of the fixture which has `branch` checked out, to be rebased onto `origin/main`.
+fn repo_on(branch: &str) -> crate::Result<(gix::Repository, tempfile::TempDir)> {
+    let (_, tmp) = repo_rw("make_rebase_repo.sh")?;
+    let repo = gix::open_opts(tmp.path().join(branch), restricted())?;
+    Ok((repo, tmp))
+}
+
+fn id(repo: &gix::Repository, rev: &str) -> crate::Result<gix::ObjectId> {
+    Ok(repo.rev_parse_single(rev)?.detach())
+}
+
+fn steps(outcome: rebase::Outcome<'_>) -> Vec<rebase::Step<'_>> {
+    match outcome {
+        rebase::Outcome::Finished { steps } => steps,
+        other => panic!("expected the rebase to finish, got {other:?}"),
+    }
+}
+
+fn state(repo: &gix::Repository, name: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(repo.git_dir().join("rebase-merge").join(name))
+}
+
+#[test]
+fn commits_are_replayed_onto_upstream_and_the_branch_is_updated() -> crate::Result {
+    let (repo, _tmp) = repo_on("topic")?;
+    let orig_head = id(&repo, "HEAD")?;
+    let replayed = [id(&repo, "HEAD~1")?, orig_head];
+    let upstream = id(&repo, "origin/main")?;
+    let steps = steps(repo.rebase(upstream, Default::default())?);
+
+    assert_eq!(
+        steps.iter().map(|step| step.commit).collect::<Vec<_>>(),
+        replayed,
+        "the original commits are replayed oldest first"
+    );
+    let head = repo.head_commit()?;
+    assert_eq!(
+        head.tree_id()?,
+        hex_to_id("245b144c7e0563869b348dcdeb8b1b3b638efbe3"),
+        "the same tree as `git rebase` writes"
+    );
+    assert_eq!(head.author()?.name, "Other Author", "authors are kept");
+    let parent = head.parent_ids().next().expect("one parent").object()?.into_commit();
+    assert_eq!(parent.parent_ids().collect::<Vec<_>>(), [upstream]);
+    assert!(matches!(steps[1].outcome, cherry_pick::Outcome::Committed(id) if id == head.id));
+
+    assert_eq!(
+        repo.head_name()?.expect("not detached").as_bstr(),
+        "refs/heads/topic",
+        "HEAD points to the updated branch again"
+    );
+    assert_eq!(id(&repo, "ORIG_HEAD")?, orig_head);
+    assert_eq!(repo.state(), None, "the rebase state is removed");
+    assert_eq!(
+        std::fs::read_to_string(repo.work_dir().expect("non-bare").join("numbers"))?,
+        "one\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn commits_whose_changes_are_present_upstream_are_dropped() -> crate::Result {
+    let (repo, _tmp) = repo_on("redundant")?;
+    let steps = steps(repo.rebase(id(&repo, "origin/main")?, Default::default())?);
+    assert!(matches!(steps[0].outcome, cherry_pick::Outcome::Empty));
+    assert!(matches!(steps[1].outcome, cherry_pick::Outcome::Committed(_)));
+
+    let head = repo.head_commit()?;
+    assert_eq!(
+        head.tree_id()?,
+        hex_to_id("d3c6e572afa0331e15291e3e7275b0453bfbac3d"),
+        "the same tree as `git rebase` writes"
+    );
+    assert_eq!(
+        head.parent_ids().collect::<Vec<_>>(),
+        [id(&repo, "origin/main")?],
+        "only the commit with new changes remains"
+    );
+    Ok(())
+}
+
+#[test]
+fn branches_already_based_on_upstream_are_up_to_date() -> crate::Result {
+    let (repo, _tmp) = repo_on("topic")?;
+    let head = id(&repo, "HEAD")?;
+    let outcome = repo.rebase(id(&repo, "base")?, Default::default())?;
+    assert!(matches!(outcome, rebase::Outcome::UpToDate));
+    assert_eq!(id(&repo, "HEAD")?, head);
+    assert!(repo.try_find_reference("ORIG_HEAD")?.is_none(), "nothing was changed");
+
+    let onto = id(&repo, "origin/main")?;
+    let outcome = repo.rebase(
+        id(&repo, "base")?,
+        rebase::Options {
+            onto: Some(onto),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(
+        steps(outcome).len(),
+        2,
+        "with a different commit to replay onto, the same commits are replayed"
+    );
+    Ok(())
+}
+
+#[test]
+fn conflicts_stop_the_rebase_with_state_for_git_to_continue() -> crate::Result {
+    let (repo, _tmp) = repo_on("conflicting")?;
+    let orig_head = id(&repo, "HEAD")?;
+    let upstream = id(&repo, "origin/main")?;
+    let stopped = id(&repo, "HEAD~1")?;
+    let steps = match repo.rebase(upstream, Default::default())? {
+        rebase::Outcome::Stopped { steps } => steps,
+        other => panic!("expected the rebase to stop, got {other:?}"),
+    };
+    assert_eq!(steps.len(), 1, "it stops at the first conflicting commit");
+    assert_eq!(steps[0].commit, stopped);
+    let conflicts = match &steps[0].outcome {
+        cherry_pick::Outcome::Conflicts(conflicts) => conflicts,
+        other => panic!("expected conflicts, got {other:?}"),
+    };
+    assert_eq!(
+        conflicts.iter().map(|c| c.path.to_string()).collect::<Vec<_>>(),
+        ["file"]
+    );
+
+    assert_eq!(repo.head_name()?, None, "HEAD is detached");
+    assert_eq!(id(&repo, "HEAD")?, upstream);
+    assert_eq!(id(&repo, "ORIG_HEAD")?, orig_head);
+    assert_eq!(
+        repo.state(),
+        Some(gix::state::InProgress::RebaseInteractive),
+        "like `git rebase`, the state is marked as interactive"
+    );
+    assert_eq!(
+        std::fs::read_to_string(repo.git_dir().join("REBASE_HEAD"))?,
+        format!("{stopped}\n")
+    );
+    assert_eq!(state(&repo, "head-name")?, "refs/heads/conflicting\n");
+    assert_eq!(state(&repo, "onto")?, format!("{upstream}\n"));
+    assert_eq!(state(&repo, "orig-head")?, format!("{orig_head}\n"));
+    assert_eq!(state(&repo, "msgnum")?, "1\n");
+    assert_eq!(state(&repo, "end")?, "2\n");
+    assert_eq!(state(&repo, "done")?, format!("pick {stopped} change the file\n"));
+    assert_eq!(
+        state(&repo, "git-rebase-todo")?,
+        format!("pick {orig_head} add another file\n")
+    );
+    assert_eq!(state(&repo, "stopped-sha")?, format!("{stopped}\n"));
+    assert_eq!(state(&repo, "message")?, "change the file\n");
+    assert_eq!(
+        state(&repo, "author-script")?,
+        "GIT_AUTHOR_NAME='author'\nGIT_AUTHOR_EMAIL='author@example.com'\nGIT_AUTHOR_DATE='@946684800 +0000'\n"
+    );
+
+    assert!(
+        matches!(
+            repo.rebase(upstream, Default::default()),
+            Err(rebase::Error::InProgress(gix::state::InProgress::RebaseInteractive))
+        ),
+        "another rebase can't be started before this one is concluded"
+    );
+    Ok(())
+}
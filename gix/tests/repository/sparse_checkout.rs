@@ -0,0 +1,105 @@
+#[test]
+fn set_add_and_disable_update_the_worktree() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_sparse_checkout_repo.sh")?;
+    let work_dir = repo.work_dir().expect("non-bare").to_owned();
+    let config_path = repo.git_dir().join("config.worktree");
+    assert_eq!(repo.sparse_checkout().list()?.expect("sparse"), ["a/b/c"]);
+    assert!(
+        !work_dir.join("d/4").exists(),
+        "files outside of the cone aren't checked out"
+    );
+
+    let outcome = repo.sparse_checkout().add(Some("d"))?;
+    assert_eq!(outcome.entries_changed, 1);
+    assert_eq!(outcome.checkout.files_updated, 1);
+    assert_eq!(std::fs::read(work_dir.join("d/4"))?, b"d/4\n");
+    assert_eq!(repo.sparse_checkout().list()?.expect("sparse"), ["a/b/c", "d"]);
+    assert_eq!(
+        std::fs::read_to_string(repo.git_dir().join("info/sparse-checkout"))?,
+        "/*\n!/*/\n/a/\n!/a/*/\n/a/b/\n!/a/b/*/\n/a/b/c/\n/d/\n"
+    );
+
+    std::fs::write(work_dir.join("d/4"), "modified")?;
+    let outcome = repo.sparse_checkout().set(Some("a/b/"), false)?;
+    assert_eq!(outcome.files_removed, 2, "r and a/1 were unmodified");
+    assert_eq!(
+        outcome.modified_files_kept,
+        ["d/4"],
+        "modified files are kept and remain included"
+    );
+    assert!(!work_dir.join("r").exists());
+    assert!(!work_dir.join("a/1").exists());
+    assert!(work_dir.join("a/b/c/3").is_file());
+    assert_eq!(repo.sparse_checkout().list()?.expect("sparse"), ["a/b/"]);
+    assert!(
+        std::fs::read_to_string(&config_path)?.contains("sparseCheckoutCone = false"),
+        "like git, the configuration is written per worktree as extensions.worktreeConfig is set"
+    );
+    assert_eq!(
+        repo.config_snapshot().boolean("core.sparseCheckoutCone"),
+        Some(false),
+        "the configuration of the instance is updated as well"
+    );
+
+    let outcome = repo.sparse_checkout().disable()?;
+    assert_eq!(outcome.checkout.files_updated, 2);
+    assert!(work_dir.join("r").is_file());
+    assert!(work_dir.join("a/1").is_file());
+    assert_eq!(std::fs::read(work_dir.join("d/4"))?, b"modified");
+    assert_eq!(repo.sparse_checkout().list()?, None);
+    assert!(
+        repo.git_dir().join("info/sparse-checkout").is_file(),
+        "the patterns are kept for when the sparse checkout is enabled again"
+    );
+    let index = repo.open_index()?;
+    assert!(index
+        .entries()
+        .iter()
+        .all(|entry| !entry.flags.contains(gix::index::entry::Flags::SKIP_WORKTREE)));
+    Ok(())
+}
+
+#[test]
+fn add_requires_a_sparse_worktree() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_basic_repo.sh")?;
+    assert_eq!(repo.sparse_checkout().list()?, None);
+    assert!(matches!(
+        repo.sparse_checkout().add(Some("a")),
+        Err(gix::sparse_checkout::Error::NotSparse)
+    ));
+    Ok(())
+}
+
+#[test]
+fn sparse_index_is_written_if_configured_and_expanded_if_needed() -> crate::Result {
+    let (mut repo, _tmp) = crate::repo_rw("make_sparse_checkout_repo.sh")?;
+    let mut config = repo.config_snapshot_mut();
+    config.set_raw_value("index", None, "sparse", "true")?;
+    config.commit()?;
+
+    let outcome = repo.sparse_checkout().set(Some("a/b/c"), true)?;
+    assert_eq!(outcome.entries_changed, 0, "the cone didn't change");
+    let index = repo.open_index()?;
+    assert!(index.is_sparse());
+    assert_eq!(
+        index
+            .entries()
+            .iter()
+            .filter(|entry| entry.mode.is_sparse())
+            .map(|entry| entry.path(&index).to_string())
+            .collect::<Vec<_>>(),
+        ["d/"],
+        "directories outside of the cone are collapsed"
+    );
+    assert_eq!(
+        repo.rev_parse_single(":d/4")?,
+        repo.rev_parse_single("HEAD:d/4")?,
+        "paths within sparse directories can still be looked up"
+    );
+
+    let outcome = repo.sparse_checkout().add(Some("d"))?;
+    assert_eq!(outcome.checkout.files_updated, 1);
+    assert!(repo.work_dir().expect("non-bare").join("d/4").is_file());
+    assert!(!repo.open_index()?.is_sparse(), "all directories are part of the cone");
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use gix::refs::transaction::PreviousValue;
+
+use crate::util::{hex_to_id, repo_rw};
+
+fn read(repo: &gix::Repository, path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(repo.work_dir().expect("non-bare").join(path))
+}
+
+fn exists(repo: &gix::Repository, path: &str) -> bool {
+    repo.work_dir().expect("non-bare").join(path).exists()
+}
+
+fn index_entries(repo: &gix::Repository) -> crate::Result<Vec<(String, gix::ObjectId)>> {
+    let index = repo.open_index()?;
+    Ok(index
+        .entries()
+        .iter()
+        .map(|e| (e.path(&index).to_string(), e.id))
+        .collect())
+}
+
+fn messages(repo: &gix::Repository) -> crate::Result<Vec<String>> {
+    Ok(repo
+        .stash()
+        .list()?
+        .into_iter()
+        .map(|entry| entry.message.to_string())
+        .collect())
+}
+
+const BASE: &str = "df967b96a579e45a18b8251732d16804b2e56a55";
+
+#[test]
+fn push_saves_the_index_and_the_worktree_and_resets_them() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_stash_repo.sh")?;
+    let head = repo.head_id()?;
+    let id = repo.stash().push(None)?.expect("there are changes");
+
+    let commit = id.object()?.into_commit();
+    assert_eq!(
+        commit.tree_id()?,
+        hex_to_id("cb679743d0966aab6d913d1613e44481a77a86da"),
+        "the worktree as `git stash` stores it"
+    );
+    let parents: Vec<_> = commit.parent_ids().collect();
+    assert_eq!(parents[0], head);
+    let index_commit = parents[1].object()?.into_commit();
+    assert_eq!(
+        index_commit.tree_id()?,
+        hex_to_id("7b59236e95dcf346e61bf4be9eff1be2135f45a3"),
+        "the index as `git stash` stores it"
+    );
+    assert_eq!(index_commit.parent_ids().collect::<Vec<_>>(), [head]);
+    let short_head = head.shorten_or_id();
+    assert_eq!(commit.message_raw()?, format!("WIP on main: {short_head} base\n"));
+    assert_eq!(
+        index_commit.message_raw()?,
+        format!("index on main: {short_head} base\n")
+    );
+    assert_eq!(repo.find_reference("refs/stash")?.id(), id);
+    assert_eq!(messages(&repo)?, [format!("WIP on main: {short_head} base")]);
+
+    assert_eq!(read(&repo, "file")?, "base\n");
+    assert_eq!(read(&repo, "dir/nested")?, "base\n");
+    assert_eq!(read(&repo, "removed")?, "base\n");
+    assert!(!exists(&repo, "added"));
+    assert_eq!(read(&repo, "untracked")?, "untracked\n", "untracked files are kept");
+    assert_eq!(
+        index_entries(&repo)?,
+        ["dir/nested", "file", "removed", "unchanged"].map(|path| (path.to_owned(), hex_to_id(BASE))),
+        "the index matches HEAD again"
+    );
+
+    assert!(repo.stash().push(None)?.is_none(), "there is nothing left to stash");
+    Ok(())
+}
+
+#[test]
+fn pop_restores_the_changes_and_drops_the_entry() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_stash_repo.sh")?;
+    repo.stash().push(Some("changes"))?.expect("there are changes");
+    assert_eq!(messages(&repo)?, ["On main: changes"]);
+
+    let conflicts = repo.stash().pop(0)?;
+    assert!(conflicts.is_empty());
+    assert_eq!(read(&repo, "file")?, "modified\n");
+    assert_eq!(read(&repo, "dir/nested")?, "staged\n");
+    assert_eq!(read(&repo, "added")?, "added\n");
+    assert!(!exists(&repo, "removed"));
+    assert_eq!(
+        index_entries(&repo)?,
+        [
+            ("added", hex_to_id("d5f7fc3f74f7dec08280f370a975b112e8f60818")),
+            ("dir/nested", hex_to_id(BASE)),
+            ("file", hex_to_id(BASE)),
+            ("removed", hex_to_id(BASE)),
+            ("unchanged", hex_to_id(BASE)),
+        ]
+        .map(|(path, id)| (path.to_owned(), id)),
+        "like `git stash pop`, only new files are added to the index"
+    );
+    assert!(messages(&repo)?.is_empty());
+    assert!(repo.try_find_reference("refs/stash")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn conflicting_entries_are_kept_when_popping() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_stash_repo.sh")?;
+    let id = repo.stash().push(None)?.expect("there are changes");
+    let other = repo.rev_parse_single("other")?.detach();
+    repo.reference("refs/heads/main", other, PreviousValue::Any, "move to other")?;
+    std::fs::write(repo.work_dir().expect("non-bare").join("file"), "other\n")?;
+    let mut index = repo.open_index()?;
+    let other_file = repo.rev_parse_single("other:file")?.detach();
+    for (entry, path) in index.entries_mut_with_paths() {
+        if path == "file" {
+            entry.id = other_file;
+        }
+    }
+    index.write(Default::default())?;
+
+    let conflicts = repo.stash().pop(0)?;
+    assert_eq!(
+        conflicts.iter().map(|c| c.path.to_string()).collect::<Vec<_>>(),
+        ["file"]
+    );
+    assert_eq!(
+        read(&repo, "file")?,
+        "<<<<<<< Updated upstream\nother\n=======\nmodified\n>>>>>>> Stashed changes\n"
+    );
+    assert_eq!(repo.stash().list()?[0].id, id, "the entry is kept");
+    Ok(())
+}
+
+#[test]
+fn drop_removes_entries_and_updates_the_reference() -> crate::Result {
+    let (repo, _tmp) = repo_rw("make_stash_repo.sh")?;
+    let first = repo.stash().push(Some("first"))?.expect("changes");
+    std::fs::write(repo.work_dir().expect("non-bare").join("file"), "second\n")?;
+    let second = repo.stash().push(Some("second"))?.expect("changes");
+    std::fs::write(repo.work_dir().expect("non-bare").join("file"), "third\n")?;
+    let third = repo.stash().push(Some("third"))?.expect("changes");
+    assert_eq!(
+        messages(&repo)?,
+        ["On main: third", "On main: second", "On main: first"]
+    );
+
+    assert_eq!(repo.stash().drop(1)?, second);
+    assert_eq!(messages(&repo)?, ["On main: third", "On main: first"]);
+    assert_eq!(repo.find_reference("refs/stash")?.id(), third);
+
+    assert_eq!(repo.stash().drop(0)?, third);
+    assert_eq!(messages(&repo)?, ["On main: first"]);
+    assert_eq!(
+        repo.find_reference("refs/stash")?.id(),
+        first,
+        "the reference points to the most recent entry"
+    );
+    assert!(matches!(
+        repo.stash().drop(1),
+        Err(gix::stash::Error::NoSuchEntry { index: 1 })
+    ));
+
+    assert_eq!(repo.stash().drop(0)?, first);
+    assert!(messages(&repo)?.is_empty());
+    assert!(repo.try_find_reference("refs/stash")?.is_none());
+    Ok(())
+}
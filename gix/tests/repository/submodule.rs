@@ -0,0 +1,233 @@
+use crate::util::{named_subrepo_opts, restricted};
+
+fn repo(name: &str) -> crate::Result<gix::Repository> {
+    named_subrepo_opts("make_submodule_states.sh", name, restricted())
+}
+
+#[test]
+fn repositories_without_gitmodules_have_no_submodules() -> crate::Result {
+    let repo = repo("module1")?;
+    assert!(repo.submodules()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn enumeration_in_configuration_order() -> crate::Result {
+    let repo = repo("with-submodules")?;
+    let submodules = repo.submodules()?.expect("configured");
+    assert_eq!(
+        submodules
+            .iter()
+            .map(|sm| (sm.name().to_string(), sm.path().to_string()))
+            .collect::<Vec<_>>(),
+        [
+            ("m1".to_owned(), "m1".to_owned()),
+            ("dir/m1".to_owned(), "dir/m1".to_owned())
+        ]
+    );
+    for sm in &submodules {
+        assert!(sm.is_initialized(), "`git submodule add` initializes the submodule");
+        assert_eq!(sm.branch(), None);
+        assert_eq!(
+            sm.git_dir(),
+            repo.common_dir().join("modules").join(sm.path().to_string())
+        );
+        assert_eq!(
+            sm.url()?.expect("set").path.to_string().rsplit('/').next(),
+            Some("module1")
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn status_of_checked_out_submodules() -> crate::Result {
+    let repo = repo("with-submodules")?;
+    for sm in repo.submodules()?.expect("configured") {
+        let status = sm.status()?;
+        assert_eq!(
+            status.state,
+            gix::submodule::State {
+                repository_exists: true,
+                is_old_form: false,
+                worktree_checkout: true,
+                superproject_configuration: true,
+            }
+        );
+        assert!(status.index_id.is_some());
+        assert_eq!(status.index_id, sm.head_id()?, "the index matches HEAD");
+        assert_eq!(status.checked_out_head_id, status.index_id);
+        assert_eq!(status.is_dirty, Some(false));
+        assert!(!status.is_uninitialized());
+        assert!(!status.is_head_mismatch());
+        assert!(sm.open()?.is_some());
+    }
+    Ok(())
+}
+
+#[test]
+fn status_of_uninitialized_submodules() -> crate::Result {
+    let repo = repo("not-initialized")?;
+    for sm in repo.submodules()?.expect("configured") {
+        assert!(!sm.is_initialized());
+        let status = sm.status()?;
+        assert_eq!(
+            status.state,
+            gix::submodule::State {
+                repository_exists: false,
+                is_old_form: false,
+                worktree_checkout: false,
+                superproject_configuration: false,
+            }
+        );
+        assert!(status.is_uninitialized());
+        assert!(status.index_id.is_some(), "the gitlink is still recorded");
+        assert_eq!(status.checked_out_head_id, None);
+        assert_eq!(status.is_dirty, None, "unknown as it's not checked out");
+        assert!(!status.is_head_mismatch());
+        assert!(sm.open()?.is_none());
+    }
+    Ok(())
+}
+
+#[test]
+fn status_of_dirty_submodule() -> crate::Result {
+    let repo = repo("dirty")?;
+    let submodules = repo.submodules()?.expect("configured");
+    let status = submodules[0].status()?;
+    assert_eq!(submodules[0].path(), "m1");
+    assert_eq!(status.is_dirty, Some(true), "a tracked file was changed");
+    assert!(!status.is_head_mismatch());
+
+    let status = submodules[1].status()?;
+    assert_eq!(status.is_dirty, Some(false));
+    Ok(())
+}
+
+#[test]
+fn status_of_submodule_with_head_mismatch() -> crate::Result {
+    let repo = repo("head-mismatch")?;
+    let submodules = repo.submodules()?.expect("configured");
+    let status = submodules[0].status()?;
+    assert!(status.is_head_mismatch());
+    assert_eq!(status.is_dirty, Some(false), "the checkout is clean");
+    assert!(!submodules[1].status()?.is_head_mismatch());
+    Ok(())
+}
+
+#[test]
+fn relative_urls_are_resolved_against_the_default_remote() -> crate::Result {
+    for (remote_url, expected) in [
+        ("https://example.com/org/super", "https://example.com/org/module1"),
+        ("https://example.com/org/super/", "https://example.com/org/module1"),
+        ("git@example.com:org/super", "git@example.com:org/module1"),
+        ("git@example.com:super", "git@example.com:module1"),
+    ] {
+        let repo = named_subrepo_opts(
+            "make_submodule_states.sh",
+            "not-initialized",
+            restricted().config_overrides([format!("remote.origin.url={remote_url}")]),
+        )?;
+        let sm = &repo.submodules()?.expect("configured")[0];
+        assert_eq!(sm.url()?.expect("set").to_bstring(), expected);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "blocking-network-client")]
+mod update {
+    use std::sync::atomic::AtomicBool;
+
+    use gix::submodule::update::{Kind, Options};
+
+    use crate::util::restricted;
+
+    fn writable_repo(name: &str) -> crate::Result<(gix::Repository, gix_testtools::tempfile::TempDir)> {
+        let dir = gix_testtools::scripted_fixture_writable("make_submodule_states.sh")?;
+        let repo = gix::open_opts(dir.path().join(name), restricted())?;
+        Ok((repo, dir))
+    }
+
+    fn update(repo: &gix::Repository, options: Options) -> crate::Result<Vec<gix::submodule::update::Outcome>> {
+        Ok(gix::submodule::update(
+            repo,
+            &mut gix::progress::Discard,
+            &AtomicBool::default(),
+            options,
+            None,
+        )?)
+    }
+
+    #[test]
+    fn uninitialized_submodules_are_skipped_without_init() -> crate::Result {
+        let (repo, _tmp) = writable_repo("not-initialized")?;
+        let outcome = update(&repo, Options::default())?;
+        assert_eq!(outcome.len(), 2);
+        assert!(outcome.iter().all(|sm| sm.kind == Kind::Skipped && sm.id.is_none()));
+        assert!(!repo.common_dir().join("modules").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn init_clones_and_checks_out_recorded_commits() -> crate::Result {
+        let (repo, _tmp) = writable_repo("not-initialized")?;
+        let outcome = update(
+            &repo,
+            Options {
+                init: true,
+                recursive: true,
+            },
+        )?;
+        assert_eq!(
+            outcome
+                .iter()
+                .map(|sm| (sm.path.to_string(), sm.kind))
+                .collect::<Vec<_>>(),
+            [("m1".to_owned(), Kind::Cloned), ("dir/m1".to_owned(), Kind::Cloned)]
+        );
+
+        let repo = gix::open_opts(repo.work_dir().expect("non-bare"), restricted())?;
+        for (sm, outcome) in repo.submodules()?.expect("configured").iter().zip(&outcome) {
+            assert!(sm.is_initialized(), "the url was written to the local configuration");
+            let status = sm.status()?;
+            assert!(
+                !status.state.is_old_form,
+                "the repository is placed into the modules directory"
+            );
+            assert!(status.state.repository_exists);
+            assert_eq!(status.checked_out_head_id, status.index_id);
+            assert_eq!(outcome.id, status.index_id);
+            assert_eq!(status.is_dirty, Some(false));
+            assert!(outcome.submodules.is_empty(), "there are no nested submodules");
+
+            let sm_repo = sm.open()?.expect("cloned");
+            assert!(sm_repo.head()?.is_detached());
+            assert_eq!(
+                std::fs::read(sm.work_dir().expect("non-bare").join("file"))?,
+                b"content\nmore\n"
+            );
+        }
+
+        let outcome = update(&repo, Options::default())?;
+        assert!(
+            outcome.iter().all(|sm| sm.kind == Kind::UpToDate),
+            "a second update has nothing to do"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn submodules_with_mismatching_head_are_checked_out() -> crate::Result {
+        let (repo, _tmp) = writable_repo("head-mismatch")?;
+        let outcome = update(&repo, Options::default())?;
+        assert_eq!(
+            outcome.iter().map(|sm| sm.kind).collect::<Vec<_>>(),
+            [Kind::CheckedOut, Kind::UpToDate]
+        );
+        let sm = &repo.submodules()?.expect("configured")[0];
+        let status = sm.status()?;
+        assert!(!status.is_head_mismatch());
+        assert_eq!(status.is_dirty, Some(false));
+        Ok(())
+    }
+}
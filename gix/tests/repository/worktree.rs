@@ -265,6 +265,11 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
         assert_eq!(proxy_is_locked, proxy_lock_reason.is_some());
         // TODO: check id of expected worktree, but need access to .gitdir from worktree base
         let proxy_id = actual.id().to_owned();
+        assert_eq!(
+            actual.prune_reason(),
+            expected.prunable,
+            "we report the same reasons as git"
+        );
         assert_eq!(
             base.is_dir(),
             expected.prunable.is_none(),
@@ -301,3 +306,208 @@ fn run_assertions(main_repo: gix::Repository, should_be_bare: bool) {
         );
     }
 }
+
+mod management {
+    use gix::worktree::add::{Head, Options};
+
+    use crate::util::basic_rw_repo;
+
+    #[test]
+    fn add_with_new_branch_then_move_and_prune() -> crate::Result {
+        let (repo, tmp) = basic_rw_repo()?;
+        let path = tmp.path().join("wt");
+        let wt = repo.worktree_add(&path, Options::default())?;
+        assert_eq!(wt.head_name()?.expect("on a branch").as_bstr(), "refs/heads/wt");
+        assert_eq!(wt.head_id()?, repo.head_id()?, "the new branch starts at HEAD");
+        assert_eq!(std::fs::read(path.join("this"))?, b"hello\n", "HEAD is checked out");
+        assert_eq!(wt.index()?.entries().len(), 1);
+        assert_eq!(wt.worktree().expect("linked").id(), Some("wt".into()));
+        assert_eq!(wt.main_repo()?, repo);
+
+        let worktrees = repo.worktrees()?;
+        assert_eq!(worktrees.len(), 1);
+        assert_eq!(worktrees[0].id(), "wt");
+        assert_eq!(worktrees[0].prune_reason(), None);
+        assert!(!worktrees[0].is_locked());
+
+        assert!(
+            matches!(
+                repo.worktree_add(
+                    tmp.path().join("other"),
+                    Options {
+                        head: Head::Branch("wt".into()),
+                        lock_reason: None,
+                    }
+                ),
+                Err(gix::worktree::add::Error::BranchCheckedOut { .. })
+            ),
+            "branches can only be checked out in one worktree"
+        );
+
+        let new_path = tmp.path().join("moved");
+        worktrees[0].move_to(&new_path)?;
+        assert!(!path.exists());
+        assert_eq!(worktrees[0].base()?, gix_path::realpath(&new_path)?);
+        assert_eq!(worktrees[0].clone().into_repo()?.head_name()?, wt.head_name()?);
+
+        assert_eq!(repo.worktree_prune()?.len(), 0, "nothing to prune yet");
+        std::fs::remove_dir_all(&new_path)?;
+        assert_eq!(
+            worktrees[0].prune_reason().expect("prunable"),
+            "gitdir file points to non-existent location"
+        );
+        assert_eq!(repo.worktree_prune()?, ["wt"]);
+        assert_eq!(repo.worktrees()?.len(), 0);
+        assert!(!repo.common_dir().join("worktrees").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn add_detached_and_locked() -> crate::Result {
+        let (repo, tmp) = basic_rw_repo()?;
+        let first_commit = repo.rev_parse_single("HEAD~1")?.detach();
+        let wt = repo.worktree_add(
+            tmp.path().join("wt"),
+            Options {
+                head: Head::Detached(first_commit),
+                lock_reason: Some("on a usb drive".into()),
+            },
+        )?;
+        assert!(wt.head()?.is_detached());
+        assert_eq!(wt.head_id()?, first_commit);
+        assert_eq!(std::fs::read(tmp.path().join("wt").join("this"))?, b"");
+
+        let worktree = wt.worktree().expect("linked");
+        assert_eq!(worktree.lock_reason().expect("locked"), "on a usb drive");
+
+        std::fs::remove_dir_all(tmp.path().join("wt"))?;
+        let worktrees = repo.worktrees()?;
+        assert_eq!(worktrees[0].prune_reason(), None, "locked worktrees are never pruned");
+        assert!(matches!(
+            worktrees[0].move_to(tmp.path().join("moved")),
+            Err(gix::worktree::proxy::move_to::Error::Locked { .. })
+        ));
+        assert_eq!(repo.worktree_prune()?.len(), 0);
+
+        let second = repo.worktree_add(
+            tmp.path().join("some").join("wt"),
+            Options {
+                head: Head::NewBranch(Some("feature".into())),
+                lock_reason: None,
+            },
+        )?;
+        assert_eq!(
+            second.worktree().expect("linked").id(),
+            Some("wt1".into()),
+            "names of administrative directories are made unique"
+        );
+        assert_eq!(second.head_name()?.expect("on branch").as_bstr(), "refs/heads/feature");
+        Ok(())
+    }
+
+    #[test]
+    fn add_writes_the_configured_index_version() -> crate::Result {
+        let (repo, tmp) = basic_rw_repo()?;
+        let wt = repo.worktree_add(tmp.path().join("default"), Options::default())?;
+        assert_eq!(wt.index()?.version(), gix::index::Version::V2);
+
+        let mut config = std::fs::read(repo.git_dir().join("config"))?;
+        config.extend_from_slice(b"[index]\n\tversion = 4\n");
+        std::fs::write(repo.git_dir().join("config"), config)?;
+        let repo = gix::open_opts(repo.git_dir(), crate::restricted())?;
+        let wt = repo.worktree_add(tmp.path().join("v4"), Options::default())?;
+        let index = wt.index()?;
+        assert_eq!(index.version(), gix::index::Version::V4);
+        assert_eq!(index.entries().len(), 1);
+        Ok(())
+    }
+}
+
+mod untracked {
+    use crate::util::basic_rw_repo;
+
+    #[test]
+    fn untracked_files_with_and_without_untracked_cache() -> crate::Result {
+        let (mut repo, _tmp) = basic_rw_repo()?;
+        let outcome = repo.worktree().expect("non-bare").untracked_files()?;
+        assert_eq!(
+            outcome.untracked,
+            ["bare.git/"],
+            "empty directories aren't listed, just like in `git status`"
+        );
+        assert!(
+            repo.open_index()?.untracked().is_none(),
+            "by default, the untracked cache is neither created nor removed"
+        );
+
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value("core", None, "untrackedCache", "true")?;
+        let repo = config.commit()?;
+        let outcome = repo.worktree().expect("non-bare").untracked_files()?;
+        assert_eq!(outcome.untracked, ["bare.git/"]);
+        assert_eq!(outcome.directories_cached, 0);
+        assert!(
+            repo.open_index()?.untracked().is_some(),
+            "the cache was created and written"
+        );
+
+        let outcome = repo.worktree().expect("non-bare").untracked_files()?;
+        assert_eq!(outcome.untracked, ["bare.git/"]);
+        assert_eq!(outcome.directories_read, 0, "everything comes from the cache");
+
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value("core", None, "untrackedCache", "false")?;
+        let repo = config.commit()?;
+        let outcome = repo.worktree().expect("non-bare").untracked_files()?;
+        assert_eq!(outcome.untracked, ["bare.git/"]);
+        assert!(repo.open_index()?.untracked().is_none(), "the cache was removed");
+        Ok(())
+    }
+
+    #[test]
+    fn untracked_files_with_fs_monitor_hook() -> crate::Result {
+        let (mut repo, _tmp) = basic_rw_repo()?;
+        let hook = repo.git_dir().join("hook.sh");
+        std::fs::write(&hook, r#"printf 'first\0'"#)?;
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value("core", None, "untrackedCache", "true")?;
+        config.set_raw_value("core", None, "fsmonitor", "sh .git/hook.sh")?;
+        let repo = config.commit()?;
+
+        let outcome = repo.worktree().expect("non-bare").untracked_files()?;
+        assert_eq!(outcome.untracked, ["bare.git/"]);
+        assert_eq!(outcome.directories_read, 1, "without token, everything is read");
+        assert!(
+            repo.open_index()?.fs_monitor().is_some(),
+            "the token is stored in the index"
+        );
+
+        std::fs::write(repo.work_dir().expect("non-bare").join("new"), b"")?;
+        let outcome = repo.worktree().expect("non-bare").untracked_files()?;
+        assert_eq!(
+            outcome.untracked,
+            ["bare.git/"],
+            "the hook didn't report the new file, so it's not seen"
+        );
+        assert_eq!(outcome.directories_read, 0);
+
+        std::fs::write(&hook, r#"printf 'second\0new\0'"#)?;
+        let outcome = repo.worktree().expect("non-bare").untracked_files()?;
+        assert_eq!(outcome.untracked, ["bare.git/", "new"]);
+        assert_eq!(outcome.directories_read, 1);
+        assert_eq!(
+            repo.open_index()?.fs_monitor().expect("present").token,
+            gix::index::extension::fs_monitor::Token::V2 { token: "second".into() }
+        );
+
+        let mut config = repo.config_snapshot_mut();
+        config.set_raw_value("core", None, "fsmonitor", "false")?;
+        let repo = config.commit()?;
+        repo.worktree().expect("non-bare").untracked_files()?;
+        assert!(
+            repo.open_index()?.fs_monitor().is_none(),
+            "the extension is removed if the fsmonitor is disabled"
+        );
+        Ok(())
+    }
+}
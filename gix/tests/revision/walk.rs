@@ -0,0 +1,60 @@
+use crate::util::named_subrepo_opts;
+
+fn ids(repo: &gix::Repository, revs: &[&str]) -> Vec<gix::ObjectId> {
+    let mut ids: Vec<_> = revs
+        .iter()
+        .map(|rev| repo.rev_parse_single(*rev).expect("tag exists").detach())
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn walk_paths(repo: &gix::Repository, paths: &[&str]) -> crate::Result<Vec<gix::ObjectId>> {
+    let mut ids = repo
+        .rev_walk(Some(repo.head_id()?))
+        .paths(paths.iter().copied())
+        .all()?
+        .map(|id| id.map(gix::Id::detach))
+        .collect::<Result<Vec<_>, _>>()?;
+    ids.sort();
+    Ok(ids)
+}
+
+#[test]
+fn paths_limit_the_walk_to_commits_changing_them() -> crate::Result {
+    for name in ["no-commit-graph", "with-commit-graph"] {
+        let repo = named_subrepo_opts("make_rev_walk_paths_repo.sh", name, crate::restricted())?;
+        let graph = gix::commitgraph::Graph::from_info_dir(repo.objects.store_ref().path().join("info")).ok();
+        assert_eq!(
+            graph
+                .as_ref()
+                .and_then(|graph| graph.commit_by_id(repo.head_id().expect("valid")))
+                .map(|commit| commit.bloom_filter().is_some()),
+            (name == "with-commit-graph").then_some(true),
+            "only one of the repositories has changed-path filters"
+        );
+
+        for (paths, expected) in [
+            (&["a"][..], &["c4", "c2", "c1"][..]),
+            (&["a/"], &["c4", "c2", "c1"]),
+            (&["dir"], &["merge", "c3", "side", "c1"]),
+            (&["dir/sub"], &["merge", "side"]),
+            (&["dir/sub/c"], &["merge", "side"]),
+            (&["a", "dir/sub"], &["c4", "merge", "side", "c2", "c1"]),
+            (&["missing"], &[]),
+            (&["a/missing"], &[]),
+        ] {
+            assert_eq!(
+                walk_paths(&repo, paths)?,
+                ids(&repo, expected),
+                "{paths:?} in {name}: merges are compared to their first parent"
+            );
+        }
+        assert_eq!(
+            walk_paths(&repo, &[])?.len(),
+            6,
+            "without paths, all commits are returned"
+        );
+    }
+    Ok(())
+}
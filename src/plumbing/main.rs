@@ -155,6 +155,7 @@ pub fn main() -> Result<()> {
             handshake_info,
             remote,
             shallow,
+            bundle_uri,
             ref_spec,
         }) => {
             let opts = core::repository::fetch::Options {
@@ -163,6 +164,7 @@ pub fn main() -> Result<()> {
                 remote,
                 handshake_info,
                 shallow: shallow.into(),
+                bundle_uri,
                 ref_specs: ref_spec,
             };
             prepare_and_run(
@@ -565,7 +567,26 @@ pub fn main() -> Result<()> {
                                 core::pack::multi_index::verify(multi_index_path, progress, &should_interrupt)
                             },
                         ),
-                        free::pack::multi_index::Subcommands::Create { index_paths } => prepare_and_run(
+                        free::pack::multi_index::Subcommands::VerifyObject { id } => prepare_and_run(
+                            "pack-multi-index-verify-object",
+                            verbose,
+                            progress,
+                            progress_keep_open,
+                            core::pack::multi_index::PROGRESS_RANGE,
+                            move |_progress, out, _err| {
+                                core::pack::multi_index::verify_object(multi_index_path, id, out, &should_interrupt)
+                            },
+                        ),
+                        free::pack::multi_index::Subcommands::Create {
+                            index_paths,
+                            reverse_index,
+                            resumable,
+                            max_packs,
+                            check_delta_bases,
+                            incremental,
+                            bitmap,
+                            preferred_pack,
+                        } => prepare_and_run(
                             "pack-multi-index-create",
                             verbose,
                             progress,
@@ -577,7 +598,56 @@ pub fn main() -> Result<()> {
                                     multi_index_path,
                                     progress,
                                     &should_interrupt,
-                                    object_hash,
+                                    core::pack::multi_index::CreateOptions {
+                                        object_hash,
+                                        reverse_index,
+                                        resumable,
+                                        max_packs,
+                                        check_delta_bases,
+                                        incremental,
+                                        bitmap,
+                                        preferred_pack,
+                                    },
+                                )
+                            },
+                        ),
+                        free::pack::multi_index::Subcommands::Expire => prepare_and_run(
+                            "pack-multi-index-expire",
+                            verbose,
+                            progress,
+                            progress_keep_open,
+                            core::pack::multi_index::PROGRESS_RANGE,
+                            move |progress, _out, _err| {
+                                core::pack::multi_index::expire(multi_index_path, progress, &should_interrupt)
+                            },
+                        ),
+                        free::pack::multi_index::Subcommands::Repack { batch_size } => prepare_and_run(
+                            "pack-multi-index-repack",
+                            verbose,
+                            progress,
+                            progress_keep_open,
+                            core::pack::multi_index::PROGRESS_RANGE,
+                            move |progress, _out, _err| {
+                                core::pack::multi_index::repack(
+                                    multi_index_path,
+                                    batch_size,
+                                    progress,
+                                    &should_interrupt,
+                                )
+                            },
+                        ),
+                        free::pack::multi_index::Subcommands::Split { max_pack_size } => prepare_and_run(
+                            "pack-multi-index-split",
+                            verbose,
+                            progress,
+                            progress_keep_open,
+                            core::pack::multi_index::PROGRESS_RANGE,
+                            move |progress, _out, _err| {
+                                core::pack::multi_index::split(
+                                    multi_index_path,
+                                    max_pack_size,
+                                    progress,
+                                    &should_interrupt,
                                 )
                             },
                         ),
@@ -654,6 +724,38 @@ pub fn main() -> Result<()> {
                 )
             },
         ),
+        Subcommands::Repack {
+            all,
+            delete_redundant,
+            write_bitmap_index,
+            write_commit_graph,
+            split,
+            changed_paths,
+            keep_pack,
+        } => prepare_and_run(
+            "repack",
+            auto_verbose,
+            progress,
+            progress_keep_open,
+            core::pack::repack::PROGRESS_RANGE,
+            move |progress, out, _err| {
+                core::pack::repack(
+                    repository(Mode::Strict)?,
+                    progress,
+                    &should_interrupt,
+                    out,
+                    core::pack::repack::Options {
+                        format,
+                        all_into_one: all,
+                        keep_packs: keep_pack,
+                        delete_redundant,
+                        write_bitmap_index,
+                        commit_graph: write_commit_graph
+                            .then_some(gix::maintenance::commit_graph::Options { split, changed_paths }),
+                    },
+                )
+            },
+        ),
         Subcommands::Revision(cmd) => match cmd {
             revision::Subcommands::List { spec } => prepare_and_run(
                 "revision-list",
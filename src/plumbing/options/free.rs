@@ -293,6 +293,13 @@ pub mod pack {
             Info,
             /// Verify a multi-index quickly without inspecting objects themselves
             Verify,
+            /// Verify a single object by decoding it from its pack and checking its hash
+            ///
+            /// Prints: <oid> <kind> <size> <pack-id> <pack-offset>
+            VerifyObject {
+                /// The id of the object to verify.
+                id: gix::ObjectId,
+            },
             /// Create a multi-pack index from one or more pack index files, overwriting possibloy existing files.
             Create {
                 /// Paths to the pack index files to read (with .idx extension).
@@ -300,6 +307,53 @@ pub mod pack {
                 /// Note for the multi-index to be useful, it should be side-by-side with the supplied `.idx` files.
                 #[clap(required = true)]
                 index_paths: Vec<PathBuf>,
+                /// Also write a reverse index ('.rev' file) next to the multi-index to speed up lookups by pack offset.
+                #[clap(long)]
+                reverse_index: bool,
+                /// Record progress in a checkpoint file next to the multi-index, and resume from it if it exists and was
+                /// created for the same index files.
+                #[clap(long)]
+                resumable: bool,
+                /// Fail if there are more than the given amount of index files, as a signal that a repack is overdue.
+                #[clap(long)]
+                max_packs: Option<usize>,
+                /// Fail if objects in the packs are ref-deltas whose base isn't contained in any of the packs.
+                #[clap(long)]
+                check_delta_bases: bool,
+                /// Write a new layer of an incremental multi-index into the 'multi-pack-index.d' directory next to the
+                /// multi-index, with only the packs that aren't contained in its existing layers.
+                #[clap(long, conflicts_with_all = ["reverse_index", "bitmap"])]
+                incremental: bool,
+                /// Also write a reachability bitmap for all objects reachable from references of the repository owning the packs.
+                ///
+                /// This implies writing the reverse index.
+                #[clap(long)]
+                bitmap: bool,
+                /// The file name of the index of the pack whose objects should be preferred over duplicates in other packs.
+                ///
+                /// When writing a bitmap, it defaults to the pack with the least recently modified index.
+                #[clap(long)]
+                preferred_pack: Option<PathBuf>,
+            },
+            /// Remove packs whose objects are all contained in other packs of the multi-index, and rewrite it without them.
+            ///
+            /// Packs with a '.keep' file are retained.
+            Expire,
+            /// Write the objects of a batch of packs into a new pack and add it to the multi-index, leaving the batch to be
+            /// removed by 'expire'.
+            Repack {
+                /// The size in bytes of the objects to put into the new pack, selected from the least recently modified packs.
+                ///
+                /// If 0, all packs without '.keep' file are repacked.
+                #[clap(long, default_value_t = 0)]
+                batch_size: u64,
+            },
+            /// Split all packs referenced by the multi-index into packs no larger than the given size, and rewrite the multi-index
+            /// to refer to them instead.
+            Split {
+                /// The maximum size of each pack in bytes.
+                #[clap(long, short = 's')]
+                max_pack_size: u64,
             },
         }
     }
@@ -86,6 +86,30 @@ pub enum Subcommands {
         #[clap(flatten)]
         args: free::pack::VerifyOptions,
     },
+    /// Consolidate loose objects and packs into a new pack, similar to `git repack`.
+    Repack {
+        /// Write the objects of all packs that aren't kept into the new pack, instead of only loose objects.
+        #[clap(long, short = 'a')]
+        all: bool,
+        /// Delete packs and loose objects that are redundant after writing the new pack.
+        #[clap(long, short = 'd')]
+        delete_redundant: bool,
+        /// Write a reachability bitmap for the new pack, which requires `--all`.
+        #[clap(long, short = 'b', requires = "all")]
+        write_bitmap_index: bool,
+        /// Write a commit-graph with all commits reachable from references after packing.
+        #[clap(long)]
+        write_commit_graph: bool,
+        /// Only write new commits into a new file on top of the existing commit-graph files, which requires `--write-commit-graph`.
+        #[clap(long, requires = "write_commit_graph")]
+        split: bool,
+        /// Store bloom filters of the paths changed by each commit in the commit-graph, which requires `--write-commit-graph`.
+        #[clap(long, requires = "write_commit_graph")]
+        changed_paths: bool,
+        /// The file name of a pack like `pack-<hash>.pack` to keep as is, in addition to those with a `.keep` file.
+        #[clap(long)]
+        keep_pack: Vec<PathBuf>,
+    },
     /// Query and obtain information about revisions.
     #[clap(subcommand)]
     Revision(revision::Subcommands),
@@ -152,6 +176,12 @@ pub mod fetch {
         #[clap(flatten)]
         pub shallow: ShallowOptions,
 
+        /// Download the bundles the remote advertises via `bundle-uri` first, and only fetch what they don't contain.
+        ///
+        /// Bundles that can't be downloaded or applied are skipped.
+        #[clap(long)]
+        pub bundle_uri: bool,
+
         /// The name of the remote to connect to, or the url of the remote to connect to directly.
         ///
         /// If unset, the current branch will determine the remote.
@@ -136,22 +136,10 @@ static GIT_CONFIG: &[Record] = &[
         config: "core.protectNTFS",
         usage: NotPlanned { reason: "lack of demand"},
     },
-    Record {
-        config: "core.sparseCheckout",
-        usage: Planned { note: Some("we want to support huge repos and be the fastest in doing so") },
-    },
-    Record {
-        config: "core.sparseCheckoutCone",
-        usage: Planned { note: Some("this is a nice improvement over spareCheckout alone and should one day be available too") },
-    },
     Record {
         config: "checkout.defaultRemote",
         usage: Planned { note: Some("needed for correct checkout behaviour, similar to what git does") },
     },
-    Record {
-        config: "core.untrackedCache",
-        usage: Planned { note: Some("needed for fast worktree operation") },
-    },
     Record {
         config: "checkout.guess",
         usage: Planned { note: None },
@@ -168,10 +156,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "core.preloadIndex",
         usage: Planned {note: Some("it's enabled by default and allows parallel stat checks - it's using a lot of CPU for just minor performance boosts though")},
     },
-    Record {
-        config: "index.sparse",
-        usage: Planned {note: Some("we can read sparse indices and support for it will be added early on")},
-    },
     Record {
         config: "index.skipHash",
         usage: Planned {note: Some("important to not unnecessarily reject indices just because they are missing a hash (or it is null)")},
@@ -206,10 +190,6 @@ static GIT_CONFIG: &[Record] = &[
             reason: "no plan to implement format-patch or request-pull summary"
         },
     },
-    Record {
-        config: "core.eol",
-        usage: Planned {note: Some("needed for filters, but also for doing diffs correctly")}
-    },
     Record {
     config: "clone.filterSubmodules,",
         usage: Planned {
@@ -290,17 +270,9 @@ static GIT_CONFIG: &[Record] = &[
         config: "advice.updateSparsePath",
         usage: NotApplicable { reason: "gitoxide does not yet have an 'advice' system" },
     },
-    Record {
-        config: "core.sparseCheckout",
-        usage: Planned { note: Some("together with 'index.sparse' and 'core.sparseCheckoutCone', configures if the index should be written sparse or not") },
-    },
-    Record {
-        config: "core.sparseCheckoutCone",
-        usage: Planned { note: Some("non-cone mode is deprecated but should still fail gracefully if encountered") },
-    },
     Record {
         config: "core.splitIndex",
-        usage: NotPlanned { reason: "we don't want to be able to create split indices, but we will read them. It's (somewhat) superseded by sparse indices" },
+        usage: NotPlanned { reason: "we don't want to be able to create split indices, but we read them and write changes relative to their shared index. It's (somewhat) superseded by sparse indices" },
     },
     Record {
         config: "splitIndex.maxPercentageChange",
@@ -310,14 +282,6 @@ static GIT_CONFIG: &[Record] = &[
         config: "splitIndex.sharedIndexExpire",
         usage: NotPlanned { reason: "seems like it's superseded by sparse indices" },
     },
-    Record {
-        config: "index.sparse",
-        usage: Planned { note: Some("together with 'core.sparseCheckout' and 'core.sparseCheckoutCone', configures if the index should be written sparse or not") },
-    },
-    Record {
-        config: "index.version",
-        usage: Planned { note: Some("once V4 indices can be written, we need to be able to set a desired version. For now we write the smallest possible index version only.") },
-    },
     Record {
         config: "http.<url>.*",
         usage: Planned { note: Some("definitely needed for correctness, testing against baseline is a must") }
@@ -398,6 +398,81 @@ title "gix (with repository)"
               )
             )
         )
+        title "gix free pack multi-index verify-object"
+        (with "the 'verify-object' sub-command"
+            (sandbox
+                cp $fixtures/packs/pack-* .
+                "$exe_plumbing" free pack multi-index -i multi-pack-index create *.idx
+                it "prints the kind, size and location of an intact object" && {
+                  expect_run_sh $SUCCESSFULLY "$exe_plumbing free pack multi-index -i multi-pack-index verify-object faec2291d9e622c76dcdb3ef13d0876c5e3e6f28 | grep -x 'faec2291d9e622c76dcdb3ef13d0876c5e3e6f28 blob 4324 [0-9]* 42999'"
+                }
+                it "fails for objects that aren't in the multi-index" && {
+                  expect_run $WITH_FAILURE "$exe_plumbing" free pack multi-index -i multi-pack-index verify-object 0000000000000000000000000000000000000000
+                }
+            )
+        )
+        title "gix free pack multi-index repack"
+        (with "the 'repack' sub-command"
+            (sandbox
+                cp $fixtures/packs/pack-* .
+                "$exe_plumbing" free pack multi-index -i multi-pack-index create *.idx
+                it "writes all objects into a new pack which keeps their deltas" && {
+                  expect_run $SUCCESSFULLY "$exe_plumbing" free pack multi-index -i multi-pack-index repack
+                  expect_run_sh $SUCCESSFULLY 'test "$(ls pack-*.pack | wc -l)" -eq 3'
+                  expect_run_sh $SUCCESSFULLY 'git verify-pack -v $(ls -t pack-*.idx | head -n 1) | grep "chain length" >/dev/null'
+                }
+                it "leaves no temporary files behind" && {
+                  expect_run_sh $SUCCESSFULLY 'test -z "$(ls -a | grep -v -e "^\.\.*$" -e "^pack-.*\.pack$" -e "^pack-.*\.idx$" -e "^multi-pack-index$")"'
+                }
+                it "retains all objects" && {
+                  expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose free pack verify multi-pack-index
+                }
+            )
+        )
+        title "gix free pack multi-index split"
+        (with "the 'split' sub-command"
+            (with 'a multi-index over multiple packs'
+              (sandbox
+                cp $fixtures/packs/pack-* .
+                "$exe_plumbing" free pack multi-index -i multi-pack-index create *.idx
+                it "splits the packs into packs of at most the given size" && {
+                  expect_run $SUCCESSFULLY "$exe_plumbing" free pack multi-index -i multi-pack-index split --max-pack-size 20000
+                  expect_run_sh $SUCCESSFULLY 'test "$(ls pack-*.pack | wc -l)" -gt 2 && test -z "$(find . -name "pack-*.pack" -size +20000c)"'
+                }
+                it "leaves only packs, their indices and the multi-index behind" && {
+                  expect_run_sh $SUCCESSFULLY 'test -z "$(ls | grep -v -e "^pack-.*\.pack$" -e "^pack-.*\.idx$" -e "^multi-pack-index$")"'
+                }
+                it "retains all objects and keeps deltas" && {
+                  expect_run $SUCCESSFULLY "$exe_plumbing" --no-verbose free pack verify multi-pack-index
+                  expect_run_sh $SUCCESSFULLY 'git verify-pack -v pack-*.idx | grep "chain length" >/dev/null'
+                }
+              )
+            )
+            (with 'packs with sidecar files'
+              (sandbox
+                cp $fixtures/packs/pack-* .
+                for pack in pack-*.pack; do
+                  git index-pack --rev-index "$pack" >/dev/null
+                  touch "${pack%.pack}.keep"
+                done
+                "$exe_plumbing" free pack multi-index -i multi-pack-index create *.idx
+                it "removes the sidecar files of the replaced packs" && {
+                  expect_run $SUCCESSFULLY "$exe_plumbing" free pack multi-index -i multi-pack-index split --max-pack-size 20000
+                  expect_run_sh $SUCCESSFULLY 'test -z "$(ls | grep -v -e "^pack-.*\.pack$" -e "^pack-.*\.idx$" -e "^multi-pack-index$")"'
+                }
+              )
+            )
+            (with 'a multi-index with a reverse index'
+              (sandbox
+                cp $fixtures/packs/pack-* .
+                "$exe_plumbing" free pack multi-index -i multi-pack-index create --reverse-index *.idx
+                it "writes the reverse index for the rewritten multi-index" && {
+                  expect_run $SUCCESSFULLY "$exe_plumbing" free pack multi-index -i multi-pack-index split --max-pack-size 20000
+                  expect_run_sh $SUCCESSFULLY 'test "$(ls multi-pack-index-*.rev | wc -l)" -eq 1'
+                }
+              )
+            )
+        )
     )
 
     title "gix free pack explode"